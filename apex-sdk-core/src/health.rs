@@ -0,0 +1,537 @@
+//! Endpoint health shared by the EVM and Substrate connection pools
+//!
+//! A pool probes every endpoint for the network it serves (chain ID or genesis
+//! hash) and its head block, then classifies the results here:
+//! - endpoints on another network than the expected one are marked wrong-network
+//! - the best head is the highest block, picking the hash most endpoints agree on
+//! - endpoints lagging more than `max_block_lag` blocks are degraded
+//! - endpoints whose head is not on the best endpoint's chain are degraded (forks)
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::time::Instant;
+
+/// Consecutive failures before an endpoint is marked unhealthy
+const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+/// Health status enumeration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// Endpoint is healthy and responsive
+    Healthy,
+    /// Endpoint responds but lags behind or diverges from the pool's best head
+    Degraded,
+    /// Endpoint is not responding or failing requests
+    Unhealthy,
+    /// Endpoint serves another network than the pool expects
+    WrongNetwork,
+    /// Health status is not yet determined
+    Unknown,
+}
+
+/// Head block reported by an endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadInfo {
+    /// Block number
+    pub number: u64,
+    /// Block hash (hex encoded)
+    pub hash: String,
+}
+
+impl HeadInfo {
+    /// Create a new head descriptor
+    pub fn new(number: u64, hash: impl Into<String>) -> Self {
+        Self {
+            number,
+            hash: hash.into(),
+        }
+    }
+
+    /// Number of blocks this head is behind `best`
+    pub fn blocks_behind(&self, best: &HeadInfo) -> u64 {
+        best.number.saturating_sub(self.number)
+    }
+
+    /// Classify this head against the pool's best-known head
+    ///
+    /// A head lagging by more than `max_block_lag` blocks, or reporting a
+    /// different hash at the same height (a fork), is considered degraded.
+    pub fn status_against(&self, best: &HeadInfo, max_block_lag: u64) -> HealthStatus {
+        if self.blocks_behind(best) > max_block_lag {
+            return HealthStatus::Degraded;
+        }
+        if self.number == best.number && !self.has_hash(&best.hash) {
+            return HealthStatus::Degraded;
+        }
+        HealthStatus::Healthy
+    }
+
+    fn has_hash(&self, hash: &str) -> bool {
+        self.hash.eq_ignore_ascii_case(hash)
+    }
+}
+
+/// Health status of an endpoint
+#[derive(Debug, Clone)]
+pub struct EndpointHealth {
+    /// Whether the endpoint is currently healthy
+    pub is_healthy: bool,
+    /// Detailed health classification
+    pub status: HealthStatus,
+    /// Head block observed during the last health check
+    pub head: Option<HeadInfo>,
+    /// Number of blocks behind the pool's best-known head
+    pub blocks_behind: u64,
+    /// Last successful connection timestamp
+    pub last_success: Option<Instant>,
+    /// Last failed connection timestamp
+    pub last_failure: Option<Instant>,
+    /// Consecutive failure count
+    pub failure_count: u32,
+    /// Average response time in milliseconds
+    pub avg_response_time_ms: u64,
+}
+
+impl Default for EndpointHealth {
+    fn default() -> Self {
+        Self {
+            is_healthy: true,
+            status: HealthStatus::Unknown,
+            head: None,
+            blocks_behind: 0,
+            last_success: None,
+            last_failure: None,
+            failure_count: 0,
+            avg_response_time_ms: 0,
+        }
+    }
+}
+
+impl EndpointHealth {
+    /// Record a successful request or probe
+    ///
+    /// The status is left to [`record_verdict`](Self::record_verdict): a
+    /// response alone does not show that the endpoint is on the right network
+    /// and chain.
+    pub fn record_success(&mut self, response_time_ms: u64) {
+        self.last_success = Some(Instant::now());
+        self.failure_count = 0;
+
+        // Update average response time (exponential moving average)
+        if self.avg_response_time_ms == 0 {
+            self.avg_response_time_ms = response_time_ms;
+        } else {
+            self.avg_response_time_ms = (self.avg_response_time_ms * 9 + response_time_ms) / 10;
+        }
+    }
+
+    /// Record a failed request or probe
+    pub fn record_failure(&mut self, endpoint: &str) {
+        self.last_failure = Some(Instant::now());
+        self.failure_count += 1;
+
+        if self.failure_count >= MAX_CONSECUTIVE_FAILURES {
+            self.is_healthy = false;
+            self.status = HealthStatus::Unhealthy;
+            tracing::warn!("Endpoint {} marked as unhealthy", endpoint);
+        }
+    }
+
+    /// Apply the classification of a health check
+    pub fn record_verdict<N: Display>(&mut self, endpoint: &str, verdict: Verdict<N>) {
+        match verdict {
+            Verdict::WrongNetwork { found, expected } => {
+                self.is_healthy = false;
+                self.status = HealthStatus::WrongNetwork;
+                self.last_failure = Some(Instant::now());
+                tracing::warn!(
+                    "Endpoint {} is on network {} but the pool expects {}",
+                    endpoint,
+                    found,
+                    expected
+                );
+            }
+            Verdict::Head { head, best, status } => {
+                if status == HealthStatus::Degraded {
+                    tracing::warn!(
+                        "Endpoint {} degraded: head #{} ({}) vs best #{} ({})",
+                        endpoint,
+                        head.number,
+                        head.hash,
+                        best.number,
+                        best.hash
+                    );
+                }
+                self.blocks_behind = head.blocks_behind(&best);
+                self.is_healthy = true;
+                self.status = status;
+                self.head = Some(head);
+            }
+        }
+    }
+}
+
+/// Network and head an endpoint reported in a health check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Probe<N> {
+    /// Network identifier: the chain ID (EVM) or genesis hash (Substrate)
+    pub network: N,
+    /// Latest block
+    pub head: HeadInfo,
+}
+
+/// Classification of a successful probe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict<N> {
+    /// The endpoint serves another network
+    WrongNetwork { found: N, expected: N },
+    /// The endpoint's head compared with the pool's best head
+    Head {
+        head: HeadInfo,
+        best: HeadInfo,
+        status: HealthStatus,
+    },
+}
+
+/// Outcome of a pool's health check, before canonical hashes are verified
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthCheck<N> {
+    /// Network the endpoints must serve
+    pub expected: N,
+    /// Best head among the endpoints on the expected network
+    pub best: Option<HeadInfo>,
+    /// Index of the endpoint that reported the best head
+    pub best_index: Option<usize>,
+}
+
+impl<N: Eq + Hash + Clone> HealthCheck<N> {
+    /// Determine the expected network and best head from the probes, in pool order
+    ///
+    /// Without a configured network, the one most endpoints report wins. `None`
+    /// if no probe succeeded and no network is configured.
+    pub fn new(probes: &[Option<Probe<N>>], configured: Option<N>) -> Option<Self> {
+        let expected = match configured {
+            Some(network) => network,
+            None => majority(probes.iter().flatten().map(|probe| &probe.network))?.clone(),
+        };
+
+        let on_network: Vec<(usize, &HeadInfo)> = probes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, probe)| probe.as_ref().map(|probe| (index, probe)))
+            .filter(|(_, probe)| probe.network == expected)
+            .map(|(index, probe)| (index, &probe.head))
+            .collect();
+
+        // The highest block wins; at that height, the hash most endpoints agree
+        // on, and on a tie the endpoint listed first
+        let best = on_network
+            .iter()
+            .map(|(_, head)| head.number)
+            .max()
+            .and_then(|number| {
+                majority(
+                    on_network
+                        .iter()
+                        .filter(|(_, head)| head.number == number)
+                        .map(|(_, head)| head.hash.to_lowercase()),
+                )
+                .map(|hash| HeadInfo::new(number, hash))
+            });
+        let best_index = best.as_ref().and_then(|best| {
+            on_network
+                .iter()
+                .find(|(_, head)| head.number == best.number && head.has_hash(&best.hash))
+                .map(|(index, _)| *index)
+        });
+
+        Some(Self {
+            expected,
+            best,
+            best_index,
+        })
+    }
+
+    /// Heights whose canonical hash must be fetched from the best endpoint to
+    /// tell whether endpoints behind it are on a fork
+    ///
+    /// Endpoints lagging beyond `max_block_lag` are degraded anyway.
+    pub fn heights_to_verify(&self, probes: &[Option<Probe<N>>], max_block_lag: u64) -> Vec<u64> {
+        let Some(best) = &self.best else {
+            return Vec::new();
+        };
+        let mut heights: Vec<u64> = probes
+            .iter()
+            .flatten()
+            .filter(|probe| probe.network == self.expected)
+            .map(|probe| probe.head.number)
+            .filter(|&number| number < best.number && best.number - number <= max_block_lag)
+            .collect();
+        heights.sort_unstable();
+        heights.dedup();
+        heights
+    }
+
+    /// Classify every successful probe, in pool order
+    ///
+    /// `canonical` maps heights to the best endpoint's block hashes; a head
+    /// behind the best one whose hash differs is on a fork. Heights missing
+    /// from the map are only checked for lag.
+    pub fn classify(
+        &self,
+        probes: Vec<Option<Probe<N>>>,
+        canonical: &HashMap<u64, String>,
+        max_block_lag: u64,
+    ) -> Vec<Option<Verdict<N>>> {
+        probes
+            .into_iter()
+            .map(|probe| {
+                let probe = probe?;
+                if probe.network != self.expected {
+                    return Some(Verdict::WrongNetwork {
+                        found: probe.network,
+                        expected: self.expected.clone(),
+                    });
+                }
+
+                let best = self.best.clone()?;
+                let mut status = probe.head.status_against(&best, max_block_lag);
+                if status == HealthStatus::Healthy {
+                    if let Some(hash) = canonical.get(&probe.head.number) {
+                        if !probe.head.has_hash(hash) {
+                            status = HealthStatus::Degraded;
+                        }
+                    }
+                }
+                Some(Verdict::Head {
+                    head: probe.head,
+                    best,
+                    status,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A connection pool exposing the health of its endpoints
+#[async_trait]
+pub trait PoolHealth: Send + Sync {
+    /// Name of the chain served by the pool
+    fn chain_name(&self) -> &str;
+
+    /// Whether a health check has completed since the pool was created
+    fn health_checked(&self) -> bool;
+
+    /// Health of every endpoint, keyed by endpoint URL
+    async fn health_status(&self) -> Vec<(String, EndpointHealth)>;
+}
+
+/// Most frequently reported value, preferring the earliest on ties
+pub fn majority<T: Eq + Hash + Clone>(values: impl Iterator<Item = T>) -> Option<T> {
+    let mut counts: Vec<(T, usize)> = Vec::new();
+    let mut index: HashMap<T, usize> = HashMap::new();
+
+    for value in values {
+        match index.get(&value) {
+            Some(&i) => counts[i].1 += 1,
+            None => {
+                index.insert(value.clone(), counts.len());
+                counts.push((value, 1));
+            }
+        }
+    }
+
+    counts
+        .into_iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map(|(value, _)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(network: u64, number: u64, hash: &str) -> Option<Probe<u64>> {
+        Some(Probe {
+            network,
+            head: HeadInfo::new(number, hash),
+        })
+    }
+
+    fn statuses(verdicts: &[Option<Verdict<u64>>]) -> Vec<Option<HealthStatus>> {
+        verdicts
+            .iter()
+            .map(|verdict| match verdict {
+                Some(Verdict::Head { status, .. }) => Some(status.clone()),
+                Some(Verdict::WrongNetwork { .. }) => Some(HealthStatus::WrongNetwork),
+                None => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_head_status_against_best() {
+        let best = HeadInfo::new(1_000, "0xAA");
+
+        assert_eq!(
+            HeadInfo::new(998, "0xbb").status_against(&best, 5),
+            HealthStatus::Healthy
+        );
+        assert_eq!(
+            HeadInfo::new(990, "0xbb").status_against(&best, 5),
+            HealthStatus::Degraded
+        );
+        assert_eq!(
+            HeadInfo::new(1_000, "0xaa").status_against(&best, 5),
+            HealthStatus::Healthy
+        );
+        assert_eq!(
+            HeadInfo::new(1_000, "0xcc").status_against(&best, 5),
+            HealthStatus::Degraded
+        );
+    }
+
+    #[test]
+    fn test_majority() {
+        assert_eq!(
+            majority(["0x91b1", "0xe143", "0x91b1"].into_iter()),
+            Some("0x91b1")
+        );
+        assert_eq!(majority([2, 1].into_iter()), Some(2));
+        assert_eq!(majority(std::iter::empty::<&str>()), None);
+    }
+
+    #[test]
+    fn test_best_head_prefers_majority_hash() {
+        let probes = vec![
+            probe(1, 100, "0xfork"),
+            probe(1, 100, "0xaa"),
+            probe(1, 100, "0xAA"),
+            probe(1, 99, "0x99"),
+        ];
+        let check = HealthCheck::new(&probes, None).unwrap();
+        assert_eq!(check.best, Some(HeadInfo::new(100, "0xaa")));
+        assert_eq!(check.best_index, Some(1));
+
+        // On a tie, the endpoint listed first wins
+        let probes = vec![probe(1, 100, "0xbb"), probe(1, 100, "0xaa")];
+        let check = HealthCheck::new(&probes, None).unwrap();
+        assert_eq!(check.best_index, Some(0));
+    }
+
+    #[test]
+    fn test_wrong_network() {
+        let probes = vec![
+            probe(1, 100, "0xaa"),
+            probe(5, 500, "0xee"),
+            None,
+            probe(1, 100, "0xaa"),
+        ];
+
+        // The majority network wins, and its head is the best one
+        let check = HealthCheck::new(&probes, None).unwrap();
+        assert_eq!(check.expected, 1);
+        assert_eq!(check.best, Some(HeadInfo::new(100, "0xaa")));
+        let verdicts = check.classify(probes.clone(), &HashMap::new(), 5);
+        assert_eq!(
+            verdicts[1],
+            Some(Verdict::WrongNetwork {
+                found: 5,
+                expected: 1
+            })
+        );
+        assert_eq!(
+            statuses(&verdicts),
+            vec![
+                Some(HealthStatus::Healthy),
+                Some(HealthStatus::WrongNetwork),
+                None,
+                Some(HealthStatus::Healthy)
+            ]
+        );
+
+        // A configured network overrides the majority
+        let check = HealthCheck::new(&probes, Some(5)).unwrap();
+        assert_eq!(check.best_index, Some(1));
+        assert!(HealthCheck::<u64>::new(&[None], None).is_none());
+    }
+
+    #[test]
+    fn test_forks_below_best_head() {
+        let probes = vec![
+            probe(1, 100, "0xaa"),
+            probe(1, 98, "0xcanonical"),
+            probe(1, 98, "0xfork"),
+            probe(1, 97, "0x97"),
+            probe(1, 90, "0x90"),
+        ];
+        let check = HealthCheck::new(&probes, None).unwrap();
+        assert_eq!(check.heights_to_verify(&probes, 5), vec![97, 98]);
+
+        // 97 could not be fetched, so it is only checked for lag
+        let canonical = HashMap::from([(98, "0xCANONICAL".to_string())]);
+        let verdicts = check.classify(probes, &canonical, 5);
+        assert_eq!(
+            statuses(&verdicts),
+            vec![
+                Some(HealthStatus::Healthy),
+                Some(HealthStatus::Healthy),
+                Some(HealthStatus::Degraded),
+                Some(HealthStatus::Healthy),
+                Some(HealthStatus::Degraded)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_endpoint_health_records() {
+        let mut health = EndpointHealth::default();
+        assert_eq!(health.status, HealthStatus::Unknown);
+
+        health.record_success(100);
+        health.record_success(200);
+        assert_eq!(health.avg_response_time_ms, 110);
+
+        for _ in 0..MAX_CONSECUTIVE_FAILURES {
+            health.record_failure("ws://a");
+        }
+        assert!(!health.is_healthy);
+        assert_eq!(health.status, HealthStatus::Unhealthy);
+
+        health.record_success(100);
+        assert!(!health.is_healthy);
+        assert_eq!(health.failure_count, 0);
+        health.record_verdict(
+            "ws://a",
+            Verdict::<u64>::Head {
+                head: HeadInfo::new(90, "0x90"),
+                best: HeadInfo::new(100, "0xaa"),
+                status: HealthStatus::Degraded,
+            },
+        );
+        assert!(health.is_healthy);
+        assert_eq!(health.status, HealthStatus::Degraded);
+        assert_eq!(health.blocks_behind, 10);
+
+        // Requests succeeding on a degraded endpoint keep it degraded
+        health.record_success(100);
+        assert_eq!(health.status, HealthStatus::Degraded);
+
+        health.record_verdict(
+            "ws://a",
+            Verdict::WrongNetwork {
+                found: 5,
+                expected: 1,
+            },
+        );
+        assert!(!health.is_healthy);
+        assert_eq!(health.status, HealthStatus::WrongNetwork);
+
+        health.record_success(100);
+        assert!(!health.is_healthy);
+        assert_eq!(health.status, HealthStatus::WrongNetwork);
+    }
+}
@@ -45,14 +45,21 @@ pub mod pipeline;
 /// Metrics collection and monitoring
 pub mod metrics;
 
+/// Tracing spans, latency histograms and optional OTLP export
+pub mod telemetry;
+
 /// Golden vectors for encoding verification
 pub mod golden_vectors;
 
+/// Endpoint health classification shared by the connection pools
+pub mod health;
+
 pub use golden_vectors::{
     load_default_golden_vectors, verify_golden_vector, ChainType, GoldenVector, GoldenVectorSet,
 };
-pub use metrics::{MetricType, MetricsCollector};
+pub use metrics::{Histogram, MetricType, MetricsCollector};
 pub use pipeline::{TransactionPipeline, TransactionResult};
+pub use telemetry::{global_metrics, PipelineStage, Telemetry};
 
 /// Unified error taxonomy for the SDK
 #[derive(Error, Debug)]
@@ -185,6 +192,9 @@ pub struct BlockEvent {
     pub pallet: String,
     /// Event name
     pub event: String,
+    /// Decoded event fields, when the runtime metadata describes them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<serde_json::Value>,
 }
 
 /// Signer trait for signing transactions
@@ -1,10 +1,11 @@
 //! # Metrics Collection and Monitoring
 //!
 //! This module provides comprehensive metrics collection for the Apex SDK,
-//! including transaction metrics, performance tracking, and Prometheus export.
+//! including transaction metrics, latency histograms, performance tracking,
+//! and Prometheus export.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
@@ -75,10 +76,87 @@ impl Metric {
     }
 }
 
+/// Default latency buckets in seconds, from 5ms up to one minute
+pub const DEFAULT_LATENCY_BUCKETS: &[f64] = &[
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0,
+];
+
+/// Maximum number of raw metric samples kept by a [`MetricsCollector`]
+///
+/// Once full, the oldest samples are dropped. Histograms are unaffected.
+pub const MAX_RECORDED_METRICS: usize = 10_000;
+
+/// A bucketed histogram for a single label set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Histogram {
+    /// The metric name
+    pub name: String,
+    /// Help text describing the metric
+    pub help: String,
+    /// Labels identifying this series
+    pub labels: BTreeMap<String, String>,
+    /// Upper bounds of the buckets (the implicit `+Inf` bucket is not listed)
+    pub bounds: Vec<f64>,
+    /// Observation count per bucket, with one extra slot for `+Inf`
+    pub counts: Vec<u64>,
+    /// Sum of all observed values
+    pub sum: f64,
+    /// Total number of observations
+    pub count: u64,
+}
+
+impl Histogram {
+    /// Create an empty histogram with the given bucket bounds
+    pub fn new(
+        name: impl Into<String>,
+        help: impl Into<String>,
+        labels: BTreeMap<String, String>,
+        bounds: &[f64],
+    ) -> Self {
+        Self {
+            name: name.into(),
+            help: help.into(),
+            labels,
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len() + 1],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    /// Record an observation
+    pub fn observe(&mut self, value: f64) {
+        let bucket = self
+            .bounds
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.bounds.len());
+        self.counts[bucket] += 1;
+        self.sum += value;
+        self.count += 1;
+    }
+
+    /// Cumulative bucket counts paired with their upper bound (`None` is `+Inf`)
+    pub fn cumulative_buckets(&self) -> Vec<(Option<f64>, u64)> {
+        let mut total = 0;
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(i, count)| {
+                total += count;
+                (self.bounds.get(i).copied(), total)
+            })
+            .collect()
+    }
+}
+
+type HistogramKey = (String, BTreeMap<String, String>);
+
 /// Metrics collector for gathering and exporting metrics
 #[derive(Debug, Clone)]
 pub struct MetricsCollector {
-    metrics: Arc<Mutex<Vec<Metric>>>,
+    metrics: Arc<Mutex<VecDeque<Metric>>>,
+    histograms: Arc<Mutex<BTreeMap<HistogramKey, Histogram>>>,
     start_time: Instant,
 }
 
@@ -86,15 +164,19 @@ impl MetricsCollector {
     /// Create a new metrics collector
     pub fn new() -> Self {
         Self {
-            metrics: Arc::new(Mutex::new(Vec::new())),
+            metrics: Arc::new(Mutex::new(VecDeque::new())),
+            histograms: Arc::new(Mutex::new(BTreeMap::new())),
             start_time: Instant::now(),
         }
     }
 
-    /// Record a metric
+    /// Record a metric, dropping the oldest sample beyond [`MAX_RECORDED_METRICS`]
     pub fn record(&self, metric: Metric) {
         if let Ok(mut metrics) = self.metrics.lock() {
-            metrics.push(metric);
+            if metrics.len() >= MAX_RECORDED_METRICS {
+                metrics.pop_front();
+            }
+            metrics.push_back(metric);
         }
     }
 
@@ -113,11 +195,44 @@ impl MetricsCollector {
 
     /// Record a histogram metric (duration)
     pub fn record_duration(&self, name: impl Into<String>, duration: Duration) {
+        let name = name.into();
+        self.observe_histogram(&name, "Duration histogram", &[], duration.as_secs_f64());
+
         let metric = Metric::new(MetricType::TransactionLatency, name, duration.as_secs_f64())
             .with_help("Duration histogram");
         self.record(metric);
     }
 
+    /// Record an observation into a latency histogram
+    ///
+    /// Series are keyed by name and labels; new series use
+    /// [`DEFAULT_LATENCY_BUCKETS`].
+    pub fn observe_histogram(&self, name: &str, help: &str, labels: &[(&str, &str)], value: f64) {
+        let labels: BTreeMap<String, String> = labels
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+
+        let mut histograms = self
+            .histograms
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        histograms
+            .entry((name.to_string(), labels.clone()))
+            .or_insert_with(|| Histogram::new(name, help, labels, DEFAULT_LATENCY_BUCKETS))
+            .observe(value);
+    }
+
+    /// Get all histogram series
+    pub fn get_histograms(&self) -> Vec<Histogram> {
+        self.histograms
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .values()
+            .cloned()
+            .collect()
+    }
+
     /// Record a transaction success
     pub fn record_transaction_success(&self, chain: &str, tx_hash: &str) {
         let metric = Metric::new(
@@ -177,6 +292,12 @@ impl MetricsCollector {
         .with_label("chain", chain)
         .with_label("operation", operation)
         .with_help("Provider response time in seconds");
+        self.observe_histogram(
+            &metric.name,
+            "Provider response time in seconds",
+            &[("chain", chain), ("operation", operation)],
+            metric.value,
+        );
         self.record(metric);
     }
 
@@ -196,7 +317,9 @@ impl MetricsCollector {
             .unwrap_or_else(|poisoned| {
                 poisoned.into_inner() // Recover from poisoned mutex
             })
-            .clone()
+            .iter()
+            .cloned()
+            .collect()
     }
 
     /// Clear all collected metrics
@@ -204,6 +327,9 @@ impl MetricsCollector {
         if let Ok(mut metrics) = self.metrics.lock() {
             metrics.clear();
         }
+        if let Ok(mut histograms) = self.histograms.lock() {
+            histograms.clear();
+        }
     }
 
     /// Get the number of collected metrics
@@ -234,9 +360,16 @@ impl MetricsCollector {
             self.uptime().as_secs()
         ));
 
+        // Histograms are rendered with real buckets, so skip their raw samples
+        let histograms = self.get_histograms();
+        output.push_str(&render_histograms(&histograms));
+
         // Group metrics by name
         let mut grouped_metrics: HashMap<String, Vec<&Metric>> = HashMap::new();
         for metric in &metrics {
+            if histograms.iter().any(|h| h.name == metric.name) {
+                continue;
+            }
             grouped_metrics
                 .entry(metric.name.clone())
                 .or_default()
@@ -300,6 +433,68 @@ impl Default for MetricsCollector {
     }
 }
 
+/// Render histograms in Prometheus text format, one `# TYPE` block per name
+pub fn render_histograms(histograms: &[Histogram]) -> String {
+    let mut output = String::new();
+    let mut current: Option<&str> = None;
+
+    for histogram in histograms {
+        if current != Some(histogram.name.as_str()) {
+            if current.is_some() {
+                output.push('\n');
+            }
+            output.push_str(&format!("# HELP {} {}\n", histogram.name, histogram.help));
+            output.push_str(&format!("# TYPE {} histogram\n", histogram.name));
+            current = Some(&histogram.name);
+        }
+
+        let base_labels: Vec<String> = histogram
+            .labels
+            .iter()
+            .map(|(k, v)| format!("{}=\"{}\"", k, escape_label(v)))
+            .collect();
+
+        for (bound, count) in histogram.cumulative_buckets() {
+            let le = bound.map_or_else(|| "+Inf".to_string(), |b| b.to_string());
+            let mut labels = base_labels.clone();
+            labels.push(format!("le=\"{}\"", le));
+            output.push_str(&format!(
+                "{}_bucket{{{}}} {}\n",
+                histogram.name,
+                labels.join(","),
+                count
+            ));
+        }
+
+        let labels = if base_labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{}}}", base_labels.join(","))
+        };
+        output.push_str(&format!(
+            "{}_sum{} {}\n",
+            histogram.name, labels, histogram.sum
+        ));
+        output.push_str(&format!(
+            "{}_count{} {}\n",
+            histogram.name, labels, histogram.count
+        ));
+    }
+
+    if current.is_some() {
+        output.push('\n');
+    }
+    output
+}
+
+/// Escape a Prometheus label value
+pub fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -356,6 +551,59 @@ mod tests {
         assert!(json_output.contains("3.1415"));
     }
 
+    #[test]
+    fn test_histogram_buckets() {
+        let mut histogram = Histogram::new("latency", "Latency", BTreeMap::new(), &[0.1, 1.0]);
+        histogram.observe(0.05);
+        histogram.observe(0.5);
+        histogram.observe(5.0);
+
+        assert_eq!(histogram.count, 3);
+        assert!((histogram.sum - 5.55).abs() < 1e-9);
+        assert_eq!(
+            histogram.cumulative_buckets(),
+            vec![(Some(0.1), 1), (Some(1.0), 2), (None, 3)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_histogram_export() {
+        let collector = MetricsCollector::new();
+        collector.observe_histogram("rpc_seconds", "RPC latency", &[("chain", "ethereum")], 0.02);
+        collector.observe_histogram("rpc_seconds", "RPC latency", &[("chain", "ethereum")], 0.2);
+
+        let output = collector.export_prometheus().await;
+        assert!(output.contains("# TYPE rpc_seconds histogram"));
+        assert!(output.contains("rpc_seconds_bucket{chain=\"ethereum\",le=\"0.025\"} 1"));
+        assert!(output.contains("rpc_seconds_bucket{chain=\"ethereum\",le=\"+Inf\"} 2"));
+        assert!(output.contains("rpc_seconds_count{chain=\"ethereum\"} 2"));
+    }
+
+    #[tokio::test]
+    async fn test_duration_recorded_as_histogram() {
+        let collector = MetricsCollector::new();
+        collector.record_duration("op_seconds", Duration::from_millis(30));
+
+        assert_eq!(collector.count(), 1);
+        assert_eq!(collector.get_histograms().len(), 1);
+
+        let output = collector.export_prometheus().await;
+        assert_eq!(output.matches("# TYPE op_seconds histogram").count(), 1);
+        assert!(output.contains("op_seconds_count 1"));
+    }
+
+    #[test]
+    fn test_recorded_metrics_are_bounded() {
+        let collector = MetricsCollector::new();
+        for i in 0..MAX_RECORDED_METRICS + 5 {
+            collector.record_duration(format!("op_{}", i), Duration::from_millis(1));
+        }
+
+        let metrics = collector.get_metrics();
+        assert_eq!(metrics.len(), MAX_RECORDED_METRICS);
+        assert_eq!(metrics[0].name, "op_5");
+    }
+
     #[test]
     fn test_metric_with_labels() {
         let metric = Metric::new(MetricType::TransactionCount, "tx_count", 1.0)
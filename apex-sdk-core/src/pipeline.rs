@@ -3,6 +3,7 @@
 //! Core transaction pipeline implementation providing unified transaction handling
 //! across EVM and Substrate chains.
 
+use crate::telemetry::{PipelineStage, Telemetry};
 use crate::{
     Broadcaster, ConfirmationStrategy, FeeEstimator, NonceManager, Provider, ReceiptWatcher,
     RetryConfig, SdkError, SdkLog, Signer, TimeoutConfig,
@@ -11,6 +12,7 @@ use apex_sdk_types::{Address, ChainType, TransactionStatus};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tokio::time::timeout;
+use tracing::Instrument;
 
 /// Transaction pipeline for unified transaction execution
 #[derive(Debug, Clone)]
@@ -32,6 +34,7 @@ where
     retry_config: RetryConfig,
     timeout_config: TimeoutConfig,
     confirmation_strategy: ConfirmationStrategy,
+    telemetry: Telemetry,
 }
 
 impl<P, S, FE, N, B, R> TransactionPipeline<P, S, FE, N, B, R>
@@ -62,6 +65,7 @@ where
             retry_config: RetryConfig::default(),
             timeout_config: TimeoutConfig::default(),
             confirmation_strategy: ConfirmationStrategy::default(),
+            telemetry: Telemetry::default(),
         }
     }
 
@@ -83,25 +87,72 @@ where
         self
     }
 
+    /// Set the telemetry handle used for stage spans and latency histograms
+    pub fn with_telemetry(mut self, telemetry: Telemetry) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Get the telemetry handle
+    pub fn telemetry(&self) -> &Telemetry {
+        &self.telemetry
+    }
+
     /// Execute a transaction through the complete pipeline
+    ///
+    /// Each stage runs inside its own span (see [`Telemetry::stage_span`]).
     pub async fn execute_transaction(
         &self,
         unsigned_tx: &[u8],
     ) -> Result<TransactionResult, SdkError> {
+        let span = tracing::info_span!(
+            "execute_transaction",
+            chain = %self.telemetry.chain(),
+            endpoint = %self.telemetry.endpoint(),
+        );
+        self.run_stages(unsigned_tx).instrument(span).await
+    }
+
+    /// Run all pipeline stages in order
+    async fn run_stages(&self, unsigned_tx: &[u8]) -> Result<TransactionResult, SdkError> {
         // Pre-transaction validation (health check + balance)
         let estimated_fee = self.validate_transaction_readiness(unsigned_tx).await?;
 
         // Step 2: Get nonce
-        let nonce = self.get_nonce_with_retry(&self.signer.address()).await?;
+        let nonce = self
+            .telemetry
+            .instrument_stage(
+                PipelineStage::Nonce,
+                self.get_nonce_with_retry(&self.signer.address()),
+            )
+            .await?;
 
         // Step 3: Sign transaction
-        let signed_tx = self.sign_transaction_with_retry(unsigned_tx).await?;
+        let signed_tx = self
+            .telemetry
+            .instrument_stage(
+                PipelineStage::Sign,
+                self.sign_transaction_with_retry(unsigned_tx),
+            )
+            .await?;
 
         // Step 4: Broadcast transaction
-        let tx_hash = self.broadcast_with_retry(&signed_tx).await?;
+        let tx_hash = self
+            .telemetry
+            .instrument_stage(
+                PipelineStage::Broadcast,
+                self.broadcast_with_retry(&signed_tx),
+            )
+            .await?;
 
         // Step 5: Wait for confirmation
-        let status = self.wait_for_confirmation_with_retry(&tx_hash).await?;
+        let status = self
+            .telemetry
+            .instrument_stage(
+                PipelineStage::Confirm,
+                self.wait_for_confirmation_with_retry(&tx_hash),
+            )
+            .await?;
 
         Ok(TransactionResult {
             hash: tx_hash,
@@ -115,12 +166,16 @@ where
     /// Check the balance of the signer account
     pub async fn check_signer_balance(&self) -> Result<u128, SdkError> {
         let address = self.signer.address();
-        timeout(
-            Duration::from_secs(self.timeout_config.rpc_timeout_secs),
-            self.provider.get_balance(&address),
-        )
-        .await
-        .map_err(|_| SdkError::NetworkError("Balance check timeout".to_string()))?
+        self.telemetry
+            .instrument_rpc("get_balance", async {
+                timeout(
+                    Duration::from_secs(self.timeout_config.rpc_timeout_secs),
+                    self.provider.get_balance(&address),
+                )
+                .await
+                .map_err(|_| SdkError::NetworkError("Balance check timeout".to_string()))?
+            })
+            .await
     }
 
     /// Get current block number from the provider
@@ -151,13 +206,19 @@ where
         unsigned_tx: &[u8],
     ) -> Result<u128, SdkError> {
         // Health check
-        self.provider
-            .health_check()
+        self.telemetry
+            .instrument_rpc("health_check", self.provider.health_check())
             .await
             .map_err(|e| SdkError::NetworkError(format!("Provider health check failed: {}", e)))?;
 
         // Estimate fees first
-        let estimated_fee = self.estimate_fee_with_retry(unsigned_tx).await?;
+        let estimated_fee = self
+            .telemetry
+            .instrument_stage(
+                PipelineStage::Estimate,
+                self.estimate_fee_with_retry(unsigned_tx),
+            )
+            .await?;
 
         // Validate sufficient balance
         self.validate_sufficient_balance(estimated_fee).await?;
@@ -419,7 +480,8 @@ where
         data: Option<Vec<u8>>,
     ) -> Result<Vec<u8>, SdkError> {
         let nonce = self.get_nonce_with_retry(&self.signer.address()).await?;
-        let gas_estimate = self.fee_estimator.estimate_fee(&[]).await?;
+        let metadata = evm_call_metadata(to, value, data.as_deref())?;
+        let gas_estimate = self.fee_estimator.estimate_fee(&metadata).await?;
 
         // Note: This builds a simplified JSON representation of a transaction
         // For production EVM transactions, use EvmAdapter which properly queries chain ID
@@ -589,6 +651,27 @@ pub struct TransactionResult {
     pub confirmation_strategy: ConfirmationStrategy,
 }
 
+/// Encode an EVM call as fee estimator metadata
+///
+/// Layout: `[0x02, to (20 bytes), value (32 bytes, big-endian), calldata]`,
+/// the generic contract call format decoded by EVM fee estimators and signers.
+fn evm_call_metadata(to: &Address, value: u128, data: Option<&[u8]>) -> Result<Vec<u8>, SdkError> {
+    let to = hex::decode(to.to_string().trim_start_matches("0x"))
+        .ok()
+        .filter(|bytes| bytes.len() == 20)
+        .ok_or_else(|| SdkError::TransactionError(format!("Invalid EVM address: {}", to)))?;
+
+    let mut metadata = Vec::with_capacity(53 + data.map_or(0, <[u8]>::len));
+    metadata.push(0x02);
+    metadata.extend_from_slice(&to);
+    metadata.extend_from_slice(&[0u8; 16]);
+    metadata.extend_from_slice(&value.to_be_bytes());
+    if let Some(data) = data {
+        metadata.extend_from_slice(data);
+    }
+    Ok(metadata)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -711,4 +794,23 @@ mod tests {
         let result = pipeline.validate_transaction_readiness(&[1, 2, 3]).await;
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_evm_call_metadata_layout() {
+        let to = Address::evm("0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb7");
+        let metadata = evm_call_metadata(&to, 1_000, Some(&[0xde, 0xad])).unwrap();
+
+        assert_eq!(metadata.len(), 55);
+        assert_eq!(metadata[0], 0x02);
+        assert_eq!(
+            hex::encode(&metadata[1..21]),
+            "742d35cc6634c0532925a3b844bc9e7595f0beb7"
+        );
+        assert_eq!(&metadata[51..53], &1_000u16.to_be_bytes());
+        assert!(metadata[21..51].iter().all(|b| *b == 0));
+        assert_eq!(&metadata[53..], &[0xde, 0xad]);
+
+        let substrate = Address::substrate("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY");
+        assert!(evm_call_metadata(&substrate, 0, None).is_err());
+    }
 }
@@ -0,0 +1,446 @@
+//! # Telemetry
+//!
+//! Unified instrumentation layer for the Apex SDK.
+//!
+//! Every RPC call and transaction pipeline stage runs inside a `tracing` span
+//! carrying `chain` and `endpoint` attributes, and its latency is recorded in a
+//! bucketed histogram of the process-wide [`MetricsCollector`] returned by
+//! [`global_metrics`].
+//!
+//! With the `otel` feature enabled, `init_otlp` installs an OpenTelemetry
+//! tracing layer and meter provider so spans and histograms are exported over
+//! OTLP as well.
+
+use crate::metrics::MetricsCollector;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::future::IntoFuture;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tracing::{Instrument, Span};
+
+/// Histogram of RPC call latencies in seconds
+pub const RPC_LATENCY_METRIC: &str = "apex_rpc_duration_seconds";
+
+/// Histogram of transaction pipeline stage latencies in seconds
+pub const STAGE_LATENCY_METRIC: &str = "apex_pipeline_stage_duration_seconds";
+
+static GLOBAL_METRICS: OnceLock<MetricsCollector> = OnceLock::new();
+
+/// Process-wide metrics collector fed by all SDK instrumentation
+pub fn global_metrics() -> &'static MetricsCollector {
+    GLOBAL_METRICS.get_or_init(MetricsCollector::new)
+}
+
+/// Stages of the transaction pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PipelineStage {
+    /// Fee estimation
+    Estimate,
+    /// Nonce retrieval
+    Nonce,
+    /// Transaction signing
+    Sign,
+    /// Broadcasting to the network
+    Broadcast,
+    /// Waiting for confirmation
+    Confirm,
+}
+
+impl PipelineStage {
+    /// All stages in execution order
+    pub const ALL: [PipelineStage; 5] = [
+        PipelineStage::Estimate,
+        PipelineStage::Nonce,
+        PipelineStage::Sign,
+        PipelineStage::Broadcast,
+        PipelineStage::Confirm,
+    ];
+
+    /// Stage name used in span and metric attributes
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PipelineStage::Estimate => "estimate",
+            PipelineStage::Nonce => "nonce",
+            PipelineStage::Sign => "sign",
+            PipelineStage::Broadcast => "broadcast",
+            PipelineStage::Confirm => "confirm",
+        }
+    }
+}
+
+impl std::fmt::Display for PipelineStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Instrumentation handle bound to a chain and endpoint
+#[derive(Debug, Clone)]
+pub struct Telemetry {
+    chain: String,
+    endpoint: String,
+    collector: MetricsCollector,
+}
+
+impl Telemetry {
+    /// Create a telemetry handle recording into [`global_metrics`]
+    pub fn new(chain: impl Into<String>, endpoint: impl Into<String>) -> Self {
+        Self {
+            chain: chain.into(),
+            endpoint: endpoint.into(),
+            collector: global_metrics().clone(),
+        }
+    }
+
+    /// Record into a specific collector instead of the global one
+    pub fn with_collector(mut self, collector: MetricsCollector) -> Self {
+        self.collector = collector;
+        self
+    }
+
+    /// Chain attribute
+    pub fn chain(&self) -> &str {
+        &self.chain
+    }
+
+    /// Endpoint attribute
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Collector receiving this handle's histograms
+    pub fn collector(&self) -> &MetricsCollector {
+        &self.collector
+    }
+
+    /// Create a span for a single RPC call
+    pub fn rpc_span(&self, method: &str) -> Span {
+        tracing::info_span!(
+            "rpc",
+            otel.name = %format!("rpc {}", method),
+            rpc.method = %method,
+            chain = %self.chain,
+            endpoint = %self.endpoint,
+        )
+    }
+
+    /// Create a span for a transaction pipeline stage
+    pub fn stage_span(&self, stage: PipelineStage) -> Span {
+        tracing::info_span!(
+            "pipeline_stage",
+            otel.name = %format!("pipeline {}", stage),
+            stage = %stage,
+            chain = %self.chain,
+            endpoint = %self.endpoint,
+        )
+    }
+
+    /// Run an RPC call inside its span and record its latency
+    pub async fn instrument_rpc<F, T, E>(&self, method: &str, fut: F) -> Result<T, E>
+    where
+        F: IntoFuture<Output = Result<T, E>>,
+        E: Display,
+    {
+        let span = self.rpc_span(method);
+        let start = Instant::now();
+        let result = fut.into_future().instrument(span.clone()).await;
+        if let Err(e) = &result {
+            span.in_scope(|| tracing::debug!(error = %e, "RPC call failed"));
+        }
+        self.record_rpc(method, start.elapsed(), result.is_ok());
+        result
+    }
+
+    /// Run a pipeline stage inside its span and record its latency
+    pub async fn instrument_stage<F, T, E>(&self, stage: PipelineStage, fut: F) -> Result<T, E>
+    where
+        F: IntoFuture<Output = Result<T, E>>,
+        E: Display,
+    {
+        let span = self.stage_span(stage);
+        let start = Instant::now();
+        let result = fut.into_future().instrument(span.clone()).await;
+        if let Err(e) = &result {
+            span.in_scope(|| tracing::warn!(error = %e, "Pipeline stage failed"));
+        }
+        self.record_stage(stage, start.elapsed(), result.is_ok());
+        result
+    }
+
+    /// Record the latency of an RPC call timed by the caller
+    pub fn record_rpc(&self, method: &str, duration: Duration, success: bool) {
+        let labels = [
+            ("chain", self.chain.as_str()),
+            ("endpoint", self.endpoint.as_str()),
+            ("method", method),
+            ("status", status_label(success)),
+        ];
+        self.observe(
+            RPC_LATENCY_METRIC,
+            "RPC call latency in seconds",
+            &labels,
+            duration,
+        );
+    }
+
+    /// Record the latency of a pipeline stage timed by the caller
+    pub fn record_stage(&self, stage: PipelineStage, duration: Duration, success: bool) {
+        let labels = [
+            ("chain", self.chain.as_str()),
+            ("endpoint", self.endpoint.as_str()),
+            ("stage", stage.as_str()),
+            ("status", status_label(success)),
+        ];
+        self.observe(
+            STAGE_LATENCY_METRIC,
+            "Transaction pipeline stage latency in seconds",
+            &labels,
+            duration,
+        );
+    }
+
+    fn observe(&self, name: &str, help: &str, labels: &[(&str, &str)], duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        self.collector
+            .observe_histogram(name, help, labels, seconds);
+
+        #[cfg(feature = "otel")]
+        otlp::record_histogram(name, help, labels, seconds);
+    }
+}
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        Self::new("unknown", "unknown")
+    }
+}
+
+fn status_label(success: bool) -> &'static str {
+    if success {
+        "ok"
+    } else {
+        "error"
+    }
+}
+
+#[cfg(feature = "otel")]
+pub use otlp::{init_otlp, OtlpConfig, TelemetryGuard};
+
+/// OTLP export of spans and histograms
+#[cfg(feature = "otel")]
+mod otlp {
+    use crate::SdkError;
+    use opentelemetry::metrics::Histogram as OtelHistogram;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use opentelemetry_sdk::Resource;
+    use std::collections::HashMap;
+    use std::sync::{Mutex, OnceLock};
+    use std::time::Duration;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    static HISTOGRAMS: OnceLock<Mutex<HashMap<String, OtelHistogram<f64>>>> = OnceLock::new();
+
+    /// Configuration for OTLP export
+    #[derive(Debug, Clone)]
+    pub struct OtlpConfig {
+        /// OTLP/HTTP collector base URL
+        pub endpoint: String,
+        /// `service.name` resource attribute
+        pub service_name: String,
+        /// Interval between metric exports
+        pub metrics_interval: Duration,
+    }
+
+    impl Default for OtlpConfig {
+        fn default() -> Self {
+            Self {
+                endpoint: "http://localhost:4318".to_string(),
+                service_name: "apex-sdk".to_string(),
+                metrics_interval: Duration::from_secs(30),
+            }
+        }
+    }
+
+    impl OtlpConfig {
+        /// Create a configuration for the given collector endpoint
+        pub fn new(endpoint: impl Into<String>) -> Self {
+            Self {
+                endpoint: endpoint.into(),
+                ..Default::default()
+            }
+        }
+
+        /// Set the service name
+        pub fn with_service_name(mut self, service_name: impl Into<String>) -> Self {
+            self.service_name = service_name.into();
+            self
+        }
+
+        /// Set the metric export interval
+        pub fn with_metrics_interval(mut self, interval: Duration) -> Self {
+            self.metrics_interval = interval;
+            self
+        }
+    }
+
+    /// Keeps the OTLP providers alive and flushes them on drop
+    pub struct TelemetryGuard {
+        tracer_provider: SdkTracerProvider,
+        meter_provider: SdkMeterProvider,
+    }
+
+    impl Drop for TelemetryGuard {
+        fn drop(&mut self) {
+            if let Err(e) = self.tracer_provider.shutdown() {
+                eprintln!("Failed to shut down OTLP tracer provider: {}", e);
+            }
+            if let Err(e) = self.meter_provider.shutdown() {
+                eprintln!("Failed to shut down OTLP meter provider: {}", e);
+            }
+        }
+    }
+
+    /// Install OTLP span and metric exporters
+    ///
+    /// Registers a global `tracing` subscriber that forwards spans to the
+    /// collector and logs to stderr, and a global meter provider receiving the
+    /// SDK's latency histograms. Must be called from within a Tokio runtime.
+    pub fn init_otlp(config: OtlpConfig) -> Result<TelemetryGuard, SdkError> {
+        let endpoint = config.endpoint.trim_end_matches('/');
+        let resource = Resource::builder()
+            .with_service_name(config.service_name.clone())
+            .build();
+
+        let span_exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{}/v1/traces", endpoint))
+            .build()
+            .map_err(|e| SdkError::ConfigError(format!("Failed to build span exporter: {}", e)))?;
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_batch_exporter(span_exporter)
+            .with_resource(resource.clone())
+            .build();
+
+        let metric_exporter = opentelemetry_otlp::MetricExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{}/v1/metrics", endpoint))
+            .build()
+            .map_err(|e| {
+                SdkError::ConfigError(format!("Failed to build metric exporter: {}", e))
+            })?;
+        let reader = opentelemetry_sdk::metrics::PeriodicReader::builder(metric_exporter)
+            .with_interval(config.metrics_interval)
+            .build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(reader)
+            .with_resource(resource)
+            .build();
+
+        opentelemetry::global::set_tracer_provider(tracer_provider.clone());
+        opentelemetry::global::set_meter_provider(meter_provider.clone());
+
+        let tracer = tracer_provider.tracer("apex-sdk");
+        tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(tracer))
+            .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+            .try_init()
+            .map_err(|e| {
+                SdkError::ConfigError(format!("Failed to install tracing subscriber: {}", e))
+            })?;
+
+        Ok(TelemetryGuard {
+            tracer_provider,
+            meter_provider,
+        })
+    }
+
+    /// Record a histogram observation with the global meter provider
+    pub(super) fn record_histogram(name: &str, help: &str, labels: &[(&str, &str)], value: f64) {
+        let histograms = HISTOGRAMS.get_or_init(|| Mutex::new(HashMap::new()));
+        let mut histograms = histograms
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let histogram = histograms.entry(name.to_string()).or_insert_with(|| {
+            opentelemetry::global::meter("apex-sdk")
+                .f64_histogram(name.to_string())
+                .with_description(help.to_string())
+                .with_unit("s")
+                .with_boundaries(crate::metrics::DEFAULT_LATENCY_BUCKETS.to_vec())
+                .build()
+        });
+
+        let attributes: Vec<KeyValue> = labels
+            .iter()
+            .map(|(k, v)| KeyValue::new(k.to_string(), v.to_string()))
+            .collect();
+        histogram.record(value, &attributes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_stage_names() {
+        let names: Vec<&str> = PipelineStage::ALL.iter().map(|s| s.as_str()).collect();
+        assert_eq!(names, ["estimate", "nonce", "sign", "broadcast", "confirm"]);
+        assert_eq!(PipelineStage::Broadcast.to_string(), "broadcast");
+    }
+
+    #[tokio::test]
+    async fn test_instrument_rpc_records_histogram() {
+        let collector = MetricsCollector::new();
+        let telemetry =
+            Telemetry::new("ethereum", "http://localhost:8545").with_collector(collector);
+
+        let ok: Result<u64, String> = telemetry
+            .instrument_rpc("eth_blockNumber", async { Ok(1) })
+            .await;
+        let err: Result<u64, String> = telemetry
+            .instrument_rpc("eth_blockNumber", async { Err("boom".to_string()) })
+            .await;
+        assert!(ok.is_ok());
+        assert!(err.is_err());
+
+        let histograms = telemetry.collector().get_histograms();
+        assert_eq!(histograms.len(), 2);
+        assert!(histograms
+            .iter()
+            .all(|h| h.name == RPC_LATENCY_METRIC && h.count == 1));
+        assert!(histograms
+            .iter()
+            .any(|h| h.labels.get("status").map(String::as_str) == Some("error")));
+        assert!(
+            histograms
+                .iter()
+                .all(|h| h.labels.get("endpoint").map(String::as_str)
+                    == Some("http://localhost:8545"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_instrument_stage_records_histogram() {
+        let collector = MetricsCollector::new();
+        let telemetry =
+            Telemetry::new("westend", "wss://westend-rpc.polkadot.io").with_collector(collector);
+
+        let result: Result<(), String> = telemetry
+            .instrument_stage(PipelineStage::Sign, async { Ok(()) })
+            .await;
+        assert!(result.is_ok());
+
+        let histograms = telemetry.collector().get_histograms();
+        assert_eq!(histograms.len(), 1);
+        assert_eq!(histograms[0].name, STAGE_LATENCY_METRIC);
+        assert_eq!(histograms[0].labels.get("stage").unwrap(), "sign");
+        assert_eq!(histograms[0].labels.get("chain").unwrap(), "westend");
+    }
+}
@@ -0,0 +1,631 @@
+//! Solidity JSON ABI encoding and decoding
+//!
+//! [`ContractAbi`] wraps a contract's JSON ABI and encodes function calls and
+//! constructor arguments, and decodes calldata, return data, revert reasons
+//! and event logs. Arguments can be given as [`DynSolValue`]s or as JSON, so
+//! the same ABI handling serves EVM chains and Ethereum-compatible contract
+//! pallets.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use apex_sdk_evm::abi::ContractAbi;
+//! use serde_json::json;
+//!
+//! # fn main() -> Result<(), apex_sdk_evm::Error> {
+//! let abi = ContractAbi::from_json(r#"[{"type":"function","name":"transfer",
+//!     "inputs":[{"name":"to","type":"address"},{"name":"amount","type":"uint256"}],
+//!     "outputs":[{"name":"","type":"bool"}],"stateMutability":"nonpayable"}]"#)?;
+//!
+//! let data = abi.encode_call_json(
+//!     "transfer",
+//!     &[json!("0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb7"), json!("1000")],
+//! )?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::Error;
+use alloy::dyn_abi::{DynSolType, EventExt, FunctionExt, JsonAbiExt, Specifier};
+use alloy::json_abi::{Function, JsonAbi, Param};
+use alloy::primitives::B256;
+
+pub use alloy::dyn_abi::DynSolValue;
+
+/// Selector of the `Error(string)` revert
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Selector of the `Panic(uint256)` revert
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// A contract's JSON ABI
+#[derive(Debug, Clone)]
+pub struct ContractAbi {
+    abi: JsonAbi,
+}
+
+/// An event log decoded with a contract ABI
+#[derive(Debug, Clone)]
+pub struct DecodedLog {
+    /// Event name
+    pub name: String,
+    /// Event parameters in declaration order
+    pub params: Vec<(String, DynSolValue)>,
+}
+
+impl DecodedLog {
+    /// Get a parameter by name
+    pub fn param(&self, name: &str) -> Option<&DynSolValue> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Render the parameters as a JSON object
+    pub fn to_json(&self) -> serde_json::Value {
+        params_json(&self.params)
+    }
+}
+
+/// Calldata or revert data decoded with a contract ABI
+#[derive(Debug, Clone)]
+pub struct DecodedCall {
+    /// Function or error name
+    pub name: String,
+    /// Canonical signature, e.g. `transfer(address,uint256)`
+    pub signature: String,
+    /// Arguments in declaration order
+    pub params: Vec<(String, DynSolValue)>,
+}
+
+impl DecodedCall {
+    /// Render the arguments as a JSON object
+    pub fn to_json(&self) -> serde_json::Value {
+        params_json(&self.params)
+    }
+}
+
+impl ContractAbi {
+    /// Wrap a parsed JSON ABI
+    pub fn new(abi: JsonAbi) -> Self {
+        Self { abi }
+    }
+
+    /// Parse a JSON ABI
+    ///
+    /// Accepts a bare ABI array as well as compiler artifacts (solc, Hardhat,
+    /// Foundry, resolc) that carry the ABI in an `abi` field.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let value: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| Error::Contract(format!("Failed to parse ABI: {}", e)))?;
+        let abi = match value {
+            serde_json::Value::Object(mut artifact) => artifact
+                .remove("abi")
+                .ok_or_else(|| Error::Contract("Artifact has no `abi` field".to_string()))?,
+            abi => abi,
+        };
+
+        serde_json::from_value(abi)
+            .map(Self::new)
+            .map_err(|e| Error::Contract(format!("Failed to parse ABI: {}", e)))
+    }
+
+    /// Build an ABI holding a single function from its signature
+    ///
+    /// Accepts `"transfer(address,uint256)"` as well as the human-readable form
+    /// with names and outputs, e.g. `"function balanceOf(address owner) returns (uint256)"`.
+    pub fn from_signature(signature: &str) -> Result<Self, Error> {
+        let function = Function::parse(signature).map_err(|e| {
+            Error::Contract(format!("Invalid function signature '{}': {}", signature, e))
+        })?;
+
+        let mut abi = JsonAbi::new();
+        abi.functions
+            .entry(function.name.clone())
+            .or_default()
+            .push(function);
+        Ok(Self::new(abi))
+    }
+
+    /// Get the underlying JSON ABI
+    pub fn abi(&self) -> &JsonAbi {
+        &self.abi
+    }
+
+    /// Merge several ABIs into one, dropping duplicate items
+    ///
+    /// Useful to decode data from several contracts with one ABI.
+    pub fn merge(abis: impl IntoIterator<Item = ContractAbi>) -> Self {
+        let mut abi: JsonAbi = abis
+            .into_iter()
+            .flat_map(|other| other.abi.into_items())
+            .collect();
+        abi.dedup();
+        Self::new(abi)
+    }
+
+    /// Look up a function by name, or by full signature for overloads
+    /// (e.g. `"transfer(address,uint256)"`)
+    pub fn function(&self, name: &str) -> Result<&Function, Error> {
+        if name.contains('(') {
+            return self
+                .abi
+                .functions()
+                .find(|f| f.signature() == name)
+                .ok_or_else(|| Error::Contract(format!("Function '{}' not found", name)));
+        }
+
+        match self.abi.function(name).map(Vec::as_slice) {
+            Some([function]) => Ok(function),
+            Some(overloads) if !overloads.is_empty() => Err(Error::Contract(format!(
+                "Function '{}' is overloaded, use one of: {}",
+                name,
+                overloads
+                    .iter()
+                    .map(|f| f.signature())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))),
+            _ => Err(Error::Contract(format!("Function '{}' not found", name))),
+        }
+    }
+
+    /// Encode a function call: the selector followed by the ABI-encoded arguments
+    pub fn encode_call(&self, function: &str, args: &[DynSolValue]) -> Result<Vec<u8>, Error> {
+        self.function(function)?
+            .abi_encode_input(args)
+            .map_err(|e| Error::Contract(format!("Failed to encode {} arguments: {}", function, e)))
+    }
+
+    /// Encode a function call with JSON arguments (see [`json_to_sol`])
+    pub fn encode_call_json(
+        &self,
+        function: &str,
+        args: &[serde_json::Value],
+    ) -> Result<Vec<u8>, Error> {
+        let values = json_args(&self.function(function)?.inputs, args, function)?;
+        self.encode_call(function, &values)
+    }
+
+    /// ABI-encode constructor arguments (without the contract bytecode)
+    pub fn encode_constructor(&self, args: &[DynSolValue]) -> Result<Vec<u8>, Error> {
+        match &self.abi.constructor {
+            Some(constructor) => constructor.abi_encode_input(args).map_err(|e| {
+                Error::Contract(format!("Failed to encode constructor arguments: {}", e))
+            }),
+            None if args.is_empty() => Ok(Vec::new()),
+            None => Err(Error::Contract(
+                "Contract has no constructor but arguments were given".to_string(),
+            )),
+        }
+    }
+
+    /// ABI-encode constructor arguments given as JSON
+    pub fn encode_constructor_json(&self, args: &[serde_json::Value]) -> Result<Vec<u8>, Error> {
+        let inputs = self
+            .abi
+            .constructor
+            .as_ref()
+            .map(|c| c.inputs.as_slice())
+            .unwrap_or_default();
+        let values = json_args(inputs, args, "constructor")?;
+        self.encode_constructor(&values)
+    }
+
+    /// Decode calldata: the function is looked up by the selector in the first 4 bytes
+    pub fn decode_call(&self, data: &[u8]) -> Result<DecodedCall, Error> {
+        let (selector, _) = data
+            .split_first_chunk::<4>()
+            .ok_or_else(|| Error::Contract("Calldata is shorter than a selector".to_string()))?;
+        let function = self
+            .abi
+            .function_by_selector((*selector).into())
+            .ok_or_else(|| {
+                Error::Contract(format!(
+                    "No function in the ABI matches selector 0x{}",
+                    hex::encode(selector)
+                ))
+            })?;
+
+        let values = function.abi_decode_input(&data[4..]).map_err(|e| {
+            Error::Contract(format!("Failed to decode {} call: {}", function.name, e))
+        })?;
+        Ok(DecodedCall {
+            name: function.name.clone(),
+            signature: function.signature(),
+            params: named(&function.inputs, values),
+        })
+    }
+
+    /// Decode a function's return data
+    pub fn decode_output(&self, function: &str, data: &[u8]) -> Result<Vec<DynSolValue>, Error> {
+        self.function(function)?
+            .abi_decode_output(data)
+            .map_err(|e| Error::Contract(format!("Failed to decode {} output: {}", function, e)))
+    }
+
+    /// Decode a function's return data as JSON
+    ///
+    /// A single return value is returned as is, several as an array.
+    pub fn decode_output_json(
+        &self,
+        function: &str,
+        data: &[u8],
+    ) -> Result<serde_json::Value, Error> {
+        let mut values = self.decode_output(function, data)?;
+        Ok(match values.len() {
+            0 => serde_json::Value::Null,
+            1 => sol_to_json(&values.remove(0)),
+            _ => serde_json::Value::Array(values.iter().map(sol_to_json).collect()),
+        })
+    }
+
+    /// Decode revert data into a readable reason
+    ///
+    /// Understands `Error(string)`, `Panic(uint256)` and the custom errors
+    /// declared in the ABI. Returns `None` for empty or unknown revert data.
+    pub fn decode_revert(&self, data: &[u8]) -> Option<String> {
+        let error = self.decode_error(data)?;
+        match (error.name.as_str(), error.params.as_slice()) {
+            ("Error", [(_, DynSolValue::String(reason))]) => Some(reason.clone()),
+            _ => {
+                let args = error
+                    .params
+                    .iter()
+                    .map(|(_, v)| match v {
+                        DynSolValue::Int(i, _) => i.to_string(),
+                        DynSolValue::Uint(u, _) => u.to_string(),
+                        v => sol_to_json(v).to_string(),
+                    })
+                    .collect::<Vec<_>>();
+                Some(format!("{}({})", error.name, args.join(", ")))
+            }
+        }
+    }
+
+    /// Decode revert data into the error and its arguments
+    ///
+    /// Like [`decode_revert`](Self::decode_revert), `Error(string)` and
+    /// `Panic(uint256)` are recognized without being declared in the ABI.
+    pub fn decode_error(&self, data: &[u8]) -> Option<DecodedCall> {
+        let (selector, payload) = data.split_first_chunk::<4>()?;
+
+        let builtin = |name: &str, param: &str, ty: DynSolType| {
+            let value = ty.abi_decode(payload).ok()?;
+            Some(DecodedCall {
+                name: name.to_string(),
+                signature: format!("{}({})", name, ty),
+                params: vec![(param.to_string(), value)],
+            })
+        };
+        if *selector == ERROR_SELECTOR {
+            return builtin("Error", "reason", DynSolType::String);
+        }
+        if *selector == PANIC_SELECTOR {
+            return builtin("Panic", "code", DynSolType::Uint(256));
+        }
+
+        let error = self
+            .abi
+            .errors()
+            .find(|error| error.selector() == *selector)?;
+        let values = error.abi_decode_input(payload).ok()?;
+        Some(DecodedCall {
+            name: error.name.clone(),
+            signature: error.signature(),
+            params: named(&error.inputs, values),
+        })
+    }
+
+    /// Decode an event log from its topics and data
+    pub fn decode_log(&self, topics: &[[u8; 32]], data: &[u8]) -> Result<DecodedLog, Error> {
+        let signature = topics.first().map(|topic| B256::from(*topic));
+        let event = self
+            .abi
+            .events()
+            .find(|event| !event.anonymous && Some(event.selector()) == signature)
+            .ok_or_else(|| Error::Contract("No event in the ABI matches the log".to_string()))?;
+
+        let decoded = event
+            .decode_log_parts(topics.iter().map(|topic| B256::from(*topic)), data)
+            .map_err(|e| Error::Contract(format!("Failed to decode {} log: {}", event.name, e)))?;
+
+        let mut indexed = decoded.indexed.into_iter();
+        let mut body = decoded.body.into_iter();
+        let params = event
+            .inputs
+            .iter()
+            .filter_map(|input| {
+                let value = if input.indexed {
+                    indexed.next()
+                } else {
+                    body.next()
+                }?;
+                Some((input.name.clone(), value))
+            })
+            .collect();
+
+        Ok(DecodedLog {
+            name: event.name.clone(),
+            params,
+        })
+    }
+}
+
+fn named(inputs: &[Param], values: Vec<DynSolValue>) -> Vec<(String, DynSolValue)> {
+    inputs
+        .iter()
+        .map(|input| input.name.clone())
+        .zip(values)
+        .collect()
+}
+
+fn params_json(params: &[(String, DynSolValue)]) -> serde_json::Value {
+    serde_json::Value::Object(
+        params
+            .iter()
+            .map(|(name, value)| (name.clone(), sol_to_json(value)))
+            .collect(),
+    )
+}
+
+fn json_args(
+    inputs: &[Param],
+    args: &[serde_json::Value],
+    name: &str,
+) -> Result<Vec<DynSolValue>, Error> {
+    if inputs.len() != args.len() {
+        return Err(Error::Contract(format!(
+            "{} expects {} arguments, got {}",
+            name,
+            inputs.len(),
+            args.len()
+        )));
+    }
+
+    inputs
+        .iter()
+        .zip(args)
+        .map(|(input, arg)| {
+            let ty = input.resolve().map_err(|e| {
+                Error::Contract(format!("Invalid type for argument '{}': {}", input.name, e))
+            })?;
+            json_to_sol(arg, &ty).map_err(|e| {
+                Error::Contract(format!(
+                    "Invalid value for argument '{}': {}",
+                    input.name, e
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Convert a JSON value into a [`DynSolValue`] of the given type
+///
+/// Strings are parsed with Solidity literal syntax (addresses, `0x` hex bytes,
+/// decimal or hex integers, optionally with units like `"1 ether"`). Numbers and
+/// booleans map directly, and arrays fill arrays and tuples element-wise.
+pub fn json_to_sol(json: &serde_json::Value, ty: &DynSolType) -> Result<DynSolValue, Error> {
+    let coerce = |s: &str| {
+        ty.coerce_str(s)
+            .map_err(|e| Error::Contract(format!("Cannot parse {:?} as {}: {}", s, ty, e)))
+    };
+
+    match (json, ty) {
+        (serde_json::Value::String(s), _) => coerce(s),
+        (serde_json::Value::Number(n), _) => coerce(&n.to_string()),
+        (serde_json::Value::Bool(b), DynSolType::Bool) => Ok(DynSolValue::Bool(*b)),
+        (serde_json::Value::Array(items), DynSolType::Array(inner)) => items
+            .iter()
+            .map(|item| json_to_sol(item, inner))
+            .collect::<Result<_, _>>()
+            .map(DynSolValue::Array),
+        (serde_json::Value::Array(items), DynSolType::FixedArray(inner, len))
+            if items.len() == *len =>
+        {
+            items
+                .iter()
+                .map(|item| json_to_sol(item, inner))
+                .collect::<Result<_, _>>()
+                .map(DynSolValue::FixedArray)
+        }
+        (serde_json::Value::Array(items), DynSolType::Tuple(types))
+            if items.len() == types.len() =>
+        {
+            items
+                .iter()
+                .zip(types)
+                .map(|(item, ty)| json_to_sol(item, ty))
+                .collect::<Result<_, _>>()
+                .map(DynSolValue::Tuple)
+        }
+        _ => Err(Error::Contract(format!(
+            "Cannot convert {} to {}",
+            json, ty
+        ))),
+    }
+}
+
+/// Render a [`DynSolValue`] as JSON
+///
+/// Addresses are checksummed, bytes are `0x` hex, and integers are decimal
+/// strings so that values wider than 53 bits survive JSON consumers.
+pub fn sol_to_json(value: &DynSolValue) -> serde_json::Value {
+    use serde_json::Value as Json;
+
+    let items = |values: &[DynSolValue]| Json::Array(values.iter().map(sol_to_json).collect());
+
+    match value {
+        DynSolValue::Bool(b) => Json::Bool(*b),
+        DynSolValue::Int(i, _) => Json::String(i.to_string()),
+        DynSolValue::Uint(u, _) => Json::String(u.to_string()),
+        DynSolValue::FixedBytes(word, size) => {
+            Json::String(format!("0x{}", hex::encode(&word[..*size])))
+        }
+        DynSolValue::Address(address) => Json::String(address.to_checksum(None)),
+        DynSolValue::Function(function) => Json::String(function.to_string()),
+        DynSolValue::Bytes(bytes) => Json::String(format!("0x{}", hex::encode(bytes))),
+        DynSolValue::String(s) => Json::String(s.clone()),
+        DynSolValue::Array(values) | DynSolValue::FixedArray(values) => items(values),
+        DynSolValue::Tuple(values) => items(values),
+        #[allow(unreachable_patterns)]
+        _ => value
+            .as_custom_struct()
+            .map(|(_, _, values)| items(values))
+            .unwrap_or(Json::Null),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{Address, U256};
+    use serde_json::json;
+
+    const ABI: &str = r#"[
+        {"type": "constructor", "inputs": [{"name": "supply", "type": "uint256"}], "stateMutability": "nonpayable"},
+        {"type": "function", "name": "transfer", "stateMutability": "nonpayable",
+         "inputs": [{"name": "to", "type": "address"}, {"name": "amount", "type": "uint256"}],
+         "outputs": [{"name": "", "type": "bool"}]},
+        {"type": "function", "name": "balances", "stateMutability": "view",
+         "inputs": [{"name": "owners", "type": "address[]"}],
+         "outputs": [{"name": "", "type": "uint256[]"}]},
+        {"type": "event", "name": "Transfer", "anonymous": false,
+         "inputs": [{"name": "from", "type": "address", "indexed": true},
+                    {"name": "to", "type": "address", "indexed": true},
+                    {"name": "value", "type": "uint256", "indexed": false}]},
+        {"type": "error", "name": "InsufficientBalance",
+         "inputs": [{"name": "needed", "type": "uint256"}]}
+    ]"#;
+
+    const OWNER: &str = "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb7";
+
+    #[test]
+    fn test_encode_call_json() {
+        let abi = ContractAbi::from_json(ABI).unwrap();
+        let data = abi
+            .encode_call_json("transfer", &[json!(OWNER), json!(1000)])
+            .unwrap();
+
+        // transfer(address,uint256)
+        assert_eq!(&data[..4], &[0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(data.len(), 4 + 64);
+        assert_eq!(U256::from_be_slice(&data[36..]), U256::from(1000));
+
+        let data = abi
+            .encode_call_json("balances", &[json!([OWNER, OWNER])])
+            .unwrap();
+        assert_eq!(data.len(), 4 + 32 * 4);
+
+        assert!(abi.encode_call_json("transfer", &[json!(OWNER)]).is_err());
+        assert!(abi.encode_call_json("missing", &[]).is_err());
+    }
+
+    #[test]
+    fn test_from_signature() {
+        let abi = ContractAbi::from_signature("transfer(address,uint256)").unwrap();
+        let data = abi
+            .encode_call_json("transfer", &[json!(OWNER), json!("1 gwei")])
+            .unwrap();
+        assert_eq!(&data[..4], &[0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(
+            U256::from_be_slice(&data[36..]),
+            U256::from(1_000_000_000u64)
+        );
+
+        let abi =
+            ContractAbi::from_signature("function balanceOf(address owner) view returns (uint256)")
+                .unwrap();
+        let output = DynSolValue::Uint(U256::from(5), 256).abi_encode();
+        assert_eq!(
+            abi.decode_output_json("balanceOf", &output).unwrap(),
+            json!("5")
+        );
+
+        assert!(ContractAbi::from_signature("not a signature(").is_err());
+    }
+
+    #[test]
+    fn test_constructor_and_artifact() {
+        let artifact = format!(r#"{{"contractName": "Token", "abi": {}}}"#, ABI);
+        let abi = ContractAbi::from_json(&artifact).unwrap();
+
+        let encoded = abi.encode_constructor_json(&[json!("42")]).unwrap();
+        assert_eq!(encoded, U256::from(42).to_be_bytes::<32>().to_vec());
+    }
+
+    #[test]
+    fn test_decode_output_and_revert() {
+        let abi = ContractAbi::from_json(ABI).unwrap();
+
+        let ok = DynSolValue::Bool(true).abi_encode();
+        assert_eq!(
+            abi.decode_output_json("transfer", &ok).unwrap(),
+            json!(true)
+        );
+
+        let mut revert = ERROR_SELECTOR.to_vec();
+        revert.extend(DynSolValue::String("paused".to_string()).abi_encode());
+        assert_eq!(abi.decode_revert(&revert).as_deref(), Some("paused"));
+
+        let mut custom = abi.abi().errors().next().unwrap().selector().to_vec();
+        custom.extend(DynSolValue::Uint(U256::from(5), 256).abi_encode());
+        assert_eq!(
+            abi.decode_revert(&custom).as_deref(),
+            Some("InsufficientBalance(5)")
+        );
+
+        assert_eq!(abi.decode_revert(&[]), None);
+    }
+
+    #[test]
+    fn test_decode_call_and_error() {
+        let abi = ContractAbi::merge([
+            ContractAbi::from_signature("approve(address,uint256)").unwrap(),
+            ContractAbi::from_json(ABI).unwrap(),
+            ContractAbi::from_json(ABI).unwrap(),
+        ]);
+        assert_eq!(abi.abi().functions().count(), 3);
+
+        let data = abi
+            .encode_call_json("transfer", &[json!(OWNER), json!(1000)])
+            .unwrap();
+        let call = abi.decode_call(&data).unwrap();
+        assert_eq!(call.signature, "transfer(address,uint256)");
+        let to: Address = OWNER.parse().unwrap();
+        assert_eq!(
+            call.to_json(),
+            json!({"to": to.to_checksum(None), "amount": "1000"})
+        );
+
+        assert!(abi.decode_call(&[0xde, 0xad, 0xbe, 0xef]).is_err());
+        assert!(abi.decode_call(&[0xa9]).is_err());
+
+        let mut panic = PANIC_SELECTOR.to_vec();
+        panic.extend(DynSolValue::Uint(U256::from(0x11), 256).abi_encode());
+        let error = abi.decode_error(&panic).unwrap();
+        assert_eq!(error.signature, "Panic(uint256)");
+        assert_eq!(error.to_json(), json!({"code": "17"}));
+        assert_eq!(abi.decode_revert(&panic).as_deref(), Some("Panic(17)"));
+    }
+
+    #[test]
+    fn test_decode_log() {
+        let abi = ContractAbi::from_json(ABI).unwrap();
+        let event = abi.abi().events().next().unwrap();
+        let from: Address = OWNER.parse().unwrap();
+
+        let topics = [
+            event.selector().0,
+            from.into_word().0,
+            Address::ZERO.into_word().0,
+        ];
+        let data = DynSolValue::Uint(U256::from(7), 256).abi_encode();
+
+        let log = abi.decode_log(&topics, &data).unwrap();
+        assert_eq!(log.name, "Transfer");
+        assert_eq!(log.to_json()["from"], json!(from.to_checksum(None)));
+        assert_eq!(log.to_json()["value"], json!("7"));
+    }
+}
@@ -1,5 +1,7 @@
 //! Unified EVM Adapter with Transaction Pipeline
 
+use crate::fee_oracle::FeeOracleConfig;
+use crate::l2_fee::FeeModel;
 use crate::{
     Error, EvmBroadcaster, EvmFeeEstimator, EvmNonceManager, EvmProvider, EvmReceiptWatcher,
     EvmSigner,
@@ -33,7 +35,7 @@ pub struct EvmAdapter {
 impl EvmAdapter {
     /// Create a new EVM adapter
     pub async fn new(rpc_url: &str, chain_name: &str) -> Result<Self, Error> {
-        let provider = EvmProvider::new(rpc_url).await?;
+        let provider = EvmProvider::new(rpc_url).await?.with_chain_name(chain_name);
 
         Ok(Self {
             provider,
@@ -51,17 +53,22 @@ impl EvmAdapter {
     /// Configure the adapter with a signer and create the transaction pipeline
     pub fn with_signer(mut self, signer: EvmSigner) -> Self {
         let provider_clone = self.provider.clone();
+        let sender = signer.alloy_signer().address();
 
         // Set the provider on the signer so it can build proper EVM transactions
         let signer_with_provider = signer.with_provider(provider_clone.provider.clone());
 
         // Create all the pipeline components
-        let fee_estimator = EvmFeeEstimator::new(provider_clone.provider.clone());
+        let fee_estimator = EvmFeeEstimator::new(provider_clone.provider.clone())
+            .with_oracle_config(FeeOracleConfig::for_chain_id(provider_clone.chain_id()))
+            .with_fee_model(FeeModel::for_chain_id(provider_clone.chain_id()))
+            .with_sender(sender);
         let nonce_manager = EvmNonceManager::new(provider_clone.provider.clone());
         let broadcaster = EvmBroadcaster::new(provider_clone.provider.clone());
         let receipt_watcher = EvmReceiptWatcher::new(provider_clone.provider.clone());
 
-        // Create the transaction pipeline
+        // Create the transaction pipeline, sharing the provider's telemetry labels
+        let telemetry = provider_clone.telemetry().clone();
         let pipeline = TransactionPipeline::new(
             provider_clone,
             signer_with_provider,
@@ -69,7 +76,8 @@ impl EvmAdapter {
             nonce_manager,
             broadcaster,
             receipt_watcher,
-        );
+        )
+        .with_telemetry(telemetry);
 
         self.pipeline = Some(pipeline);
         self
@@ -277,13 +285,13 @@ impl EvmAdapter {
 
     /// Get transaction executor for direct transaction operations
     pub fn get_transaction_executor(&self) -> crate::transaction::TransactionExecutor {
-        // Create a basic ProviderType from the provider
-        // Note: This is a simplified approach for test compatibility
-        use alloy::providers::ProviderBuilder;
-        let provider =
-            ProviderBuilder::new().connect_http("http://localhost:8545".parse().unwrap());
-        let provider_type = crate::ProviderType::new(provider);
-        crate::transaction::TransactionExecutor::new(provider_type)
+        let provider_type = crate::ProviderType::new(self.provider.provider.clone());
+        let gas_config = crate::transaction::GasConfig {
+            fee_oracle: FeeOracleConfig::for_chain_id(self.chain_id()),
+            fee_model: FeeModel::for_chain_id(self.chain_id()),
+            ..Default::default()
+        };
+        crate::transaction::TransactionExecutor::new(provider_type).with_gas_config(gas_config)
     }
 }
 
@@ -1,8 +1,13 @@
 //! EVM Fee Estimator implementation
 
+use crate::fee_oracle::{FeeOracle, FeeOracleConfig, FeeSpeed};
+use crate::l2_fee::{FeeModel, L2FeeEstimator};
+use crate::signer::decode_metadata;
 use crate::{AlloyHttpProvider, Error};
-use alloy::primitives::U256;
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{Address as EthAddress, Bytes, U256};
 use alloy::providers::Provider;
+use alloy::rpc::types::TransactionRequest;
 use apex_sdk_core::{FeeEstimator as CoreFeeEstimator, SdkError};
 use async_trait::async_trait;
 
@@ -10,45 +15,104 @@ use async_trait::async_trait;
 #[derive(Debug, Clone)]
 pub struct EvmFeeEstimator {
     provider: AlloyHttpProvider,
+    oracle: FeeOracle,
+    l2: L2FeeEstimator,
+    fee_speed: FeeSpeed,
     gas_multiplier: f64,
+    sender: Option<EthAddress>,
 }
 
 impl EvmFeeEstimator {
     /// Create a new EVM fee estimator
     pub fn new(provider: AlloyHttpProvider) -> Self {
         Self {
+            oracle: FeeOracle::new(provider.clone()),
+            l2: L2FeeEstimator::new(provider.clone(), FeeModel::Standard),
             provider,
+            fee_speed: FeeSpeed::default(),
             gas_multiplier: 1.2, // 20% buffer for gas price fluctuations
+            sender: None,
         }
     }
 
+    /// Set the fee oracle configuration, e.g. [`FeeOracleConfig::for_chain_id`]
+    pub fn with_oracle_config(mut self, config: FeeOracleConfig) -> Self {
+        self.oracle = self.oracle.with_config(config);
+        self
+    }
+
+    /// Set how the chain charges for L1 data, e.g. [`FeeModel::for_chain_id`]
+    pub fn with_fee_model(mut self, model: FeeModel) -> Self {
+        self.l2 = L2FeeEstimator::new(self.provider.clone(), model);
+        self
+    }
+
+    /// Set the fee tier used for estimates
+    pub fn with_fee_speed(mut self, speed: FeeSpeed) -> Self {
+        self.fee_speed = speed;
+        self
+    }
+
     /// Set the gas multiplier for fee estimation
     pub fn with_gas_multiplier(mut self, multiplier: f64) -> Self {
         self.gas_multiplier = multiplier;
         self
     }
 
-    /// Get current gas price with multiplier
+    /// Set the sender passed as `from` to `eth_estimateGas`
+    ///
+    /// Calls that depend on `msg.sender` (token transfers, access-controlled
+    /// functions) revert when estimated from the zero address.
+    pub fn with_sender(mut self, sender: EthAddress) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
+    /// Get the expected gas price with multiplier
+    ///
+    /// Uses the fee oracle's predicted base fee plus the tier's priority fee,
+    /// falling back to `eth_gasPrice` on chains without EIP-1559.
     async fn get_adjusted_gas_price(&self) -> Result<U256, Error> {
-        let base_price = self
-            .provider
-            .get_gas_price()
-            .await
-            .map_err(|e| Error::Connection(format!("Failed to get gas price: {}", e)))?;
+        let base_price = match self.oracle.estimate().await {
+            Ok(fees) => fees.effective_gas_price(self.fee_speed).to::<u128>(),
+            Err(_) => self
+                .provider
+                .get_gas_price()
+                .await
+                .map_err(|e| Error::Connection(format!("Failed to get gas price: {}", e)))?,
+        };
 
         let adjusted_price = ((base_price as f64) * self.gas_multiplier) as u128;
         Ok(U256::from(adjusted_price))
     }
 
-    /// Estimate gas limit for a transaction
-    async fn estimate_gas_limit(&self, tx: &[u8]) -> Result<u64, Error> {
-        // Decode transaction data to determine type and estimate gas accurately
-        if tx.is_empty() {
-            return Ok(21_000); // Standard transfer
+    /// Build an RPC request from pipeline transaction metadata
+    fn request_from_metadata(&self, tx: &[u8]) -> Result<TransactionRequest, Error> {
+        let (_, to, value, data) = decode_metadata(tx)?;
+        let mut request = TransactionRequest::default().with_to(to).with_value(value);
+        if let Some(sender) = self.sender {
+            request = request.with_from(sender);
+        }
+        if let Some(calldata) = data {
+            request = request.with_input(Bytes::from(calldata));
         }
+        Ok(request)
+    }
 
-        // Try to analyze transaction patterns and estimate gas requirements
-        self.estimate_gas_from_patterns(tx).await
+    /// Estimate gas limit for a transaction
+    async fn estimate_gas_limit(
+        &self,
+        tx: &[u8],
+        request: &TransactionRequest,
+    ) -> Result<u64, Error> {
+        // Ask the node first, fall back to byte patterns if it rejects the call
+        match self.provider.estimate_gas(request.clone()).await {
+            Ok(gas) => Ok(gas),
+            Err(e) => {
+                tracing::debug!("eth_estimateGas failed, using heuristics: {}", e);
+                self.estimate_gas_from_patterns(tx).await
+            }
+        }
     }
 
     /// Estimate gas from transaction patterns and byte analysis
@@ -81,10 +145,38 @@ impl EvmFeeEstimator {
 #[async_trait]
 impl CoreFeeEstimator for EvmFeeEstimator {
     async fn estimate_fee(&self, tx: &[u8]) -> Result<u128, SdkError> {
-        let gas_price = self.get_adjusted_gas_price().await?;
-        let gas_limit = self.estimate_gas_limit(tx).await?;
+        // Reject empty or malformed metadata before touching the network
+        let request = self.request_from_metadata(tx)?;
+
+        let mut gas_price = self.get_adjusted_gas_price().await?;
+        let mut gas_limit = U256::from(self.estimate_gas_limit(tx, &request).await?);
+        let mut l1_data_fee = U256::ZERO;
+
+        // Rollups add an L1 data component or fold it into their own limits
+        let model = self.l2.model();
+        if model != FeeModel::Standard {
+            let request = request.with_gas_limit(gas_limit.to::<u64>());
+            match self.l2.estimate(&request).await {
+                Ok(l2_fees) => {
+                    if let Some(limit) = l2_fees.gas_limit {
+                        gas_limit = limit;
+                    }
+                    if let Some(max_fee) = l2_fees.max_fee_per_gas {
+                        gas_price = max_fee;
+                    }
+                    if model.charges_l1_fee_separately() {
+                        l1_data_fee = l2_fees.l1_data_fee.unwrap_or_default();
+                    }
+                }
+                Err(e) => tracing::warn!(
+                    "{:?} fee estimation failed, using the plain L2 estimate: {}",
+                    model,
+                    e
+                ),
+            }
+        }
 
-        let total_fee = gas_price * U256::from(gas_limit);
+        let total_fee = gas_price * gas_limit + l1_data_fee;
         Ok(total_fee.to::<u128>())
     }
 }
@@ -100,8 +192,11 @@ mod tests {
             let provider = EvmProvider::new("http://localhost:8545").await.unwrap();
             let estimator = EvmFeeEstimator::new(provider.provider);
 
-            let tx = b"mock transaction data";
-            let fee = estimator.estimate_fee(tx).await;
+            // Native transfer metadata: [0x00, to (20 bytes), value (32 bytes)]
+            let mut tx = vec![0x00];
+            tx.extend_from_slice(&[0x11; 20]);
+            tx.extend_from_slice(&[0u8; 32]);
+            let fee = estimator.estimate_fee(&tx).await;
             assert!(fee.is_ok());
             assert!(fee.unwrap() > 0);
         }
@@ -0,0 +1,405 @@
+//! EIP-1559 fee oracle based on `eth_feeHistory`
+//!
+//! This module provides:
+//! - Slow, standard and fast priority fee tiers from historical reward percentiles
+//! - Next block base fee prediction
+//! - Per-chain overrides such as priority fee floors
+
+use crate::{AlloyHttpProvider, Error};
+use alloy::primitives::U256;
+use alloy::providers::Provider;
+use alloy::rpc::types::{BlockNumberOrTag, FeeHistory};
+
+/// One gwei in wei
+const GWEI: u128 = 1_000_000_000;
+
+/// EIP-1559 base fee max change denominator
+const BASE_FEE_MAX_CHANGE_DENOMINATOR: u128 = 8;
+
+/// EIP-1559 elasticity multiplier
+const ELASTICITY_MULTIPLIER: u64 = 2;
+
+/// Polygon PoS mainnet chain ID
+const POLYGON_CHAIN_ID: u64 = 137;
+
+/// Polygon Amoy testnet chain ID
+const POLYGON_AMOY_CHAIN_ID: u64 = 80_002;
+
+/// How quickly a transaction should be included
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FeeSpeed {
+    /// Low tip, may wait several blocks
+    Slow,
+    /// Median tip, usually included within a few blocks
+    #[default]
+    Standard,
+    /// High tip, aims for the next block
+    Fast,
+}
+
+impl FeeSpeed {
+    /// All speeds, from slowest to fastest
+    pub const ALL: [FeeSpeed; 3] = [FeeSpeed::Slow, FeeSpeed::Standard, FeeSpeed::Fast];
+
+    fn index(&self) -> usize {
+        match self {
+            FeeSpeed::Slow => 0,
+            FeeSpeed::Standard => 1,
+            FeeSpeed::Fast => 2,
+        }
+    }
+}
+
+/// Configuration for the fee oracle
+#[derive(Debug, Clone)]
+pub struct FeeOracleConfig {
+    /// Number of past blocks to sample
+    pub block_count: u64,
+    /// Reward percentiles for the slow, standard and fast tiers
+    pub reward_percentiles: [f64; 3],
+    /// Multiplier applied to the predicted base fee to cap `maxFeePerGas`
+    pub base_fee_multiplier: f64,
+    /// Lowest priority fee the chain accepts, in wei
+    pub min_priority_fee: U256,
+    /// Priority fee used when the sampled blocks carry no rewards, in wei
+    pub fallback_priority_fee: U256,
+}
+
+impl Default for FeeOracleConfig {
+    fn default() -> Self {
+        Self {
+            block_count: 20,
+            reward_percentiles: [10.0, 50.0, 90.0],
+            base_fee_multiplier: 2.0,
+            min_priority_fee: U256::ZERO,
+            fallback_priority_fee: U256::from(GWEI), // 1 gwei
+        }
+    }
+}
+
+impl FeeOracleConfig {
+    /// Create the default configuration for a chain, applying known overrides
+    ///
+    /// Polygon rejects transactions whose priority fee is below its network
+    /// minimum, so those chains get a priority fee floor.
+    pub fn for_chain_id(chain_id: u64) -> Self {
+        let config = Self::default();
+        match chain_id {
+            POLYGON_CHAIN_ID => config.with_min_priority_fee(U256::from(30 * GWEI)),
+            POLYGON_AMOY_CHAIN_ID => config.with_min_priority_fee(U256::from(25 * GWEI)),
+            _ => config,
+        }
+    }
+
+    /// Set the number of past blocks to sample
+    pub fn with_block_count(mut self, block_count: u64) -> Self {
+        self.block_count = block_count;
+        self
+    }
+
+    /// Set the reward percentiles for the slow, standard and fast tiers
+    pub fn with_reward_percentiles(mut self, percentiles: [f64; 3]) -> Self {
+        self.reward_percentiles = percentiles;
+        self
+    }
+
+    /// Set the base fee multiplier used for `maxFeePerGas`
+    pub fn with_base_fee_multiplier(mut self, multiplier: f64) -> Self {
+        self.base_fee_multiplier = multiplier;
+        self
+    }
+
+    /// Set the priority fee floor in wei
+    pub fn with_min_priority_fee(mut self, min_priority_fee: U256) -> Self {
+        self.min_priority_fee = min_priority_fee;
+        self
+    }
+}
+
+/// EIP-1559 fee parameters for one speed tier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeTier {
+    /// Max fee per gas in wei
+    pub max_fee_per_gas: U256,
+    /// Max priority fee per gas in wei
+    pub max_priority_fee_per_gas: U256,
+}
+
+/// Fee estimates for the next block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeEstimates {
+    /// Base fee of the latest block in wei
+    pub base_fee_per_gas: U256,
+    /// Predicted base fee of the next block in wei
+    pub next_base_fee_per_gas: U256,
+    /// Slow tier
+    pub slow: FeeTier,
+    /// Standard tier
+    pub standard: FeeTier,
+    /// Fast tier
+    pub fast: FeeTier,
+}
+
+impl FeeEstimates {
+    /// Get the tier for a speed
+    pub fn tier(&self, speed: FeeSpeed) -> FeeTier {
+        match speed {
+            FeeSpeed::Slow => self.slow,
+            FeeSpeed::Standard => self.standard,
+            FeeSpeed::Fast => self.fast,
+        }
+    }
+
+    /// Expected price per gas for a tier, as opposed to the `maxFeePerGas` cap
+    pub fn effective_gas_price(&self, speed: FeeSpeed) -> U256 {
+        self.next_base_fee_per_gas + self.tier(speed).max_priority_fee_per_gas
+    }
+
+    /// Derive estimates from an `eth_feeHistory` response
+    ///
+    /// Each tier's tip is the median of its reward percentile across the
+    /// sampled blocks, skipping empty blocks. Returns an error if the chain
+    /// reports no base fee, i.e. does not support EIP-1559.
+    pub fn from_fee_history(history: &FeeHistory, config: &FeeOracleConfig) -> Result<Self, Error> {
+        let base_fee = history
+            .latest_block_base_fee()
+            .or_else(|| history.base_fee_per_gas.last().copied())
+            .unwrap_or(0);
+        let next_base_fee = if history.base_fee_per_gas.len() > history.gas_used_ratio.len() {
+            history.next_block_base_fee().unwrap_or(0)
+        } else {
+            // Some clients omit the next block's entry, derive it from the latest block
+            let ratio = history.gas_used_ratio.last().copied().unwrap_or(0.5);
+            predict_next_base_fee(base_fee, (ratio * 1_000_000.0) as u64, 1_000_000)
+        };
+
+        if base_fee == 0 && next_base_fee == 0 {
+            return Err(Error::Other("EIP-1559 not supported".to_string()));
+        }
+
+        let rewards: Vec<&Vec<u128>> = history
+            .reward
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .zip(history.gas_used_ratio.iter())
+            .filter(|(_, ratio)| **ratio > 0.0)
+            .map(|(reward, _)| reward)
+            .collect();
+
+        let next_base_fee = U256::from(next_base_fee);
+        let max_base_fee =
+            U256::from((next_base_fee.to::<u128>() as f64 * config.base_fee_multiplier) as u128);
+        let tier = |speed: FeeSpeed| {
+            let mut samples: Vec<u128> = rewards
+                .iter()
+                .filter_map(|reward| reward.get(speed.index()).copied())
+                .collect();
+            let tip = median(&mut samples)
+                .map(U256::from)
+                .unwrap_or(config.fallback_priority_fee)
+                .max(config.min_priority_fee);
+            FeeTier {
+                max_fee_per_gas: max_base_fee + tip,
+                max_priority_fee_per_gas: tip,
+            }
+        };
+
+        Ok(Self {
+            base_fee_per_gas: U256::from(base_fee),
+            next_base_fee_per_gas: next_base_fee,
+            slow: tier(FeeSpeed::Slow),
+            standard: tier(FeeSpeed::Standard),
+            fast: tier(FeeSpeed::Fast),
+        })
+    }
+}
+
+/// Predict the next block's base fee from the parent block (EIP-1559)
+pub fn predict_next_base_fee(base_fee: u128, gas_used: u64, gas_limit: u64) -> u128 {
+    let gas_target = (gas_limit / ELASTICITY_MULTIPLIER) as u128;
+    let gas_used = gas_used as u128;
+
+    if gas_target == 0 || gas_used == gas_target {
+        return base_fee;
+    }
+
+    if gas_used > gas_target {
+        let delta =
+            base_fee * (gas_used - gas_target) / gas_target / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+        base_fee + delta.max(1)
+    } else {
+        let delta =
+            base_fee * (gas_target - gas_used) / gas_target / BASE_FEE_MAX_CHANGE_DENOMINATOR;
+        base_fee.saturating_sub(delta)
+    }
+}
+
+fn median(samples: &mut [u128]) -> Option<u128> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+    let mid = samples.len() / 2;
+    if samples.len() % 2 == 0 {
+        // Average without overflowing on values close to u128::MAX
+        let (a, b) = (samples[mid - 1], samples[mid]);
+        Some(a / 2 + b / 2 + (a % 2 + b % 2) / 2)
+    } else {
+        Some(samples[mid])
+    }
+}
+
+/// Fee oracle querying `eth_feeHistory`
+#[derive(Debug, Clone)]
+pub struct FeeOracle {
+    provider: AlloyHttpProvider,
+    config: FeeOracleConfig,
+}
+
+impl FeeOracle {
+    /// Create a new fee oracle with the default configuration
+    pub fn new(provider: AlloyHttpProvider) -> Self {
+        Self {
+            provider,
+            config: FeeOracleConfig::default(),
+        }
+    }
+
+    /// Set the oracle configuration
+    pub fn with_config(mut self, config: FeeOracleConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Get the oracle configuration
+    pub fn config(&self) -> &FeeOracleConfig {
+        &self.config
+    }
+
+    /// Estimate fees for the next block
+    pub async fn estimate(&self) -> Result<FeeEstimates, Error> {
+        let history = self
+            .provider
+            .get_fee_history(
+                self.config.block_count,
+                BlockNumberOrTag::Latest,
+                &self.config.reward_percentiles,
+            )
+            .await
+            .map_err(|e| Error::Connection(format!("Failed to get fee history: {}", e)))?;
+
+        let estimates = FeeEstimates::from_fee_history(&history, &self.config)?;
+        tracing::debug!(
+            "Fee oracle: next base fee={} wei, tips slow={} standard={} fast={} wei",
+            estimates.next_base_fee_per_gas,
+            estimates.slow.max_priority_fee_per_gas,
+            estimates.standard.max_priority_fee_per_gas,
+            estimates.fast.max_priority_fee_per_gas
+        );
+        Ok(estimates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(base_fees: Vec<u128>, ratios: Vec<f64>, rewards: Vec<Vec<u128>>) -> FeeHistory {
+        FeeHistory {
+            base_fee_per_gas: base_fees,
+            gas_used_ratio: ratios,
+            reward: Some(rewards),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_median_does_not_overflow() {
+        assert_eq!(median(&mut []), None);
+        assert_eq!(median(&mut [3, 1, 2]), Some(2));
+        assert_eq!(median(&mut [1, 4]), Some(2));
+        assert_eq!(median(&mut [3, 5]), Some(4));
+        assert_eq!(median(&mut [u128::MAX, u128::MAX]), Some(u128::MAX));
+        assert_eq!(median(&mut [u128::MAX - 1, u128::MAX]), Some(u128::MAX - 1));
+    }
+
+    #[test]
+    fn test_predict_next_base_fee() {
+        let base = 100 * GWEI;
+        // At target the base fee is unchanged
+        assert_eq!(predict_next_base_fee(base, 15_000_000, 30_000_000), base);
+        // Full block raises it by 12.5%
+        assert_eq!(
+            predict_next_base_fee(base, 30_000_000, 30_000_000),
+            112_500_000_000
+        );
+        // Empty block lowers it by 12.5%
+        assert_eq!(predict_next_base_fee(base, 0, 30_000_000), 87_500_000_000);
+    }
+
+    #[test]
+    fn test_tiers_from_fee_history() {
+        let h = history(
+            vec![10 * GWEI, 11 * GWEI, 12 * GWEI, 13 * GWEI],
+            vec![0.9, 0.0, 0.8],
+            vec![
+                vec![GWEI, 2 * GWEI, 5 * GWEI],
+                // Empty block, ignored
+                vec![0, 0, 0],
+                vec![GWEI, 3 * GWEI, 7 * GWEI],
+            ],
+        );
+        let est = FeeEstimates::from_fee_history(&h, &FeeOracleConfig::default()).unwrap();
+
+        assert_eq!(est.base_fee_per_gas, U256::from(12 * GWEI));
+        assert_eq!(est.next_base_fee_per_gas, U256::from(13 * GWEI));
+        assert_eq!(est.slow.max_priority_fee_per_gas, U256::from(GWEI));
+        assert_eq!(
+            est.standard.max_priority_fee_per_gas,
+            U256::from(2_500_000_000u64)
+        );
+        assert_eq!(est.fast.max_priority_fee_per_gas, U256::from(6 * GWEI));
+        assert_eq!(est.fast.max_fee_per_gas, U256::from(26 * GWEI + 6 * GWEI));
+        assert_eq!(
+            est.effective_gas_price(FeeSpeed::Standard),
+            U256::from(15_500_000_000u64)
+        );
+    }
+
+    #[test]
+    fn test_priority_fee_floor_for_polygon() {
+        let h = history(
+            vec![GWEI, GWEI],
+            vec![0.5],
+            vec![vec![GWEI, GWEI, 40 * GWEI]],
+        );
+        let config = FeeOracleConfig::for_chain_id(POLYGON_CHAIN_ID);
+        let est = FeeEstimates::from_fee_history(&h, &config).unwrap();
+
+        assert_eq!(est.slow.max_priority_fee_per_gas, U256::from(30 * GWEI));
+        assert_eq!(est.standard.max_priority_fee_per_gas, U256::from(30 * GWEI));
+        assert_eq!(est.fast.max_priority_fee_per_gas, U256::from(40 * GWEI));
+    }
+
+    #[test]
+    fn test_fallback_without_rewards() {
+        let h = history(vec![GWEI, GWEI], vec![0.0], vec![vec![0, 0, 0]]);
+        let est = FeeEstimates::from_fee_history(&h, &FeeOracleConfig::default()).unwrap();
+        assert_eq!(est.standard.max_priority_fee_per_gas, U256::from(GWEI));
+    }
+
+    #[test]
+    fn test_legacy_chain_is_rejected() {
+        let h = history(vec![0, 0], vec![0.5], vec![vec![0, 0, 0]]);
+        assert!(FeeEstimates::from_fee_history(&h, &FeeOracleConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_next_base_fee_derived_when_missing() {
+        // Only one entry per block, as returned by some clients
+        let h = history(vec![100 * GWEI], vec![1.0], vec![vec![GWEI, GWEI, GWEI]]);
+        let est = FeeEstimates::from_fee_history(&h, &FeeOracleConfig::default()).unwrap();
+        assert_eq!(est.next_base_fee_per_gas, U256::from(112_500_000_000u64));
+    }
+}
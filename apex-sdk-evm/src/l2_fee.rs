@@ -0,0 +1,282 @@
+//! L2 fee estimation including L1 data costs
+//!
+//! Rollups charge for posting transaction data to Ethereum on top of L2
+//! execution gas. This module provides:
+//! - OP-stack (Optimism, Base) L1 fees via the `GasPriceOracle` predeploy
+//! - Arbitrum L1 gas via `NodeInterface.gasEstimateComponents`
+//! - zkSync Era limits and prices via `zks_estimateFee`
+
+use crate::{AlloyHttpProvider, Error};
+use alloy::consensus::SignableTransaction;
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{address, Address as EthAddress, Bytes, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::TransactionRequest;
+use alloy::sol;
+use alloy::sol_types::SolCall;
+use apex_sdk_types::Chain;
+use serde::Deserialize;
+
+/// OP-stack `GasPriceOracle` predeploy
+pub const OP_GAS_PRICE_ORACLE: EthAddress = address!("420000000000000000000000000000000000000F");
+
+/// Arbitrum `NodeInterface` precompile
+pub const ARB_NODE_INTERFACE: EthAddress = address!("00000000000000000000000000000000000000C8");
+
+sol! {
+    interface IGasPriceOracle {
+        function getL1Fee(bytes memory _data) external view returns (uint256);
+    }
+
+    interface INodeInterface {
+        function gasEstimateComponents(address to, bool contractCreation, bytes calldata data)
+            external
+            payable
+            returns (uint64 gasEstimate, uint64 gasEstimateForL1, uint256 baseFee, uint256 l1BaseFeeEstimate);
+    }
+}
+
+/// How a chain charges for transaction data posted to L1
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FeeModel {
+    /// L1 chain or sidechain, execution gas only
+    #[default]
+    Standard,
+    /// OP-stack rollup, L1 data fee charged on top of execution gas
+    OpStack,
+    /// Arbitrum Nitro, L1 data cost folded into the gas limit
+    Arbitrum,
+    /// zkSync Era, pubdata cost folded into the gas limit
+    ZkSync,
+}
+
+impl FeeModel {
+    /// Fee model for a known chain ID, including public testnets
+    pub fn for_chain_id(chain_id: u64) -> Self {
+        match chain_id {
+            // Optimism, Base, OP Sepolia, Base Sepolia
+            10 | 8453 | 11_155_420 | 84_532 => FeeModel::OpStack,
+            // Arbitrum One, Arbitrum Nova, Arbitrum Sepolia
+            42_161 | 42_170 | 421_614 => FeeModel::Arbitrum,
+            // zkSync Era, zkSync Sepolia
+            324 | 300 => FeeModel::ZkSync,
+            _ => FeeModel::Standard,
+        }
+    }
+
+    /// Fee model for a chain
+    pub fn for_chain(chain: &Chain) -> Self {
+        match chain {
+            Chain::Optimism | Chain::Base => FeeModel::OpStack,
+            Chain::Arbitrum => FeeModel::Arbitrum,
+            Chain::ZkSync => FeeModel::ZkSync,
+            _ => FeeModel::Standard,
+        }
+    }
+
+    /// Whether the L1 data fee is paid in addition to `gas_limit * gas_price`
+    pub fn charges_l1_fee_separately(&self) -> bool {
+        matches!(self, FeeModel::OpStack)
+    }
+}
+
+/// Chain-specific adjustments to an execution gas estimate
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct L2Fees {
+    /// L1 data component in wei, when the chain reports it
+    pub l1_data_fee: Option<U256>,
+    /// Part of `gas_limit` paying for L1 data, on chains that fold it in
+    pub l1_gas: Option<U256>,
+    /// Maximum gas paid per byte of pubdata (zkSync Era)
+    pub gas_per_pubdata_limit: Option<U256>,
+    /// Gas limit replacing the `eth_estimateGas` result
+    pub gas_limit: Option<U256>,
+    /// Max fee per gas replacing the fee oracle result
+    pub max_fee_per_gas: Option<U256>,
+    /// Max priority fee per gas replacing the fee oracle result
+    pub max_priority_fee_per_gas: Option<U256>,
+}
+
+/// `zks_estimateFee` response
+#[derive(Debug, Deserialize)]
+struct ZksFee {
+    gas_limit: U256,
+    gas_per_pubdata_limit: U256,
+    max_fee_per_gas: U256,
+    max_priority_fee_per_gas: U256,
+}
+
+/// Estimator for rollup-specific fee components
+#[derive(Debug, Clone)]
+pub struct L2FeeEstimator {
+    provider: AlloyHttpProvider,
+    model: FeeModel,
+}
+
+impl L2FeeEstimator {
+    /// Create a new estimator for a fee model
+    pub fn new(provider: AlloyHttpProvider, model: FeeModel) -> Self {
+        Self { provider, model }
+    }
+
+    /// Get the fee model
+    pub fn model(&self) -> FeeModel {
+        self.model
+    }
+
+    /// Estimate the rollup-specific components for a transaction
+    ///
+    /// Returns an empty [`L2Fees`] for [`FeeModel::Standard`].
+    pub async fn estimate(&self, tx: &TransactionRequest) -> Result<L2Fees, Error> {
+        match self.model {
+            FeeModel::Standard => Ok(L2Fees::default()),
+            FeeModel::OpStack => self.estimate_op_stack(tx).await,
+            FeeModel::Arbitrum => self.estimate_arbitrum(tx).await,
+            FeeModel::ZkSync => self.estimate_zksync(tx).await,
+        }
+    }
+
+    async fn estimate_op_stack(&self, tx: &TransactionRequest) -> Result<L2Fees, Error> {
+        let call = IGasPriceOracle::getL1FeeCall {
+            _data: Bytes::from(unsigned_tx_bytes(tx)),
+        };
+        let request = TransactionRequest::default()
+            .with_to(OP_GAS_PRICE_ORACLE)
+            .with_input(call.abi_encode());
+
+        let output = self
+            .provider
+            .call(request)
+            .await
+            .map_err(|e| Error::Connection(format!("GasPriceOracle.getL1Fee failed: {}", e)))?;
+        let l1_fee = IGasPriceOracle::getL1FeeCall::abi_decode_returns(&output)
+            .map_err(|e| Error::Other(format!("Invalid getL1Fee response: {}", e)))?;
+
+        Ok(L2Fees {
+            l1_data_fee: Some(l1_fee),
+            ..Default::default()
+        })
+    }
+
+    async fn estimate_arbitrum(&self, tx: &TransactionRequest) -> Result<L2Fees, Error> {
+        let to = tx.to.and_then(|kind| kind.to().copied());
+        let call = INodeInterface::gasEstimateComponentsCall {
+            to: to.unwrap_or_default(),
+            contractCreation: to.is_none(),
+            data: tx.input.input().cloned().unwrap_or_default(),
+        };
+        let mut request = TransactionRequest::default()
+            .with_to(ARB_NODE_INTERFACE)
+            .with_input(call.abi_encode());
+        if let Some(from) = tx.from {
+            request = request.with_from(from);
+        }
+        if let Some(value) = tx.value {
+            request = request.with_value(value);
+        }
+
+        let output = self.provider.call(request).await.map_err(|e| {
+            Error::Connection(format!("NodeInterface.gasEstimateComponents failed: {}", e))
+        })?;
+        let components = INodeInterface::gasEstimateComponentsCall::abi_decode_returns(&output)
+            .map_err(|e| Error::Other(format!("Invalid gasEstimateComponents response: {}", e)))?;
+
+        let l1_gas = U256::from(components.gasEstimateForL1);
+        Ok(L2Fees {
+            l1_data_fee: Some(l1_gas * components.baseFee),
+            l1_gas: Some(l1_gas),
+            gas_limit: Some(U256::from(components.gasEstimate)),
+            ..Default::default()
+        })
+    }
+
+    async fn estimate_zksync(&self, tx: &TransactionRequest) -> Result<L2Fees, Error> {
+        let fee: ZksFee = self
+            .provider
+            .raw_request("zks_estimateFee".into(), (tx.clone(),))
+            .await
+            .map_err(|e| Error::Connection(format!("zks_estimateFee failed: {}", e)))?;
+
+        // Pubdata is paid through the gas limit. `zks_estimateFee` reports the
+        // price ceiling per pubdata byte but not how many bytes the transaction
+        // publishes (that depends on the state diff at execution), so the L1
+        // part cannot be separated from the limit and is left unreported.
+        Ok(L2Fees {
+            l1_data_fee: None,
+            l1_gas: None,
+            gas_per_pubdata_limit: Some(fee.gas_per_pubdata_limit),
+            gas_limit: Some(fee.gas_limit),
+            max_fee_per_gas: Some(fee.max_fee_per_gas),
+            max_priority_fee_per_gas: Some(fee.max_priority_fee_per_gas),
+        })
+    }
+}
+
+/// Unsigned RLP encoding of a transaction, as expected by `getL1Fee`
+///
+/// Fields that are not known yet are filled with placeholders; the L1 fee
+/// only depends on the encoded size and zero-byte count.
+pub fn unsigned_tx_bytes(tx: &TransactionRequest) -> Vec<u8> {
+    let mut tx = tx.clone();
+    tx.nonce.get_or_insert(0);
+    tx.gas.get_or_insert(21_000);
+    tx.chain_id.get_or_insert(1);
+    if tx.gas_price.is_none() {
+        tx.max_fee_per_gas.get_or_insert(1);
+        tx.max_priority_fee_per_gas.get_or_insert(1);
+    }
+    if tx.to.is_none() {
+        // Contract creation
+        tx.to = Some(alloy::primitives::TxKind::Create);
+    }
+
+    match tx.build_typed_tx() {
+        Ok(typed) => typed.encoded_for_signing(),
+        Err(tx) => tx.input.input().map(|b| b.to_vec()).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_model_for_chain() {
+        assert_eq!(FeeModel::for_chain(&Chain::Optimism), FeeModel::OpStack);
+        assert_eq!(FeeModel::for_chain(&Chain::Base), FeeModel::OpStack);
+        assert_eq!(FeeModel::for_chain(&Chain::Arbitrum), FeeModel::Arbitrum);
+        assert_eq!(FeeModel::for_chain(&Chain::ZkSync), FeeModel::ZkSync);
+        assert_eq!(FeeModel::for_chain(&Chain::Ethereum), FeeModel::Standard);
+
+        // Every layer 2 chain has a dedicated model
+        for chain in [Chain::Arbitrum, Chain::Optimism, Chain::ZkSync, Chain::Base] {
+            assert!(chain.is_layer2());
+            let id = chain.chain_id().unwrap();
+            assert_eq!(FeeModel::for_chain_id(id), FeeModel::for_chain(&chain));
+        }
+    }
+
+    #[test]
+    fn test_only_op_stack_charges_l1_separately() {
+        assert!(FeeModel::OpStack.charges_l1_fee_separately());
+        assert!(!FeeModel::Arbitrum.charges_l1_fee_separately());
+        assert!(!FeeModel::ZkSync.charges_l1_fee_separately());
+        assert!(!FeeModel::Standard.charges_l1_fee_separately());
+    }
+
+    #[test]
+    fn test_unsigned_tx_bytes_grows_with_calldata() {
+        let to = address!("742d35Cc6634C0532925a3b844Bc9e7595f0bEbD");
+        let transfer = TransactionRequest::default()
+            .with_to(to)
+            .with_value(U256::from(1));
+        let call = transfer.clone().with_input(vec![0xab; 100]);
+
+        let transfer_bytes = unsigned_tx_bytes(&transfer);
+        let call_bytes = unsigned_tx_bytes(&call);
+
+        // EIP-1559 envelope
+        assert_eq!(transfer_bytes[0], 0x02);
+        assert!(call_bytes.len() >= transfer_bytes.len() + 100);
+    }
+}
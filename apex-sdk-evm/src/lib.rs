@@ -16,6 +16,7 @@
 //! - **HTTP and WebSocket Support**: Flexible connection types
 //! - **Transaction Management**: Send, track, and query transactions
 //! - **Smart Contract Interaction**: Call and deploy contracts
+//! - **JSON ABI Encoding**: Encode calls and decode results, reverts and logs from a contract ABI
 //! - **Wallet Integration**: Built-in wallet and signing support
 //! - **Connection Pooling**: Efficient resource management
 //! - **Metrics Collection**: Performance monitoring
@@ -38,11 +39,14 @@
 //! }
 //! ```
 
+pub mod abi;
 pub mod adapter;
 pub mod broadcaster;
 pub mod cache;
 pub mod contract;
 pub mod fee_estimator;
+pub mod fee_oracle;
+pub mod l2_fee;
 pub mod metrics;
 pub mod nonce_manager;
 pub mod pool;
@@ -56,6 +60,8 @@ pub mod wallet;
 pub use adapter::EvmAdapter;
 pub use broadcaster::EvmBroadcaster;
 pub use fee_estimator::EvmFeeEstimator;
+pub use fee_oracle::{FeeEstimates, FeeOracle, FeeOracleConfig, FeeSpeed};
+pub use l2_fee::{FeeModel, L2FeeEstimator, L2Fees};
 pub use nonce_manager::EvmNonceManager;
 pub use provider::EvmProvider;
 pub use receipt_watcher::EvmReceiptWatcher;
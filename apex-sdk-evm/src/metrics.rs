@@ -4,7 +4,11 @@
 //! - Metrics tracking for RPC calls, transaction submissions, and errors
 //! - A snapshot mechanism for retrieving current metrics
 //! - Atomic counters for thread-safe metric updates
+//! - Forwarding of RPC spans and latencies to the core [`Telemetry`] layer
 
+use apex_sdk_core::Telemetry;
+use std::fmt::Display;
+use std::future::IntoFuture;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -35,6 +39,7 @@ pub struct Metrics {
     transactions_failed: Arc<AtomicU64>,
     connection_errors: Arc<AtomicU64>,
     total_rpc_response_time_ms: Arc<AtomicU64>,
+    telemetry: Telemetry,
 }
 
 impl Metrics {
@@ -43,6 +48,17 @@ impl Metrics {
         Self::default()
     }
 
+    /// Attach a telemetry handle so RPC latencies carry chain and endpoint labels
+    pub fn with_telemetry(mut self, telemetry: Telemetry) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Get the telemetry handle
+    pub fn telemetry(&self) -> &Telemetry {
+        &self.telemetry
+    }
+
     /// Record an RPC call
     pub fn record_rpc_call(&self, duration: Duration) {
         self.rpc_calls.fetch_add(1, Ordering::Relaxed);
@@ -50,6 +66,18 @@ impl Metrics {
             .fetch_add(duration.as_millis() as u64, Ordering::Relaxed);
     }
 
+    /// Run an RPC call inside a span, recording it here and in the telemetry layer
+    pub async fn instrument<F, T, E>(&self, method: &str, fut: F) -> Result<T, E>
+    where
+        F: IntoFuture<Output = Result<T, E>>,
+        E: Display,
+    {
+        let start = Instant::now();
+        let result = self.telemetry.instrument_rpc(method, fut).await;
+        self.record_rpc_call(start.elapsed());
+        result
+    }
+
     /// Record a transaction submission attempt
     pub fn record_transaction_attempt(&self) {
         self.transactions_submitted.fetch_add(1, Ordering::Relaxed);
@@ -75,11 +103,7 @@ impl Metrics {
         let rpc_calls = self.rpc_calls.load(Ordering::Relaxed);
         let total_response_time = self.total_rpc_response_time_ms.load(Ordering::Relaxed);
 
-        let avg_rpc_response_time_ms = if rpc_calls > 0 {
-            total_response_time / rpc_calls
-        } else {
-            0
-        };
+        let avg_rpc_response_time_ms = total_response_time.checked_div(rpc_calls).unwrap_or(0);
 
         MetricsSnapshot {
             rpc_calls,
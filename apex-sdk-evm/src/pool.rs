@@ -2,43 +2,24 @@
 //!
 //! This module provides:
 //! - Connection pooling with round-robin load balancing
-//! - Health checks for endpoints
+//! - Health checks for endpoints, including head lag and chain ID verification
 //! - Automatic failover to backup endpoints
 //! - Connection reuse
 
 use crate::{Error, EvmAdapter};
-use apex_sdk_core::Provider;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use alloy::providers::Provider as AlloyProvider;
+use alloy::rpc::types::BlockNumberOrTag;
+use apex_sdk_core::health::{HealthCheck, PoolHealth, Probe};
+use apex_sdk_core::ChainAdapter;
+use async_trait::async_trait;
+use futures::future::join_all;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
-/// Health status of an endpoint
-#[derive(Debug, Clone)]
-pub struct EndpointHealth {
-    /// Whether the endpoint is currently healthy
-    pub is_healthy: bool,
-    /// Last successful connection timestamp
-    pub last_success: Option<Instant>,
-    /// Last failed connection timestamp
-    pub last_failure: Option<Instant>,
-    /// Consecutive failure count
-    pub failure_count: u32,
-    /// Average response time in milliseconds
-    pub avg_response_time_ms: u64,
-}
-
-impl Default for EndpointHealth {
-    fn default() -> Self {
-        Self {
-            is_healthy: true,
-            last_success: None,
-            last_failure: None,
-            failure_count: 0,
-            avg_response_time_ms: 0,
-        }
-    }
-}
+pub use apex_sdk_core::health::{EndpointHealth, HeadInfo, HealthStatus};
 
 /// Pooled connection to an EVM endpoint
 pub struct PooledConnection {
@@ -65,30 +46,12 @@ impl PooledConnection {
 
     /// Mark connection as healthy after successful operation
     pub async fn mark_healthy(&self, response_time_ms: u64) {
-        let mut health = self.health.write().await;
-        health.is_healthy = true;
-        health.last_success = Some(Instant::now());
-        health.failure_count = 0;
-
-        // Update average response time (exponential moving average)
-        if health.avg_response_time_ms == 0 {
-            health.avg_response_time_ms = response_time_ms;
-        } else {
-            health.avg_response_time_ms = (health.avg_response_time_ms * 9 + response_time_ms) / 10;
-        }
+        self.health.write().await.record_success(response_time_ms);
     }
 
     /// Mark connection as unhealthy after failure
     pub async fn mark_unhealthy(&self) {
-        let mut health = self.health.write().await;
-        health.last_failure = Some(Instant::now());
-        health.failure_count += 1;
-
-        // Mark as unhealthy after 3 consecutive failures
-        if health.failure_count >= 3 {
-            health.is_healthy = false;
-            tracing::warn!("Endpoint {} marked as unhealthy", self.endpoint);
-        }
+        self.health.write().await.record_failure(&self.endpoint);
     }
 }
 
@@ -105,6 +68,11 @@ pub struct PoolConfig {
     pub max_failures: u32,
     /// Time to wait before retrying unhealthy endpoint (seconds)
     pub unhealthy_retry_delay_secs: u64,
+    /// Maximum number of blocks an endpoint may lag behind the best head before
+    /// it is marked degraded
+    pub max_block_lag: u64,
+    /// Chain ID every endpoint must report; when unset, the majority wins
+    pub expected_chain_id: Option<u64>,
 }
 
 impl Default for PoolConfig {
@@ -115,16 +83,52 @@ impl Default for PoolConfig {
             health_check_timeout_secs: 5,
             max_failures: 3,
             unhealthy_retry_delay_secs: 60,
+            max_block_lag: 5,
+            expected_chain_id: None,
         }
     }
 }
 
+impl PoolConfig {
+    /// Set health check interval
+    pub fn with_health_check_interval(mut self, interval: Duration) -> Self {
+        self.health_check_interval_secs = interval.as_secs();
+        self
+    }
+
+    /// Set connection timeout
+    pub fn with_connection_timeout(mut self, timeout: Duration) -> Self {
+        self.health_check_timeout_secs = timeout.as_secs();
+        self
+    }
+
+    /// Set maximum retries
+    pub fn with_max_retries(mut self, max_retries: u32) -> Self {
+        self.max_failures = max_retries;
+        self
+    }
+
+    /// Set the maximum block lag before an endpoint is marked degraded
+    pub fn with_max_block_lag(mut self, max_block_lag: u64) -> Self {
+        self.max_block_lag = max_block_lag;
+        self
+    }
+
+    /// Require every endpoint to report the given chain ID
+    pub fn with_expected_chain_id(mut self, chain_id: u64) -> Self {
+        self.expected_chain_id = Some(chain_id);
+        self
+    }
+}
+
 /// Connection pool for EVM providers
 pub struct ConnectionPool {
     endpoints: Vec<String>,
+    chain_name: String,
     connections: Arc<RwLock<Vec<PooledConnection>>>,
     next_index: AtomicUsize,
     config: PoolConfig,
+    health_checked: AtomicBool,
 }
 
 impl ConnectionPool {
@@ -178,11 +182,18 @@ impl ConnectionPool {
             }
         }
 
+        let chain_name = connections
+            .first()
+            .map(|conn| conn.adapter.chain_name().to_string())
+            .unwrap_or_default();
+
         Ok(Self {
             endpoints,
+            chain_name,
             connections: Arc::new(RwLock::new(connections)),
             next_index: AtomicUsize::new(0),
             config,
+            health_checked: AtomicBool::new(false),
         })
     }
 
@@ -198,6 +209,7 @@ impl ConnectionPool {
 
         let total = connections.len();
         let mut attempts = 0;
+        let mut degraded = None;
 
         // Try to find a healthy connection
         while attempts < total {
@@ -205,7 +217,12 @@ impl ConnectionPool {
             let conn = &connections[index];
 
             let health = conn.health.read().await;
-            if health.is_healthy {
+            if health.is_healthy && health.status == HealthStatus::Degraded {
+                // Usable, but only if nothing better is available
+                degraded.get_or_insert(index);
+                attempts += 1;
+                continue;
+            } else if health.is_healthy {
                 drop(health);
                 return Ok(Arc::new(PooledConnection {
                     adapter: conn.adapter.clone(),
@@ -214,8 +231,12 @@ impl ConnectionPool {
                 }));
             }
 
-            // Check if enough time has passed to retry unhealthy endpoint
-            if let Some(last_failure) = health.last_failure {
+            // Check if enough time has passed to retry unhealthy endpoint;
+            // endpoints on the wrong network wait for the next health check
+            if let (Some(last_failure), false) = (
+                health.last_failure,
+                health.status == HealthStatus::WrongNetwork,
+            ) {
                 if last_failure.elapsed().as_secs() > self.config.unhealthy_retry_delay_secs {
                     drop(health);
                     tracing::info!("Retrying previously unhealthy endpoint: {}", conn.endpoint);
@@ -230,14 +251,35 @@ impl ConnectionPool {
             attempts += 1;
         }
 
-        // All endpoints unhealthy, return the first one and let caller handle retry
-        let conn = &connections[0];
-        tracing::warn!("All endpoints unhealthy, returning first endpoint");
-        Ok(Arc::new(PooledConnection {
-            adapter: conn.adapter.clone(),
-            endpoint: conn.endpoint.clone(),
-            health: conn.health.clone(),
-        }))
+        if let Some(index) = degraded {
+            let conn = &connections[index];
+            tracing::debug!(
+                "No fully healthy endpoint, using degraded {}",
+                conn.endpoint
+            );
+            return Ok(Arc::new(PooledConnection {
+                adapter: conn.adapter.clone(),
+                endpoint: conn.endpoint.clone(),
+                health: conn.health.clone(),
+            }));
+        }
+
+        // All endpoints unhealthy, return the first one on the expected
+        // network and let caller handle retry
+        for conn in connections.iter() {
+            if conn.health.read().await.status != HealthStatus::WrongNetwork {
+                tracing::warn!("All endpoints unhealthy, returning {}", conn.endpoint);
+                return Ok(Arc::new(PooledConnection {
+                    adapter: conn.adapter.clone(),
+                    endpoint: conn.endpoint.clone(),
+                    health: conn.health.clone(),
+                }));
+            }
+        }
+
+        Err(Error::Connection(
+            "All endpoints serve another network".to_string(),
+        ))
     }
 
     /// Get health status of all endpoints
@@ -254,25 +296,80 @@ impl ConnectionPool {
     }
 
     /// Run health checks on all endpoints
+    ///
+    /// Each endpoint is probed concurrently for its chain ID and latest block.
+    /// Endpoints reporting a chain ID other than the expected one are marked
+    /// unhealthy, and endpoints lagging behind or forked from the best-known
+    /// head are marked [`HealthStatus::Degraded`].
     pub async fn run_health_checks(&self) -> Result<(), Error> {
         tracing::debug!("Running health checks on all endpoints");
 
         let connections = self.connections.read().await;
+        let timeout = Duration::from_secs(self.config.health_check_timeout_secs);
 
-        for conn in connections.iter() {
+        let results = join_all(connections.iter().map(|conn| async move {
             let start = Instant::now();
+            let result = tokio::time::timeout(timeout, probe_endpoint(&conn.adapter)).await;
+            (result, start.elapsed().as_millis() as u64)
+        }))
+        .await;
 
-            // Try to get block number as health check
-            match conn.adapter.provider().get_block_number().await {
-                Ok(_) => {
-                    let elapsed = start.elapsed().as_millis() as u64;
+        let mut probes = Vec::with_capacity(connections.len());
+        for (conn, (result, elapsed)) in connections.iter().zip(results) {
+            match result {
+                Ok(Ok(probe)) => {
                     conn.mark_healthy(elapsed).await;
                     tracing::debug!("Health check passed for {}: {}ms", conn.endpoint, elapsed);
+                    probes.push(Some(probe));
                 }
-                Err(e) => {
+                Ok(Err(e)) => {
                     conn.mark_unhealthy().await;
                     tracing::warn!("Health check failed for {}: {}", conn.endpoint, e);
+                    probes.push(None);
                 }
+                Err(_) => {
+                    conn.mark_unhealthy().await;
+                    tracing::warn!("Health check timed out for {}", conn.endpoint);
+                    probes.push(None);
+                }
+            }
+        }
+
+        self.health_checked.store(true, Ordering::Relaxed);
+
+        let Some(check) = HealthCheck::new(&probes, self.config.expected_chain_id) else {
+            return Ok(());
+        };
+
+        // Endpoints behind the best head are on a fork if their head is not the
+        // best endpoint's block at that height
+        let mut canonical = HashMap::new();
+        if let Some(best) = check.best_index {
+            let adapter = &connections[best].adapter;
+            let heights = check.heights_to_verify(&probes, self.config.max_block_lag);
+            let hashes = join_all(
+                heights
+                    .iter()
+                    .map(|&number| tokio::time::timeout(timeout, block_hash_at(adapter, number))),
+            )
+            .await;
+            for (number, hash) in heights.into_iter().zip(hashes) {
+                match hash {
+                    Ok(Ok(hash)) => {
+                        canonical.insert(number, hash);
+                    }
+                    _ => tracing::debug!("Could not fetch canonical block #{}", number),
+                }
+            }
+        }
+
+        let verdicts = check.classify(probes, &canonical, self.config.max_block_lag);
+        for (conn, verdict) in connections.iter().zip(verdicts) {
+            if let Some(verdict) = verdict {
+                conn.health
+                    .write()
+                    .await
+                    .record_verdict(&conn.endpoint, verdict);
             }
         }
 
@@ -298,6 +395,11 @@ impl ConnectionPool {
         tracing::info!("Started health checker with interval: {}s", interval_secs);
     }
 
+    /// Name of the chain served by the pool
+    pub fn chain_name(&self) -> &str {
+        &self.chain_name
+    }
+
     /// Get the number of endpoints
     pub fn endpoint_count(&self) -> usize {
         self.endpoints.len()
@@ -309,6 +411,54 @@ impl ConnectionPool {
     }
 }
 
+#[async_trait]
+impl PoolHealth for ConnectionPool {
+    fn chain_name(&self) -> &str {
+        &self.chain_name
+    }
+
+    fn health_checked(&self) -> bool {
+        self.health_checked.load(Ordering::Relaxed)
+    }
+
+    async fn health_status(&self) -> Vec<(String, EndpointHealth)> {
+        ConnectionPool::health_status(self).await
+    }
+}
+
+/// Fetch the chain ID and latest block of an endpoint
+async fn probe_endpoint(adapter: &EvmAdapter) -> Result<Probe<u64>, Error> {
+    let provider = &adapter.provider().provider;
+
+    let chain_id = provider
+        .get_chain_id()
+        .await
+        .map_err(|e| Error::Connection(format!("Failed to get chain ID: {}", e)))?;
+
+    let block = provider
+        .get_block_by_number(BlockNumberOrTag::Latest)
+        .await
+        .map_err(|e| Error::Connection(format!("Failed to get latest block: {}", e)))?
+        .ok_or_else(|| Error::Connection("Latest block not available".to_string()))?;
+
+    Ok(Probe {
+        network: chain_id,
+        head: HeadInfo::new(block.header.number, format!("0x{:x}", block.header.hash)),
+    })
+}
+
+/// Hash of the endpoint's block at `number`
+async fn block_hash_at(adapter: &EvmAdapter, number: u64) -> Result<String, Error> {
+    let block = adapter
+        .provider()
+        .provider
+        .get_block_by_number(BlockNumberOrTag::Number(number))
+        .await
+        .map_err(|e| Error::Connection(format!("Failed to get block #{}: {}", number, e)))?
+        .ok_or_else(|| Error::Connection(format!("Block #{} not available", number)))?;
+    Ok(format!("0x{:x}", block.header.hash))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,6 +472,8 @@ mod tests {
         assert_eq!(config.health_check_timeout_secs, 5);
         assert_eq!(config.max_failures, 3);
         assert_eq!(config.unhealthy_retry_delay_secs, 60);
+        assert_eq!(config.max_block_lag, 5);
+        assert!(config.expected_chain_id.is_none());
     }
 
     #[test]
@@ -332,6 +484,7 @@ mod tests {
             health_check_timeout_secs: 10,
             max_failures: 5,
             unhealthy_retry_delay_secs: 120,
+            ..Default::default()
         };
 
         assert_eq!(config.max_connections_per_endpoint, 20);
@@ -341,10 +494,28 @@ mod tests {
         assert_eq!(config.unhealthy_retry_delay_secs, 120);
     }
 
+    #[test]
+    fn test_pool_config_builder() {
+        let config = PoolConfig::default()
+            .with_health_check_interval(Duration::from_secs(10))
+            .with_connection_timeout(Duration::from_secs(2))
+            .with_max_retries(5)
+            .with_max_block_lag(10)
+            .with_expected_chain_id(1);
+
+        assert_eq!(config.health_check_interval_secs, 10);
+        assert_eq!(config.health_check_timeout_secs, 2);
+        assert_eq!(config.max_failures, 5);
+        assert_eq!(config.max_block_lag, 10);
+        assert_eq!(config.expected_chain_id, Some(1));
+    }
+
     #[test]
     fn test_endpoint_health_default() {
         let health = EndpointHealth::default();
         assert!(health.is_healthy);
+        assert_eq!(health.status, HealthStatus::Unknown);
+        assert!(health.head.is_none());
         assert_eq!(health.failure_count, 0);
         assert_eq!(health.avg_response_time_ms, 0);
         assert!(health.last_success.is_none());
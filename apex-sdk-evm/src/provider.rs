@@ -1,9 +1,10 @@
 //! EVM Provider implementation
 
+use crate::metrics::{Metrics, MetricsSnapshot};
 use crate::{AlloyHttpProvider, Error};
 use alloy::primitives::Address as EthAddress;
 use alloy::providers::{Provider, ProviderBuilder};
-use apex_sdk_core::{BlockInfo, Provider as CoreProvider, SdkError};
+use apex_sdk_core::{BlockInfo, Provider as CoreProvider, SdkError, Telemetry};
 use apex_sdk_types::Address;
 use async_trait::async_trait;
 use std::str::FromStr;
@@ -14,6 +15,7 @@ pub struct EvmProvider {
     pub provider: AlloyHttpProvider,
     chain_id: u64,
     rpc_url: String,
+    metrics: Metrics,
 }
 
 impl EvmProvider {
@@ -30,13 +32,38 @@ impl EvmProvider {
             .await
             .map_err(|e| Error::Connection(format!("Failed to get chain ID: {}", e)))?;
 
+        let telemetry = Telemetry::new(format!("evm-{}", chain_id), rpc_url);
+
         Ok(Self {
             provider,
             chain_id,
             rpc_url: rpc_url.to_string(),
+            metrics: Metrics::new().with_telemetry(telemetry),
         })
     }
 
+    /// Label RPC spans and metrics with a chain name instead of the chain ID
+    pub fn with_chain_name(mut self, chain_name: &str) -> Self {
+        let telemetry = Telemetry::new(chain_name, &self.rpc_url);
+        self.metrics = self.metrics.with_telemetry(telemetry);
+        self
+    }
+
+    /// Get the telemetry handle used for RPC spans
+    pub fn telemetry(&self) -> &Telemetry {
+        self.metrics.telemetry()
+    }
+
+    /// Get a snapshot of the RPC metrics
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Get the live metrics handle, e.g. for a Prometheus exporter
+    pub fn rpc_metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
     /// Get the chain ID
     pub fn chain_id(&self) -> u64 {
         self.chain_id
@@ -58,8 +85,8 @@ impl EvmProvider {
 #[async_trait]
 impl CoreProvider for EvmProvider {
     async fn get_block_number(&self) -> Result<u64, SdkError> {
-        self.provider
-            .get_block_number()
+        self.metrics
+            .instrument("eth_blockNumber", self.provider.get_block_number())
             .await
             .map_err(|e| Error::Connection(format!("Failed to get block number: {}", e)).into())
     }
@@ -67,8 +94,8 @@ impl CoreProvider for EvmProvider {
     async fn get_balance(&self, address: &Address) -> Result<u128, SdkError> {
         let eth_address = self.to_eth_address(address)?;
         let balance = self
-            .provider
-            .get_balance(eth_address)
+            .metrics
+            .instrument("eth_getBalance", self.provider.get_balance(eth_address))
             .await
             .map_err(|e| Error::Connection(format!("Failed to get balance: {}", e)))?;
 
@@ -78,8 +105,11 @@ impl CoreProvider for EvmProvider {
     async fn get_transaction_count(&self, address: &Address) -> Result<u64, SdkError> {
         let eth_address = self.to_eth_address(address)?;
         let nonce = self
-            .provider
-            .get_transaction_count(eth_address)
+            .metrics
+            .instrument(
+                "eth_getTransactionCount",
+                self.provider.get_transaction_count(eth_address),
+            )
             .await
             .map_err(|e| Error::Connection(format!("Failed to get transaction count: {}", e)))?;
 
@@ -89,8 +119,8 @@ impl CoreProvider for EvmProvider {
     async fn estimate_fee(&self, tx: &[u8]) -> Result<u128, SdkError> {
         // For EVM, estimate gas price * gas limit
         let gas_price = self
-            .provider
-            .get_gas_price()
+            .metrics
+            .instrument("eth_gasPrice", self.provider.get_gas_price())
             .await
             .map_err(|e| Error::Connection(format!("Failed to get gas price: {}", e)))?;
 
@@ -120,8 +150,12 @@ impl CoreProvider for EvmProvider {
 
     async fn get_block(&self, block_number: u64) -> Result<BlockInfo, SdkError> {
         let block = self
-            .provider
-            .get_block_by_number(alloy::rpc::types::BlockNumberOrTag::Number(block_number))
+            .metrics
+            .instrument(
+                "eth_getBlockByNumber",
+                self.provider
+                    .get_block_by_number(alloy::rpc::types::BlockNumberOrTag::Number(block_number)),
+            )
             .await
             .map_err(|e| SdkError::ProviderError(format!("Failed to get block: {}", e)))?
             .ok_or_else(|| SdkError::ProviderError("Block not found".to_string()))?;
@@ -145,8 +179,8 @@ impl CoreProvider for EvmProvider {
     }
 
     async fn health_check(&self) -> Result<(), SdkError> {
-        self.provider
-            .get_chain_id()
+        self.metrics
+            .instrument("eth_chainId", self.provider.get_chain_id())
             .await
             .map_err(|e| Error::Connection(format!("Health check failed: {}", e)))?;
         Ok(())
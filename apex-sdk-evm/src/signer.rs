@@ -1,11 +1,12 @@
 //! EVM Signer implementation
 
+use crate::fee_oracle::{FeeOracle, FeeOracleConfig, FeeSpeed};
 use crate::{AlloyHttpProvider, Error};
 use alloy::consensus::SignableTransaction;
 use alloy::network::TransactionBuilder;
 use alloy::primitives::{Address as EthAddress, Bytes, Signature as EthSignature, U256};
 use alloy::providers::Provider;
-use alloy::rpc::types::{Block, BlockNumberOrTag, TransactionRequest};
+use alloy::rpc::types::TransactionRequest;
 use alloy::signers::{local::PrivateKeySigner, Signer as AlloySigner};
 use alloy_eips::eip2718::Encodable2718;
 use apex_sdk_core::{SdkError, Signer as CoreSigner};
@@ -22,6 +23,7 @@ pub struct EvmSigner {
     signer: PrivateKeySigner,
     address: Address,
     provider: Option<AlloyHttpProvider>,
+    fee_speed: FeeSpeed,
 }
 
 impl EvmSigner {
@@ -37,6 +39,7 @@ impl EvmSigner {
             signer,
             address,
             provider: None,
+            fee_speed: FeeSpeed::default(),
         })
     }
 
@@ -50,6 +53,7 @@ impl EvmSigner {
             signer,
             address,
             provider: None,
+            fee_speed: FeeSpeed::default(),
         })
     }
 
@@ -59,6 +63,12 @@ impl EvmSigner {
         self
     }
 
+    /// Set the fee tier used when building EIP-1559 transactions
+    pub fn with_fee_speed(mut self, speed: FeeSpeed) -> Self {
+        self.fee_speed = speed;
+        self
+    }
+
     /// Get the underlying Alloy signer
     pub fn alloy_signer(&self) -> &PrivateKeySigner {
         &self.signer
@@ -72,93 +82,6 @@ impl EvmSigner {
             .map_err(|e| Error::Other(format!("Failed to sign message: {}", e)))
     }
 
-    /// Decode transaction metadata
-    fn decode_metadata(&self, metadata: &[u8]) -> Result<DecodedMetadata, Error> {
-        if metadata.is_empty() {
-            return Err(Error::Transaction("Empty metadata".to_string()));
-        }
-
-        let tx_type = metadata[0];
-
-        match tx_type {
-            0x00 => {
-                // Native ETH transfer: [0x00, to_address (20 bytes), value (32 bytes)]
-                if metadata.len() < 53 {
-                    return Err(Error::Transaction(
-                        "Invalid native transfer metadata".to_string(),
-                    ));
-                }
-                let to_bytes: [u8; 20] = metadata[1..21]
-                    .try_into()
-                    .map_err(|_| Error::Transaction("Invalid address".to_string()))?;
-                let to = EthAddress::from(to_bytes);
-                let value = U256::from_be_slice(&metadata[21..53]);
-                Ok((tx_type, to, value, None))
-            }
-            0x01 => {
-                // ERC-20 transfer: [0x01, token_address (20 bytes), calldata]
-                if metadata.len() < 21 {
-                    return Err(Error::Transaction(
-                        "Invalid ERC-20 transfer metadata".to_string(),
-                    ));
-                }
-                let to_bytes: [u8; 20] = metadata[1..21]
-                    .try_into()
-                    .map_err(|_| Error::Transaction("Invalid token address".to_string()))?;
-                let token_address = EthAddress::from(to_bytes);
-                let calldata = metadata[21..].to_vec();
-                Ok((tx_type, token_address, U256::ZERO, Some(calldata)))
-            }
-            0x02 => {
-                // Generic contract call: [0x02, contract_address (20 bytes), value (32 bytes), calldata]
-                if metadata.len() < 53 {
-                    return Err(Error::Transaction(
-                        "Invalid contract call metadata".to_string(),
-                    ));
-                }
-                let to_bytes: [u8; 20] = metadata[1..21]
-                    .try_into()
-                    .map_err(|_| Error::Transaction("Invalid contract address".to_string()))?;
-                let to = EthAddress::from(to_bytes);
-                let value = U256::from_be_slice(&metadata[21..53]);
-                let calldata = if metadata.len() > 53 {
-                    Some(metadata[53..].to_vec())
-                } else {
-                    None
-                };
-                Ok((tx_type, to, value, calldata))
-            }
-            _ => Err(Error::Transaction(format!(
-                "Unknown transaction type: {}",
-                tx_type
-            ))),
-        }
-    }
-
-    /// Get EIP-1559 fees from the provider
-    async fn get_eip1559_fees(&self) -> Result<(U256, U256), Error> {
-        let provider = self
-            .provider
-            .as_ref()
-            .ok_or_else(|| Error::Other("Provider not set".to_string()))?;
-
-        let block: Block = provider
-            .get_block_by_number(BlockNumberOrTag::Latest)
-            .await
-            .map_err(|e| Error::Connection(format!("Failed to get block: {}", e)))?
-            .ok_or_else(|| Error::Connection("No latest block".to_string()))?;
-
-        let base_fee = block
-            .header
-            .base_fee_per_gas
-            .map(U256::from)
-            .ok_or_else(|| Error::Other("EIP-1559 not supported".to_string()))?;
-
-        let priority_fee = U256::from(2_000_000_000u64); // 2 gwei
-
-        Ok((base_fee, priority_fee))
-    }
-
     /// Build a proper EVM transaction from metadata
     async fn build_transaction(&self, metadata: &[u8]) -> Result<TransactionRequest, Error> {
         let provider = self
@@ -166,7 +89,7 @@ impl EvmSigner {
             .as_ref()
             .ok_or_else(|| Error::Other("Provider not set".to_string()))?;
 
-        let (_tx_type, to, value, data) = self.decode_metadata(metadata)?;
+        let (_tx_type, to, value, data) = decode_metadata(metadata)?;
         let from = self.signer.address();
 
         // Get nonce
@@ -202,12 +125,14 @@ impl EvmSigner {
         tx = tx.with_gas_limit(gas_limit);
 
         // Try to get EIP-1559 fees, fallback to legacy if not supported
-        match self.get_eip1559_fees().await {
-            Ok((base_fee, priority_fee)) => {
-                let max_fee = base_fee * U256::from(2) + priority_fee;
+        let oracle =
+            FeeOracle::new(provider.clone()).with_config(FeeOracleConfig::for_chain_id(chain_id));
+        match oracle.estimate().await {
+            Ok(fees) => {
+                let tier = fees.tier(self.fee_speed);
                 tx = tx
-                    .with_max_fee_per_gas(max_fee.to::<u128>())
-                    .with_max_priority_fee_per_gas(priority_fee.to::<u128>());
+                    .with_max_fee_per_gas(tier.max_fee_per_gas.to::<u128>())
+                    .with_max_priority_fee_per_gas(tier.max_priority_fee_per_gas.to::<u128>());
             }
             Err(_) => {
                 // Fallback to legacy gas price
@@ -223,6 +148,69 @@ impl EvmSigner {
     }
 }
 
+/// Decode transaction metadata
+pub(crate) fn decode_metadata(metadata: &[u8]) -> Result<DecodedMetadata, Error> {
+    if metadata.is_empty() {
+        return Err(Error::Transaction("Empty metadata".to_string()));
+    }
+
+    let tx_type = metadata[0];
+
+    match tx_type {
+        0x00 => {
+            // Native ETH transfer: [0x00, to_address (20 bytes), value (32 bytes)]
+            if metadata.len() < 53 {
+                return Err(Error::Transaction(
+                    "Invalid native transfer metadata".to_string(),
+                ));
+            }
+            let to_bytes: [u8; 20] = metadata[1..21]
+                .try_into()
+                .map_err(|_| Error::Transaction("Invalid address".to_string()))?;
+            let to = EthAddress::from(to_bytes);
+            let value = U256::from_be_slice(&metadata[21..53]);
+            Ok((tx_type, to, value, None))
+        }
+        0x01 => {
+            // ERC-20 transfer: [0x01, token_address (20 bytes), calldata]
+            if metadata.len() < 21 {
+                return Err(Error::Transaction(
+                    "Invalid ERC-20 transfer metadata".to_string(),
+                ));
+            }
+            let to_bytes: [u8; 20] = metadata[1..21]
+                .try_into()
+                .map_err(|_| Error::Transaction("Invalid token address".to_string()))?;
+            let token_address = EthAddress::from(to_bytes);
+            let calldata = metadata[21..].to_vec();
+            Ok((tx_type, token_address, U256::ZERO, Some(calldata)))
+        }
+        0x02 => {
+            // Generic contract call: [0x02, contract_address (20 bytes), value (32 bytes), calldata]
+            if metadata.len() < 53 {
+                return Err(Error::Transaction(
+                    "Invalid contract call metadata".to_string(),
+                ));
+            }
+            let to_bytes: [u8; 20] = metadata[1..21]
+                .try_into()
+                .map_err(|_| Error::Transaction("Invalid contract address".to_string()))?;
+            let to = EthAddress::from(to_bytes);
+            let value = U256::from_be_slice(&metadata[21..53]);
+            let calldata = if metadata.len() > 53 {
+                Some(metadata[53..].to_vec())
+            } else {
+                None
+            };
+            Ok((tx_type, to, value, calldata))
+        }
+        _ => Err(Error::Transaction(format!(
+            "Unknown transaction type: {}",
+            tx_type
+        ))),
+    }
+}
+
 #[async_trait]
 impl CoreSigner for EvmSigner {
     async fn sign_transaction(&self, tx: &[u8]) -> Result<Vec<u8>, SdkError> {
@@ -6,12 +6,14 @@
 //! - Transaction submission with retry logic
 //! - Transaction monitoring
 
+use crate::fee_oracle::{FeeEstimates, FeeOracle, FeeOracleConfig, FeeSpeed};
+use crate::l2_fee::{FeeModel, L2FeeEstimator};
 use crate::{wallet::Wallet, Error, ProviderType};
 use alloy::consensus::SignableTransaction;
 use alloy::network::TransactionBuilder;
 use alloy::primitives::{Address as EthAddress, Bytes, B256, U256};
 use alloy::providers::Provider;
-use alloy::rpc::types::{Block, BlockNumberOrTag, TransactionReceipt, TransactionRequest};
+use alloy::rpc::types::{TransactionReceipt, TransactionRequest};
 use alloy_eips::eip2718::Encodable2718;
 use apex_sdk_core::{FeeEstimator, SdkError};
 use async_trait::async_trait;
@@ -28,6 +30,12 @@ pub struct GasConfig {
     pub max_fee_per_gas: Option<U256>,
     /// Gas price for legacy transactions in gwei
     pub gas_price: Option<U256>,
+    /// Fee tier used when the fees are not set explicitly
+    pub fee_speed: FeeSpeed,
+    /// Fee oracle settings, including per-chain priority fee floors
+    pub fee_oracle: FeeOracleConfig,
+    /// How the chain charges for L1 data, see [`FeeModel::for_chain_id`]
+    pub fee_model: FeeModel,
 }
 
 impl Default for GasConfig {
@@ -37,6 +45,9 @@ impl Default for GasConfig {
             max_priority_fee_per_gas: None,
             max_fee_per_gas: None,
             gas_price: None,
+            fee_speed: FeeSpeed::default(),
+            fee_oracle: FeeOracleConfig::default(),
+            fee_model: FeeModel::default(),
         }
     }
 }
@@ -81,7 +92,13 @@ pub struct GasEstimate {
     pub max_priority_fee_per_gas: Option<U256>,
     /// Whether this is an EIP-1559 transaction
     pub is_eip1559: bool,
-    /// Estimated total cost in wei
+    /// Predicted base fee of the next block (EIP-1559 only)
+    pub next_base_fee_per_gas: Option<U256>,
+    /// Slow, standard and fast fee tiers from the fee oracle (EIP-1559 only)
+    pub fee_tiers: Option<FeeEstimates>,
+    /// L1 data component of the cost on rollups, in wei
+    pub l1_data_fee: Option<U256>,
+    /// Estimated total cost in wei, including any L1 data fee
     pub total_cost: U256,
 }
 
@@ -105,6 +122,17 @@ impl GasEstimate {
     pub fn total_cost_eth(&self) -> String {
         format_eth(self.total_cost)
     }
+
+    /// L2 execution part of the total cost in wei
+    pub fn execution_cost(&self) -> U256 {
+        self.total_cost
+            .saturating_sub(self.l1_data_fee.unwrap_or_default())
+    }
+
+    /// Format the L1 data fee in ETH
+    pub fn l1_data_fee_eth(&self) -> Option<String> {
+        self.l1_data_fee.map(format_eth)
+    }
 }
 
 /// Transaction executor with gas estimation and retry logic
@@ -112,6 +140,7 @@ pub struct TransactionExecutor {
     provider: ProviderType,
     gas_config: GasConfig,
     retry_config: RetryConfig,
+    sender: Option<EthAddress>,
 }
 
 impl TransactionExecutor {
@@ -121,6 +150,7 @@ impl TransactionExecutor {
             provider,
             gas_config: GasConfig::default(),
             retry_config: RetryConfig::default(),
+            sender: None,
         }
     }
 
@@ -136,9 +166,17 @@ impl TransactionExecutor {
         self
     }
 
+    /// Set the sender [`FeeEstimator::estimate_fee`] estimates gas for
+    pub fn with_sender(mut self, sender: EthAddress) -> Self {
+        self.sender = Some(sender);
+        self
+    }
+
     /// Estimate gas for a transaction
     ///
-    /// This handles both EIP-1559 (London fork) and legacy transactions
+    /// This handles both EIP-1559 (London fork) and legacy transactions. On
+    /// rollups (see [`GasConfig::fee_model`]) the estimate also carries the
+    /// L1 data fee.
     pub async fn estimate_gas(
         &self,
         from: EthAddress,
@@ -172,17 +210,80 @@ impl TransactionExecutor {
             (self.gas_config.gas_limit_multiplier - 1.0) * 100.0
         );
 
-        let (gas_price, base_fee, priority_fee, is_eip1559) = self.estimate_gas_price().await?;
-        let total_cost = gas_limit * gas_price;
+        let mut estimate = self.estimate_gas_price().await?;
+        estimate.gas_limit = gas_limit;
 
-        Ok(GasEstimate {
-            gas_limit,
-            gas_price,
-            base_fee_per_gas: base_fee,
-            max_priority_fee_per_gas: priority_fee,
-            is_eip1559,
-            total_cost,
-        })
+        if self.gas_config.fee_model != FeeModel::Standard {
+            self.apply_l2_fees(&mut estimate, tx).await?;
+        }
+
+        estimate.total_cost = estimate.gas_limit * estimate.gas_price;
+        if self.gas_config.fee_model.charges_l1_fee_separately() {
+            estimate.total_cost += estimate.l1_data_fee.unwrap_or_default();
+        }
+
+        Ok(estimate)
+    }
+
+    /// Add rollup-specific limits, prices and the L1 data fee to an estimate
+    async fn apply_l2_fees(
+        &self,
+        estimate: &mut GasEstimate,
+        tx: TransactionRequest,
+    ) -> Result<(), Error> {
+        let mut tx = tx.with_gas_limit(estimate.gas_limit.to::<u64>());
+        if estimate.is_eip1559 {
+            tx = tx.with_max_fee_per_gas(estimate.gas_price.to::<u128>());
+            if let Some(priority_fee) = estimate.max_priority_fee_per_gas {
+                tx = tx.with_max_priority_fee_per_gas(priority_fee.to::<u128>());
+            }
+        } else {
+            tx = tx.with_gas_price(estimate.gas_price.to::<u128>());
+        }
+
+        let l2_fees =
+            match L2FeeEstimator::new(self.provider.inner.clone(), self.gas_config.fee_model)
+                .estimate(&tx)
+                .await
+            {
+                Ok(fees) => fees,
+                Err(e) => {
+                    tracing::warn!(
+                        "{:?} fee estimation failed, using the plain L2 estimate: {}",
+                        self.gas_config.fee_model,
+                        e
+                    );
+                    return Ok(());
+                }
+            };
+
+        let scale = |gas: U256| {
+            U256::from((gas.to::<u128>() as f64 * self.gas_config.gas_limit_multiplier) as u128)
+        };
+        if let Some(gas_limit) = l2_fees.gas_limit {
+            estimate.gas_limit = scale(gas_limit);
+        }
+        if let Some(max_fee) = l2_fees.max_fee_per_gas {
+            estimate.gas_price = max_fee;
+            estimate.is_eip1559 = true;
+        }
+        if let Some(priority_fee) = l2_fees.max_priority_fee_per_gas {
+            estimate.max_priority_fee_per_gas = Some(priority_fee);
+        }
+        // When L1 gas is folded into the limit, price it like the rest of the
+        // limit so the execution part of the total uses the same price
+        estimate.l1_data_fee = match l2_fees.l1_gas {
+            Some(l1_gas) => Some(scale(l1_gas) * estimate.gas_price),
+            None => l2_fees.l1_data_fee,
+        };
+
+        tracing::debug!(
+            "{:?} fees: gas limit={}, L1 data fee={:?} wei",
+            self.gas_config.fee_model,
+            estimate.gas_limit,
+            estimate.l1_data_fee
+        );
+        Ok(())
     }
 
     /// Estimate gas limit for a transaction
@@ -198,48 +299,64 @@ impl TransactionExecutor {
     }
 
     /// Estimate gas price (handles both EIP-1559 and legacy)
-    async fn estimate_gas_price(&self) -> Result<(U256, Option<U256>, Option<U256>, bool), Error> {
+    ///
+    /// The returned estimate carries fee fields only; the gas limit and
+    /// total cost are filled in by the caller.
+    async fn estimate_gas_price(&self) -> Result<GasEstimate, Error> {
         match self.get_eip1559_fees().await {
-            Ok((base_fee, priority_fee)) => {
-                let max_fee = base_fee * U256::from(2) + priority_fee;
+            Ok(fees) => {
+                let tier = fees.tier(self.gas_config.fee_speed);
+                let priority_fee = self
+                    .gas_config
+                    .max_priority_fee_per_gas
+                    .unwrap_or(tier.max_priority_fee_per_gas);
+                let max_fee = self.gas_config.max_fee_per_gas.unwrap_or_else(|| {
+                    tier.max_fee_per_gas - tier.max_priority_fee_per_gas + priority_fee
+                });
                 tracing::debug!(
-                    "Using EIP-1559: base={} gwei, priority={} gwei, max={} gwei",
-                    format_gwei(base_fee),
+                    "Using EIP-1559 ({:?}): base={} gwei, next base={} gwei, priority={} gwei, max={} gwei",
+                    self.gas_config.fee_speed,
+                    format_gwei(fees.base_fee_per_gas),
+                    format_gwei(fees.next_base_fee_per_gas),
                     format_gwei(priority_fee),
                     format_gwei(max_fee)
                 );
-                Ok((max_fee, Some(base_fee), Some(priority_fee), true))
+                Ok(GasEstimate {
+                    gas_limit: U256::ZERO,
+                    gas_price: max_fee,
+                    base_fee_per_gas: Some(fees.base_fee_per_gas),
+                    max_priority_fee_per_gas: Some(priority_fee),
+                    is_eip1559: true,
+                    next_base_fee_per_gas: Some(fees.next_base_fee_per_gas),
+                    fee_tiers: Some(fees),
+                    l1_data_fee: None,
+                    total_cost: U256::ZERO,
+                })
             }
             Err(_) => {
                 let gas_price = self.get_legacy_gas_price().await?;
                 tracing::debug!("Using legacy gas price: {} gwei", format_gwei(gas_price));
-                Ok((gas_price, None, None, false))
+                Ok(GasEstimate {
+                    gas_limit: U256::ZERO,
+                    gas_price,
+                    base_fee_per_gas: None,
+                    max_priority_fee_per_gas: None,
+                    is_eip1559: false,
+                    next_base_fee_per_gas: None,
+                    fee_tiers: None,
+                    l1_data_fee: None,
+                    total_cost: U256::ZERO,
+                })
             }
         }
     }
 
-    /// Get EIP-1559 fee estimates
-    async fn get_eip1559_fees(&self) -> Result<(U256, U256), Error> {
-        let block: Block = self
-            .provider
-            .inner
-            .get_block_by_number(BlockNumberOrTag::Latest)
+    /// Get EIP-1559 fee estimates from the fee oracle
+    async fn get_eip1559_fees(&self) -> Result<FeeEstimates, Error> {
+        FeeOracle::new(self.provider.inner.clone())
+            .with_config(self.gas_config.fee_oracle.clone())
+            .estimate()
             .await
-            .map_err(|e| Error::Connection(format!("Failed to get block: {}", e)))?
-            .ok_or_else(|| Error::Connection("No latest block".to_string()))?;
-
-        let base_fee = block
-            .header
-            .base_fee_per_gas
-            .map(U256::from)
-            .ok_or_else(|| Error::Other("EIP-1559 not supported".to_string()))?;
-
-        let priority_fee = self
-            .gas_config
-            .max_priority_fee_per_gas
-            .unwrap_or_else(|| U256::from(2_000_000_000u64)); // 2 gwei
-
-        Ok((base_fee, priority_fee))
     }
 
     /// Get legacy gas price
@@ -267,12 +384,77 @@ impl TransactionExecutor {
         data: Option<Vec<u8>>,
         gas_estimate: Option<GasEstimate>,
     ) -> Result<TransactionRequest, Error> {
-        let from = wallet.eth_address();
+        let tx = self
+            .build_transaction_from(wallet.eth_address(), to, value, data, gas_estimate)
+            .await?;
+
+        Ok(match wallet.chain_id() {
+            Some(chain_id) => tx.with_chain_id(chain_id),
+            None => tx,
+        })
+    }
+
+    /// Build an unsigned transaction for a sender whose key is held elsewhere,
+    /// e.g. a remote signer
+    ///
+    /// The chain ID is left unset.
+    pub async fn build_transaction_from(
+        &self,
+        from: EthAddress,
+        to: EthAddress,
+        value: U256,
+        data: Option<Vec<u8>>,
+        gas_estimate: Option<GasEstimate>,
+    ) -> Result<TransactionRequest, Error> {
+        self.build_request(from, Some(to), value, data, gas_estimate)
+            .await
+    }
 
+    /// Build a contract creation transaction from the contract's init code
+    /// (bytecode followed by the ABI-encoded constructor arguments)
+    pub async fn build_deployment(
+        &self,
+        wallet: &Wallet,
+        init_code: Vec<u8>,
+        gas_estimate: Option<GasEstimate>,
+    ) -> Result<TransactionRequest, Error> {
+        let tx = self
+            .build_deployment_from(wallet.eth_address(), init_code, gas_estimate)
+            .await?;
+
+        Ok(match wallet.chain_id() {
+            Some(chain_id) => tx.with_chain_id(chain_id),
+            None => tx,
+        })
+    }
+
+    /// Build an unsigned contract creation transaction for a sender whose key
+    /// is held elsewhere
+    ///
+    /// The chain ID is left unset.
+    pub async fn build_deployment_from(
+        &self,
+        from: EthAddress,
+        init_code: Vec<u8>,
+        gas_estimate: Option<GasEstimate>,
+    ) -> Result<TransactionRequest, Error> {
+        self.build_request(from, None, U256::ZERO, Some(init_code), gas_estimate)
+            .await
+    }
+
+    /// Build a call, or a contract creation when `to` is `None`
+    async fn build_request(
+        &self,
+        from: EthAddress,
+        to: Option<EthAddress>,
+        value: U256,
+        data: Option<Vec<u8>>,
+        gas_estimate: Option<GasEstimate>,
+    ) -> Result<TransactionRequest, Error> {
         let gas_est = if let Some(est) = gas_estimate {
             est
         } else {
-            self.estimate_gas(from, Some(to), Some(value), data.clone())
+            self.estimate_gas(from, to, Some(value), data.clone())
                 .await?
         };
 
@@ -280,19 +462,13 @@ impl TransactionExecutor {
 
         let mut tx = TransactionRequest::default()
             .with_from(from)
-            .with_to(to)
             .with_value(value)
             .with_gas_limit(gas_est.gas_limit.to::<u64>())
             .with_nonce(nonce.to::<u64>());
 
         if gas_est.is_eip1559 {
-            if let Some(base_fee) = gas_est.base_fee_per_gas {
-                let max_fee = base_fee * U256::from(2)
-                    + gas_est
-                        .max_priority_fee_per_gas
-                        .unwrap_or_else(|| U256::from(2_000_000_000u64));
-                tx = tx.with_max_fee_per_gas(max_fee.to::<u128>());
-            }
+            // For EIP-1559 estimates, gas_price already holds maxFeePerGas
+            tx = tx.with_max_fee_per_gas(gas_est.gas_price.to::<u128>());
 
             if let Some(priority_fee) = gas_est.max_priority_fee_per_gas {
                 tx = tx.with_max_priority_fee_per_gas(priority_fee.to::<u128>());
@@ -301,13 +477,11 @@ impl TransactionExecutor {
             tx = tx.with_gas_price(gas_est.gas_price.to::<u128>());
         }
 
-        if let Some(tx_data) = data {
-            tx = tx.with_input(Bytes::from(tx_data));
-        }
-
-        if let Some(chain_id) = wallet.chain_id() {
-            tx = tx.with_chain_id(chain_id);
-        }
+        tx = match (to, data) {
+            (Some(to), Some(data)) => tx.with_to(to).with_input(Bytes::from(data)),
+            (Some(to), None) => tx.with_to(to),
+            (None, data) => tx.with_deploy_code(Bytes::from(data.unwrap_or_default())),
+        };
 
         Ok(tx)
     }
@@ -456,44 +630,18 @@ impl TransactionExecutor {
 
 #[async_trait]
 impl FeeEstimator for TransactionExecutor {
+    /// Estimate with an [`EvmFeeEstimator`](crate::EvmFeeEstimator) using this
+    /// executor's gas configuration and sender, so both report the same fee
     async fn estimate_fee(&self, tx: &[u8]) -> Result<u128, SdkError> {
-        // Enhanced fee estimation with proper transaction analysis
-
-        if tx.is_empty() {
-            // Return minimum fee for empty transaction
-            let base_fee = U256::from(20_000_000_000u64); // 20 gwei
-            let gas_limit = U256::from(21_000u64);
-            return Ok((base_fee * gas_limit).to::<u128>());
+        let mut estimator = crate::EvmFeeEstimator::new(self.provider.inner.clone())
+            .with_oracle_config(self.gas_config.fee_oracle.clone())
+            .with_fee_model(self.gas_config.fee_model)
+            .with_fee_speed(self.gas_config.fee_speed)
+            .with_gas_multiplier(self.gas_config.gas_limit_multiplier);
+        if let Some(sender) = self.sender {
+            estimator = estimator.with_sender(sender);
         }
-
-        // Analyze transaction bytes to estimate appropriate gas and price
-        let length = tx.len();
-
-        let gas_limit = match length {
-            0..=32 => 21_000u64, // Simple transfer
-            33..=100 => {
-                // Check for ERC-20 transfer pattern
-                if length >= 68 && tx.get(32..36) == Some(&[0xa9, 0x05, 0x9c, 0xbb]) {
-                    65_000u64 // ERC-20 transfer
-                } else {
-                    50_000u64 // Other token operations
-                }
-            }
-            101..=500 => {
-                // Contract interaction with moderate complexity
-                let base_gas = 75_000u64;
-                let data_gas = (length as u64) * 16; // 16 gas per byte
-                base_gas + data_gas.min(150_000)
-            }
-            501..=2000 => 250_000u64, // Complex contract call
-            _ => 500_000u64,          // Contract deployment or very complex operation
-        };
-
-        // Use a reasonable gas price (20 gwei)
-        let gas_price = U256::from(20_000_000_000u64);
-        let total_cost = gas_price * U256::from(gas_limit);
-
-        Ok(total_cost.to::<u128>())
+        estimator.estimate_fee(tx).await
     }
 }
 
@@ -545,6 +693,7 @@ mod tests {
             max_priority_fee_per_gas: Some(U256::from(2_000_000_000u64)), // 2 Gwei
             max_fee_per_gas: Some(U256::from(20_000_000_000u64)),         // 20 Gwei
             gas_price: Some(U256::from(15_000_000_000u64)),               // 15 Gwei
+            ..Default::default()
         };
 
         assert_eq!(config.gas_limit_multiplier, 1.5);
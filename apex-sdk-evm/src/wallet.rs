@@ -117,6 +117,40 @@ impl Wallet {
         })
     }
 
+    /// Create a wallet from a mnemonic phrase and a BIP-32 derivation path
+    ///
+    /// # Arguments
+    /// * `mnemonic` - The BIP-39 mnemonic phrase
+    /// * `path` - The derivation path, e.g. `m/44'/60'/0'/0/1`
+    ///
+    /// # Example
+    /// ```no_run
+    /// use apex_sdk_evm::wallet::Wallet;
+    ///
+    /// let wallet = Wallet::from_mnemonic_with_path(
+    ///     "test test test test test test test test test test test junk",
+    ///     "m/44'/60'/1'/0/0"
+    /// ).unwrap();
+    /// ```
+    pub fn from_mnemonic_with_path(mnemonic: &str, path: &str) -> Result<Self, Error> {
+        let signer = MnemonicBuilder::<English>::default()
+            .phrase(mnemonic)
+            .derivation_path(path)
+            .map_err(|e| Error::Other(format!("Invalid derivation path '{}': {}", path, e)))?
+            .build()
+            .map_err(|e| Error::Other(format!("Failed to build wallet from mnemonic: {}", e)))?;
+
+        let address = signer.address();
+
+        tracing::info!("Loaded wallet from mnemonic at path {}: {}", path, address);
+
+        Ok(Self {
+            inner: signer,
+            address,
+            chain_id: None,
+        })
+    }
+
     /// Create a wallet with a specific chain ID
     ///
     /// This is important for EIP-155 replay protection
@@ -355,6 +389,21 @@ mod tests {
         assert_eq!(wallet.address().len(), 42);
     }
 
+    #[test]
+    fn test_from_mnemonic_with_path() {
+        let mnemonic = "test test test test test test test test test test test junk";
+
+        let by_index = Wallet::from_mnemonic(mnemonic, 1).unwrap();
+        let by_path = Wallet::from_mnemonic_with_path(mnemonic, "m/44'/60'/0'/0/1").unwrap();
+        assert_eq!(by_index.address(), by_path.address());
+        assert_eq!(
+            by_path.address().to_lowercase(),
+            "0x70997970c51812dc3a010c7d01b50e0d17dc79c8"
+        );
+
+        assert!(Wallet::from_mnemonic_with_path(mnemonic, "not/a/path").is_err());
+    }
+
     #[test]
     fn test_wallet_with_chain_id() {
         let wallet = Wallet::new_random().with_chain_id(1);
@@ -0,0 +1,182 @@
+//! Fee oracle tests against a mocked `eth_feeHistory` endpoint
+
+use alloy::primitives::{Address as EthAddress, U256};
+use alloy::providers::ProviderBuilder;
+use apex_sdk_core::FeeEstimator;
+use apex_sdk_evm::transaction::GasConfig;
+use apex_sdk_evm::{
+    EvmFeeEstimator, FeeOracle, FeeOracleConfig, FeeSpeed, ProviderType, TransactionExecutor,
+};
+use wiremock::{
+    matchers::{body_string_contains, method},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const GWEI: u64 = 1_000_000_000;
+
+async fn mock_fee_history(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(body_string_contains("eth_feeHistory"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "result": {
+                "oldestBlock": "0x10",
+                "baseFeePerGas": [
+                    format!("0x{:x}", 20 * GWEI),
+                    format!("0x{:x}", 21 * GWEI),
+                    format!("0x{:x}", 22 * GWEI)
+                ],
+                "gasUsedRatio": [0.6, 0.7],
+                "reward": [
+                    [format!("0x{:x}", GWEI), format!("0x{:x}", 2 * GWEI), format!("0x{:x}", 4 * GWEI)],
+                    [format!("0x{:x}", GWEI), format!("0x{:x}", 2 * GWEI), format!("0x{:x}", 6 * GWEI)]
+                ]
+            }
+        })))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn test_fee_oracle_tiers_from_rpc() {
+    let server = MockServer::start().await;
+    mock_fee_history(&server).await;
+
+    let provider = ProviderBuilder::new().connect_http(server.uri().parse().unwrap());
+    let estimates = FeeOracle::new(provider).estimate().await.unwrap();
+
+    assert_eq!(estimates.base_fee_per_gas, U256::from(21 * GWEI));
+    assert_eq!(estimates.next_base_fee_per_gas, U256::from(22 * GWEI));
+    assert_eq!(
+        estimates.tier(FeeSpeed::Slow).max_priority_fee_per_gas,
+        U256::from(GWEI)
+    );
+    assert_eq!(
+        estimates.tier(FeeSpeed::Standard).max_priority_fee_per_gas,
+        U256::from(2 * GWEI)
+    );
+    assert_eq!(
+        estimates.tier(FeeSpeed::Fast).max_fee_per_gas,
+        U256::from(44 * GWEI + 5 * GWEI)
+    );
+}
+
+#[tokio::test]
+async fn test_fee_oracle_polygon_floor_from_rpc() {
+    let server = MockServer::start().await;
+    mock_fee_history(&server).await;
+
+    let provider = ProviderBuilder::new().connect_http(server.uri().parse().unwrap());
+    let estimates = FeeOracle::new(provider)
+        .with_config(FeeOracleConfig::for_chain_id(137))
+        .estimate()
+        .await
+        .unwrap();
+
+    for speed in FeeSpeed::ALL {
+        assert_eq!(
+            estimates.tier(speed).max_priority_fee_per_gas,
+            U256::from(30 * GWEI)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_fee_estimator_rejects_invalid_metadata_without_rpc() {
+    let server = MockServer::start().await;
+    let provider = ProviderBuilder::new().connect_http(server.uri().parse().unwrap());
+    let estimator = EvmFeeEstimator::new(provider);
+
+    assert!(estimator.estimate_fee(&[]).await.is_err());
+    assert!(estimator
+        .estimate_fee(b"mock transaction data")
+        .await
+        .is_err());
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_fee_estimator_passes_sender_to_estimate_gas() {
+    let server = MockServer::start().await;
+    mock_fee_history(&server).await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("eth_estimateGas"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "result": "0x5208"
+        })))
+        .mount(&server)
+        .await;
+
+    let sender: EthAddress = "0x1111111111111111111111111111111111111111"
+        .parse()
+        .unwrap();
+    let provider = ProviderBuilder::new().connect_http(server.uri().parse().unwrap());
+    let estimator = EvmFeeEstimator::new(provider)
+        .with_sender(sender)
+        .with_gas_multiplier(1.0);
+
+    // Native transfer metadata: [0x00, to (20 bytes), value (32 bytes)]
+    let mut metadata = vec![0x00];
+    metadata.extend_from_slice(&[0x22; 20]);
+    metadata.extend_from_slice(&[0u8; 32]);
+
+    let fee = estimator.estimate_fee(&metadata).await.unwrap();
+    assert_eq!(fee, 21_000 * u128::from(22 * GWEI + 2 * GWEI));
+
+    let requests = server.received_requests().await.unwrap();
+    let estimate = requests
+        .iter()
+        .map(|r| String::from_utf8_lossy(&r.body).to_string())
+        .find(|body| body.contains("eth_estimateGas"))
+        .unwrap();
+    assert!(estimate.contains("\"from\":\"0x1111111111111111111111111111111111111111\""));
+}
+
+#[tokio::test]
+async fn test_executor_fee_matches_fee_estimator() {
+    let server = MockServer::start().await;
+    mock_fee_history(&server).await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("eth_estimateGas"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "result": "0x186a0"
+        })))
+        .mount(&server)
+        .await;
+
+    let sender: EthAddress = "0x1111111111111111111111111111111111111111"
+        .parse()
+        .unwrap();
+    let provider = ProviderBuilder::new().connect_http(server.uri().parse().unwrap());
+    let executor = TransactionExecutor::new(ProviderType::new(provider.clone()))
+        .with_gas_config(GasConfig {
+            gas_limit_multiplier: 1.0,
+            ..Default::default()
+        })
+        .with_sender(sender);
+    let estimator = EvmFeeEstimator::new(provider)
+        .with_sender(sender)
+        .with_gas_multiplier(1.0);
+
+    // Short contract call metadata whose byte length would suggest a transfer
+    let mut metadata = vec![0x02];
+    metadata.extend_from_slice(&[0x22; 20]);
+    metadata.extend_from_slice(&[0u8; 32]);
+    metadata.extend_from_slice(&[0xa9, 0x05, 0x9c, 0xbb]);
+
+    let fee = executor.estimate_fee(&metadata).await.unwrap();
+    assert_eq!(fee, 100_000 * u128::from(22 * GWEI + 2 * GWEI));
+    assert_eq!(fee, estimator.estimate_fee(&metadata).await.unwrap());
+
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests
+        .iter()
+        .map(|r| String::from_utf8_lossy(&r.body).to_string())
+        .filter(|body| body.contains("eth_estimateGas"))
+        .all(|body| body.contains("\"from\":\"0x1111111111111111111111111111111111111111\"")));
+}
@@ -0,0 +1,212 @@
+//! L2 fee estimation tests against mocked rollup RPC endpoints
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{Address as EthAddress, U256};
+use alloy::providers::ProviderBuilder;
+use alloy::rpc::types::TransactionRequest;
+use apex_sdk_evm::transaction::GasConfig;
+use apex_sdk_evm::{FeeModel, L2FeeEstimator, ProviderType, TransactionExecutor};
+use wiremock::{
+    matchers::{body_string_contains, method},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const GWEI: u64 = 1_000_000_000;
+
+async fn mock_rpc(server: &MockServer, rpc_method: &str, result: serde_json::Value) {
+    Mock::given(method("POST"))
+        .and(body_string_contains(rpc_method))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "result": result
+        })))
+        .mount(server)
+        .await;
+}
+
+async fn mock_fee_history(server: &MockServer) {
+    mock_rpc(
+        server,
+        "eth_feeHistory",
+        serde_json::json!({
+            "oldestBlock": "0x1",
+            "baseFeePerGas": [format!("0x{:x}", GWEI), format!("0x{:x}", GWEI)],
+            "gasUsedRatio": [0.5],
+            "reward": [["0x1", "0x1", "0x1"]]
+        }),
+    )
+    .await;
+}
+
+fn word(value: u128) -> String {
+    format!("{:064x}", value)
+}
+
+fn recipient() -> EthAddress {
+    "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEbD"
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_op_stack_l1_fee_is_added_to_total() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "eth_estimateGas", serde_json::json!("0x5208")).await;
+    mock_fee_history(&server).await;
+    let l1_fee: u128 = 100_000_000_000_000; // 0.0001 ETH
+    mock_rpc(
+        &server,
+        "eth_call",
+        serde_json::json!(format!("0x{}", word(l1_fee))),
+    )
+    .await;
+
+    let provider = ProviderBuilder::new().connect_http(server.uri().parse().unwrap());
+    let executor =
+        TransactionExecutor::new(ProviderType::new(provider)).with_gas_config(GasConfig {
+            fee_model: FeeModel::OpStack,
+            ..Default::default()
+        });
+
+    let estimate = executor
+        .estimate_gas(
+            EthAddress::ZERO,
+            Some(recipient()),
+            Some(U256::from(1)),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let execution = U256::from(25_200u64) * U256::from(2 * GWEI + 1);
+    assert_eq!(estimate.l1_data_fee, Some(U256::from(l1_fee)));
+    assert_eq!(estimate.execution_cost(), execution);
+    assert_eq!(estimate.total_cost, execution + U256::from(l1_fee));
+}
+
+#[tokio::test]
+async fn test_arbitrum_gas_estimate_components() {
+    let server = MockServer::start().await;
+    let output = format!(
+        "0x{}{}{}{}",
+        word(600_000),    // gasEstimate
+        word(500_000),    // gasEstimateForL1
+        word(10_000_000), // baseFee (0.01 gwei)
+        word(30 * GWEI as u128)
+    );
+    mock_rpc(&server, "eth_call", serde_json::json!(output)).await;
+
+    let provider = ProviderBuilder::new().connect_http(server.uri().parse().unwrap());
+    let tx = TransactionRequest::default()
+        .with_to(recipient())
+        .with_input(vec![0u8; 4]);
+    let fees = L2FeeEstimator::new(provider, FeeModel::Arbitrum)
+        .estimate(&tx)
+        .await
+        .unwrap();
+
+    assert_eq!(fees.gas_limit, Some(U256::from(600_000u64)));
+    assert_eq!(fees.l1_gas, Some(U256::from(500_000u64)));
+    assert_eq!(
+        fees.l1_data_fee,
+        Some(U256::from(500_000u64) * U256::from(10_000_000u64))
+    );
+}
+
+#[tokio::test]
+async fn test_arbitrum_cost_split_uses_one_price() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "eth_estimateGas", serde_json::json!("0x5208")).await;
+    mock_fee_history(&server).await;
+    let output = format!(
+        "0x{}{}{}{}",
+        word(600_000),    // gasEstimate
+        word(500_000),    // gasEstimateForL1
+        word(10_000_000), // baseFee (0.01 gwei)
+        word(30 * GWEI as u128)
+    );
+    mock_rpc(&server, "eth_call", serde_json::json!(output)).await;
+
+    let provider = ProviderBuilder::new().connect_http(server.uri().parse().unwrap());
+    let executor =
+        TransactionExecutor::new(ProviderType::new(provider)).with_gas_config(GasConfig {
+            fee_model: FeeModel::Arbitrum,
+            ..Default::default()
+        });
+
+    let estimate = executor
+        .estimate_gas(EthAddress::ZERO, Some(recipient()), None, None)
+        .await
+        .unwrap();
+
+    // Both parts of the 1.2x buffered limit are priced at the same max fee
+    let price = U256::from(2 * GWEI + 1);
+    assert_eq!(estimate.gas_limit, U256::from(720_000u64));
+    assert_eq!(estimate.l1_data_fee, Some(U256::from(600_000u64) * price));
+    assert_eq!(estimate.execution_cost(), U256::from(120_000u64) * price);
+    assert_eq!(estimate.total_cost, estimate.gas_limit * price);
+}
+
+#[tokio::test]
+async fn test_l2_estimator_failure_falls_back_to_plain_estimate() {
+    let server = MockServer::start().await;
+    mock_rpc(&server, "eth_estimateGas", serde_json::json!("0x5208")).await;
+    mock_fee_history(&server).await;
+    Mock::given(method("POST"))
+        .and(body_string_contains("eth_call"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "error": { "code": -32000, "message": "execution reverted" }
+        })))
+        .mount(&server)
+        .await;
+
+    let provider = ProviderBuilder::new().connect_http(server.uri().parse().unwrap());
+    let executor =
+        TransactionExecutor::new(ProviderType::new(provider)).with_gas_config(GasConfig {
+            fee_model: FeeModel::OpStack,
+            ..Default::default()
+        });
+
+    let estimate = executor
+        .estimate_gas(EthAddress::ZERO, Some(recipient()), None, None)
+        .await
+        .unwrap();
+
+    assert_eq!(estimate.l1_data_fee, None);
+    assert_eq!(
+        estimate.total_cost,
+        U256::from(25_200u64) * U256::from(2 * GWEI + 1)
+    );
+}
+
+#[tokio::test]
+async fn test_zksync_estimate_fee() {
+    let server = MockServer::start().await;
+    mock_rpc(
+        &server,
+        "zks_estimateFee",
+        serde_json::json!({
+            "gas_limit": "0x2dc6c0",
+            "gas_per_pubdata_limit": "0xc350",
+            "max_fee_per_gas": "0x2b275d0",
+            "max_priority_fee_per_gas": "0x0"
+        }),
+    )
+    .await;
+
+    let provider = ProviderBuilder::new().connect_http(server.uri().parse().unwrap());
+    let tx = TransactionRequest::default().with_to(recipient());
+    let fees = L2FeeEstimator::new(provider, FeeModel::ZkSync)
+        .estimate(&tx)
+        .await
+        .unwrap();
+
+    assert_eq!(fees.gas_limit, Some(U256::from(3_000_000u64)));
+    assert_eq!(fees.max_fee_per_gas, Some(U256::from(45_250_000u64)));
+    assert_eq!(fees.max_priority_fee_per_gas, Some(U256::ZERO));
+    assert_eq!(fees.l1_data_fee, None);
+    assert_eq!(fees.gas_per_pubdata_limit, Some(U256::from(50_000u64)));
+}
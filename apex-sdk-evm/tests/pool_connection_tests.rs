@@ -31,6 +31,7 @@ fn test_pool_config_custom() {
         health_check_timeout_secs: 10,
         max_failures: 5,
         unhealthy_retry_delay_secs: 120,
+        ..Default::default()
     };
 
     assert_eq!(config.max_connections_per_endpoint, 20);
@@ -48,6 +49,7 @@ fn test_pool_config_clone() {
         health_check_timeout_secs: 8,
         max_failures: 4,
         unhealthy_retry_delay_secs: 90,
+        ..Default::default()
     };
 
     let cloned = config.clone();
@@ -80,6 +82,7 @@ fn test_pool_config_edge_cases() {
         health_check_timeout_secs: 1,
         max_failures: 1,
         unhealthy_retry_delay_secs: 1,
+        ..Default::default()
     };
 
     assert_eq!(config.max_connections_per_endpoint, 1);
@@ -92,6 +95,7 @@ fn test_pool_config_edge_cases() {
         health_check_timeout_secs: 300,
         max_failures: 100,
         unhealthy_retry_delay_secs: 7200,
+        ..Default::default()
     };
 
     assert_eq!(config.max_connections_per_endpoint, 1000);
@@ -121,6 +125,7 @@ fn test_endpoint_health_clone() {
         last_failure: None,
         failure_count: 0,
         avg_response_time_ms: 100,
+        ..Default::default()
     };
 
     let cloned = health.clone();
@@ -139,6 +144,7 @@ fn test_endpoint_health_states() {
         last_failure: None,
         failure_count: 0,
         avg_response_time_ms: 50,
+        ..Default::default()
     };
 
     assert!(healthy.is_healthy);
@@ -151,6 +157,7 @@ fn test_endpoint_health_states() {
         last_failure: Some(Instant::now()),
         failure_count: 5,
         avg_response_time_ms: 0,
+        ..Default::default()
     };
 
     assert!(!unhealthy.is_healthy);
@@ -166,6 +173,7 @@ fn test_endpoint_health_response_time_tracking() {
         last_failure: None,
         failure_count: 0,
         avg_response_time_ms: 150,
+        ..Default::default()
     };
 
     assert_eq!(health.avg_response_time_ms, 150);
@@ -467,6 +475,7 @@ fn test_health_state_unhealthy_to_healthy() {
         last_failure: Some(Instant::now()),
         last_success: None,
         avg_response_time_ms: 0,
+        ..Default::default()
     };
 
     assert!(!health.is_healthy);
@@ -489,6 +498,7 @@ fn test_health_state_partial_recovery() {
         last_failure: Some(Instant::now()),
         last_success: None,
         avg_response_time_ms: 0,
+        ..Default::default()
     };
 
     // One successful request doesn't immediately make it healthy
@@ -620,6 +630,7 @@ async fn test_pool_with_custom_config_real() {
         health_check_timeout_secs: 10,
         max_failures: 5,
         unhealthy_retry_delay_secs: 120,
+        ..Default::default()
     };
 
     let pool = ConnectionPool::with_config(endpoints, config).await;
@@ -96,6 +96,29 @@ impl BlockQuery {
     pub async fn get_block_by_hash(&self, hash_hex: &str) -> Result<BlockInfo, Error> {
         debug!("Fetching block by hash: {}", hash_hex);
 
+        let block = self.block_at(hash_hex).await?;
+        self.parse_block_info(block).await
+    }
+
+    /// Get detailed block information by block hash
+    ///
+    /// Unlike [`get_detailed_block`](Self::get_detailed_block), this also works
+    /// for best blocks that are not finalized yet.
+    pub async fn get_detailed_block_by_hash(
+        &self,
+        hash_hex: &str,
+    ) -> Result<DetailedBlockInfo, Error> {
+        debug!("Fetching detailed block info for block: {}", hash_hex);
+
+        let block = self.block_at(hash_hex).await?;
+        self.parse_detailed_block(block).await
+    }
+
+    /// Fetch a block by its hex-encoded hash
+    async fn block_at(
+        &self,
+        hash_hex: &str,
+    ) -> Result<subxt::blocks::Block<PolkadotConfig, OnlineClient<PolkadotConfig>>, Error> {
         // Parse the hex string to H256
         let hash_hex = hash_hex.trim_start_matches("0x");
         let hash_bytes = hex::decode(hash_hex)
@@ -112,14 +135,11 @@ impl BlockQuery {
         let block_hash: subxt::utils::H256 = hash_array.into();
 
         // Query the block
-        let block = self
-            .client
+        self.client
             .blocks()
             .at(block_hash)
             .await
-            .map_err(|e| Error::Connection(format!("Failed to get block: {}", e)))?;
-
-        self.parse_block_info(block).await
+            .map_err(|e| Error::Connection(format!("Failed to get block: {}", e)))
     }
 
     /// Get detailed block information including extrinsics and events
@@ -173,6 +193,14 @@ impl BlockQuery {
             current_block
         };
 
+        self.parse_detailed_block(block).await
+    }
+
+    /// Parse block information, extrinsics and events from a subxt Block
+    async fn parse_detailed_block(
+        &self,
+        block: subxt::blocks::Block<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    ) -> Result<DetailedBlockInfo, Error> {
         // Parse basic block info
         let basic_info = self.parse_block_info(block.clone()).await?;
 
@@ -376,33 +404,38 @@ impl BlockQuery {
         Ok(extrinsic_infos)
     }
 
-    /// Extract all events from a block
+    /// Extract all events from a block, including those emitted outside
+    /// extrinsics (on initialize and finalize), with their decoded fields
     async fn extract_block_events(
         &self,
         block: &subxt::blocks::Block<PolkadotConfig, OnlineClient<PolkadotConfig>>,
     ) -> Result<Vec<BlockEvent>, Error> {
-        let extrinsics = block
-            .extrinsics()
+        use subxt::events::Phase;
+
+        let events = block
+            .events()
             .await
-            .map_err(|e| Error::Transaction(format!("Failed to get extrinsics: {}", e)))?;
+            .map_err(|e| Error::Transaction(format!("Failed to get events: {}", e)))?;
+        let metadata = self.client.metadata();
 
         let mut all_events = Vec::new();
-        let mut event_index = 0u32;
-
-        for ext_details in extrinsics.iter() {
-            let extrinsic_index = ext_details.index();
-
-            if let Ok(events) = ext_details.events().await {
-                for event in events.iter().flatten() {
-                    all_events.push(BlockEvent {
-                        index: event_index,
-                        extrinsic_index: Some(extrinsic_index),
-                        pallet: event.pallet_name().to_string(),
-                        event: event.variant_name().to_string(),
-                    });
-                    event_index += 1;
-                }
-            }
+        for event in events.iter().flatten() {
+            let extrinsic_index = match event.phase() {
+                Phase::ApplyExtrinsic(index) => Some(index),
+                Phase::Initialization | Phase::Finalization => None,
+            };
+            let fields = event
+                .field_values()
+                .ok()
+                .map(|values| crate::storage::composite_to_json(&values, metadata.types()));
+
+            all_events.push(BlockEvent {
+                index: event.index(),
+                extrinsic_index,
+                pallet: event.pallet_name().to_string(),
+                event: event.variant_name().to_string(),
+                fields,
+            });
         }
 
         Ok(all_events)
@@ -413,19 +446,12 @@ impl BlockQuery {
         &self,
         block: &subxt::blocks::Block<PolkadotConfig, OnlineClient<PolkadotConfig>>,
     ) -> Result<u32, Error> {
-        let extrinsics = block
-            .extrinsics()
+        let events = block
+            .events()
             .await
-            .map_err(|e| Error::Transaction(format!("Failed to get extrinsics: {}", e)))?;
-
-        let mut count = 0u32;
-        for ext_details in extrinsics.iter() {
-            if let Ok(events) = ext_details.events().await {
-                count += events.iter().count() as u32;
-            }
-        }
+            .map_err(|e| Error::Transaction(format!("Failed to get events: {}", e)))?;
 
-        Ok(count)
+        Ok(events.len())
     }
 }
 
@@ -128,6 +128,9 @@ impl CacheConfig {
 }
 
 /// Multi-level cache for Substrate queries
+///
+/// Clones share the same underlying caches.
+#[derive(Clone)]
 pub struct Cache {
     config: CacheConfig,
     storage_cache: Arc<RwLock<LruCache<String, CacheEntry<Vec<u8>>>>>,
@@ -299,6 +302,11 @@ impl Cache {
         cache.put(hash_key, entry);
     }
 
+    /// Drop all metadata entries, e.g. after a runtime upgrade
+    pub fn invalidate_metadata(&self) {
+        self.metadata_cache.write().clear();
+    }
+
     /// Clear all caches
     pub fn clear(&self) {
         self.storage_cache.write().clear();
@@ -556,6 +564,19 @@ mod tests {
         assert_eq!(cache.get_metadata("pallet1"), Some("metadata".to_string()));
     }
 
+    #[test]
+    fn test_invalidate_metadata() {
+        let cache = Cache::new();
+        let shared = cache.clone();
+
+        cache.put_metadata("pallet1".to_string(), "metadata".to_string());
+        cache.put_balance("alice".to_string(), 100);
+        shared.invalidate_metadata();
+
+        assert_eq!(cache.get_metadata("pallet1"), None);
+        assert_eq!(cache.get_balance("alice"), Some(100));
+    }
+
     #[test]
     fn test_rpc_cache() {
         let cache = Cache::new();
@@ -0,0 +1,464 @@
+//! Type registry based encoding of contract arguments, return values and events
+
+use super::{ContractEvent, ContractMetadata, MessageArg, TypeRef};
+use crate::{Error, Result};
+use scale_info::{PortableRegistry, TypeDef, TypeDefPrimitive};
+use subxt::dynamic::{DecodedValue, Value};
+use subxt::ext::scale_value::{self, ValueDef};
+
+/// Convert a JSON value into a dynamic value of the given registry type.
+///
+/// This is the inverse of [`value_to_json`](crate::storage::value_to_json):
+/// objects fill named fields, arrays fill unnamed fields, sequences and tuples,
+/// and single-field tuples accept their inner value directly. Byte sequences and
+/// arrays accept `0x` hex strings, and 32-byte arrays also accept SS58 addresses.
+/// Variants are written as `"Name"` or `{"Name": fields}`; `Option` also accepts
+/// `null` and a bare value. Integers may be numbers or decimal strings.
+pub fn json_to_value(
+    json: &serde_json::Value,
+    ty: u32,
+    registry: &PortableRegistry,
+) -> Result<Value> {
+    use serde_json::Value as Json;
+
+    let resolved = registry
+        .resolve(ty)
+        .ok_or_else(|| Error::Encoding(format!("Type {} not found in contract registry", ty)))?;
+    let mismatch = |expected: &str| {
+        Error::Encoding(format!(
+            "Expected {} for type {} but got {}",
+            expected,
+            type_name(resolved, ty),
+            json
+        ))
+    };
+
+    match &resolved.type_def {
+        TypeDef::Composite(composite) => {
+            let fields = &composite.fields;
+            if fields.iter().all(|f| f.name.is_some()) && !fields.is_empty() {
+                let object = json.as_object().ok_or_else(|| mismatch("an object"))?;
+                let values = fields
+                    .iter()
+                    .map(|f| {
+                        let name = f.name.as_deref().unwrap_or_default();
+                        let field = object
+                            .get(name)
+                            .ok_or_else(|| Error::Encoding(format!("Missing field '{}'", name)))?;
+                        Ok((name.to_string(), json_to_value(field, f.ty.id, registry)?))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::named_composite(values))
+            } else if fields.len() == 1 {
+                let inner = json_to_value(json, fields[0].ty.id, registry)?;
+                Ok(Value::unnamed_composite([inner]))
+            } else {
+                let items = json_items(json, fields.len()).ok_or_else(|| mismatch("an array"))?;
+                let values = fields
+                    .iter()
+                    .zip(items)
+                    .map(|(f, item)| json_to_value(item, f.ty.id, registry))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::unnamed_composite(values))
+            }
+        }
+        TypeDef::Variant(variant) => {
+            let is_option = resolved.path.segments.last().map(String::as_str) == Some("Option");
+            let (name, payload) = match json {
+                Json::String(name) => (name.as_str(), None),
+                Json::Object(object) if object.len() == 1 => {
+                    let (name, payload) = object.iter().next().expect("one entry");
+                    if variant.variants.iter().any(|v| &v.name == name) {
+                        (name.as_str(), Some(payload))
+                    } else if is_option {
+                        ("Some", Some(json))
+                    } else {
+                        return Err(mismatch("a variant"));
+                    }
+                }
+                Json::Null if is_option => ("None", None),
+                _ if is_option => ("Some", Some(json)),
+                _ => return Err(mismatch("a variant")),
+            };
+
+            let selected = variant
+                .variants
+                .iter()
+                .find(|v| v.name == name)
+                .ok_or_else(|| Error::Encoding(format!("Unknown variant '{}'", name)))?;
+
+            let fields = &selected.fields;
+            if fields.is_empty() {
+                return Ok(Value::unnamed_variant(name, []));
+            }
+            let payload = payload
+                .ok_or_else(|| Error::Encoding(format!("Variant '{}' needs fields", name)))?;
+            if fields.iter().all(|f| f.name.is_some()) {
+                let object = payload
+                    .as_object()
+                    .ok_or_else(|| mismatch("an object of variant fields"))?;
+                let values = fields
+                    .iter()
+                    .map(|f| {
+                        let field_name = f.name.as_deref().unwrap_or_default();
+                        let field = object.get(field_name).ok_or_else(|| {
+                            Error::Encoding(format!("Missing field '{}'", field_name))
+                        })?;
+                        Ok((
+                            field_name.to_string(),
+                            json_to_value(field, f.ty.id, registry)?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::named_variant(name, values))
+            } else if fields.len() == 1 {
+                let inner = json_to_value(payload, fields[0].ty.id, registry)?;
+                Ok(Value::unnamed_variant(name, [inner]))
+            } else {
+                let items =
+                    json_items(payload, fields.len()).ok_or_else(|| mismatch("an array"))?;
+                let values = fields
+                    .iter()
+                    .zip(items)
+                    .map(|(f, item)| json_to_value(item, f.ty.id, registry))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Value::unnamed_variant(name, values))
+            }
+        }
+        TypeDef::Sequence(seq) => {
+            if is_u8(seq.type_param.id, registry) {
+                if let Json::String(s) = json {
+                    return Ok(Value::from_bytes(parse_hex(s)?));
+                }
+            }
+            let items = json.as_array().ok_or_else(|| mismatch("an array"))?;
+            let values = items
+                .iter()
+                .map(|item| json_to_value(item, seq.type_param.id, registry))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Value::unnamed_composite(values))
+        }
+        TypeDef::Array(array) => {
+            if is_u8(array.type_param.id, registry) {
+                if let Json::String(s) = json {
+                    let bytes = if s.starts_with("0x") || array.len != 32 {
+                        parse_hex(s)?
+                    } else {
+                        parse_ss58(s)?
+                    };
+                    if bytes.len() != array.len as usize {
+                        return Err(mismatch(&format!("{} bytes", array.len)));
+                    }
+                    return Ok(Value::from_bytes(bytes));
+                }
+            }
+            let items = json_items(json, array.len as usize).ok_or_else(|| mismatch("an array"))?;
+            let values = items
+                .into_iter()
+                .map(|item| json_to_value(item, array.type_param.id, registry))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Value::unnamed_composite(values))
+        }
+        TypeDef::Tuple(tuple) => {
+            if tuple.fields.len() == 1 {
+                let inner = json_to_value(json, tuple.fields[0].id, registry)?;
+                return Ok(Value::unnamed_composite([inner]));
+            }
+            let items = json_items(json, tuple.fields.len()).ok_or_else(|| mismatch("an array"))?;
+            let values = tuple
+                .fields
+                .iter()
+                .zip(items)
+                .map(|(f, item)| json_to_value(item, f.id, registry))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Value::unnamed_composite(values))
+        }
+        TypeDef::Primitive(primitive) => match primitive {
+            TypeDefPrimitive::Bool => json
+                .as_bool()
+                .map(Value::bool)
+                .ok_or_else(|| mismatch("a bool")),
+            TypeDefPrimitive::Char => json
+                .as_str()
+                .and_then(|s| {
+                    let mut chars = s.chars();
+                    let c = chars.next()?;
+                    chars.next().is_none().then_some(c)
+                })
+                .map(Value::char)
+                .ok_or_else(|| mismatch("a single character")),
+            TypeDefPrimitive::Str => json
+                .as_str()
+                .map(Value::string)
+                .ok_or_else(|| mismatch("a string")),
+            TypeDefPrimitive::U8
+            | TypeDefPrimitive::U16
+            | TypeDefPrimitive::U32
+            | TypeDefPrimitive::U64
+            | TypeDefPrimitive::U128 => parse_unsigned(json)
+                .map(Value::u128)
+                .ok_or_else(|| mismatch("an unsigned integer")),
+            TypeDefPrimitive::I8
+            | TypeDefPrimitive::I16
+            | TypeDefPrimitive::I32
+            | TypeDefPrimitive::I64
+            | TypeDefPrimitive::I128 => parse_signed(json)
+                .map(Value::i128)
+                .ok_or_else(|| mismatch("an integer")),
+            TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => {
+                let s = json.as_str().ok_or_else(|| mismatch("a 0x hex string"))?;
+                let bytes: [u8; 32] = parse_hex(s)?
+                    .try_into()
+                    .map_err(|_| mismatch("32 bytes of hex"))?;
+                Ok(Value::primitive(
+                    if matches!(primitive, TypeDefPrimitive::U256) {
+                        scale_value::Primitive::U256(bytes)
+                    } else {
+                        scale_value::Primitive::I256(bytes)
+                    },
+                ))
+            }
+        },
+        TypeDef::Compact(compact) => json_to_value(json, compact.type_param.id, registry),
+        TypeDef::BitSequence(_) => {
+            let bits = json
+                .as_array()
+                .and_then(|items| {
+                    items
+                        .iter()
+                        .map(|b| b.as_bool())
+                        .collect::<Option<Vec<_>>>()
+                })
+                .ok_or_else(|| mismatch("an array of bools"))?;
+            Ok(Value::bit_sequence(bits.into_iter().collect()))
+        }
+    }
+}
+
+/// Encode a value as the given registry type
+pub(super) fn encode_value(value: &Value, ty: u32, registry: &PortableRegistry) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    scale_value::scale::encode_as_type(value, ty, registry, &mut out)
+        .map_err(|e| Error::Encoding(format!("Failed to encode contract argument: {}", e)))?;
+    Ok(out)
+}
+
+/// Encode selector and arguments for a message or constructor
+pub(super) fn encode_call_data(
+    selector: [u8; 4],
+    args: &[MessageArg],
+    values: &[Value],
+    registry: &PortableRegistry,
+) -> Result<Vec<u8>> {
+    if values.len() != args.len() {
+        return Err(Error::Encoding(format!(
+            "Expected {} arguments but got {}",
+            args.len(),
+            values.len()
+        )));
+    }
+
+    let mut call_data = selector.to_vec();
+    for (arg, value) in args.iter().zip(values) {
+        let encoded = encode_value(value, arg.type_ref.ty, registry)
+            .map_err(|e| Error::Encoding(format!("Argument '{}': {}", arg.label, e)))?;
+        call_data.extend_from_slice(&encoded);
+    }
+    Ok(call_data)
+}
+
+/// Convert JSON arguments into dynamic values of the declared argument types
+pub(super) fn json_args(
+    args: &[MessageArg],
+    json: &[serde_json::Value],
+    registry: &PortableRegistry,
+) -> Result<Vec<Value>> {
+    if json.len() != args.len() {
+        return Err(Error::Encoding(format!(
+            "Expected {} arguments but got {}",
+            args.len(),
+            json.len()
+        )));
+    }
+
+    args.iter()
+        .zip(json)
+        .map(|(arg, json)| {
+            json_to_value(json, arg.type_ref.ty, registry)
+                .map_err(|e| Error::Encoding(format!("Argument '{}': {}", arg.label, e)))
+        })
+        .collect()
+}
+
+/// Decode a message's return data, unwrapping ink!'s `Result<T, LangError>`
+pub(super) fn decode_return(
+    metadata: &ContractMetadata,
+    return_type: Option<&TypeRef>,
+    data: &[u8],
+) -> Result<DecodedValue> {
+    let Some(return_type) = return_type else {
+        return Ok(Value::unnamed_composite([]).map_context(|_| 0));
+    };
+
+    let registry = &metadata.registry;
+    let mut input = data;
+    let value = scale_value::scale::decode_as_type(&mut input, return_type.ty, registry)
+        .map_err(|e| Error::Encoding(format!("Failed to decode return value: {}", e)))?;
+
+    if !is_lang_result(metadata, return_type.ty) {
+        return Ok(value);
+    }
+
+    match value.value {
+        ValueDef::Variant(variant) if variant.name == "Ok" => Ok(variant
+            .values
+            .into_values()
+            .next()
+            .unwrap_or_else(|| Value::unnamed_composite([]).map_context(|_| return_type.ty))),
+        ValueDef::Variant(variant) => {
+            let reason = variant
+                .values
+                .values()
+                .next()
+                .and_then(|e| match &e.value {
+                    ValueDef::Variant(v) => Some(v.name.clone()),
+                    _ => None,
+                })
+                .unwrap_or(variant.name);
+            Err(Error::Transaction(format!(
+                "Contract returned LangError: {}",
+                reason
+            )))
+        }
+        _ => Err(Error::Encoding(
+            "Message result is not a Result variant".to_string(),
+        )),
+    }
+}
+
+/// Whether a type is `Result<T, LangError>`, which ink! 4+ wraps every message return in
+fn is_lang_result(metadata: &ContractMetadata, ty: u32) -> bool {
+    let registry = &metadata.registry;
+    let Some(TypeDef::Variant(variant)) = registry.resolve(ty).map(|t| &t.type_def) else {
+        return false;
+    };
+    let Some(err) = variant.variants.iter().find(|v| v.name == "Err") else {
+        return false;
+    };
+    let Some(err_ty) = err.fields.first().map(|f| f.ty.id) else {
+        return false;
+    };
+
+    match &metadata.lang_error {
+        Some(lang_error) => lang_error.ty == err_ty,
+        None => registry
+            .resolve(err_ty)
+            .and_then(|t| t.path.segments.last())
+            .is_some_and(|name| name == "LangError"),
+    }
+}
+
+/// Decode a `ContractEmitted` payload into the matching event spec.
+///
+/// ink! 5 identifies events by their signature topic (the first topic);
+/// ink! 4 prefixes the data with the event's index in the spec.
+pub(super) fn decode_event(
+    metadata: &ContractMetadata,
+    data: &[u8],
+    topics: &[[u8; 32]],
+) -> Result<ContractEvent> {
+    let events = &metadata.spec.events;
+    let mut input = data;
+
+    let uses_topics = events.iter().any(|e| e.signature_topic.is_some());
+    let spec = if uses_topics {
+        let by_topic = topics
+            .first()
+            .and_then(|topic| events.iter().find(|e| e.signature_topic == Some(*topic)));
+        let anonymous = || {
+            let mut anonymous = events.iter().filter(|e| e.signature_topic.is_none());
+            match (anonymous.next(), anonymous.next()) {
+                (Some(event), None) => Some(event),
+                _ => None,
+            }
+        };
+        by_topic
+            .or_else(anonymous)
+            .ok_or_else(|| Error::Encoding("No event matches the emitted topics".to_string()))?
+    } else {
+        let (index, rest) = input
+            .split_first()
+            .ok_or_else(|| Error::Encoding("Empty contract event".to_string()))?;
+        input = rest;
+        events
+            .get(*index as usize)
+            .ok_or_else(|| Error::Encoding(format!("Unknown contract event index {}", index)))?
+    };
+
+    let mut fields = Vec::with_capacity(spec.args.len());
+    for arg in &spec.args {
+        let value =
+            scale_value::scale::decode_as_type(&mut input, arg.type_ref.ty, &metadata.registry)
+                .map_err(|e| {
+                    Error::Encoding(format!(
+                        "Failed to decode field '{}' of event {}: {}",
+                        arg.label, spec.label, e
+                    ))
+                })?;
+        fields.push((arg.label.clone(), value));
+    }
+
+    Ok(ContractEvent {
+        label: spec.label.clone(),
+        fields,
+        topics: topics.to_vec(),
+    })
+}
+
+fn json_items(json: &serde_json::Value, len: usize) -> Option<Vec<&serde_json::Value>> {
+    let items = json.as_array()?;
+    (items.len() == len).then(|| items.iter().collect())
+}
+
+fn is_u8(ty: u32, registry: &PortableRegistry) -> bool {
+    matches!(
+        registry.resolve(ty).map(|t| &t.type_def),
+        Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+    )
+}
+
+fn parse_hex(s: &str) -> Result<Vec<u8>> {
+    hex::decode(s.trim_start_matches("0x"))
+        .map_err(|e| Error::Encoding(format!("Invalid hex '{}': {}", s, e)))
+}
+
+fn parse_ss58(s: &str) -> Result<Vec<u8>> {
+    use sp_core::crypto::{AccountId32, Ss58Codec};
+    let account = AccountId32::from_ss58check(s)
+        .map_err(|e| Error::Encoding(format!("Invalid SS58 address '{}': {:?}", s, e)))?;
+    Ok(<AccountId32 as AsRef<[u8]>>::as_ref(&account).to_vec())
+}
+
+fn parse_unsigned(json: &serde_json::Value) -> Option<u128> {
+    match json {
+        serde_json::Value::Number(n) => n.as_u64().map(u128::from),
+        serde_json::Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn parse_signed(json: &serde_json::Value) -> Option<i128> {
+    match json {
+        serde_json::Value::Number(n) => n.as_i64().map(i128::from),
+        serde_json::Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+fn type_name(ty: &scale_info::Type<scale_info::form::PortableForm>, id: u32) -> String {
+    if ty.path.segments.is_empty() {
+        format!("#{}", id)
+    } else {
+        ty.path.segments.join("::")
+    }
+}
@@ -0,0 +1,392 @@
+//! Contract dry-runs through the `ContractsApi` runtime API
+//!
+//! Calls and deployments are executed against the latest finalized state
+//! without being submitted, which reports the weight and storage deposit they
+//! need together with their return (or revert) data.
+
+use super::{codec, ContractAddress, ContractMetadata, GasLimit, StorageDepositLimit, TypeRef};
+use crate::{Error, Result};
+use parity_scale_codec::{Decode, Encode};
+use subxt::dynamic::DecodedValue;
+use subxt::ext::scale_value;
+use subxt::{OnlineClient, PolkadotConfig};
+
+/// Default margin added to dry-run estimates, in percent
+pub const DEFAULT_GAS_MARGIN_PERCENT: u64 = 10;
+
+/// `ReturnFlags::REVERT` set by a contract that reverted its changes
+const REVERT_FLAG: u32 = 0x0000_0001;
+
+/// `sp_weights::Weight`, whose fields are compact-encoded in runtime APIs
+#[derive(Encode, Decode)]
+struct Weight {
+    #[codec(compact)]
+    ref_time: u64,
+    #[codec(compact)]
+    proof_size: u64,
+}
+
+impl From<Weight> for GasLimit {
+    fn from(weight: Weight) -> Self {
+        GasLimit::new(weight.ref_time, weight.proof_size)
+    }
+}
+
+/// Arguments of `ContractsApi_call`
+#[derive(Encode)]
+struct CallRequest<'a> {
+    origin: [u8; 32],
+    dest: ContractAddress,
+    value: u128,
+    gas_limit: Option<Weight>,
+    storage_deposit_limit: Option<u128>,
+    input_data: &'a [u8],
+}
+
+/// `pallet_contracts::Code`
+#[derive(Encode)]
+enum Code<'a> {
+    Upload(&'a [u8]),
+}
+
+/// Arguments of `ContractsApi_instantiate`
+#[derive(Encode)]
+struct InstantiateRequest<'a> {
+    origin: [u8; 32],
+    value: u128,
+    gas_limit: Option<Weight>,
+    storage_deposit_limit: Option<u128>,
+    code: Code<'a>,
+    data: &'a [u8],
+    salt: &'a [u8],
+}
+
+/// Storage deposit reported by a dry-run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode)]
+pub enum StorageDeposit {
+    /// Deposit refunded to the caller
+    Refund(u128),
+    /// Deposit charged from the caller
+    Charge(u128),
+}
+
+impl StorageDeposit {
+    /// Amount charged by the execution (zero for refunds)
+    pub fn charge(&self) -> u128 {
+        match self {
+            StorageDeposit::Refund(_) => 0,
+            StorageDeposit::Charge(amount) => *amount,
+        }
+    }
+
+    /// Deposit limit covering the charge plus `margin_percent`
+    pub fn limit(&self, margin_percent: u64) -> StorageDepositLimit {
+        let charge = self.charge();
+        StorageDepositLimit::Limited(
+            charge.saturating_add(charge.saturating_mul(margin_percent as u128) / 100),
+        )
+    }
+}
+
+/// How a dry-run execution ended
+#[derive(Debug, Clone)]
+pub enum DryRunOutcome {
+    /// The contract returned successfully
+    Success {
+        /// Raw return data
+        data: Vec<u8>,
+    },
+    /// The contract reverted its state changes
+    Reverted {
+        /// Raw revert data
+        data: Vec<u8>,
+        /// Revert data decoded with the message's return type, if known
+        decoded: Option<DecodedValue>,
+    },
+    /// The runtime rejected the execution with a `DispatchError`
+    Failed(String),
+}
+
+/// Result of a contract call or deployment dry-run
+#[derive(Debug, Clone)]
+pub struct DryRunResult {
+    /// Weight consumed by the execution
+    pub gas_consumed: GasLimit,
+    /// Weight required for the execution to succeed
+    pub gas_required: GasLimit,
+    /// Storage deposit charged or refunded
+    pub storage_deposit: StorageDeposit,
+    /// Debug buffer output (only filled on chains with contract debugging enabled)
+    pub debug_message: String,
+    /// How the execution ended
+    pub outcome: DryRunOutcome,
+    /// Address of the contract a deployment would create
+    pub contract_address: Option<ContractAddress>,
+}
+
+impl DryRunResult {
+    /// Whether the execution succeeded without reverting
+    pub fn is_success(&self) -> bool {
+        matches!(self.outcome, DryRunOutcome::Success { .. })
+    }
+
+    /// Return data of a successful or reverted execution
+    pub fn data(&self) -> Option<&[u8]> {
+        match &self.outcome {
+            DryRunOutcome::Success { data } | DryRunOutcome::Reverted { data, .. } => Some(data),
+            DryRunOutcome::Failed(_) => None,
+        }
+    }
+
+    /// Gas limit to submit with: `gas_required` plus `margin_percent`
+    pub fn gas_limit(&self, margin_percent: u64) -> GasLimit {
+        self.gas_required.with_margin(margin_percent)
+    }
+
+    /// Storage deposit limit to submit with: the charged deposit plus `margin_percent`
+    pub fn storage_deposit_limit(&self, margin_percent: u64) -> StorageDepositLimit {
+        self.storage_deposit.limit(margin_percent)
+    }
+
+    /// Turn a reverted or failed dry-run into an error
+    pub fn ensure_success(self) -> Result<Self> {
+        let reason = match &self.outcome {
+            DryRunOutcome::Success { .. } => return Ok(self),
+            DryRunOutcome::Reverted {
+                decoded: Some(value),
+                ..
+            } => format!("Contract reverted: {}", value),
+            DryRunOutcome::Reverted { data, .. } => {
+                format!("Contract reverted: 0x{}", hex::encode(data))
+            }
+            DryRunOutcome::Failed(error) => format!("Contract execution failed: {}", error),
+        };
+
+        if self.debug_message.is_empty() {
+            Err(Error::Transaction(reason))
+        } else {
+            Err(Error::Transaction(format!(
+                "{} (debug: {})",
+                reason, self.debug_message
+            )))
+        }
+    }
+}
+
+/// Dry-run a call to `dest` through `ContractsApi_call`
+pub(super) async fn call(
+    client: &OnlineClient<PolkadotConfig>,
+    origin: [u8; 32],
+    dest: ContractAddress,
+    value: u128,
+    input_data: &[u8],
+    metadata: Option<&ContractMetadata>,
+) -> Result<DryRunResult> {
+    let request = CallRequest {
+        origin,
+        dest,
+        value,
+        gas_limit: None,
+        storage_deposit_limit: None,
+        input_data,
+    };
+    let bytes = runtime_call(client, "ContractsApi_call", request.encode()).await?;
+
+    // Revert data is decoded with the return type of the called message
+    let return_type = metadata.and_then(|metadata| {
+        let selector = input_data.get(..4)?;
+        let message = metadata
+            .spec
+            .messages
+            .iter()
+            .find(|m| m.selector == selector)?;
+        Some((metadata, message.return_type.as_ref()?))
+    });
+
+    decode_exec_result(&bytes, false, client, return_type)
+}
+
+/// Dry-run a deployment of `code` through `ContractsApi_instantiate`
+pub(super) async fn instantiate(
+    client: &OnlineClient<PolkadotConfig>,
+    origin: [u8; 32],
+    value: u128,
+    code: &[u8],
+    data: &[u8],
+    salt: &[u8],
+    metadata: &ContractMetadata,
+) -> Result<DryRunResult> {
+    let request = InstantiateRequest {
+        origin,
+        value,
+        gas_limit: None,
+        storage_deposit_limit: None,
+        code: Code::Upload(code),
+        data,
+        salt,
+    };
+    let bytes = runtime_call(client, "ContractsApi_instantiate", request.encode()).await?;
+
+    let return_type = data.get(..4).and_then(|selector| {
+        let constructor = metadata
+            .spec
+            .constructors
+            .iter()
+            .find(|c| c.selector == selector)?;
+        Some((metadata, constructor.return_type.as_ref()?))
+    });
+
+    decode_exec_result(&bytes, true, client, return_type)
+}
+
+async fn runtime_call(
+    client: &OnlineClient<PolkadotConfig>,
+    method: &str,
+    params: Vec<u8>,
+) -> Result<Vec<u8>> {
+    let at = client.backend().latest_finalized_block_ref().await?.hash();
+    client
+        .backend()
+        .call(method, Some(&params), at)
+        .await
+        .map_err(|e| Error::Transaction(format!("{} failed: {}", method, e)))
+}
+
+/// Decode a `ContractResult`:
+///
+/// ```text
+/// { gas_consumed: Weight, gas_required: Weight, storage_deposit: StorageDeposit,
+///   debug_message: Vec<u8>, result: Result<R, DispatchError>, .. }
+/// ```
+///
+/// where `R` is `ExecReturnValue { flags: u32, data: Vec<u8> }`, followed by
+/// the new account id for instantiations.
+fn decode_exec_result(
+    bytes: &[u8],
+    instantiate: bool,
+    client: &OnlineClient<PolkadotConfig>,
+    return_type: Option<(&ContractMetadata, &TypeRef)>,
+) -> Result<DryRunResult> {
+    let mut input = bytes;
+    let field = |name: &str, e: parity_scale_codec::Error| {
+        Error::Transaction(format!("Failed to decode {}: {}", name, e))
+    };
+
+    let gas_consumed = Weight::decode(&mut input).map_err(|e| field("gas_consumed", e))?;
+    let gas_required = Weight::decode(&mut input).map_err(|e| field("gas_required", e))?;
+    let storage_deposit =
+        StorageDeposit::decode(&mut input).map_err(|e| field("storage_deposit", e))?;
+    let debug_message = Vec::<u8>::decode(&mut input).map_err(|e| field("debug_message", e))?;
+
+    let mut result = DryRunResult {
+        gas_consumed: gas_consumed.into(),
+        gas_required: gas_required.into(),
+        storage_deposit,
+        debug_message: String::from_utf8_lossy(&debug_message).into_owned(),
+        outcome: DryRunOutcome::Failed(String::new()),
+        contract_address: None,
+    };
+
+    // `Result` encodes `Ok` as 0 and `Err` as 1
+    let variant = u8::decode(&mut input).map_err(|e| field("result", e))?;
+    if variant != 0 {
+        result.outcome = DryRunOutcome::Failed(dispatch_error(input, client));
+        return Ok(result);
+    }
+
+    let flags = u32::decode(&mut input).map_err(|e| field("flags", e))?;
+    let data = Vec::<u8>::decode(&mut input).map_err(|e| field("return data", e))?;
+    if instantiate {
+        let address = ContractAddress::decode(&mut input).map_err(|e| field("account_id", e))?;
+        result.contract_address = Some(address);
+    }
+
+    result.outcome = if flags & REVERT_FLAG != 0 {
+        let decoded = return_type.and_then(|(metadata, ty)| decode_revert(metadata, ty, &data));
+        DryRunOutcome::Reverted { data, decoded }
+    } else {
+        DryRunOutcome::Success { data }
+    };
+    Ok(result)
+}
+
+/// Render an encoded `DispatchError`, naming module errors after their pallet
+pub(super) fn dispatch_error(bytes: &[u8], client: &OnlineClient<PolkadotConfig>) -> String {
+    subxt::error::DispatchError::decode_from(bytes, client.metadata())
+        .map(|e| e.to_string())
+        .unwrap_or_else(|_| format!("0x{}", hex::encode(bytes)))
+}
+
+/// Decode revert data, unwrapping ink!'s `Result<T, LangError>` where possible
+fn decode_revert(
+    metadata: &ContractMetadata,
+    return_type: &TypeRef,
+    data: &[u8],
+) -> Option<DecodedValue> {
+    codec::decode_return(metadata, Some(return_type), data)
+        .ok()
+        .or_else(|| {
+            scale_value::scale::decode_as_type(&mut &data[..], return_type.ty, &metadata.registry)
+                .ok()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weight_is_compact_encoded() {
+        let weight = Weight {
+            ref_time: 1,
+            proof_size: 2,
+        };
+        assert_eq!(weight.encode(), vec![4, 8]);
+    }
+
+    #[test]
+    fn test_storage_deposit_limit_margin() {
+        let result = DryRunResult {
+            gas_consumed: GasLimit::new(800, 80),
+            gas_required: GasLimit::new(1_000, 100),
+            storage_deposit: StorageDeposit::Charge(2_000),
+            debug_message: String::new(),
+            outcome: DryRunOutcome::Success { data: vec![] },
+            contract_address: None,
+        };
+
+        let gas = result.gas_limit(10);
+        assert_eq!((gas.ref_time, gas.proof_size), (1_100, 110));
+        assert!(matches!(
+            result.storage_deposit_limit(10),
+            StorageDepositLimit::Limited(2_200)
+        ));
+
+        let refund = DryRunResult {
+            storage_deposit: StorageDeposit::Refund(500),
+            ..result
+        };
+        assert!(matches!(
+            refund.storage_deposit_limit(10),
+            StorageDepositLimit::Limited(0)
+        ));
+    }
+
+    #[test]
+    fn test_ensure_success() {
+        let reverted = DryRunResult {
+            gas_consumed: GasLimit::new(0, 0),
+            gas_required: GasLimit::new(0, 0),
+            storage_deposit: StorageDeposit::Charge(0),
+            debug_message: "panicked".to_string(),
+            outcome: DryRunOutcome::Reverted {
+                data: vec![1, 2],
+                decoded: None,
+            },
+            contract_address: None,
+        };
+        let err = reverted.ensure_success().unwrap_err().to_string();
+        assert!(err.contains("0x0102"));
+        assert!(err.contains("panicked"));
+    }
+}
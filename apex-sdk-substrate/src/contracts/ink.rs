@@ -0,0 +1,457 @@
+//! ink! v4/v5 metadata parsing
+//!
+//! `cargo contract` emits the contract's type registry in scale-info's portable
+//! form. This module reads that format into [`ContractMetadata`] and converts
+//! between its [`TypeDef`] list and a [`PortableRegistry`] used for encoding.
+
+use super::{
+    ConstructorSpec, ContractMetadata, ContractSpec, EventArg, EventSpec, Field, LayoutKey,
+    MessageArg, MessageSpec, StorageLayout, TypeDef, TypeDefVariant, TypeParam, TypeRef, Variant,
+};
+use crate::{Error, Result};
+use scale_info::form::PortableForm;
+use scale_info::{PortableRegistry, PortableType, TypeDefPrimitive};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct InkProject {
+    version: serde_json::Value,
+    types: Vec<PortableType>,
+    #[serde(default)]
+    storage: Option<InkStorage>,
+    spec: InkSpec,
+}
+
+#[derive(Deserialize)]
+struct InkStorage {
+    root: InkStorageRoot,
+}
+
+#[derive(Deserialize)]
+struct InkStorageRoot {
+    root_key: String,
+    ty: u32,
+}
+
+#[derive(Deserialize)]
+struct InkSpec {
+    constructors: Vec<InkConstructor>,
+    messages: Vec<InkMessage>,
+    #[serde(default)]
+    events: Vec<InkEvent>,
+    #[serde(default)]
+    lang_error: Option<InkTypeSpec>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InkConstructor {
+    label: String,
+    selector: String,
+    #[serde(default)]
+    args: Vec<InkArg>,
+    #[serde(default)]
+    return_type: Option<InkTypeSpec>,
+    #[serde(default)]
+    docs: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InkMessage {
+    label: String,
+    selector: String,
+    #[serde(default)]
+    args: Vec<InkArg>,
+    #[serde(default)]
+    return_type: Option<InkTypeSpec>,
+    mutates: bool,
+    payable: bool,
+    #[serde(default)]
+    docs: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct InkArg {
+    label: String,
+    #[serde(rename = "type")]
+    ty: InkTypeSpec,
+}
+
+#[derive(Deserialize)]
+struct InkEvent {
+    label: String,
+    #[serde(default)]
+    args: Vec<InkEventArg>,
+    #[serde(default)]
+    docs: Vec<String>,
+    #[serde(default)]
+    signature_topic: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct InkEventArg {
+    label: String,
+    #[serde(rename = "type")]
+    ty: InkTypeSpec,
+    indexed: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InkTypeSpec {
+    #[serde(rename = "type")]
+    ty: u32,
+    #[serde(default)]
+    display_name: Vec<String>,
+}
+
+impl From<InkTypeSpec> for TypeRef {
+    fn from(spec: InkTypeSpec) -> Self {
+        TypeRef {
+            ty: spec.ty,
+            display_name: spec.display_name,
+        }
+    }
+}
+
+impl From<InkArg> for MessageArg {
+    fn from(arg: InkArg) -> Self {
+        MessageArg {
+            label: arg.label,
+            type_ref: arg.ty.into(),
+        }
+    }
+}
+
+/// Parse ink! metadata as emitted by `cargo contract` (format versions 4 and 5)
+pub(super) fn parse_ink_metadata(json: serde_json::Value) -> Result<ContractMetadata> {
+    let project: InkProject = serde_json::from_value(json)
+        .map_err(|e| Error::Metadata(format!("Failed to parse ink! metadata: {}", e)))?;
+
+    // v4 writes the version as a string, v5 as a number
+    let version = match &project.version {
+        serde_json::Value::String(v) => v.parse::<u32>().ok(),
+        serde_json::Value::Number(v) => v.as_u64().and_then(|v| u32::try_from(v).ok()),
+        _ => None,
+    };
+    let version = match version {
+        Some(version @ (4 | 5)) => version,
+        _ => {
+            return Err(Error::Metadata(format!(
+                "Unsupported ink! metadata version: {}",
+                project.version
+            )))
+        }
+    };
+
+    let registry = PortableRegistry {
+        types: project.types,
+    };
+
+    let constructors = project
+        .spec
+        .constructors
+        .into_iter()
+        .map(|c| {
+            Ok(ConstructorSpec {
+                selector: parse_selector(&c.selector)?,
+                label: c.label,
+                args: c.args.into_iter().map(Into::into).collect(),
+                return_type: c.return_type.map(Into::into),
+                docs: c.docs,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    let messages = project
+        .spec
+        .messages
+        .into_iter()
+        .map(|m| {
+            Ok(MessageSpec {
+                selector: parse_selector(&m.selector)?,
+                label: m.label,
+                args: m.args.into_iter().map(Into::into).collect(),
+                return_type: m.return_type.map(Into::into),
+                mutates: m.mutates,
+                payable: m.payable,
+                docs: m.docs,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    let events = project
+        .spec
+        .events
+        .into_iter()
+        .map(|e| {
+            Ok(EventSpec {
+                signature_topic: e.signature_topic.as_deref().map(parse_topic).transpose()?,
+                label: e.label,
+                args: e
+                    .args
+                    .into_iter()
+                    .map(|a| EventArg {
+                        label: a.label,
+                        type_ref: a.ty.into(),
+                        indexed: a.indexed,
+                    })
+                    .collect(),
+                docs: e.docs,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    let storage = project
+        .storage
+        .map(|s| LayoutKey {
+            key: s.root.root_key,
+            ty: s.root.ty,
+        })
+        .unwrap_or_else(|| LayoutKey {
+            key: "0x00000000".to_string(),
+            ty: 0,
+        });
+
+    Ok(ContractMetadata {
+        spec: ContractSpec {
+            constructors,
+            messages,
+            events,
+        },
+        storage: StorageLayout { root: storage },
+        types: types_from_registry(&registry),
+        version: Some(version),
+        lang_error: project.spec.lang_error.map(Into::into),
+        registry,
+    })
+}
+
+fn parse_selector(selector: &str) -> Result<[u8; 4]> {
+    let mut bytes = [0u8; 4];
+    hex::decode_to_slice(selector.trim_start_matches("0x"), &mut bytes)
+        .map_err(|e| Error::Metadata(format!("Invalid selector {}: {}", selector, e)))?;
+    Ok(bytes)
+}
+
+fn parse_topic(topic: &str) -> Result<[u8; 32]> {
+    let mut bytes = [0u8; 32];
+    hex::decode_to_slice(topic.trim_start_matches("0x"), &mut bytes)
+        .map_err(|e| Error::Metadata(format!("Invalid signature topic {}: {}", topic, e)))?;
+    Ok(bytes)
+}
+
+/// Convert a portable registry into the metadata's [`TypeDef`] list
+pub(super) fn types_from_registry(registry: &PortableRegistry) -> Vec<TypeDef> {
+    use scale_info::TypeDef as Def;
+
+    let fields = |fields: &[scale_info::Field<PortableForm>]| {
+        fields
+            .iter()
+            .map(|f| Field {
+                name: f.name.clone(),
+                ty: f.ty.id,
+            })
+            .collect()
+    };
+
+    registry
+        .types
+        .iter()
+        .map(|t| TypeDef {
+            id: t.id,
+            path: t.ty.path.segments.clone(),
+            params: t
+                .ty
+                .type_params
+                .iter()
+                .map(|p| TypeParam {
+                    name: p.name.clone(),
+                    ty: p.ty.map(|ty| ty.id),
+                })
+                .collect(),
+            def: match &t.ty.type_def {
+                Def::Composite(c) => TypeDefVariant::Composite {
+                    fields: fields(&c.fields),
+                },
+                Def::Variant(v) => TypeDefVariant::Variant {
+                    variants: v
+                        .variants
+                        .iter()
+                        .map(|v| Variant {
+                            name: v.name.clone(),
+                            fields: fields(&v.fields),
+                            index: v.index,
+                        })
+                        .collect(),
+                },
+                Def::Sequence(s) => TypeDefVariant::Sequence {
+                    type_param: s.type_param.id,
+                },
+                Def::Array(a) => TypeDefVariant::Array {
+                    len: a.len,
+                    type_param: a.type_param.id,
+                },
+                Def::Tuple(t) => TypeDefVariant::Tuple {
+                    fields: t.fields.iter().map(|f| f.id).collect(),
+                },
+                Def::Primitive(p) => TypeDefVariant::Primitive {
+                    primitive: primitive_name(p).to_string(),
+                },
+                Def::Compact(c) => TypeDefVariant::Compact {
+                    type_param: c.type_param.id,
+                },
+                Def::BitSequence(b) => TypeDefVariant::BitSequence {
+                    bit_store_type: b.bit_store_type.id,
+                    bit_order_type: b.bit_order_type.id,
+                },
+            },
+        })
+        .collect()
+}
+
+/// Build a portable registry from the metadata's [`TypeDef`] list.
+///
+/// Type ids must be contiguous from zero, as they are in ink! metadata.
+pub(super) fn registry_from_types(types: &[TypeDef]) -> Result<PortableRegistry> {
+    use scale_info::{
+        Path, Type, TypeDefArray, TypeDefBitSequence, TypeDefCompact, TypeDefComposite,
+        TypeDefSequence, TypeDefTuple, TypeParameter,
+    };
+
+    let fields = |fields: &[Field]| {
+        fields
+            .iter()
+            .map(|f| scale_info::Field {
+                name: f.name.clone(),
+                ty: f.ty.into(),
+                type_name: None,
+                docs: Vec::new(),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut sorted: Vec<&TypeDef> = types.iter().collect();
+    sorted.sort_by_key(|t| t.id);
+
+    let mut registry = PortableRegistry { types: Vec::new() };
+    for (index, t) in sorted.into_iter().enumerate() {
+        if t.id as usize != index {
+            return Err(Error::Metadata(format!(
+                "Contract type ids must be contiguous, expected {} but found {}",
+                index, t.id
+            )));
+        }
+
+        let type_def = match &t.def {
+            TypeDefVariant::Composite { fields: f } => {
+                scale_info::TypeDef::Composite(TypeDefComposite { fields: fields(f) })
+            }
+            TypeDefVariant::Variant { variants } => {
+                scale_info::TypeDef::Variant(scale_info::TypeDefVariant {
+                    variants: variants
+                        .iter()
+                        .map(|v| scale_info::Variant {
+                            name: v.name.clone(),
+                            fields: fields(&v.fields),
+                            index: v.index,
+                            docs: Vec::new(),
+                        })
+                        .collect(),
+                })
+            }
+            TypeDefVariant::Sequence { type_param } => {
+                scale_info::TypeDef::Sequence(TypeDefSequence {
+                    type_param: (*type_param).into(),
+                })
+            }
+            TypeDefVariant::Array { len, type_param } => scale_info::TypeDef::Array(TypeDefArray {
+                len: *len,
+                type_param: (*type_param).into(),
+            }),
+            TypeDefVariant::Tuple { fields } => scale_info::TypeDef::Tuple(TypeDefTuple {
+                fields: fields.iter().map(|f| (*f).into()).collect(),
+            }),
+            TypeDefVariant::Primitive { primitive } => {
+                scale_info::TypeDef::Primitive(parse_primitive(primitive).ok_or_else(|| {
+                    Error::Metadata(format!("Unknown primitive type: {}", primitive))
+                })?)
+            }
+            TypeDefVariant::Compact { type_param } => {
+                scale_info::TypeDef::Compact(TypeDefCompact {
+                    type_param: (*type_param).into(),
+                })
+            }
+            TypeDefVariant::BitSequence {
+                bit_store_type,
+                bit_order_type,
+            } => scale_info::TypeDef::BitSequence(TypeDefBitSequence {
+                bit_store_type: (*bit_store_type).into(),
+                bit_order_type: (*bit_order_type).into(),
+            }),
+        };
+
+        registry.types.push(PortableType {
+            id: t.id,
+            ty: Type {
+                path: Path {
+                    segments: t.path.clone(),
+                },
+                type_params: t
+                    .params
+                    .iter()
+                    .map(|p| TypeParameter {
+                        name: p.name.clone(),
+                        ty: p.ty.map(Into::into),
+                    })
+                    .collect(),
+                type_def,
+                docs: Vec::new(),
+            },
+        });
+    }
+
+    Ok(registry)
+}
+
+fn primitive_name(primitive: &TypeDefPrimitive) -> &'static str {
+    match primitive {
+        TypeDefPrimitive::Bool => "bool",
+        TypeDefPrimitive::Char => "char",
+        TypeDefPrimitive::Str => "str",
+        TypeDefPrimitive::U8 => "u8",
+        TypeDefPrimitive::U16 => "u16",
+        TypeDefPrimitive::U32 => "u32",
+        TypeDefPrimitive::U64 => "u64",
+        TypeDefPrimitive::U128 => "u128",
+        TypeDefPrimitive::U256 => "u256",
+        TypeDefPrimitive::I8 => "i8",
+        TypeDefPrimitive::I16 => "i16",
+        TypeDefPrimitive::I32 => "i32",
+        TypeDefPrimitive::I64 => "i64",
+        TypeDefPrimitive::I128 => "i128",
+        TypeDefPrimitive::I256 => "i256",
+    }
+}
+
+fn parse_primitive(name: &str) -> Option<TypeDefPrimitive> {
+    Some(match name {
+        "bool" => TypeDefPrimitive::Bool,
+        "char" => TypeDefPrimitive::Char,
+        "str" | "String" => TypeDefPrimitive::Str,
+        "u8" => TypeDefPrimitive::U8,
+        "u16" => TypeDefPrimitive::U16,
+        "u32" => TypeDefPrimitive::U32,
+        "u64" => TypeDefPrimitive::U64,
+        "u128" => TypeDefPrimitive::U128,
+        "u256" => TypeDefPrimitive::U256,
+        "i8" => TypeDefPrimitive::I8,
+        "i16" => TypeDefPrimitive::I16,
+        "i32" => TypeDefPrimitive::I32,
+        "i64" => TypeDefPrimitive::I64,
+        "i128" => TypeDefPrimitive::I128,
+        "i256" => TypeDefPrimitive::I256,
+        _ => return None,
+    })
+}
@@ -0,0 +1,1267 @@
+//! Ink! smart contract deployment and interaction
+//!
+//! This module provides functionality for deploying and interacting with
+//! ink! smart contracts on Substrate-based chains.
+//!
+//! ## Features
+//!
+//! - Deploy compiled ink! contracts (Wasm)
+//! - Call contract methods (read and write)
+//! - Parse contract metadata, including ink! v4 and v5 metadata files
+//! - Encode arguments and decode return values with the contract's type registry
+//! - Handle contract events
+//! - Gas and storage deposit estimation by dry-running calls and deployments
+//! - pallet-revive (PolkaVM) contracts with Solidity ABIs, behind the `revive`
+//!   feature (see [`revive`])
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use apex_sdk_substrate::contracts::ContractClient;
+//!
+//! // Deploy a contract
+//! let contract = ContractClient::deploy(
+//!     client,
+//!     wasm_code,
+//!     metadata,
+//!     constructor_args,
+//! ).await?;
+//!
+//! // Call a contract method
+//! let result = contract
+//!     .call("transfer")
+//!     .args(&[recipient, amount])
+//!     .execute(&wallet)
+//!     .await?;
+//! ```
+
+use crate::{Error, Result, Sr25519Signer, Wallet};
+use scale_info::PortableRegistry;
+use serde::{Deserialize, Serialize};
+use sp_core::Pair;
+use subxt::dynamic::{DecodedValue, Value};
+use subxt::{OnlineClient, PolkadotConfig};
+use tracing::{debug, info};
+
+mod codec;
+mod dry_run;
+mod ink;
+#[cfg(feature = "revive")]
+pub mod revive;
+
+pub use codec::json_to_value;
+pub use dry_run::{DryRunOutcome, DryRunResult, StorageDeposit, DEFAULT_GAS_MARGIN_PERCENT};
+
+/// Contract address type (32-byte account ID)
+pub type ContractAddress = [u8; 32];
+
+/// Contract metadata from the ink! compilation
+///
+/// Deserializing rebuilds [`registry`](Self::registry) from `types`, so a
+/// serialized copy round-trips through [`parse_metadata`] or `serde_json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "SerializedMetadata")]
+pub struct ContractMetadata {
+    /// Contract specification
+    pub spec: ContractSpec,
+    /// Storage layout
+    pub storage: StorageLayout,
+    /// Types used in the contract
+    pub types: Vec<TypeDef>,
+    /// ink! metadata format version (4 or 5), if read from an ink! metadata file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    /// Type of ink!'s `LangError`, which wraps every message result since ink! 4
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang_error: Option<TypeRef>,
+    /// Type registry built from `types`, used to encode and decode values
+    #[serde(skip_serializing)]
+    pub registry: PortableRegistry,
+}
+
+/// Serialized form of [`ContractMetadata`], without the derived registry
+#[derive(Deserialize)]
+struct SerializedMetadata {
+    spec: ContractSpec,
+    storage: StorageLayout,
+    types: Vec<TypeDef>,
+    #[serde(default)]
+    version: Option<u32>,
+    #[serde(default)]
+    lang_error: Option<TypeRef>,
+}
+
+impl TryFrom<SerializedMetadata> for ContractMetadata {
+    type Error = Error;
+
+    fn try_from(metadata: SerializedMetadata) -> Result<Self> {
+        Ok(Self {
+            registry: ink::registry_from_types(&metadata.types)?,
+            spec: metadata.spec,
+            storage: metadata.storage,
+            types: metadata.types,
+            version: metadata.version,
+            lang_error: metadata.lang_error,
+        })
+    }
+}
+
+impl ContractMetadata {
+    /// Find a message by label
+    pub fn message(&self, label: &str) -> Result<&MessageSpec> {
+        self.spec
+            .messages
+            .iter()
+            .find(|m| m.label == label)
+            .ok_or_else(|| Error::Transaction(format!("Method '{}' not found", label)))
+    }
+
+    /// Find a constructor by label
+    pub fn constructor(&self, label: &str) -> Result<&ConstructorSpec> {
+        self.spec
+            .constructors
+            .iter()
+            .find(|c| c.label == label)
+            .ok_or_else(|| Error::Transaction(format!("Constructor '{}' not found", label)))
+    }
+
+    /// Encode the call data (selector and arguments) for a message
+    pub fn encode_message(&self, label: &str, args: &[Value]) -> Result<Vec<u8>> {
+        let message = self.message(label)?;
+        codec::encode_call_data(message.selector, &message.args, args, &self.registry)
+    }
+
+    /// Encode the call data for a message from JSON arguments
+    pub fn encode_message_json(&self, label: &str, args: &[serde_json::Value]) -> Result<Vec<u8>> {
+        let message = self.message(label)?;
+        let values = codec::json_args(&message.args, args, &self.registry)?;
+        codec::encode_call_data(message.selector, &message.args, &values, &self.registry)
+    }
+
+    /// Encode the call data (selector and arguments) for a constructor
+    pub fn encode_constructor(&self, label: &str, args: &[Value]) -> Result<Vec<u8>> {
+        let constructor = self.constructor(label)?;
+        codec::encode_call_data(
+            constructor.selector,
+            &constructor.args,
+            args,
+            &self.registry,
+        )
+    }
+
+    /// Encode the call data for a constructor from JSON arguments
+    pub fn encode_constructor_json(
+        &self,
+        label: &str,
+        args: &[serde_json::Value],
+    ) -> Result<Vec<u8>> {
+        let constructor = self.constructor(label)?;
+        let values = codec::json_args(&constructor.args, args, &self.registry)?;
+        codec::encode_call_data(
+            constructor.selector,
+            &constructor.args,
+            &values,
+            &self.registry,
+        )
+    }
+
+    /// Decode a message's return data.
+    ///
+    /// `Result<T, LangError>` returns are unwrapped to `T`; a `LangError` becomes
+    /// an error.
+    pub fn decode_return(&self, label: &str, data: &[u8]) -> Result<DecodedValue> {
+        let message = self.message(label)?;
+        codec::decode_return(self, message.return_type.as_ref(), data)
+    }
+
+    /// Decode the payload and topics of a `ContractEmitted` event
+    pub fn decode_event(&self, data: &[u8], topics: &[[u8; 32]]) -> Result<ContractEvent> {
+        codec::decode_event(self, data, topics)
+    }
+
+    /// Render a decoded value as JSON using the contract's type registry
+    pub fn to_json(&self, value: &DecodedValue) -> serde_json::Value {
+        crate::storage::value_to_json(value, &self.registry)
+    }
+}
+
+/// Contract specification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractSpec {
+    /// Contract constructors
+    pub constructors: Vec<ConstructorSpec>,
+    /// Contract messages (methods)
+    pub messages: Vec<MessageSpec>,
+    /// Contract events
+    pub events: Vec<EventSpec>,
+}
+
+/// Constructor specification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstructorSpec {
+    /// Constructor name
+    pub label: String,
+    /// Selector (first 4 bytes of hash)
+    pub selector: [u8; 4],
+    /// Arguments
+    pub args: Vec<MessageArg>,
+    /// Return type (ink! 4 and later)
+    #[serde(default)]
+    pub return_type: Option<TypeRef>,
+    /// Documentation
+    pub docs: Vec<String>,
+}
+
+/// Message (method) specification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageSpec {
+    /// Message name
+    pub label: String,
+    /// Selector
+    pub selector: [u8; 4],
+    /// Arguments
+    pub args: Vec<MessageArg>,
+    /// Return type
+    pub return_type: Option<TypeRef>,
+    /// Is this a mutable call?
+    pub mutates: bool,
+    /// Is this payable?
+    pub payable: bool,
+    /// Documentation
+    pub docs: Vec<String>,
+}
+
+/// Message argument
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageArg {
+    /// Argument name
+    pub label: String,
+    /// Type reference
+    pub type_ref: TypeRef,
+}
+
+/// Event specification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventSpec {
+    /// Event name
+    pub label: String,
+    /// Event arguments
+    pub args: Vec<EventArg>,
+    /// Documentation
+    pub docs: Vec<String>,
+    /// Signature topic identifying the event (ink! 5, absent for anonymous events)
+    #[serde(default)]
+    pub signature_topic: Option<[u8; 32]>,
+}
+
+/// Event argument
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventArg {
+    /// Argument name
+    pub label: String,
+    /// Type reference
+    pub type_ref: TypeRef,
+    /// Is this indexed?
+    pub indexed: bool,
+}
+
+/// Storage layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageLayout {
+    /// Root storage key
+    pub root: LayoutKey,
+}
+
+/// Layout key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutKey {
+    /// Key value
+    pub key: String,
+    /// Type ID
+    pub ty: u32,
+}
+
+/// Type reference
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeRef {
+    /// Type ID
+    pub ty: u32,
+    /// Display name
+    pub display_name: Vec<String>,
+}
+
+/// Type definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeDef {
+    /// Type ID
+    pub id: u32,
+    /// Type path
+    pub path: Vec<String>,
+    /// Type params
+    pub params: Vec<TypeParam>,
+    /// Type definition
+    pub def: TypeDefVariant,
+}
+
+/// Type parameter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeParam {
+    /// Parameter name
+    pub name: String,
+    /// Type reference
+    pub ty: Option<u32>,
+}
+
+/// Type definition variant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum TypeDefVariant {
+    Composite {
+        fields: Vec<Field>,
+    },
+    Variant {
+        variants: Vec<Variant>,
+    },
+    Sequence {
+        type_param: u32,
+    },
+    Array {
+        len: u32,
+        type_param: u32,
+    },
+    Tuple {
+        fields: Vec<u32>,
+    },
+    Primitive {
+        primitive: String,
+    },
+    Compact {
+        type_param: u32,
+    },
+    BitSequence {
+        bit_store_type: u32,
+        bit_order_type: u32,
+    },
+}
+
+/// Field definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Field {
+    /// Field name
+    pub name: Option<String>,
+    /// Type reference
+    pub ty: u32,
+}
+
+/// Variant definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Variant {
+    /// Variant name
+    pub name: String,
+    /// Fields
+    pub fields: Vec<Field>,
+    /// Index
+    pub index: u8,
+}
+
+/// Gas limit for contract calls
+#[derive(Debug, Clone, Copy, parity_scale_codec::Encode, parity_scale_codec::Decode)]
+pub struct GasLimit {
+    /// Reference time
+    pub ref_time: u64,
+    /// Proof size
+    pub proof_size: u64,
+}
+
+impl GasLimit {
+    /// Create a new gas limit
+    pub fn new(ref_time: u64, proof_size: u64) -> Self {
+        Self {
+            ref_time,
+            proof_size,
+        }
+    }
+
+    /// Default gas limit for most operations
+    pub fn default_call() -> Self {
+        Self {
+            ref_time: 1_000_000_000_000, // 1 trillion
+            proof_size: 3_145_728,       // ~3MB
+        }
+    }
+
+    /// Higher gas limit for deployment
+    pub fn default_deploy() -> Self {
+        Self {
+            ref_time: 5_000_000_000_000, // 5 trillion
+            proof_size: 10_485_760,      // ~10MB
+        }
+    }
+
+    /// Increase both components by `percent`
+    pub fn with_margin(self, percent: u64) -> Self {
+        let add = |v: u64| {
+            let extra = v as u128 * percent as u128 / 100;
+            v.saturating_add(u64::try_from(extra).unwrap_or(u64::MAX))
+        };
+        Self {
+            ref_time: add(self.ref_time),
+            proof_size: add(self.proof_size),
+        }
+    }
+}
+
+/// Storage deposit limit
+#[derive(Debug, Clone, Copy)]
+pub enum StorageDepositLimit {
+    /// No limit (use account balance)
+    NoLimit,
+    /// Specific limit in tokens
+    Limited(u128),
+}
+
+/// A contract event decoded with the contract metadata
+#[derive(Debug, Clone)]
+pub struct ContractEvent {
+    /// Event name
+    pub label: String,
+    /// Decoded fields in declaration order
+    pub fields: Vec<(String, DecodedValue)>,
+    /// Topics the event was emitted with
+    pub topics: Vec<[u8; 32]>,
+}
+
+impl ContractEvent {
+    /// Look up a field by name
+    pub fn field(&self, label: &str) -> Option<&DecodedValue> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == label)
+            .map(|(_, value)| value)
+    }
+
+    /// Render the fields as a JSON object using the contract's type registry
+    pub fn to_json(&self, metadata: &ContractMetadata) -> serde_json::Value {
+        serde_json::Value::Object(
+            self.fields
+                .iter()
+                .map(|(name, value)| (name.clone(), metadata.to_json(value)))
+                .collect(),
+        )
+    }
+}
+
+/// Result of a submitted contract call
+#[derive(Debug, Clone)]
+pub struct ContractExecution {
+    /// Transaction hash
+    pub tx_hash: String,
+    /// Events emitted by this contract during the call
+    pub events: Vec<ContractEvent>,
+}
+
+/// Contract call builder
+#[allow(dead_code)]
+pub struct ContractCallBuilder {
+    contract_address: ContractAddress,
+    selector: [u8; 4],
+    args: Vec<u8>,
+    gas_limit: GasLimit,
+    storage_deposit: StorageDepositLimit,
+    value: u128,
+}
+
+impl ContractCallBuilder {
+    /// Create a new contract call builder
+    pub fn new(contract_address: ContractAddress, selector: [u8; 4]) -> Self {
+        Self {
+            contract_address,
+            selector,
+            args: Vec::new(),
+            gas_limit: GasLimit::default_call(),
+            storage_deposit: StorageDepositLimit::NoLimit,
+            value: 0,
+        }
+    }
+
+    /// Set the call arguments (SCALE-encoded)
+    pub fn args(mut self, args: &[u8]) -> Self {
+        self.args = args.to_vec();
+        self
+    }
+
+    /// Set the gas limit
+    pub fn gas_limit(mut self, limit: GasLimit) -> Self {
+        self.gas_limit = limit;
+        self
+    }
+
+    /// Set the storage deposit limit
+    pub fn storage_deposit(mut self, limit: StorageDepositLimit) -> Self {
+        self.storage_deposit = limit;
+        self
+    }
+
+    /// Set the value to transfer with the call
+    pub fn value(mut self, value: u128) -> Self {
+        self.value = value;
+        self
+    }
+
+    /// Build the call data
+    pub fn build_call_data(&self) -> Vec<u8> {
+        let mut call_data = Vec::new();
+        call_data.extend_from_slice(&self.selector);
+        call_data.extend_from_slice(&self.args);
+        call_data
+    }
+}
+
+/// Contract client for interacting with deployed contracts
+pub struct ContractClient {
+    client: OnlineClient<PolkadotConfig>,
+    address: ContractAddress,
+    metadata: Option<ContractMetadata>,
+    gas_margin: u64,
+}
+
+impl ContractClient {
+    /// Create a new contract client for an existing contract
+    pub fn new(client: OnlineClient<PolkadotConfig>, address: ContractAddress) -> Self {
+        Self {
+            client,
+            address,
+            metadata: None,
+            gas_margin: DEFAULT_GAS_MARGIN_PERCENT,
+        }
+    }
+
+    /// Create a contract client with metadata
+    pub fn with_metadata(
+        client: OnlineClient<PolkadotConfig>,
+        address: ContractAddress,
+        metadata: ContractMetadata,
+    ) -> Self {
+        Self {
+            client,
+            address,
+            metadata: Some(metadata),
+            gas_margin: DEFAULT_GAS_MARGIN_PERCENT,
+        }
+    }
+
+    /// Set the margin (in percent) added to dry-run gas and storage deposit
+    /// estimates before submitting a call
+    pub fn with_gas_margin(mut self, percent: u64) -> Self {
+        self.gas_margin = percent;
+        self
+    }
+
+    /// Dry-run a deployment through the `ContractsApi_instantiate` runtime API
+    ///
+    /// `call_data` is the constructor selector followed by its SCALE-encoded
+    /// arguments (see [`ContractMetadata::encode_constructor`]).
+    pub async fn dry_run_deploy(
+        client: &OnlineClient<PolkadotConfig>,
+        wasm_code: &[u8],
+        metadata: &ContractMetadata,
+        call_data: &[u8],
+        value: u128,
+        salt: &[u8],
+        caller: &[u8; 32],
+    ) -> Result<DryRunResult> {
+        dry_run::instantiate(client, *caller, value, wasm_code, call_data, salt, metadata).await
+    }
+
+    /// Dry-run a call through the `ContractsApi_call` runtime API
+    ///
+    /// `call_data` is the message selector followed by its SCALE-encoded
+    /// arguments (see [`ContractMetadata::encode_message`]). Revert data is
+    /// decoded with the message's return type when metadata is available.
+    pub async fn dry_run(
+        &self,
+        call_data: &[u8],
+        value: u128,
+        caller: &[u8; 32],
+    ) -> Result<DryRunResult> {
+        dry_run::call(
+            &self.client,
+            *caller,
+            self.address,
+            value,
+            call_data,
+            self.metadata.as_ref(),
+        )
+        .await
+    }
+
+    /// Deploy a new contract
+    ///
+    /// The deployment is dry-run first; its gas and storage deposit estimates
+    /// plus [`DEFAULT_GAS_MARGIN_PERCENT`] are used as the transaction limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Subxt client
+    /// * `wasm_code` - Compiled Wasm code
+    /// * `metadata` - Contract metadata
+    /// * `constructor_name` - Name of the constructor to call
+    /// * `constructor_args` - SCALE-encoded constructor arguments
+    /// * `wallet` - Wallet to sign the deployment transaction
+    /// * `salt` - Salt for deterministic address generation
+    ///
+    /// # Returns
+    ///
+    /// Contract client for the deployed contract
+    pub async fn deploy(
+        client: OnlineClient<PolkadotConfig>,
+        wasm_code: Vec<u8>,
+        metadata: ContractMetadata,
+        constructor_name: &str,
+        constructor_args: &[u8],
+        wallet: &Wallet,
+        salt: Option<Vec<u8>>,
+    ) -> Result<Self> {
+        info!("Deploying contract with constructor: {}", constructor_name);
+
+        // Find the constructor
+        let constructor = metadata
+            .spec
+            .constructors
+            .iter()
+            .find(|c| c.label == constructor_name)
+            .ok_or_else(|| {
+                Error::Transaction(format!("Constructor '{}' not found", constructor_name))
+            })?;
+
+        // Build constructor call data
+        let mut call_data = Vec::new();
+        call_data.extend_from_slice(&constructor.selector);
+        call_data.extend_from_slice(constructor_args);
+
+        // Prepare salt (use default if not provided)
+        let salt = salt.unwrap_or_else(|| vec![0u8; 32]);
+
+        let pair = wallet
+            .sr25519_pair()
+            .ok_or_else(|| Error::Transaction("Wallet does not have SR25519 key".to_string()))?;
+
+        // Estimate gas and storage deposit
+        let estimate = dry_run::instantiate(
+            &client,
+            pair.public().0,
+            0,
+            &wasm_code,
+            &call_data,
+            &salt,
+            &metadata,
+        )
+        .await?
+        .ensure_success()?;
+        debug!(
+            "Deploy dry-run: gas_required={:?}, storage_deposit={:?}",
+            estimate.gas_required, estimate.storage_deposit
+        );
+
+        // Build the instantiate call
+        let gas_limit = estimate.gas_limit(DEFAULT_GAS_MARGIN_PERCENT);
+        let storage_deposit = estimate.storage_deposit_limit(DEFAULT_GAS_MARGIN_PERCENT);
+
+        let instantiate_call = subxt::dynamic::tx(
+            "Contracts",
+            "instantiate",
+            vec![
+                subxt::dynamic::Value::u128(0), // value
+                Self::encode_gas_limit(&gas_limit)?,
+                Self::encode_storage_deposit(&storage_deposit)?,
+                subxt::dynamic::Value::from_bytes(&wasm_code),
+                subxt::dynamic::Value::from_bytes(&call_data),
+                subxt::dynamic::Value::from_bytes(&salt),
+            ],
+        );
+
+        // Submit the transaction
+        let signer = Sr25519Signer::new(pair.clone());
+
+        let mut progress = client
+            .tx()
+            .sign_and_submit_then_watch_default(&instantiate_call, &signer)
+            .await
+            .map_err(|e| {
+                Error::Transaction(format!("Failed to submit deploy transaction: {}", e))
+            })?;
+
+        // Wait for finalization and extract contract address
+        while let Some(event) = progress.next().await {
+            let event = event
+                .map_err(|e| Error::Transaction(format!("Deploy transaction error: {}", e)))?;
+
+            if let Some(finalized) = event.as_finalized() {
+                info!("Contract deployment finalized");
+
+                // Extract contract address from events
+                let events = finalized
+                    .fetch_events()
+                    .await
+                    .map_err(|e| Error::Transaction(format!("Failed to get events: {}", e)))?;
+
+                for evt in events.iter() {
+                    let evt = evt.map_err(|e| {
+                        Error::Transaction(format!("Failed to decode event: {}", e))
+                    })?;
+
+                    // Look for Contracts.Instantiated event
+                    if evt.pallet_name() == "Contracts" && evt.variant_name() == "Instantiated" {
+                        debug!("Contract instantiated event found");
+
+                        // Extract contract address from event using raw field bytes
+                        // The Instantiated event has two AccountId32 fields:
+                        // Field 0: deployer (32 bytes)
+                        // Field 1: contract (32 bytes)
+                        let field_bytes = evt.field_bytes();
+
+                        // Parse using raw bytes - each AccountId32 is 32 bytes
+                        if field_bytes.len() >= 64 {
+                            let mut contract_address = [0u8; 32];
+                            // Extract second AccountId32 (offset 32, length 32)
+                            contract_address.copy_from_slice(&field_bytes[32..64]);
+                            return Ok(Self::with_metadata(client, contract_address, metadata));
+                        } else {
+                            // Fallback: try to parse using the field values
+                            // This is less reliable but can work for simple cases
+                            debug!("Attempting fallback parsing for contract address");
+
+                            if field_bytes.len() == 32 {
+                                // Only one field present - might be the contract address
+                                let mut contract_address = [0u8; 32];
+                                contract_address.copy_from_slice(&field_bytes[..32]);
+                                return Ok(Self::with_metadata(client, contract_address, metadata));
+                            } else {
+                                return Err(Error::Transaction(
+                                    format!("Could not extract contract address from Instantiated event. Field bytes length: {}", field_bytes.len())
+                                ));
+                            }
+                        }
+                    }
+                }
+
+                return Err(Error::Transaction(
+                    "Contract deployment succeeded but address not found in events".to_string(),
+                ));
+            }
+        }
+
+        Err(Error::Transaction(
+            "Deploy transaction stream ended without finalization".to_string(),
+        ))
+    }
+
+    /// Call a contract method (mutable)
+    ///
+    /// The call is dry-run first; its gas and storage deposit estimates plus
+    /// the client's margin (see [`ContractClient::with_gas_margin`]) are used as
+    /// the transaction limits, and a revert is reported before submitting.
+    ///
+    /// # Arguments
+    ///
+    /// * `method_name` - Name of the method to call
+    /// * `args` - SCALE-encoded method arguments
+    /// * `wallet` - Wallet to sign the transaction
+    ///
+    /// # Returns
+    ///
+    /// Transaction hash of the call
+    pub async fn call(&self, method_name: &str, args: &[u8], wallet: &Wallet) -> Result<String> {
+        info!("Calling contract method: {}", method_name);
+
+        let message = self.metadata_ref()?.message(method_name)?;
+
+        // Build call data
+        let mut call_data = Vec::new();
+        call_data.extend_from_slice(&message.selector);
+        call_data.extend_from_slice(args);
+
+        Ok(self.submit_call(&call_data, wallet).await?.tx_hash)
+    }
+
+    /// Call a contract method with arguments encoded from dynamic values
+    ///
+    /// Arguments are encoded according to the contract's type registry, and
+    /// events emitted by the contract are decoded the same way.
+    pub async fn call_with_values(
+        &self,
+        method_name: &str,
+        args: &[Value],
+        wallet: &Wallet,
+    ) -> Result<ContractExecution> {
+        info!("Calling contract method: {}", method_name);
+        let call_data = self.metadata_ref()?.encode_message(method_name, args)?;
+        self.submit_call(&call_data, wallet).await
+    }
+
+    /// Call a contract method with JSON arguments (see [`json_to_value`])
+    pub async fn call_with_json(
+        &self,
+        method_name: &str,
+        args: &[serde_json::Value],
+        wallet: &Wallet,
+    ) -> Result<ContractExecution> {
+        info!("Calling contract method: {}", method_name);
+        let call_data = self
+            .metadata_ref()?
+            .encode_message_json(method_name, args)?;
+        self.submit_call(&call_data, wallet).await
+    }
+
+    async fn submit_call(&self, call_data: &[u8], wallet: &Wallet) -> Result<ContractExecution> {
+        let pair = wallet
+            .sr25519_pair()
+            .ok_or_else(|| Error::Transaction("Wallet does not have SR25519 key".to_string()))?;
+
+        // Estimate gas and storage deposit
+        let estimate = self
+            .dry_run(call_data, 0, &pair.public().0)
+            .await?
+            .ensure_success()?;
+        debug!(
+            "Call dry-run: gas_required={:?}, storage_deposit={:?}",
+            estimate.gas_required, estimate.storage_deposit
+        );
+
+        // Build the call transaction
+        let gas_limit = estimate.gas_limit(self.gas_margin);
+        let storage_deposit = estimate.storage_deposit_limit(self.gas_margin);
+
+        let call_tx = subxt::dynamic::tx(
+            "Contracts",
+            "call",
+            vec![
+                subxt::dynamic::Value::from_bytes(self.address),
+                subxt::dynamic::Value::u128(0), // value
+                Self::encode_gas_limit(&gas_limit)?,
+                Self::encode_storage_deposit(&storage_deposit)?,
+                subxt::dynamic::Value::from_bytes(call_data),
+            ],
+        );
+
+        // Submit the transaction
+        let signer = Sr25519Signer::new(pair.clone());
+
+        let mut progress = self
+            .client
+            .tx()
+            .sign_and_submit_then_watch_default(&call_tx, &signer)
+            .await
+            .map_err(|e| Error::Transaction(format!("Failed to submit call transaction: {}", e)))?;
+
+        // Wait for finalization
+        while let Some(event) = progress.next().await {
+            let event =
+                event.map_err(|e| Error::Transaction(format!("Call transaction error: {}", e)))?;
+
+            if let Some(finalized) = event.as_finalized() {
+                let tx_hash = format!("0x{}", hex::encode(finalized.extrinsic_hash()));
+                info!("Contract call finalized: {}", tx_hash);
+
+                let events = finalized
+                    .wait_for_success()
+                    .await
+                    .map_err(|e| Error::Transaction(format!("Contract call failed: {}", e)))?;
+
+                return Ok(ContractExecution {
+                    tx_hash,
+                    events: self.contract_events(&events),
+                });
+            }
+        }
+
+        Err(Error::Transaction(
+            "Call transaction stream ended without finalization".to_string(),
+        ))
+    }
+
+    /// Decode `Contracts::ContractEmitted` events emitted by this contract
+    fn contract_events(
+        &self,
+        events: &subxt::blocks::ExtrinsicEvents<PolkadotConfig>,
+    ) -> Vec<ContractEvent> {
+        use parity_scale_codec::Decode;
+
+        let Some(metadata) = &self.metadata else {
+            return Vec::new();
+        };
+
+        let mut decoded = Vec::new();
+        for event in events.iter().flatten() {
+            if event.pallet_name() != "Contracts" || event.variant_name() != "ContractEmitted" {
+                continue;
+            }
+            let Ok((contract, data)) = <([u8; 32], Vec<u8>)>::decode(&mut event.field_bytes())
+            else {
+                continue;
+            };
+            if contract != self.address {
+                continue;
+            }
+
+            let topics: Vec<[u8; 32]> = event.topics().iter().map(|t| t.0).collect();
+            match metadata.decode_event(&data, &topics) {
+                Ok(contract_event) => decoded.push(contract_event),
+                Err(e) => debug!("Skipping undecodable contract event: {}", e),
+            }
+        }
+        decoded
+    }
+
+    /// Read contract state (dry-run, doesn't modify state)
+    ///
+    /// This method performs a read-only call to a smart contract method without
+    /// submitting a transaction. It uses the `ContractsApi_call` runtime API to
+    /// execute the contract call and return the result.
+    ///
+    /// # Arguments
+    ///
+    /// * `method_name` - Name of the method to call (from contract metadata)
+    /// * `args` - SCALE-encoded method arguments
+    /// * `caller` - Address of the caller (32-byte account ID, for access control checks)
+    ///
+    /// # Returns
+    ///
+    /// SCALE-encoded return value from the contract method
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// // Read the balance of an account from a token contract
+    /// let caller = [0u8; 32]; // Ilara's account
+    /// let result = contract.read("get_balance", &encoded_args, &caller).await?;
+    /// let balance: u128 = Decode::decode(&mut &result[..])?;
+    /// ```
+    ///
+    /// # Implementation Details
+    ///
+    /// This method:
+    /// 1. Looks up the method selector from contract metadata
+    /// 2. Encodes the call data (selector + arguments)
+    /// 3. Calls `ContractsApi_call` runtime API via `state_call`
+    /// 4. Decodes the `ContractExecResult` to extract the return data
+    ///
+    /// The runtime API call is made at the latest finalized block to ensure
+    /// consistent results.
+    pub async fn read(&self, method_name: &str, args: &[u8], caller: &[u8; 32]) -> Result<Vec<u8>> {
+        debug!("Reading contract state: {}", method_name);
+
+        let message = self.metadata_ref()?.message(method_name)?;
+
+        // Build call data
+        let mut call_data = Vec::new();
+        call_data.extend_from_slice(&message.selector);
+        call_data.extend_from_slice(args);
+
+        self.read_call_data(&call_data, caller).await
+    }
+
+    /// Read contract state with arguments and return value encoded through the
+    /// contract's type registry
+    ///
+    /// ink!'s `Result<T, LangError>` wrapper is removed from the return value.
+    pub async fn read_value(
+        &self,
+        method_name: &str,
+        args: &[Value],
+        caller: &[u8; 32],
+    ) -> Result<DecodedValue> {
+        debug!("Reading contract state: {}", method_name);
+        let metadata = self.metadata_ref()?;
+        let call_data = metadata.encode_message(method_name, args)?;
+        let data = self.read_call_data(&call_data, caller).await?;
+        metadata.decode_return(method_name, &data)
+    }
+
+    /// Read contract state with JSON arguments, returning the value as JSON
+    pub async fn read_json(
+        &self,
+        method_name: &str,
+        args: &[serde_json::Value],
+        caller: &[u8; 32],
+    ) -> Result<serde_json::Value> {
+        debug!("Reading contract state: {}", method_name);
+        let metadata = self.metadata_ref()?;
+        let call_data = metadata.encode_message_json(method_name, args)?;
+        let data = self.read_call_data(&call_data, caller).await?;
+        let value = metadata.decode_return(method_name, &data)?;
+        Ok(metadata.to_json(&value))
+    }
+
+    async fn read_call_data(&self, call_data: &[u8], caller: &[u8; 32]) -> Result<Vec<u8>> {
+        let result = self.dry_run(call_data, 0, caller).await?;
+
+        if !result.debug_message.is_empty() {
+            debug!("Contract debug message: {}", result.debug_message);
+        }
+
+        // Reverted reads still carry the encoded error for the caller to decode
+        match result.outcome {
+            DryRunOutcome::Success { data } | DryRunOutcome::Reverted { data, .. } => Ok(data),
+            DryRunOutcome::Failed(error) => Err(Error::Transaction(format!(
+                "Contract execution failed: {}",
+                error
+            ))),
+        }
+    }
+
+    /// Get the contract address
+    pub fn address(&self) -> &ContractAddress {
+        &self.address
+    }
+
+    /// Get the contract metadata
+    pub fn metadata(&self) -> Option<&ContractMetadata> {
+        self.metadata.as_ref()
+    }
+
+    fn metadata_ref(&self) -> Result<&ContractMetadata> {
+        self.metadata
+            .as_ref()
+            .ok_or_else(|| Error::Transaction("Contract metadata not available".to_string()))
+    }
+
+    // Helper methods
+
+    fn encode_gas_limit(limit: &GasLimit) -> Result<subxt::dynamic::Value> {
+        Ok(subxt::dynamic::Value::named_composite([
+            (
+                "ref_time",
+                subxt::dynamic::Value::u128(limit.ref_time as u128),
+            ),
+            (
+                "proof_size",
+                subxt::dynamic::Value::u128(limit.proof_size as u128),
+            ),
+        ]))
+    }
+
+    fn encode_storage_deposit(limit: &StorageDepositLimit) -> Result<subxt::dynamic::Value> {
+        match limit {
+            StorageDepositLimit::NoLimit => {
+                Ok(subxt::dynamic::Value::unnamed_variant("None", vec![]))
+            }
+            StorageDepositLimit::Limited(amount) => Ok(subxt::dynamic::Value::unnamed_variant(
+                "Some",
+                vec![subxt::dynamic::Value::u128(*amount)],
+            )),
+        }
+    }
+}
+
+/// Parse contract metadata from JSON
+///
+/// Accepts ink! v4 and v5 metadata files as produced by `cargo contract`
+/// (recognised by their `version` field) as well as this crate's own
+/// serialized [`ContractMetadata`].
+pub fn parse_metadata(json: &str) -> Result<ContractMetadata> {
+    let value: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| Error::Metadata(format!("Failed to parse contract metadata: {}", e)))?;
+
+    if is_ink_metadata(&value) {
+        return ink::parse_ink_metadata(value);
+    }
+
+    serde_json::from_value(value)
+        .map_err(|e| Error::Metadata(format!("Failed to parse contract metadata: {}", e)))
+}
+
+/// Whether `value` is a `cargo contract` metadata file rather than a serialized
+/// [`ContractMetadata`]
+///
+/// Both carry a version (a string in ink! 4, a number in ink! 5 and here), but
+/// only ink! writes its types as scale-info `{ "id", "type" }` entries.
+fn is_ink_metadata(value: &serde_json::Value) -> bool {
+    let version = &value["version"];
+    let versioned = version.is_u64() || version.is_string();
+    let own_types = value["types"]
+        .as_array()
+        .is_some_and(|types| types.iter().any(|ty| ty.get("def").is_some()));
+    versioned && !own_types
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gas_limit() {
+        let limit = GasLimit::default_call();
+        assert!(limit.ref_time > 0);
+        assert!(limit.proof_size > 0);
+    }
+
+    #[test]
+    fn test_contract_call_builder() {
+        let address = [1u8; 32];
+        let selector = [0x12, 0x34, 0x56, 0x78];
+
+        let builder = ContractCallBuilder::new(address, selector)
+            .args(&[1, 2, 3])
+            .value(1000);
+
+        let call_data = builder.build_call_data();
+        assert_eq!(&call_data[0..4], &selector);
+        assert_eq!(&call_data[4..], &[1, 2, 3]);
+    }
+
+    const INK_V5_METADATA: &str = r#"{
+        "version": 5,
+        "types": [
+            {"id": 0, "type": {"def": {"primitive": "u128"}}},
+            {"id": 1, "type": {"def": {"primitive": "u8"}}},
+            {"id": 2, "type": {"def": {"array": {"len": 32, "type": 1}}}},
+            {"id": 3, "type": {
+                "path": ["ink_primitives", "types", "AccountId"],
+                "def": {"composite": {"fields": [{"type": 2, "typeName": "[u8; 32]"}]}}
+            }},
+            {"id": 4, "type": {
+                "path": ["ink_primitives", "LangError"],
+                "def": {"variant": {"variants": [{"name": "CouldNotReadInput", "index": 1}]}}
+            }},
+            {"id": 5, "type": {
+                "path": ["Result"],
+                "params": [{"name": "T", "type": 0}, {"name": "E", "type": 4}],
+                "def": {"variant": {"variants": [
+                    {"name": "Ok", "fields": [{"type": 0}], "index": 0},
+                    {"name": "Err", "fields": [{"type": 4}], "index": 1}
+                ]}}
+            }}
+        ],
+        "storage": {"root": {"root_key": "0x00000000", "ty": 0}},
+        "spec": {
+            "constructors": [
+                {"label": "new", "selector": "0x9bae9d5e",
+                 "args": [{"label": "total_supply", "type": {"type": 0, "displayName": ["Balance"]}}]}
+            ],
+            "messages": [
+                {"label": "balance_of", "selector": "0x0f755a56", "mutates": false, "payable": false,
+                 "args": [{"label": "owner", "type": {"type": 3, "displayName": ["AccountId"]}}],
+                 "returnType": {"type": 5, "displayName": ["ink", "MessageResult"]}}
+            ],
+            "events": [
+                {"label": "Transfer",
+                 "signature_topic": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+                 "args": [
+                    {"label": "to", "type": {"type": 3}, "indexed": true},
+                    {"label": "value", "type": {"type": 0}, "indexed": false}
+                 ]}
+            ],
+            "lang_error": {"type": 4, "displayName": ["ink", "LangError"]}
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_ink_v5_metadata() {
+        let metadata = parse_metadata(INK_V5_METADATA).unwrap();
+        assert_eq!(metadata.version, Some(5));
+        assert_eq!(metadata.types.len(), 6);
+        assert_eq!(metadata.lang_error.as_ref().map(|t| t.ty), Some(4));
+        assert_eq!(metadata.spec.events[0].signature_topic, Some([0xaa; 32]));
+    }
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let metadata = parse_metadata(INK_V5_METADATA).unwrap();
+        let owner = serde_json::json!(format!("0x{}", hex::encode([7u8; 32])));
+        let call_data = metadata
+            .encode_message_json("balance_of", std::slice::from_ref(&owner))
+            .unwrap();
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        for parsed in [
+            parse_metadata(&json).unwrap(),
+            serde_json::from_str::<ContractMetadata>(&json).unwrap(),
+        ] {
+            assert_eq!(parsed.version, Some(5));
+            assert_eq!(parsed.registry.types.len(), metadata.registry.types.len());
+            assert_eq!(
+                parsed
+                    .encode_message_json("balance_of", std::slice::from_ref(&owner))
+                    .unwrap(),
+                call_data
+            );
+        }
+
+        // Without a version the field is left out rather than written as null
+        let mut unversioned = metadata;
+        unversioned.version = None;
+        unversioned.lang_error = None;
+        let json = serde_json::to_value(&unversioned).unwrap();
+        assert!(json.get("version").is_none());
+        let parsed = parse_metadata(&json.to_string()).unwrap();
+        assert_eq!(parsed.version, None);
+        assert_eq!(parsed.types.len(), unversioned.types.len());
+    }
+
+    #[test]
+    fn test_encode_message_json() {
+        let metadata = parse_metadata(INK_V5_METADATA).unwrap();
+        let owner = format!("0x{}", hex::encode([7u8; 32]));
+
+        let call_data = metadata
+            .encode_message_json("balance_of", &[serde_json::json!(owner)])
+            .unwrap();
+        assert_eq!(&call_data[..4], &[0x0f, 0x75, 0x5a, 0x56]);
+        assert_eq!(&call_data[4..], &[7u8; 32]);
+
+        let call_data = metadata
+            .encode_constructor_json("new", &[serde_json::json!("1000")])
+            .unwrap();
+        assert_eq!(&call_data[..4], &[0x9b, 0xae, 0x9d, 0x5e]);
+        assert_eq!(&call_data[4..], &1000u128.to_le_bytes());
+
+        assert!(metadata.encode_message_json("balance_of", &[]).is_err());
+        assert!(metadata.encode_message_json("missing", &[]).is_err());
+    }
+
+    #[test]
+    fn test_decode_lang_result() {
+        let metadata = parse_metadata(INK_V5_METADATA).unwrap();
+
+        let mut ok = vec![0u8];
+        ok.extend_from_slice(&42u128.to_le_bytes());
+        let value = metadata.decode_return("balance_of", &ok).unwrap();
+        assert_eq!(value.as_u128(), Some(42));
+
+        let err = metadata.decode_return("balance_of", &[1, 1]).unwrap_err();
+        assert!(err.to_string().contains("CouldNotReadInput"));
+    }
+
+    #[test]
+    fn test_decode_event() {
+        let v5 = parse_metadata(INK_V5_METADATA).unwrap();
+        let mut data = vec![9u8; 32];
+        data.extend_from_slice(&5u128.to_le_bytes());
+
+        let event = v5.decode_event(&data, &[[0xaa; 32]]).unwrap();
+        assert_eq!(event.label, "Transfer");
+        assert_eq!(event.field("value").and_then(|v| v.as_u128()), Some(5));
+        assert_eq!(
+            event.to_json(&v5)["to"],
+            serde_json::json!(format!("0x{}", hex::encode([9u8; 32])))
+        );
+        assert!(v5.decode_event(&data, &[[0xbb; 32]]).is_err());
+
+        // ink! 4 prefixes the payload with the event index instead
+        let v4 = parse_metadata(
+            &INK_V5_METADATA
+                .replace("\"version\": 5", "\"version\": \"4\"")
+                .replace(
+                    "\"signature_topic\": \"0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\",",
+                    "",
+                ),
+        )
+        .unwrap();
+        let mut indexed = vec![0u8];
+        indexed.extend_from_slice(&data);
+        let event = v4.decode_event(&indexed, &[]).unwrap();
+        assert_eq!(event.label, "Transfer");
+        assert_eq!(v4.version, Some(4));
+    }
+}
@@ -0,0 +1,814 @@
+//! pallet-revive (PolkaVM) contracts
+//!
+//! `pallet_revive` runs PolkaVM bytecode compiled from Solidity (e.g. with
+//! `resolc`). Contracts have Ethereum-style H160 addresses and speak the
+//! Solidity ABI, so calls are encoded with [`ContractAbi`] from the EVM crate
+//! while transactions and dry-runs go through the `Revive` pallet and the
+//! `ReviveApi` runtime API.
+//!
+//! Accounts with 32-byte ids must be registered once with
+//! [`ReviveContract::map_account`] before they can deploy or call contracts.
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use apex_sdk_evm::abi::ContractAbi;
+//! use apex_sdk_substrate::contracts::revive::ReviveContract;
+//! use serde_json::json;
+//!
+//! let abi = ContractAbi::from_json(&std::fs::read_to_string("Token.json")?)?;
+//! let token = ReviveContract::instantiate_with_code(
+//!     client, code, abi, &[json!("1000000")], 0, None, &wallet,
+//! ).await?;
+//!
+//! let balance = token.read_json("balanceOf", &[json!(owner)], &caller).await?;
+//! token.call_json("transfer", &[json!(to), json!("100")], 0, &wallet).await?;
+//! ```
+
+use super::dry_run::{StorageDeposit, DEFAULT_GAS_MARGIN_PERCENT};
+use super::GasLimit;
+use crate::{Error, Result, Sr25519Signer, Wallet};
+use apex_sdk_evm::abi::{ContractAbi, DecodedLog};
+use parity_scale_codec::Decode;
+use sp_core::Pair;
+use subxt::dynamic::Value;
+use subxt::ext::scale_value::{At, ValueDef};
+use subxt::{OnlineClient, PolkadotConfig};
+use tracing::{debug, info};
+
+/// Ethereum-style contract address used by pallet-revive
+pub type ReviveAddress = [u8; 20];
+
+/// `ReturnFlags::REVERT` set by a contract that reverted its changes
+const REVERT_FLAG: u128 = 0x0000_0001;
+
+/// Address pallet-revive assigns to a 32-byte account id
+///
+/// Accounts derived from an Ethereum address (suffixed with twelve `0xEE`
+/// bytes) map back to that address; all others use the last 20 bytes of the
+/// account id's keccak-256 hash.
+pub fn address_from_account_id(account_id: &[u8; 32]) -> ReviveAddress {
+    let mut address = [0u8; 20];
+    if account_id[20..].iter().all(|b| *b == 0xEE) {
+        address.copy_from_slice(&account_id[..20]);
+    } else {
+        address.copy_from_slice(&sp_core::hashing::keccak_256(account_id)[12..]);
+    }
+    address
+}
+
+/// Code to instantiate a contract from
+#[derive(Debug, Clone)]
+pub enum ReviveCode {
+    /// Upload PolkaVM bytecode along with the instantiation
+    Upload(Vec<u8>),
+    /// Use code already stored on chain under this hash
+    Existing([u8; 32]),
+}
+
+/// How a pallet-revive dry-run ended
+#[derive(Debug, Clone)]
+pub enum ReviveOutcome {
+    /// The contract returned successfully
+    Success {
+        /// ABI-encoded return data
+        data: Vec<u8>,
+    },
+    /// The contract reverted
+    Reverted {
+        /// Raw revert data
+        data: Vec<u8>,
+        /// Revert reason decoded with the contract ABI, if known
+        reason: Option<String>,
+    },
+    /// The runtime rejected the execution with a `DispatchError`
+    Failed(String),
+}
+
+/// Result of a pallet-revive call or instantiation dry-run
+#[derive(Debug, Clone)]
+pub struct ReviveDryRun {
+    /// Weight consumed by the execution
+    pub gas_consumed: GasLimit,
+    /// Weight required for the execution to succeed
+    pub gas_required: GasLimit,
+    /// Storage deposit charged or refunded
+    pub storage_deposit: StorageDeposit,
+    /// Debug buffer output, on runtimes that still report it
+    pub debug_message: String,
+    /// How the execution ended
+    pub outcome: ReviveOutcome,
+    /// Address of the contract an instantiation would create
+    pub address: Option<ReviveAddress>,
+}
+
+impl ReviveDryRun {
+    /// Whether the execution succeeded without reverting
+    pub fn is_success(&self) -> bool {
+        matches!(self.outcome, ReviveOutcome::Success { .. })
+    }
+
+    /// Return data of a successful or reverted execution
+    pub fn data(&self) -> Option<&[u8]> {
+        match &self.outcome {
+            ReviveOutcome::Success { data } | ReviveOutcome::Reverted { data, .. } => Some(data),
+            ReviveOutcome::Failed(_) => None,
+        }
+    }
+
+    /// Gas limit to submit with: `gas_required` plus `margin_percent`
+    pub fn gas_limit(&self, margin_percent: u64) -> GasLimit {
+        self.gas_required.with_margin(margin_percent)
+    }
+
+    /// Storage deposit limit to submit with: the charged deposit plus `margin_percent`
+    pub fn storage_deposit_limit(&self, margin_percent: u64) -> u128 {
+        deposit_limit(self.storage_deposit, margin_percent)
+    }
+
+    /// Turn a reverted or failed dry-run into an error
+    pub fn ensure_success(self) -> Result<Self> {
+        match &self.outcome {
+            ReviveOutcome::Success { .. } => Ok(self),
+            ReviveOutcome::Reverted {
+                reason: Some(reason),
+                ..
+            } => Err(Error::Transaction(format!("Contract reverted: {}", reason))),
+            ReviveOutcome::Reverted { data, .. } => Err(Error::Transaction(format!(
+                "Contract reverted: 0x{}",
+                hex::encode(data)
+            ))),
+            ReviveOutcome::Failed(error) => Err(Error::Transaction(format!(
+                "Contract execution failed: {}",
+                error
+            ))),
+        }
+    }
+}
+
+/// An event log emitted by a pallet-revive contract
+#[derive(Debug, Clone)]
+pub struct ContractLog {
+    /// Log topics; the first is the event signature for non-anonymous events
+    pub topics: Vec<[u8; 32]>,
+    /// ABI-encoded non-indexed parameters
+    pub data: Vec<u8>,
+    /// The log decoded with the contract ABI, if it matched an event
+    pub decoded: Option<DecodedLog>,
+}
+
+/// Result of a submitted pallet-revive call
+#[derive(Debug, Clone)]
+pub struct ReviveExecution {
+    /// Transaction hash
+    pub tx_hash: String,
+    /// Logs emitted by the contract
+    pub logs: Vec<ContractLog>,
+}
+
+/// Client for a contract deployed with pallet-revive
+pub struct ReviveContract {
+    client: OnlineClient<PolkadotConfig>,
+    address: ReviveAddress,
+    abi: Option<ContractAbi>,
+    gas_margin: u64,
+}
+
+impl ReviveContract {
+    /// Create a client for an existing contract
+    pub fn new(client: OnlineClient<PolkadotConfig>, address: ReviveAddress) -> Self {
+        Self {
+            client,
+            address,
+            abi: None,
+            gas_margin: DEFAULT_GAS_MARGIN_PERCENT,
+        }
+    }
+
+    /// Create a client for an existing contract with its ABI
+    pub fn with_abi(
+        client: OnlineClient<PolkadotConfig>,
+        address: ReviveAddress,
+        abi: ContractAbi,
+    ) -> Self {
+        Self {
+            client,
+            address,
+            abi: Some(abi),
+            gas_margin: DEFAULT_GAS_MARGIN_PERCENT,
+        }
+    }
+
+    /// Set the margin (in percent) added to dry-run gas and storage deposit
+    /// estimates before submitting
+    pub fn with_gas_margin(mut self, percent: u64) -> Self {
+        self.gas_margin = percent;
+        self
+    }
+
+    /// Get the contract address
+    pub fn address(&self) -> &ReviveAddress {
+        &self.address
+    }
+
+    /// Get the contract ABI
+    pub fn abi(&self) -> Option<&ContractAbi> {
+        self.abi.as_ref()
+    }
+
+    /// Register the wallet's account with pallet-revive
+    ///
+    /// Required once per account before it can deploy or call contracts.
+    pub async fn map_account(
+        client: &OnlineClient<PolkadotConfig>,
+        wallet: &Wallet,
+    ) -> Result<String> {
+        info!("Mapping account to a pallet-revive address");
+        let tx = subxt::dynamic::tx("Revive", "map_account", Vec::<Value>::new());
+        let (tx_hash, _) = submit(client, &tx, wallet).await?;
+        Ok(tx_hash)
+    }
+
+    /// Upload PolkaVM code without instantiating it, returning the code hash
+    pub async fn upload_code(
+        client: &OnlineClient<PolkadotConfig>,
+        code: &[u8],
+        wallet: &Wallet,
+    ) -> Result<[u8; 32]> {
+        info!("Uploading contract code ({} bytes)", code.len());
+        let origin = signer_account(wallet)?;
+
+        let result = runtime_call(
+            client,
+            "upload_code",
+            vec![
+                Value::from_bytes(origin),
+                Value::from_bytes(code),
+                Value::unnamed_variant("None", vec![]),
+            ],
+        )
+        .await?;
+        let upload = split_result(&result, client)?
+            .map_err(|e| Error::Transaction(format!("Code upload would fail: {}", e)))?;
+        let code_hash = upload
+            .at("code_hash")
+            .and_then(bytes)
+            .and_then(|hash| <[u8; 32]>::try_from(hash).ok())
+            .ok_or_else(|| Error::Transaction("Invalid upload_code result".to_string()))?;
+        let deposit = upload
+            .at("deposit")
+            .and_then(first_u128)
+            .ok_or_else(|| Error::Transaction("Invalid upload_code result".to_string()))?;
+
+        let limit = deposit_limit(StorageDeposit::Charge(deposit), DEFAULT_GAS_MARGIN_PERCENT);
+        let tx = subxt::dynamic::tx(
+            "Revive",
+            "upload_code",
+            vec![Value::from_bytes(code), Value::u128(limit)],
+        );
+        submit(client, &tx, wallet).await?;
+
+        Ok(code_hash)
+    }
+
+    /// Upload PolkaVM code and instantiate it with ABI-encoded constructor arguments
+    ///
+    /// The instantiation is dry-run first; its estimates plus
+    /// [`DEFAULT_GAS_MARGIN_PERCENT`] are used as the transaction limits.
+    pub async fn instantiate_with_code(
+        client: OnlineClient<PolkadotConfig>,
+        code: Vec<u8>,
+        abi: ContractAbi,
+        args: &[serde_json::Value],
+        value: u128,
+        salt: Option<[u8; 32]>,
+        wallet: &Wallet,
+    ) -> Result<Self> {
+        Self::instantiate_code(
+            client,
+            ReviveCode::Upload(code),
+            abi,
+            args,
+            value,
+            salt,
+            wallet,
+        )
+        .await
+    }
+
+    /// Instantiate previously uploaded code (see [`ReviveContract::upload_code`])
+    pub async fn instantiate(
+        client: OnlineClient<PolkadotConfig>,
+        code_hash: [u8; 32],
+        abi: ContractAbi,
+        args: &[serde_json::Value],
+        value: u128,
+        salt: Option<[u8; 32]>,
+        wallet: &Wallet,
+    ) -> Result<Self> {
+        Self::instantiate_code(
+            client,
+            ReviveCode::Existing(code_hash),
+            abi,
+            args,
+            value,
+            salt,
+            wallet,
+        )
+        .await
+    }
+
+    async fn instantiate_code(
+        client: OnlineClient<PolkadotConfig>,
+        code: ReviveCode,
+        abi: ContractAbi,
+        args: &[serde_json::Value],
+        value: u128,
+        salt: Option<[u8; 32]>,
+        wallet: &Wallet,
+    ) -> Result<Self> {
+        info!("Instantiating pallet-revive contract");
+
+        let data = abi
+            .encode_constructor_json(args)
+            .map_err(|e| Error::Encoding(e.to_string()))?;
+        let origin = signer_account(wallet)?;
+
+        let estimate =
+            Self::dry_run_instantiate(&client, &code, &data, value, salt, &origin, Some(&abi))
+                .await?
+                .ensure_success()?;
+        debug!(
+            "Instantiate dry-run: gas_required={:?}, storage_deposit={:?}",
+            estimate.gas_required, estimate.storage_deposit
+        );
+
+        let gas_limit = estimate.gas_limit(DEFAULT_GAS_MARGIN_PERCENT);
+        let deposit_limit = estimate.storage_deposit_limit(DEFAULT_GAS_MARGIN_PERCENT);
+        let (call, code_value) = match &code {
+            ReviveCode::Upload(code) => ("instantiate_with_code", Value::from_bytes(code)),
+            ReviveCode::Existing(hash) => ("instantiate", Value::from_bytes(hash)),
+        };
+        let tx = subxt::dynamic::tx(
+            "Revive",
+            call,
+            vec![
+                Value::u128(value),
+                weight_value(&gas_limit),
+                Value::u128(deposit_limit),
+                code_value,
+                Value::from_bytes(&data),
+                salt_value(salt),
+            ],
+        );
+        let (_, events) = submit(&client, &tx, wallet).await?;
+
+        // Instantiated { deployer: H160, contract: H160 }
+        let address = events
+            .iter()
+            .flatten()
+            .filter(|evt| evt.pallet_name() == "Revive" && evt.variant_name() == "Instantiated")
+            .find_map(|evt| {
+                <(ReviveAddress, ReviveAddress)>::decode(&mut evt.field_bytes())
+                    .ok()
+                    .map(|(_, contract)| contract)
+            })
+            .or(estimate.address)
+            .ok_or_else(|| {
+                Error::Transaction(
+                    "Contract instantiation succeeded but address not found in events".to_string(),
+                )
+            })?;
+
+        info!("Contract instantiated at 0x{}", hex::encode(address));
+        Ok(Self::with_abi(client, address, abi))
+    }
+
+    /// Dry-run an instantiation through the `ReviveApi_instantiate` runtime API
+    ///
+    /// `data` holds the ABI-encoded constructor arguments. Revert reasons are
+    /// decoded with `abi` when given.
+    pub async fn dry_run_instantiate(
+        client: &OnlineClient<PolkadotConfig>,
+        code: &ReviveCode,
+        data: &[u8],
+        value: u128,
+        salt: Option<[u8; 32]>,
+        caller: &[u8; 32],
+        abi: Option<&ContractAbi>,
+    ) -> Result<ReviveDryRun> {
+        let code = match code {
+            ReviveCode::Upload(code) => {
+                Value::unnamed_variant("Upload", vec![Value::from_bytes(code)])
+            }
+            ReviveCode::Existing(hash) => {
+                Value::unnamed_variant("Existing", vec![Value::from_bytes(hash)])
+            }
+        };
+
+        let result = runtime_call(
+            client,
+            "instantiate",
+            vec![
+                Value::from_bytes(caller),
+                Value::u128(value),
+                Value::unnamed_variant("None", vec![]),
+                Value::unnamed_variant("None", vec![]),
+                code,
+                Value::from_bytes(data),
+                salt_value(salt),
+            ],
+        )
+        .await?;
+
+        decode_contract_result(&result, client, abi)
+    }
+
+    /// Dry-run a call through the `ReviveApi_call` runtime API
+    ///
+    /// `data` is the ABI-encoded call (selector and arguments).
+    pub async fn dry_run(
+        &self,
+        data: &[u8],
+        value: u128,
+        caller: &[u8; 32],
+    ) -> Result<ReviveDryRun> {
+        let result = runtime_call(
+            &self.client,
+            "call",
+            vec![
+                Value::from_bytes(caller),
+                Value::from_bytes(self.address),
+                Value::u128(value),
+                Value::unnamed_variant("None", vec![]),
+                Value::unnamed_variant("None", vec![]),
+                Value::from_bytes(data),
+            ],
+        )
+        .await?;
+
+        decode_contract_result(&result, &self.client, self.abi.as_ref())
+    }
+
+    /// Call the contract with ABI-encoded call data
+    ///
+    /// The call is dry-run first; its estimates plus the client's margin are
+    /// used as the transaction limits, and a revert is reported before submitting.
+    pub async fn call(&self, data: &[u8], value: u128, wallet: &Wallet) -> Result<ReviveExecution> {
+        let origin = signer_account(wallet)?;
+        let estimate = self.dry_run(data, value, &origin).await?.ensure_success()?;
+        debug!(
+            "Call dry-run: gas_required={:?}, storage_deposit={:?}",
+            estimate.gas_required, estimate.storage_deposit
+        );
+
+        let tx = subxt::dynamic::tx(
+            "Revive",
+            "call",
+            vec![
+                Value::from_bytes(self.address),
+                Value::u128(value),
+                weight_value(&estimate.gas_limit(self.gas_margin)),
+                Value::u128(estimate.storage_deposit_limit(self.gas_margin)),
+                Value::from_bytes(data),
+            ],
+        );
+        let (tx_hash, events) = submit(&self.client, &tx, wallet).await?;
+        info!("Contract call finalized: {}", tx_hash);
+
+        Ok(ReviveExecution {
+            tx_hash,
+            logs: self.contract_logs(&events),
+        })
+    }
+
+    /// Call a contract function with JSON arguments encoded through the ABI
+    pub async fn call_json(
+        &self,
+        function: &str,
+        args: &[serde_json::Value],
+        value: u128,
+        wallet: &Wallet,
+    ) -> Result<ReviveExecution> {
+        info!("Calling contract function: {}", function);
+        let data = self
+            .abi_ref()?
+            .encode_call_json(function, args)
+            .map_err(|e| Error::Encoding(e.to_string()))?;
+        self.call(&data, value, wallet).await
+    }
+
+    /// Read from the contract without submitting a transaction
+    ///
+    /// Returns the ABI-encoded return data; reverts are reported as errors.
+    pub async fn read(&self, data: &[u8], caller: &[u8; 32]) -> Result<Vec<u8>> {
+        let result = self.dry_run(data, 0, caller).await?.ensure_success()?;
+        Ok(result.data().unwrap_or_default().to_vec())
+    }
+
+    /// Read a contract function with JSON arguments, decoding the result as JSON
+    pub async fn read_json(
+        &self,
+        function: &str,
+        args: &[serde_json::Value],
+        caller: &[u8; 32],
+    ) -> Result<serde_json::Value> {
+        debug!("Reading contract function: {}", function);
+        let abi = self.abi_ref()?;
+        let data = abi
+            .encode_call_json(function, args)
+            .map_err(|e| Error::Encoding(e.to_string()))?;
+        let output = self.read(&data, caller).await?;
+        abi.decode_output_json(function, &output)
+            .map_err(|e| Error::Encoding(e.to_string()))
+    }
+
+    fn abi_ref(&self) -> Result<&ContractAbi> {
+        self.abi
+            .as_ref()
+            .ok_or_else(|| Error::Transaction("Contract ABI not available".to_string()))
+    }
+
+    /// Collect `Revive::ContractEmitted` logs emitted by this contract
+    fn contract_logs(
+        &self,
+        events: &subxt::blocks::ExtrinsicEvents<PolkadotConfig>,
+    ) -> Vec<ContractLog> {
+        events
+            .iter()
+            .flatten()
+            .filter(|evt| evt.pallet_name() == "Revive" && evt.variant_name() == "ContractEmitted")
+            // ContractEmitted { contract: H160, data: Vec<u8>, topics: Vec<H256> }
+            .filter_map(|evt| {
+                <(ReviveAddress, Vec<u8>, Vec<[u8; 32]>)>::decode(&mut evt.field_bytes()).ok()
+            })
+            .filter(|(contract, _, _)| *contract == self.address)
+            .map(|(_, data, topics)| {
+                let decoded = self
+                    .abi
+                    .as_ref()
+                    .and_then(|abi| abi.decode_log(&topics, &data).ok());
+                ContractLog {
+                    topics,
+                    data,
+                    decoded,
+                }
+            })
+            .collect()
+    }
+}
+
+fn signer_account(wallet: &Wallet) -> Result<[u8; 32]> {
+    wallet
+        .sr25519_pair()
+        .map(|pair| pair.public().0)
+        .ok_or_else(|| Error::Transaction("Wallet does not have SR25519 key".to_string()))
+}
+
+/// Sign, submit and wait for a successful finalized transaction
+async fn submit(
+    client: &OnlineClient<PolkadotConfig>,
+    tx: &subxt::tx::DynamicPayload,
+    wallet: &Wallet,
+) -> Result<(String, subxt::blocks::ExtrinsicEvents<PolkadotConfig>)> {
+    let pair = wallet
+        .sr25519_pair()
+        .ok_or_else(|| Error::Transaction("Wallet does not have SR25519 key".to_string()))?;
+    let signer = Sr25519Signer::new(pair.clone());
+
+    let mut progress = client
+        .tx()
+        .sign_and_submit_then_watch_default(tx, &signer)
+        .await
+        .map_err(|e| Error::Transaction(format!("Failed to submit transaction: {}", e)))?;
+
+    while let Some(event) = progress.next().await {
+        let event = event.map_err(|e| Error::Transaction(format!("Transaction error: {}", e)))?;
+
+        if let Some(finalized) = event.as_finalized() {
+            let tx_hash = format!("0x{}", hex::encode(finalized.extrinsic_hash()));
+            let events = finalized
+                .wait_for_success()
+                .await
+                .map_err(|e| Error::Transaction(format!("Transaction failed: {}", e)))?;
+            return Ok((tx_hash, events));
+        }
+    }
+
+    Err(Error::Transaction(
+        "Transaction stream ended without finalization".to_string(),
+    ))
+}
+
+/// Call a `ReviveApi` method at the latest block
+async fn runtime_call(
+    client: &OnlineClient<PolkadotConfig>,
+    method: &str,
+    args: Vec<Value>,
+) -> Result<Value> {
+    let payload = subxt::dynamic::runtime_api_call("ReviveApi", method, args);
+    Ok(client
+        .runtime_api()
+        .at_latest()
+        .await
+        .map_err(|e| Error::Connection(format!("Failed to get latest block: {}", e)))?
+        .call(payload)
+        .await
+        .map_err(|e| Error::Transaction(format!("ReviveApi::{} failed: {}", method, e)))?
+        .to_value()
+        .map_err(|e| Error::Transaction(format!("Failed to decode ReviveApi::{}: {}", method, e)))?
+        .remove_context())
+}
+
+/// Decode a `ContractResult` returned by `ReviveApi_call` or `ReviveApi_instantiate`
+fn decode_contract_result(
+    value: &Value,
+    client: &OnlineClient<PolkadotConfig>,
+    abi: Option<&ContractAbi>,
+) -> Result<ReviveDryRun> {
+    let invalid = || Error::Transaction("Invalid ReviveApi result".to_string());
+
+    let weight = |name: &str| -> Option<GasLimit> {
+        let weight = value.at(name)?;
+        Some(GasLimit::new(
+            u64::try_from(weight.at("ref_time")?.as_u128()?).ok()?,
+            u64::try_from(weight.at("proof_size")?.as_u128()?).ok()?,
+        ))
+    };
+    let storage_deposit = match &value.at("storage_deposit").ok_or_else(invalid)?.value {
+        ValueDef::Variant(deposit) => {
+            let amount = deposit.values.values().next().and_then(first_u128);
+            match (deposit.name.as_str(), amount) {
+                ("Refund", Some(amount)) => StorageDeposit::Refund(amount),
+                ("Charge", Some(amount)) => StorageDeposit::Charge(amount),
+                _ => return Err(invalid()),
+            }
+        }
+        _ => return Err(invalid()),
+    };
+
+    let mut dry_run = ReviveDryRun {
+        gas_consumed: weight("gas_consumed").ok_or_else(invalid)?,
+        gas_required: weight("gas_required").ok_or_else(invalid)?,
+        storage_deposit,
+        debug_message: value
+            .at("debug_message")
+            .and_then(bytes)
+            .map(|msg| String::from_utf8_lossy(&msg).into_owned())
+            .unwrap_or_default(),
+        outcome: ReviveOutcome::Failed(String::new()),
+        address: None,
+    };
+
+    let result = match split_result(value.at("result").ok_or_else(invalid)?, client)? {
+        Ok(result) => result,
+        Err(error) => {
+            dry_run.outcome = ReviveOutcome::Failed(error);
+            return Ok(dry_run);
+        }
+    };
+
+    // InstantiateReturnValue wraps the ExecReturnValue together with the address
+    let exec = match result.at("addr") {
+        Some(addr) => {
+            dry_run.address = bytes(addr).and_then(|addr| ReviveAddress::try_from(addr).ok());
+            result.at("result").ok_or_else(invalid)?
+        }
+        None => result,
+    };
+
+    let flags = exec.at("flags").and_then(first_u128).ok_or_else(invalid)?;
+    let data = exec.at("data").and_then(bytes).ok_or_else(invalid)?;
+    dry_run.outcome = if flags & REVERT_FLAG != 0 {
+        let reason = abi.and_then(|abi| abi.decode_revert(&data));
+        ReviveOutcome::Reverted { data, reason }
+    } else {
+        ReviveOutcome::Success { data }
+    };
+    Ok(dry_run)
+}
+
+/// Split a `Result` value into its `Ok` value or the rendered `DispatchError`
+fn split_result<'a>(
+    value: &'a Value,
+    client: &OnlineClient<PolkadotConfig>,
+) -> Result<std::result::Result<&'a Value, String>> {
+    let invalid = || Error::Transaction("Invalid ReviveApi result".to_string());
+    match &value.value {
+        ValueDef::Variant(result) if result.name == "Ok" => {
+            result.values.values().next().map(Ok).ok_or_else(invalid)
+        }
+        ValueDef::Variant(result) if result.name == "Err" => {
+            let error = result.values.values().next().ok_or_else(invalid)?;
+            Ok(Err(dispatch_error(error, client)))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+/// Render a decoded `DispatchError` the same way as ink! dry runs
+fn dispatch_error(error: &Value, client: &OnlineClient<PolkadotConfig>) -> String {
+    use subxt::ext::scale_encode::EncodeAsType as _;
+
+    let metadata = client.metadata();
+    metadata
+        .dispatch_error_ty()
+        .and_then(|ty| error.encode_as_type(ty, metadata.types()).ok())
+        .map(|encoded| super::dry_run::dispatch_error(&encoded, client))
+        .unwrap_or_else(|| error.to_string())
+}
+
+fn weight_value(limit: &GasLimit) -> Value {
+    Value::named_composite([
+        ("ref_time", Value::u128(limit.ref_time as u128)),
+        ("proof_size", Value::u128(limit.proof_size as u128)),
+    ])
+}
+
+/// Storage deposit limit as a plain balance, as pallet-revive expects it
+fn deposit_limit(deposit: StorageDeposit, margin_percent: u64) -> u128 {
+    match deposit.limit(margin_percent) {
+        super::StorageDepositLimit::Limited(limit) => limit,
+        super::StorageDepositLimit::NoLimit => u128::MAX,
+    }
+}
+
+fn salt_value(salt: Option<[u8; 32]>) -> Value {
+    match salt {
+        Some(salt) => Value::unnamed_variant("Some", vec![Value::from_bytes(salt)]),
+        None => Value::unnamed_variant("None", vec![]),
+    }
+}
+
+/// Bytes of a `Vec<u8>`, `[u8; N]` or newtype around one (like `H160`)
+fn bytes(value: &Value) -> Option<Vec<u8>> {
+    let ValueDef::Composite(fields) = &value.value else {
+        return None;
+    };
+    let values: Vec<&Value> = fields.values().collect();
+    if let [inner] = values[..] {
+        if matches!(inner.value, ValueDef::Composite(_)) {
+            return bytes(inner);
+        }
+    }
+    values
+        .into_iter()
+        .map(|v| v.as_u128().and_then(|b| u8::try_from(b).ok()))
+        .collect()
+}
+
+/// First primitive in a value, looking through newtypes like `ReturnFlags`
+fn first_u128(value: &Value) -> Option<u128> {
+    match &value.value {
+        ValueDef::Composite(fields) => fields.values().next().and_then(first_u128),
+        _ => value.as_u128(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_from_account_id() {
+        // Ethereum-derived accounts map back to their address
+        let mut eth_derived = [0xEE; 32];
+        eth_derived[..20].copy_from_slice(&[0x11; 20]);
+        assert_eq!(address_from_account_id(&eth_derived), [0x11; 20]);
+
+        let account = [1u8; 32];
+        let hash = sp_core::hashing::keccak_256(&account);
+        assert_eq!(address_from_account_id(&account)[..], hash[12..]);
+    }
+
+    #[test]
+    fn test_value_bytes() {
+        let h160 = Value::unnamed_composite([Value::from_bytes([7u8; 20])]);
+        assert_eq!(bytes(&h160), Some(vec![7u8; 20]));
+        assert_eq!(bytes(&Value::from_bytes([])), Some(vec![]));
+        assert_eq!(bytes(&Value::u128(1)), None);
+
+        let flags = Value::named_composite([("bits", Value::u128(1))]);
+        assert_eq!(first_u128(&flags), Some(REVERT_FLAG));
+    }
+
+    #[test]
+    fn test_dry_run_limits() {
+        let dry_run = ReviveDryRun {
+            gas_consumed: GasLimit::new(90, 9),
+            gas_required: GasLimit::new(100, 10),
+            storage_deposit: StorageDeposit::Charge(1_000),
+            debug_message: String::new(),
+            outcome: ReviveOutcome::Reverted {
+                data: vec![],
+                reason: Some("paused".to_string()),
+            },
+            address: None,
+        };
+
+        assert_eq!(dry_run.gas_limit(20).ref_time, 120);
+        assert_eq!(dry_run.storage_deposit_limit(20), 1_200);
+        let err = dry_run.ensure_success().unwrap_err();
+        assert!(err.to_string().contains("paused"));
+    }
+}
@@ -8,14 +8,16 @@
 //! - Storage queries
 //! - Connection pooling
 //! - Caching
+//! - Runtime upgrade detection
 //! - Metrics collection
 
 use apex_sdk_core::{
     BlockInfo, Broadcaster, ConfirmationStrategy, NonceManager, Provider as CoreProvider,
-    ReceiptWatcher, SdkError,
+    ReceiptWatcher, SdkError, Telemetry,
 };
 use apex_sdk_types::{Address, TransactionStatus, TxStatus};
 use async_trait::async_trait;
+use std::sync::Arc;
 use subxt::{OnlineClient, PolkadotConfig};
 use thiserror::Error;
 use tracing::{debug, info};
@@ -23,9 +25,11 @@ use tracing::{debug, info};
 pub mod block;
 pub mod cache;
 pub mod contracts;
+pub mod metadata_diff;
 pub mod metrics;
 pub mod nonce_manager;
 pub mod pool;
+pub mod runtime;
 pub mod signer;
 pub mod storage;
 pub mod transaction;
@@ -38,19 +42,27 @@ pub mod metadata;
 pub use block::BlockQuery;
 pub use cache::{Cache, CacheConfig};
 pub use contracts::{
-    parse_metadata, ContractCallBuilder, ContractClient, ContractMetadata, GasLimit,
+    json_to_value, parse_metadata, ContractCallBuilder, ContractClient, ContractEvent,
+    ContractExecution, ContractMetadata, DryRunOutcome, DryRunResult, GasLimit, StorageDeposit,
     StorageDepositLimit,
 };
+pub use metadata_diff::MetadataDiff;
 pub use metrics::{Metrics, MetricsSnapshot};
 pub use nonce_manager::SubstrateNonceManager;
 pub use pool::{ConnectionPool, PoolConfig};
+pub use runtime::{RuntimeUpgradeWatcher, RuntimeUpgraded};
 pub use signer::{ApexSigner, Ed25519Signer, Sr25519Signer};
-pub use storage::{AccountInfo, StorageClient, StorageQuery};
+pub use storage::{
+    value_to_json, AccountInfo, PageOptions, StorageChange, StorageChangeStream, StorageClient,
+    StorageEntry, StoragePage, StoragePageStream, StorageQuery, DEFAULT_PAGE_SIZE,
+};
 pub use transaction::{BatchCall, BatchMode, FeeConfig, RetryConfig, TransactionExecutor};
 pub use wallet::{KeyPairType, Wallet, WalletManager};
 pub use xcm::{
-    AssetId, Fungibility, Junction, MultiLocation, NetworkId, WeightLimit, XcmAsset, XcmConfig,
-    XcmExecutor, XcmTransferType, XcmVersion,
+    decode_versioned_location, encode_versioned_assets, encode_versioned_location, AssetFilter,
+    AssetId, ForwardedXcm, Fungibility, Junction, MultiLocation, NetworkId, OriginKind, Weight,
+    WeightLimit, WildAsset, XcmAsset, XcmConfig, XcmExecutor, XcmFeeEstimate, XcmInstruction,
+    XcmProgram, XcmSubmission, XcmTracker, XcmTransferType, XcmVersion,
 };
 
 /// Maximum number of blocks to search when looking up transaction history
@@ -190,12 +202,18 @@ pub struct SubstrateAdapter {
     endpoint: String,
     /// Subxt client
     client: OnlineClient<PolkadotConfig>,
+    /// Raw RPC client shared with the subxt client
+    rpc: subxt::backend::rpc::RpcClient,
     /// Chain configuration
     config: ChainConfig,
     /// Connection status
     connected: bool,
     /// Metrics collector
     metrics: Metrics,
+    /// Runtime upgrade watcher sharing the subxt client
+    upgrades: Arc<RuntimeUpgradeWatcher>,
+    /// Background task applying runtime upgrades
+    upgrade_task: tokio::task::JoinHandle<()>,
 }
 
 impl SubstrateAdapter {
@@ -208,8 +226,11 @@ impl SubstrateAdapter {
     pub async fn connect_with_config(config: ChainConfig) -> Result<Self> {
         info!("Connecting to {} at {}", config.name, config.endpoint);
 
-        // Create subxt client
-        let client = OnlineClient::<PolkadotConfig>::from_url(&config.endpoint)
+        // Create subxt client on top of a raw RPC client we keep for paged queries
+        let rpc = subxt::backend::rpc::RpcClient::from_url(&config.endpoint)
+            .await
+            .map_err(|e| Error::Connection(format!("Failed to connect: {}", e)))?;
+        let client = OnlineClient::<PolkadotConfig>::from_rpc_client(rpc.clone())
             .await
             .map_err(|e| Error::Connection(format!("Failed to connect: {}", e)))?;
 
@@ -217,15 +238,43 @@ impl SubstrateAdapter {
         let _metadata = client.metadata();
         debug!("Connected to {}", config.name);
 
+        let telemetry = Telemetry::new(&config.name, &config.endpoint);
+
+        // Swap in new metadata after runtime upgrades, whether or not anyone
+        // subscribes to the notifications
+        let upgrades = Arc::new(RuntimeUpgradeWatcher::new(client.clone()));
+        let upgrade_task = upgrades.clone().start();
+
         Ok(Self {
             endpoint: config.endpoint.clone(),
+            upgrades,
+            upgrade_task,
             client,
+            rpc,
             config,
             connected: true,
-            metrics: Metrics::new(),
+            metrics: Metrics::new().with_telemetry(telemetry),
         })
     }
 
+    /// Invalidate this cache's metadata entries whenever the runtime is upgraded
+    ///
+    /// The cache is attached to the adapter's running watcher, which keeps its
+    /// subscribers.
+    pub fn with_cache(self, cache: Cache) -> Self {
+        self.upgrades.attach_cache(cache);
+        self
+    }
+
+    /// Subscribe to runtime upgrade notifications.
+    ///
+    /// The adapter swaps in the new metadata after each upgrade on its own, so
+    /// executors and clients created from it keep working without
+    /// reconnecting; a notification is sent once the swap is done.
+    pub fn watch_runtime_upgrades(&self) -> tokio::sync::broadcast::Receiver<RuntimeUpgraded> {
+        self.upgrades.subscribe()
+    }
+
     /// Get reference to the subxt client
     pub fn client(&self) -> &OnlineClient<PolkadotConfig> {
         &self.client
@@ -251,6 +300,11 @@ impl SubstrateAdapter {
         self.metrics.snapshot()
     }
 
+    /// Get the live metrics handle, e.g. for a Prometheus exporter
+    pub fn rpc_metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
     /// Get block by hash
     ///
     /// This is more efficient than get_block if you have the block hash.
@@ -288,7 +342,6 @@ impl SubstrateAdapter {
         }
 
         debug!("Getting transaction status for: {}", tx_hash);
-        self.metrics.record_rpc_call("get_transaction_status");
 
         // Parse the transaction hash
         let hash_bytes = hex::decode(tx_hash.trim_start_matches("0x"))
@@ -309,9 +362,8 @@ impl SubstrateAdapter {
 
         // Get the latest finalized block
         let latest_block = self
-            .client
-            .blocks()
-            .at_latest()
+            .metrics
+            .instrument("get_transaction_status", self.client.blocks().at_latest())
             .await
             .map_err(|e| Error::Connection(format!("Failed to get latest block: {}", e)))?;
 
@@ -436,7 +488,6 @@ impl SubstrateAdapter {
         }
 
         debug!("Getting balance for address: {}", address);
-        self.metrics.record_rpc_call("get_balance");
 
         // Parse SS58 address to get AccountId32
         use sp_core::crypto::{AccountId32, Ss58Codec};
@@ -452,14 +503,18 @@ impl SubstrateAdapter {
         );
 
         let result = self
-            .client
-            .storage()
-            .at_latest()
-            .await
-            .map_err(|e| Error::Storage(format!("Failed to get latest block: {}", e)))?
-            .fetch(&storage_query)
-            .await
-            .map_err(|e| Error::Storage(format!("Failed to query storage: {}", e)))?;
+            .metrics
+            .instrument("get_balance", async {
+                self.client
+                    .storage()
+                    .at_latest()
+                    .await
+                    .map_err(|e| Error::Storage(format!("Failed to get latest block: {}", e)))?
+                    .fetch(&storage_query)
+                    .await
+                    .map_err(|e| Error::Storage(format!("Failed to query storage: {}", e)))
+            })
+            .await?;
 
         if let Some(account_data) = result {
             // Decode the storage value
@@ -514,6 +569,7 @@ impl SubstrateAdapter {
     /// Create a storage client for querying chain storage
     pub fn storage(&self) -> StorageClient {
         StorageClient::new(self.client.clone(), self.metrics.clone())
+            .with_rpc_client(self.rpc.clone())
     }
 
     /// Create a transaction executor
@@ -522,6 +578,8 @@ impl SubstrateAdapter {
     }
 
     /// Get runtime version
+    ///
+    /// Stays current after runtime upgrades.
     pub fn runtime_version(&self) -> u32 {
         self.client.runtime_version().spec_version
     }
@@ -532,6 +590,12 @@ impl SubstrateAdapter {
     }
 }
 
+impl Drop for SubstrateAdapter {
+    fn drop(&mut self) {
+        self.upgrade_task.abort();
+    }
+}
+
 #[async_trait]
 impl apex_sdk_core::ChainAdapter for SubstrateAdapter {
     async fn get_transaction_status(
@@ -701,13 +765,9 @@ impl SubstrateAdapter {
     }
 
     async fn submit_and_watch_extrinsic(&self, extrinsic_bytes: &[u8]) -> Result<String> {
-        use subxt::backend::{legacy::LegacyRpcMethods, rpc::RpcClient};
-
-        let rpc_client = RpcClient::from_url(&self.endpoint)
-            .await
-            .map_err(|e| Error::Connection(format!("Failed to create RPC client: {}", e)))?;
+        use subxt::backend::legacy::LegacyRpcMethods;
 
-        let legacy_rpc = LegacyRpcMethods::<PolkadotConfig>::new(rpc_client);
+        let legacy_rpc = LegacyRpcMethods::<PolkadotConfig>::new(self.rpc.clone());
 
         let tx_hash = legacy_rpc
             .author_submit_extrinsic(extrinsic_bytes)
@@ -728,21 +788,39 @@ impl ReceiptWatcher for SubstrateAdapter {
         &self,
         tx_hash: &str,
     ) -> std::result::Result<TransactionStatus, SdkError> {
+        self.wait_for_receipt_with_strategy(
+            tx_hash,
+            &ConfirmationStrategy::Finalized { timeout_secs: 60 },
+        )
+        .await
+    }
+
+    async fn wait_for_receipt_with_strategy(
+        &self,
+        tx_hash: &str,
+        strategy: &ConfirmationStrategy,
+    ) -> std::result::Result<TransactionStatus, SdkError> {
+        let timeout_secs = match strategy {
+            ConfirmationStrategy::BlockConfirmations { timeout_secs, .. }
+            | ConfirmationStrategy::Finalized { timeout_secs } => *timeout_secs,
+            ConfirmationStrategy::Immediate => {
+                return Ok(TransactionStatus::pending(tx_hash.to_string()))
+            }
+        };
+
         // Simple polling implementation
         // In a real implementation, we might want to use the retry/backoff logic or subscriptions
         let start = std::time::Instant::now();
-        let timeout = std::time::Duration::from_secs(60); // 60s timeout from plan
+        let timeout = std::time::Duration::from_secs(timeout_secs);
 
         while start.elapsed() < timeout {
             let status = self
                 .get_transaction_status(tx_hash)
                 .await
                 .map_err(|e| SdkError::NetworkError(e.to_string()))?;
-            // Check if status represents finalized or confirmed
-            if status.status == TxStatus::Confirmed || status.status == TxStatus::Finalized {
+            if confirmation_reached(&status, strategy) {
                 return Ok(status);
             }
-            // For Substrate, we default to finalized head confirmation policy
             tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
         }
 
@@ -751,17 +829,6 @@ impl ReceiptWatcher for SubstrateAdapter {
         ))
     }
 
-    async fn wait_for_receipt_with_strategy(
-        &self,
-        tx_hash: &str,
-        _strategy: &ConfirmationStrategy,
-    ) -> std::result::Result<TransactionStatus, SdkError> {
-        // For now, use the basic wait implementation regardless of strategy
-        self.wait_for_receipt(tx_hash)
-            .await
-            .map_err(|e| SdkError::NetworkError(e.to_string()))
-    }
-
     async fn get_receipt_status(
         &self,
         tx_hash: &str,
@@ -773,11 +840,59 @@ impl ReceiptWatcher for SubstrateAdapter {
     }
 }
 
+/// Whether a polled status ends the wait for `strategy`
+///
+/// Failures end it at once. Successful extrinsics are found in finalized
+/// blocks; `BlockConfirmations` also waits until that many finalized blocks
+/// follow the inclusion block.
+fn confirmation_reached(status: &TransactionStatus, strategy: &ConfirmationStrategy) -> bool {
+    match status.status {
+        TxStatus::Failed => true,
+        TxStatus::Confirmed | TxStatus::Finalized => match strategy {
+            ConfirmationStrategy::BlockConfirmations { confirmations, .. } => {
+                status.confirmations.unwrap_or(0) >= *confirmations
+            }
+            _ => true,
+        },
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use apex_sdk_types::Address;
 
+    #[test]
+    fn test_confirmation_reached_waits_for_depth() {
+        let strategy = ConfirmationStrategy::BlockConfirmations {
+            confirmations: 3,
+            timeout_secs: 60,
+        };
+        let included = |depth: u32| {
+            TransactionStatus::confirmed(
+                "0x01".to_string(),
+                10,
+                "0x02".to_string(),
+                None,
+                None,
+                Some(depth),
+            )
+        };
+
+        assert!(!confirmation_reached(&included(2), &strategy));
+        assert!(confirmation_reached(&included(3), &strategy));
+        assert!(confirmation_reached(
+            &included(0),
+            &ConfirmationStrategy::Finalized { timeout_secs: 60 }
+        ));
+
+        let failed = TransactionStatus::failed("0x01".to_string(), "boom".to_string());
+        assert!(confirmation_reached(&failed, &strategy));
+        let unknown = TransactionStatus::unknown("0x01".to_string());
+        assert!(!confirmation_reached(&unknown, &strategy));
+    }
+
     #[test]
     fn test_chain_config_polkadot() {
         let polkadot = ChainConfig::polkadot();
@@ -0,0 +1,744 @@
+//! Runtime metadata rendering and comparison
+//!
+//! This module turns runtime metadata into readable signatures and compares two
+//! versions of it, e.g. before and after a runtime upgrade:
+//! - [`type_name`] renders a registry type the way it is written in Rust
+//! - [`type_definition`] renders the full definition of a struct or enum
+//! - [`variant_signature`] renders a call, event or error with its fields
+//! - [`MetadataDiff`] lists pallets and items that were added, removed or changed
+//!
+//! Items count as changed when their signature, index or the full definition of
+//! any type they use differs, so a field added deep inside a struct is reported
+//! for every call, event or storage item using it.
+
+use scale_info::form::PortableForm;
+use scale_info::{PortableRegistry, TypeDef};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use subxt::ext::scale_value;
+
+/// Render a registry type by name, e.g. `Vec<AccountId32>` or `Option<u128>`
+///
+/// Composites and variants are shown by the last segment of their path with
+/// their generic parameters; anonymous ones by their type id.
+pub fn type_name(ty: u32, types: &PortableRegistry) -> String {
+    let Some(resolved) = types.resolve(ty) else {
+        return format!("#{}", ty);
+    };
+
+    match &resolved.type_def {
+        TypeDef::Sequence(seq) => format!("Vec<{}>", type_name(seq.type_param.id, types)),
+        TypeDef::Array(array) => {
+            format!("[{}; {}]", type_name(array.type_param.id, types), array.len)
+        }
+        TypeDef::Tuple(tuple) => format!(
+            "({})",
+            tuple
+                .fields
+                .iter()
+                .map(|f| type_name(f.id, types))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        TypeDef::Primitive(primitive) => format!("{:?}", primitive).to_lowercase(),
+        TypeDef::Compact(compact) => {
+            format!("Compact<{}>", type_name(compact.type_param.id, types))
+        }
+        TypeDef::BitSequence(_) => "BitVec".to_string(),
+        TypeDef::Composite(_) | TypeDef::Variant(_) => {
+            let name = resolved
+                .path
+                .segments
+                .last()
+                .cloned()
+                .unwrap_or_else(|| format!("#{}", ty));
+            let params: Vec<String> = resolved
+                .type_params
+                .iter()
+                .filter_map(|p| p.ty.map(|t| type_name(t.id, types)))
+                .collect();
+            if params.is_empty() {
+                name
+            } else {
+                format!("{}<{}>", name, params.join(", "))
+            }
+        }
+    }
+}
+
+/// Render the full definition of a registry type, e.g.
+/// `struct AccountInfo<u32, AccountData<u128>> { nonce: u32, ... }` or
+/// `enum Option<u32> { #0 None, #1 Some(u32) }`
+///
+/// Non-composite types are rendered by name, as with [`type_name`].
+pub fn type_definition(ty: u32, types: &PortableRegistry) -> String {
+    let Some(resolved) = types.resolve(ty) else {
+        return format!("#{}", ty);
+    };
+    let name = type_name(ty, types);
+    let path = if resolved.path.segments.is_empty() {
+        name
+    } else {
+        let params = name.find('<').map(|i| &name[i..]).unwrap_or_default();
+        format!("{}{}", resolved.path.segments.join("::"), params)
+    };
+
+    match &resolved.type_def {
+        TypeDef::Composite(composite) if composite.fields.is_empty() => {
+            format!("struct {}", path)
+        }
+        TypeDef::Composite(composite) if composite.fields[0].name.is_some() => {
+            format!(
+                "struct {} {{ {} }}",
+                path,
+                fields_signature(&composite.fields, types)
+            )
+        }
+        TypeDef::Composite(composite) => format!(
+            "struct {}({})",
+            path,
+            fields_signature(&composite.fields, types)
+        ),
+        TypeDef::Variant(variant) => format!(
+            "enum {} {{ {} }}",
+            path,
+            variant
+                .variants
+                .iter()
+                .map(|v| {
+                    if v.fields.is_empty() {
+                        format!("#{} {}", v.index, v.name)
+                    } else if v.fields[0].name.is_some() {
+                        format!(
+                            "#{} {} {{ {} }}",
+                            v.index,
+                            v.name,
+                            fields_signature(&v.fields, types)
+                        )
+                    } else {
+                        format!("#{} {}", v.index, variant_signature(v, types))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        _ => type_name(ty, types),
+    }
+}
+
+/// Render a call, event or error variant as `name(field: Type, ...)`
+pub fn variant_signature(
+    variant: &scale_info::Variant<PortableForm>,
+    types: &PortableRegistry,
+) -> String {
+    format!(
+        "{}({})",
+        variant.name,
+        fields_signature(&variant.fields, types)
+    )
+}
+
+fn fields_signature(
+    fields: &[scale_info::Field<PortableForm>],
+    types: &PortableRegistry,
+) -> String {
+    fields
+        .iter()
+        .map(|f| match &f.name {
+            Some(name) => format!("{}: {}", name, type_name(f.ty.id, types)),
+            None => type_name(f.ty.id, types),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Render a storage entry as `Map(hashers) Key -> Value` or `Plain Value`,
+/// prefixed with `Optional` or `Default`
+pub fn storage_signature(
+    entry: &subxt::metadata::types::StorageEntryMetadata,
+    types: &PortableRegistry,
+) -> String {
+    use subxt::metadata::types::{StorageEntryModifier, StorageEntryType};
+
+    let modifier = match entry.modifier() {
+        StorageEntryModifier::Optional => "Optional",
+        StorageEntryModifier::Default => "Default",
+    };
+    match entry.entry_type() {
+        StorageEntryType::Plain(value_ty) => {
+            format!("{} Plain {}", modifier, type_name(*value_ty, types))
+        }
+        StorageEntryType::Map {
+            hashers,
+            key_ty,
+            value_ty,
+        } => format!(
+            "{} Map({}) {} -> {}",
+            modifier,
+            hashers
+                .iter()
+                .map(|h| format!("{:?}", h))
+                .collect::<Vec<_>>()
+                .join(", "),
+            type_name(*key_ty, types),
+            type_name(*value_ty, types)
+        ),
+    }
+}
+
+/// Render a constant as `Type = value`, with the value decoded to JSON when possible
+pub fn constant_signature(
+    constant: &subxt::metadata::types::ConstantMetadata,
+    types: &PortableRegistry,
+) -> String {
+    let value = scale_value::scale::decode_as_type(&mut constant.value(), constant.ty(), types)
+        .map(|v| crate::storage::value_to_json(&v, types).to_string())
+        .unwrap_or_else(|_| format!("0x{}", hex::encode(constant.value())));
+    format!("{} = {}", type_name(constant.ty(), types), value)
+}
+
+/// Whether a pallet or item was added, removed or changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// Kind of pallet item compared by [`MetadataDiff`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+    Call,
+    Event,
+    Error,
+    Storage,
+    Constant,
+}
+
+impl std::fmt::Display for ItemKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ItemKind::Call => "call",
+            ItemKind::Event => "event",
+            ItemKind::Error => "error",
+            ItemKind::Storage => "storage",
+            ItemKind::Constant => "constant",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A changed call, event, error, storage item or constant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ItemDiff {
+    pub item: ItemKind,
+    pub name: String,
+    pub change: ChangeKind,
+    /// Signature before, absent for added items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<String>,
+    /// Signature after, absent for removed items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<String>,
+}
+
+/// Changes to one pallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PalletDiff {
+    pub name: String,
+    pub change: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_index: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_index: Option<u8>,
+    /// Item changes, for pallets present in both versions
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<ItemDiff>,
+}
+
+/// Differences between two versions of a runtime's metadata
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MetadataDiff {
+    /// Pallets that changed, ordered by name
+    pub pallets: Vec<PalletDiff>,
+}
+
+impl MetadataDiff {
+    /// Compare two metadata versions
+    pub fn between(old: &subxt::Metadata, new: &subxt::Metadata) -> Self {
+        Self::from_summaries(summarize(old), summarize(new))
+    }
+
+    /// Whether both versions are equivalent
+    pub fn is_empty(&self) -> bool {
+        self.pallets.is_empty()
+    }
+
+    fn from_summaries(
+        old: BTreeMap<String, PalletSummary>,
+        mut new: BTreeMap<String, PalletSummary>,
+    ) -> Self {
+        let mut pallets = Vec::new();
+
+        for (name, old_pallet) in old {
+            let Some(new_pallet) = new.remove(&name) else {
+                pallets.push(PalletDiff {
+                    name,
+                    change: ChangeKind::Removed,
+                    old_index: Some(old_pallet.index),
+                    new_index: None,
+                    items: Vec::new(),
+                });
+                continue;
+            };
+
+            let items = diff_items(old_pallet.items, new_pallet.items);
+            if items.is_empty() && old_pallet.index == new_pallet.index {
+                continue;
+            }
+            pallets.push(PalletDiff {
+                name,
+                change: ChangeKind::Changed,
+                old_index: Some(old_pallet.index),
+                new_index: Some(new_pallet.index),
+                items,
+            });
+        }
+
+        for (name, new_pallet) in new {
+            pallets.push(PalletDiff {
+                name,
+                change: ChangeKind::Added,
+                old_index: None,
+                new_index: Some(new_pallet.index),
+                items: Vec::new(),
+            });
+        }
+
+        pallets.sort_by(|a, b| a.name.cmp(&b.name));
+        Self { pallets }
+    }
+}
+
+/// A pallet's items keyed by kind and name
+#[derive(Debug, Clone, Default)]
+struct PalletSummary {
+    index: u8,
+    items: BTreeMap<(ItemKind, String), ItemSummary>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ItemSummary {
+    signature: String,
+    /// Hash of the full definitions of the types the item uses
+    fingerprint: u64,
+}
+
+fn diff_items(
+    old: BTreeMap<(ItemKind, String), ItemSummary>,
+    mut new: BTreeMap<(ItemKind, String), ItemSummary>,
+) -> Vec<ItemDiff> {
+    let mut items = Vec::new();
+
+    for ((item, name), old_item) in old {
+        match new.remove(&(item, name.clone())) {
+            None => items.push(ItemDiff {
+                item,
+                name,
+                change: ChangeKind::Removed,
+                old: Some(old_item.signature),
+                new: None,
+            }),
+            Some(new_item) if new_item != old_item => {
+                let mut new_signature = new_item.signature;
+                if new_signature == old_item.signature {
+                    new_signature.push_str(" (type definition changed)");
+                }
+                items.push(ItemDiff {
+                    item,
+                    name,
+                    change: ChangeKind::Changed,
+                    old: Some(old_item.signature),
+                    new: Some(new_signature),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+
+    for ((item, name), new_item) in new {
+        items.push(ItemDiff {
+            item,
+            name,
+            change: ChangeKind::Added,
+            old: None,
+            new: Some(new_item.signature),
+        });
+    }
+
+    items.sort_by(|a, b| (a.item, &a.name).cmp(&(b.item, &b.name)));
+    items
+}
+
+fn summarize(metadata: &subxt::Metadata) -> BTreeMap<String, PalletSummary> {
+    let types = metadata.types();
+    let mut fingerprints = Fingerprints::new(types);
+
+    metadata
+        .pallets()
+        .map(|pallet| {
+            let mut items = BTreeMap::new();
+
+            let variant_kinds = [
+                (ItemKind::Call, pallet.call_variants()),
+                (ItemKind::Event, pallet.event_variants()),
+                (ItemKind::Error, pallet.error_variants()),
+            ];
+            for (kind, variants) in variant_kinds {
+                for variant in variants.unwrap_or_default() {
+                    let signature =
+                        format!("#{} {}", variant.index, variant_signature(variant, types));
+                    let fingerprint =
+                        fingerprints.combine(&signature, variant.fields.iter().map(|f| f.ty.id));
+                    items.insert(
+                        (kind, variant.name.clone()),
+                        ItemSummary {
+                            signature,
+                            fingerprint,
+                        },
+                    );
+                }
+            }
+
+            if let Some(storage) = pallet.storage() {
+                for entry in storage.entries() {
+                    use subxt::metadata::types::StorageEntryType;
+
+                    let signature = storage_signature(entry, types);
+                    let type_ids = match entry.entry_type() {
+                        StorageEntryType::Plain(value_ty) => vec![*value_ty],
+                        StorageEntryType::Map {
+                            key_ty, value_ty, ..
+                        } => vec![*key_ty, *value_ty],
+                    };
+                    let fingerprint = fingerprints.combine(&signature, type_ids);
+                    items.insert(
+                        (ItemKind::Storage, entry.name().to_string()),
+                        ItemSummary {
+                            signature,
+                            fingerprint,
+                        },
+                    );
+                }
+            }
+
+            for constant in pallet.constants() {
+                let signature = constant_signature(constant, types);
+                let fingerprint = fingerprints.combine(
+                    &format!("{} 0x{}", signature, hex::encode(constant.value())),
+                    [constant.ty()],
+                );
+                items.insert(
+                    (ItemKind::Constant, constant.name().to_string()),
+                    ItemSummary {
+                        signature,
+                        fingerprint,
+                    },
+                );
+            }
+
+            (
+                pallet.name().to_string(),
+                PalletSummary {
+                    index: pallet.index(),
+                    items,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Structural hashes of registry types, independent of their type ids
+struct Fingerprints<'a> {
+    types: &'a PortableRegistry,
+    memo: HashMap<u32, u64>,
+    visiting: HashSet<u32>,
+    /// Back references seen so far; hashes computed across one are not memoized,
+    /// as they depend on where the cycle was entered
+    back_references: usize,
+}
+
+impl<'a> Fingerprints<'a> {
+    fn new(types: &'a PortableRegistry) -> Self {
+        Self {
+            types,
+            memo: HashMap::new(),
+            visiting: HashSet::new(),
+            back_references: 0,
+        }
+    }
+
+    /// Hash a signature together with the types it refers to
+    fn combine(&mut self, signature: &str, type_ids: impl IntoIterator<Item = u32>) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        signature.hash(&mut hasher);
+        for ty in type_ids {
+            self.of(ty).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    fn of(&mut self, ty: u32) -> u64 {
+        if let Some(hash) = self.memo.get(&ty) {
+            return *hash;
+        }
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        let Some(resolved) = self.types.resolve(ty) else {
+            "unknown".hash(&mut hasher);
+            return hasher.finish();
+        };
+        resolved.path.segments.hash(&mut hasher);
+
+        // Recursive types refer back to themselves by path only
+        if !self.visiting.insert(ty) {
+            self.back_references += 1;
+            "recursive".hash(&mut hasher);
+            return hasher.finish();
+        }
+        let back_references = self.back_references;
+
+        match &resolved.type_def {
+            TypeDef::Composite(composite) => {
+                "composite".hash(&mut hasher);
+                self.hash_fields(&composite.fields, &mut hasher);
+            }
+            TypeDef::Variant(variant) => {
+                "variant".hash(&mut hasher);
+                for v in &variant.variants {
+                    (v.index, &v.name).hash(&mut hasher);
+                    self.hash_fields(&v.fields, &mut hasher);
+                }
+            }
+            TypeDef::Sequence(seq) => ("sequence", self.of(seq.type_param.id)).hash(&mut hasher),
+            TypeDef::Array(array) => {
+                ("array", array.len, self.of(array.type_param.id)).hash(&mut hasher)
+            }
+            TypeDef::Tuple(tuple) => {
+                "tuple".hash(&mut hasher);
+                for field in &tuple.fields {
+                    self.of(field.id).hash(&mut hasher);
+                }
+            }
+            TypeDef::Primitive(primitive) => format!("{:?}", primitive).hash(&mut hasher),
+            TypeDef::Compact(compact) => {
+                ("compact", self.of(compact.type_param.id)).hash(&mut hasher)
+            }
+            TypeDef::BitSequence(bits) => (
+                "bits",
+                self.of(bits.bit_store_type.id),
+                self.of(bits.bit_order_type.id),
+            )
+                .hash(&mut hasher),
+        }
+
+        self.visiting.remove(&ty);
+        let hash = hasher.finish();
+        if self.back_references == back_references {
+            self.memo.insert(ty, hash);
+        }
+        hash
+    }
+
+    fn hash_fields(
+        &mut self,
+        fields: &[scale_info::Field<PortableForm>],
+        hasher: &mut impl Hasher,
+    ) {
+        for field in fields {
+            field.name.hash(hasher);
+            self.of(field.ty.id).hash(hasher);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry_with<T: scale_info::TypeInfo + 'static>() -> (PortableRegistry, u32) {
+        let mut registry = scale_info::Registry::new();
+        let id = registry.register_type(&scale_info::meta_type::<T>()).id;
+        (registry.into(), id)
+    }
+
+    #[test]
+    fn test_type_name() {
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        struct AccountData<Balance> {
+            free: Balance,
+        }
+
+        let (types, id) = registry_with::<(Vec<u8>, Option<u32>, [u8; 32], AccountData<u128>)>();
+        assert_eq!(
+            type_name(id, &types),
+            "(Vec<u8>, Option<u32>, [u8; 32], AccountData<u128>)"
+        );
+        assert_eq!(type_name(9999, &types), "#9999");
+    }
+
+    #[test]
+    fn test_variant_signature() {
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        enum Call {
+            Transfer { dest: [u8; 32], value: u128 },
+            Remark(Vec<u8>),
+        }
+
+        let (types, id) = registry_with::<Call>();
+        let TypeDef::Variant(variant) = &types.resolve(id).unwrap().type_def else {
+            panic!("not a variant");
+        };
+        assert_eq!(
+            variant_signature(&variant.variants[0], &types),
+            "Transfer(dest: [u8; 32], value: u128)"
+        );
+        assert_eq!(
+            variant_signature(&variant.variants[1], &types),
+            "Remark(Vec<u8>)"
+        );
+        assert!(type_definition(id, &types).ends_with(
+            "::Call { #0 Transfer { dest: [u8; 32], value: u128 }, #1 Remark(Vec<u8>) }"
+        ));
+    }
+
+    #[test]
+    fn test_fingerprint_sees_nested_changes() {
+        #[allow(dead_code)]
+        mod v1 {
+            #[derive(scale_info::TypeInfo)]
+            pub struct Inner {
+                pub a: u32,
+            }
+            #[derive(scale_info::TypeInfo)]
+            pub struct Outer {
+                pub inner: Inner,
+            }
+        }
+        #[allow(dead_code)]
+        mod v2 {
+            #[derive(scale_info::TypeInfo)]
+            pub struct Inner {
+                pub a: u32,
+                pub b: u64,
+            }
+            #[derive(scale_info::TypeInfo)]
+            pub struct Outer {
+                pub inner: Inner,
+            }
+        }
+
+        let (old_types, old_id) = registry_with::<v1::Outer>();
+        let (new_types, new_id) = registry_with::<v2::Outer>();
+        let (same_types, same_id) = registry_with::<v1::Outer>();
+
+        let old = Fingerprints::new(&old_types).of(old_id);
+        assert_ne!(old, Fingerprints::new(&new_types).of(new_id));
+        assert_eq!(old, Fingerprints::new(&same_types).of(same_id));
+    }
+
+    #[test]
+    fn test_diff_summaries() {
+        fn item(signature: &str, fingerprint: u64) -> ItemSummary {
+            ItemSummary {
+                signature: signature.to_string(),
+                fingerprint,
+            }
+        }
+        fn pallet(index: u8, items: &[(ItemKind, &str, ItemSummary)]) -> PalletSummary {
+            PalletSummary {
+                index,
+                items: items
+                    .iter()
+                    .map(|(kind, name, summary)| ((*kind, name.to_string()), summary.clone()))
+                    .collect(),
+            }
+        }
+
+        let old = BTreeMap::from([
+            (
+                "Balances".to_string(),
+                pallet(
+                    5,
+                    &[
+                        (
+                            ItemKind::Call,
+                            "transfer",
+                            item("#0 transfer(value: u128)", 1),
+                        ),
+                        (ItemKind::Call, "burn", item("#1 burn(value: u128)", 2)),
+                        (ItemKind::Storage, "Account", item("Default Map", 3)),
+                    ],
+                ),
+            ),
+            ("Sudo".to_string(), pallet(9, &[])),
+            ("System".to_string(), pallet(0, &[])),
+        ]);
+        let new = BTreeMap::from([
+            (
+                "Balances".to_string(),
+                pallet(
+                    5,
+                    &[
+                        (
+                            ItemKind::Call,
+                            "transfer",
+                            item("#0 transfer(value: u64)", 4),
+                        ),
+                        (ItemKind::Storage, "Account", item("Default Map", 5)),
+                        (
+                            ItemKind::Event,
+                            "Minted",
+                            item("#2 Minted(amount: u128)", 6),
+                        ),
+                    ],
+                ),
+            ),
+            ("Revive".to_string(), pallet(10, &[])),
+            ("System".to_string(), pallet(0, &[])),
+        ]);
+
+        let diff = MetadataDiff::from_summaries(old, new);
+        let names: Vec<_> = diff
+            .pallets
+            .iter()
+            .map(|p| (p.name.as_str(), p.change))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("Balances", ChangeKind::Changed),
+                ("Revive", ChangeKind::Added),
+                ("Sudo", ChangeKind::Removed),
+            ]
+        );
+
+        let items = &diff.pallets[0].items;
+        assert_eq!(items.len(), 4);
+        assert_eq!(
+            (items[0].item, items[0].name.as_str(), items[0].change),
+            (ItemKind::Call, "burn", ChangeKind::Removed)
+        );
+        assert_eq!(items[1].new.as_deref(), Some("#0 transfer(value: u64)"));
+        assert_eq!(items[2].change, ChangeKind::Added);
+        assert_eq!(
+            items[3].new.as_deref(),
+            Some("Default Map (type definition changed)")
+        );
+    }
+}
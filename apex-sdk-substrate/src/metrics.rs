@@ -4,9 +4,14 @@
 //! - Metrics tracking for RPC calls, extrinsic submissions, and errors
 //! - A snapshot mechanism for retrieving current metrics
 //! - Atomic counters for thread-safe metric updates
+//! - Forwarding of RPC spans and latencies to the core [`Telemetry`] layer
 
+use apex_sdk_core::Telemetry;
+use std::fmt::Display;
+use std::future::IntoFuture;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 /// A snapshot of the current metrics
 #[derive(Debug, Clone, Default)]
@@ -34,6 +39,7 @@ pub struct Metrics {
     extrinsics_failed: Arc<AtomicU64>,
     connection_errors: Arc<AtomicU64>,
     total_rpc_response_time_ms: Arc<AtomicU64>,
+    telemetry: Telemetry,
 }
 
 impl Metrics {
@@ -42,14 +48,45 @@ impl Metrics {
         Self::default()
     }
 
+    /// Attach a telemetry handle so RPC latencies carry chain and endpoint labels
+    pub fn with_telemetry(mut self, telemetry: Telemetry) -> Self {
+        self.telemetry = telemetry;
+        self
+    }
+
+    /// Get the telemetry handle
+    pub fn telemetry(&self) -> &Telemetry {
+        &self.telemetry
+    }
+
     /// Record an RPC call
+    #[deprecated(note = "does not record latency, use `record_timed_rpc_call`")]
     pub fn record_rpc_call(&self, call_name: &str) {
         self.rpc_calls.fetch_add(1, Ordering::Relaxed);
         // In a real-world scenario, you might want to track metrics per call type
         let _ = call_name;
     }
 
-    /// Record a storage query
+    /// Record an RPC call and its response time
+    pub fn record_timed_rpc_call(&self, duration: Duration) {
+        self.rpc_calls.fetch_add(1, Ordering::Relaxed);
+        self.total_rpc_response_time_ms
+            .fetch_add(duration.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Run an RPC call inside a span, recording it here and in the telemetry layer
+    pub async fn instrument<F, T, E>(&self, call_name: &str, fut: F) -> Result<T, E>
+    where
+        F: IntoFuture<Output = Result<T, E>>,
+        E: Display,
+    {
+        let start = Instant::now();
+        let result = self.telemetry.instrument_rpc(call_name, fut).await;
+        self.record_timed_rpc_call(start.elapsed());
+        result
+    }
+
+    /// Record a storage query that is not run through [`Metrics::instrument`]
     pub fn record_storage_query(&self) {
         // For now, treat storage queries as RPC calls
         self.rpc_calls.fetch_add(1, Ordering::Relaxed);
@@ -80,11 +117,7 @@ impl Metrics {
         let rpc_calls = self.rpc_calls.load(Ordering::Relaxed);
         let total_response_time = self.total_rpc_response_time_ms.load(Ordering::Relaxed);
 
-        let avg_rpc_response_time_ms = if rpc_calls > 0 {
-            total_response_time / rpc_calls
-        } else {
-            0
-        };
+        let avg_rpc_response_time_ms = total_response_time.checked_div(rpc_calls).unwrap_or(0);
 
         MetricsSnapshot {
             rpc_calls,
@@ -96,3 +129,37 @@ impl Metrics {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_instrument_counts_rpc_calls() {
+        let metrics = Metrics::new();
+        let _ = metrics
+            .instrument("ok", async { Ok::<_, String>(()) })
+            .await;
+        let _ = metrics
+            .instrument("err", async { Err::<(), _>("boom".to_string()) })
+            .await;
+        metrics.record_storage_query();
+
+        assert_eq!(metrics.snapshot().rpc_calls, 3);
+    }
+
+    #[test]
+    fn test_timed_rpc_calls_average() {
+        let metrics = Metrics::new();
+        assert_eq!(metrics.snapshot().avg_rpc_response_time_ms, 0);
+
+        metrics.record_timed_rpc_call(Duration::from_millis(10));
+        metrics.record_timed_rpc_call(Duration::from_millis(30));
+        #[allow(deprecated)]
+        metrics.record_rpc_call("state_getStorage");
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.rpc_calls, 3);
+        assert_eq!(snapshot.avg_rpc_response_time_ms, 13);
+    }
+}
@@ -153,7 +153,7 @@ impl CoreNonceManager for SubstrateNonceManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{Metrics, SubstrateAdapter};
+    use crate::SubstrateAdapter;
 
     #[tokio::test]
     async fn test_nonce_manager_basic() {
@@ -165,7 +165,7 @@ mod tests {
                 .await
                 .expect("Should connect to Substrate node");
 
-            let storage_client = StorageClient::new(adapter.client().clone(), Metrics::new());
+            let storage_client = adapter.storage();
             let nonce_manager = SubstrateNonceManager::new(storage_client);
 
             // Test with a known address (Alice on dev chains)
@@ -196,7 +196,7 @@ mod tests {
                 .await
                 .expect("Should connect to Substrate node");
 
-            let storage_client = StorageClient::new(adapter.client().clone(), Metrics::new());
+            let storage_client = adapter.storage();
             let nonce_manager = SubstrateNonceManager::new(storage_client);
 
             let address_str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
@@ -228,7 +228,7 @@ mod tests {
                 .await
                 .expect("Should connect to Substrate node");
 
-            let storage_client = StorageClient::new(adapter.client().clone(), Metrics::new());
+            let storage_client = adapter.storage();
             let nonce_manager = SubstrateNonceManager::new(storage_client);
 
             let address_str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
@@ -250,7 +250,6 @@ mod tests {
     #[test]
     fn test_local_nonce_cache_without_network() {
         let substrate_url = "ws://localhost:9944".to_string();
-        let metrics = Metrics::new();
 
         // This test doesn't require actual network connection
         // We just test the local cache operations
@@ -258,7 +257,7 @@ mod tests {
         rt.block_on(async {
             // Skip if we can't connect, this is just for cache testing
             if let Ok(adapter) = SubstrateAdapter::connect(&substrate_url).await {
-                let storage_client = StorageClient::new(adapter.client().clone(), metrics);
+                let storage_client = adapter.storage();
                 let nonce_manager = SubstrateNonceManager::new(storage_client);
 
                 let address = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
@@ -2,26 +2,21 @@
 //!
 //! This module provides:
 //! - Connection pooling with round-robin load balancing
-//! - Health checks for WebSocket endpoints
+//! - Health checks for WebSocket endpoints, including head lag and genesis verification
 //! - Automatic failover to backup endpoints
 //! - Connection reuse
 
 use crate::{Error, SubstrateAdapter};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use apex_sdk_core::health::{HealthCheck, PoolHealth, Probe};
+use async_trait::async_trait;
+use futures::future::join_all;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
-/// Health status enumeration
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum HealthStatus {
-    /// Endpoint is healthy and responsive
-    Healthy,
-    /// Endpoint is not responding or failing requests
-    Unhealthy,
-    /// Health status is not yet determined
-    Unknown,
-}
+pub use apex_sdk_core::health::{HeadInfo, HealthStatus};
 
 /// Pool statistics
 #[derive(Debug, Clone, Default)]
@@ -44,32 +39,7 @@ pub struct PoolStats {
     pub pool_misses: u64,
 }
 
-/// Health status of an endpoint
-#[derive(Debug, Clone)]
-pub struct EndpointHealth {
-    /// Whether the endpoint is currently healthy
-    pub is_healthy: bool,
-    /// Last successful connection timestamp
-    pub last_success: Option<Instant>,
-    /// Last failed connection timestamp
-    pub last_failure: Option<Instant>,
-    /// Consecutive failure count
-    pub failure_count: u32,
-    /// Average response time in milliseconds
-    pub avg_response_time_ms: u64,
-}
-
-impl Default for EndpointHealth {
-    fn default() -> Self {
-        Self {
-            is_healthy: true,
-            last_success: None,
-            last_failure: None,
-            failure_count: 0,
-            avg_response_time_ms: 0,
-        }
-    }
-}
+pub use apex_sdk_core::health::EndpointHealth;
 
 /// Pooled connection to a Substrate endpoint
 pub struct PooledConnection {
@@ -96,30 +66,12 @@ impl PooledConnection {
 
     /// Mark connection as healthy after successful operation
     pub async fn mark_healthy(&self, response_time_ms: u64) {
-        let mut health = self.health.write().await;
-        health.is_healthy = true;
-        health.last_success = Some(Instant::now());
-        health.failure_count = 0;
-
-        // Update average response time (exponential moving average)
-        if health.avg_response_time_ms == 0 {
-            health.avg_response_time_ms = response_time_ms;
-        } else {
-            health.avg_response_time_ms = (health.avg_response_time_ms * 9 + response_time_ms) / 10;
-        }
+        self.health.write().await.record_success(response_time_ms);
     }
 
     /// Mark connection as unhealthy after failure
     pub async fn mark_unhealthy(&self) {
-        let mut health = self.health.write().await;
-        health.last_failure = Some(Instant::now());
-        health.failure_count += 1;
-
-        // Mark as unhealthy after 3 consecutive failures
-        if health.failure_count >= 3 {
-            health.is_healthy = false;
-            tracing::warn!("Endpoint {} marked as unhealthy", self.endpoint);
-        }
+        self.health.write().await.record_failure(&self.endpoint);
     }
 }
 
@@ -136,6 +88,11 @@ pub struct PoolConfig {
     pub max_failures: u32,
     /// Time to wait before retrying unhealthy endpoint (seconds)
     pub unhealthy_retry_delay_secs: u64,
+    /// Maximum number of blocks an endpoint may lag behind the best head before
+    /// it is marked degraded
+    pub max_block_lag: u64,
+    /// Genesis hash every endpoint must report; when unset, the majority wins
+    pub expected_genesis_hash: Option<String>,
 }
 
 impl Default for PoolConfig {
@@ -146,6 +103,8 @@ impl Default for PoolConfig {
             health_check_timeout_secs: 5,
             max_failures: 3,
             unhealthy_retry_delay_secs: 60,
+            max_block_lag: 5,
+            expected_genesis_hash: None,
         }
     }
 }
@@ -175,6 +134,18 @@ impl PoolConfig {
         self
     }
 
+    /// Set the maximum block lag before an endpoint is marked degraded
+    pub fn with_max_block_lag(mut self, max_block_lag: u64) -> Self {
+        self.max_block_lag = max_block_lag;
+        self
+    }
+
+    /// Require every endpoint to report the given genesis hash
+    pub fn with_expected_genesis_hash(mut self, genesis_hash: impl Into<String>) -> Self {
+        self.expected_genesis_hash = Some(genesis_hash.into());
+        self
+    }
+
     /// Set auto health check enabled/disabled
     pub fn with_auto_health_check(self, _enabled: bool) -> Self {
         // Note: In this implementation, health checks are always enabled
@@ -185,9 +156,11 @@ impl PoolConfig {
 /// Connection pool for Substrate providers
 pub struct ConnectionPool {
     endpoints: Vec<String>,
+    chain_name: String,
     connections: Arc<RwLock<Vec<PooledConnection>>>,
     next_index: AtomicUsize,
     config: PoolConfig,
+    health_checked: AtomicBool,
 }
 
 impl ConnectionPool {
@@ -241,11 +214,18 @@ impl ConnectionPool {
             }
         }
 
+        let chain_name = connections
+            .first()
+            .map(|conn| conn.adapter.chain_name().to_string())
+            .unwrap_or_default();
+
         Ok(Self {
             endpoints,
+            chain_name,
             connections: Arc::new(RwLock::new(connections)),
             next_index: AtomicUsize::new(0),
             config,
+            health_checked: AtomicBool::new(false),
         })
     }
 
@@ -261,6 +241,7 @@ impl ConnectionPool {
 
         let total = connections.len();
         let mut attempts = 0;
+        let mut degraded = None;
 
         // Try to find a healthy connection
         while attempts < total {
@@ -268,7 +249,12 @@ impl ConnectionPool {
             let conn = &connections[index];
 
             let health = conn.health.read().await;
-            if health.is_healthy {
+            if health.is_healthy && health.status == HealthStatus::Degraded {
+                // Usable, but only if nothing better is available
+                degraded.get_or_insert(index);
+                attempts += 1;
+                continue;
+            } else if health.is_healthy {
                 drop(health);
                 return Ok(Arc::new(PooledConnection {
                     adapter: conn.adapter.clone(),
@@ -277,8 +263,12 @@ impl ConnectionPool {
                 }));
             }
 
-            // Check if enough time has passed to retry unhealthy endpoint
-            if let Some(last_failure) = health.last_failure {
+            // Check if enough time has passed to retry unhealthy endpoint;
+            // endpoints on the wrong network wait for the next health check
+            if let (Some(last_failure), false) = (
+                health.last_failure,
+                health.status == HealthStatus::WrongNetwork,
+            ) {
                 if last_failure.elapsed().as_secs() > self.config.unhealthy_retry_delay_secs {
                     drop(health);
                     tracing::info!("Retrying previously unhealthy endpoint: {}", conn.endpoint);
@@ -293,14 +283,35 @@ impl ConnectionPool {
             attempts += 1;
         }
 
-        // All endpoints unhealthy, return the first one and let caller handle retry
-        let conn = &connections[0];
-        tracing::warn!("All endpoints unhealthy, returning first endpoint");
-        Ok(Arc::new(PooledConnection {
-            adapter: conn.adapter.clone(),
-            endpoint: conn.endpoint.clone(),
-            health: conn.health.clone(),
-        }))
+        if let Some(index) = degraded {
+            let conn = &connections[index];
+            tracing::debug!(
+                "No fully healthy endpoint, using degraded {}",
+                conn.endpoint
+            );
+            return Ok(Arc::new(PooledConnection {
+                adapter: conn.adapter.clone(),
+                endpoint: conn.endpoint.clone(),
+                health: conn.health.clone(),
+            }));
+        }
+
+        // All endpoints unhealthy, return the first one on the expected
+        // network and let caller handle retry
+        for conn in connections.iter() {
+            if conn.health.read().await.status != HealthStatus::WrongNetwork {
+                tracing::warn!("All endpoints unhealthy, returning {}", conn.endpoint);
+                return Ok(Arc::new(PooledConnection {
+                    adapter: conn.adapter.clone(),
+                    endpoint: conn.endpoint.clone(),
+                    health: conn.health.clone(),
+                }));
+            }
+        }
+
+        Err(Error::Connection(
+            "All endpoints serve another network".to_string(),
+        ))
     }
 
     /// Get health status of all endpoints
@@ -317,28 +328,88 @@ impl ConnectionPool {
     }
 
     /// Run health checks on all endpoints
+    ///
+    /// Each endpoint is probed concurrently for its genesis hash and latest
+    /// block. Endpoints connected to a chain with a different genesis hash are
+    /// marked unhealthy, and endpoints lagging behind or forked from the
+    /// best-known head are marked [`HealthStatus::Degraded`].
     pub async fn run_health_checks(&self) -> Result<(), Error> {
         tracing::debug!("Running health checks on all endpoints");
 
         let connections = self.connections.read().await;
+        let timeout = Duration::from_secs(self.config.health_check_timeout_secs);
 
-        for conn in connections.iter() {
+        let results = join_all(connections.iter().map(|conn| async move {
             let start = Instant::now();
+            let result = tokio::time::timeout(timeout, probe_endpoint(&conn.adapter)).await;
+            (result, start.elapsed().as_millis() as u64)
+        }))
+        .await;
 
-            // Try to get block number as health check
-            match conn.adapter.client().blocks().at_latest().await {
-                Ok(_) => {
-                    let elapsed = start.elapsed().as_millis() as u64;
+        let mut probes = Vec::with_capacity(connections.len());
+        for (conn, (result, elapsed)) in connections.iter().zip(results) {
+            match result {
+                Ok(Ok(probe)) => {
                     conn.mark_healthy(elapsed).await;
                     tracing::debug!("Health check passed for {}: {}ms", conn.endpoint, elapsed);
+                    probes.push(Some(probe));
                 }
-                Err(e) => {
+                Ok(Err(e)) => {
                     conn.mark_unhealthy().await;
                     tracing::warn!("Health check failed for {}: {}", conn.endpoint, e);
+                    probes.push(None);
+                }
+                Err(_) => {
+                    conn.mark_unhealthy().await;
+                    tracing::warn!("Health check timed out for {}", conn.endpoint);
+                    probes.push(None);
                 }
             }
         }
 
+        let expected = self
+            .config
+            .expected_genesis_hash
+            .as_ref()
+            .map(|hash| hash.to_lowercase());
+        self.health_checked.store(true, Ordering::Relaxed);
+
+        let Some(check) = HealthCheck::new(&probes, expected) else {
+            return Ok(());
+        };
+
+        // Endpoints behind the best head are on a fork if their head is not the
+        // best endpoint's block at that height
+        let mut canonical = HashMap::new();
+        if let Some(best) = check.best_index {
+            let adapter = &connections[best].adapter;
+            let heights = check.heights_to_verify(&probes, self.config.max_block_lag);
+            let hashes = join_all(
+                heights
+                    .iter()
+                    .map(|&number| tokio::time::timeout(timeout, block_hash_at(adapter, number))),
+            )
+            .await;
+            for (number, hash) in heights.into_iter().zip(hashes) {
+                match hash {
+                    Ok(Ok(hash)) => {
+                        canonical.insert(number, hash);
+                    }
+                    _ => tracing::debug!("Could not fetch canonical block #{}", number),
+                }
+            }
+        }
+
+        let verdicts = check.classify(probes, &canonical, self.config.max_block_lag);
+        for (conn, verdict) in connections.iter().zip(verdicts) {
+            if let Some(verdict) = verdict {
+                conn.health
+                    .write()
+                    .await
+                    .record_verdict(&conn.endpoint, verdict);
+            }
+        }
+
         Ok(())
     }
 
@@ -361,6 +432,11 @@ impl ConnectionPool {
         tracing::info!("Started health checker with interval: {}s", interval_secs);
     }
 
+    /// Name of the chain served by the pool
+    pub fn chain_name(&self) -> &str {
+        &self.chain_name
+    }
+
     /// Get the number of endpoints
     pub fn endpoint_count(&self) -> usize {
         self.endpoints.len()
@@ -372,10 +448,71 @@ impl ConnectionPool {
     }
 }
 
+#[async_trait]
+impl PoolHealth for ConnectionPool {
+    fn chain_name(&self) -> &str {
+        &self.chain_name
+    }
+
+    fn health_checked(&self) -> bool {
+        self.health_checked.load(Ordering::Relaxed)
+    }
+
+    async fn health_status(&self) -> Vec<(String, EndpointHealth)> {
+        ConnectionPool::health_status(self).await
+    }
+}
+
+/// Fetch the genesis hash and latest block of an endpoint
+async fn probe_endpoint(adapter: &SubstrateAdapter) -> Result<Probe<String>, Error> {
+    let client = adapter.client();
+    let genesis_hash = format!("0x{}", hex::encode(client.genesis_hash()));
+
+    let block = client
+        .blocks()
+        .at_latest()
+        .await
+        .map_err(|e| Error::Connection(format!("Failed to get latest block: {}", e)))?;
+
+    Ok(Probe {
+        network: genesis_hash,
+        head: HeadInfo::new(
+            block.number() as u64,
+            format!("0x{}", hex::encode(block.hash())),
+        ),
+    })
+}
+
+/// Hash of the endpoint's block at `number`
+async fn block_hash_at(adapter: &SubstrateAdapter, number: u64) -> Result<String, Error> {
+    use subxt::backend::legacy::rpc_methods::NumberOrHex;
+    use subxt::backend::legacy::LegacyRpcMethods;
+
+    let hash = LegacyRpcMethods::<subxt::PolkadotConfig>::new(adapter.rpc.clone())
+        .chain_get_block_hash(Some(NumberOrHex::Number(number)))
+        .await
+        .map_err(|e| Error::Connection(format!("Failed to get block #{}: {}", number, e)))?
+        .ok_or_else(|| Error::Connection(format!("Block #{} not available", number)))?;
+    Ok(format!("0x{}", hex::encode(hash)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_pool_config_block_lag() {
+        let config = PoolConfig::default();
+        assert_eq!(config.max_block_lag, 5);
+        assert!(config.expected_genesis_hash.is_none());
+
+        let config = PoolConfig::default()
+            .with_max_block_lag(10)
+            .with_expected_genesis_hash("0xe143f23803ac50e8f6f8e62695d1ce9e");
+        assert_eq!(config.max_block_lag, 10);
+        assert!(config.expected_genesis_hash.is_some());
+    }
+
     #[tokio::test]
     async fn test_connection_pool_creation_empty() {
         let endpoints: Vec<String> = vec![];
@@ -0,0 +1,245 @@
+//! Runtime upgrade detection
+//!
+//! This module watches a chain for runtime upgrades and keeps the shared subxt
+//! client current:
+//! - The runtime version subscription announces an upgrade; new metadata and the
+//!   new runtime version are swapped in once a finalized block runs that version
+//! - Metadata entries in an attached [`Cache`] are invalidated
+//! - A [`RuntimeUpgraded`] notification is broadcast to subscribers
+//!
+//! Every `TransactionExecutor`, `XcmExecutor`, `ContractClient` and `StorageClient`
+//! created from the same adapter shares the client, so they all pick up the new
+//! metadata without reconnecting.
+
+use crate::{Cache, Error, Result};
+use parking_lot::RwLock;
+use std::sync::Arc;
+use std::time::Duration;
+use subxt::client::RuntimeVersion;
+use subxt::{OnlineClient, PolkadotConfig};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// Delay before resubscribing after the runtime version subscription drops
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(5);
+
+/// Number of notifications buffered for slow subscribers
+const NOTIFICATION_CAPACITY: usize = 16;
+
+/// Notification sent after the chain's runtime was upgraded and the client's
+/// metadata was replaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeUpgraded {
+    /// Spec version before the upgrade
+    pub old_spec: u32,
+    /// Spec version after the upgrade
+    pub new_spec: u32,
+    /// Transaction version after the upgrade
+    pub transaction_version: u32,
+}
+
+/// Watches for runtime upgrades and hot-swaps the client's metadata
+pub struct RuntimeUpgradeWatcher {
+    client: OnlineClient<PolkadotConfig>,
+    cache: RwLock<Option<Cache>>,
+    sender: broadcast::Sender<RuntimeUpgraded>,
+}
+
+impl RuntimeUpgradeWatcher {
+    /// Create a watcher for the given client
+    pub fn new(client: OnlineClient<PolkadotConfig>) -> Self {
+        let (sender, _) = broadcast::channel(NOTIFICATION_CAPACITY);
+        Self {
+            client,
+            cache: RwLock::new(None),
+            sender,
+        }
+    }
+
+    /// Invalidate this cache's metadata entries on every upgrade
+    pub fn with_cache(self, cache: Cache) -> Self {
+        self.attach_cache(cache);
+        self
+    }
+
+    /// Attach a cache to a watcher that may already be running
+    ///
+    /// Subscribers and the background task are kept; only the cache is replaced.
+    pub fn attach_cache(&self, cache: Cache) {
+        *self.cache.write() = Some(cache);
+    }
+
+    /// Subscribe to upgrade notifications
+    pub fn subscribe(&self) -> broadcast::Receiver<RuntimeUpgraded> {
+        self.sender.subscribe()
+    }
+
+    /// Spec version the client is currently using
+    pub fn spec_version(&self) -> u32 {
+        self.client.runtime_version().spec_version
+    }
+
+    /// Apply runtime upgrades until the subscription ends or fails
+    pub async fn run(&self) -> Result<()> {
+        let updater = self.client.updater();
+        let mut updates = updater.runtime_updates().await.map_err(|e| {
+            Error::Connection(format!("Failed to subscribe to runtime version: {}", e))
+        })?;
+
+        while let Some(update) = updates.next().await {
+            let update = update.map_err(|e| {
+                Error::Metadata(format!("Failed to fetch upgraded runtime metadata: {}", e))
+            })?;
+
+            let old_spec = self.spec_version();
+            let version = *update.runtime_version();
+
+            // The subscription starts with the current version, which is not an upgrade
+            if updater.apply_update(update).is_err() {
+                debug!("Runtime version {} unchanged", version.spec_version);
+                continue;
+            }
+            self.upgraded(old_spec, version);
+        }
+
+        Ok(())
+    }
+
+    /// Invalidate the cache and notify subscribers of an applied upgrade
+    fn upgraded(&self, old_spec: u32, version: RuntimeVersion) {
+        if let Some(cache) = &*self.cache.read() {
+            cache.invalidate_metadata();
+        }
+
+        info!(
+            "Runtime upgraded from spec {} to {}",
+            old_spec, version.spec_version
+        );
+        // Sending only fails when nobody is subscribed
+        let _ = self.sender.send(RuntimeUpgraded {
+            old_spec,
+            new_spec: version.spec_version,
+            transaction_version: version.transaction_version,
+        });
+    }
+
+    /// Start watching in the background, resubscribing if the subscription drops
+    pub fn start(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                match self.run().await {
+                    Ok(()) => warn!("Runtime version subscription ended"),
+                    Err(e) => warn!("Runtime upgrade watcher error: {}", e),
+                }
+                tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scale_info::meta_type;
+    use subxt::backend::rpc::{RawRpcFuture, RawRpcSubscription, RawValue, RpcClient, RpcClientT};
+    use subxt::config::substrate::H256;
+    use subxt::ext::frame_metadata::v15::{
+        CustomMetadata, ExtrinsicMetadata, OuterEnums, RuntimeMetadataV15,
+    };
+
+    /// RPC client for tests that never reach the node
+    struct NoRpc;
+
+    impl RpcClientT for NoRpc {
+        fn request_raw<'a>(
+            &'a self,
+            method: &'a str,
+            _params: Option<Box<RawValue>>,
+        ) -> RawRpcFuture<'a, Box<RawValue>> {
+            Box::pin(async move {
+                Err(subxt::ext::subxt_rpcs::Error::Client(
+                    format!("unexpected RPC request {}", method).into(),
+                ))
+            })
+        }
+
+        fn subscribe_raw<'a>(
+            &'a self,
+            sub: &'a str,
+            _params: Option<Box<RawValue>>,
+            _unsub: &'a str,
+        ) -> RawRpcFuture<'a, RawRpcSubscription> {
+            Box::pin(async move {
+                Err(subxt::ext::subxt_rpcs::Error::Client(
+                    format!("unexpected RPC subscription {}", sub).into(),
+                ))
+            })
+        }
+    }
+
+    fn offline_client(spec_version: u32) -> OnlineClient<PolkadotConfig> {
+        let metadata = RuntimeMetadataV15::new(
+            Vec::new(),
+            ExtrinsicMetadata {
+                version: 4,
+                address_ty: meta_type::<()>(),
+                call_ty: meta_type::<()>(),
+                signature_ty: meta_type::<()>(),
+                extra_ty: meta_type::<()>(),
+                signed_extensions: Vec::new(),
+            },
+            meta_type::<()>(),
+            Vec::new(),
+            OuterEnums {
+                call_enum_ty: meta_type::<()>(),
+                event_enum_ty: meta_type::<()>(),
+                error_enum_ty: meta_type::<()>(),
+            },
+            CustomMetadata {
+                map: Default::default(),
+            },
+        );
+        let metadata = subxt::metadata::types::Metadata::try_from(metadata).unwrap();
+        OnlineClient::from_rpc_client_with(
+            H256::zero(),
+            RuntimeVersion {
+                spec_version,
+                transaction_version: 1,
+            },
+            metadata,
+            RpcClient::new(NoRpc),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_is_broadcast_and_invalidates_attached_cache() {
+        let watcher = RuntimeUpgradeWatcher::new(offline_client(100));
+        assert_eq!(watcher.spec_version(), 100);
+        let mut before = watcher.subscribe();
+
+        // A cache attached after subscribing keeps the existing subscribers
+        let cache = Cache::new();
+        cache.put_metadata("pallets".to_string(), "cached".to_string());
+        watcher.attach_cache(cache.clone());
+        let mut after = watcher.subscribe();
+
+        watcher.upgraded(
+            100,
+            RuntimeVersion {
+                spec_version: 101,
+                transaction_version: 2,
+            },
+        );
+
+        let expected = RuntimeUpgraded {
+            old_spec: 100,
+            new_spec: 101,
+            transaction_version: 2,
+        };
+        assert_eq!(before.recv().await.unwrap(), expected);
+        assert_eq!(after.recv().await.unwrap(), expected);
+        assert!(cache.get_metadata("pallets").is_none());
+    }
+}
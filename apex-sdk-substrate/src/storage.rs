@@ -2,32 +2,140 @@
 //!
 //! This module provides functionality for querying chain storage including:
 //! - Account information and balances
-//! - Storage item queries
+//! - Storage item queries, raw or decoded into dynamic values, JSON or any `Decode` type
+//! - Paged, streaming iteration over storage maps (with partial keys for double maps)
+//! - Queries pinned to a specific block via [`StorageClient::storage_at`]
 //! - Runtime constants
 //! - Metadata inspection
 
 use crate::{Error, Metrics, Result};
+use parity_scale_codec::DecodeAll;
+use scale_info::{PortableRegistry, TypeDef, TypeDefPrimitive};
+use sp_core::U256;
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use subxt::backend::{legacy::LegacyRpcMethods, rpc::RpcClient, StreamOfResults};
+use subxt::config::substrate::H256;
 use subxt::dynamic::At as _;
+use subxt::ext::futures::{stream, Stream, TryStreamExt as _};
+use subxt::ext::scale_value::{self, Composite, Primitive, ValueDef};
+use subxt::ext::subxt_core::storage::address::{StorageHashers, StorageKey as _};
 use subxt::{OnlineClient, PolkadotConfig};
-use tracing::debug;
+use tracing::{debug, warn};
+
+/// Default number of keys fetched per page by [`StorageClient::iter_pages`]
+pub const DEFAULT_PAGE_SIZE: u32 = 256;
+
+/// Stream of storage pages returned by [`StorageClient::iter_pages`]
+pub type StoragePageStream = Pin<Box<dyn Stream<Item = Result<StoragePage>> + Send>>;
 
 /// Storage query client for accessing chain storage
 #[derive(Debug, Clone)]
 pub struct StorageClient {
     client: OnlineClient<PolkadotConfig>,
     metrics: Metrics,
+    rpc: Option<LegacyRpcMethods<PolkadotConfig>>,
+    rpc_client: Option<RpcClient>,
+    at: Option<H256>,
 }
 
 impl StorageClient {
     /// Create a new storage client
     pub fn new(client: OnlineClient<PolkadotConfig>, metrics: Metrics) -> Self {
-        Self { client, metrics }
+        Self {
+            client,
+            metrics,
+            rpc: None,
+            rpc_client: None,
+            at: None,
+        }
+    }
+
+    /// Use a raw RPC client for key paging.
+    ///
+    /// With an RPC client, [`iter_pages`](Self::iter_pages) fetches keys with
+    /// `state_getKeysPaged` so a start key resumes iteration on the node. Without
+    /// one, keys are streamed from the backend and skipped up to the start key.
+    pub fn with_rpc_client(mut self, rpc: RpcClient) -> Self {
+        self.rpc = Some(LegacyRpcMethods::new(rpc.clone()));
+        self.rpc_client = Some(rpc);
+        self
+    }
+
+    /// Return a client whose queries all run against the given block
+    ///
+    /// If the block ran an older runtime, its metadata is loaded so keys and
+    /// values are converted with the types they were written with. This needs
+    /// an RPC client (see [`with_rpc_client`](Self::with_rpc_client)).
+    pub async fn storage_at(&self, block_hash_hex: &str) -> Result<Self> {
+        let hash = parse_block_hash(block_hash_hex)?;
+        let (Some(rpc), Some(rpc_client)) = (&self.rpc, &self.rpc_client) else {
+            return Err(Error::Metadata(
+                "Reading storage at a block needs an RPC client".to_string(),
+            ));
+        };
+
+        let mut pinned = self.clone();
+        pinned.at = Some(hash);
+
+        let version = rpc
+            .state_get_runtime_version(Some(hash))
+            .await
+            .map_err(|e| Error::Metadata(format!("Failed to fetch runtime version: {}", e)))?;
+        if version.spec_version == self.client.runtime_version().spec_version {
+            return Ok(pinned);
+        }
+
+        use subxt::ext::codec::Decode;
+
+        debug!(
+            "Block {:?} runs spec version {}, loading its metadata",
+            hash, version.spec_version
+        );
+        let bytes = rpc
+            .state_get_metadata(Some(hash))
+            .await
+            .map_err(|e| Error::Metadata(format!("Failed to fetch metadata: {}", e)))?
+            .into_raw();
+        let metadata = subxt::Metadata::decode(&mut &bytes[..])
+            .map_err(|e| Error::Metadata(format!("Failed to decode metadata: {}", e)))?;
+
+        pinned.client = OnlineClient::from_rpc_client_with(
+            self.client.genesis_hash(),
+            subxt::client::RuntimeVersion {
+                spec_version: version.spec_version,
+                transaction_version: version.transaction_version,
+            },
+            metadata,
+            rpc_client.clone(),
+        )
+        .map_err(|e| Error::Connection(format!("Failed to create client: {}", e)))?;
+        Ok(pinned)
+    }
+
+    /// Block this client is pinned to, if any
+    pub fn block_hash(&self) -> Option<H256> {
+        self.at
+    }
+
+    /// Storage view at the pinned block, or at the latest block
+    async fn storage(
+        &self,
+    ) -> Result<subxt::storage::Storage<PolkadotConfig, OnlineClient<PolkadotConfig>>> {
+        match self.at {
+            Some(hash) => Ok(self.client.storage().at(hash)),
+            None => self
+                .client
+                .storage()
+                .at_latest()
+                .await
+                .map_err(|e| Error::Connection(format!("Failed to fetch latest block: {}", e))),
+        }
     }
 
     /// Query account information including balance and nonce
     pub async fn get_account_info(&self, address: &str) -> Result<AccountInfo> {
         debug!("Querying account info for: {}", address);
-        self.metrics.record_storage_query();
 
         // Parse SS58 address to get AccountId32
         use sp_core::crypto::{AccountId32, Ss58Codec};
@@ -42,17 +150,16 @@ impl StorageClient {
             vec![subxt::dynamic::Value::from_bytes(account_bytes)],
         );
 
-        let storage = self
-            .client
-            .storage()
-            .at_latest()
-            .await
-            .map_err(|e| Error::Connection(format!("Failed to fetch latest block: {}", e)))?;
-
-        let result = storage
-            .fetch(&storage_query)
-            .await
-            .map_err(|e| Error::Storage(format!("Failed to query account info: {}", e)))?;
+        let result = self
+            .metrics
+            .instrument("get_account_info", async {
+                self.storage()
+                    .await?
+                    .fetch(&storage_query)
+                    .await
+                    .map_err(|e| Error::Storage(format!("Failed to query account info: {}", e)))
+            })
+            .await?;
 
         // Decode the result
         if let Some(value) = result {
@@ -111,33 +218,93 @@ impl StorageClient {
         Ok(account_info.nonce)
     }
 
-    /// Query a storage value by pallet and item name
+    /// Query a storage value by pallet and item name (returns raw SCALE bytes)
     pub async fn query_storage(
         &self,
         pallet: &str,
         item: &str,
         keys: Vec<subxt::dynamic::Value>,
     ) -> Result<Option<Vec<u8>>> {
-        debug!("Querying storage: {}::{}", pallet, item);
-        self.metrics.record_storage_query();
+        let result = self.fetch("query_storage", pallet, item, keys).await?;
+        Ok(result.map(|v| v.encoded().to_vec()))
+    }
 
-        let storage_query = subxt::dynamic::storage(pallet, item, keys);
+    /// Query a storage value and decode it into a dynamic value using the runtime metadata
+    ///
+    /// The value keeps its type ids as context; use `remove_context` to drop them.
+    pub async fn query_value(
+        &self,
+        pallet: &str,
+        item: &str,
+        keys: Vec<subxt::dynamic::Value>,
+    ) -> Result<Option<subxt::dynamic::DecodedValue>> {
+        let result = self.fetch("query_value", pallet, item, keys).await?;
+        result
+            .map(|v| {
+                v.to_value().map_err(|e| {
+                    Error::Storage(format!("Failed to decode {}::{}: {}", pallet, item, e))
+                })
+            })
+            .transpose()
+    }
 
-        let storage = self
-            .client
-            .storage()
-            .at_latest()
-            .await
-            .map_err(|e| Error::Connection(format!("Failed to fetch latest block: {}", e)))?;
+    /// Query a storage value and render it as JSON (see [`value_to_json`])
+    pub async fn query_json(
+        &self,
+        pallet: &str,
+        item: &str,
+        keys: Vec<subxt::dynamic::Value>,
+    ) -> Result<Option<serde_json::Value>> {
+        let value = self.query_value(pallet, item, keys).await?;
+        Ok(value.as_ref().map(|v| self.to_json(v)))
+    }
 
-        let result = storage.fetch(&storage_query).await.map_err(|e| {
-            Error::Storage(format!(
-                "Failed to query storage {}::{}: {}",
-                pallet, item, e
-            ))
-        })?;
+    /// Render a decoded value as JSON using this chain's type registry
+    pub fn to_json(&self, value: &subxt::dynamic::DecodedValue) -> serde_json::Value {
+        value_to_json(value, self.client.metadata().types())
+    }
 
-        Ok(result.map(|v| v.encoded().to_vec()))
+    /// Query a storage value and decode it into a concrete SCALE type
+    pub async fn query_decoded<T: DecodeAll>(
+        &self,
+        pallet: &str,
+        item: &str,
+        keys: Vec<subxt::dynamic::Value>,
+    ) -> Result<Option<T>> {
+        let bytes = self.query_storage(pallet, item, keys).await?;
+        bytes
+            .map(|bytes| {
+                T::decode_all(&mut &bytes[..]).map_err(|e| {
+                    Error::Storage(format!("Failed to decode {}::{}: {}", pallet, item, e))
+                })
+            })
+            .transpose()
+    }
+
+    async fn fetch(
+        &self,
+        operation: &str,
+        pallet: &str,
+        item: &str,
+        keys: Vec<subxt::dynamic::Value>,
+    ) -> Result<Option<subxt::dynamic::DecodedValueThunk>> {
+        debug!("Querying storage: {}::{}", pallet, item);
+        let storage_query = subxt::dynamic::storage(pallet, item, keys);
+
+        self.metrics
+            .instrument(operation, async {
+                self.storage()
+                    .await?
+                    .fetch(&storage_query)
+                    .await
+                    .map_err(|e| {
+                        Error::Storage(format!(
+                            "Failed to query storage {}::{}: {}",
+                            pallet, item, e
+                        ))
+                    })
+            })
+            .await
     }
 
     /// Get a runtime constant (returns raw bytes)
@@ -174,34 +341,17 @@ impl StorageClient {
         item: &str,
         keys: Vec<subxt::dynamic::Value>,
     ) -> Result<Option<Vec<u8>>> {
-        debug!(
-            "Querying storage at block {} for: {}::{}",
-            block_hash_hex, pallet, item
-        );
-        self.metrics.record_storage_query();
-
-        // Parse the block hash
-        let block_hash = parse_block_hash(block_hash_hex)?;
-
-        let storage_query = subxt::dynamic::storage(pallet, item, keys);
-
-        let result = self
-            .client
-            .storage()
-            .at(block_hash)
-            .fetch(&storage_query)
+        debug!("Querying storage at block {}", block_hash_hex);
+        self.storage_at(block_hash_hex)
+            .await?
+            .query_storage(pallet, item, keys)
             .await
-            .map_err(|e| {
-                Error::Storage(format!(
-                    "Failed to query storage {}::{} at block: {}",
-                    pallet, item, e
-                ))
-            })?;
-
-        Ok(result.map(|v| v.encoded().to_vec()))
     }
 
     /// Iterate over storage entries and return their keys and values
+    ///
+    /// This loads the whole map into memory; prefer [`iter_pages`](Self::iter_pages)
+    /// for large maps such as `System::Account`.
     pub async fn iter_storage(&self, pallet: &str, item: &str) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
         debug!("Iterating storage: {}::{}", pallet, item);
         self.metrics.record_storage_query();
@@ -210,12 +360,7 @@ impl StorageClient {
             subxt::dynamic::storage(pallet, item, Vec::<subxt::dynamic::Value>::new());
 
         let mut results = Vec::new();
-        let storage = self
-            .client
-            .storage()
-            .at_latest()
-            .await
-            .map_err(|e| Error::Connection(format!("Failed to fetch latest block: {}", e)))?;
+        let storage = self.storage().await?;
 
         let mut iter = storage.iter(storage_query).await.map_err(|e| {
             Error::Storage(format!(
@@ -234,6 +379,297 @@ impl StorageClient {
         Ok(results)
     }
 
+    /// Stream a storage map page by page.
+    ///
+    /// `partial_keys` narrows the iteration to a prefix of the map's keys, e.g. the
+    /// first key of a double map. Every page is read at the same block: the pinned
+    /// block if set, otherwise the latest block when the stream starts. Pass a
+    /// page's [`next_key`](StoragePage::next_key) as the start key to resume.
+    pub fn iter_pages(
+        &self,
+        pallet: &str,
+        item: &str,
+        partial_keys: Vec<subxt::dynamic::Value>,
+        options: PageOptions,
+    ) -> StoragePageStream {
+        let this = self.clone();
+        let pallet = pallet.to_string();
+        let item = item.to_string();
+
+        let pages = stream::once(async move {
+            this.page_cursor(&pallet, &item, partial_keys, options)
+                .await
+        })
+        .map_ok(|cursor| {
+            stream::try_unfold(cursor, |mut cursor| async move {
+                Ok(cursor.next_page().await?.map(|page| (page, cursor)))
+            })
+        })
+        .try_flatten();
+
+        Box::pin(pages)
+    }
+
+    async fn page_cursor(
+        &self,
+        pallet: &str,
+        item: &str,
+        partial_keys: Vec<subxt::dynamic::Value>,
+        options: PageOptions,
+    ) -> Result<PageCursor> {
+        debug!("Paging storage: {}::{}", pallet, item);
+
+        let metadata = self.client.metadata();
+        let entry = storage_entry(&metadata, pallet, item)?;
+        let value_ty = entry.entry_type().value_ty();
+        let hashers = StorageHashers::new(entry.entry_type(), metadata.types())
+            .map_err(|e| Error::Metadata(format!("Invalid storage entry hashers: {}", e)))?;
+
+        let prefix = self
+            .client
+            .storage()
+            .address_bytes(&subxt::dynamic::storage(pallet, item, partial_keys))
+            .map_err(|e| Error::Storage(format!("Invalid storage keys: {}", e)))?;
+
+        let block_hash = match self.at {
+            Some(hash) => hash,
+            None => self
+                .client
+                .backend()
+                .latest_finalized_block_ref()
+                .await
+                .map_err(|e| Error::Connection(format!("Failed to fetch latest block: {}", e)))?
+                .hash(),
+        };
+
+        let source = match &self.rpc {
+            Some(rpc) => KeySource::Paged(rpc.clone()),
+            None => KeySource::Descendants {
+                keys: self
+                    .client
+                    .backend()
+                    .storage_fetch_descendant_keys(prefix.clone(), block_hash)
+                    .await
+                    .map_err(|e| Error::Storage(format!("Failed to iterate storage: {}", e)))?,
+                skip_to: options.start_key.clone(),
+            },
+        };
+
+        Ok(PageCursor {
+            client: self.client.clone(),
+            metrics: self.metrics.clone(),
+            metadata,
+            hashers,
+            value_ty,
+            keys: KeyPager {
+                source,
+                prefix,
+                block_hash,
+                page_size: options.page_size.max(1),
+                start_key: options.start_key,
+                exhausted: false,
+            },
+        })
+    }
+
+    /// Watch a storage value, emitting a change whenever a new finalized block alters it
+    pub fn watch(&self, query: &StorageQuery) -> StorageChangeStream {
+        self.watch_many(std::slice::from_ref(query))
+    }
+
+    /// Watch several storage values at once.
+    ///
+    /// All values are fetched in one batch per finalized block, so thousands of
+    /// accounts cost one request per block rather than one per account. The
+    /// first block only records the current values; later blocks emit a
+    /// [`StorageChange`] for every value that differs.
+    pub fn watch_many(&self, queries: &[StorageQuery]) -> StorageChangeStream {
+        let this = self.clone();
+        let queries = queries.to_vec();
+
+        let changes = stream::once(async move { this.watch_state(&queries).await })
+            .map_ok(|state| {
+                stream::try_unfold(state, |mut state| async move {
+                    Ok(state.next_change().await?.map(|change| (change, state)))
+                })
+            })
+            .try_flatten();
+
+        Box::pin(changes)
+    }
+
+    async fn watch_state(&self, queries: &[StorageQuery]) -> Result<WatchState> {
+        let metadata = self.client.metadata();
+        let mut keys = Vec::with_capacity(queries.len());
+        for query in queries {
+            keys.push(self.watched_key(&metadata, query)?);
+        }
+        debug!("Watching {} storage values", keys.len());
+
+        let mut blocks = self
+            .client
+            .blocks()
+            .subscribe_finalized()
+            .await
+            .map_err(|e| Error::Connection(format!("Failed to subscribe to blocks: {}", e)))?;
+        let first = match blocks.next().await {
+            Some(block) => {
+                block.map_err(|e| Error::Connection(format!("Block subscription error: {}", e)))?
+            }
+            None => return Err(Error::Connection("Block subscription ended".to_string())),
+        };
+
+        let runtime_key = self
+            .client
+            .storage()
+            .address_bytes(&subxt::dynamic::storage(
+                "System",
+                "LastRuntimeUpgrade",
+                Vec::<subxt::dynamic::Value>::new(),
+            ))
+            .map_err(|e| Error::Storage(format!("Invalid storage keys: {}", e)))?;
+        let mut key_bytes: Vec<Vec<u8>> = keys.iter().map(|k| k.key.clone()).collect();
+        key_bytes.push(runtime_key.clone());
+        let mut current = fetch_raw_values(&self.client, key_bytes, first.hash()).await?;
+        let runtime = current.remove(&runtime_key);
+
+        Ok(WatchState {
+            client: self.client.clone(),
+            metrics: self.metrics.clone(),
+            metadata,
+            keys,
+            runtime_key,
+            runtime,
+            current,
+            blocks,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// History of changes to a storage value between two blocks (inclusive).
+    ///
+    /// The value at `from_block` is the baseline; each returned change names the
+    /// block that altered it. With an RPC client this is a single
+    /// `state_queryStorage` call. Without one, or if the node rejects the call,
+    /// the chain is walked back from `to_block` one header at a time.
+    pub async fn diff(
+        &self,
+        query: &StorageQuery,
+        from_block: &str,
+        to_block: &str,
+    ) -> Result<Vec<StorageChange>> {
+        debug!(
+            "Diffing {}::{} from {} to {}",
+            query.pallet, query.item, from_block, to_block
+        );
+        self.metrics.record_storage_query();
+
+        let metadata = self.client.metadata();
+        let watched = self.watched_key(&metadata, query)?;
+        let from = parse_block_hash(from_block)?;
+        let to = parse_block_hash(to_block)?;
+
+        // Value per block, in chain order starting at `from`
+        let history = match &self.rpc {
+            Some(rpc) => match rpc
+                .state_query_storage([&watched.key[..]], from, Some(to))
+                .await
+            {
+                Ok(change_sets) => Some(history_from_change_sets(change_sets, &watched.key)),
+                Err(e) => {
+                    warn!("state_queryStorage failed, walking block headers: {}", e);
+                    None
+                }
+            },
+            None => None,
+        };
+        let history = match history {
+            Some(history) => history,
+            None => {
+                let mut history = Vec::new();
+                for hash in self.block_range(from, to).await? {
+                    let values =
+                        fetch_raw_values(&self.client, vec![watched.key.clone()], hash).await?;
+                    history.push((hash, values.get(&watched.key).cloned()));
+                }
+                history
+            }
+        };
+
+        let mut changes = Vec::new();
+        for (block_hash, old, new) in value_history(history) {
+            let block_number = self.block_number(block_hash).await?;
+            changes.push(watched.change(
+                block_hash,
+                block_number,
+                watched.decode(&metadata, old)?,
+                watched.decode(&metadata, new)?,
+            ));
+        }
+
+        Ok(changes)
+    }
+
+    fn watched_key(&self, metadata: &subxt::Metadata, query: &StorageQuery) -> Result<WatchedKey> {
+        let value_ty = storage_entry(metadata, &query.pallet, &query.item)?
+            .entry_type()
+            .value_ty();
+        let key = self
+            .client
+            .storage()
+            .address_bytes(&subxt::dynamic::storage(
+                query.pallet.as_str(),
+                query.item.as_str(),
+                query.keys.clone(),
+            ))
+            .map_err(|e| Error::Storage(format!("Invalid storage keys: {}", e)))?;
+        Ok(WatchedKey {
+            pallet: query.pallet.clone(),
+            item: query.item.clone(),
+            key,
+            value_ty,
+        })
+    }
+
+    /// Block hashes from `from` to `to` inclusive, found by walking parent hashes
+    async fn block_range(&self, from: H256, to: H256) -> Result<Vec<H256>> {
+        let from_number = self.block_number(from).await?;
+        let mut hashes = vec![to];
+        let mut block = self
+            .client
+            .blocks()
+            .at(to)
+            .await
+            .map_err(|e| Error::Connection(format!("Failed to get block: {}", e)))?;
+        while block.hash() != from {
+            if u64::from(block.number()) <= from_number {
+                return Err(Error::Storage(
+                    "from_block is not an ancestor of to_block".to_string(),
+                ));
+            }
+            block = self
+                .client
+                .blocks()
+                .at(block.header().parent_hash)
+                .await
+                .map_err(|e| Error::Connection(format!("Failed to get parent block: {}", e)))?;
+            hashes.push(block.hash());
+        }
+        hashes.reverse();
+        Ok(hashes)
+    }
+
+    async fn block_number(&self, hash: H256) -> Result<u64> {
+        let header = self
+            .client
+            .backend()
+            .block_header(hash)
+            .await
+            .map_err(|e| Error::Connection(format!("Failed to get block header: {}", e)))?
+            .ok_or_else(|| Error::Storage(format!("Block {:?} not found", hash)))?;
+        Ok(header.number.into())
+    }
+
     /// Get metadata about a pallet
     pub fn get_pallet_metadata(&self, pallet: &str) -> Result<PalletMetadata> {
         debug!("Getting pallet metadata: {}", pallet);
@@ -282,6 +718,78 @@ impl StorageClient {
         let metadata = self.client.metadata();
         metadata.pallets().map(|p| p.name().to_string()).collect()
     }
+
+    /// Runtime metadata at the pinned block, or the client's current metadata
+    ///
+    /// A client pinned with [`storage_at`](Self::storage_at) already carries
+    /// the metadata of its block, which may belong to an older runtime.
+    pub async fn runtime_metadata(&self) -> Result<subxt::Metadata> {
+        Ok(self.client.metadata())
+    }
+
+    /// Convert JSON map keys into dynamic values typed by a storage entry's key types
+    ///
+    /// Fewer keys than the map has select a prefix, e.g. for [`iter_pages`](Self::iter_pages).
+    /// See [`json_to_value`](crate::contracts::json_to_value) for the accepted JSON.
+    pub fn keys_from_json(
+        &self,
+        pallet: &str,
+        item: &str,
+        keys: &[serde_json::Value],
+    ) -> Result<Vec<subxt::dynamic::Value>> {
+        let metadata = self.client.metadata();
+        let key_types = key_types(&metadata, pallet, item)?;
+        if keys.len() > key_types.len() {
+            return Err(Error::Storage(format!(
+                "{}::{} takes {} key(s), got {}",
+                pallet,
+                item,
+                key_types.len(),
+                keys.len()
+            )));
+        }
+
+        keys.iter()
+            .zip(key_types)
+            .map(|(key, ty)| crate::contracts::json_to_value(key, ty, metadata.types()))
+            .collect()
+    }
+
+    /// Render the map keys of a [`StorageEntry`] as JSON
+    ///
+    /// Keys behind opaque hashers cannot be recovered and become `null`.
+    pub fn keys_to_json(
+        &self,
+        pallet: &str,
+        item: &str,
+        keys: &[subxt::dynamic::Value],
+    ) -> Result<Vec<serde_json::Value>> {
+        use subxt::ext::scale_encode::EncodeAsType;
+
+        let metadata = self.client.metadata();
+        let types = metadata.types();
+        let key_types = key_types(&metadata, pallet, item)?;
+
+        Ok(keys
+            .iter()
+            .zip(key_types)
+            .map(|(key, ty)| {
+                key.encode_as_type(ty, types)
+                    .ok()
+                    .and_then(|bytes| decode_value(&bytes, ty, types).ok())
+                    .map(|value| value_to_json(&value, types))
+                    .unwrap_or(serde_json::Value::Null)
+            })
+            .collect())
+    }
+}
+
+/// Type ids of a storage entry's map keys, empty for plain values
+fn key_types(metadata: &subxt::Metadata, pallet: &str, item: &str) -> Result<Vec<u32>> {
+    let entry = storage_entry(metadata, pallet, item)?;
+    let hashers = StorageHashers::new(entry.entry_type(), metadata.types())
+        .map_err(|e| Error::Metadata(format!("Invalid storage entry hashers: {}", e)))?;
+    Ok(hashers.iter().map(|(_, ty)| ty).collect())
 }
 
 /// Account information structure
@@ -335,6 +843,7 @@ pub struct PalletMetadata {
 }
 
 /// Storage query helper
+#[derive(Debug, Clone)]
 pub struct StorageQuery {
     pallet: String,
     item: String,
@@ -371,10 +880,628 @@ impl StorageQuery {
     }
 }
 
-// Helper function for parsing block hash from hex string
-fn parse_block_hash(hash_hex: &str) -> Result<subxt::config::substrate::H256> {
-    use subxt::config::substrate::H256;
+/// Options for [`StorageClient::iter_pages`]
+#[derive(Debug, Clone)]
+pub struct PageOptions {
+    /// Number of keys fetched per page
+    pub page_size: u32,
+    /// Resume after this storage key (exclusive)
+    pub start_key: Option<Vec<u8>>,
+}
+
+impl Default for PageOptions {
+    fn default() -> Self {
+        Self {
+            page_size: DEFAULT_PAGE_SIZE,
+            start_key: None,
+        }
+    }
+}
+
+impl PageOptions {
+    /// Create options with the default page size
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of keys fetched per page
+    pub fn with_page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size;
+        self
+    }
 
+    /// Resume iteration after the given storage key
+    pub fn with_start_key(mut self, start_key: impl Into<Vec<u8>>) -> Self {
+        self.start_key = Some(start_key.into());
+        self
+    }
+}
+
+/// A storage map entry decoded with the runtime metadata
+#[derive(Debug, Clone)]
+pub struct StorageEntry {
+    /// Full storage key
+    pub key: Vec<u8>,
+    /// Map keys recovered from the storage key (empty composites for opaque hashers)
+    pub keys: Vec<subxt::dynamic::Value>,
+    /// Decoded value, with type ids as context (see [`StorageClient::to_json`])
+    pub value: subxt::dynamic::DecodedValue,
+    /// Raw SCALE-encoded value
+    pub encoded: Vec<u8>,
+}
+
+impl StorageEntry {
+    /// Decode the value into a concrete SCALE type
+    pub fn decode<T: DecodeAll>(&self) -> Result<T> {
+        T::decode_all(&mut &self.encoded[..])
+            .map_err(|e| Error::Storage(format!("Failed to decode storage entry: {}", e)))
+    }
+}
+
+/// One page of storage entries
+#[derive(Debug, Clone)]
+pub struct StoragePage {
+    /// Entries in key order
+    pub entries: Vec<StorageEntry>,
+    /// Start key for the next page, `None` once the map is exhausted
+    pub next_key: Option<Vec<u8>>,
+    /// Block the page was read at
+    pub block_hash: H256,
+}
+
+impl StoragePage {
+    /// Number of entries in the page
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the page has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+enum KeySource {
+    Paged(LegacyRpcMethods<PolkadotConfig>),
+    /// Descendant keys in order. Keys up to `skip_to` are dropped once; after
+    /// that every page continues where the stream left off.
+    Descendants {
+        keys: StreamOfResults<Vec<u8>>,
+        skip_to: Option<Vec<u8>>,
+    },
+}
+
+/// Fetches the keys of a storage map one page at a time
+struct KeyPager {
+    source: KeySource,
+    prefix: Vec<u8>,
+    block_hash: H256,
+    page_size: u32,
+    start_key: Option<Vec<u8>>,
+    exhausted: bool,
+}
+
+impl KeyPager {
+    async fn next_keys(&mut self) -> Result<Vec<Vec<u8>>> {
+        if self.exhausted {
+            return Ok(Vec::new());
+        }
+        let page_size = self.page_size as usize;
+        let keys = match &mut self.source {
+            KeySource::Paged(rpc) => {
+                let keys = rpc
+                    .state_get_keys_paged(
+                        &self.prefix,
+                        self.page_size,
+                        self.start_key.as_deref(),
+                        Some(self.block_hash),
+                    )
+                    .await
+                    .map_err(|e| Error::Storage(format!("Failed to fetch storage keys: {}", e)))?;
+                if keys.len() < page_size {
+                    self.exhausted = true;
+                }
+                keys
+            }
+            KeySource::Descendants {
+                keys: stream,
+                skip_to,
+            } => {
+                let mut keys = Vec::with_capacity(page_size);
+                while keys.len() < page_size {
+                    let Some(key) = stream.next().await else {
+                        self.exhausted = true;
+                        break;
+                    };
+                    let key = key.map_err(|e| {
+                        Error::Storage(format!("Failed to fetch storage keys: {}", e))
+                    })?;
+                    if let Some(start) = skip_to {
+                        if &key <= start {
+                            continue;
+                        }
+                        *skip_to = None;
+                    }
+                    keys.push(key);
+                }
+                keys
+            }
+        };
+        if let Some(last) = keys.last() {
+            self.start_key = Some(last.clone());
+        }
+        Ok(keys)
+    }
+}
+
+struct PageCursor {
+    client: OnlineClient<PolkadotConfig>,
+    metrics: Metrics,
+    metadata: subxt::Metadata,
+    hashers: StorageHashers,
+    value_ty: u32,
+    keys: KeyPager,
+}
+
+impl PageCursor {
+    async fn next_page(&mut self) -> Result<Option<StoragePage>> {
+        if self.keys.exhausted {
+            return Ok(None);
+        }
+        self.metrics.record_storage_query();
+
+        let keys = self.keys.next_keys().await?;
+        if keys.is_empty() {
+            return Ok(None);
+        }
+
+        let mut values = self
+            .client
+            .backend()
+            .storage_fetch_values(keys.clone(), self.keys.block_hash)
+            .await
+            .map_err(|e| Error::Storage(format!("Failed to fetch storage page: {}", e)))?;
+
+        let mut fetched = Vec::with_capacity(keys.len());
+        while let Some(response) = values.next().await {
+            let response = response
+                .map_err(|e| Error::Storage(format!("Failed to fetch storage entry: {}", e)))?;
+            fetched.push((response.key, response.value));
+        }
+
+        let entries = decode_entries(fetched, self.metadata.types(), &self.hashers, self.value_ty)?;
+        Ok(Some(StoragePage {
+            entries,
+            next_key: if self.keys.exhausted {
+                None
+            } else {
+                keys.last().cloned()
+            },
+            block_hash: self.keys.block_hash,
+        }))
+    }
+}
+
+/// Decode fetched key/value pairs into entries sorted by key
+fn decode_entries(
+    fetched: Vec<(Vec<u8>, Vec<u8>)>,
+    types: &PortableRegistry,
+    hashers: &StorageHashers,
+    value_ty: u32,
+) -> Result<Vec<StorageEntry>> {
+    let mut entries = fetched
+        .into_iter()
+        .map(|(key, encoded)| decode_entry(key, encoded, types, hashers, value_ty))
+        .collect::<Result<Vec<_>>>()?;
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(entries)
+}
+
+fn decode_entry(
+    key: Vec<u8>,
+    encoded: Vec<u8>,
+    types: &PortableRegistry,
+    hashers: &StorageHashers,
+    value_ty: u32,
+) -> Result<StorageEntry> {
+    let value = decode_value(&encoded, value_ty, types)?;
+
+    // The first 32 bytes are the pallet and entry name hashes.
+    let mut cursor = key
+        .get(32..)
+        .ok_or_else(|| Error::Storage("Storage key shorter than its prefix".to_string()))?;
+    let keys =
+        Vec::<subxt::dynamic::Value>::decode_storage_key(&mut cursor, &mut hashers.iter(), types)
+            .map_err(|e| Error::Storage(format!("Failed to decode storage key: {}", e)))?;
+
+    Ok(StorageEntry {
+        key,
+        keys,
+        value,
+        encoded,
+    })
+}
+
+/// Stream of storage changes returned by [`StorageClient::watch`]
+pub type StorageChangeStream = Pin<Box<dyn Stream<Item = Result<StorageChange>> + Send>>;
+
+/// A change to a storage value
+#[derive(Debug, Clone)]
+pub struct StorageChange {
+    /// Full storage key
+    pub key: Vec<u8>,
+    /// Block in which the value changed
+    pub block_hash: H256,
+    /// Number of that block
+    pub block_number: u64,
+    /// Raw SCALE-encoded value before the change, `None` if it did not exist
+    pub old: Option<Vec<u8>>,
+    /// Raw SCALE-encoded value after the change, `None` if it was removed
+    pub new: Option<Vec<u8>>,
+    /// Decoded value before the change
+    pub old_value: Option<subxt::dynamic::DecodedValue>,
+    /// Decoded value after the change
+    pub new_value: Option<subxt::dynamic::DecodedValue>,
+}
+
+impl StorageChange {
+    /// Decode the previous value into a concrete SCALE type
+    pub fn decode_old<T: DecodeAll>(&self) -> Result<Option<T>> {
+        decode_optional(self.old.as_deref())
+    }
+
+    /// Decode the new value into a concrete SCALE type
+    pub fn decode_new<T: DecodeAll>(&self) -> Result<Option<T>> {
+        decode_optional(self.new.as_deref())
+    }
+}
+
+fn decode_optional<T: DecodeAll>(bytes: Option<&[u8]>) -> Result<Option<T>> {
+    bytes
+        .map(|mut bytes| {
+            T::decode_all(&mut bytes)
+                .map_err(|e| Error::Storage(format!("Failed to decode storage value: {}", e)))
+        })
+        .transpose()
+}
+
+#[derive(Clone)]
+struct WatchedKey {
+    pallet: String,
+    item: String,
+    key: Vec<u8>,
+    value_ty: u32,
+}
+
+/// Where a raw value changed, with its old and new bytes
+type RawChange<T> = (T, Option<Vec<u8>>, Option<Vec<u8>>);
+
+/// Raw value and its decoded form
+type DecodedPair = (Option<Vec<u8>>, Option<subxt::dynamic::DecodedValue>);
+
+impl WatchedKey {
+    fn decode(&self, metadata: &subxt::Metadata, bytes: Option<Vec<u8>>) -> Result<DecodedPair> {
+        let value = bytes
+            .as_deref()
+            .map(|bytes| decode_value(bytes, self.value_ty, metadata.types()))
+            .transpose()?;
+        Ok((bytes, value))
+    }
+
+    fn change(
+        &self,
+        block_hash: H256,
+        block_number: u64,
+        (old, old_value): DecodedPair,
+        (new, new_value): DecodedPair,
+    ) -> StorageChange {
+        StorageChange {
+            key: self.key.clone(),
+            block_hash,
+            block_number,
+            old_value,
+            new_value,
+            old,
+            new,
+        }
+    }
+}
+
+struct WatchState {
+    client: OnlineClient<PolkadotConfig>,
+    metrics: Metrics,
+    metadata: subxt::Metadata,
+    keys: Vec<WatchedKey>,
+    /// `System::LastRuntimeUpgrade`, fetched with the watched keys to spot upgrades
+    runtime_key: Vec<u8>,
+    runtime: Option<Vec<u8>>,
+    current: HashMap<Vec<u8>, Vec<u8>>,
+    blocks: subxt::backend::StreamOfResults<
+        subxt::blocks::Block<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+    >,
+    pending: VecDeque<StorageChange>,
+}
+
+impl WatchState {
+    async fn next_change(&mut self) -> Result<Option<StorageChange>> {
+        loop {
+            if let Some(change) = self.pending.pop_front() {
+                return Ok(Some(change));
+            }
+
+            let Some(block) = self.blocks.next().await else {
+                return Ok(None);
+            };
+            let block =
+                block.map_err(|e| Error::Connection(format!("Block subscription error: {}", e)))?;
+            self.metrics.record_storage_query();
+
+            let mut keys: Vec<Vec<u8>> = self.keys.iter().map(|k| k.key.clone()).collect();
+            keys.push(self.runtime_key.clone());
+            let mut values = fetch_raw_values(&self.client, keys, block.hash()).await?;
+
+            // Old values were encoded with the runtime before this block
+            let before = (self.metadata.clone(), self.keys.clone());
+            let runtime = values.remove(&self.runtime_key);
+            if runtime != self.runtime {
+                self.refresh_metadata(block.hash()).await?;
+                self.runtime = runtime;
+            }
+
+            let (metadata, keys) = &before;
+            let watched = self.keys.iter().map(|k| k.key.as_slice());
+            for (index, old, new) in changed_values(watched, &mut self.current, values) {
+                let change = self.keys[index].change(
+                    block.hash(),
+                    u64::from(block.number()),
+                    keys[index].decode(metadata, old)?,
+                    self.keys[index].decode(&self.metadata, new)?,
+                );
+                self.pending.push_back(change);
+            }
+        }
+    }
+
+    /// Load the metadata of an upgraded runtime and retype the watched values
+    async fn refresh_metadata(&mut self, at: H256) -> Result<()> {
+        use subxt::backend::BackendExt as _;
+
+        let backend = self.client.backend();
+        let metadata = match backend.metadata_at_version(15, at).await {
+            Ok(metadata) => metadata,
+            Err(_) => backend
+                .legacy_metadata(at)
+                .await
+                .map_err(|e| Error::Metadata(format!("Failed to fetch metadata: {}", e)))?,
+        };
+
+        for watched in &mut self.keys {
+            watched.value_ty = storage_entry(&metadata, &watched.pallet, &watched.item)?
+                .entry_type()
+                .value_ty();
+        }
+        debug!("Runtime upgraded, watching with new metadata");
+        self.metadata = metadata;
+        Ok(())
+    }
+}
+
+/// Watched values that differ from `current`, as (index, old, new).
+///
+/// `current` is updated to the new values; keys missing from `values` are absent.
+fn changed_values<'a>(
+    keys: impl Iterator<Item = &'a [u8]>,
+    current: &mut HashMap<Vec<u8>, Vec<u8>>,
+    mut values: HashMap<Vec<u8>, Vec<u8>>,
+) -> Vec<RawChange<usize>> {
+    let mut changes = Vec::new();
+    for (index, key) in keys.enumerate() {
+        let new = values.remove(key);
+        if current.get(key) == new.as_ref() {
+            continue;
+        }
+        let old = match &new {
+            Some(new) => current.insert(key.to_vec(), new.clone()),
+            None => current.remove(key),
+        };
+        changes.push((index, old, new));
+    }
+    changes
+}
+
+/// Value per block from `state_queryStorage` change sets
+fn history_from_change_sets(
+    change_sets: Vec<subxt::backend::legacy::rpc_methods::StorageChangeSet<H256>>,
+    key: &[u8],
+) -> Vec<(H256, Option<Vec<u8>>)> {
+    change_sets
+        .into_iter()
+        .filter_map(|set| {
+            let value = set
+                .changes
+                .into_iter()
+                .find(|(changed, _)| changed.0 == key)?
+                .1
+                .map(|data| data.0);
+            Some((set.block, value))
+        })
+        .collect()
+}
+
+/// Changes in a history in chain order, as (block, old, new).
+///
+/// The first entry is the baseline; repeated values are not changes.
+fn value_history(history: Vec<(H256, Option<Vec<u8>>)>) -> Vec<RawChange<H256>> {
+    let mut changes = Vec::new();
+    let mut history = history.into_iter();
+    let mut previous = history.next().and_then(|(_, value)| value);
+    for (block_hash, value) in history {
+        if value == previous {
+            continue;
+        }
+        changes.push((block_hash, previous.take(), value.clone()));
+        previous = value;
+    }
+    changes
+}
+
+/// Fetch raw values for a batch of keys; missing keys are absent from the map
+async fn fetch_raw_values(
+    client: &OnlineClient<PolkadotConfig>,
+    keys: Vec<Vec<u8>>,
+    at: H256,
+) -> Result<HashMap<Vec<u8>, Vec<u8>>> {
+    let mut responses = client
+        .backend()
+        .storage_fetch_values(keys, at)
+        .await
+        .map_err(|e| Error::Storage(format!("Failed to fetch storage values: {}", e)))?;
+
+    let mut values = HashMap::new();
+    while let Some(response) = responses.next().await {
+        let response = response
+            .map_err(|e| Error::Storage(format!("Failed to fetch storage value: {}", e)))?;
+        values.insert(response.key, response.value);
+    }
+    Ok(values)
+}
+
+fn storage_entry<'a>(
+    metadata: &'a subxt::Metadata,
+    pallet: &str,
+    item: &str,
+) -> Result<&'a subxt::metadata::types::StorageEntryMetadata> {
+    metadata
+        .pallet_by_name(pallet)
+        .and_then(|p| p.storage())
+        .and_then(|s| s.entry_by_name(item))
+        .ok_or_else(|| Error::Metadata(format!("Storage entry {}::{} not found", pallet, item)))
+}
+
+/// Decode a SCALE value of type `ty`, rejecting trailing bytes
+pub fn decode_value(
+    bytes: &[u8],
+    ty: u32,
+    types: &PortableRegistry,
+) -> Result<subxt::dynamic::DecodedValue> {
+    let mut input = bytes;
+    let value = scale_value::scale::decode_as_type(&mut input, ty, types)
+        .map_err(|e| Error::Storage(format!("Failed to decode storage value: {}", e)))?;
+    if !input.is_empty() {
+        return Err(Error::Storage(format!(
+            "Failed to decode storage value: {} trailing bytes",
+            input.len()
+        )));
+    }
+    Ok(value)
+}
+
+/// Render a decoded value as JSON.
+///
+/// Named composites become objects and unnamed composites arrays, except that
+/// byte sequences and arrays become `0x` hex strings and single-field tuples are
+/// unwrapped. Unit variants become their name, other variants `{"Name": fields}`.
+/// Integers that do not fit in a JSON number are rendered as decimal strings.
+pub fn value_to_json(
+    value: &subxt::dynamic::DecodedValue,
+    types: &PortableRegistry,
+) -> serde_json::Value {
+    use serde_json::{json, Value as Json};
+
+    match &value.value {
+        ValueDef::Composite(composite) => {
+            if let Some(bytes) = byte_sequence(value.context, composite, types) {
+                return Json::String(format!("0x{}", hex::encode(bytes)));
+            }
+            composite_to_json(composite, types)
+        }
+        ValueDef::Variant(variant) => {
+            if variant.values.is_empty() {
+                Json::String(variant.name.clone())
+            } else {
+                json!({ variant.name.clone(): composite_to_json(&variant.values, types) })
+            }
+        }
+        ValueDef::BitSequence(bits) => Json::Array(bits.iter().map(Json::Bool).collect()),
+        ValueDef::Primitive(primitive) => match primitive {
+            Primitive::Bool(b) => Json::Bool(*b),
+            Primitive::Char(c) => Json::String(c.to_string()),
+            Primitive::String(s) => Json::String(s.clone()),
+            Primitive::U128(n) => u64::try_from(*n)
+                .map(Json::from)
+                .unwrap_or_else(|_| Json::String(n.to_string())),
+            Primitive::I128(n) => i64::try_from(*n)
+                .map(Json::from)
+                .unwrap_or_else(|_| Json::String(n.to_string())),
+            Primitive::U256(bytes) => {
+                let n = U256::from_little_endian(bytes);
+                if n <= U256::from(u64::MAX) {
+                    Json::from(n.as_u64())
+                } else {
+                    Json::String(n.to_string())
+                }
+            }
+            Primitive::I256(bytes) => {
+                // Two's complement, little-endian
+                let n = U256::from_little_endian(bytes);
+                let (magnitude, sign) = if n.bit(255) {
+                    ((!n).overflowing_add(U256::one()).0, "-")
+                } else {
+                    (n, "")
+                };
+                if magnitude <= U256::from(i64::MAX) {
+                    let n = magnitude.as_u64() as i64;
+                    Json::from(if sign.is_empty() { n } else { -n })
+                } else {
+                    Json::String(format!("{}{}", sign, magnitude))
+                }
+            }
+        },
+    }
+}
+
+pub(crate) fn composite_to_json(
+    composite: &Composite<u32>,
+    types: &PortableRegistry,
+) -> serde_json::Value {
+    match composite {
+        Composite::Named(fields) => serde_json::Value::Object(
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), value_to_json(value, types)))
+                .collect(),
+        ),
+        Composite::Unnamed(values) if values.len() == 1 => value_to_json(&values[0], types),
+        Composite::Unnamed(values) => {
+            serde_json::Value::Array(values.iter().map(|v| value_to_json(v, types)).collect())
+        }
+    }
+}
+
+/// Bytes of a `Vec<u8>` or `[u8; N]` value, looked up through its type
+fn byte_sequence(ty: u32, composite: &Composite<u32>, types: &PortableRegistry) -> Option<Vec<u8>> {
+    let element = match &types.resolve(ty)?.type_def {
+        TypeDef::Sequence(seq) => seq.type_param.id,
+        TypeDef::Array(array) => array.type_param.id,
+        _ => return None,
+    };
+    if !matches!(
+        types.resolve(element)?.type_def,
+        TypeDef::Primitive(TypeDefPrimitive::U8)
+    ) {
+        return None;
+    }
+    composite
+        .values()
+        .map(|v| match &v.value {
+            ValueDef::Primitive(Primitive::U128(n)) => u8::try_from(*n).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+// Helper function for parsing block hash from hex string
+fn parse_block_hash(hash_hex: &str) -> Result<H256> {
     // Remove 0x prefix if present
     let hash_hex = hash_hex.strip_prefix("0x").unwrap_or(hash_hex);
 
@@ -510,4 +1637,495 @@ mod tests {
         assert_eq!(query.item, "Account");
         assert_eq!(query.keys.len(), 1);
     }
+
+    /// Answers `state_getKeysPaged` from a sorted key list
+    struct MockKeysRpc {
+        keys: Vec<Vec<u8>>,
+    }
+
+    impl subxt::backend::rpc::RpcClientT for MockKeysRpc {
+        fn request_raw<'a>(
+            &'a self,
+            method: &'a str,
+            params: Option<Box<subxt::backend::rpc::RawValue>>,
+        ) -> subxt::backend::rpc::RawRpcFuture<'a, Box<subxt::backend::rpc::RawValue>> {
+            Box::pin(async move {
+                if method != "state_getKeysPaged" {
+                    return Err(subxt::ext::subxt_rpcs::Error::Client(
+                        format!("unexpected RPC request {}", method).into(),
+                    ));
+                }
+                let params: serde_json::Value =
+                    serde_json::from_str(params.as_ref().unwrap().get()).unwrap();
+                let count = params[1].as_u64().unwrap() as usize;
+                let start = params[2]
+                    .as_str()
+                    .map(|key| hex::decode(key.trim_start_matches("0x")).unwrap());
+                let keys: Vec<String> = self
+                    .keys
+                    .iter()
+                    .filter(|key| start.as_ref().is_none_or(|start| *key > start))
+                    .take(count)
+                    .map(|key| format!("0x{}", hex::encode(key)))
+                    .collect();
+                Ok(serde_json::value::to_raw_value(&keys).unwrap())
+            })
+        }
+
+        fn subscribe_raw<'a>(
+            &'a self,
+            sub: &'a str,
+            _params: Option<Box<subxt::backend::rpc::RawValue>>,
+            _unsub: &'a str,
+        ) -> subxt::backend::rpc::RawRpcFuture<'a, subxt::backend::rpc::RawRpcSubscription>
+        {
+            Box::pin(async move {
+                Err(subxt::ext::subxt_rpcs::Error::Client(
+                    format!("unexpected RPC subscription {}", sub).into(),
+                ))
+            })
+        }
+    }
+
+    /// Node whose pinned block ran spec version 1 with a `Legacy::Entries`
+    /// map keyed by `u32`
+    struct OldRuntimeRpc;
+
+    impl subxt::backend::rpc::RpcClientT for OldRuntimeRpc {
+        fn request_raw<'a>(
+            &'a self,
+            method: &'a str,
+            _params: Option<Box<subxt::backend::rpc::RawValue>>,
+        ) -> subxt::backend::rpc::RawRpcFuture<'a, Box<subxt::backend::rpc::RawValue>> {
+            use subxt::ext::codec::Encode;
+            use subxt::ext::frame_metadata::RuntimeMetadataPrefixed;
+
+            Box::pin(async move {
+                let result = match method {
+                    "state_getRuntimeVersion" => {
+                        serde_json::json!({"specVersion": 1, "transactionVersion": 1})
+                    }
+                    "state_getMetadata" => {
+                        let metadata = RuntimeMetadataPrefixed::from(test_metadata(true));
+                        serde_json::json!(format!("0x{}", hex::encode(metadata.encode())))
+                    }
+                    _ => {
+                        return Err(subxt::ext::subxt_rpcs::Error::Client(
+                            format!("unexpected RPC request {}", method).into(),
+                        ))
+                    }
+                };
+                Ok(serde_json::value::to_raw_value(&result).unwrap())
+            })
+        }
+
+        fn subscribe_raw<'a>(
+            &'a self,
+            sub: &'a str,
+            _params: Option<Box<subxt::backend::rpc::RawValue>>,
+            _unsub: &'a str,
+        ) -> subxt::backend::rpc::RawRpcFuture<'a, subxt::backend::rpc::RawRpcSubscription>
+        {
+            Box::pin(async move {
+                Err(subxt::ext::subxt_rpcs::Error::Client(
+                    format!("unexpected RPC subscription {}", sub).into(),
+                ))
+            })
+        }
+    }
+
+    /// Runtime metadata with no pallets, or with a `Legacy` pallet holding an
+    /// `Entries: map u32 => u64` storage item
+    fn test_metadata(with_legacy: bool) -> subxt::ext::frame_metadata::v15::RuntimeMetadataV15 {
+        use scale_info::meta_type;
+        use subxt::ext::frame_metadata::v15::{
+            CustomMetadata, ExtrinsicMetadata, OuterEnums, PalletMetadata, PalletStorageMetadata,
+            RuntimeMetadataV15, StorageEntryMetadata, StorageEntryModifier, StorageEntryType,
+            StorageHasher,
+        };
+
+        let legacy = PalletMetadata {
+            name: "Legacy",
+            storage: Some(PalletStorageMetadata {
+                prefix: "Legacy",
+                entries: vec![StorageEntryMetadata {
+                    name: "Entries",
+                    modifier: StorageEntryModifier::Default,
+                    ty: StorageEntryType::Map {
+                        hashers: vec![StorageHasher::Twox64Concat],
+                        key: meta_type::<u32>(),
+                        value: meta_type::<u64>(),
+                    },
+                    default: vec![0; 8],
+                    docs: Vec::new(),
+                }],
+            }),
+            calls: None,
+            event: None,
+            constants: Vec::new(),
+            error: None,
+            index: 0,
+            docs: Vec::new(),
+        };
+
+        RuntimeMetadataV15::new(
+            if with_legacy {
+                vec![legacy]
+            } else {
+                Vec::new()
+            },
+            ExtrinsicMetadata {
+                version: 4,
+                address_ty: meta_type::<()>(),
+                call_ty: meta_type::<()>(),
+                signature_ty: meta_type::<()>(),
+                extra_ty: meta_type::<()>(),
+                signed_extensions: Vec::new(),
+            },
+            meta_type::<()>(),
+            Vec::new(),
+            OuterEnums {
+                call_enum_ty: meta_type::<()>(),
+                event_enum_ty: meta_type::<()>(),
+                error_enum_ty: meta_type::<()>(),
+            },
+            CustomMetadata {
+                map: Default::default(),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_storage_at_uses_the_block_runtime() {
+        let client = OnlineClient::<PolkadotConfig>::from_rpc_client_with(
+            H256::zero(),
+            subxt::client::RuntimeVersion {
+                spec_version: 2,
+                transaction_version: 1,
+            },
+            subxt::metadata::types::Metadata::try_from(test_metadata(false)).unwrap(),
+            RpcClient::new(OldRuntimeRpc),
+        )
+        .unwrap();
+        let storage = StorageClient::new(client, Metrics::new())
+            .with_rpc_client(RpcClient::new(OldRuntimeRpc));
+        let keys = [serde_json::json!(7)];
+
+        // The current runtime no longer has the item
+        assert!(storage.keys_from_json("Legacy", "Entries", &keys).is_err());
+
+        let pinned = storage
+            .storage_at("0x0000000000000000000000000000000000000000000000000000000000000001")
+            .await
+            .unwrap();
+        let typed = pinned.keys_from_json("Legacy", "Entries", &keys).unwrap();
+        assert_eq!(
+            pinned.keys_to_json("Legacy", "Entries", &typed).unwrap(),
+            vec![serde_json::json!(7)]
+        );
+        assert!(pinned
+            .runtime_metadata()
+            .await
+            .unwrap()
+            .pallet_by_name("Legacy")
+            .is_some());
+    }
+
+    fn mock_keys() -> Vec<Vec<u8>> {
+        (0u8..5).map(|i| vec![0xaa, i]).collect()
+    }
+
+    fn pager(source: KeySource, page_size: u32, start_key: Option<Vec<u8>>) -> KeyPager {
+        KeyPager {
+            source,
+            prefix: vec![0xaa],
+            block_hash: H256::zero(),
+            page_size,
+            start_key,
+            exhausted: false,
+        }
+    }
+
+    fn descendants(keys: Vec<Vec<u8>>, skip_to: Option<Vec<u8>>) -> KeySource {
+        KeySource::Descendants {
+            keys: StreamOfResults::new(Box::pin(stream::iter(keys.into_iter().map(Ok)))),
+            skip_to,
+        }
+    }
+
+    async fn all_pages(mut pager: KeyPager) -> Vec<Vec<Vec<u8>>> {
+        let mut pages = Vec::new();
+        loop {
+            let keys = pager.next_keys().await.unwrap();
+            if keys.is_empty() {
+                break;
+            }
+            pages.push(keys);
+        }
+        pages
+    }
+
+    #[tokio::test]
+    async fn test_key_pager_pages_over_rpc() {
+        let keys = mock_keys();
+        let rpc = LegacyRpcMethods::new(RpcClient::new(MockKeysRpc { keys: keys.clone() }));
+
+        let pages = all_pages(pager(KeySource::Paged(rpc.clone()), 2, None)).await;
+        assert_eq!(
+            pages,
+            vec![keys[0..2].to_vec(), keys[2..4].to_vec(), keys[4..].to_vec()]
+        );
+
+        let pages = all_pages(pager(KeySource::Paged(rpc), 2, Some(keys[1].clone()))).await;
+        assert_eq!(pages, vec![keys[2..4].to_vec(), keys[4..].to_vec()]);
+    }
+
+    #[tokio::test]
+    async fn test_key_pager_descendants_fallback() {
+        let keys = mock_keys();
+
+        let pages = all_pages(pager(descendants(keys.clone(), None), 2, None)).await;
+        assert_eq!(
+            pages,
+            vec![keys[0..2].to_vec(), keys[2..4].to_vec(), keys[4..].to_vec()]
+        );
+
+        // Resuming skips up to the start key, then continues from the stream
+        let start = Some(keys[1].clone());
+        let mut resumed = pager(descendants(keys.clone(), start.clone()), 2, start);
+        assert_eq!(resumed.next_keys().await.unwrap(), keys[2..4].to_vec());
+        assert_eq!(resumed.start_key.as_deref(), Some(&keys[3][..]));
+        assert!(matches!(
+            resumed.source,
+            KeySource::Descendants { skip_to: None, .. }
+        ));
+        assert_eq!(resumed.next_keys().await.unwrap(), keys[4..].to_vec());
+        assert!(resumed.exhausted);
+        assert!(resumed.next_keys().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_decode_entries() {
+        use parity_scale_codec::Encode;
+        use subxt::metadata::types::{StorageEntryType, StorageHasher};
+
+        #[derive(Encode, scale_info::TypeInfo)]
+        struct Ledger {
+            free: u128,
+            locked: bool,
+        }
+
+        let mut registry = scale_info::Registry::new();
+        let key_ty = registry.register_type(&scale_info::meta_type::<u32>()).id;
+        let value_ty = registry
+            .register_type(&scale_info::meta_type::<Ledger>())
+            .id;
+        let types: PortableRegistry = registry.into();
+        let hashers = StorageHashers::new(
+            &StorageEntryType::Map {
+                hashers: vec![StorageHasher::Twox64Concat],
+                key_ty,
+                value_ty,
+            },
+            &types,
+        )
+        .unwrap();
+
+        let key = |who: u32| {
+            let mut key = vec![0u8; 32];
+            key.extend(sp_core::twox_64(&who.encode()));
+            key.extend(who.encode());
+            key
+        };
+        let value = |free: u128| {
+            Ledger {
+                free,
+                locked: false,
+            }
+            .encode()
+        };
+
+        let entries = decode_entries(
+            vec![(key(9), value(90)), (key(2), value(20))],
+            &types,
+            &hashers,
+            value_ty,
+        )
+        .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].key < entries[1].key);
+        for entry in &entries {
+            let who = entry.keys[0].as_u128().unwrap();
+            assert_eq!(
+                value_to_json(&entry.value, &types),
+                serde_json::json!({ "free": who * 10, "locked": false })
+            );
+        }
+
+        let mut trailing = value(1);
+        trailing.push(0);
+        let err = decode_entries(vec![(key(1), trailing)], &types, &hashers, value_ty)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("trailing bytes"), "{}", err);
+
+        assert!(
+            decode_entries(vec![(vec![0u8; 16], value(1))], &types, &hashers, value_ty).is_err()
+        );
+    }
+
+    #[test]
+    fn test_value_to_json_wide_integers() {
+        use subxt::dynamic::DecodedValue;
+
+        let types = PortableRegistry::from(scale_info::Registry::new());
+        let render = |primitive: Primitive| {
+            value_to_json(
+                &DecodedValue {
+                    value: ValueDef::Primitive(primitive),
+                    context: 0,
+                },
+                &types,
+            )
+        };
+        let le = |n: U256| n.to_little_endian();
+
+        assert_eq!(render(Primitive::U256(le(U256::from(42)))), 42);
+        let big = U256::from(u128::MAX) * 1000;
+        assert_eq!(render(Primitive::U256(le(big))), big.to_string());
+
+        let minus = |n: U256| le((!n).overflowing_add(U256::one()).0);
+        assert_eq!(render(Primitive::I256(minus(U256::from(7)))), -7);
+        assert_eq!(render(Primitive::I256(minus(big))), format!("-{}", big));
+    }
+
+    #[test]
+    fn test_changed_values() {
+        let keys = [vec![1u8], vec![2u8], vec![3u8]];
+        let mut current = HashMap::from([(vec![1u8], vec![10u8]), (vec![2u8], vec![20u8])]);
+
+        // Key 1 unchanged, key 2 removed, key 3 created
+        let values = HashMap::from([(vec![1u8], vec![10u8]), (vec![3u8], vec![30u8])]);
+        let changes = changed_values(keys.iter().map(Vec::as_slice), &mut current, values);
+        assert_eq!(
+            changes,
+            vec![(1, Some(vec![20u8]), None), (2, None, Some(vec![30u8]))]
+        );
+        assert_eq!(
+            current,
+            HashMap::from([(vec![1u8], vec![10u8]), (vec![3u8], vec![30u8])])
+        );
+
+        // Same values again: nothing to report
+        let values = current.clone();
+        assert!(changed_values(keys.iter().map(Vec::as_slice), &mut current, values).is_empty());
+    }
+
+    #[test]
+    fn test_value_history() {
+        use subxt::backend::legacy::rpc_methods::{Bytes, StorageChangeSet};
+
+        let block = |n: u8| H256::repeat_byte(n);
+        let key = vec![0xaa];
+        let set = |n: u8, changes: Vec<(Vec<u8>, Option<Vec<u8>>)>| StorageChangeSet {
+            block: block(n),
+            changes: changes
+                .into_iter()
+                .map(|(key, value)| (Bytes(key), value.map(Bytes)))
+                .collect(),
+        };
+
+        // The first set is the baseline; sets for other keys are skipped
+        let history = history_from_change_sets(
+            vec![
+                set(1, vec![(key.clone(), Some(vec![1]))]),
+                set(2, vec![(vec![0xbb], Some(vec![9]))]),
+                set(3, vec![(key.clone(), Some(vec![2]))]),
+                set(4, vec![(key.clone(), None)]),
+            ],
+            &key,
+        );
+        assert_eq!(
+            history,
+            vec![
+                (block(1), Some(vec![1])),
+                (block(3), Some(vec![2])),
+                (block(4), None),
+            ]
+        );
+        assert_eq!(
+            value_history(history),
+            vec![
+                (block(3), Some(vec![1]), Some(vec![2])),
+                (block(4), Some(vec![2]), None),
+            ]
+        );
+
+        // A header walk repeats unchanged values
+        let walked = vec![
+            (block(1), None),
+            (block(2), None),
+            (block(3), Some(vec![5])),
+            (block(4), Some(vec![5])),
+        ];
+        assert_eq!(value_history(walked), vec![(block(3), None, Some(vec![5]))]);
+        assert!(value_history(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_value_to_json() {
+        use parity_scale_codec::Encode;
+        use serde_json::json;
+
+        #[derive(Encode, scale_info::TypeInfo)]
+        struct Balance(u128);
+
+        #[derive(Encode, scale_info::TypeInfo)]
+        enum Status {
+            Idle,
+            Locked { until: u32 },
+        }
+
+        #[derive(Encode, scale_info::TypeInfo)]
+        struct Sample {
+            who: [u8; 4],
+            memo: Vec<u8>,
+            amounts: Vec<u32>,
+            balance: Balance,
+            huge: u128,
+            status: Status,
+            idle: Status,
+        }
+
+        let mut registry = scale_info::Registry::new();
+        let id = registry
+            .register_type(&scale_info::meta_type::<Sample>())
+            .id;
+        let types: PortableRegistry = registry.into();
+
+        let encoded = Sample {
+            who: [1, 2, 3, 4],
+            memo: Vec::new(),
+            amounts: vec![1, 2, 3],
+            balance: Balance(42),
+            huge: u128::MAX,
+            status: Status::Locked { until: 7 },
+            idle: Status::Idle,
+        }
+        .encode();
+        let value = scale_value::scale::decode_as_type(&mut &encoded[..], id, &types).unwrap();
+
+        assert_eq!(
+            value_to_json(&value, &types),
+            json!({
+                "who": "0x01020304",
+                "memo": "0x",
+                "amounts": [1, 2, 3],
+                "balance": 42,
+                "huge": u128::MAX.to_string(),
+                "status": { "Locked": { "until": 7 } },
+                "idle": "Idle",
+            })
+        );
+    }
 }
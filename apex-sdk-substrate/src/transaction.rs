@@ -8,9 +8,10 @@
 //! - Transaction confirmation tracking
 
 use crate::{Error, Metrics, Result, Sr25519Signer, Wallet};
-use apex_sdk_core::{FeeEstimator, SdkError};
+use apex_sdk_core::{FeeEstimator, PipelineStage, SdkError};
 use async_trait::async_trait;
 use std::time::Duration;
+use subxt::config::DefaultExtrinsicParamsBuilder;
 use subxt::{OnlineClient, PolkadotConfig};
 use tokio::time::sleep;
 use tracing::{debug, info, warn};
@@ -254,37 +255,70 @@ impl TransactionExecutor {
 
         let apex_signer = Sr25519Signer::new(pair.clone());
 
-        let mut progress = self
-            .client
-            .tx()
-            .sign_and_submit_then_watch_default(call, &apex_signer)
-            .await
-            .map_err(|e| Error::Transaction(format!("Failed to submit transaction: {}", e)))?;
+        let telemetry = self.metrics.telemetry();
+        let account_id = subxt::tx::Signer::<PolkadotConfig>::account_id(&apex_signer);
 
-        while let Some(event) = progress.next().await {
-            let event =
-                event.map_err(|e| Error::Transaction(format!("Transaction error: {}", e)))?;
+        let nonce = telemetry
+            .instrument_stage(PipelineStage::Nonce, async {
+                self.client
+                    .tx()
+                    .account_nonce(&account_id)
+                    .await
+                    .map_err(|e| Error::Transaction(format!("Failed to fetch nonce: {}", e)))
+            })
+            .await?;
+
+        let signed = telemetry
+            .instrument_stage(PipelineStage::Sign, async {
+                let params = DefaultExtrinsicParamsBuilder::new().nonce(nonce).build();
+                let mut partial = self
+                    .client
+                    .tx()
+                    .create_partial(call, &account_id, params)
+                    .await
+                    .map_err(|e| {
+                        Error::Transaction(format!("Failed to build transaction: {}", e))
+                    })?;
+                Ok::<_, Error>(partial.sign(&apex_signer))
+            })
+            .await?;
 
-            if event.as_in_block().is_some() {
-                info!("Transaction included in block");
-            }
+        let mut progress = telemetry
+            .instrument_stage(PipelineStage::Broadcast, async {
+                signed
+                    .submit_and_watch()
+                    .await
+                    .map_err(|e| Error::Transaction(format!("Failed to submit transaction: {}", e)))
+            })
+            .await?;
 
-            if let Some(finalized) = event.as_finalized() {
-                let tx_hash = format!("0x{}", hex::encode(finalized.extrinsic_hash()));
-                info!("Transaction finalized: {}", tx_hash);
+        telemetry
+            .instrument_stage(PipelineStage::Confirm, async {
+                while let Some(event) = progress.next().await {
+                    let event = event
+                        .map_err(|e| Error::Transaction(format!("Transaction error: {}", e)))?;
 
-                finalized
-                    .wait_for_success()
-                    .await
-                    .map_err(|e| Error::Transaction(format!("Transaction failed: {}", e)))?;
+                    if event.as_in_block().is_some() {
+                        info!("Transaction included in block");
+                    }
 
-                return Ok(tx_hash);
-            }
-        }
+                    if let Some(finalized) = event.as_finalized() {
+                        let tx_hash = format!("0x{}", hex::encode(finalized.extrinsic_hash()));
+                        info!("Transaction finalized: {}", tx_hash);
 
-        Err(Error::Transaction(
-            "Transaction stream ended without finalization".to_string(),
-        ))
+                        finalized.wait_for_success().await.map_err(|e| {
+                            Error::Transaction(format!("Transaction failed: {}", e))
+                        })?;
+
+                        return Ok(tx_hash);
+                    }
+                }
+
+                Err(Error::Transaction(
+                    "Transaction stream ended without finalization".to_string(),
+                ))
+            })
+            .await
     }
 
     /// Estimate fees for a transaction
@@ -323,14 +357,19 @@ impl TransactionExecutor {
         };
 
         let result = self
-            .client
-            .runtime_api()
-            .at_latest()
-            .await
-            .map_err(|e| Error::Connection(format!("Failed to get latest block: {}", e)))?
-            .call_raw("TransactionPaymentApi_query_info", Some(&call_data))
-            .await
-            .map_err(|e| Error::Transaction(format!("Failed to query fee info: {}", e)))?;
+            .metrics
+            .telemetry()
+            .instrument_stage(PipelineStage::Estimate, async {
+                self.client
+                    .runtime_api()
+                    .at_latest()
+                    .await
+                    .map_err(|e| Error::Connection(format!("Failed to get latest block: {}", e)))?
+                    .call_raw("TransactionPaymentApi_query_info", Some(&call_data))
+                    .await
+                    .map_err(|e| Error::Transaction(format!("Failed to query fee info: {}", e)))
+            })
+            .await?;
 
         if result.len() >= 16 {
             let fee_bytes = &result[result.len() - 16..];
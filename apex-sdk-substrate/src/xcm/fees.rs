@@ -0,0 +1,563 @@
+//! XCM fee estimation via the `XcmPaymentApi` and `DryRunApi` runtime APIs
+//!
+//! A transfer is dry-run on the origin to learn which messages it would
+//! forward. Delivery fees for those messages are quoted by the origin, and,
+//! when a destination client is available, the destination dry-runs the
+//! message and prices its weight in the message's fee asset.
+
+use super::{
+    decode_asset_id, decode_versioned_location, encode_asset_id, encode_versioned_location,
+    AssetId, Junction, MultiLocation, Weight, XcmCall, XcmExecutor, XcmProgram, XcmTransferType,
+    XcmVersion,
+};
+use crate::{Error, Result, XcmAsset};
+use subxt::dynamic::Value;
+use subxt::ext::scale_value::{At, Composite, ValueDef};
+use subxt::{OnlineClient, PolkadotConfig};
+use tracing::debug;
+
+/// Message a dry run would send to another chain
+#[derive(Debug, Clone)]
+pub struct ForwardedXcm {
+    /// Destination as a `VersionedLocation` value
+    pub destination: Value,
+    /// Destination decoded, if it only uses known junctions
+    pub location: Option<MultiLocation>,
+    /// Messages sent to the destination, as `VersionedXcm` values
+    pub messages: Vec<Value>,
+}
+
+/// Estimated fees of an XCM call
+#[derive(Debug, Clone, Default)]
+pub struct XcmFeeEstimate {
+    /// Fees for delivering the forwarded messages, charged on the origin
+    ///
+    /// Only fees in the transfer's fee asset are counted for transfers.
+    pub delivery_fee: u128,
+    /// Execution fee on the destination, when a destination client is set
+    pub destination_fee: Option<u128>,
+    /// Weight of the forwarded messages on the destination
+    pub destination_weight: Option<Weight>,
+    /// Messages the call would send
+    pub forwarded: Vec<ForwardedXcm>,
+}
+
+impl XcmFeeEstimate {
+    /// Delivery fee plus destination execution fee
+    pub fn total_fee(&self) -> u128 {
+        self.delivery_fee
+            .saturating_add(self.destination_fee.unwrap_or(0))
+    }
+}
+
+impl XcmExecutor {
+    /// Estimate the fees of a reserve transfer or teleport
+    ///
+    /// `origin` is the account that would sign the transfer.
+    pub async fn estimate_transfer(
+        &self,
+        origin: [u8; 32],
+        transfer_type: XcmTransferType,
+        dest: &MultiLocation,
+        beneficiary: [u8; 32],
+        assets: &[XcmAsset],
+    ) -> Result<XcmFeeEstimate> {
+        let call = self
+            .transfer_call(
+                transfer_type,
+                dest,
+                beneficiary,
+                assets,
+                self.config.weight_limit,
+            )
+            .await?;
+        let fee_asset = assets.get(self.fee_asset_index(assets)).map(|a| &a.id);
+        self.estimate_call(origin, dest, &call, fee_asset).await
+    }
+
+    /// Estimate the fees of sending `program` to `dest`
+    pub async fn estimate_send(
+        &self,
+        origin: [u8; 32],
+        dest: &MultiLocation,
+        program: &XcmProgram,
+    ) -> Result<XcmFeeEstimate> {
+        let version = self.resolve_version(dest).await?;
+        let call = XcmCall {
+            pallet: self.pallet_name()?,
+            name: "send",
+            fields: vec![
+                encode_versioned_location(dest, version)?,
+                program.encode(version)?,
+            ],
+        };
+        let fee_asset = self.config.fee_asset.as_ref().map(|a| &a.id);
+        self.estimate_call(origin, dest, &call, fee_asset).await
+    }
+
+    /// Check whether the connected runtime exposes `DryRunApi`
+    pub(super) fn supports_dry_run(&self) -> bool {
+        self.client
+            .metadata()
+            .runtime_api_trait_by_name("DryRunApi")
+            .is_some()
+    }
+
+    /// Dry-run `call` and price its messages
+    ///
+    /// With `fee_asset`, delivery fees quoted in other assets are ignored.
+    pub(super) async fn estimate_call(
+        &self,
+        origin: [u8; 32],
+        dest: &MultiLocation,
+        call: &XcmCall,
+        fee_asset: Option<&AssetId>,
+    ) -> Result<XcmFeeEstimate> {
+        let forwarded = self.dry_run_call(origin, call).await?;
+
+        let mut estimate = XcmFeeEstimate::default();
+        for fwd in &forwarded {
+            for message in &fwd.messages {
+                let fees = runtime_call(
+                    &self.client,
+                    "XcmPaymentApi",
+                    "query_delivery_fees",
+                    vec![fwd.destination.clone(), message.clone()],
+                )
+                .await?;
+                estimate.delivery_fee = estimate
+                    .delivery_fee
+                    .saturating_add(fungible_total(&fees, fee_asset));
+            }
+        }
+
+        if let Some(destination) = &self.destination {
+            let origin_location = self.origin_as_seen_from(dest).await?;
+            for fwd in forwarded
+                .iter()
+                .filter(|f| f.location.as_ref() == Some(dest))
+            {
+                for message in &fwd.messages {
+                    if let Some(origin_location) = &origin_location {
+                        dry_run_xcm(destination, origin_location, message).await?;
+                    }
+
+                    let weight = decode_weight(
+                        &runtime_call(
+                            destination,
+                            "XcmPaymentApi",
+                            "query_xcm_weight",
+                            vec![message.clone()],
+                        )
+                        .await?,
+                    )
+                    .ok_or_else(|| Error::Transaction("Invalid XCM weight".to_string()))?;
+                    let total = estimate.destination_weight.get_or_insert(Weight::new(0, 0));
+                    total.ref_time = total.ref_time.saturating_add(weight.ref_time);
+                    total.proof_size = total.proof_size.saturating_add(weight.proof_size);
+
+                    let Some(asset_id) = self.destination_fee_asset(message)? else {
+                        debug!("No fee asset found for destination message");
+                        continue;
+                    };
+                    let fee = runtime_call(
+                        destination,
+                        "XcmPaymentApi",
+                        "query_weight_to_asset_fee",
+                        vec![
+                            Value::named_composite([
+                                ("ref_time", Value::u128(weight.ref_time as u128)),
+                                ("proof_size", Value::u128(weight.proof_size as u128)),
+                            ]),
+                            asset_id,
+                        ],
+                    )
+                    .await?
+                    .as_u128()
+                    .ok_or_else(|| Error::Transaction("Invalid asset fee".to_string()))?;
+                    estimate.destination_fee =
+                        Some(estimate.destination_fee.unwrap_or(0).saturating_add(fee));
+                }
+            }
+        }
+
+        debug!(
+            "XCM fee estimate: delivery {}, destination {:?}, {} forwarded",
+            estimate.delivery_fee,
+            estimate.destination_fee,
+            forwarded.len()
+        );
+        estimate.forwarded = forwarded;
+        Ok(estimate)
+    }
+
+    /// Dry-run `call` from `origin`, failing if it would not succeed
+    async fn dry_run_call(&self, origin: [u8; 32], call: &XcmCall) -> Result<Vec<ForwardedXcm>> {
+        let origin_caller = Value::unnamed_variant(
+            "system",
+            vec![Value::unnamed_variant(
+                "Signed",
+                vec![Value::from_bytes(origin)],
+            )],
+        );
+        let mut args = vec![origin_caller, call.runtime_call()];
+
+        // Version 2 of the API takes the XCM version of the returned messages
+        let metadata = self.client.metadata();
+        let takes_version = metadata
+            .runtime_api_trait_by_name("DryRunApi")
+            .and_then(|api| api.method_by_name("dry_run_call"))
+            .is_some_and(|method| method.inputs().len() > 2);
+        if takes_version {
+            let version = self
+                .local_versions()
+                .last()
+                .copied()
+                .unwrap_or(XcmVersion::V3);
+            args.push(Value::u128(version.as_u32() as u128));
+        }
+
+        let effects = runtime_call(&self.client, "DryRunApi", "dry_run_call", args).await?;
+        let execution = effects
+            .at("execution_result")
+            .ok_or_else(|| Error::Transaction("Invalid dry run result".to_string()))?;
+        if let ValueDef::Variant(result) = &execution.value {
+            if result.name == "Err" {
+                return Err(Error::Transaction(format!(
+                    "XCM call would fail: {}",
+                    execution
+                )));
+            }
+        }
+
+        Ok(effects
+            .at("forwarded_xcms")
+            .map(|list| {
+                items(list)
+                    .into_iter()
+                    .filter_map(decode_forwarded)
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Location of this chain as seen from `dest`
+    ///
+    /// Returns `None` if this chain is a parachain without `ParachainInfo`.
+    async fn origin_as_seen_from(&self, dest: &MultiLocation) -> Result<Option<MultiLocation>> {
+        if dest.parents == 0 {
+            return Ok(Some(MultiLocation::parent()));
+        }
+
+        let query = subxt::dynamic::storage("ParachainInfo", "ParachainId", Vec::<Value>::new());
+        let storage = self
+            .client
+            .storage()
+            .at_latest()
+            .await
+            .map_err(|e| Error::Connection(format!("Failed to fetch latest block: {}", e)))?;
+        let para_id = match storage.fetch(&query).await {
+            Ok(Some(value)) => value
+                .to_value()
+                .ok()
+                .and_then(|v| first_u128(&v))
+                .and_then(|id| u32::try_from(id).ok()),
+            _ => None,
+        };
+
+        Ok(para_id.map(|id| {
+            let parents = if dest.interior.is_empty() { 0 } else { 1 };
+            MultiLocation::new(parents, vec![Junction::Parachain(id)])
+        }))
+    }
+
+    /// `VersionedAssetId` the destination charges execution in
+    ///
+    /// Uses the configured fee asset, or the asset of the message's
+    /// `BuyExecution`.
+    fn destination_fee_asset(&self, message: &Value) -> Result<Option<Value>> {
+        let ValueDef::Variant(versioned) = &message.value else {
+            return Ok(None);
+        };
+
+        if let Some(fee_asset) = &self.config.fee_asset {
+            let Some(version) = XcmVersion::from_variant_name(&versioned.name) else {
+                return Ok(None);
+            };
+            return Ok(Some(Value::unnamed_variant(
+                versioned.name.clone(),
+                vec![encode_asset_id(&fee_asset.id, version)?],
+            )));
+        }
+
+        let instructions = versioned.values.values().next().map(items);
+        let fees_id = instructions
+            .into_iter()
+            .flatten()
+            .find_map(|instruction| match &instruction.value {
+                ValueDef::Variant(v) if v.name == "BuyExecution" => v.at("fees")?.at("id"),
+                _ => None,
+            });
+        Ok(fees_id.map(|id| Value::unnamed_variant(versioned.name.clone(), vec![id.clone()])))
+    }
+}
+
+/// Call a runtime API and unwrap its `Result` return value
+async fn runtime_call(
+    client: &OnlineClient<PolkadotConfig>,
+    api: &str,
+    method: &str,
+    args: Vec<Value>,
+) -> Result<Value> {
+    let payload = subxt::dynamic::runtime_api_call(api, method, args);
+    let value = client
+        .runtime_api()
+        .at_latest()
+        .await
+        .map_err(|e| Error::Connection(format!("Failed to get latest block: {}", e)))?
+        .call(payload)
+        .await
+        .map_err(|e| Error::Transaction(format!("{}::{} failed: {}", api, method, e)))?
+        .to_value()
+        .map_err(|e| Error::Transaction(format!("Failed to decode {}::{}: {}", api, method, e)))?
+        .remove_context();
+
+    match value.value {
+        ValueDef::Variant(result) if result.name == "Ok" => result
+            .values
+            .into_values()
+            .next()
+            .ok_or_else(|| Error::Transaction(format!("Empty {}::{} result", api, method))),
+        ValueDef::Variant(result) if result.name == "Err" => Err(Error::Transaction(format!(
+            "{}::{} returned an error: {}",
+            api,
+            method,
+            Value::variant(result.name, result.values)
+        ))),
+        other => Ok(Value {
+            value: other,
+            context: (),
+        }),
+    }
+}
+
+/// Dry-run a message on the destination, failing unless it completes
+async fn dry_run_xcm(
+    client: &OnlineClient<PolkadotConfig>,
+    origin: &MultiLocation,
+    message: &Value,
+) -> Result<()> {
+    let ValueDef::Variant(versioned) = &message.value else {
+        return Ok(());
+    };
+    let Some(version) = XcmVersion::from_variant_name(&versioned.name) else {
+        return Ok(());
+    };
+
+    let effects = runtime_call(
+        client,
+        "DryRunApi",
+        "dry_run_xcm",
+        vec![encode_versioned_location(origin, version)?, message.clone()],
+    )
+    .await?;
+
+    match effects.at("execution_result").map(|outcome| &outcome.value) {
+        Some(ValueDef::Variant(outcome)) if outcome.name != "Complete" => {
+            Err(Error::Transaction(format!(
+                "XCM message would not complete on destination: {}",
+                Value::variant(outcome.name.clone(), outcome.values.clone())
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn decode_forwarded(entry: &Value) -> Option<ForwardedXcm> {
+    let destination = entry.at(0)?.clone();
+    let messages = items(entry.at(1)?).into_iter().cloned().collect();
+    Some(ForwardedXcm {
+        location: decode_versioned_location(&destination),
+        destination,
+        messages,
+    })
+}
+
+/// Elements of a sequence, looking through newtype wrappers like `Assets`
+fn items(value: &Value) -> Vec<&Value> {
+    let mut current = value;
+    loop {
+        match &current.value {
+            ValueDef::Composite(Composite::Unnamed(values)) => match &values[..] {
+                [inner] if matches!(inner.value, ValueDef::Composite(Composite::Unnamed(_))) => {
+                    current = inner
+                }
+                _ => return values.iter().collect(),
+            },
+            _ => return Vec::new(),
+        }
+    }
+}
+
+/// Sum of fungible amounts in a `VersionedAssets` value
+///
+/// With `fee_asset`, only assets with that ID are counted.
+fn fungible_total(assets: &Value, fee_asset: Option<&AssetId>) -> u128 {
+    let ValueDef::Variant(versioned) = &assets.value else {
+        return 0;
+    };
+    let version = XcmVersion::from_variant_name(&versioned.name);
+    // Round-trip the wanted ID so it compares like a decoded one (v3 padding)
+    let wanted = match (fee_asset, version) {
+        (None, _) => None,
+        (Some(id), Some(version)) => {
+            match encode_asset_id(id, version)
+                .ok()
+                .and_then(|value| decode_asset_id(&value, version))
+            {
+                Some(id) => Some((id, version)),
+                None => return 0,
+            }
+        }
+        (Some(_), None) => return 0,
+    };
+
+    versioned
+        .values
+        .values()
+        .next()
+        .map(items)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|asset| match &wanted {
+            Some((id, version)) => {
+                asset
+                    .at("id")
+                    .and_then(|value| decode_asset_id(value, *version))
+                    .as_ref()
+                    == Some(id)
+            }
+            None => true,
+        })
+        .filter_map(|asset| match &asset.at("fun")?.value {
+            ValueDef::Variant(fun) if fun.name == "Fungible" => fun.at(0)?.as_u128(),
+            _ => None,
+        })
+        .fold(0u128, u128::saturating_add)
+}
+
+fn decode_weight(value: &Value) -> Option<Weight> {
+    Some(Weight::new(
+        u64::try_from(value.at("ref_time")?.as_u128()?).ok()?,
+        u64::try_from(value.at("proof_size")?.as_u128()?).ok()?,
+    ))
+}
+
+/// First primitive in a value, looking through newtypes like `ParaId`
+fn first_u128<T>(value: &subxt::dynamic::Value<T>) -> Option<u128> {
+    match &value.value {
+        ValueDef::Composite(fields) => fields.values().next().and_then(first_u128),
+        _ => value.as_u128(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xcm::{encode_location, encode_versioned_assets};
+
+    #[test]
+    fn test_fungible_total_sums_assets() {
+        let assets = vec![XcmAsset::native(100), XcmAsset::native(250)];
+        for version in [XcmVersion::V3, XcmVersion::V4] {
+            let encoded = encode_versioned_assets(&assets, version).unwrap();
+            assert_eq!(fungible_total(&encoded, None), 350);
+        }
+
+        // Decoded values keep the `Assets` newtype wrapper
+        let wrapped = Value::unnamed_variant(
+            "V4",
+            vec![Value::unnamed_composite(vec![Value::unnamed_composite(
+                vec![Value::named_composite([
+                    ("id", Value::unnamed_composite(vec![])),
+                    (
+                        "fun",
+                        Value::unnamed_variant("Fungible", vec![Value::u128(7)]),
+                    ),
+                ])],
+            )])],
+        );
+        assert_eq!(fungible_total(&wrapped, None), 7);
+    }
+
+    #[test]
+    fn test_fungible_total_counts_only_fee_asset() {
+        let relay = AssetId::Concrete(MultiLocation::parent());
+        let usdt = AssetId::Concrete(MultiLocation::new(
+            0,
+            vec![Junction::PalletInstance(50), Junction::GeneralIndex(1984)],
+        ));
+        let assets = vec![
+            XcmAsset::fungible(relay.clone(), 100),
+            XcmAsset::fungible(usdt.clone(), 40),
+            XcmAsset::fungible(relay.clone(), 5),
+        ];
+
+        for version in [XcmVersion::V3, XcmVersion::V4, XcmVersion::V5] {
+            let encoded = encode_versioned_assets(&assets, version).unwrap();
+            assert_eq!(fungible_total(&encoded, Some(&relay)), 105);
+            assert_eq!(fungible_total(&encoded, Some(&usdt)), 40);
+            assert_eq!(fungible_total(&encoded, None), 145);
+
+            let native = AssetId::Concrete(MultiLocation::new(0, vec![]));
+            assert_eq!(fungible_total(&encoded, Some(&native)), 0);
+        }
+
+        // A v4 ID without the `AssetId` newtype wrapper is a bare location
+        let bare = Value::unnamed_variant(
+            "V4",
+            vec![Value::unnamed_composite(vec![Value::named_composite([
+                (
+                    "id",
+                    encode_location(&MultiLocation::parent(), XcmVersion::V4).unwrap(),
+                ),
+                (
+                    "fun",
+                    Value::unnamed_variant("Fungible", vec![Value::u128(9)]),
+                ),
+            ])])],
+        );
+        assert_eq!(fungible_total(&bare, Some(&relay)), 9);
+    }
+
+    #[test]
+    fn test_decode_forwarded_message() {
+        let dest =
+            encode_versioned_location(&MultiLocation::parachain(1000), XcmVersion::V4).unwrap();
+        let message = XcmProgram::new()
+            .buy_execution(XcmAsset::native(5), crate::WeightLimit::Unlimited)
+            .encode(XcmVersion::V4)
+            .unwrap();
+        let entry = Value::unnamed_composite(vec![dest, Value::unnamed_composite(vec![message])]);
+
+        let forwarded = decode_forwarded(&entry).unwrap();
+        assert_eq!(forwarded.location, Some(MultiLocation::parachain(1000)));
+        assert_eq!(forwarded.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_decode_weight_and_total() {
+        let weight = Value::named_composite([
+            ("ref_time", Value::u128(1_000)),
+            ("proof_size", Value::u128(64)),
+        ]);
+        assert_eq!(decode_weight(&weight), Some(Weight::new(1_000, 64)));
+
+        let estimate = XcmFeeEstimate {
+            delivery_fee: 10,
+            destination_fee: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(estimate.total_fee(), 15);
+    }
+}
@@ -0,0 +1,1937 @@
+//! XCM (Cross-Consensus Messaging) support for cross-chain transfers
+//!
+//! This module provides functionality for sending XCM messages across parachains
+//! and relay chains in the Polkadot ecosystem.
+//!
+//! ## Features
+//!
+//! - Reserve transfers (transfer assets via reserve chain)
+//! - Teleport transfers (burn and mint across chains)
+//! - Multi-location address handling
+//! - Version-correct `VersionedLocation`/`VersionedAssets` encoding for XCM v3, v4 and v5
+//! - Destination version discovery via `XcmPallet::SupportedVersion`
+//! - Arbitrary XCM programs submitted with `execute` or `send`
+//! - `XcmPallet` (relay chains) and `PolkadotXcm` (parachains) detection
+//! - Delivery and destination fee estimation via `XcmPaymentApi` and `DryRunApi`
+//! - Transfer tracking from origin events to `MessageQueue::Processed` on the destination
+//! - Parachain-to-parachain transfers
+//! - Parachain-to-relay transfers
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use apex_sdk_substrate::xcm::{XcmExecutor, XcmTransferType, MultiLocation};
+//!
+//! let executor = XcmExecutor::new(client);
+//!
+//! // Transfer from parachain to relay chain
+//! let tx_hash = executor
+//!     .transfer(
+//!         beneficiary,
+//!         amount,
+//!         XcmTransferType::ReserveTransfer,
+//!         MultiLocation::parent(),
+//!     )
+//!     .await?;
+//! ```
+
+use crate::{Error, Result, Sr25519Signer, Wallet};
+use scale_info::{PortableRegistry, TypeDef};
+use subxt::dynamic::Value;
+use subxt::ext::scale_value::{At, ValueDef};
+use subxt::{OnlineClient, PolkadotConfig};
+use tracing::{debug, info, warn};
+
+mod fees;
+mod tracking;
+
+pub use fees::{ForwardedXcm, XcmFeeEstimate};
+pub use tracking::{XcmSubmission, XcmTracker};
+
+use tracking::sent_message_id;
+
+/// Genesis hash of Westend, used for its `NetworkId` from XCM v5 on
+pub const WESTEND_GENESIS: [u8; 32] = [
+    0xe1, 0x43, 0xf2, 0x38, 0x03, 0xac, 0x50, 0xe8, 0xf6, 0xf8, 0xe6, 0x26, 0x95, 0xd1, 0xce, 0x9e,
+    0x4e, 0x1d, 0x68, 0xaa, 0x36, 0xc1, 0xcd, 0x2c, 0xfd, 0x15, 0x34, 0x02, 0x13, 0xf3, 0x42, 0x3e,
+];
+
+/// Genesis hash of Rococo, used for its `NetworkId` from XCM v5 on
+pub const ROCOCO_GENESIS: [u8; 32] = [
+    0x64, 0x08, 0xde, 0x77, 0x37, 0xc5, 0x9c, 0x23, 0x88, 0x90, 0x53, 0x3a, 0xf2, 0x58, 0x96, 0xa2,
+    0xc2, 0x06, 0x08, 0xd8, 0xb3, 0x80, 0xbb, 0x01, 0x02, 0x9a, 0xcb, 0x39, 0x27, 0x81, 0x06, 0x3e,
+];
+
+/// Proof size attached to [`WeightLimit::Limited`] (the relay chain PoV limit)
+const MAX_PROOF_SIZE: u64 = 5 * 1024 * 1024;
+
+/// XCM version to use for message construction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum XcmVersion {
+    /// XCM version 2 (no longer accepted by current runtimes)
+    V2,
+    /// XCM version 3 (recommended)
+    #[default]
+    V3,
+    /// XCM version 4
+    V4,
+    /// XCM version 5 (latest)
+    V5,
+}
+
+impl XcmVersion {
+    /// Numeric version as stored on chain
+    pub fn as_u32(&self) -> u32 {
+        match self {
+            XcmVersion::V2 => 2,
+            XcmVersion::V3 => 3,
+            XcmVersion::V4 => 4,
+            XcmVersion::V5 => 5,
+        }
+    }
+
+    /// Version from its numeric value
+    pub fn from_u32(version: u32) -> Option<Self> {
+        match version {
+            2 => Some(XcmVersion::V2),
+            3 => Some(XcmVersion::V3),
+            4 => Some(XcmVersion::V4),
+            5 => Some(XcmVersion::V5),
+            _ => None,
+        }
+    }
+
+    /// Variant name used by `VersionedLocation`, `VersionedAssets`, etc.
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            XcmVersion::V2 => "V2",
+            XcmVersion::V3 => "V3",
+            XcmVersion::V4 => "V4",
+            XcmVersion::V5 => "V5",
+        }
+    }
+
+    fn from_variant_name(name: &str) -> Option<Self> {
+        name.strip_prefix('V')
+            .and_then(|n| n.parse().ok())
+            .and_then(Self::from_u32)
+    }
+}
+
+/// Type of XCM transfer to perform
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XcmTransferType {
+    /// Reserve transfer - assets are held in reserve on the origin chain
+    /// Suitable for transfers to parachains that trust the origin
+    ReserveTransfer,
+
+    /// Teleport - assets are burned on origin and minted on destination
+    /// Requires mutual trust between chains
+    Teleport,
+
+    /// Limited reserve transfer - like reserve transfer but with weight limits
+    LimitedReserveTransfer,
+
+    /// Limited teleport - like teleport but with weight limits
+    LimitedTeleport,
+}
+
+/// Multi-location representation for XCM addressing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiLocation {
+    /// Number of parent levels to traverse
+    pub parents: u8,
+    /// Interior junctions (e.g., parachain ID, account ID)
+    pub interior: Vec<Junction>,
+}
+
+impl MultiLocation {
+    /// Create a MultiLocation pointing to the parent (relay chain)
+    pub fn parent() -> Self {
+        Self {
+            parents: 1,
+            interior: vec![],
+        }
+    }
+
+    /// Create a MultiLocation for a specific parachain
+    pub fn parachain(para_id: u32) -> Self {
+        Self {
+            parents: 1,
+            interior: vec![Junction::Parachain(para_id)],
+        }
+    }
+
+    /// Create a MultiLocation for an account on the current chain
+    pub fn account(account_id: [u8; 32]) -> Self {
+        Self {
+            parents: 0,
+            interior: vec![Junction::AccountId32 {
+                network: None,
+                id: account_id,
+            }],
+        }
+    }
+
+    /// Create a MultiLocation for an account on a specific parachain
+    pub fn parachain_account(para_id: u32, account_id: [u8; 32]) -> Self {
+        Self {
+            parents: 1,
+            interior: vec![
+                Junction::Parachain(para_id),
+                Junction::AccountId32 {
+                    network: None,
+                    id: account_id,
+                },
+            ],
+        }
+    }
+
+    /// Create a MultiLocation from raw parts
+    pub fn new(parents: u8, interior: Vec<Junction>) -> Self {
+        Self { parents, interior }
+    }
+
+    /// Check if this location points to the parent chain
+    pub fn is_parent(&self) -> bool {
+        self.parents == 1 && self.interior.is_empty()
+    }
+
+    /// Check if this location points to a parachain
+    pub fn is_parachain(&self) -> bool {
+        matches!(self.interior.first(), Some(Junction::Parachain(_)))
+    }
+
+    /// Get the parachain ID if this location points to a parachain
+    pub fn parachain_id(&self) -> Option<u32> {
+        self.interior.first().and_then(|j| {
+            if let Junction::Parachain(id) = j {
+                Some(*id)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Interior junction types for multi-location
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Junction {
+    /// Parachain junction with parachain ID
+    Parachain(u32),
+
+    /// AccountId32 junction
+    AccountId32 {
+        network: Option<NetworkId>,
+        id: [u8; 32],
+    },
+
+    /// AccountId20 junction (for EVM accounts)
+    AccountId20 {
+        network: Option<NetworkId>,
+        key: [u8; 20],
+    },
+
+    /// General index junction
+    GeneralIndex(u128),
+
+    /// General key junction
+    GeneralKey { data: Vec<u8> },
+
+    /// Pallet instance junction
+    PalletInstance(u8),
+}
+
+/// Network identifier for cross-consensus messaging
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkId {
+    /// Polkadot relay chain
+    Polkadot,
+    /// Kusama relay chain
+    Kusama,
+    /// Westend test network
+    Westend,
+    /// Rococo test network
+    Rococo,
+    /// Generic network by ID
+    ByGenesis([u8; 32]),
+    /// Fork of a network, identified by the first block after the fork
+    ByFork {
+        block_number: u64,
+        block_hash: [u8; 32],
+    },
+    /// Ethereum-compatible network by EIP-155 chain ID
+    Ethereum { chain_id: u64 },
+}
+
+/// XCM asset representation
+#[derive(Debug, Clone)]
+pub struct XcmAsset {
+    /// Asset identifier (multi-location)
+    pub id: AssetId,
+    /// Fungibility (amount or instance)
+    pub fun: Fungibility,
+}
+
+/// Asset identifier for XCM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetId {
+    /// Concrete asset identified by multi-location
+    Concrete(MultiLocation),
+    /// Abstract asset identified by bytes
+    Abstract(Vec<u8>),
+}
+
+/// Fungibility of an asset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fungibility {
+    /// Fungible asset with amount
+    Fungible(u128),
+    /// Non-fungible asset with instance ID
+    NonFungible(u128),
+}
+
+impl XcmAsset {
+    /// Create a fungible asset (e.g., tokens)
+    pub fn fungible(id: AssetId, amount: u128) -> Self {
+        Self {
+            id,
+            fun: Fungibility::Fungible(amount),
+        }
+    }
+
+    /// Create a non-fungible asset (e.g., NFT)
+    pub fn non_fungible(id: AssetId, instance: u128) -> Self {
+        Self {
+            id,
+            fun: Fungibility::NonFungible(instance),
+        }
+    }
+
+    /// Create a native token asset for the current chain
+    pub fn native(amount: u128) -> Self {
+        Self::fungible(AssetId::Concrete(MultiLocation::new(0, vec![])), amount)
+    }
+}
+
+/// Weight limit for XCM execution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeightLimit {
+    /// Unlimited weight (dangerous, use with caution)
+    Unlimited,
+    /// Limited to specific weight units
+    Limited(u64),
+}
+
+impl Default for WeightLimit {
+    fn default() -> Self {
+        // Default to a conservative weight limit (5 billion units)
+        Self::Limited(5_000_000_000)
+    }
+}
+
+/// Configuration for XCM transfers
+#[derive(Debug, Clone)]
+pub struct XcmConfig {
+    /// XCM version to use, or the fallback when negotiation finds nothing
+    pub version: XcmVersion,
+    /// Look up the destination's version in `XcmPallet::SupportedVersion`
+    pub negotiate_version: bool,
+    /// Weight limit for execution
+    pub weight_limit: WeightLimit,
+    /// Fee asset to use (defaults to the first transferred asset)
+    ///
+    /// Selects `fee_asset_item` of transfers and the asset destination
+    /// execution fees are quoted in; only its `id` is used.
+    pub fee_asset: Option<XcmAsset>,
+}
+
+impl Default for XcmConfig {
+    fn default() -> Self {
+        Self {
+            version: XcmVersion::default(),
+            negotiate_version: true,
+            weight_limit: WeightLimit::default(),
+            fee_asset: None,
+        }
+    }
+}
+
+/// Two-dimensional weight
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Weight {
+    /// Computation time in picoseconds
+    pub ref_time: u64,
+    /// Proof size in bytes
+    pub proof_size: u64,
+}
+
+impl Weight {
+    /// Create a new weight
+    pub fn new(ref_time: u64, proof_size: u64) -> Self {
+        Self {
+            ref_time,
+            proof_size,
+        }
+    }
+}
+
+/// Origin a `Transact` call is dispatched with on the destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OriginKind {
+    /// Native origin of the message's origin location
+    Native,
+    /// Signed origin of the origin's sovereign account
+    SovereignAccount,
+    /// Root origin
+    Superuser,
+    /// XCM pallet origin carrying the origin location
+    Xcm,
+}
+
+/// Wildcard asset selection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WildAsset {
+    /// All assets in the holding register
+    All,
+    /// All assets, up to the given number of distinct assets
+    AllCounted(u32),
+}
+
+/// Selection of assets in the holding register
+#[derive(Debug, Clone)]
+pub enum AssetFilter {
+    /// Exactly these assets
+    Definite(Vec<XcmAsset>),
+    /// Wildcard selection
+    Wild(WildAsset),
+}
+
+impl AssetFilter {
+    /// Select everything in the holding register
+    pub fn all() -> Self {
+        Self::Wild(WildAsset::All)
+    }
+}
+
+/// Typed XCM instruction
+#[derive(Debug, Clone)]
+pub enum XcmInstruction {
+    /// Move assets from the origin's account into the holding register
+    WithdrawAsset(Vec<XcmAsset>),
+    /// Pay for execution with assets from the holding register
+    BuyExecution {
+        fees: XcmAsset,
+        weight_limit: WeightLimit,
+    },
+    /// Deposit assets from the holding register to a beneficiary
+    DepositAsset {
+        assets: AssetFilter,
+        beneficiary: MultiLocation,
+    },
+    /// Dispatch an encoded runtime call
+    ///
+    /// `require_weight_at_most` is mandatory before XCM v5 and is sent as
+    /// `fallback_max_weight` from v5 on.
+    Transact {
+        origin_kind: OriginKind,
+        require_weight_at_most: Option<Weight>,
+        call: Vec<u8>,
+    },
+    /// Burn assets and send the reserve an instruction to release them
+    InitiateReserveWithdraw {
+        assets: AssetFilter,
+        reserve: MultiLocation,
+        xcm: XcmProgram,
+    },
+    /// Deposit assets to a destination's sovereign account and notify it
+    DepositReserveAsset {
+        assets: AssetFilter,
+        dest: MultiLocation,
+        xcm: XcmProgram,
+    },
+    /// Instructions to run after the program, even on error
+    SetAppendix(XcmProgram),
+    /// Refund unused execution fees into the holding register
+    RefundSurplus,
+}
+
+/// Ordered list of XCM instructions
+///
+/// ```rust,ignore
+/// let program = XcmProgram::new()
+///     .withdraw_asset(vec![fees.clone()])
+///     .buy_execution(fees, WeightLimit::Unlimited)
+///     .transact(OriginKind::SovereignAccount, Some(Weight::new(1_000_000_000, 65_536)), call)
+///     .refund_surplus()
+///     .deposit_asset(AssetFilter::all(), refund_to);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct XcmProgram {
+    instructions: Vec<XcmInstruction>,
+}
+
+impl XcmProgram {
+    /// Create an empty program
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an instruction
+    pub fn push(mut self, instruction: XcmInstruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    /// Append `WithdrawAsset`
+    pub fn withdraw_asset(self, assets: Vec<XcmAsset>) -> Self {
+        self.push(XcmInstruction::WithdrawAsset(assets))
+    }
+
+    /// Append `BuyExecution`
+    pub fn buy_execution(self, fees: XcmAsset, weight_limit: WeightLimit) -> Self {
+        self.push(XcmInstruction::BuyExecution { fees, weight_limit })
+    }
+
+    /// Append `DepositAsset`
+    pub fn deposit_asset(self, assets: AssetFilter, beneficiary: MultiLocation) -> Self {
+        self.push(XcmInstruction::DepositAsset {
+            assets,
+            beneficiary,
+        })
+    }
+
+    /// Append `Transact` with a call encoded for the destination runtime
+    pub fn transact(
+        self,
+        origin_kind: OriginKind,
+        require_weight_at_most: Option<Weight>,
+        call: Vec<u8>,
+    ) -> Self {
+        self.push(XcmInstruction::Transact {
+            origin_kind,
+            require_weight_at_most,
+            call,
+        })
+    }
+
+    /// Append `InitiateReserveWithdraw`
+    pub fn initiate_reserve_withdraw(
+        self,
+        assets: AssetFilter,
+        reserve: MultiLocation,
+        xcm: XcmProgram,
+    ) -> Self {
+        self.push(XcmInstruction::InitiateReserveWithdraw {
+            assets,
+            reserve,
+            xcm,
+        })
+    }
+
+    /// Append `DepositReserveAsset`
+    pub fn deposit_reserve_asset(
+        self,
+        assets: AssetFilter,
+        dest: MultiLocation,
+        xcm: XcmProgram,
+    ) -> Self {
+        self.push(XcmInstruction::DepositReserveAsset { assets, dest, xcm })
+    }
+
+    /// Append `SetAppendix`
+    pub fn set_appendix(self, appendix: XcmProgram) -> Self {
+        self.push(XcmInstruction::SetAppendix(appendix))
+    }
+
+    /// Append `RefundSurplus`
+    pub fn refund_surplus(self) -> Self {
+        self.push(XcmInstruction::RefundSurplus)
+    }
+
+    /// Instructions in order
+    pub fn instructions(&self) -> &[XcmInstruction] {
+        &self.instructions
+    }
+
+    /// Number of top-level instructions
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /// Check if the program has no instructions
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// Encode as `VersionedXcm` for the given XCM version
+    pub fn encode(&self, version: XcmVersion) -> Result<Value> {
+        ensure_supported(version)?;
+        Ok(Value::unnamed_variant(
+            version.variant_name(),
+            vec![encode_xcm(self, version)?],
+        ))
+    }
+}
+
+/// XCM pallet call, usable as an extrinsic or as a `RuntimeCall` value
+#[derive(Debug, Clone)]
+struct XcmCall {
+    pallet: &'static str,
+    name: &'static str,
+    fields: Vec<Value>,
+}
+
+impl XcmCall {
+    fn tx(&self) -> subxt::tx::DynamicPayload {
+        subxt::dynamic::tx(self.pallet, self.name, self.fields.clone())
+    }
+
+    fn runtime_call(&self) -> Value {
+        Value::unnamed_variant(
+            self.pallet,
+            vec![Value::unnamed_variant(self.name, self.fields.clone())],
+        )
+    }
+}
+
+/// XCM executor for sending cross-chain messages
+pub struct XcmExecutor {
+    client: OnlineClient<PolkadotConfig>,
+    destination: Option<OnlineClient<PolkadotConfig>>,
+    config: XcmConfig,
+}
+
+impl XcmExecutor {
+    /// Create a new XCM executor
+    pub fn new(client: OnlineClient<PolkadotConfig>) -> Self {
+        Self::with_config(client, XcmConfig::default())
+    }
+
+    /// Create a new XCM executor with custom configuration
+    pub fn with_config(client: OnlineClient<PolkadotConfig>, config: XcmConfig) -> Self {
+        Self {
+            client,
+            destination: None,
+            config,
+        }
+    }
+
+    /// Connect to the destination chain for execution fee estimates
+    ///
+    /// Without it only the local dry run and delivery fees are checked, and
+    /// transfers buy execution with the configured weight limit.
+    pub fn with_destination_client(mut self, client: OnlineClient<PolkadotConfig>) -> Self {
+        self.destination = Some(client);
+        self
+    }
+
+    /// Pin the XCM version, disabling version negotiation
+    pub fn with_version(mut self, version: XcmVersion) -> Self {
+        self.config.version = version;
+        self.config.negotiate_version = false;
+        self
+    }
+
+    /// Enable or disable discovery of the destination's XCM version
+    pub fn with_version_negotiation(mut self, enabled: bool) -> Self {
+        self.config.negotiate_version = enabled;
+        self
+    }
+
+    /// Set the weight limit
+    ///
+    /// Transfers use the destination weight estimated by their dry run when
+    /// a destination client is set, and this limit otherwise.
+    pub fn with_weight_limit(mut self, limit: WeightLimit) -> Self {
+        self.config.weight_limit = limit;
+        self
+    }
+
+    /// Execute a reserve transfer to another chain
+    ///
+    /// The transfer is dry-run first and refused if its estimated fees
+    /// exceed the amount of the fee asset.
+    ///
+    /// # Arguments
+    ///
+    /// * `wallet` - Wallet to sign the transaction
+    /// * `dest` - Destination multi-location (parachain or relay chain)
+    /// * `beneficiary` - Beneficiary account on the destination chain
+    /// * `assets` - Assets to transfer
+    ///
+    /// # Returns
+    ///
+    /// Transaction hash of the XCM transfer extrinsic
+    pub async fn reserve_transfer(
+        &self,
+        wallet: &Wallet,
+        dest: MultiLocation,
+        beneficiary: [u8; 32],
+        assets: Vec<XcmAsset>,
+    ) -> Result<String> {
+        info!("Executing reserve transfer to {:?} for beneficiary", dest);
+
+        self.submit_transfer(
+            wallet,
+            XcmTransferType::LimitedReserveTransfer,
+            dest,
+            beneficiary,
+            assets,
+        )
+        .await
+        .map(|submission| submission.tx_hash)
+    }
+
+    /// Execute a teleport transfer to another chain
+    ///
+    /// The transfer is dry-run first and refused if its estimated fees
+    /// exceed the amount of the fee asset.
+    ///
+    /// # Arguments
+    ///
+    /// * `wallet` - Wallet to sign the transaction
+    /// * `dest` - Destination multi-location
+    /// * `beneficiary` - Beneficiary account on the destination chain
+    /// * `assets` - Assets to transfer
+    ///
+    /// # Returns
+    ///
+    /// Transaction hash of the XCM transfer extrinsic
+    pub async fn teleport(
+        &self,
+        wallet: &Wallet,
+        dest: MultiLocation,
+        beneficiary: [u8; 32],
+        assets: Vec<XcmAsset>,
+    ) -> Result<String> {
+        info!("Executing teleport to {:?} for beneficiary", dest);
+
+        self.submit_transfer(
+            wallet,
+            XcmTransferType::LimitedTeleport,
+            dest,
+            beneficiary,
+            assets,
+        )
+        .await
+        .map(|submission| submission.tx_hash)
+    }
+
+    /// Transfer to relay chain (convenience method)
+    ///
+    /// Automatically uses reserve transfer to parent chain
+    pub async fn transfer_to_relay(
+        &self,
+        wallet: &Wallet,
+        beneficiary: [u8; 32],
+        amount: u128,
+    ) -> Result<String> {
+        debug!("Transferring {} to relay chain", amount);
+
+        self.reserve_transfer(
+            wallet,
+            MultiLocation::parent(),
+            beneficiary,
+            vec![XcmAsset::native(amount)],
+        )
+        .await
+    }
+
+    /// Transfer to another parachain
+    ///
+    /// Automatically uses reserve transfer via relay chain
+    pub async fn transfer_to_parachain(
+        &self,
+        wallet: &Wallet,
+        para_id: u32,
+        beneficiary: [u8; 32],
+        amount: u128,
+    ) -> Result<String> {
+        debug!("Transferring {} to parachain {}", amount, para_id);
+
+        self.reserve_transfer(
+            wallet,
+            MultiLocation::parachain(para_id),
+            beneficiary,
+            vec![XcmAsset::native(amount)],
+        )
+        .await
+    }
+
+    /// Execute an XCM program locally with `execute`
+    ///
+    /// The program is encoded for the highest XCM version of the local
+    /// runtime and may use at most the configured weight limit.
+    pub async fn execute(&self, wallet: &Wallet, program: &XcmProgram) -> Result<String> {
+        let WeightLimit::Limited(ref_time) = self.config.weight_limit else {
+            return Err(Error::Transaction(
+                "execute requires a limited weight".to_string(),
+            ));
+        };
+        let version = self.local_versions().last().copied().ok_or_else(|| {
+            Error::Metadata("No XCM versions found in runtime metadata".to_string())
+        })?;
+        info!("Executing XCM program with {} instructions", program.len());
+
+        let call = subxt::dynamic::tx(
+            self.pallet_name()?,
+            "execute",
+            vec![
+                program.encode(version)?,
+                encode_weight(Weight::new(ref_time, MAX_PROOF_SIZE)),
+            ],
+        );
+
+        self.submit_xcm_call(&call, wallet)
+            .await
+            .map(|submission| submission.tx_hash)
+    }
+
+    /// Send an XCM program to `dest` with `send`
+    ///
+    /// The program runs on the destination with the sender's location as
+    /// origin, so remote `Transact` calls are dispatched from its sovereign
+    /// account unless the destination grants otherwise.
+    pub async fn send(
+        &self,
+        wallet: &Wallet,
+        dest: MultiLocation,
+        program: &XcmProgram,
+    ) -> Result<String> {
+        self.submit_send(wallet, dest, program)
+            .await
+            .map(|submission| submission.tx_hash)
+    }
+
+    /// Send an XCM program and return the message IDs for [`XcmTracker`]
+    pub async fn submit_send(
+        &self,
+        wallet: &Wallet,
+        dest: MultiLocation,
+        program: &XcmProgram,
+    ) -> Result<XcmSubmission> {
+        info!(
+            "Sending XCM program with {} instructions to {:?}",
+            program.len(),
+            dest
+        );
+
+        let version = self.resolve_version(&dest).await?;
+        let call = subxt::dynamic::tx(
+            self.pallet_name()?,
+            "send",
+            vec![
+                encode_versioned_location(&dest, version)?,
+                program.encode(version)?,
+            ],
+        );
+
+        self.submit_xcm_call(&call, wallet).await
+    }
+
+    /// Submit a transfer and return the message IDs for [`XcmTracker`]
+    ///
+    /// The transfer is dry-run first and refused if its estimated fees
+    /// exceed the amount of the fee asset.
+    pub async fn submit_transfer(
+        &self,
+        wallet: &Wallet,
+        transfer_type: XcmTransferType,
+        dest: MultiLocation,
+        beneficiary: [u8; 32],
+        assets: Vec<XcmAsset>,
+    ) -> Result<XcmSubmission> {
+        let fee_asset = assets.get(self.fee_asset_index(&assets));
+        let mut call = self
+            .transfer_call(
+                transfer_type,
+                &dest,
+                beneficiary,
+                &assets,
+                self.config.weight_limit,
+            )
+            .await?;
+
+        if self.supports_dry_run() {
+            let origin = account_bytes(wallet)?;
+            let estimate = self
+                .estimate_call(origin, &dest, &call, fee_asset.map(|a| &a.id))
+                .await?;
+
+            match fee_asset.map(|a| &a.fun) {
+                Some(Fungibility::Fungible(amount)) => {
+                    if estimate.destination_fee.is_none() {
+                        warn!("No destination fee estimate, checking only delivery fees against the transfer amount");
+                    }
+                    if estimate.total_fee() >= *amount {
+                        return Err(Error::Transaction(format!(
+                            "Estimated XCM fees {} exceed transfer amount {}",
+                            estimate.total_fee(),
+                            amount
+                        )));
+                    }
+                }
+                _ => warn!("XCM fee asset is not fungible, skipping the fee check"),
+            }
+
+            // Buy exactly the execution the destination needs
+            if let Some(weight) = estimate.destination_weight {
+                call = self
+                    .transfer_call(
+                        transfer_type,
+                        &dest,
+                        beneficiary,
+                        &assets,
+                        WeightLimit::Limited(weight.ref_time),
+                    )
+                    .await?;
+            }
+        } else {
+            warn!("Runtime has no DryRunApi, submitting XCM transfer without fee check");
+        }
+
+        self.submit_xcm_call(&call.tx(), wallet).await
+    }
+
+    async fn transfer_call(
+        &self,
+        transfer_type: XcmTransferType,
+        dest: &MultiLocation,
+        beneficiary: [u8; 32],
+        assets: &[XcmAsset],
+        weight_limit: WeightLimit,
+    ) -> Result<XcmCall> {
+        let call_name = match transfer_type {
+            XcmTransferType::ReserveTransfer | XcmTransferType::LimitedReserveTransfer => {
+                "limited_reserve_transfer_assets"
+            }
+            XcmTransferType::Teleport | XcmTransferType::LimitedTeleport => {
+                "limited_teleport_assets"
+            }
+        };
+
+        let version = self.resolve_version(dest).await?;
+        Ok(XcmCall {
+            pallet: self.pallet_name()?,
+            name: call_name,
+            fields: vec![
+                encode_versioned_location(dest, version)?,
+                encode_versioned_location(&MultiLocation::account(beneficiary), version)?,
+                encode_versioned_assets(assets, version)?,
+                Value::u128(self.fee_asset_index(assets) as u128),
+                encode_weight_limit(weight_limit),
+            ],
+        })
+    }
+
+    /// Index of the configured fee asset in `assets`, defaulting to the first
+    fn fee_asset_index(&self, assets: &[XcmAsset]) -> usize {
+        self.config
+            .fee_asset
+            .as_ref()
+            .and_then(|fee| assets.iter().position(|a| a.id == fee.id))
+            .unwrap_or(0)
+    }
+
+    /// Name of the XCM pallet in the connected runtime
+    ///
+    /// Relay chains call it `XcmPallet`, parachains `PolkadotXcm`.
+    pub fn pallet_name(&self) -> Result<&'static str> {
+        let metadata = self.client.metadata();
+        ["XcmPallet", "PolkadotXcm"]
+            .into_iter()
+            .find(|name| metadata.pallet_by_name(name).is_some())
+            .ok_or_else(|| Error::Metadata("No XCM pallet in runtime metadata".to_string()))
+    }
+
+    /// XCM versions the connected runtime understands, lowest first
+    ///
+    /// Read from the `VersionedLocation` variants of the XCM pallet's
+    /// `limited_reserve_transfer_assets` call in the runtime metadata.
+    pub fn local_versions(&self) -> Vec<XcmVersion> {
+        let Ok(pallet) = self.pallet_name() else {
+            return Vec::new();
+        };
+        let metadata = self.client.metadata();
+        metadata
+            .pallet_by_name(pallet)
+            .and_then(|pallet| pallet.call_variant_by_name("limited_reserve_transfer_assets"))
+            .and_then(|call| call.fields.first())
+            .map(|dest| versions_in_type(metadata.types(), dest.ty.id))
+            .unwrap_or_default()
+    }
+
+    /// Discover the XCM version to use for messages to `dest`
+    ///
+    /// Uses the version the destination advertised in the XCM pallet's
+    /// `SupportedVersion` storage, then `SafeXcmVersion`, then
+    /// the configured version, capped at the highest version the local
+    /// runtime supports.
+    pub async fn negotiate_version(&self, dest: &MultiLocation) -> Result<XcmVersion> {
+        let pallet = self.pallet_name()?;
+        let local = self.local_versions();
+        let Some(&local_max) = local.last() else {
+            return Err(Error::Metadata(format!(
+                "{}::limited_reserve_transfer_assets not found in runtime metadata",
+                pallet
+            )));
+        };
+
+        let storage = self
+            .client
+            .storage()
+            .at_latest()
+            .await
+            .map_err(|e| Error::Connection(format!("Failed to fetch latest block: {}", e)))?;
+
+        // The pallet keys entries by its own current version and the
+        // destination encoded at that version
+        let supported = subxt::dynamic::storage(
+            pallet,
+            "SupportedVersion",
+            vec![
+                Value::u128(local_max.as_u32() as u128),
+                encode_versioned_location(dest, local_max)?,
+            ],
+        );
+        let safe = subxt::dynamic::storage(pallet, "SafeXcmVersion", Vec::<Value>::new());
+
+        let mut remote = None;
+        for query in [supported, safe] {
+            let found = storage
+                .fetch(&query)
+                .await
+                .map_err(|e| Error::Storage(format!("Failed to query XCM version: {}", e)))?;
+            if let Some(thunk) = found {
+                remote = thunk
+                    .to_value()
+                    .map_err(|e| Error::Storage(format!("Failed to decode XCM version: {}", e)))?
+                    .as_u128();
+                break;
+            }
+        }
+
+        let wanted = match remote {
+            Some(version) => {
+                debug!("Destination {:?} supports XCM v{}", dest, version);
+                if version < 3 {
+                    return Err(Error::Transaction(format!(
+                        "Destination only supports XCM v{}",
+                        version
+                    )));
+                }
+                XcmVersion::from_u32(version as u32).unwrap_or(local_max)
+            }
+            None => {
+                warn!(
+                    "No XCM version known for {:?}, using v{}",
+                    dest,
+                    self.config.version.as_u32()
+                );
+                self.config.version
+            }
+        };
+
+        select_version(&local, wanted).ok_or_else(|| {
+            Error::Transaction(format!(
+                "Runtime supports none of XCM v3..=v{}",
+                wanted.as_u32()
+            ))
+        })
+    }
+
+    async fn resolve_version(&self, dest: &MultiLocation) -> Result<XcmVersion> {
+        if self.config.negotiate_version {
+            self.negotiate_version(dest).await
+        } else {
+            Ok(self.config.version)
+        }
+    }
+
+    async fn submit_xcm_call<Call>(&self, call: &Call, wallet: &Wallet) -> Result<XcmSubmission>
+    where
+        Call: subxt::tx::Payload,
+    {
+        debug!("Submitting XCM extrinsic");
+
+        let pair = wallet
+            .sr25519_pair()
+            .ok_or_else(|| Error::Transaction("Wallet does not have SR25519 key".to_string()))?;
+
+        let signer = Sr25519Signer::new(pair.clone());
+
+        let mut progress = self
+            .client
+            .tx()
+            .sign_and_submit_then_watch_default(call, &signer)
+            .await
+            .map_err(|e| Error::Transaction(format!("Failed to submit XCM transaction: {}", e)))?;
+
+        // Wait for finalization
+        while let Some(event) = progress.next().await {
+            let event =
+                event.map_err(|e| Error::Transaction(format!("XCM transaction error: {}", e)))?;
+
+            if event.as_in_block().is_some() {
+                info!("XCM transaction included in block");
+            }
+
+            if let Some(finalized) = event.as_finalized() {
+                let tx_hash = format!("0x{}", hex::encode(finalized.extrinsic_hash()));
+                info!("XCM transaction finalized: {}", tx_hash);
+
+                let events = finalized
+                    .wait_for_success()
+                    .await
+                    .map_err(|e| Error::Transaction(format!("XCM transaction failed: {}", e)))?;
+
+                let mut message_ids = Vec::new();
+                for event in events.iter() {
+                    let event = event.map_err(|e| {
+                        Error::Transaction(format!("Failed to decode XCM event: {}", e))
+                    })?;
+                    if let Ok(fields) = event.field_values() {
+                        if let Some(id) =
+                            sent_message_id(event.pallet_name(), event.variant_name(), &fields)
+                        {
+                            message_ids.push(id);
+                        }
+                    }
+                }
+                debug!("XCM transaction sent {} messages", message_ids.len());
+
+                return Ok(XcmSubmission {
+                    tx_hash,
+                    block_hash: format!("0x{}", hex::encode(finalized.block_hash())),
+                    message_ids,
+                });
+            }
+        }
+
+        Err(Error::Transaction(
+            "XCM transaction stream ended without finalization".to_string(),
+        ))
+    }
+}
+
+/// Highest version in `local` that does not exceed `wanted`, from v3 on
+fn select_version(local: &[XcmVersion], wanted: XcmVersion) -> Option<XcmVersion> {
+    local
+        .iter()
+        .copied()
+        .filter(|v| *v >= XcmVersion::V3 && *v <= wanted)
+        .max()
+}
+
+/// XCM versions listed as variants of a `Versioned*` type
+fn versions_in_type(types: &PortableRegistry, type_id: u32) -> Vec<XcmVersion> {
+    let mut versions: Vec<XcmVersion> = match types.resolve(type_id).map(|ty| &ty.type_def) {
+        Some(TypeDef::Variant(def)) => def
+            .variants
+            .iter()
+            .filter_map(|v| XcmVersion::from_variant_name(&v.name))
+            .collect(),
+        _ => Vec::new(),
+    };
+    versions.sort();
+    versions
+}
+
+/// Decode a `VersionedLocation` value back into a [`MultiLocation`]
+///
+/// Returns `None` for versions or junctions this module does not model.
+pub fn decode_versioned_location<T>(value: &Value<T>) -> Option<MultiLocation> {
+    let ValueDef::Variant(versioned) = &value.value else {
+        return None;
+    };
+    XcmVersion::from_variant_name(&versioned.name).filter(|v| *v >= XcmVersion::V3)?;
+    decode_location(versioned.values.values().next()?)
+}
+
+/// Decode a v3 or later `Location` value
+fn decode_location<T>(location: &Value<T>) -> Option<MultiLocation> {
+    let parents = u8::try_from(location.at("parents")?.as_u128()?).ok()?;
+    let ValueDef::Variant(interior) = &location.at("interior")?.value else {
+        return None;
+    };
+    let mut fields: Vec<&Value<T>> = interior.values.values().collect();
+    // v4 and later hold the junctions in a single array field
+    if let [only] = fields[..] {
+        if let ValueDef::Composite(array) = &only.value {
+            fields = array.values().collect();
+        }
+    }
+    let interior = fields
+        .into_iter()
+        .map(decode_junction)
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(MultiLocation::new(parents, interior))
+}
+
+/// Decode an `AssetId` value of the given XCM version
+fn decode_asset_id<T>(value: &Value<T>, version: XcmVersion) -> Option<AssetId> {
+    if version == XcmVersion::V3 {
+        let ValueDef::Variant(id) = &value.value else {
+            return None;
+        };
+        let inner = id.values.values().next()?;
+        return match id.name.as_str() {
+            "Concrete" => decode_location(inner).map(AssetId::Concrete),
+            "Abstract" => decode_bytes(inner).map(AssetId::Abstract),
+            _ => None,
+        };
+    }
+
+    // From v4 on an asset ID is a location, possibly behind a newtype wrapper
+    let mut current = value;
+    while current.at("parents").is_none() {
+        let ValueDef::Composite(fields) = &current.value else {
+            return None;
+        };
+        let mut fields = fields.values();
+        match (fields.next(), fields.next()) {
+            (Some(inner), None) => current = inner,
+            _ => return None,
+        }
+    }
+    decode_location(current).map(AssetId::Concrete)
+}
+
+fn decode_junction<T>(value: &Value<T>) -> Option<Junction> {
+    let ValueDef::Variant(junction) = &value.value else {
+        return None;
+    };
+    let first = || junction.values.values().next();
+    Some(match junction.name.as_str() {
+        "Parachain" => Junction::Parachain(u32::try_from(first()?.as_u128()?).ok()?),
+        "AccountId32" => Junction::AccountId32 {
+            network: decode_optional_network(junction.at("network")?)?,
+            id: decode_bytes(junction.at("id")?)?.try_into().ok()?,
+        },
+        "AccountId20" => Junction::AccountId20 {
+            network: decode_optional_network(junction.at("network")?)?,
+            key: decode_bytes(junction.at("key")?)?.try_into().ok()?,
+        },
+        "GeneralIndex" => Junction::GeneralIndex(first()?.as_u128()?),
+        "GeneralKey" => {
+            let length = usize::try_from(junction.at("length")?.as_u128()?).ok()?;
+            let mut data = decode_bytes(junction.at("data")?)?;
+            data.truncate(length);
+            Junction::GeneralKey { data }
+        }
+        "PalletInstance" => Junction::PalletInstance(u8::try_from(first()?.as_u128()?).ok()?),
+        _ => return None,
+    })
+}
+
+/// Outer `Option` is a decoding failure, inner `Option` the junction's network
+fn decode_optional_network<T>(value: &Value<T>) -> Option<Option<NetworkId>> {
+    let ValueDef::Variant(option) = &value.value else {
+        return None;
+    };
+    if option.name == "None" {
+        return Some(None);
+    }
+    let ValueDef::Variant(network) = &option.values.values().next()?.value else {
+        return None;
+    };
+    Some(Some(match network.name.as_str() {
+        "Polkadot" => NetworkId::Polkadot,
+        "Kusama" => NetworkId::Kusama,
+        "Westend" => NetworkId::Westend,
+        "Rococo" => NetworkId::Rococo,
+        "ByGenesis" => {
+            let hash: [u8; 32] = decode_bytes(network.values.values().next()?)?
+                .try_into()
+                .ok()?;
+            match hash {
+                WESTEND_GENESIS => NetworkId::Westend,
+                ROCOCO_GENESIS => NetworkId::Rococo,
+                _ => NetworkId::ByGenesis(hash),
+            }
+        }
+        "ByFork" => NetworkId::ByFork {
+            block_number: u64::try_from(network.at("block_number")?.as_u128()?).ok()?,
+            block_hash: decode_bytes(network.at("block_hash")?)?.try_into().ok()?,
+        },
+        "Ethereum" => NetworkId::Ethereum {
+            chain_id: u64::try_from(network.at("chain_id")?.as_u128()?).ok()?,
+        },
+        _ => return None,
+    }))
+}
+
+fn decode_bytes<T>(value: &Value<T>) -> Option<Vec<u8>> {
+    let ValueDef::Composite(bytes) = &value.value else {
+        return None;
+    };
+    bytes
+        .values()
+        .map(|b| b.as_u128().and_then(|b| u8::try_from(b).ok()))
+        .collect()
+}
+
+fn account_bytes(wallet: &Wallet) -> Result<[u8; 32]> {
+    wallet
+        .public_key()
+        .try_into()
+        .map_err(|_| Error::Transaction("Wallet public key is not 32 bytes".to_string()))
+}
+
+fn ensure_supported(version: XcmVersion) -> Result<()> {
+    if version == XcmVersion::V2 {
+        return Err(Error::Transaction(
+            "XCM v2 is no longer supported, use v3 or later".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Encode a location as `VersionedLocation` for the given XCM version
+pub fn encode_versioned_location(location: &MultiLocation, version: XcmVersion) -> Result<Value> {
+    ensure_supported(version)?;
+    Ok(Value::unnamed_variant(
+        version.variant_name(),
+        vec![encode_location(location, version)?],
+    ))
+}
+
+/// Encode assets as `VersionedAssets` for the given XCM version
+pub fn encode_versioned_assets(assets: &[XcmAsset], version: XcmVersion) -> Result<Value> {
+    ensure_supported(version)?;
+    Ok(Value::unnamed_variant(
+        version.variant_name(),
+        vec![encode_assets(assets, version)?],
+    ))
+}
+
+/// Encode a network identifier for the given XCM version
+///
+/// XCM v5 dropped the Westend and Rococo variants; they are encoded by
+/// genesis hash instead.
+pub fn encode_network_id(network: &NetworkId, version: XcmVersion) -> Result<Value> {
+    ensure_supported(version)?;
+    let by_genesis =
+        |hash: &[u8; 32]| Value::unnamed_variant("ByGenesis", vec![Value::from_bytes(hash)]);
+    Ok(match network {
+        NetworkId::Polkadot => Value::unnamed_variant("Polkadot", vec![]),
+        NetworkId::Kusama => Value::unnamed_variant("Kusama", vec![]),
+        NetworkId::Westend if version >= XcmVersion::V5 => by_genesis(&WESTEND_GENESIS),
+        NetworkId::Westend => Value::unnamed_variant("Westend", vec![]),
+        NetworkId::Rococo if version >= XcmVersion::V5 => by_genesis(&ROCOCO_GENESIS),
+        NetworkId::Rococo => Value::unnamed_variant("Rococo", vec![]),
+        NetworkId::ByGenesis(hash) => by_genesis(hash),
+        NetworkId::ByFork {
+            block_number,
+            block_hash,
+        } => Value::named_variant(
+            "ByFork",
+            [
+                ("block_number", Value::u128(*block_number as u128)),
+                ("block_hash", Value::from_bytes(block_hash)),
+            ],
+        ),
+        NetworkId::Ethereum { chain_id } => {
+            Value::named_variant("Ethereum", [("chain_id", Value::u128(*chain_id as u128))])
+        }
+    })
+}
+
+fn encode_location(location: &MultiLocation, version: XcmVersion) -> Result<Value> {
+    Ok(Value::named_composite([
+        ("parents", Value::u128(location.parents as u128)),
+        ("interior", encode_junctions(&location.interior, version)?),
+    ]))
+}
+
+fn encode_junctions(junctions: &[Junction], version: XcmVersion) -> Result<Value> {
+    if junctions.is_empty() {
+        return Ok(Value::unnamed_variant("Here", vec![]));
+    }
+    if junctions.len() > 8 {
+        return Err(Error::Transaction("Too many junctions (max 8)".to_string()));
+    }
+
+    let encoded = junctions
+        .iter()
+        .map(|j| encode_junction(j, version))
+        .collect::<Result<Vec<_>>>()?;
+    let variant_name = format!("X{}", junctions.len());
+
+    // v3 uses tuple variants, v4 and later wrap the junctions in an array
+    let fields = if version >= XcmVersion::V4 {
+        vec![Value::unnamed_composite(encoded)]
+    } else {
+        encoded
+    };
+    Ok(Value::unnamed_variant(variant_name, fields))
+}
+
+fn encode_junction(junction: &Junction, version: XcmVersion) -> Result<Value> {
+    let network_value = |network: &Option<NetworkId>| -> Result<Value> {
+        Ok(match network {
+            Some(net) => Value::unnamed_variant("Some", vec![encode_network_id(net, version)?]),
+            None => Value::unnamed_variant("None", vec![]),
+        })
+    };
+
+    Ok(match junction {
+        Junction::Parachain(id) => {
+            Value::unnamed_variant("Parachain", vec![Value::u128(*id as u128)])
+        }
+        Junction::AccountId32 { network, id } => Value::named_variant(
+            "AccountId32",
+            [
+                ("network", network_value(network)?),
+                ("id", Value::from_bytes(id)),
+            ],
+        ),
+        Junction::AccountId20 { network, key } => Value::named_variant(
+            "AccountId20",
+            [
+                ("network", network_value(network)?),
+                ("key", Value::from_bytes(key)),
+            ],
+        ),
+        Junction::GeneralIndex(index) => {
+            Value::unnamed_variant("GeneralIndex", vec![Value::u128(*index)])
+        }
+        Junction::GeneralKey { data } => {
+            if data.len() > 32 {
+                return Err(Error::Transaction(format!(
+                    "GeneralKey is {} bytes, max 32",
+                    data.len()
+                )));
+            }
+            let mut padded = [0u8; 32];
+            padded[..data.len()].copy_from_slice(data);
+            Value::named_variant(
+                "GeneralKey",
+                [
+                    ("length", Value::u128(data.len() as u128)),
+                    ("data", Value::from_bytes(padded)),
+                ],
+            )
+        }
+        Junction::PalletInstance(instance) => {
+            Value::unnamed_variant("PalletInstance", vec![Value::u128(*instance as u128)])
+        }
+    })
+}
+
+fn encode_asset_id(id: &AssetId, version: XcmVersion) -> Result<Value> {
+    Ok(match (id, version) {
+        (AssetId::Concrete(location), XcmVersion::V3) => {
+            Value::unnamed_variant("Concrete", vec![encode_location(location, version)?])
+        }
+        (AssetId::Abstract(data), XcmVersion::V3) => {
+            if data.len() > 32 {
+                return Err(Error::Transaction(format!(
+                    "Abstract asset ID is {} bytes, max 32",
+                    data.len()
+                )));
+            }
+            let mut padded = [0u8; 32];
+            padded[..data.len()].copy_from_slice(data);
+            Value::unnamed_variant("Abstract", vec![Value::from_bytes(padded)])
+        }
+        // From v4 on an asset ID is a plain location
+        (AssetId::Concrete(location), _) => {
+            Value::unnamed_composite(vec![encode_location(location, version)?])
+        }
+        (AssetId::Abstract(_), _) => {
+            return Err(Error::Transaction(format!(
+                "Abstract asset IDs are not supported in XCM v{}",
+                version.as_u32()
+            )))
+        }
+    })
+}
+
+fn encode_asset(asset: &XcmAsset, version: XcmVersion) -> Result<Value> {
+    let id_value = encode_asset_id(&asset.id, version)?;
+    let fun_value = match asset.fun {
+        Fungibility::Fungible(amount) => {
+            Value::unnamed_variant("Fungible", vec![Value::u128(amount)])
+        }
+        Fungibility::NonFungible(instance) => Value::unnamed_variant(
+            "NonFungible",
+            vec![Value::unnamed_variant("Index", vec![Value::u128(instance)])],
+        ),
+    };
+
+    Ok(Value::named_composite([
+        ("id", id_value),
+        ("fun", fun_value),
+    ]))
+}
+
+fn encode_weight(weight: Weight) -> Value {
+    Value::named_composite([
+        ("ref_time", Value::u128(weight.ref_time as u128)),
+        ("proof_size", Value::u128(weight.proof_size as u128)),
+    ])
+}
+
+fn encode_weight_limit(limit: WeightLimit) -> Value {
+    match limit {
+        WeightLimit::Unlimited => Value::unnamed_variant("Unlimited", vec![]),
+        WeightLimit::Limited(ref_time) => Value::unnamed_variant(
+            "Limited",
+            vec![encode_weight(Weight::new(ref_time, MAX_PROOF_SIZE))],
+        ),
+    }
+}
+
+fn encode_assets(assets: &[XcmAsset], version: XcmVersion) -> Result<Value> {
+    Ok(Value::unnamed_composite(
+        assets
+            .iter()
+            .map(|asset| encode_asset(asset, version))
+            .collect::<Result<Vec<_>>>()?,
+    ))
+}
+
+fn encode_asset_filter(filter: &AssetFilter, version: XcmVersion) -> Result<Value> {
+    Ok(match filter {
+        AssetFilter::Definite(assets) => {
+            Value::unnamed_variant("Definite", vec![encode_assets(assets, version)?])
+        }
+        AssetFilter::Wild(WildAsset::All) => {
+            Value::unnamed_variant("Wild", vec![Value::unnamed_variant("All", vec![])])
+        }
+        AssetFilter::Wild(WildAsset::AllCounted(count)) => Value::unnamed_variant(
+            "Wild",
+            vec![Value::unnamed_variant(
+                "AllCounted",
+                vec![Value::u128(*count as u128)],
+            )],
+        ),
+    })
+}
+
+fn encode_xcm(program: &XcmProgram, version: XcmVersion) -> Result<Value> {
+    Ok(Value::unnamed_composite(
+        program
+            .instructions
+            .iter()
+            .map(|instruction| encode_instruction(instruction, version))
+            .collect::<Result<Vec<_>>>()?,
+    ))
+}
+
+fn encode_origin_kind(origin_kind: OriginKind) -> Value {
+    let name = match origin_kind {
+        OriginKind::Native => "Native",
+        OriginKind::SovereignAccount => "SovereignAccount",
+        OriginKind::Superuser => "Superuser",
+        OriginKind::Xcm => "Xcm",
+    };
+    Value::unnamed_variant(name, vec![])
+}
+
+fn encode_instruction(instruction: &XcmInstruction, version: XcmVersion) -> Result<Value> {
+    Ok(match instruction {
+        XcmInstruction::WithdrawAsset(assets) => {
+            Value::unnamed_variant("WithdrawAsset", vec![encode_assets(assets, version)?])
+        }
+        XcmInstruction::BuyExecution { fees, weight_limit } => Value::named_variant(
+            "BuyExecution",
+            [
+                ("fees", encode_asset(fees, version)?),
+                ("weight_limit", encode_weight_limit(*weight_limit)),
+            ],
+        ),
+        XcmInstruction::DepositAsset {
+            assets,
+            beneficiary,
+        } => Value::named_variant(
+            "DepositAsset",
+            [
+                ("assets", encode_asset_filter(assets, version)?),
+                ("beneficiary", encode_location(beneficiary, version)?),
+            ],
+        ),
+        XcmInstruction::Transact {
+            origin_kind,
+            require_weight_at_most,
+            call,
+        } => {
+            let call_value = Value::named_composite([("encoded", Value::from_bytes(call))]);
+            if version >= XcmVersion::V5 {
+                let fallback = match require_weight_at_most {
+                    Some(weight) => Value::unnamed_variant("Some", vec![encode_weight(*weight)]),
+                    None => Value::unnamed_variant("None", vec![]),
+                };
+                Value::named_variant(
+                    "Transact",
+                    [
+                        ("origin_kind", encode_origin_kind(*origin_kind)),
+                        ("fallback_max_weight", fallback),
+                        ("call", call_value),
+                    ],
+                )
+            } else {
+                let weight = require_weight_at_most.ok_or_else(|| {
+                    Error::Transaction(format!(
+                        "Transact requires a weight in XCM v{}",
+                        version.as_u32()
+                    ))
+                })?;
+                Value::named_variant(
+                    "Transact",
+                    [
+                        ("origin_kind", encode_origin_kind(*origin_kind)),
+                        ("require_weight_at_most", encode_weight(weight)),
+                        ("call", call_value),
+                    ],
+                )
+            }
+        }
+        XcmInstruction::InitiateReserveWithdraw {
+            assets,
+            reserve,
+            xcm,
+        } => Value::named_variant(
+            "InitiateReserveWithdraw",
+            [
+                ("assets", encode_asset_filter(assets, version)?),
+                ("reserve", encode_location(reserve, version)?),
+                ("xcm", encode_xcm(xcm, version)?),
+            ],
+        ),
+        XcmInstruction::DepositReserveAsset { assets, dest, xcm } => Value::named_variant(
+            "DepositReserveAsset",
+            [
+                ("assets", encode_asset_filter(assets, version)?),
+                ("dest", encode_location(dest, version)?),
+                ("xcm", encode_xcm(xcm, version)?),
+            ],
+        ),
+        XcmInstruction::SetAppendix(appendix) => {
+            Value::unnamed_variant("SetAppendix", vec![encode_xcm(appendix, version)?])
+        }
+        XcmInstruction::RefundSurplus => Value::unnamed_variant("RefundSurplus", vec![]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use subxt::ext::scale_value::Composite;
+
+    #[test]
+    fn test_multilocation_parent() {
+        let location = MultiLocation::parent();
+        assert_eq!(location.parents, 1);
+        assert!(location.interior.is_empty());
+        assert!(location.is_parent());
+    }
+
+    #[test]
+    fn test_multilocation_parachain() {
+        let location = MultiLocation::parachain(2000);
+        assert_eq!(location.parents, 1);
+        assert_eq!(location.parachain_id(), Some(2000));
+        assert!(location.is_parachain());
+    }
+
+    #[test]
+    fn test_multilocation_account() {
+        let account = [1u8; 32];
+        let location = MultiLocation::account(account);
+        assert_eq!(location.parents, 0);
+        assert_eq!(location.interior.len(), 1);
+    }
+
+    #[test]
+    fn test_xcm_asset_native() {
+        let asset = XcmAsset::native(1000);
+        assert!(matches!(asset.fun, Fungibility::Fungible(1000)));
+    }
+
+    #[test]
+    fn test_weight_limit_default() {
+        let limit = WeightLimit::default();
+        assert!(matches!(limit, WeightLimit::Limited(5_000_000_000)));
+    }
+
+    #[test]
+    fn test_xcm_config_default() {
+        let config = XcmConfig::default();
+        assert_eq!(config.version, XcmVersion::V3);
+        assert!(matches!(config.weight_limit, WeightLimit::Limited(_)));
+    }
+
+    fn variant(value: &Value) -> (&str, Vec<&Value>) {
+        match &value.value {
+            ValueDef::Variant(v) => (v.name.as_str(), v.values.values().collect()),
+            other => panic!("expected variant, got {:?}", other),
+        }
+    }
+
+    fn field<'a>(value: &'a Value, name: &str) -> &'a Value {
+        match &value.value {
+            ValueDef::Composite(Composite::Named(fields)) => {
+                &fields.iter().find(|(n, _)| n == name).unwrap().1
+            }
+            other => panic!("expected named composite, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_xcm_version_numbers() {
+        for version in [
+            XcmVersion::V2,
+            XcmVersion::V3,
+            XcmVersion::V4,
+            XcmVersion::V5,
+        ] {
+            assert_eq!(XcmVersion::from_u32(version.as_u32()), Some(version));
+            assert_eq!(
+                XcmVersion::from_variant_name(version.variant_name()),
+                Some(version)
+            );
+        }
+        assert!(XcmVersion::V3 < XcmVersion::V5);
+    }
+
+    #[test]
+    fn test_versioned_location_junction_layout() {
+        let location = MultiLocation::parachain_account(1000, [7u8; 32]);
+
+        let v3 = encode_versioned_location(&location, XcmVersion::V3).unwrap();
+        let (name, inner) = variant(&v3);
+        assert_eq!(name, "V3");
+        let (junctions, fields) = variant(field(inner[0], "interior"));
+        assert_eq!(junctions, "X2");
+        assert_eq!(fields.len(), 2);
+
+        let v4 = encode_versioned_location(&location, XcmVersion::V4).unwrap();
+        let (name, inner) = variant(&v4);
+        assert_eq!(name, "V4");
+        let (junctions, fields) = variant(field(inner[0], "interior"));
+        assert_eq!(junctions, "X2");
+        // Single array field
+        assert_eq!(fields.len(), 1);
+
+        assert!(encode_versioned_location(&location, XcmVersion::V2).is_err());
+    }
+
+    #[test]
+    fn test_network_id_encoding() {
+        let junction = Junction::AccountId32 {
+            network: Some(NetworkId::Polkadot),
+            id: [1u8; 32],
+        };
+        let encoded = encode_junction(&junction, XcmVersion::V4).unwrap();
+        let (_, fields) = variant(&encoded);
+        let (option, network) = variant(fields[0]);
+        assert_eq!(option, "Some");
+        assert_eq!(variant(network[0]).0, "Polkadot");
+
+        let westend_v4 = encode_network_id(&NetworkId::Westend, XcmVersion::V4).unwrap();
+        assert_eq!(variant(&westend_v4).0, "Westend");
+        let westend_v5 = encode_network_id(&NetworkId::Westend, XcmVersion::V5).unwrap();
+        assert_eq!(variant(&westend_v5).0, "ByGenesis");
+
+        let ethereum = encode_network_id(&NetworkId::Ethereum { chain_id: 1 }, XcmVersion::V5);
+        assert_eq!(variant(&ethereum.unwrap()).0, "Ethereum");
+    }
+
+    #[test]
+    fn test_versioned_assets_id_layout() {
+        let assets = vec![XcmAsset::native(10)];
+
+        let v3 = encode_versioned_assets(&assets, XcmVersion::V3).unwrap();
+        let (_, inner) = variant(&v3);
+        let ValueDef::Composite(list) = &inner[0].value else {
+            panic!("expected asset list");
+        };
+        let asset = list.values().next().unwrap();
+        assert_eq!(variant(field(asset, "id")).0, "Concrete");
+
+        let v5 = encode_versioned_assets(&assets, XcmVersion::V5).unwrap();
+        assert_eq!(variant(&v5).0, "V5");
+
+        let abstract_asset = vec![XcmAsset::fungible(AssetId::Abstract(vec![1]), 1)];
+        assert!(encode_versioned_assets(&abstract_asset, XcmVersion::V3).is_ok());
+        assert!(encode_versioned_assets(&abstract_asset, XcmVersion::V4).is_err());
+    }
+
+    #[test]
+    fn test_general_key_is_padded() {
+        let key = Junction::GeneralKey { data: vec![1, 2] };
+        let encoded = encode_junction(&key, XcmVersion::V4).unwrap();
+        let ValueDef::Variant(v) = &encoded.value else {
+            panic!("expected variant");
+        };
+        let Composite::Named(fields) = &v.values else {
+            panic!("expected named fields");
+        };
+        assert_eq!(fields[0].1.as_u128(), Some(2));
+
+        let too_long = Junction::GeneralKey { data: vec![0; 33] };
+        assert!(encode_junction(&too_long, XcmVersion::V3).is_err());
+    }
+
+    #[test]
+    fn test_versions_from_metadata_type() {
+        #[allow(dead_code)]
+        #[derive(scale_info::TypeInfo)]
+        enum VersionedLocation {
+            V3(u8),
+            V4(u8),
+            V5(u8),
+        }
+
+        let mut registry = scale_info::Registry::new();
+        let id = registry
+            .register_type(&scale_info::meta_type::<VersionedLocation>())
+            .id;
+        let types: PortableRegistry = registry.into();
+
+        let versions = versions_in_type(&types, id);
+        assert_eq!(
+            versions,
+            vec![XcmVersion::V3, XcmVersion::V4, XcmVersion::V5]
+        );
+        assert_eq!(
+            select_version(&versions, XcmVersion::V4),
+            Some(XcmVersion::V4)
+        );
+        assert_eq!(
+            select_version(&versions[..1], XcmVersion::V5),
+            Some(XcmVersion::V3)
+        );
+        assert_eq!(select_version(&versions, XcmVersion::V2), None);
+    }
+
+    #[test]
+    fn test_xcm_config_negotiates_by_default() {
+        let config = XcmConfig::default();
+        assert!(config.negotiate_version);
+    }
+
+    fn program_for_remote_swap() -> XcmProgram {
+        let fees = XcmAsset::native(1_000_000_000);
+        XcmProgram::new()
+            .withdraw_asset(vec![fees.clone()])
+            .buy_execution(fees, WeightLimit::Unlimited)
+            .set_appendix(
+                XcmProgram::new()
+                    .refund_surplus()
+                    .deposit_asset(AssetFilter::all(), MultiLocation::account([3u8; 32])),
+            )
+            .transact(
+                OriginKind::SovereignAccount,
+                Some(Weight::new(1_000_000_000, 65_536)),
+                vec![0x0a, 0x00],
+            )
+    }
+
+    #[test]
+    fn test_xcm_program_builder_order() {
+        let program = program_for_remote_swap();
+        assert_eq!(program.len(), 4);
+        assert!(matches!(
+            program.instructions()[0],
+            XcmInstruction::WithdrawAsset(_)
+        ));
+        assert!(matches!(
+            program.instructions()[3],
+            XcmInstruction::Transact { .. }
+        ));
+        assert!(XcmProgram::new().is_empty());
+    }
+
+    #[test]
+    fn test_xcm_program_encoding() {
+        let encoded = program_for_remote_swap().encode(XcmVersion::V4).unwrap();
+        let (name, inner) = variant(&encoded);
+        assert_eq!(name, "V4");
+        let ValueDef::Composite(list) = &inner[0].value else {
+            panic!("expected instruction list");
+        };
+        let names: Vec<_> = list.values().map(|i| variant(i).0).collect();
+        assert_eq!(
+            names,
+            vec!["WithdrawAsset", "BuyExecution", "SetAppendix", "Transact"]
+        );
+    }
+
+    #[test]
+    fn test_transact_weight_field_per_version() {
+        let transact = XcmInstruction::Transact {
+            origin_kind: OriginKind::Xcm,
+            require_weight_at_most: None,
+            call: vec![1, 2, 3],
+        };
+        assert!(encode_instruction(&transact, XcmVersion::V4).is_err());
+
+        let v5 = encode_instruction(&transact, XcmVersion::V5).unwrap();
+        let ValueDef::Variant(v) = &v5.value else {
+            panic!("expected variant");
+        };
+        let Composite::Named(fields) = &v.values else {
+            panic!("expected named fields");
+        };
+        let names: Vec<_> = fields.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["origin_kind", "fallback_max_weight", "call"]);
+    }
+
+    #[test]
+    fn test_reserve_instructions_nest_programs() {
+        let inner = XcmProgram::new()
+            .buy_execution(XcmAsset::native(1), WeightLimit::Unlimited)
+            .deposit_asset(
+                AssetFilter::Wild(WildAsset::AllCounted(1)),
+                MultiLocation::account([1u8; 32]),
+            );
+        let program = XcmProgram::new()
+            .withdraw_asset(vec![XcmAsset::native(10)])
+            .initiate_reserve_withdraw(AssetFilter::all(), MultiLocation::parent(), inner.clone())
+            .deposit_reserve_asset(AssetFilter::all(), MultiLocation::parachain(2000), inner);
+
+        let encoded = encode_xcm(&program, XcmVersion::V3).unwrap();
+        let ValueDef::Composite(list) = &encoded.value else {
+            panic!("expected instruction list");
+        };
+        let reserve_withdraw = list.values().nth(1).unwrap();
+        let (name, fields) = variant(reserve_withdraw);
+        assert_eq!(name, "InitiateReserveWithdraw");
+        let ValueDef::Composite(nested) = &fields[2].value else {
+            panic!("expected nested program");
+        };
+        assert_eq!(nested.values().count(), 2);
+    }
+
+    #[test]
+    fn test_versioned_location_roundtrip() {
+        let location = MultiLocation::new(
+            1,
+            vec![
+                Junction::Parachain(1000),
+                Junction::PalletInstance(50),
+                Junction::GeneralIndex(1984),
+                Junction::AccountId32 {
+                    network: Some(NetworkId::Westend),
+                    id: [9u8; 32],
+                },
+            ],
+        );
+
+        for version in [XcmVersion::V3, XcmVersion::V4, XcmVersion::V5] {
+            let encoded = encode_versioned_location(&location, version).unwrap();
+            assert_eq!(decode_versioned_location(&encoded), Some(location.clone()));
+        }
+
+        let here = encode_versioned_location(&MultiLocation::parent(), XcmVersion::V4).unwrap();
+        assert_eq!(
+            decode_versioned_location(&here),
+            Some(MultiLocation::parent())
+        );
+    }
+}
@@ -0,0 +1,551 @@
+//! Cross-chain tracking of XCM transfers
+//!
+//! The origin emits the ID of every message it sends (`XcmPallet::Sent`,
+//! `XcmpQueue::XcmpMessageSent`, `ParachainSystem::UpwardMessageSent`). The
+//! destination reports the same ID in `MessageQueue::Processed` once the
+//! message has been executed, or in `MessageQueue::ProcessingFailed`.
+
+use crate::{Error, Result};
+use apex_sdk_types::{Chain, CrossChainTransaction, TransactionStatus, TxStatus};
+use subxt::blocks::Block;
+use subxt::events::Phase;
+use subxt::ext::scale_value::{At, Composite, Value, ValueDef};
+use subxt::{OnlineClient, PolkadotConfig};
+use tracing::{debug, info};
+
+/// XCM errors reported by destination executors
+const XCM_ERRORS: &[&str] = &[
+    "Overflow",
+    "Unimplemented",
+    "UntrustedReserveLocation",
+    "UntrustedTeleportLocation",
+    "LocationFull",
+    "LocationNotInvertible",
+    "BadOrigin",
+    "InvalidLocation",
+    "AssetNotFound",
+    "FailedToTransactAsset",
+    "NotWithdrawable",
+    "LocationCannotHold",
+    "ExceedsMaxMessageSize",
+    "DestinationUnsupported",
+    "Transport",
+    "Unroutable",
+    "UnknownClaim",
+    "FailedToDecode",
+    "MaxWeightInvalid",
+    "NotHoldingFees",
+    "TooExpensive",
+    "Trap",
+    "ExpectationFalse",
+    "PalletNotFound",
+    "NameMismatch",
+    "VersionIncompatible",
+    "HoldingWouldOverflow",
+    "ExportError",
+    "ReanchorFailed",
+    "NoDeal",
+    "FeesNotMet",
+    "LockError",
+    "NoPermission",
+    "Unanchored",
+    "NotDepositable",
+    "TooManyAssets",
+    "UnhandledXcmVersion",
+    "WeightLimitReached",
+    "Barrier",
+    "WeightNotComputable",
+    "ExceedsStackLimit",
+];
+
+/// Finalized XCM extrinsic and the IDs of the messages it sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XcmSubmission {
+    /// Origin extrinsic hash
+    pub tx_hash: String,
+    /// Origin block the extrinsic was finalized in
+    pub block_hash: String,
+    /// Message hashes and topic IDs from the origin events
+    pub message_ids: Vec<[u8; 32]>,
+}
+
+/// Result of a message on the destination
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Delivery {
+    Processed { id: [u8; 32], success: bool },
+    Failed { id: [u8; 32], error: String },
+}
+
+/// Follows XCM messages to their destination chain
+pub struct XcmTracker {
+    destination: OnlineClient<PolkadotConfig>,
+    source_chain: Chain,
+    destination_chain: Chain,
+    lookback_blocks: u32,
+    timeout_blocks: u32,
+}
+
+impl XcmTracker {
+    /// Create a tracker watching `destination`
+    pub fn new(
+        destination: OnlineClient<PolkadotConfig>,
+        source_chain: Chain,
+        destination_chain: Chain,
+    ) -> Self {
+        Self {
+            destination,
+            source_chain,
+            destination_chain,
+            lookback_blocks: 10,
+            timeout_blocks: 50,
+        }
+    }
+
+    /// Set how many already finalized blocks are searched first
+    pub fn with_lookback_blocks(mut self, blocks: u32) -> Self {
+        self.lookback_blocks = blocks;
+        self
+    }
+
+    /// Set how many new finalized blocks to wait for the message
+    pub fn with_timeout_blocks(mut self, blocks: u32) -> Self {
+        self.timeout_blocks = blocks;
+        self
+    }
+
+    /// Wait for the submission's messages to be processed on the destination
+    ///
+    /// `destination_tx_hash` is the destination block that processed the
+    /// message. If it is not seen within the timeout the status stays
+    /// pending.
+    pub async fn track(&self, submission: &XcmSubmission) -> Result<CrossChainTransaction> {
+        let Some(first_id) = submission.message_ids.first() else {
+            return Err(Error::Transaction(format!(
+                "Extrinsic {} did not send an XCM message",
+                submission.tx_hash
+            )));
+        };
+        info!(
+            "Tracking XCM message 0x{} on {:?}",
+            hex::encode(first_id),
+            self.destination_chain
+        );
+
+        // The message may already be processed by the time the origin
+        // extrinsic is finalized
+        let mut block = self
+            .destination
+            .blocks()
+            .at_latest()
+            .await
+            .map_err(|e| Error::Connection(format!("Failed to get latest block: {}", e)))?;
+        for _ in 0..self.lookback_blocks {
+            if let Some(tx) = self.check_block(&block, submission).await? {
+                return Ok(tx);
+            }
+            if block.number() == 0 {
+                break;
+            }
+            block = self
+                .destination
+                .blocks()
+                .at(block.header().parent_hash)
+                .await
+                .map_err(|e| Error::Connection(format!("Failed to get parent block: {}", e)))?;
+        }
+
+        let mut blocks = self
+            .destination
+            .blocks()
+            .subscribe_finalized()
+            .await
+            .map_err(|e| Error::Connection(format!("Failed to subscribe to blocks: {}", e)))?;
+        for _ in 0..self.timeout_blocks {
+            let Some(block) = blocks.next().await else {
+                break;
+            };
+            let block =
+                block.map_err(|e| Error::Connection(format!("Block subscription error: {}", e)))?;
+            if let Some(tx) = self.check_block(&block, submission).await? {
+                return Ok(tx);
+            }
+        }
+
+        debug!("XCM message not seen within {} blocks", self.timeout_blocks);
+        Ok(self.transaction(
+            submission,
+            *first_id,
+            None,
+            TransactionStatus::pending(format!("0x{}", hex::encode(first_id))),
+        ))
+    }
+
+    async fn check_block(
+        &self,
+        block: &Block<PolkadotConfig, OnlineClient<PolkadotConfig>>,
+        submission: &XcmSubmission,
+    ) -> Result<Option<CrossChainTransaction>> {
+        let events = block
+            .events()
+            .await
+            .map_err(|e| Error::Transaction(format!("Failed to get events: {}", e)))?;
+
+        let mut decoded = Vec::new();
+        for event in events.iter() {
+            let event =
+                event.map_err(|e| Error::Transaction(format!("Failed to decode event: {}", e)))?;
+            if let Ok(fields) = event.field_values() {
+                decoded.push(DecodedEvent {
+                    phase: event.phase(),
+                    pallet: event.pallet_name().to_string(),
+                    variant: event.variant_name().to_string(),
+                    fields,
+                });
+            }
+        }
+
+        let Some((id, error)) = delivery_outcome(&decoded, &submission.message_ids) else {
+            return Ok(None);
+        };
+
+        let block_hash = format!("0x{}", hex::encode(block.hash()));
+        let block_number = block.number() as u64;
+        let id_hex = format!("0x{}", hex::encode(id));
+        let mut status = TransactionStatus::finalized(
+            id_hex,
+            block_number,
+            block_hash.clone(),
+            None,
+            None,
+            None,
+        );
+        if let Some(error) = error {
+            info!("XCM message failed on destination: {}", error);
+            status.status = TxStatus::Failed;
+            status.error = Some(error);
+        } else {
+            info!("XCM message processed in destination block {}", block_hash);
+        }
+
+        Ok(Some(self.transaction(
+            submission,
+            id,
+            Some(block_hash),
+            status,
+        )))
+    }
+
+    fn transaction(
+        &self,
+        submission: &XcmSubmission,
+        id: [u8; 32],
+        destination_tx_hash: Option<String>,
+        status: TransactionStatus,
+    ) -> CrossChainTransaction {
+        CrossChainTransaction {
+            id: format!("0x{}", hex::encode(id)),
+            source_chain: self.source_chain.clone(),
+            destination_chain: self.destination_chain.clone(),
+            source_tx_hash: Some(submission.tx_hash.clone()),
+            destination_tx_hash,
+            status,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        }
+    }
+}
+
+/// Message ID in an origin event, if it reports a sent message
+pub(super) fn sent_message_id<T>(
+    pallet: &str,
+    variant: &str,
+    fields: &Composite<T>,
+) -> Option<[u8; 32]> {
+    let field = match (pallet, variant) {
+        ("XcmPallet" | "PolkadotXcm", "Sent") => "message_id",
+        ("XcmpQueue", "XcmpMessageSent") | ("ParachainSystem", "UpwardMessageSent") => {
+            "message_hash"
+        }
+        _ => return None,
+    };
+    hash_bytes(fields.at(field)?)
+}
+
+/// Event as seen in a destination block
+struct DecodedEvent<T> {
+    phase: Phase,
+    pallet: String,
+    variant: String,
+    fields: Composite<T>,
+}
+
+/// Delivered message ID and its error, if one of `ids` was processed
+///
+/// The error of an incomplete execution is only looked up in events emitted
+/// in the same phase that carry the same message ID, so another message
+/// failing in the same block is not reported against this one.
+fn delivery_outcome<T>(
+    events: &[DecodedEvent<T>],
+    ids: &[[u8; 32]],
+) -> Option<([u8; 32], Option<String>)> {
+    let (phase, delivery) = events.iter().find_map(|event| {
+        destination_delivery(&event.pallet, &event.variant, &event.fields, ids)
+            .map(|delivery| (event.phase, delivery))
+    })?;
+    Some(match delivery {
+        Delivery::Processed { id, success: true } => (id, None),
+        Delivery::Processed { id, success: false } => {
+            let error = events
+                .iter()
+                .filter(|event| event.phase == phase)
+                .filter(|event| {
+                    event
+                        .fields
+                        .values()
+                        .any(|value| hash_bytes(value) == Some(id))
+                })
+                .find_map(|event| event.fields.values().find_map(find_xcm_error))
+                .unwrap_or("XCM execution incomplete");
+            (id, Some(error.to_string()))
+        }
+        Delivery::Failed { id, error } => (id, Some(error)),
+    })
+}
+
+fn destination_delivery<T>(
+    pallet: &str,
+    variant: &str,
+    fields: &Composite<T>,
+    ids: &[[u8; 32]],
+) -> Option<Delivery> {
+    if pallet != "MessageQueue" {
+        return None;
+    }
+    let id = hash_bytes(fields.at("id")?).filter(|id| ids.contains(id))?;
+    match variant {
+        "Processed" => Some(Delivery::Processed {
+            id,
+            success: fields.at("success")?.as_bool()?,
+        }),
+        "ProcessingFailed" => {
+            let error = match &fields.at("error")?.value {
+                ValueDef::Variant(error) => error.name.clone(),
+                _ => "processing failed".to_string(),
+            };
+            Some(Delivery::Failed { id, error })
+        }
+        _ => None,
+    }
+}
+
+/// 32-byte hash, looking through `Option` and newtypes like `H256`
+fn hash_bytes<T>(value: &Value<T>) -> Option<[u8; 32]> {
+    match &value.value {
+        ValueDef::Variant(option) if option.name == "Some" => {
+            hash_bytes(option.values.values().next()?)
+        }
+        ValueDef::Composite(fields) if fields.len() == 1 => hash_bytes(fields.values().next()?),
+        ValueDef::Composite(fields) => fields
+            .values()
+            .map(|b| b.as_u128().and_then(|b| u8::try_from(b).ok()))
+            .collect::<Option<Vec<u8>>>()?
+            .try_into()
+            .ok(),
+        _ => None,
+    }
+}
+
+/// First XCM error variant anywhere inside `value`
+fn find_xcm_error<T>(value: &Value<T>) -> Option<&'static str> {
+    match &value.value {
+        ValueDef::Variant(variant) => XCM_ERRORS
+            .iter()
+            .find(|name| **name == variant.name)
+            .copied()
+            .or_else(|| variant.values.values().find_map(find_xcm_error)),
+        ValueDef::Composite(fields) => fields.values().find_map(find_xcm_error),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_value(hash: [u8; 32]) -> Value {
+        // H256 decodes as a newtype around the byte array
+        Value::unnamed_composite(vec![Value::from_bytes(hash)])
+    }
+
+    #[test]
+    fn test_sent_message_id_from_origin_events() {
+        let sent = Composite::named([
+            ("message_id", Value::from_bytes([1u8; 32])),
+            ("destination", Value::unnamed_composite(vec![])),
+        ]);
+        assert_eq!(
+            sent_message_id("PolkadotXcm", "Sent", &sent),
+            Some([1u8; 32])
+        );
+
+        let upward = Composite::named([(
+            "message_hash",
+            Value::unnamed_variant("Some", vec![hash_value([2u8; 32])]),
+        )]);
+        assert_eq!(
+            sent_message_id("ParachainSystem", "UpwardMessageSent", &upward),
+            Some([2u8; 32])
+        );
+
+        let none = Composite::named([("message_hash", Value::unnamed_variant("None", vec![]))]);
+        assert_eq!(
+            sent_message_id("ParachainSystem", "UpwardMessageSent", &none),
+            None
+        );
+        assert_eq!(sent_message_id("Balances", "Transfer", &sent), None);
+    }
+
+    #[test]
+    fn test_destination_delivery_matches_id() {
+        let ids = [[7u8; 32]];
+        let processed = |id: [u8; 32], success: bool| {
+            Composite::named([("id", hash_value(id)), ("success", Value::bool(success))])
+        };
+
+        assert_eq!(
+            destination_delivery(
+                "MessageQueue",
+                "Processed",
+                &processed([7u8; 32], true),
+                &ids
+            ),
+            Some(Delivery::Processed {
+                id: [7u8; 32],
+                success: true
+            })
+        );
+        assert_eq!(
+            destination_delivery(
+                "MessageQueue",
+                "Processed",
+                &processed([8u8; 32], true),
+                &ids
+            ),
+            None
+        );
+
+        let failed = Composite::named([
+            ("id", hash_value([7u8; 32])),
+            ("error", Value::unnamed_variant("Unsupported", vec![])),
+        ]);
+        assert_eq!(
+            destination_delivery("MessageQueue", "ProcessingFailed", &failed, &ids),
+            Some(Delivery::Failed {
+                id: [7u8; 32],
+                error: "Unsupported".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_delivery_outcome_keeps_errors_per_message() {
+        let event =
+            |phase: Phase, pallet: &str, variant: &str, fields: Composite<()>| DecodedEvent {
+                phase,
+                pallet: pallet.to_string(),
+                variant: variant.to_string(),
+                fields,
+            };
+        let executed = |id: [u8; 32], error: &str| {
+            Composite::named([
+                ("message_id", hash_value(id)),
+                (
+                    "outcome",
+                    Value::named_variant(
+                        "Incomplete",
+                        [("error", Value::unnamed_variant(error, vec![]))],
+                    ),
+                ),
+            ])
+        };
+        let processed = |id: [u8; 32]| {
+            Composite::named([("id", hash_value(id)), ("success", Value::bool(false))])
+        };
+
+        // Two messages fail in the same block, each with its own error
+        let events = [
+            event(
+                Phase::Initialization,
+                "DmpQueue",
+                "ExecutedDownward",
+                executed([1u8; 32], "TooExpensive"),
+            ),
+            event(
+                Phase::Initialization,
+                "MessageQueue",
+                "Processed",
+                processed([1u8; 32]),
+            ),
+            event(
+                Phase::ApplyExtrinsic(1),
+                "DmpQueue",
+                "ExecutedDownward",
+                executed([2u8; 32], "Barrier"),
+            ),
+            event(
+                Phase::ApplyExtrinsic(1),
+                "MessageQueue",
+                "Processed",
+                processed([2u8; 32]),
+            ),
+        ];
+
+        assert_eq!(
+            delivery_outcome(&events, &[[2u8; 32]]),
+            Some(([2u8; 32], Some("Barrier".to_string())))
+        );
+        assert_eq!(
+            delivery_outcome(&events, &[[1u8; 32]]),
+            Some(([1u8; 32], Some("TooExpensive".to_string())))
+        );
+
+        // An error for another message in the same phase is not used
+        let events = [
+            event(
+                Phase::Initialization,
+                "DmpQueue",
+                "ExecutedDownward",
+                executed([1u8; 32], "TooExpensive"),
+            ),
+            event(
+                Phase::Initialization,
+                "MessageQueue",
+                "Processed",
+                processed([3u8; 32]),
+            ),
+        ];
+        assert_eq!(
+            delivery_outcome(&events, &[[3u8; 32]]),
+            Some(([3u8; 32], Some("XCM execution incomplete".to_string())))
+        );
+        assert_eq!(delivery_outcome(&events, &[[4u8; 32]]), None);
+    }
+
+    #[test]
+    fn test_find_xcm_error_in_outcome() {
+        let outcome = Value::named_variant(
+            "Incomplete",
+            [
+                ("used", Value::u128(1)),
+                (
+                    "error",
+                    Value::unnamed_variant("UntrustedReserveLocation", vec![]),
+                ),
+            ],
+        );
+        assert_eq!(find_xcm_error(&outcome), Some("UntrustedReserveLocation"));
+        assert_eq!(
+            find_xcm_error(&Value::unnamed_variant("Complete", vec![])),
+            None
+        );
+    }
+}
@@ -41,6 +41,22 @@ fn test_gas_limit_encode_decode() {
     assert_eq!(decoded.proof_size, 789012);
 }
 
+#[test]
+fn test_gas_limit_with_margin() {
+    let limit = GasLimit::new(1_000, 50).with_margin(DEFAULT_GAS_MARGIN_PERCENT);
+    assert_eq!(limit.ref_time, 1_100);
+    assert_eq!(limit.proof_size, 55);
+
+    let saturated = GasLimit::new(u64::MAX, 0).with_margin(50);
+    assert_eq!(saturated.ref_time, u64::MAX);
+}
+
+#[test]
+fn test_storage_deposit_charge() {
+    assert_eq!(StorageDeposit::Charge(42).charge(), 42);
+    assert_eq!(StorageDeposit::Refund(42).charge(), 0);
+}
+
 #[test]
 fn test_storage_deposit_limit_no_limit() {
     let limit = StorageDepositLimit::NoLimit;
@@ -329,6 +345,7 @@ fn test_constructor_spec_structure() {
         label: "new".to_string(),
         selector: [0x9B, 0xAE, 0x9D, 0x5E],
         args: vec![],
+        return_type: None,
         docs: vec!["Creates a new instance".to_string()],
     };
 
@@ -362,6 +379,7 @@ fn test_event_spec_structure() {
         label: "Approval".to_string(),
         args: vec![],
         docs: vec!["Approval event".to_string()],
+        signature_topic: None,
     };
 
     assert_eq!(event.label, "Approval");
@@ -498,3 +516,44 @@ fn test_large_args() {
     assert_eq!(call_data.len(), 1028);
     assert_eq!(&call_data[0..4], &selector);
 }
+
+#[test]
+fn test_encode_message_with_legacy_types() {
+    let json = r#"{
+        "spec": {
+            "constructors": [],
+            "messages": [{
+                "label": "set",
+                "selector": [1, 2, 3, 4],
+                "args": [
+                    {"label": "value", "type_ref": {"ty": 0, "display_name": ["u32"]}},
+                    {"label": "flag", "type_ref": {"ty": 1, "display_name": ["bool"]}}
+                ],
+                "return_type": {"ty": 0, "display_name": ["u32"]},
+                "mutates": true,
+                "payable": false,
+                "docs": []
+            }],
+            "events": []
+        },
+        "storage": {"root": {"key": "0x00", "ty": 0}},
+        "types": [
+            {"id": 0, "path": [], "params": [], "def": {"type": "Primitive", "primitive": "u32"}},
+            {"id": 1, "path": [], "params": [], "def": {"type": "Primitive", "primitive": "bool"}}
+        ]
+    }"#;
+
+    let metadata = parse_metadata(json).unwrap();
+    let call_data = metadata
+        .encode_message_json("set", &[serde_json::json!(7), serde_json::json!(true)])
+        .unwrap();
+    assert_eq!(call_data, vec![1, 2, 3, 4, 7, 0, 0, 0, 1]);
+
+    let value = metadata.decode_return("set", &[7, 0, 0, 0]).unwrap();
+    assert_eq!(metadata.to_json(&value), serde_json::json!(7));
+
+    // Arguments of the wrong shape are rejected rather than silently encoded
+    assert!(metadata
+        .encode_message_json("set", &[serde_json::json!("x"), serde_json::json!(true)])
+        .is_err());
+}
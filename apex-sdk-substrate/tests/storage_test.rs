@@ -490,3 +490,22 @@ fn test_pallet_metadata_zero_counts() {
     assert_eq!(metadata.call_count, 0);
     assert_eq!(metadata.event_count, 0);
 }
+
+#[test]
+fn test_storage_change_decode() {
+    use parity_scale_codec::Encode;
+
+    let change = StorageChange {
+        key: vec![0u8; 48],
+        block_hash: Default::default(),
+        block_number: 100,
+        old: None,
+        new: Some(42u128.encode()),
+        old_value: None,
+        new_value: None,
+    };
+
+    assert_eq!(change.decode_old::<u128>().unwrap(), None);
+    assert_eq!(change.decode_new::<u128>().unwrap(), Some(42));
+    assert!(change.decode_new::<u32>().is_err());
+}
@@ -385,6 +385,7 @@ fn test_xcm_config_default() {
 fn test_xcm_config_structure() {
     let config = XcmConfig {
         version: XcmVersion::V4,
+        negotiate_version: false,
         weight_limit: WeightLimit::Limited(10_000_000_000),
         fee_asset: Some(XcmAsset::native(1_000_000)),
     };
@@ -30,6 +30,8 @@ pub mod advanced;
 pub mod builder;
 pub mod error;
 pub mod error_recovery;
+#[cfg(feature = "metrics-server")]
+pub mod metrics_server;
 pub mod performance;
 pub mod sdk;
 pub mod transaction;
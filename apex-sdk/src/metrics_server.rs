@@ -0,0 +1,739 @@
+//! Embedded Prometheus and health endpoint.
+//!
+//! Serves three routes for long-running services built on the SDK:
+//!
+//! - `GET /metrics` - Prometheus text exposition merging the core
+//!   [`MetricsCollector`] (including latency histogram buckets) with the
+//!   counters of every registered EVM and Substrate adapter
+//! - `GET /healthz` - `200` unless a registered pool has no usable endpoint
+//! - `GET /readyz` - `200` once every registered pool has a verified endpoint
+//!
+//! The server is a minimal HTTP/1.1 responder on top of tokio and is only
+//! compiled with the `metrics-server` feature.
+//!
+//! ```rust,no_run
+//! use apex_sdk::metrics_server::{MetricsServer, MetricsServerConfig};
+//!
+//! # async fn example() -> apex_sdk::Result<()> {
+//! let handle = MetricsServer::new(MetricsServerConfig::default())
+//!     .bind()
+//!     .await?;
+//! println!("metrics on http://{}/metrics", handle.local_addr());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{Error, Result};
+use apex_sdk_core::health::{HealthStatus, PoolHealth};
+use apex_sdk_core::{global_metrics, MetricsCollector};
+use async_trait::async_trait;
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// Largest request head the server will read before rejecting the request
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Time allowed for a client to send its request head
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Configuration for the metrics server
+#[derive(Debug, Clone)]
+pub struct MetricsServerConfig {
+    /// Address to listen on
+    pub bind_addr: SocketAddr,
+}
+
+impl Default for MetricsServerConfig {
+    fn default() -> Self {
+        Self {
+            // Same default port as the Substrate node Prometheus exporter
+            bind_addr: SocketAddr::from(([127, 0, 0, 1], 9615)),
+        }
+    }
+}
+
+impl MetricsServerConfig {
+    /// Create a configuration listening on the given address
+    pub fn new(bind_addr: SocketAddr) -> Self {
+        Self { bind_addr }
+    }
+}
+
+/// Coarse health of a component or endpoint as reported on `/healthz`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// Verified by a health check and serving requests
+    Healthy,
+    /// Serving requests but lagging or forked
+    Degraded,
+    /// Failing requests or serving the wrong network
+    Unhealthy,
+    /// Not checked yet
+    Unknown,
+}
+
+impl Status {
+    /// Whether requests can be routed to an endpoint with this status
+    pub fn is_usable(&self) -> bool {
+        !matches!(self, Status::Unhealthy)
+    }
+
+    /// Whether a health check has confirmed the endpoint works
+    pub fn is_verified(&self) -> bool {
+        matches!(self, Status::Healthy | Status::Degraded)
+    }
+}
+
+/// Health of a single endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointStatus {
+    /// Endpoint URL
+    pub endpoint: String,
+    /// Endpoint status
+    pub status: Status,
+    /// Blocks behind the pool's best head
+    pub blocks_behind: u64,
+    /// Consecutive failed requests
+    pub failure_count: u32,
+}
+
+/// Health of a registered component, typically a connection pool
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentHealth {
+    /// Component name
+    pub name: String,
+    /// Aggregated status
+    pub status: Status,
+    /// Per-endpoint status
+    pub endpoints: Vec<EndpointStatus>,
+}
+
+impl ComponentHealth {
+    /// Build a component report, deriving the aggregate from its endpoints
+    ///
+    /// The component is healthy if any endpoint is healthy, degraded if the
+    /// best endpoint is degraded, and unhealthy if every endpoint is.
+    pub fn from_endpoints(name: impl Into<String>, endpoints: Vec<EndpointStatus>) -> Self {
+        let status = if endpoints.iter().any(|e| e.status == Status::Healthy) {
+            Status::Healthy
+        } else if endpoints.iter().any(|e| e.status == Status::Degraded) {
+            Status::Degraded
+        } else if !endpoints.is_empty() && endpoints.iter().all(|e| e.status == Status::Unhealthy) {
+            Status::Unhealthy
+        } else {
+            Status::Unknown
+        };
+
+        Self {
+            name: name.into(),
+            status,
+            endpoints,
+        }
+    }
+}
+
+/// Body served on `/healthz` and `/readyz`
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    /// Overall status
+    pub status: Status,
+    /// Whether every component has a verified endpoint
+    pub ready: bool,
+    /// Component reports
+    pub components: Vec<ComponentHealth>,
+}
+
+impl HealthReport {
+    /// Aggregate component reports
+    pub fn from_components(components: Vec<ComponentHealth>) -> Self {
+        let status = if components.iter().any(|c| c.status == Status::Unhealthy) {
+            Status::Unhealthy
+        } else if components.iter().any(|c| c.status == Status::Degraded) {
+            Status::Degraded
+        } else if components.iter().any(|c| c.status == Status::Unknown) {
+            Status::Unknown
+        } else {
+            Status::Healthy
+        };
+        let ready = components.iter().all(|c| c.status.is_verified());
+
+        Self {
+            status,
+            ready,
+            components,
+        }
+    }
+
+    /// Whether the process should be considered alive
+    pub fn is_live(&self) -> bool {
+        self.status.is_usable()
+    }
+}
+
+/// A component that can report its health on `/healthz`
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    /// Report the current health of the component
+    async fn check(&self) -> ComponentHealth;
+}
+
+impl From<&HealthStatus> for Status {
+    fn from(status: &HealthStatus) -> Self {
+        match status {
+            HealthStatus::Healthy => Status::Healthy,
+            HealthStatus::Degraded => Status::Degraded,
+            HealthStatus::Unhealthy | HealthStatus::WrongNetwork => Status::Unhealthy,
+            HealthStatus::Unknown => Status::Unknown,
+        }
+    }
+}
+
+/// Connection pool health, reported under the chain name of its adapters
+///
+/// Endpoints report [`Status::Unknown`] until the pool has run its first
+/// health check, since request successes alone do not verify the network or
+/// head of an endpoint.
+#[async_trait]
+impl<P: PoolHealth> HealthCheck for P {
+    async fn check(&self) -> ComponentHealth {
+        let checked = self.health_checked();
+        let endpoints = self
+            .health_status()
+            .await
+            .into_iter()
+            .map(|(endpoint, health)| EndpointStatus {
+                endpoint,
+                status: if checked {
+                    (&health.status).into()
+                } else {
+                    Status::Unknown
+                },
+                blocks_behind: health.blocks_behind,
+                failure_count: health.failure_count,
+            })
+            .collect();
+        ComponentHealth::from_endpoints(self.chain_name(), endpoints)
+    }
+}
+
+/// Embedded HTTP server exposing `/metrics`, `/healthz` and `/readyz`
+#[derive(Clone)]
+pub struct MetricsServer {
+    config: MetricsServerConfig,
+    collector: MetricsCollector,
+    #[cfg(feature = "evm")]
+    evm_metrics: Vec<apex_sdk_evm::Metrics>,
+    #[cfg(feature = "substrate")]
+    substrate_metrics: Vec<apex_sdk_substrate::Metrics>,
+    health_checks: Vec<Arc<dyn HealthCheck>>,
+}
+
+impl MetricsServer {
+    /// Create a server exporting the process-wide collector
+    pub fn new(config: MetricsServerConfig) -> Self {
+        Self {
+            config,
+            collector: global_metrics().clone(),
+            #[cfg(feature = "evm")]
+            evm_metrics: Vec::new(),
+            #[cfg(feature = "substrate")]
+            substrate_metrics: Vec::new(),
+            health_checks: Vec::new(),
+        }
+    }
+
+    /// Export a different core collector instead of the global one
+    pub fn with_collector(mut self, collector: MetricsCollector) -> Self {
+        self.collector = collector;
+        self
+    }
+
+    /// Export the counters of an EVM provider
+    #[cfg(feature = "evm")]
+    pub fn with_evm_metrics(mut self, metrics: apex_sdk_evm::Metrics) -> Self {
+        self.evm_metrics.push(metrics);
+        self
+    }
+
+    /// Export the counters of an EVM adapter
+    #[cfg(feature = "evm")]
+    pub fn with_evm_adapter(self, adapter: &apex_sdk_evm::EvmAdapter) -> Self {
+        self.with_evm_metrics(adapter.provider().rpc_metrics().clone())
+    }
+
+    /// Export the counters of a Substrate adapter
+    #[cfg(feature = "substrate")]
+    pub fn with_substrate_metrics(mut self, metrics: apex_sdk_substrate::Metrics) -> Self {
+        self.substrate_metrics.push(metrics);
+        self
+    }
+
+    /// Export the counters of a Substrate adapter
+    #[cfg(feature = "substrate")]
+    pub fn with_substrate_adapter(self, adapter: &apex_sdk_substrate::SubstrateAdapter) -> Self {
+        self.with_substrate_metrics(adapter.rpc_metrics().clone())
+    }
+
+    /// Report a component, such as a connection pool, on `/healthz` and `/readyz`
+    pub fn with_health_check(mut self, check: Arc<dyn HealthCheck>) -> Self {
+        self.health_checks.push(check);
+        self
+    }
+
+    /// Render the `/metrics` body
+    pub async fn render_metrics(&self) -> String {
+        let mut output = self.collector.export_prometheus().await;
+
+        #[cfg(feature = "evm")]
+        if !self.evm_metrics.is_empty() {
+            let families = self
+                .evm_metrics
+                .iter()
+                .map(|m| {
+                    let s = m.snapshot();
+                    (
+                        m.telemetry(),
+                        [
+                            s.rpc_calls,
+                            s.transactions_submitted,
+                            s.transactions_succeeded,
+                            s.transactions_failed,
+                            s.connection_errors,
+                        ],
+                    )
+                })
+                .collect::<Vec<_>>();
+            render_counters(
+                &mut output,
+                "apex_evm",
+                &[
+                    ("rpc_calls_total", "RPC calls made"),
+                    ("transactions_submitted_total", "Transactions submitted"),
+                    ("transactions_succeeded_total", "Transactions succeeded"),
+                    ("transactions_failed_total", "Transactions failed"),
+                    ("connection_errors_total", "Connection errors"),
+                ],
+                &families,
+            );
+        }
+
+        #[cfg(feature = "substrate")]
+        if !self.substrate_metrics.is_empty() {
+            let families = self
+                .substrate_metrics
+                .iter()
+                .map(|m| {
+                    let s = m.snapshot();
+                    (
+                        m.telemetry(),
+                        [
+                            s.rpc_calls,
+                            s.extrinsics_submitted,
+                            s.extrinsics_succeeded,
+                            s.extrinsics_failed,
+                            s.connection_errors,
+                        ],
+                    )
+                })
+                .collect::<Vec<_>>();
+            render_counters(
+                &mut output,
+                "apex_substrate",
+                &[
+                    ("rpc_calls_total", "RPC calls made"),
+                    ("extrinsics_submitted_total", "Extrinsics submitted"),
+                    ("extrinsics_succeeded_total", "Extrinsics succeeded"),
+                    ("extrinsics_failed_total", "Extrinsics failed"),
+                    ("connection_errors_total", "Connection errors"),
+                ],
+                &families,
+            );
+        }
+
+        output
+    }
+
+    /// Collect the report served on `/healthz` and `/readyz`
+    pub async fn health(&self) -> HealthReport {
+        let mut components = Vec::with_capacity(self.health_checks.len());
+        for check in &self.health_checks {
+            components.push(check.check().await);
+        }
+        HealthReport::from_components(components)
+    }
+
+    /// Bind the listener and serve in a background task
+    pub async fn bind(self) -> Result<MetricsServerHandle> {
+        let (listener, local_addr) = self.listen().await?;
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let task = tokio::spawn(Arc::new(self).accept_loop(listener, shutdown_rx));
+
+        Ok(MetricsServerHandle {
+            local_addr,
+            shutdown: Some(shutdown_tx),
+            task,
+        })
+    }
+
+    /// Bind the listener and serve until the future is dropped
+    pub async fn serve(self) -> Result<()> {
+        let (listener, _) = self.listen().await?;
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+        Arc::new(self).accept_loop(listener, shutdown_rx).await;
+        Ok(())
+    }
+
+    async fn listen(&self) -> Result<(TcpListener, SocketAddr)> {
+        let listener = TcpListener::bind(self.config.bind_addr)
+            .await
+            .map_err(|e| {
+                Error::Config(format!(
+                    "Failed to bind metrics server on {}: {}",
+                    self.config.bind_addr, e
+                ))
+            })?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| Error::Connection(e.to_string()))?;
+
+        tracing::info!("Metrics server listening on {}", local_addr);
+        Ok((listener, local_addr))
+    }
+
+    async fn accept_loop(
+        self: Arc<Self>,
+        listener: TcpListener,
+        mut shutdown: oneshot::Receiver<()>,
+    ) {
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => break,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => {
+                        let server = self.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) = server.handle_connection(stream).await {
+                                tracing::debug!("Metrics request failed: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => tracing::warn!("Metrics server accept failed: {}", e),
+                },
+            }
+        }
+    }
+
+    async fn handle_connection(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        let head = match tokio::time::timeout(REQUEST_TIMEOUT, read_request_head(&mut stream)).await
+        {
+            Ok(head) => head?,
+            Err(_) => return Ok(()),
+        };
+
+        let response = match head.as_deref().and_then(parse_request_line) {
+            Some((method, path)) => self.route(method, path).await,
+            None => Response::text(400, "Bad Request", "bad request\n"),
+        };
+        stream.write_all(&response.into_bytes()).await?;
+        stream.shutdown().await
+    }
+
+    async fn route(&self, method: &str, path: &str) -> Response {
+        if method != "GET" && method != "HEAD" {
+            return Response::text(405, "Method Not Allowed", "method not allowed\n");
+        }
+
+        let mut response = match path {
+            "/metrics" => Response {
+                status: 200,
+                reason: "OK",
+                content_type: "text/plain; version=0.0.4; charset=utf-8",
+                body: self.render_metrics().await,
+                head_only: false,
+            },
+            "/healthz" => {
+                let report = self.health().await;
+                Response::json(report.is_live(), &report)
+            }
+            "/readyz" => {
+                let report = self.health().await;
+                Response::json(report.ready, &report)
+            }
+            _ => Response::text(404, "Not Found", "not found\n"),
+        };
+
+        response.head_only = method == "HEAD";
+        response
+    }
+}
+
+/// Handle to a running [`MetricsServer`]; dropping it stops the server
+pub struct MetricsServerHandle {
+    local_addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+    task: JoinHandle<()>,
+}
+
+impl MetricsServerHandle {
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stop accepting connections and wait for the accept loop to exit
+    pub async fn shutdown(mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        let _ = (&mut self.task).await;
+    }
+}
+
+impl Drop for MetricsServerHandle {
+    fn drop(&mut self) {
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+struct Response {
+    status: u16,
+    reason: &'static str,
+    content_type: &'static str,
+    body: String,
+    /// Answer a `HEAD` request: headers describe `body` but it is not sent
+    head_only: bool,
+}
+
+impl Response {
+    fn text(status: u16, reason: &'static str, body: &str) -> Self {
+        Self {
+            status,
+            reason,
+            content_type: "text/plain; charset=utf-8",
+            body: body.to_string(),
+            head_only: false,
+        }
+    }
+
+    fn json(ok: bool, report: &HealthReport) -> Self {
+        let (status, reason) = if ok {
+            (200, "OK")
+        } else {
+            (503, "Service Unavailable")
+        };
+        Self {
+            status,
+            reason,
+            content_type: "application/json",
+            body: serde_json::to_string(report).unwrap_or_else(|_| "{}".to_string()),
+            head_only: false,
+        }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        let mut out = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            self.status,
+            self.reason,
+            self.content_type,
+            self.body.len()
+        )
+        .into_bytes();
+        if !self.head_only {
+            out.extend_from_slice(self.body.as_bytes());
+        }
+        out
+    }
+}
+
+/// Read up to the end of the request head, returning `None` if it is too large
+async fn read_request_head(stream: &mut TcpStream) -> std::io::Result<Option<String>> {
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+
+    loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if buf.windows(4).any(|w| w == b"\r\n\r\n") {
+            break;
+        }
+        if buf.len() > MAX_REQUEST_BYTES {
+            return Ok(None);
+        }
+    }
+
+    Ok(String::from_utf8(buf).ok())
+}
+
+/// Extract the method and path (without query string) from a request head
+fn parse_request_line(head: &str) -> Option<(&str, &str)> {
+    let mut parts = head.lines().next()?.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    parts.next().filter(|v| v.starts_with("HTTP/"))?;
+    let path = target.split('?').next().unwrap_or(target);
+    Some((method, path))
+}
+
+/// Render one counter family per name, with a sample per registered adapter
+#[cfg(any(feature = "evm", feature = "substrate"))]
+fn render_counters<const N: usize>(
+    output: &mut String,
+    prefix: &str,
+    names: &[(&str, &str); N],
+    samples: &[(&apex_sdk_core::Telemetry, [u64; N])],
+) {
+    for (i, (name, help)) in names.iter().enumerate() {
+        let _ = writeln!(output, "# HELP {}_{} {}", prefix, name, help);
+        let _ = writeln!(output, "# TYPE {}_{} counter", prefix, name);
+        for (telemetry, values) in samples {
+            let _ = writeln!(
+                output,
+                "{}_{}{{chain=\"{}\",endpoint=\"{}\"}} {}",
+                prefix,
+                name,
+                apex_sdk_core::metrics::escape_label(telemetry.chain()),
+                apex_sdk_core::metrics::escape_label(telemetry.endpoint()),
+                values[i]
+            );
+        }
+        output.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(status: Status) -> EndpointStatus {
+        EndpointStatus {
+            endpoint: "http://localhost".to_string(),
+            status,
+            blocks_behind: 0,
+            failure_count: 0,
+        }
+    }
+
+    #[test]
+    fn test_parse_request_line() {
+        assert_eq!(
+            parse_request_line("GET /metrics?x=1 HTTP/1.1\r\nHost: a\r\n\r\n"),
+            Some(("GET", "/metrics"))
+        );
+        assert_eq!(parse_request_line("GET /metrics\r\n\r\n"), None);
+        assert_eq!(parse_request_line(""), None);
+    }
+
+    #[test]
+    fn test_component_health_aggregation() {
+        let c = ComponentHealth::from_endpoints(
+            "evm",
+            vec![endpoint(Status::Unhealthy), endpoint(Status::Degraded)],
+        );
+        assert_eq!(c.status, Status::Degraded);
+
+        let c = ComponentHealth::from_endpoints("evm", vec![endpoint(Status::Unhealthy)]);
+        assert_eq!(c.status, Status::Unhealthy);
+
+        let c = ComponentHealth::from_endpoints("evm", vec![endpoint(Status::Unknown)]);
+        assert_eq!(c.status, Status::Unknown);
+    }
+
+    #[test]
+    fn test_health_report_liveness_and_readiness() {
+        let report = HealthReport::from_components(vec![]);
+        assert!(report.is_live());
+        assert!(report.ready);
+
+        let unknown = ComponentHealth::from_endpoints("a", vec![endpoint(Status::Unknown)]);
+        let report = HealthReport::from_components(vec![unknown]);
+        assert!(report.is_live());
+        assert!(!report.ready);
+
+        let down = ComponentHealth::from_endpoints("b", vec![endpoint(Status::Unhealthy)]);
+        let report = HealthReport::from_components(vec![down]);
+        assert!(!report.is_live());
+        assert!(!report.ready);
+    }
+
+    #[tokio::test]
+    async fn test_render_metrics_includes_histograms() {
+        let collector = MetricsCollector::new();
+        collector.record_duration("apex_test_latency", Duration::from_millis(20));
+        let server = MetricsServer::new(MetricsServerConfig::default()).with_collector(collector);
+
+        let body = server.render_metrics().await;
+        assert!(body.contains("# TYPE apex_test_latency histogram"));
+        assert!(body.contains("apex_test_latency_bucket{le=\"+Inf\"} 1"));
+    }
+
+    struct MockPool {
+        checked: bool,
+    }
+
+    #[async_trait]
+    impl PoolHealth for MockPool {
+        fn chain_name(&self) -> &str {
+            "moonbeam"
+        }
+
+        fn health_checked(&self) -> bool {
+            self.checked
+        }
+
+        async fn health_status(&self) -> Vec<(String, apex_sdk_core::health::EndpointHealth)> {
+            use apex_sdk_core::health::{EndpointHealth, HeadInfo, Verdict};
+
+            let mut health = EndpointHealth::default();
+            health.record_success(10);
+            health.record_verdict(
+                "ws://node",
+                Verdict::<u64>::Head {
+                    head: HeadInfo::new(1, "0x01"),
+                    best: HeadInfo::new(1, "0x01"),
+                    status: HealthStatus::Healthy,
+                },
+            );
+            vec![("ws://node".to_string(), health)]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pool_health_keyed_by_chain_and_unknown_until_checked() {
+        let component = MockPool { checked: false }.check().await;
+        assert_eq!(component.name, "moonbeam");
+        assert_eq!(component.status, Status::Unknown);
+
+        let component = MockPool { checked: true }.check().await;
+        assert_eq!(component.status, Status::Healthy);
+    }
+
+    #[tokio::test]
+    async fn test_head_response_keeps_content_length() {
+        let server = MetricsServer::new(MetricsServerConfig::default());
+
+        let get = server.route("GET", "/healthz").await.into_bytes();
+        let head = server.route("HEAD", "/healthz").await.into_bytes();
+
+        let get = String::from_utf8(get).unwrap();
+        let head = String::from_utf8(head).unwrap();
+        let (get_headers, body) = get.split_once("\r\n\r\n").unwrap();
+        assert!(!body.is_empty());
+        assert!(head.ends_with("\r\n\r\n"));
+        assert_eq!(head.trim_end(), get_headers);
+        assert!(get_headers.contains(&format!("Content-Length: {}", body.len())));
+    }
+}
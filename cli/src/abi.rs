@@ -0,0 +1,431 @@
+//! Solidity ABI encoding, decoding and selector lookup
+//!
+//! Calldata, logs and revert data are decoded with the ABIs given with
+//! `--abi`, or otherwise with a signature database built from every ABI file
+//! and compiler artifact found under the project directory (`--dir`, the
+//! current directory by default).
+
+use anyhow::{Context, Result};
+use apex_sdk_evm::abi::{sol_to_json, ContractAbi, DecodedCall, DynSolValue};
+use colored::Colorize;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::tx::parse_arg;
+
+/// Directories never searched for ABI files
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+
+/// How deep below the project directory ABI files are searched for
+const MAX_DEPTH: usize = 8;
+
+/// Where ABIs for decoding come from
+#[derive(Debug, Clone, clap::Args)]
+pub struct AbiSource {
+    /// Contract ABI or compiler artifact (repeatable); replaces the project's signature database
+    #[arg(long)]
+    pub abi: Vec<PathBuf>,
+    /// Project directory whose ABI files form the signature database
+    #[arg(long, default_value = ".")]
+    pub dir: PathBuf,
+}
+
+impl AbiSource {
+    /// The `--abi` files merged into one ABI, or the project's signature database
+    fn load(&self) -> Result<ContractAbi> {
+        if self.abi.is_empty() {
+            return Ok(signature_database(&self.dir)?.0);
+        }
+
+        let abis = self
+            .abi
+            .iter()
+            .map(|path| load_abi(path))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ContractAbi::merge(abis))
+    }
+}
+
+/// Read a contract ABI or compiler artifact
+pub(crate) fn load_abi(path: &Path) -> Result<ContractAbi> {
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read ABI {}", path.display()))?;
+    ContractAbi::from_json(&json).with_context(|| format!("Invalid ABI {}", path.display()))
+}
+
+/// Merge every ABI file under `dir` into one ABI
+///
+/// JSON files that are not ABIs or compiler artifacts are skipped, as are
+/// hidden directories, `target` and `node_modules`. Returns the merged ABI
+/// and the number of files it was built from.
+fn signature_database(dir: &Path) -> Result<(ContractAbi, usize)> {
+    fn visit(dir: &Path, depth: usize, abis: &mut Vec<ContractAbi>) -> Result<()> {
+        let entries = std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read directory {}", dir.display()))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+
+            if path.is_dir() {
+                if depth < MAX_DEPTH
+                    && !name.starts_with('.')
+                    && !SKIPPED_DIRS.contains(&name.as_ref())
+                {
+                    visit(&path, depth + 1, abis)?;
+                }
+            } else if name.ends_with(".json") {
+                if let Ok(found) = load_abi(&path) {
+                    if !found.abi().is_empty() {
+                        abis.push(found);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    let mut abis = Vec::new();
+    visit(dir, 0, &mut abis)?;
+    let files = abis.len();
+    Ok((ContractAbi::merge(abis), files))
+}
+
+/// ABI-encode a function call
+pub fn encode(signature: &str, args: &[String], args_only: bool) -> Result<()> {
+    let abi = ContractAbi::from_signature(signature)?;
+    let function = abi
+        .abi()
+        .functions()
+        .next()
+        .map(|f| f.signature())
+        .context("Signature has no function")?;
+
+    let args: Vec<serde_json::Value> = args.iter().map(|arg| parse_arg(arg)).collect();
+    let data = abi.encode_call_json(&function, &args)?;
+    let data = if args_only { &data[4..] } else { &data[..] };
+
+    println!("0x{}", hex::encode(data));
+    Ok(())
+}
+
+/// A decoded call, error or log
+#[derive(Debug, Serialize)]
+struct DecodedReport {
+    kind: &'static str,
+    name: String,
+    signature: String,
+    params: serde_json::Value,
+    /// Parameters in declaration order, for human-readable output
+    #[serde(skip)]
+    ordered: Vec<(String, serde_json::Value)>,
+}
+
+impl DecodedReport {
+    fn new(
+        kind: &'static str,
+        name: &str,
+        signature: String,
+        params: &[(String, DynSolValue)],
+    ) -> Self {
+        let ordered: Vec<(String, serde_json::Value)> = params
+            .iter()
+            .map(|(name, value)| (name.clone(), sol_to_json(value)))
+            .collect();
+        Self {
+            kind,
+            name: name.to_string(),
+            signature,
+            params: serde_json::Value::Object(ordered.iter().cloned().collect()),
+            ordered,
+        }
+    }
+
+    fn from_call(kind: &'static str, decoded: &DecodedCall) -> Self {
+        Self::new(
+            kind,
+            &decoded.name,
+            decoded.signature.clone(),
+            &decoded.params,
+        )
+    }
+
+    fn print(&self, json: bool) -> Result<()> {
+        if json {
+            println!("{}", serde_json::to_string_pretty(self)?);
+            return Ok(());
+        }
+
+        println!("{} {}", self.kind.dimmed(), self.signature.green().bold());
+        for (i, (name, value)) in self.ordered.iter().enumerate() {
+            let name = if name.is_empty() {
+                format!("[{}]", i)
+            } else {
+                name.clone()
+            };
+            match value {
+                serde_json::Value::String(s) => println!("  {}: {}", name.cyan(), s),
+                value => println!("  {}: {}", name.cyan(), value),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An event log as returned by `eth_getLogs` or in a receipt
+#[derive(Debug, serde::Deserialize)]
+struct RawLog {
+    topics: Vec<String>,
+    data: String,
+}
+
+/// Decode calldata, or an event log given as JSON or with `--topic`
+pub fn decode(data: &str, topics: &[String], source: &AbiSource, json: bool) -> Result<()> {
+    let abi = source.load()?;
+
+    let (topics, data) = if data.trim_start().starts_with('{') {
+        let log: RawLog = serde_json::from_str(data)
+            .context("Invalid log: expected an object with `topics` and `data`")?;
+        (log.topics, parse_hex(&log.data)?)
+    } else {
+        (topics.to_vec(), parse_hex(data)?)
+    };
+
+    let report = if topics.is_empty() {
+        match abi.decode_call(&data) {
+            Ok(call) => DecodedReport::from_call("function", &call),
+            // Revert data has the same layout as calldata
+            Err(e) => abi
+                .decode_error(&data)
+                .map(|error| DecodedReport::from_call("error", &error))
+                .ok_or(e)?,
+        }
+    } else {
+        let topics = topics
+            .iter()
+            .map(|t| {
+                let bytes = parse_hex(t)?;
+                <[u8; 32]>::try_from(bytes.as_slice())
+                    .map_err(|_| anyhow::anyhow!("Invalid topic '{}': expected 32 bytes", t))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let log = abi.decode_log(&topics, &data)?;
+        let signature = abi
+            .abi()
+            .events()
+            .find(|event| event.selector().0 == topics[0])
+            .map(|event| event.signature())
+            .unwrap_or_else(|| log.name.clone());
+        DecodedReport::new("event", &log.name, signature, &log.params)
+    };
+
+    report.print(json)
+}
+
+/// Decode revert data
+pub fn error(data: &str, source: &AbiSource, json: bool) -> Result<()> {
+    let abi = source.load()?;
+    let data = parse_hex(data)?;
+
+    let error = abi.decode_error(&data).ok_or_else(|| match data.get(..4) {
+        Some(selector) => anyhow::anyhow!(
+            "Unknown error selector 0x{}; pass the contract's ABI with --abi",
+            hex::encode(selector)
+        ),
+        None if data.is_empty() => anyhow::anyhow!("Empty revert data: reverted without a reason"),
+        None => anyhow::anyhow!("Revert data is shorter than a selector"),
+    })?;
+
+    let report = DecodedReport::from_call("error", &error);
+    report.print(json)?;
+    if !json && error.name == "Panic" {
+        if let Some(reason) = report.params.get("code").and_then(panic_reason) {
+            println!("  {}", reason.dimmed());
+        }
+    }
+    Ok(())
+}
+
+/// Meaning of a Solidity panic code
+fn panic_reason(code: &serde_json::Value) -> Option<&'static str> {
+    Some(match code.as_u64()? {
+        0x00 => "generic compiler panic",
+        0x01 => "assertion failed",
+        0x11 => "arithmetic overflow or underflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "invalid enum value",
+        0x22 => "invalid storage byte array encoding",
+        0x31 => "pop on an empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "out of memory",
+        0x51 => "call to an uninitialized function",
+        _ => return None,
+    })
+}
+
+/// A function or error selector, or an event topic
+#[derive(Debug, Serialize, PartialEq, Eq)]
+struct SelectorReport {
+    kind: &'static str,
+    selector: String,
+    signature: String,
+}
+
+fn selector_reports(abi: &ContractAbi) -> Vec<SelectorReport> {
+    let abi = abi.abi();
+    let functions = abi.functions().map(|f| SelectorReport {
+        kind: "function",
+        selector: f.selector().to_string(),
+        signature: f.signature(),
+    });
+    let errors = abi.errors().map(|e| SelectorReport {
+        kind: "error",
+        selector: e.selector().to_string(),
+        signature: e.signature(),
+    });
+    let events = abi.events().map(|e| SelectorReport {
+        kind: "event",
+        selector: e.selector().to_string(),
+        signature: e.signature(),
+    });
+    functions.chain(errors).chain(events).collect()
+}
+
+/// List the selectors of an ABI file or project, compute the selector of a
+/// signature, or look a selector up in the project's signature database
+pub fn selectors(query: &str, dir: &Path, json: bool) -> Result<()> {
+    let path = Path::new(query);
+    let reports = if path.is_dir() {
+        selector_reports(&signature_database(path)?.0)
+    } else if path.is_file() {
+        selector_reports(&load_abi(path)?)
+    } else if query.contains('(') {
+        vec![signature_selector(query)?]
+    } else if query.starts_with("0x") {
+        let (abi, files) = signature_database(dir)?;
+        let selector = query.to_lowercase();
+        let found: Vec<SelectorReport> = selector_reports(&abi)
+            .into_iter()
+            .filter(|r| r.selector == selector)
+            .collect();
+        if found.is_empty() {
+            anyhow::bail!(
+                "Selector {} not found in {} ABI file(s) under {}",
+                query,
+                files,
+                dir.display()
+            );
+        }
+        found
+    } else {
+        anyhow::bail!(
+            "'{}' is neither an ABI file, a directory, a signature nor a selector",
+            query
+        );
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        return Ok(());
+    }
+
+    for report in &reports {
+        println!(
+            "{:<8} {:<10} {}",
+            report.kind.dimmed(),
+            report.selector.cyan(),
+            report.signature
+        );
+    }
+    Ok(())
+}
+
+/// Selector of a function, error or event signature
+///
+/// `event Transfer(address indexed from, ...)` yields the 32-byte topic,
+/// anything else the 4-byte selector. Parameter names are ignored.
+fn signature_selector(signature: &str) -> Result<SelectorReport> {
+    use alloy::json_abi::{Error, Event, Function};
+
+    let invalid = |e: alloy::json_abi::parser::Error| {
+        anyhow::anyhow!("Invalid signature '{}': {}", signature, e)
+    };
+    let signature = signature.trim();
+    let report = if signature.starts_with("event ") {
+        let event = Event::parse(signature).map_err(invalid)?;
+        SelectorReport {
+            kind: "event",
+            selector: event.selector().to_string(),
+            signature: event.signature(),
+        }
+    } else if signature.starts_with("error ") {
+        let error = Error::parse(signature).map_err(invalid)?;
+        SelectorReport {
+            kind: "error",
+            selector: error.selector().to_string(),
+            signature: error.signature(),
+        }
+    } else {
+        let function = Function::parse(signature).map_err(invalid)?;
+        SelectorReport {
+            kind: "function",
+            selector: function.selector().to_string(),
+            signature: function.signature(),
+        }
+    };
+    Ok(report)
+}
+
+fn parse_hex(data: &str) -> Result<Vec<u8>> {
+    let data = data.trim();
+    hex::decode(data.strip_prefix("0x").unwrap_or(data))
+        .with_context(|| format!("Invalid hex data '{}'", data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_selector() {
+        let transfer = signature_selector("transfer(address to, uint256)").unwrap();
+        assert_eq!(transfer.selector, "0xa9059cbb");
+        assert_eq!(transfer.signature, "transfer(address,uint256)");
+
+        let event =
+            signature_selector("event Transfer(address indexed from, address, uint256)").unwrap();
+        assert_eq!(event.kind, "event");
+        assert_eq!(
+            event.selector,
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+    }
+
+    #[test]
+    fn test_signature_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out/Token.sol");
+        std::fs::create_dir_all(&out).unwrap();
+        std::fs::create_dir_all(dir.path().join("node_modules")).unwrap();
+
+        let error = r#"[{"type": "error", "name": "Unauthorized", "inputs": []}]"#;
+        std::fs::write(
+            out.join("Token.json"),
+            format!(r#"{{"abi": {}, "bytecode": "0x"}}"#, error),
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("node_modules/Dep.json"), error).unwrap();
+        std::fs::write(dir.path().join("package.json"), r#"{"name": "dapp"}"#).unwrap();
+
+        let (abi, files) = signature_database(dir.path()).unwrap();
+        assert_eq!(files, 1);
+        assert_eq!(
+            selector_reports(&abi),
+            vec![SelectorReport {
+                kind: "error",
+                selector: "0x82b42900".to_string(),
+                signature: "Unauthorized()".to_string(),
+            }]
+        );
+    }
+}
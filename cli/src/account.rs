@@ -2,10 +2,21 @@
 
 use anyhow::{Context, Result};
 use colored::Colorize;
-use sp_core::{crypto::Ss58Codec, sr25519, Pair};
+use sp_core::{crypto::Ss58Codec, Pair};
 use std::io::Write;
 
-use crate::keystore::{AccountType, Keystore};
+use crate::keyfile::KeyFileFormat;
+use crate::keystore::{AccountSecret, AccountType, KdfParams, Keystore, SecretKind};
+use crate::signer::SignerBackend;
+
+/// Load the keystore for a command, telling the user if it had to be migrated
+pub fn load_keystore(path: &std::path::Path) -> Result<Keystore> {
+    let keystore = Keystore::load(path)?;
+    if let Some(migration) = keystore.migration() {
+        eprintln!("{}", migration.to_string().yellow());
+    }
+    Ok(keystore)
+}
 
 /// Generate a new account
 pub fn generate_account(account_type: &str, name: Option<String>) -> Result<()> {
@@ -33,9 +44,7 @@ fn generate_substrate_account(name: Option<String>) -> Result<()> {
     let mnemonic_phrase = mnemonic.to_string();
 
     // Generate keypair from mnemonic
-    let seed = mnemonic.to_seed("");
-    let pair = sr25519::Pair::from_seed_slice(&seed[..32])
-        .context("Failed to generate keypair from seed")?;
+    let pair = AccountSecret::Mnemonic(mnemonic_phrase.clone()).substrate_pair()?;
 
     let address = pair.public().to_ss58check();
 
@@ -57,7 +66,8 @@ fn generate_substrate_account(name: Option<String>) -> Result<()> {
             account_name,
             AccountType::Substrate,
             address,
-            &mnemonic_phrase,
+            mnemonic_phrase.as_bytes(),
+            SecretKind::Mnemonic,
         )?;
     } else {
         println!("\n{}", "Tip:".cyan());
@@ -105,7 +115,13 @@ fn generate_evm_account(name: Option<String>) -> Result<()> {
 
     // Ask if user wants to save the account
     if let Some(account_name) = name {
-        save_account_interactive(account_name, AccountType::Evm, address, &mnemonic_phrase)?;
+        save_account_interactive(
+            account_name,
+            AccountType::Evm,
+            address,
+            mnemonic_phrase.as_bytes(),
+            SecretKind::Mnemonic,
+        )?;
     } else {
         println!("\n{}", "Tip:".cyan());
         println!(
@@ -122,16 +138,20 @@ pub fn import_account(mnemonic: &str, account_type: &str, name: String) -> Resul
     use alloy_signer_local::{coins_bip39::English, MnemonicBuilder};
 
     // Validate mnemonic
-    let mnemonic_obj: bip39::Mnemonic = mnemonic.parse().context("Invalid mnemonic phrase")?;
+    let _: bip39::Mnemonic = mnemonic.parse().context("Invalid mnemonic phrase")?;
 
     match account_type.to_lowercase().as_str() {
         "substrate" | "sub" => {
-            let seed = mnemonic_obj.to_seed("");
-            let pair = sr25519::Pair::from_seed_slice(&seed[..32])
-                .context("Failed to generate keypair from seed")?;
+            let pair = AccountSecret::Mnemonic(mnemonic.to_string()).substrate_pair()?;
             let address = pair.public().to_ss58check();
 
-            save_account_interactive(name, AccountType::Substrate, address, mnemonic)?;
+            save_account_interactive(
+                name,
+                AccountType::Substrate,
+                address,
+                mnemonic.as_bytes(),
+                SecretKind::Mnemonic,
+            )?;
         }
         "evm" | "ethereum" | "eth" => {
             let wallet = MnemonicBuilder::<English>::default()
@@ -140,7 +160,13 @@ pub fn import_account(mnemonic: &str, account_type: &str, name: String) -> Resul
                 .context("Failed to build wallet from mnemonic")?;
             let address = format!("{:?}", wallet.address());
 
-            save_account_interactive(name, AccountType::Evm, address, mnemonic)?;
+            save_account_interactive(
+                name,
+                AccountType::Evm,
+                address,
+                mnemonic.as_bytes(),
+                SecretKind::Mnemonic,
+            )?;
         }
         _ => anyhow::bail!(
             "Invalid account type '{}'. Supported types: substrate, evm",
@@ -151,12 +177,54 @@ pub fn import_account(mnemonic: &str, account_type: &str, name: String) -> Resul
     Ok(())
 }
 
+/// Import an account from an Ethereum V3 keystore or Polkadot.js account JSON
+pub fn import_key_file(path: &str, name: String) -> Result<()> {
+    use zeroize::Zeroize;
+
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read key file '{}'", path))?;
+    let format = KeyFileFormat::detect(&json)?;
+
+    let password = rpassword::prompt_password("Enter password for key file: ")
+        .context("Failed to read password")?;
+
+    match format {
+        KeyFileFormat::Web3 => {
+            let (mut private_key, address) = crate::keyfile::decrypt_web3(&json, &password)?;
+            let result = save_account_interactive(
+                name,
+                AccountType::Evm,
+                address,
+                &private_key,
+                SecretKind::PrivateKey,
+            );
+            private_key.zeroize();
+            result
+        }
+        KeyFileFormat::PolkadotJs => {
+            let pair = crate::keyfile::decrypt_polkadot_js(&json, &password)?;
+            let address = pair.public().to_ss58check();
+            let mut secret = pair.to_raw_vec();
+            let result = save_account_interactive(
+                name,
+                AccountType::Substrate,
+                address,
+                &secret,
+                SecretKind::PrivateKey,
+            );
+            secret.zeroize();
+            result
+        }
+    }
+}
+
 /// Save account with password prompt
 fn save_account_interactive(
     name: String,
     account_type: AccountType,
     address: String,
-    mnemonic: &str,
+    secret: &[u8],
+    secret_kind: SecretKind,
 ) -> Result<()> {
     println!("\n{}", "💾 Saving Account to Keystore".cyan().bold());
     println!("{}", "═══════════════════════════════════════".dimmed());
@@ -165,9 +233,7 @@ fn save_account_interactive(
     let password = rpassword::prompt_password("Enter password to encrypt account: ")
         .context("Failed to read password")?;
 
-    if password.len() < 8 {
-        anyhow::bail!("Password must be at least 8 characters long");
-    }
+    Keystore::validate_password(&password)?;
 
     let password_confirm = rpassword::prompt_password("Confirm password: ")
         .context("Failed to read password confirmation")?;
@@ -178,16 +244,25 @@ fn save_account_interactive(
 
     // Load keystore
     let keystore_path = crate::keystore::get_keystore_path()?;
-    let mut keystore = Keystore::load(&keystore_path)?;
+    let mut keystore = load_keystore(&keystore_path)?;
 
     // Add account
-    keystore.add_account(
-        name.clone(),
-        account_type.clone(),
-        address.clone(),
-        mnemonic.as_bytes(),
-        &password,
-    )?;
+    match secret_kind {
+        SecretKind::Mnemonic => keystore.add_account(
+            name.clone(),
+            account_type.clone(),
+            address.clone(),
+            secret,
+            &password,
+        )?,
+        SecretKind::PrivateKey => keystore.add_private_key(
+            name.clone(),
+            account_type.clone(),
+            address.clone(),
+            secret,
+            &password,
+        )?,
+    }
 
     // Save keystore
     keystore.save(&keystore_path)?;
@@ -201,10 +276,101 @@ fn save_account_interactive(
     Ok(())
 }
 
-/// List all accounts
+/// Derive a new named account from a mnemonic account
+///
+/// EVM accounts take a BIP-44 path (`m/44'/60'/0'/0/1`) and Substrate accounts
+/// take junctions (`//Alice`, `//stash/1`). An index is shorthand for
+/// `m/44'/60'/0'/0/<index>` or `//<index>`.
+pub fn derive_account(
+    parent: &str,
+    name: String,
+    path: Option<&str>,
+    index: Option<u32>,
+) -> Result<()> {
+    let keystore_path = crate::keystore::get_keystore_path()?;
+    let mut keystore = load_keystore(&keystore_path)?;
+
+    let account_type = keystore
+        .list_accounts()
+        .into_iter()
+        .find(|a| a.name == parent)
+        .map(|a| a.account_type.clone())
+        .ok_or_else(|| anyhow::anyhow!("Account '{}' not found", parent))?;
+
+    let path = match (path, index) {
+        (Some(path), _) => path.to_string(),
+        (None, Some(index)) => default_derivation_path(&account_type, index),
+        (None, None) => anyhow::bail!("Provide a derivation path with --path or an --index"),
+    };
+    match account_type {
+        AccountType::Evm if !path.starts_with("m/") => anyhow::bail!(
+            "Invalid derivation path '{}'. EVM paths look like m/44'/60'/0'/0/1",
+            path
+        ),
+        AccountType::Substrate => {
+            crate::keystore::validate_substrate_path(&path)?;
+        }
+        _ => {}
+    }
+
+    println!("\n{}", "🌱 Derive Account".cyan().bold());
+    println!("{}", "═══════════════════════════════════════".dimmed());
+
+    let phrase = match crate::signer::unlock(parent, None)?.secret()? {
+        AccountSecret::Mnemonic(phrase) => phrase.clone(),
+        _ => anyhow::bail!(
+            "Account '{}' is not a mnemonic account and cannot be derived from",
+            parent
+        ),
+    };
+
+    let derived = AccountSecret::Derived {
+        phrase,
+        path: path.clone(),
+    };
+    let address = match account_type {
+        AccountType::Substrate => derived.substrate_pair()?.public().to_ss58check(),
+        AccountType::Evm => derived.evm_wallet()?.address(),
+    };
+
+    keystore.add_derived_account(name.clone(), parent, path.clone(), address.clone())?;
+    keystore.save(&keystore_path)?;
+
+    println!("\n{}", "Account Derived Successfully".green().bold());
+    println!("{}: {}", "Name".cyan(), name);
+    println!("{}: {}", "Parent".cyan(), parent);
+    println!("{}: {}", "Path".cyan(), path);
+    println!("{}: {}", "Address".cyan(), address);
+
+    Ok(())
+}
+
+/// Derivation path for an account index
+fn default_derivation_path(account_type: &AccountType, index: u32) -> String {
+    match account_type {
+        AccountType::Evm => format!("m/44'/60'/0'/0/{}", index),
+        AccountType::Substrate => format!("//{}", index),
+    }
+}
+
+/// Sort key ordering derivation paths segment by segment, numbers numerically
+fn derivation_sort_key(path: &str) -> Vec<(u64, String)> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            let number = segment.trim_end_matches(['\'', 'h']);
+            match number.parse::<u64>() {
+                Ok(n) => (n, String::new()),
+                Err(_) => (u64::MAX, segment.to_string()),
+            }
+        })
+        .collect()
+}
+
+/// List all accounts, with derived accounts grouped under their parent
 pub fn list_accounts() -> Result<()> {
     let keystore_path = crate::keystore::get_keystore_path()?;
-    let keystore = Keystore::load(&keystore_path)?;
+    let keystore = load_keystore(&keystore_path)?;
 
     let accounts = keystore.list_accounts();
 
@@ -219,10 +385,26 @@ pub fn list_accounts() -> Result<()> {
     println!("\n{}", "Accounts".cyan().bold());
     println!("{}", "═══════════════════════════════════════".dimmed());
 
-    for (idx, account) in accounts.iter().enumerate() {
+    let roots = accounts.iter().filter(|account| match &account.parent {
+        Some(parent) => !keystore.has_account(parent),
+        None => true,
+    });
+    let unlocked = crate::agent::list().unwrap_or_default();
+
+    for (idx, account) in roots.enumerate() {
         println!("\n{}. {}", idx + 1, account.name.green().bold());
         println!("   {}: {}", "Type".dimmed(), account.account_type);
         println!("   {}: {}", "Address".dimmed(), account.address);
+        if !account.backend.is_keystore() {
+            println!("   {}: {}", "Key".dimmed(), account.backend);
+        }
+        if let Some(cached) = unlocked.iter().find(|c| c.name == account.name) {
+            println!(
+                "   {}: {}",
+                "Unlocked".dimmed(),
+                format!("{} left", format_duration(cached.expires_in_secs)).yellow()
+            );
+        }
 
         let created =
             chrono::DateTime::from_timestamp(account.created_at as i64, 0).unwrap_or_default();
@@ -231,6 +413,25 @@ pub fn list_accounts() -> Result<()> {
             "Created".dimmed(),
             created.format("%Y-%m-%d %H:%M:%S")
         );
+
+        let mut children: Vec<_> = accounts
+            .iter()
+            .filter(|a| a.parent.as_deref() == Some(account.name.as_str()))
+            .collect();
+        if children.is_empty() {
+            continue;
+        }
+        children.sort_by_key(|a| derivation_sort_key(a.derivation_path.as_deref().unwrap_or("")));
+
+        println!("   {}:", "Derived".dimmed());
+        for child in children {
+            println!(
+                "     {} {} {}",
+                child.derivation_path.as_deref().unwrap_or("").yellow(),
+                child.name.green(),
+                child.address.dimmed()
+            );
+        }
     }
 
     println!("\n{}: {}", "Total".cyan(), accounts.len());
@@ -239,45 +440,421 @@ pub fn list_accounts() -> Result<()> {
     Ok(())
 }
 
-/// Export account mnemonic
-pub fn export_account(name: &str) -> Result<()> {
+/// Export an account as its mnemonic or private key, or as a key file
+/// (`web3` for EVM accounts, `polkadot-js` for Substrate accounts)
+pub fn export_account(name: &str, format: &str, output: Option<&str>) -> Result<()> {
     let keystore_path = crate::keystore::get_keystore_path()?;
-    let mut keystore = Keystore::load(&keystore_path)?;
+    let keystore = load_keystore(&keystore_path)?;
+
+    let account_type = keystore
+        .list_accounts()
+        .into_iter()
+        .find(|a| a.name == name)
+        .map(|a| a.account_type.clone())
+        .ok_or_else(|| anyhow::anyhow!("Account '{}' not found", name))?;
+
+    let key_file_format = match format.to_lowercase().as_str() {
+        "mnemonic" | "raw" => None,
+        "web3" | "v3" | "json" if account_type == AccountType::Evm => Some(KeyFileFormat::Web3),
+        "polkadot-js" | "polkadotjs" | "pjs" | "json" if account_type == AccountType::Substrate => {
+            Some(KeyFileFormat::PolkadotJs)
+        }
+        "web3" | "v3" | "polkadot-js" | "polkadotjs" | "pjs" => anyhow::bail!(
+            "Format '{}' is not available for {} account '{}'. Use {} instead",
+            format,
+            account_type,
+            name,
+            if account_type == AccountType::Evm {
+                "web3"
+            } else {
+                "polkadot-js"
+            }
+        ),
+        _ => anyhow::bail!(
+            "Invalid export format '{}'. Supported formats: mnemonic, web3, polkadot-js",
+            format
+        ),
+    };
 
-    if !keystore.has_account(name) {
-        anyhow::bail!("Account '{}' not found", name);
+    println!("\n{}", "🔓 Export Account".yellow().bold());
+    println!("{}", "═══════════════════════════════════════".dimmed());
+
+    let Some(key_file_format) = key_file_format else {
+        println!(
+            "{}",
+            "Warning: This will display your secret key material!".red()
+        );
+
+        match crate::signer::unlock_uncached(name)?.secret()? {
+            AccountSecret::Mnemonic(mnemonic) => {
+                println!("\n{}: {}", "Mnemonic".yellow().bold(), mnemonic);
+            }
+            AccountSecret::PrivateKey(key) => {
+                println!(
+                    "\n{}: 0x{}",
+                    "Private Key".yellow().bold(),
+                    hex::encode(key)
+                );
+            }
+            AccountSecret::Derived { phrase, path } => {
+                println!("\n{}: {}", "Mnemonic".yellow().bold(), phrase);
+                println!("{}: {}", "Derivation Path".yellow().bold(), path);
+            }
+        }
+        println!("\n{}", "Security Reminder:".red().bold());
+        println!("• Never share this secret with anyone");
+        println!("• Clear your terminal history after viewing");
+        println!("• Make sure no one is looking over your shoulder");
+
+        return Ok(());
+    };
+
+    let account = crate::signer::unlock_uncached(name)?;
+    let secret = account.secret()?;
+
+    let file_password = rpassword::prompt_password("Enter password for exported file: ")
+        .context("Failed to read password")?;
+    Keystore::validate_password(&file_password)?;
+    let file_password_confirm = rpassword::prompt_password("Confirm password: ")
+        .context("Failed to read password confirmation")?;
+    if file_password != file_password_confirm {
+        anyhow::bail!("Passwords do not match");
     }
 
-    println!("\n{}", "🔓 Export Account".yellow().bold());
+    let json = match key_file_format {
+        KeyFileFormat::Web3 => {
+            let wallet = secret.evm_wallet()?;
+            let mut private_key = hex::decode(wallet.export_private_key().trim_start_matches("0x"))
+                .context("Failed to decode private key")?;
+            let key: &[u8; 32] = private_key
+                .as_slice()
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Invalid private key length"))?;
+            let json = crate::keyfile::encrypt_web3(key, &file_password);
+            zeroize::Zeroize::zeroize(&mut private_key);
+            json?
+        }
+        KeyFileFormat::PolkadotJs => {
+            crate::keyfile::encrypt_polkadot_js(&secret.substrate_pair()?, &file_password, name)?
+        }
+    };
+    let json = serde_json::to_string_pretty(&json)?;
+
+    match output {
+        Some(path) => {
+            write_private_file(std::path::Path::new(path), &json)?;
+            println!("\n{}", "Key File Exported".green().bold());
+            println!("{}: {}", "File".cyan(), path);
+        }
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+/// Write a new file readable only by the current user, refusing to replace
+/// an existing one
+fn write_private_file(path: &std::path::Path, contents: &str) -> Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .with_context(|| format!("Failed to write '{}'", path.display()))
+}
+
+/// Move an account's key from the keystore file into the OS keychain
+pub fn move_to_keychain(name: &str) -> Result<()> {
+    let keystore_path = crate::keystore::get_keystore_path()?;
+    let mut keystore = load_keystore(&keystore_path)?;
+
+    let root = keystore.root_account(name)?.clone();
+    if root.name != name {
+        anyhow::bail!(
+            "Account '{}' is derived from '{}' and uses its key. Move '{}' instead",
+            name,
+            root.name,
+            root.name
+        );
+    }
+    if !root.backend.is_keystore() {
+        anyhow::bail!("Account '{}' is already held by the {}", name, root.backend);
+    }
+
+    println!("\n{}", "🔐 Move Account to OS Keychain".cyan().bold());
     println!("{}", "═══════════════════════════════════════".dimmed());
+
+    let secret = crate::signer::load_secret_data(&mut keystore, &root)?;
+    crate::signer::keychain::store(name, &secret)?;
+
+    // Only drop the encrypted copy once the keychain hands the key back
+    if crate::signer::keychain::load(name)?.as_slice() != secret.as_slice() {
+        anyhow::bail!(
+            "OS keychain returned a different key for '{}'. The keystore file was left unchanged",
+            name
+        );
+    }
+
+    keystore.set_backend(name, SignerBackend::Keychain)?;
+    keystore.save(&keystore_path)?;
+
+    println!("\n{}", "Account Moved Successfully".green().bold());
+    println!("{}: {}", "Name".cyan(), name);
+    println!("{}: {}", "Key".cyan(), SignerBackend::Keychain);
+    println!(
+        "\n{}",
+        "The keystore file no longer holds this key. Keep a backup of the mnemonic or an export."
+            .yellow()
+    );
+
+    Ok(())
+}
+
+/// Re-encrypt an account with new Argon2 parameters, optionally changing its password
+///
+/// Parameters not given keep the stronger of the account's current ones and
+/// the defaults for new accounts.
+pub fn rekey_account(
+    name: &str,
+    change_password: bool,
+    memory_cost: Option<u32>,
+    time_cost: Option<u32>,
+    parallelism: Option<u32>,
+) -> Result<()> {
+    let keystore_path = crate::keystore::get_keystore_path()?;
+    let mut keystore = load_keystore(&keystore_path)?;
+
+    let account = keystore
+        .list_accounts()
+        .into_iter()
+        .find(|a| a.name == name)
+        .ok_or_else(|| anyhow::anyhow!("Account '{}' not found", name))?;
+    if !account.backend.is_keystore() {
+        anyhow::bail!(
+            "Account '{}' is held by the {}, not the keystore file",
+            name,
+            account.backend
+        );
+    }
+
+    let current = account.kdf.unwrap_or(KdfParams::V1);
+    let default = KdfParams::default();
+    let kdf = KdfParams {
+        m_cost: memory_cost.unwrap_or(current.m_cost.max(default.m_cost)),
+        t_cost: time_cost.unwrap_or(current.t_cost.max(default.t_cost)),
+        p_cost: parallelism.unwrap_or(current.p_cost.max(default.p_cost)),
+    };
+    kdf.validate()?;
+
+    println!("\n{}", "🔑 Rekey Account".cyan().bold());
+    println!("{}", "═══════════════════════════════════════".dimmed());
+    println!("{}: {}", "Name".cyan(), name);
+    println!("{}: {}", "Current KDF".cyan(), current);
+    println!("{}: {}", "New KDF".cyan(), kdf);
+    if kdf.is_weaker_than_default() {
+        println!(
+            "{}",
+            "⚠ These parameters are weaker than the defaults for new accounts".yellow()
+        );
+    }
+    println!();
+
+    let password = crate::signer::read_password(name)?;
+    let new_password = if change_password {
+        let new_password = zeroize::Zeroizing::new(
+            rpassword::prompt_password("Enter new password: ")
+                .context("Failed to read password")?,
+        );
+        let confirm = zeroize::Zeroizing::new(
+            rpassword::prompt_password("Confirm new password: ")
+                .context("Failed to read password confirmation")?,
+        );
+        if *new_password != *confirm {
+            anyhow::bail!("Passwords do not match");
+        }
+        Some(new_password)
+    } else {
+        None
+    };
+
+    keystore.rekey(
+        name,
+        &password,
+        new_password.as_deref().map(String::as_str),
+        kdf,
+    )?;
+    keystore.save(&keystore_path)?;
+
+    println!("\n{}", "Account Rekeyed Successfully".green().bold());
+    println!("{}: {}", "Name".cyan(), name);
+    println!("{}: {}", "KDF".cyan(), kdf);
+    if new_password.is_some() {
+        println!("{}: changed", "Password".cyan());
+    }
+
+    Ok(())
+}
+
+/// Register an EVM account held by a remote JSON-RPC signer
+///
+/// Without an address, the signer must manage exactly one account.
+pub async fn add_remote_account(name: String, url: &str, address: Option<&str>) -> Result<()> {
+    use alloy::primitives::Address;
+
+    let keystore_path = crate::keystore::get_keystore_path()?;
+    let mut keystore = load_keystore(&keystore_path)?;
+    if keystore.has_account(&name) {
+        anyhow::bail!("Account with name '{}' already exists", name);
+    }
+
+    println!("\n{}", "🌐 Add Remote Signer Account".cyan().bold());
+    println!("{}", "═══════════════════════════════════════".dimmed());
+
+    let accounts = crate::signer::RemoteSigner::accounts(url).await?;
+    let address: Address = match address {
+        Some(address) => {
+            let address: Address = address.parse().context("Invalid EVM address")?;
+            if !accounts.contains(&address) {
+                anyhow::bail!("Remote signer at {} does not manage {:?}", url, address);
+            }
+            address
+        }
+        None => match accounts.as_slice() {
+            [address] => *address,
+            [] => anyhow::bail!("Remote signer at {} has no accounts", url),
+            _ => anyhow::bail!(
+                "Remote signer at {} manages several accounts, pick one with --address:\n  {}",
+                url,
+                accounts
+                    .iter()
+                    .map(|a| format!("{:?}", a))
+                    .collect::<Vec<_>>()
+                    .join("\n  ")
+            ),
+        },
+    };
+    let address = format!("{:?}", address);
+
+    keystore.add_external_account(
+        name.clone(),
+        AccountType::Evm,
+        address.clone(),
+        SecretKind::PrivateKey,
+        SignerBackend::Remote {
+            url: url.to_string(),
+        },
+    )?;
+    keystore.save(&keystore_path)?;
+
+    println!("\n{}", "Account Added Successfully".green().bold());
+    println!("{}: {}", "Name".cyan(), name);
+    println!("{}: {}", "Address".cyan(), address);
+    println!("{}: {}", "Signer".cyan(), url);
+
+    Ok(())
+}
+
+/// Hand an account's key to the session agent so commands stop prompting
+/// for it until the timeout expires
+pub fn unlock_account(name: &str, timeout: Option<u64>) -> Result<()> {
+    let keystore_path = crate::keystore::get_keystore_path()?;
+    let mut keystore = load_keystore(&keystore_path)?;
+
+    let root = keystore.root_account(name)?.clone();
+    if let SignerBackend::Remote { url } = &root.backend {
+        anyhow::bail!(
+            "Account '{}' is held by the remote signer at {}, which handles its own unlocking",
+            name,
+            url
+        );
+    }
+
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => {
+            crate::config::Config::load(&crate::config::get_config_path()?)?
+                .preferences
+                .unlock_timeout
+        }
+    };
+    if timeout == 0 {
+        anyhow::bail!("Unlock timeout must be at least one second");
+    }
+
+    let secret = crate::signer::load_secret_data(&mut keystore, &root)?;
+    crate::agent::cache_secret(&root.name, &secret, std::time::Duration::from_secs(timeout))?;
+
+    println!("\n{}", "Account Unlocked".green().bold());
+    println!("{}: {}", "Name".cyan(), root.name);
+    println!("{}: {}", "Expires In".cyan(), format_duration(timeout));
+
+    let derived: Vec<&str> = keystore
+        .list_accounts()
+        .into_iter()
+        .filter(|a| a.parent.as_deref() == Some(root.name.as_str()))
+        .map(|a| a.name.as_str())
+        .collect();
+    if !derived.is_empty() {
+        println!("{}: {}", "Also Unlocks".cyan(), derived.join(", "));
+    }
     println!(
-        "{}",
-        "Warning: This will display your secret mnemonic!".red()
+        "\n{}",
+        format!("Lock it again with: apex account lock {}", root.name).dimmed()
     );
 
-    let password =
-        rpassword::prompt_password("Enter password: ").context("Failed to read password")?;
+    Ok(())
+}
 
-    let mnemonic_bytes = keystore.get_account(name, &password)?;
-    let mnemonic = String::from_utf8(mnemonic_bytes).context("Failed to decode mnemonic")?;
+/// Drop one account, or all accounts, from the session agent
+pub fn lock_accounts(name: Option<&str>) -> Result<()> {
+    let root = match name {
+        Some(name) => {
+            let keystore = load_keystore(&crate::keystore::get_keystore_path()?)?;
+            Some(keystore.root_account(name)?.name.clone())
+        }
+        None => None,
+    };
 
-    println!("\n{}: {}", "Mnemonic".yellow().bold(), mnemonic);
-    println!("\n{}", "Security Reminder:".red().bold());
-    println!("• Never share this mnemonic with anyone");
-    println!("• Clear your terminal history after viewing");
-    println!("• Make sure no one is looking over your shoulder");
+    match (crate::agent::forget(root.as_deref())?, root) {
+        (0, _) => println!("{}", "No unlocked accounts to lock".yellow()),
+        (_, Some(name)) => println!("{} {}", "Locked".green().bold(), name),
+        (count, None) => println!("{} {} account(s)", "Locked".green().bold(), count),
+    }
 
     Ok(())
 }
 
+/// Human-readable duration, e.g. `1h 30m` or `45s`
+fn format_duration(secs: u64) -> String {
+    let (hours, minutes, seconds) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    match (hours, minutes, seconds) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, 0) => format!("{}m", m),
+        (0, m, s) => format!("{}m {}s", m, s),
+        (h, 0, _) => format!("{}h", h),
+        (h, m, _) => format!("{}h {}m", h, m),
+    }
+}
+
 /// Remove an account
 pub fn remove_account(name: &str) -> Result<()> {
     let keystore_path = crate::keystore::get_keystore_path()?;
-    let mut keystore = Keystore::load(&keystore_path)?;
+    let mut keystore = load_keystore(&keystore_path)?;
 
-    if !keystore.has_account(name) {
-        anyhow::bail!("Account '{}' not found", name);
-    }
+    let backend = keystore
+        .list_accounts()
+        .into_iter()
+        .find(|a| a.name == name)
+        .map(|a| a.backend.clone())
+        .ok_or_else(|| anyhow::anyhow!("Account '{}' not found", name))?;
 
     println!("\n{}", "🗑️  Remove Account".red().bold());
     println!("{}", "═══════════════════════════════════════".dimmed());
@@ -300,6 +877,11 @@ pub fn remove_account(name: &str) -> Result<()> {
     keystore.remove_account(name)?;
     keystore.save(&keystore_path)?;
 
+    if backend == SignerBackend::Keychain {
+        crate::signer::keychain::delete(name)?;
+    }
+    crate::agent::forget(Some(name))?;
+
     println!("\n{}", "Account removed successfully".green());
 
     Ok(())
@@ -317,6 +899,35 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_derivation_paths() {
+        assert_eq!(
+            default_derivation_path(&AccountType::Evm, 3),
+            "m/44'/60'/0'/0/3"
+        );
+        assert_eq!(default_derivation_path(&AccountType::Substrate, 3), "//3");
+
+        let mut paths = vec!["m/44'/60'/0'/0/10", "m/44'/60'/0'/0/2", "m/44'/60'/1'/0/0"];
+        paths.sort_by_key(|p| derivation_sort_key(p));
+        assert_eq!(
+            paths,
+            vec!["m/44'/60'/0'/0/2", "m/44'/60'/0'/0/10", "m/44'/60'/1'/0/0"]
+        );
+
+        let mut paths = vec!["//stash", "//10", "//2"];
+        paths.sort_by_key(|p| derivation_sort_key(p));
+        assert_eq!(paths, vec!["//2", "//10", "//stash"]);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(900), "15m");
+        assert_eq!(format_duration(90), "1m 30s");
+        assert_eq!(format_duration(3600), "1h");
+        assert_eq!(format_duration(5400), "1h 30m");
+    }
+
     #[test]
     fn test_validate_mnemonic() {
         let valid_mnemonic =
@@ -328,4 +939,23 @@ mod tests {
         let result: Result<bip39::Mnemonic, _> = invalid_mnemonic.parse();
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_write_private_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("key.json");
+
+        write_private_file(&path, "{}").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // An existing file is never overwritten
+        assert!(write_private_file(&path, "[]").is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "{}");
+    }
 }
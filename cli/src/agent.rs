@@ -0,0 +1,519 @@
+//! Session agent caching unlocked account keys
+//!
+//! `apex account unlock` hands an account's decrypted secret to the agent,
+//! starting it in the background (the hidden `apex agent` command) if it is
+//! not running. Later commands fetch the secret over a Unix socket instead of
+//! prompting, until the unlock timeout (`preferences.unlock_timeout`) expires.
+//! The agent exits once nothing is cached.
+//!
+//! The socket is only accessible to its owner. Like the keystore itself, the
+//! agent does not protect against other processes running as the same user.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Environment variable overriding the agent socket path
+pub const SOCKET_ENV: &str = "APEX_AGENT_SOCK";
+
+/// Seconds an agent with nothing cached stays up before exiting
+const IDLE_EXIT_SECS: u64 = 10;
+
+/// Seconds to wait for a freshly spawned agent to listen
+const SPAWN_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Request {
+    /// Cache a hex-encoded secret for `timeout_secs`
+    Add {
+        name: String,
+        secret: String,
+        timeout_secs: u64,
+    },
+    Get {
+        name: String,
+    },
+    /// Forget one account, or all of them
+    Remove {
+        name: Option<String>,
+    },
+    List,
+}
+
+impl Drop for Request {
+    fn drop(&mut self) {
+        if let Request::Add { secret, .. } = self {
+            secret.zeroize();
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Response {
+    ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    secret: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    accounts: Option<Vec<CachedAccount>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    removed: Option<usize>,
+}
+
+/// An account held by the agent
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CachedAccount {
+    pub name: String,
+    pub expires_in_secs: u64,
+}
+
+/// Secrets held by the agent, keyed by root account name
+#[derive(Default)]
+struct Cache {
+    entries: HashMap<String, (Zeroizing<Vec<u8>>, Instant)>,
+}
+
+impl Cache {
+    fn handle(&mut self, request: &Request) -> Response {
+        self.purge();
+
+        match request {
+            Request::Add {
+                name,
+                secret,
+                timeout_secs,
+            } => match hex::decode(secret) {
+                Ok(secret) => {
+                    let expires = Instant::now() + Duration::from_secs(*timeout_secs);
+                    self.entries
+                        .insert(name.clone(), (Zeroizing::new(secret), expires));
+                    Response::ok()
+                }
+                Err(_) => Response::error("Invalid secret encoding"),
+            },
+            Request::Get { name } => match self.entries.get(name) {
+                Some((secret, _)) => Response {
+                    secret: Some(hex::encode(secret.as_slice())),
+                    ..Response::ok()
+                },
+                None => Response::error(format!("Account '{}' is not unlocked", name)),
+            },
+            Request::Remove { name } => {
+                let removed = match name {
+                    Some(name) => usize::from(self.entries.remove(name).is_some()),
+                    None => self.entries.drain().count(),
+                };
+                Response {
+                    removed: Some(removed),
+                    ..Response::ok()
+                }
+            }
+            Request::List => {
+                let now = Instant::now();
+                let mut accounts: Vec<CachedAccount> = self
+                    .entries
+                    .iter()
+                    .map(|(name, (_, expires))| CachedAccount {
+                        name: name.clone(),
+                        expires_in_secs: expires.saturating_duration_since(now).as_secs(),
+                    })
+                    .collect();
+                accounts.sort_by(|a, b| a.name.cmp(&b.name));
+                Response {
+                    accounts: Some(accounts),
+                    ..Response::ok()
+                }
+            }
+        }
+    }
+
+    /// Drop expired secrets
+    fn purge(&mut self) {
+        let now = Instant::now();
+        self.entries.retain(|_, (_, expires)| *expires > now);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Response {
+    fn ok() -> Self {
+        Self {
+            ok: true,
+            ..Default::default()
+        }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            error: Some(message.into()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Path of the agent socket: `APEX_AGENT_SOCK`, else `apex-sdk/agent.sock`
+/// in the runtime directory (falling back to the config directory)
+///
+/// The agent makes the socket's directory private to the owner, creating it
+/// if needed, so a directory given in `APEX_AGENT_SOCK` must be yours.
+pub fn socket_path() -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os(SOCKET_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+
+    let dir = dirs::runtime_dir()
+        .or_else(dirs::config_dir)
+        .ok_or_else(|| anyhow::anyhow!("Could not determine runtime directory"))?;
+    Ok(dir.join("apex-sdk").join("agent.sock"))
+}
+
+/// Cached secret of a root account, if the agent is running and holds it
+pub fn cached_secret(name: &str) -> Option<Zeroizing<Vec<u8>>> {
+    let path = socket_path().ok()?;
+    let response = request_at(
+        &path,
+        &Request::Get {
+            name: name.to_string(),
+        },
+    )
+    .ok()?;
+
+    let secret = Zeroizing::new(response.secret?);
+    hex::decode(secret.as_str()).ok().map(Zeroizing::new)
+}
+
+/// Hand a root account's secret to the agent, starting it if needed
+pub fn cache_secret(name: &str, secret: &[u8], timeout: Duration) -> Result<()> {
+    let path = socket_path()?;
+    if request_at(&path, &Request::List).is_err() {
+        spawn(&path)?;
+    }
+
+    request_at(
+        &path,
+        &Request::Add {
+            name: name.to_string(),
+            secret: hex::encode(secret),
+            timeout_secs: timeout.as_secs(),
+        },
+    )?;
+    Ok(())
+}
+
+/// Forget one account or all of them, returning how many were cached
+///
+/// Succeeds with 0 when no agent is running.
+pub fn forget(name: Option<&str>) -> Result<usize> {
+    let path = socket_path()?;
+    if !path.exists() {
+        return Ok(0);
+    }
+
+    match request_at(
+        &path,
+        &Request::Remove {
+            name: name.map(str::to_string),
+        },
+    ) {
+        Ok(response) => Ok(response.removed.unwrap_or_default()),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Accounts the agent currently holds; empty when no agent is running
+pub fn list() -> Result<Vec<CachedAccount>> {
+    let path = socket_path()?;
+    match request_at(&path, &Request::List) {
+        Ok(response) => Ok(response.accounts.unwrap_or_default()),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+/// Start a detached agent process listening on `path` and wait until it is up
+#[cfg(unix)]
+fn spawn(path: &Path) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+
+    Command::new(std::env::current_exe().context("Failed to locate the apex executable")?)
+        .arg("agent")
+        .arg("--socket")
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        // Own process group, so Ctrl-C in this terminal doesn't stop it
+        .process_group(0)
+        .spawn()
+        .context("Failed to start the session agent")?;
+
+    let deadline = Instant::now() + Duration::from_secs(SPAWN_TIMEOUT_SECS);
+    while Instant::now() < deadline {
+        if request_at(path, &Request::List).is_ok() {
+            return Ok(());
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    anyhow::bail!(
+        "Session agent did not start listening on {}",
+        path.display()
+    )
+}
+
+#[cfg(not(unix))]
+fn spawn(_path: &Path) -> Result<()> {
+    anyhow::bail!("The session agent needs Unix domain sockets, which this platform lacks")
+}
+
+#[cfg(unix)]
+fn request_at(path: &Path, request: &Request) -> Result<Response> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(path).context("Session agent is not running")?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut line = Zeroizing::new(serde_json::to_string(request)?);
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+
+    let mut reply = Zeroizing::new(String::new());
+    BufReader::new(&stream)
+        .read_line(&mut reply)
+        .context("Failed to read session agent response")?;
+    let response: Response =
+        serde_json::from_str(&reply).context("Invalid session agent response")?;
+
+    if !response.ok {
+        anyhow::bail!(
+            "{}",
+            response
+                .error
+                .clone()
+                .unwrap_or_else(|| "Session agent request failed".to_string())
+        );
+    }
+    Ok(response)
+}
+
+#[cfg(not(unix))]
+fn request_at(_path: &Path, _request: &Request) -> Result<Response> {
+    anyhow::bail!("The session agent needs Unix domain sockets, which this platform lacks")
+}
+
+/// Run the agent on `path` until nothing has been cached for a while
+#[cfg(unix)]
+pub async fn serve(path: &Path) -> Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    // The socket is reachable by others until its mode is set below, so keep
+    // it in a directory only the owner can enter. An existing directory may be
+    // shared: it is made private too, which only its owner is allowed to do.
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        if !std::fs::symlink_metadata(dir)?.is_dir() {
+            anyhow::bail!(
+                "Agent socket directory {} is not a directory",
+                dir.display()
+            );
+        }
+        std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700)).with_context(
+            || {
+                format!(
+                    "Agent socket directory {} must be owned by the current user",
+                    dir.display()
+                )
+            },
+        )?;
+    }
+    if path.exists() {
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            anyhow::bail!("A session agent is already listening on {}", path.display());
+        }
+        std::fs::remove_file(path).context("Failed to remove stale agent socket")?;
+    }
+
+    let listener = tokio::net::UnixListener::bind(path)
+        .with_context(|| format!("Failed to listen on {}", path.display()))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+    let mut cache = Cache::default();
+    let mut last_request = Instant::now();
+    let mut tick = tokio::time::interval(Duration::from_secs(1));
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let Ok((stream, _)) = accepted else { continue };
+                last_request = Instant::now();
+
+                let handled = tokio::time::timeout(Duration::from_secs(5), async {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut line = Zeroizing::new(String::new());
+                    BufReader::new(reader).read_line(&mut line).await?;
+
+                    let mut response = match serde_json::from_str::<Request>(&line) {
+                        Ok(request) => cache.handle(&request),
+                        Err(e) => Response::error(format!("Invalid request: {}", e)),
+                    };
+                    let mut reply = Zeroizing::new(serde_json::to_string(&response)?);
+                    if let Some(secret) = &mut response.secret {
+                        secret.zeroize();
+                    }
+                    reply.push('\n');
+                    writer.write_all(reply.as_bytes()).await?;
+                    anyhow::Ok(())
+                })
+                .await;
+                if let Ok(Err(e)) = handled {
+                    tracing::debug!("Session agent request failed: {}", e);
+                }
+            }
+            _ = tick.tick() => {
+                cache.purge();
+                if cache.is_empty() && last_request.elapsed() >= Duration::from_secs(IDLE_EXIT_SECS) {
+                    break;
+                }
+            }
+        }
+    }
+
+    let _ = std::fs::remove_file(path);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub async fn serve(_path: &Path) -> Result<()> {
+    anyhow::bail!("The session agent needs Unix domain sockets, which this platform lacks")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_expiry() {
+        let mut cache = Cache::default();
+
+        let added = cache.handle(&Request::Add {
+            name: "alice".to_string(),
+            secret: hex::encode(b"secret"),
+            timeout_secs: 60,
+        });
+        assert!(added.ok);
+        let expired = cache.handle(&Request::Add {
+            name: "bob".to_string(),
+            secret: hex::encode(b"other"),
+            timeout_secs: 0,
+        });
+        assert!(expired.ok);
+
+        let got = cache.handle(&Request::Get {
+            name: "alice".to_string(),
+        });
+        assert_eq!(got.secret.as_deref(), Some(hex::encode(b"secret").as_str()));
+        assert!(
+            !cache
+                .handle(&Request::Get {
+                    name: "bob".to_string()
+                })
+                .ok
+        );
+
+        let listed = cache.handle(&Request::List);
+        let accounts = listed.accounts.as_ref().unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].name, "alice");
+
+        let removed = cache.handle(&Request::Remove { name: None });
+        assert_eq!(removed.removed, Some(1));
+        assert!(cache.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_agent_socket_round_trip() {
+        use std::os::unix::fs::DirBuilderExt;
+
+        // An existing directory others can enter is made private
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("apex-sdk").join("agent.sock");
+        std::fs::DirBuilder::new()
+            .mode(0o755)
+            .create(path.parent().unwrap())
+            .unwrap();
+
+        let server = tokio::spawn({
+            let path = path.clone();
+            async move { serve(&path).await }
+        });
+
+        let client_path = path.clone();
+        tokio::task::spawn_blocking(move || {
+            let deadline = Instant::now() + Duration::from_secs(SPAWN_TIMEOUT_SECS);
+            while request_at(&client_path, &Request::List).is_err() {
+                assert!(Instant::now() < deadline, "agent did not start");
+                std::thread::sleep(Duration::from_millis(20));
+            }
+
+            request_at(
+                &client_path,
+                &Request::Add {
+                    name: "alice".to_string(),
+                    secret: hex::encode(b"secret"),
+                    timeout_secs: 60,
+                },
+            )
+            .unwrap();
+            let response = request_at(
+                &client_path,
+                &Request::Get {
+                    name: "alice".to_string(),
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                response.secret.as_deref(),
+                Some(hex::encode(b"secret").as_str())
+            );
+
+            let missing = request_at(
+                &client_path,
+                &Request::Get {
+                    name: "bob".to_string(),
+                },
+            );
+            assert!(missing.is_err());
+        })
+        .await
+        .unwrap();
+
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+            let mode = std::fs::metadata(path.parent().unwrap())
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+
+        // A second agent on the same socket refuses to start
+        assert!(serve(&path).await.is_err());
+        server.abort();
+    }
+}
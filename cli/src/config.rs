@@ -23,18 +23,26 @@ pub struct Preferences {
     pub progress_bars: bool,
     #[serde(default)]
     pub log_level: String,
+    /// Seconds `apex account unlock` keeps an account unlocked
+    #[serde(default = "default_unlock_timeout")]
+    pub unlock_timeout: u64,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_unlock_timeout() -> u64 {
+    900
+}
+
 impl Default for Preferences {
     fn default() -> Self {
         Self {
             color_output: true,
             progress_bars: true,
             log_level: "info".to_string(),
+            unlock_timeout: default_unlock_timeout(),
         }
     }
 }
@@ -195,6 +203,11 @@ impl Config {
                 }
                 self.preferences.log_level = value.to_string();
             }
+            "preferences.unlock_timeout" => {
+                self.preferences.unlock_timeout = value
+                    .parse()
+                    .context("Invalid number of seconds for unlock_timeout")?;
+            }
             key if key.starts_with("endpoints.") => {
                 let chain = key.strip_prefix("endpoints.").unwrap();
                 self.endpoints.insert(chain.to_string(), value.to_string());
@@ -206,6 +219,34 @@ impl Config {
         Ok(())
     }
 
+    /// Resolve the chain name and endpoint to use for a command
+    ///
+    /// The chain defaults to `default_chain`. An explicit endpoint wins over the
+    /// chain's entry in `endpoints`; `default_endpoint` is the last resort for
+    /// the default chain only.
+    pub fn resolve_endpoint(
+        &self,
+        chain: Option<&str>,
+        endpoint: Option<&str>,
+    ) -> Result<(String, String)> {
+        let chain = chain.unwrap_or(&self.default_chain).to_string();
+
+        if let Some(endpoint) = endpoint {
+            return Ok((chain, endpoint.to_string()));
+        }
+
+        match self.endpoints.get(&chain) {
+            Some(endpoint) => Ok((chain, endpoint.clone())),
+            None if chain == self.default_chain => Ok((chain, self.default_endpoint.clone())),
+            None => anyhow::bail!(
+                "No endpoint configured for chain '{}'.\n\
+                 Pass --endpoint or add one with: apex config set endpoints.{} <url>",
+                chain,
+                chain
+            ),
+        }
+    }
+
     /// Get a configuration value
     pub fn get(&self, key: &str) -> Result<String> {
         match key {
@@ -218,6 +259,7 @@ impl Config {
             "preferences.color_output" => Ok(self.preferences.color_output.to_string()),
             "preferences.progress_bars" => Ok(self.preferences.progress_bars.to_string()),
             "preferences.log_level" => Ok(self.preferences.log_level.clone()),
+            "preferences.unlock_timeout" => Ok(self.preferences.unlock_timeout.to_string()),
             key if key.starts_with("endpoints.") => {
                 let chain = key.strip_prefix("endpoints.").unwrap();
                 self.endpoints
@@ -273,6 +315,11 @@ mod tests {
 
         config.set("preferences.log_level", "debug").unwrap();
         assert_eq!(config.get("preferences.log_level").unwrap(), "debug");
+
+        assert_eq!(config.get("preferences.unlock_timeout").unwrap(), "900");
+        config.set("preferences.unlock_timeout", "60").unwrap();
+        assert_eq!(config.preferences.unlock_timeout, 60);
+        assert!(config.set("preferences.unlock_timeout", "soon").is_err());
     }
 
     #[test]
@@ -288,6 +335,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resolve_endpoint() {
+        let config = Config::default();
+
+        let (chain, endpoint) = config.resolve_endpoint(None, None).unwrap();
+        assert_eq!(chain, "paseo");
+        assert_eq!(endpoint, "wss://paseo.rpc.amforc.com");
+
+        let (chain, endpoint) = config.resolve_endpoint(Some("sepolia"), None).unwrap();
+        assert_eq!(chain, "sepolia");
+        assert!(endpoint.starts_with("https://"));
+
+        let (_, endpoint) = config
+            .resolve_endpoint(Some("ethereum"), Some("http://localhost:8545"))
+            .unwrap();
+        assert_eq!(endpoint, "http://localhost:8545");
+
+        assert!(config.resolve_endpoint(Some("unknown"), None).is_err());
+    }
+
     #[test]
     fn test_config_validation() {
         let mut config = Config::default();
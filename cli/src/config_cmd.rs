@@ -43,6 +43,11 @@ pub fn show_config() -> Result<()> {
         config.preferences.progress_bars
     );
     println!("  {}: {}", "log_level".cyan(), config.preferences.log_level);
+    println!(
+        "  {}: {}s",
+        "unlock_timeout".cyan(),
+        config.preferences.unlock_timeout
+    );
 
     if !config.endpoints.is_empty() {
         println!("\n{}", "Configured Endpoints:".yellow().bold());
@@ -246,6 +251,7 @@ pub async fn init_config_interactive() -> Result<()> {
             color_output,
             progress_bars,
             log_level,
+            ..Default::default()
         },
         ..Default::default()
     };
@@ -308,6 +314,7 @@ mod tests {
                 color_output: true,
                 progress_bars: false,
                 log_level: "debug".to_string(),
+                ..Default::default()
             },
             endpoints: std::collections::HashMap::new(),
         };
@@ -408,6 +415,7 @@ mod tests {
             color_output: false,
             progress_bars: true,
             log_level: "trace".to_string(),
+            ..Default::default()
         };
 
         assert!(!preferences.color_output);
@@ -436,6 +444,7 @@ mod tests {
                 color_output: false,
                 progress_bars: true,
                 log_level: "warn".to_string(),
+                ..Default::default()
             },
             endpoints,
         };
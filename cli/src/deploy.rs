@@ -5,6 +5,8 @@ use apex_sdk_types::Chain;
 use colored::Colorize;
 use std::path::Path;
 
+use crate::keystore::AccountType;
+
 /// Deploy a contract
 pub async fn deploy_contract(
     contract_path: &str,
@@ -34,7 +36,7 @@ async fn deploy_substrate_contract(
     account_name: Option<String>,
     dry_run: bool,
 ) -> Result<()> {
-    use sp_core::{crypto::Ss58Codec, sr25519, Pair};
+    use sp_core::{crypto::Ss58Codec, Pair};
     use subxt::{OnlineClient, PolkadotConfig};
 
     let title = if dry_run {
@@ -96,24 +98,17 @@ async fn deploy_substrate_contract(
     spinner.set_message("Reading contract file...");
     spinner.enable_steady_tick(std::time::Duration::from_millis(100));
 
-    let contract_code = std::fs::read(contract_path).context("Failed to read contract file")?;
+    let contract_code = load_ink_code(path)?;
 
     spinner.set_message(format!("Contract size: {} bytes", contract_code.len()));
 
     // Get account for signing
-    let (signer_name, mnemonic) = if let Some(name) = account_name {
+    let (signer_name, account) = if let Some(name) = account_name {
         spinner.set_message(format!("Loading account '{}'...", name));
 
-        let password = rpassword::prompt_password("Enter account password: ")
-            .context("Failed to read password")?;
-
-        let keystore_path = crate::keystore::get_keystore_path()?;
-        let mut keystore = crate::keystore::Keystore::load(&keystore_path)?;
-
-        let mnemonic_bytes = keystore.get_account(&name, &password)?;
-        let mnemonic = String::from_utf8(mnemonic_bytes).context("Failed to decode mnemonic")?;
+        let account = crate::signer::unlock(&name, Some(AccountType::Substrate))?;
 
-        (name, mnemonic)
+        (name, account)
     } else {
         spinner.finish_and_clear();
         anyhow::bail!(
@@ -135,11 +130,8 @@ async fn deploy_substrate_contract(
         .await
         .context("Failed to connect to Substrate endpoint")?;
 
-    // Create keypair from mnemonic
-    let mnemonic_obj: bip39::Mnemonic = mnemonic.parse().context("Invalid mnemonic phrase")?;
-    let seed = mnemonic_obj.to_seed("");
-    let pair = sr25519::Pair::from_seed_slice(&seed[..32])
-        .map_err(|e| anyhow::anyhow!("Failed to generate keypair: {:?}", e))?;
+    // Create keypair from the account secret
+    let pair = account.secret()?.substrate_pair()?;
 
     let signer_address = pair.public().to_ss58check();
 
@@ -212,53 +204,16 @@ async fn deploy_substrate_contract(
 
         println!("\n{}", "Uploading contract code...".cyan());
 
-        // Build the upload_code call using dynamic API
-        let upload_call = subxt::dynamic::tx(
-            "Contracts",
-            "upload_code",
-            vec![
-                subxt::dynamic::Value::from_bytes(contract_code.clone()),
-                subxt::dynamic::Value::unnamed_variant("None", vec![]), // storage_deposit_limit
-                subxt::dynamic::Value::unnamed_variant("Enforced", vec![]), // determinism
-            ],
-        );
-
-        // Create signer from seed
-        let seed_bytes: [u8; 32] = seed[..32]
-            .try_into()
-            .map_err(|_| anyhow::anyhow!("Invalid seed length"))?;
-        let signer = subxt_signer::sr25519::Keypair::from_secret_key(seed_bytes)
-            .map_err(|e| anyhow::anyhow!("Failed to create signer: {:?}", e))?;
-
-        // Submit and watch the transaction
-        let tx_progress = api
-            .tx()
-            .sign_and_submit_then_watch_default(&upload_call, &signer)
-            .await
-            .context("Failed to submit upload_code transaction")?;
-
-        let tx_hash = tx_progress.extrinsic_hash();
-        println!("{}: {}", "Transaction Hash".cyan(), tx_hash);
-
-        // Wait for finalization
         println!("{}", "Waiting for finalization...".yellow());
-
-        let events = tx_progress
-            .wait_for_finalized_success()
-            .await
-            .context("Transaction failed or was not finalized")?;
+        let signer = apex_sdk_substrate::signer::Sr25519Signer::new(pair);
+        let upload = upload_ink_code(&api, &contract_code, &signer).await?;
 
         println!("\n{}", "Contract Code Uploaded Successfully".green().bold());
         println!("{}", "═══════════════════════════════════════".dimmed());
-        println!("{}: {}", "Extrinsic Hash".cyan(), events.extrinsic_hash());
+        println!("{}: {}", "Extrinsic Hash".cyan(), upload.extrinsic_hash);
+        println!("{}: {}", "Block Number".dimmed(), upload.block_number);
         println!("{}: {} bytes", "Code Size".dimmed(), contract_code.len());
-
-        // Extract code hash from events
-        let code_hash = format!(
-            "0x{}",
-            hex::encode(&contract_code[..32.min(contract_code.len())])
-        );
-        println!("{}: {}", "Code Hash (approx)".dimmed(), code_hash);
+        println!("{}: {}", "Code Hash".dimmed(), upload.code_hash);
 
         println!("\n{}", "Next Steps:".cyan());
         println!("  - Use Polkadot.js Apps to instantiate the contract");
@@ -273,6 +228,73 @@ async fn deploy_substrate_contract(
     Ok(())
 }
 
+/// Wasm code of an ink! `.contract` bundle, or of a `.wasm` file
+fn load_ink_code(path: &Path) -> Result<Vec<u8>> {
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read contract file {}", path.display()))?;
+    if path.extension().and_then(|s| s.to_str()) != Some("contract") {
+        return Ok(bytes);
+    }
+
+    let bundle: serde_json::Value = serde_json::from_slice(&bytes)
+        .with_context(|| format!("Invalid ink! contract bundle {}", path.display()))?;
+    let wasm = bundle
+        .pointer("/source/wasm")
+        .and_then(|wasm| wasm.as_str())
+        .with_context(|| format!("{} has no source.wasm", path.display()))?;
+    hex::decode(wasm.trim_start_matches("0x"))
+        .with_context(|| format!("Invalid source.wasm in {}", path.display()))
+}
+
+/// ink! code stored on chain by `Contracts::upload_code`
+struct Upload {
+    extrinsic_hash: String,
+    /// blake2-256 of the code, the key the chain stores it under
+    code_hash: String,
+    block_number: u64,
+}
+
+/// `Contracts::upload_code` call for ink! code, built with the dynamic API
+fn ink_upload_call(code: &[u8]) -> subxt::tx::DynamicPayload {
+    subxt::dynamic::tx(
+        "Contracts",
+        "upload_code",
+        vec![
+            subxt::dynamic::Value::from_bytes(code),
+            subxt::dynamic::Value::unnamed_variant("None", vec![]), // storage_deposit_limit
+            subxt::dynamic::Value::unnamed_variant("Enforced", vec![]), // determinism
+        ],
+    )
+}
+
+/// Upload ink! code and wait for it to be finalized
+async fn upload_ink_code(
+    api: &subxt::OnlineClient<subxt::PolkadotConfig>,
+    code: &[u8],
+    signer: &apex_sdk_substrate::signer::Sr25519Signer,
+) -> Result<Upload> {
+    let in_block = api
+        .tx()
+        .sign_and_submit_then_watch_default(&ink_upload_call(code), signer)
+        .await
+        .context("Failed to submit upload_code transaction")?
+        .wait_for_finalized()
+        .await
+        .context("Transaction was not finalized")?;
+    let block_hash = in_block.block_hash();
+    let events = in_block
+        .wait_for_success()
+        .await
+        .context("upload_code transaction failed")?;
+    let block = api.blocks().at(block_hash).await?;
+
+    Ok(Upload {
+        extrinsic_hash: format!("{:?}", events.extrinsic_hash()),
+        code_hash: format!("0x{}", hex::encode(sp_core::hashing::blake2_256(code))),
+        block_number: block.number() as u64,
+    })
+}
+
 /// Deploy an EVM contract
 async fn deploy_evm_contract(
     contract_path: &str,
@@ -281,7 +303,7 @@ async fn deploy_evm_contract(
     account_name: Option<String>,
     dry_run: bool,
 ) -> Result<()> {
-    use apex_sdk_evm::{wallet::Wallet, EvmAdapter};
+    use apex_sdk_evm::EvmAdapter;
 
     let title = if dry_run {
         "Dry-Run: EVM Contract Deployment"
@@ -365,19 +387,12 @@ async fn deploy_evm_contract(
     spinner.enable_steady_tick(std::time::Duration::from_millis(100));
 
     // Get account for signing
-    let (signer_name, mnemonic) = if let Some(name) = account_name {
+    let (signer_name, account) = if let Some(name) = account_name {
         spinner.set_message(format!("Loading account '{}'...", name));
 
-        let password = rpassword::prompt_password("Enter account password: ")
-            .context("Failed to read password")?;
+        let account = crate::signer::unlock(&name, Some(AccountType::Evm))?;
 
-        let keystore_path = crate::keystore::get_keystore_path()?;
-        let mut keystore = crate::keystore::Keystore::load(&keystore_path)?;
-
-        let mnemonic_bytes = keystore.get_account(&name, &password)?;
-        let mnemonic = String::from_utf8(mnemonic_bytes).context("Failed to decode mnemonic")?;
-
-        (name, mnemonic)
+        (name, account)
     } else {
         spinner.finish_and_clear();
         anyhow::bail!(
@@ -399,9 +414,8 @@ async fn deploy_evm_contract(
         .await
         .context("Failed to connect to EVM endpoint")?;
 
-    // Create wallet from mnemonic using apex-sdk-evm
-    let wallet =
-        Wallet::from_mnemonic(&mnemonic, 0).context("Failed to create wallet from mnemonic")?;
+    // Create wallet from the account secret using apex-sdk-evm
+    let wallet = account.secret()?.evm_wallet()?;
 
     // Get chain ID from provider
     let chain_id = adapter.provider().chain_id();
@@ -587,6 +601,486 @@ async fn deploy_evm_contract(
     Ok(())
 }
 
+/// What `apex deploy --env` does with a contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Action {
+    /// The locked deployment matches and is still on chain; holds its address,
+    /// or the code hash of ink! code
+    Unchanged(String),
+    Deploy,
+}
+
+/// A contract of the deployment plan with its loaded artifact
+struct Step<'a> {
+    name: &'a str,
+    action: Action,
+    bytecode: Vec<u8>,
+    abi: Option<apex_sdk_evm::abi::ContractAbi>,
+}
+
+/// Deploy a manifest environment: contracts are deployed in dependency order,
+/// unchanged ones are skipped and every deployment is recorded in the lockfile
+pub async fn deploy_environment(
+    manifest_path: &Path,
+    env_name: &str,
+    account: Option<String>,
+    dry_run: bool,
+    force: bool,
+    yes: bool,
+) -> Result<()> {
+    use crate::manifest::{link_args, Deployment, Lockfile, Manifest};
+    use crate::tx::{Target, TxOptions, TxReport};
+    use alloy::primitives::{keccak256, Address, U256};
+    use alloy::providers::Provider;
+    use std::collections::BTreeMap;
+
+    let manifest = Manifest::load(manifest_path)?;
+    let env = manifest.environment(env_name)?;
+    let target = Target::resolve(env.chain.as_deref(), env.endpoint.as_deref())?;
+    if target.is_substrate {
+        return deploy_ink_environment(
+            manifest_path,
+            &manifest,
+            env_name,
+            &target,
+            account,
+            dry_run,
+            force,
+            yes,
+        )
+        .await;
+    }
+
+    let lock_path = Lockfile::path_for(manifest_path);
+    let mut lockfile = Lockfile::load(&lock_path)?;
+    let base = manifest_path.parent().unwrap_or(Path::new("."));
+    let order = manifest.deployment_order(env)?;
+
+    let provider = apex_sdk_evm::EvmProvider::new(&target.endpoint)
+        .await
+        .context("Failed to connect to EVM endpoint")?;
+    let chain_id = provider.chain_id();
+
+    // Plan: a contract is unchanged when its creation code, with the addresses
+    // of its (unchanged) dependencies linked in, matches the lockfile and the
+    // locked address still holds code, e.g. after a local chain restart
+    let mut addresses = BTreeMap::new();
+    let mut steps = Vec::new();
+    for name in &order {
+        let spec = &manifest.contracts[name];
+        let (bytecode, abi) = load_artifact(&base.join(&spec.artifact))?;
+
+        let locked = lockfile
+            .get(env_name, name)
+            .filter(|d| d.chain_id == Some(chain_id) && !force);
+        let mut action = Action::Deploy;
+        if let (Some(locked), Ok(args)) = (locked, link_args(manifest.args(env, name), &addresses))
+        {
+            let code = init_code(&bytecode, abi.as_ref(), &args)
+                .with_context(|| format!("Invalid constructor arguments for {}", name))?;
+            if let Some(locked_address) = &locked.address {
+                let address: Address = locked_address.parse()?;
+                if keccak256(&code).to_string() == locked.code_hash
+                    && !provider.provider.get_code_at(address).await?.is_empty()
+                {
+                    addresses.insert(name.clone(), locked_address.clone());
+                    action = Action::Unchanged(locked_address.clone());
+                }
+            }
+        }
+        steps.push(Step {
+            name,
+            action,
+            bytecode,
+            abi,
+        });
+    }
+
+    let network = ("Chain ID", chain_id.to_string());
+    if !confirm_plan(
+        &manifest,
+        env_name,
+        &target,
+        network,
+        &steps,
+        dry_run || yes,
+    )? {
+        return Ok(());
+    }
+
+    let account_name = account.or_else(|| env.account.clone());
+    let signer = crate::tx::unlock_account(account_name.as_deref(), AccountType::Evm)?;
+    let opts = TxOptions {
+        chain: Some(target.chain.clone()),
+        endpoint: Some(target.endpoint.clone()),
+        account: account_name,
+        dry_run,
+        wait: true,
+        json: false,
+    };
+
+    for step in steps.iter().filter(|step| step.action == Action::Deploy) {
+        let args = match link_args(manifest.args(env, step.name), &addresses) {
+            Ok(args) => args,
+            // Only in dry runs: the dependency was simulated, not deployed
+            Err(dependency) => {
+                println!(
+                    "\n{} {}",
+                    step.name.bold(),
+                    format!("needs {} deployed first; not simulated", dependency).dimmed()
+                );
+                continue;
+            }
+        };
+        let code = init_code(&step.bytecode, step.abi.as_ref(), &args)
+            .with_context(|| format!("Invalid constructor arguments for {}", step.name))?;
+
+        println!(
+            "\n{} {}...",
+            if dry_run { "Simulating" } else { "Deploying" },
+            step.name.bold()
+        );
+        let report = TxReport {
+            call: format!("deploy {}", step.name),
+            ..Default::default()
+        };
+        let report = crate::tx::evm_submit_as(
+            &signer,
+            &target,
+            None,
+            U256::ZERO,
+            code.clone(),
+            step.abi.as_ref().map(|abi| (abi, "constructor")),
+            report,
+            &opts,
+        )
+        .await
+        .with_context(|| format!("Failed to deploy {}", step.name))?;
+
+        if dry_run {
+            if let (Some(gas), Some(fee)) = (report.gas_limit, &report.estimated_fee) {
+                println!("  {}: {}", "Gas Limit".dimmed(), gas);
+                println!("  {}: {} wei", "Estimated Fee".yellow(), fee);
+            }
+            continue;
+        }
+
+        let hash = report.hash.context("Deployment transaction was not sent")?;
+        let receipt = provider
+            .provider
+            .get_transaction_receipt(hash.parse()?)
+            .await?
+            .with_context(|| format!("No receipt for {}", hash))?;
+        if !receipt.status() {
+            anyhow::bail!("Deployment of {} reverted in {}", step.name, hash);
+        }
+        let address = receipt
+            .contract_address
+            .context("Receipt has no contract address")?
+            .to_checksum(None);
+
+        println!("  {}: {}", "Contract Address".green().bold(), address);
+        println!("  {}: {}", "Transaction Hash".cyan(), hash);
+
+        addresses.insert(step.name.to_string(), address.clone());
+        lockfile.insert(
+            env_name,
+            step.name,
+            Deployment {
+                address: Some(address),
+                tx_hash: hash,
+                code_hash: keccak256(&code).to_string(),
+                chain_id: Some(chain_id),
+                genesis_hash: None,
+                block_number: receipt.block_number,
+                deployed_at: chrono::Utc::now().to_rfc3339(),
+            },
+        );
+        // Save after every deployment so a failure later keeps the progress
+        lockfile.save(&lock_path)?;
+    }
+
+    if dry_run {
+        println!("\n{}", "Dry-Run Complete".green().bold());
+    } else {
+        println!(
+            "\n{} {}",
+            "Deployments recorded in".green().bold(),
+            lock_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Print the deployment plan and ask for confirmation, returning whether to
+/// go on; `network` is the label and value identifying the chain
+fn confirm_plan(
+    manifest: &crate::manifest::Manifest,
+    env_name: &str,
+    target: &crate::tx::Target,
+    network: (&str, String),
+    steps: &[Step<'_>],
+    skip_prompt: bool,
+) -> Result<bool> {
+    println!(
+        "\n{}",
+        format!(
+            "Deployment Plan: {}{} ({})",
+            manifest
+                .project
+                .name
+                .as_ref()
+                .map(|name| format!("{} / ", name))
+                .unwrap_or_default(),
+            env_name,
+            target.chain
+        )
+        .cyan()
+        .bold()
+    );
+    println!("{}", "═══════════════════════════════════════".dimmed());
+    println!("{}: {}", "Endpoint".dimmed(), target.endpoint);
+    println!("{}: {}", network.0.dimmed(), network.1);
+    for step in steps {
+        match &step.action {
+            Action::Unchanged(address) => {
+                println!("  {:<24} {} {}", step.name, "unchanged".dimmed(), address)
+            }
+            Action::Deploy => println!("  {:<24} {}", step.name, "deploy".yellow()),
+        }
+    }
+
+    if steps.iter().all(|step| step.action != Action::Deploy) {
+        println!("\n{}", "All contracts are up to date.".green());
+        return Ok(false);
+    }
+
+    if !skip_prompt {
+        print!("\nProceed with deployment? (yes/no): ");
+        std::io::stdout().flush()?;
+
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+
+        if input.trim().to_lowercase() != "yes" {
+            println!("\n{}", "Deployment cancelled.".yellow());
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Deploy a manifest environment on a Substrate chain
+///
+/// ink! code is uploaded as `apex deploy <CONTRACT>` does, without
+/// instantiating it, so these contracts take no constructor arguments.
+#[allow(clippy::too_many_arguments)]
+async fn deploy_ink_environment(
+    manifest_path: &Path,
+    manifest: &crate::manifest::Manifest,
+    env_name: &str,
+    target: &crate::tx::Target,
+    account: Option<String>,
+    dry_run: bool,
+    force: bool,
+    yes: bool,
+) -> Result<()> {
+    use crate::manifest::{Deployment, Lockfile};
+    use subxt::tx::ValidationResult;
+    use subxt::{OnlineClient, PolkadotConfig};
+
+    let env = manifest.environment(env_name)?;
+    let lock_path = Lockfile::path_for(manifest_path);
+    let mut lockfile = Lockfile::load(&lock_path)?;
+    let base = manifest_path.parent().unwrap_or(Path::new("."));
+    let order = manifest.deployment_order(env)?;
+
+    let api = OnlineClient::<PolkadotConfig>::from_url(&target.endpoint)
+        .await
+        .context("Failed to connect to Substrate endpoint")?;
+    if api.metadata().pallet_by_name("Contracts").is_none() {
+        anyhow::bail!(
+            "Chain '{}' does not have the Contracts pallet enabled",
+            target.chain
+        );
+    }
+    let genesis_hash = format!("0x{}", hex::encode(api.genesis_hash()));
+    let storage = api.storage().at_latest().await?;
+
+    // Plan: code is unchanged when its hash matches the lockfile and the chain
+    // still stores it, e.g. after a local chain restart
+    let mut steps = Vec::new();
+    for name in &order {
+        if !manifest.args(env, name).is_empty() {
+            anyhow::bail!(
+                "{} has constructor arguments, but ink! code is uploaded without being instantiated",
+                name
+            );
+        }
+        let code = load_ink_code(&base.join(&manifest.contracts[name].artifact))?;
+        let code_hash = sp_core::hashing::blake2_256(&code);
+
+        let locked = lockfile
+            .get(env_name, name)
+            .filter(|d| d.genesis_hash.as_ref() == Some(&genesis_hash) && !force);
+        let mut action = Action::Deploy;
+        if let Some(locked) = locked {
+            let stored = subxt::dynamic::storage(
+                "Contracts",
+                "PristineCode",
+                vec![subxt::dynamic::Value::from_bytes(code_hash)],
+            );
+            if locked.code_hash == format!("0x{}", hex::encode(code_hash))
+                && storage.fetch(&stored).await?.is_some()
+            {
+                action = Action::Unchanged(locked.code_hash.clone());
+            }
+        }
+        steps.push(Step {
+            name,
+            action,
+            bytecode: code,
+            abi: None,
+        });
+    }
+
+    let network = ("Genesis Hash", genesis_hash.clone());
+    if !confirm_plan(manifest, env_name, target, network, &steps, dry_run || yes)? {
+        return Ok(());
+    }
+
+    let account_name = account.or_else(|| env.account.clone());
+    let account = crate::tx::unlock_account(account_name.as_deref(), AccountType::Substrate)?;
+    let signer =
+        apex_sdk_substrate::signer::Sr25519Signer::new(account.secret()?.substrate_pair()?);
+
+    for step in steps.iter().filter(|step| step.action == Action::Deploy) {
+        if dry_run {
+            println!("\nSimulating {}...", step.name.bold());
+            let tx = api
+                .tx()
+                .create_signed(
+                    &ink_upload_call(&step.bytecode),
+                    &signer,
+                    Default::default(),
+                )
+                .await
+                .context("Failed to sign extrinsic")?;
+            match tx
+                .validate()
+                .await
+                .context("Failed to validate extrinsic")?
+            {
+                ValidationResult::Valid(_) => {}
+                ValidationResult::Invalid(reason) => {
+                    anyhow::bail!("Upload of {} would be rejected: {:?}", step.name, reason)
+                }
+                ValidationResult::Unknown(reason) => {
+                    anyhow::bail!("Upload of {} validity unknown: {:?}", step.name, reason)
+                }
+            }
+            let fee = tx
+                .partial_fee_estimate()
+                .await
+                .context("Failed to estimate fee")?;
+            println!("  {}: {}", "Estimated Fee".yellow(), fee);
+            continue;
+        }
+
+        println!("\nUploading {}...", step.name.bold());
+        let upload = upload_ink_code(&api, &step.bytecode, &signer)
+            .await
+            .with_context(|| format!("Failed to upload {}", step.name))?;
+
+        println!("  {}: {}", "Code Hash".green().bold(), upload.code_hash);
+        println!("  {}: {}", "Extrinsic Hash".cyan(), upload.extrinsic_hash);
+
+        lockfile.insert(
+            env_name,
+            step.name,
+            Deployment {
+                address: None,
+                tx_hash: upload.extrinsic_hash,
+                code_hash: upload.code_hash,
+                chain_id: None,
+                genesis_hash: Some(genesis_hash.clone()),
+                block_number: Some(upload.block_number),
+                deployed_at: chrono::Utc::now().to_rfc3339(),
+            },
+        );
+        // Save after every upload so a failure later keeps the progress
+        lockfile.save(&lock_path)?;
+    }
+
+    if dry_run {
+        println!("\n{}", "Dry-Run Complete".green().bold());
+    } else {
+        println!(
+            "\n{} {}",
+            "Deployments recorded in".green().bold(),
+            lock_path.display()
+        );
+    }
+    Ok(())
+}
+
+/// Creation bytecode of a compiler artifact (`.json`) or raw bytecode file
+/// (`.bin`, `.hex`), with the artifact's ABI
+fn load_artifact(path: &Path) -> Result<(Vec<u8>, Option<apex_sdk_evm::abi::ContractAbi>)> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read artifact {}", path.display()))?;
+
+    let (code, abi) = match path.extension().and_then(|s| s.to_str()) {
+        Some("json") => {
+            let json: serde_json::Value = serde_json::from_str(&text)
+                .with_context(|| format!("Invalid JSON in {}", path.display()))?;
+            // solc and Hardhat store the bytecode as a string, Foundry as `{ "object": ... }`
+            let code = json
+                .get("bytecode")
+                .map(|b| b.get("object").unwrap_or(b))
+                .and_then(|b| b.as_str())
+                .with_context(|| format!("No bytecode in {}", path.display()))?
+                .to_string();
+            let abi = apex_sdk_evm::abi::ContractAbi::from_json(&text)
+                .with_context(|| format!("Invalid ABI in {}", path.display()))?;
+            (code, Some(abi))
+        }
+        _ => (text, None),
+    };
+
+    let code = code.trim();
+    let code = code.strip_prefix("0x").unwrap_or(code);
+    if code.contains("__") {
+        anyhow::bail!(
+            "{} has unlinked library placeholders; link its libraries first",
+            path.display()
+        );
+    }
+    let bytecode =
+        hex::decode(code).with_context(|| format!("Invalid hex bytecode in {}", path.display()))?;
+    if bytecode.is_empty() {
+        anyhow::bail!(
+            "{} has no bytecode (abstract contract or interface?)",
+            path.display()
+        );
+    }
+    Ok((bytecode, abi))
+}
+
+/// Creation code: the bytecode followed by the ABI-encoded constructor arguments
+fn init_code(
+    bytecode: &[u8],
+    abi: Option<&apex_sdk_evm::abi::ContractAbi>,
+    args: &[serde_json::Value],
+) -> Result<Vec<u8>> {
+    let encoded = match abi {
+        Some(abi) => abi.encode_constructor_json(args)?,
+        None if args.is_empty() => Vec::new(),
+        None => anyhow::bail!("Constructor arguments need an artifact with an ABI"),
+    };
+    Ok([bytecode, &encoded].concat())
+}
+
 use std::io::Write;
 
 /// Format wei to ETH (helper function)
@@ -869,6 +1363,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_load_artifact_and_init_code() {
+        let dir = tempdir().unwrap();
+
+        let foundry = dir.path().join("Token.json");
+        fs::write(
+            &foundry,
+            r#"{"abi": [{"type": "constructor", "stateMutability": "nonpayable",
+                         "inputs": [{"name": "supply", "type": "uint256"}]}],
+                "bytecode": {"object": "0x6080"}}"#,
+        )
+        .unwrap();
+        let (bytecode, abi) = load_artifact(&foundry).unwrap();
+        assert_eq!(bytecode, vec![0x60, 0x80]);
+
+        let code = init_code(&bytecode, abi.as_ref(), &[serde_json::json!(7)]).unwrap();
+        assert_eq!(code.len(), 2 + 32);
+        assert_eq!(code[33], 7);
+        assert!(init_code(&bytecode, abi.as_ref(), &[]).is_err());
+
+        let raw = dir.path().join("Oracle.bin");
+        fs::write(&raw, "6080\n").unwrap();
+        let (bytecode, abi) = load_artifact(&raw).unwrap();
+        assert!(abi.is_none());
+        assert!(init_code(&bytecode, None, &[serde_json::json!(1)]).is_err());
+
+        let unlinked = dir.path().join("Lib.bin");
+        fs::write(&unlinked, "0x6080__$abc$__").unwrap();
+        assert!(load_artifact(&unlinked).is_err());
+    }
+
+    #[test]
+    fn test_load_ink_code() {
+        let dir = tempdir().unwrap();
+        let wasm = b"\0asm\x01\0\0\0".to_vec();
+
+        let raw = dir.path().join("flipper.wasm");
+        fs::write(&raw, &wasm).unwrap();
+        assert_eq!(load_ink_code(&raw).unwrap(), wasm);
+
+        // Bundles carry the code next to the metadata, which is not uploaded
+        let bundle = dir.path().join("flipper.contract");
+        fs::write(
+            &bundle,
+            serde_json::json!({
+                "source": {"hash": "0x00", "wasm": format!("0x{}", hex::encode(&wasm))},
+                "spec": {"constructors": []}
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert_eq!(load_ink_code(&bundle).unwrap(), wasm);
+
+        fs::write(&bundle, r#"{"spec": {}}"#).unwrap();
+        assert!(load_ink_code(&bundle).is_err());
+    }
+
     fn is_substrate_endpoint(endpoint: &str) -> bool {
         endpoint.starts_with("ws://") || endpoint.starts_with("wss://")
     }
@@ -0,0 +1,589 @@
+//! Key file formats shared with other wallets
+//!
+//! - Web3 Secret Storage (Ethereum V3 keystore) as used by geth, MetaMask and
+//!   Foundry: scrypt or PBKDF2 key derivation, AES-128-CTR and a Keccak-256 MAC
+//! - Polkadot.js account JSON: scrypt key derivation and an XSalsa20-Poly1305
+//!   sealed PKCS#8 sr25519 key pair
+//!
+//! KDF parameters read from files are bounded before use so a crafted file
+//! cannot make a decryption attempt allocate gigabytes of memory.
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use alloy::primitives::keccak256;
+use anyhow::{Context, Result};
+use base64::Engine;
+use crypto_secretbox::aead::{Aead, KeyInit};
+use crypto_secretbox::XSalsa20Poly1305;
+use serde::{Deserialize, Serialize};
+use sp_core::crypto::Ss58Codec;
+use sp_core::{sr25519, Pair};
+use zeroize::Zeroize;
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+// Web3 export parameters, matching the scrypt cost used by Foundry and ethers
+const WEB3_SCRYPT_LOG_N: u8 = 13;
+const WEB3_SCRYPT_R: u32 = 8;
+const WEB3_SCRYPT_P: u32 = 1;
+const WEB3_DKLEN: usize = 32;
+
+// Polkadot.js only accepts its default scrypt parameters on import
+const PJS_SCRYPT_LOG_N: u8 = 15;
+const PJS_SCRYPT_R: u32 = 8;
+const PJS_SCRYPT_P: u32 = 1;
+const PJS_SALT_SIZE: usize = 32;
+const PJS_NONCE_SIZE: usize = 24;
+const PJS_PARAMS_SIZE: usize = PJS_SALT_SIZE + 12 + PJS_NONCE_SIZE;
+
+// PKCS#8 framing used by the Polkadot.js keyring
+const PKCS8_HEADER: [u8; 16] = [48, 83, 2, 1, 1, 48, 5, 6, 3, 43, 101, 112, 4, 34, 4, 32];
+const PKCS8_DIVIDER: [u8; 5] = [161, 35, 3, 33, 0];
+
+// Upper bounds for KDF parameters read from files
+const MAX_SCRYPT_LOG_N: u8 = 20;
+const MAX_SCRYPT_R: u32 = 32;
+const MAX_SCRYPT_P: u32 = 16;
+const MAX_PBKDF2_ROUNDS: u32 = 10_000_000;
+
+/// Format of an exported key file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFileFormat {
+    /// Ethereum V3 keystore (Web3 Secret Storage)
+    Web3,
+    /// Polkadot.js account JSON
+    PolkadotJs,
+}
+
+impl KeyFileFormat {
+    /// Detect the format of a key file from its JSON structure
+    pub fn detect(json: &str) -> Result<Self> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).context("Key file is not valid JSON")?;
+
+        if value.get("encoded").is_some() && value.get("encoding").is_some() {
+            Ok(Self::PolkadotJs)
+        } else if value.get("crypto").is_some() || value.get("Crypto").is_some() {
+            Ok(Self::Web3)
+        } else {
+            anyhow::bail!(
+                "Unrecognized key file. Expected an Ethereum V3 keystore or a Polkadot.js account JSON"
+            )
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Web3Keystore {
+    version: u32,
+    #[serde(default)]
+    id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    address: Option<String>,
+    #[serde(alias = "Crypto")]
+    crypto: Web3Crypto,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Web3Crypto {
+    cipher: String,
+    cipherparams: Web3CipherParams,
+    ciphertext: String,
+    kdf: String,
+    kdfparams: Web3KdfParams,
+    mac: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Web3CipherParams {
+    iv: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Web3KdfParams {
+    Scrypt {
+        dklen: usize,
+        n: u64,
+        p: u32,
+        r: u32,
+        salt: String,
+    },
+    Pbkdf2 {
+        c: u32,
+        dklen: usize,
+        prf: String,
+        salt: String,
+    },
+}
+
+/// Encrypt a secp256k1 private key as an Ethereum V3 keystore
+pub fn encrypt_web3(private_key: &[u8; 32], password: &str) -> Result<serde_json::Value> {
+    let signer = alloy_signer_local::PrivateKeySigner::from_slice(private_key)
+        .context("Invalid private key")?;
+
+    let salt: [u8; 32] = random_bytes();
+    let iv: [u8; 16] = random_bytes();
+
+    let params = scrypt::Params::new(WEB3_SCRYPT_LOG_N, WEB3_SCRYPT_R, WEB3_SCRYPT_P, WEB3_DKLEN)
+        .map_err(|e| anyhow::anyhow!("Invalid scrypt parameters: {}", e))?;
+    let mut derived_key = [0u8; WEB3_DKLEN];
+    scrypt::scrypt(password.as_bytes(), &salt, &params, &mut derived_key)
+        .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
+
+    let mut ciphertext = private_key.to_vec();
+    Aes128Ctr::new(derived_key[..16].into(), &iv.into()).apply_keystream(&mut ciphertext);
+    let mac = web3_mac(&derived_key, &ciphertext);
+    derived_key.zeroize();
+
+    let keystore = Web3Keystore {
+        version: 3,
+        id: Some(random_uuid()),
+        address: Some(hex::encode(signer.address())),
+        crypto: Web3Crypto {
+            cipher: "aes-128-ctr".to_string(),
+            cipherparams: Web3CipherParams {
+                iv: hex::encode(iv),
+            },
+            ciphertext: hex::encode(ciphertext),
+            kdf: "scrypt".to_string(),
+            kdfparams: Web3KdfParams::Scrypt {
+                dklen: WEB3_DKLEN,
+                n: 1 << WEB3_SCRYPT_LOG_N,
+                p: WEB3_SCRYPT_P,
+                r: WEB3_SCRYPT_R,
+                salt: hex::encode(salt),
+            },
+            mac: hex::encode(mac),
+        },
+    };
+
+    Ok(serde_json::to_value(keystore)?)
+}
+
+/// Decrypt an Ethereum V3 keystore, returning the private key and address
+pub fn decrypt_web3(json: &str, password: &str) -> Result<([u8; 32], String)> {
+    let keystore: Web3Keystore =
+        serde_json::from_str(json).context("Invalid Ethereum V3 keystore")?;
+    if keystore.version != 3 {
+        anyhow::bail!("Unsupported keystore version {}", keystore.version);
+    }
+
+    let crypto = &keystore.crypto;
+    if crypto.cipher != "aes-128-ctr" {
+        anyhow::bail!("Unsupported keystore cipher '{}'", crypto.cipher);
+    }
+
+    let mut derived_key = match (&crypto.kdf[..], &crypto.kdfparams) {
+        (
+            "scrypt",
+            Web3KdfParams::Scrypt {
+                dklen,
+                n,
+                p,
+                r,
+                salt,
+            },
+        ) => {
+            if !(32..=64).contains(dklen) || !n.is_power_of_two() {
+                anyhow::bail!("Invalid scrypt parameters in keystore");
+            }
+            let log_n = n.trailing_zeros() as u8;
+            if log_n > MAX_SCRYPT_LOG_N || *r > MAX_SCRYPT_R || *p > MAX_SCRYPT_P {
+                anyhow::bail!(
+                    "Keystore scrypt parameters are too expensive (n = {}, r = {}, p = {})",
+                    n,
+                    r,
+                    p
+                );
+            }
+
+            let salt = hex::decode(salt).context("Invalid keystore salt")?;
+            let params = scrypt::Params::new(log_n, *r, *p, *dklen)
+                .map_err(|e| anyhow::anyhow!("Invalid scrypt parameters: {}", e))?;
+            let mut key = vec![0u8; *dklen];
+            scrypt::scrypt(password.as_bytes(), &salt, &params, &mut key)
+                .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
+            key
+        }
+        (
+            "pbkdf2",
+            Web3KdfParams::Pbkdf2 {
+                c,
+                dklen,
+                prf,
+                salt,
+            },
+        ) => {
+            if prf != "hmac-sha256" {
+                anyhow::bail!("Unsupported PBKDF2 function '{}'", prf);
+            }
+            if !(32..=64).contains(dklen) || *c > MAX_PBKDF2_ROUNDS {
+                anyhow::bail!("Invalid PBKDF2 parameters in keystore");
+            }
+
+            let salt = hex::decode(salt).context("Invalid keystore salt")?;
+            let mut key = vec![0u8; *dklen];
+            pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password.as_bytes(), &salt, *c, &mut key);
+            key
+        }
+        (kdf, _) => anyhow::bail!("Unsupported or mismatched keystore KDF '{}'", kdf),
+    };
+
+    let mut ciphertext = hex::decode(&crypto.ciphertext).context("Invalid keystore ciphertext")?;
+    let expected_mac = hex::decode(&crypto.mac).context("Invalid keystore MAC")?;
+    if web3_mac(&derived_key, &ciphertext).as_slice() != expected_mac.as_slice() {
+        derived_key.zeroize();
+        anyhow::bail!("Incorrect password for keystore file");
+    }
+
+    let iv: [u8; 16] = hex::decode(&crypto.cipherparams.iv)
+        .ok()
+        .and_then(|iv| iv.try_into().ok())
+        .ok_or_else(|| anyhow::anyhow!("Invalid keystore IV"))?;
+    Aes128Ctr::new(derived_key[..16].into(), &iv.into()).apply_keystream(&mut ciphertext);
+    derived_key.zeroize();
+
+    let private_key: [u8; 32] = ciphertext
+        .as_slice()
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Keystore does not contain a 32-byte private key"))?;
+    ciphertext.zeroize();
+
+    let signer = alloy_signer_local::PrivateKeySigner::from_slice(&private_key)
+        .context("Keystore contains an invalid private key")?;
+    let address = format!("{:?}", signer.address());
+
+    if let Some(expected) = &keystore.address {
+        let expected = expected.trim_start_matches("0x");
+        if !expected.eq_ignore_ascii_case(&hex::encode(signer.address())) {
+            anyhow::bail!(
+                "Keystore address 0x{} does not match its private key ({})",
+                expected,
+                address
+            );
+        }
+    }
+
+    Ok((private_key, address))
+}
+
+/// Encrypt an sr25519 key pair as a Polkadot.js account JSON
+pub fn encrypt_polkadot_js(
+    pair: &sr25519::Pair,
+    password: &str,
+    name: &str,
+) -> Result<serde_json::Value> {
+    let mut secret = schnorrkel::SecretKey::from_bytes(&pair.to_raw_vec())
+        .map_err(|e| anyhow::anyhow!("Invalid sr25519 secret key: {}", e))?
+        .to_ed25519_bytes();
+
+    let mut plaintext = Vec::with_capacity(117);
+    plaintext.extend_from_slice(&PKCS8_HEADER);
+    plaintext.extend_from_slice(&secret);
+    plaintext.extend_from_slice(&PKCS8_DIVIDER);
+    plaintext.extend_from_slice(pair.public().as_ref());
+    secret.zeroize();
+
+    let salt: [u8; PJS_SALT_SIZE] = random_bytes();
+    let nonce: [u8; PJS_NONCE_SIZE] = random_bytes();
+
+    let mut key = polkadot_js_key(
+        password,
+        &salt,
+        PJS_SCRYPT_LOG_N,
+        PJS_SCRYPT_R,
+        PJS_SCRYPT_P,
+    )?;
+    let ciphertext = XSalsa20Poly1305::new(&key.into())
+        .encrypt(&nonce.into(), plaintext.as_slice())
+        .map_err(|e| anyhow::anyhow!("Encryption failed: {}", e))?;
+    key.zeroize();
+    plaintext.zeroize();
+
+    let mut encoded = Vec::with_capacity(PJS_PARAMS_SIZE + ciphertext.len());
+    encoded.extend_from_slice(&salt);
+    encoded.extend_from_slice(&(1u32 << PJS_SCRYPT_LOG_N).to_le_bytes());
+    encoded.extend_from_slice(&PJS_SCRYPT_P.to_le_bytes());
+    encoded.extend_from_slice(&PJS_SCRYPT_R.to_le_bytes());
+    encoded.extend_from_slice(&nonce);
+    encoded.extend_from_slice(&ciphertext);
+
+    let when_created = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .context("Failed to get system time")?
+        .as_millis() as u64;
+
+    Ok(serde_json::json!({
+        "encoded": base64::engine::general_purpose::STANDARD.encode(encoded),
+        "encoding": {
+            "content": ["pkcs8", "sr25519"],
+            "type": ["scrypt", "xsalsa20-poly1305"],
+            "version": "3"
+        },
+        "address": pair.public().to_ss58check(),
+        "meta": {
+            "name": name,
+            "whenCreated": when_created
+        }
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct PolkadotJsAccount {
+    encoded: String,
+    encoding: PolkadotJsEncoding,
+    address: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PolkadotJsEncoding {
+    content: Vec<String>,
+    #[serde(rename = "type")]
+    kind: Vec<String>,
+    version: String,
+}
+
+/// Decrypt a Polkadot.js account JSON into an sr25519 key pair
+pub fn decrypt_polkadot_js(json: &str, password: &str) -> Result<sr25519::Pair> {
+    let account: PolkadotJsAccount =
+        serde_json::from_str(json).context("Invalid Polkadot.js account JSON")?;
+
+    let encoding = &account.encoding;
+    let has = |list: &[String], item: &str| list.iter().any(|s| s == item);
+    if !has(&encoding.content, "sr25519") {
+        anyhow::bail!(
+            "Only sr25519 accounts are supported (key type: {})",
+            encoding.content.join(", ")
+        );
+    }
+    if encoding.version != "3"
+        || !has(&encoding.content, "pkcs8")
+        || !has(&encoding.kind, "scrypt")
+        || !has(&encoding.kind, "xsalsa20-poly1305")
+    {
+        anyhow::bail!(
+            "Unsupported Polkadot.js encoding (version {}, {})",
+            encoding.version,
+            encoding.kind.join(", ")
+        );
+    }
+
+    let encoded = base64::engine::general_purpose::STANDARD
+        .decode(account.encoded.trim())
+        .context("Invalid base64 in Polkadot.js account JSON")?;
+    if encoded.len() <= PJS_PARAMS_SIZE {
+        anyhow::bail!("Polkadot.js account JSON is truncated");
+    }
+
+    let read_u32 = |offset: usize| {
+        u32::from_le_bytes(
+            encoded[offset..offset + 4]
+                .try_into()
+                .expect("4-byte slice"),
+        )
+    };
+    let salt = &encoded[..PJS_SALT_SIZE];
+    let n = read_u32(PJS_SALT_SIZE);
+    let p = read_u32(PJS_SALT_SIZE + 4);
+    let r = read_u32(PJS_SALT_SIZE + 8);
+    let nonce = &encoded[PJS_SALT_SIZE + 12..PJS_PARAMS_SIZE];
+
+    if !n.is_power_of_two()
+        || n.trailing_zeros() as u8 > MAX_SCRYPT_LOG_N
+        || r > MAX_SCRYPT_R
+        || p > MAX_SCRYPT_P
+    {
+        anyhow::bail!(
+            "Unsupported scrypt parameters (n = {}, r = {}, p = {})",
+            n,
+            r,
+            p
+        );
+    }
+
+    let mut key = polkadot_js_key(password, salt, n.trailing_zeros() as u8, r, p)?;
+    let nonce: [u8; PJS_NONCE_SIZE] = nonce.try_into().expect("24-byte nonce");
+    let plaintext = XSalsa20Poly1305::new(&key.into())
+        .decrypt(&nonce.into(), &encoded[PJS_PARAMS_SIZE..])
+        .map_err(|_| anyhow::anyhow!("Incorrect password for Polkadot.js account"));
+    key.zeroize();
+    let mut plaintext = plaintext?;
+
+    if plaintext.len() != 117
+        || plaintext[..16] != PKCS8_HEADER
+        || plaintext[80..85] != PKCS8_DIVIDER
+    {
+        plaintext.zeroize();
+        anyhow::bail!("Polkadot.js account JSON contains an invalid key pair");
+    }
+
+    let secret = schnorrkel::SecretKey::from_ed25519_bytes(&plaintext[16..80])
+        .map_err(|e| anyhow::anyhow!("Invalid sr25519 secret key: {}", e));
+    let public = plaintext[85..117].to_vec();
+    plaintext.zeroize();
+
+    let pair = sr25519::Pair::from_seed_slice(&secret?.to_bytes())
+        .map_err(|e| anyhow::anyhow!("Invalid sr25519 secret key: {:?}", e))?;
+    if pair.public().0[..] != public[..] {
+        anyhow::bail!("Polkadot.js account JSON public key does not match its secret key");
+    }
+
+    let (address_key, _) = sr25519::Public::from_ss58check_with_version(&account.address)
+        .map_err(|e| anyhow::anyhow!("Invalid address '{}': {:?}", account.address, e))?;
+    if address_key != pair.public() {
+        anyhow::bail!(
+            "Polkadot.js account address {} does not match its key",
+            account.address
+        );
+    }
+
+    Ok(pair)
+}
+
+fn polkadot_js_key(password: &str, salt: &[u8], log_n: u8, r: u32, p: u32) -> Result<[u8; 32]> {
+    let params = scrypt::Params::new(log_n, r, p, 32)
+        .map_err(|e| anyhow::anyhow!("Invalid scrypt parameters: {}", e))?;
+    let mut key = [0u8; 32];
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut key)
+        .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn web3_mac(derived_key: &[u8], ciphertext: &[u8]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(16 + ciphertext.len());
+    data.extend_from_slice(&derived_key[16..32]);
+    data.extend_from_slice(ciphertext);
+    keccak256(&data).0
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    use ::rand::RngCore;
+
+    let mut bytes = [0u8; N];
+    ::rand::rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Random version 4 UUID for the keystore `id` field
+fn random_uuid() -> String {
+    let mut bytes: [u8; 16] = random_bytes();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex = hex::encode(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Anvil's first development account
+    const DEV_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const DEV_ADDRESS: &str = "f39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+    fn dev_key() -> [u8; 32] {
+        hex::decode(DEV_KEY).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_web3_roundtrip() {
+        let json = encrypt_web3(&dev_key(), "export-password").unwrap();
+        assert_eq!(json["version"], 3);
+        assert_eq!(json["address"], DEV_ADDRESS);
+        assert_eq!(json["crypto"]["kdf"], "scrypt");
+
+        let json = json.to_string();
+        assert_eq!(KeyFileFormat::detect(&json).unwrap(), KeyFileFormat::Web3);
+
+        let (key, address) = decrypt_web3(&json, "export-password").unwrap();
+        assert_eq!(key, dev_key());
+        assert!(address.eq_ignore_ascii_case(&format!("0x{}", DEV_ADDRESS)));
+
+        assert!(decrypt_web3(&json, "wrong-password").is_err());
+    }
+
+    #[test]
+    fn test_web3_pbkdf2() {
+        // Keystore produced with the PBKDF2 variant, as written by older tools
+        let salt = [7u8; 32];
+        let iv = [9u8; 16];
+        let mut derived_key = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<sha2::Sha256>(b"testpassword", &salt, 1024, &mut derived_key);
+
+        let mut ciphertext = dev_key().to_vec();
+        Aes128Ctr::new(derived_key[..16].into(), &iv.into()).apply_keystream(&mut ciphertext);
+
+        let json = serde_json::json!({
+            "version": 3,
+            "id": random_uuid(),
+            "address": DEV_ADDRESS,
+            "Crypto": {
+                "cipher": "aes-128-ctr",
+                "cipherparams": { "iv": hex::encode(iv) },
+                "ciphertext": hex::encode(&ciphertext),
+                "kdf": "pbkdf2",
+                "kdfparams": { "c": 1024, "dklen": 32, "prf": "hmac-sha256", "salt": hex::encode(salt) },
+                "mac": hex::encode(web3_mac(&derived_key, &ciphertext))
+            }
+        })
+        .to_string();
+
+        let (key, _) = decrypt_web3(&json, "testpassword").unwrap();
+        assert_eq!(key, dev_key());
+    }
+
+    #[test]
+    fn test_web3_rejects_expensive_kdf() {
+        let mut json = encrypt_web3(&dev_key(), "export-password").unwrap();
+        json["crypto"]["kdfparams"]["n"] = serde_json::json!(1u64 << 30);
+
+        let err = decrypt_web3(&json.to_string(), "export-password").unwrap_err();
+        assert!(err.to_string().contains("too expensive"));
+    }
+
+    #[test]
+    fn test_polkadot_js_roundtrip() {
+        let (pair, _) = sr25519::Pair::generate();
+
+        let json = encrypt_polkadot_js(&pair, "export-password", "alice").unwrap();
+        assert_eq!(json["meta"]["name"], "alice");
+        assert_eq!(json["address"], pair.public().to_ss58check());
+
+        let json = json.to_string();
+        assert_eq!(
+            KeyFileFormat::detect(&json).unwrap(),
+            KeyFileFormat::PolkadotJs
+        );
+
+        let decrypted = decrypt_polkadot_js(&json, "export-password").unwrap();
+        assert_eq!(decrypted.public(), pair.public());
+        assert_eq!(decrypted.to_raw_vec(), pair.to_raw_vec());
+
+        assert!(decrypt_polkadot_js(&json, "wrong-password").is_err());
+    }
+
+    #[test]
+    fn test_polkadot_js_readable_by_subxt() {
+        let (pair, _) = sr25519::Pair::generate();
+        let json = encrypt_polkadot_js(&pair, "export-password", "bob").unwrap();
+
+        let keypair =
+            subxt_signer::polkadot_js_compat::decrypt_json(&json.to_string(), "export-password")
+                .unwrap();
+        assert_eq!(keypair.public_key().0, pair.public().0);
+    }
+
+    #[test]
+    fn test_detect_unknown_format() {
+        assert!(KeyFileFormat::detect(r#"{"mnemonic": "abandon"}"#).is_err());
+        assert!(KeyFileFormat::detect("not json").is_err());
+    }
+}
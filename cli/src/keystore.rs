@@ -35,17 +35,26 @@ use argon2::{
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::time::{Duration, Instant};
 use zeroize::Zeroize;
 
+use crate::signer::SignerBackend;
+
 const NONCE_SIZE: usize = 12;
-const KEYSTORE_VERSION: u32 = 1;
 
-// OWASP recommended Argon2 parameters (2023)
+/// Keystore file format; see [`migrate`] for what changed between versions
+const KEYSTORE_VERSION: u32 = 2;
+
+// OWASP recommended Argon2 parameters (2023), used for new accounts
 const ARGON2_MEM_COST: u32 = 19 * 1024; // 19 MiB
 const ARGON2_TIME_COST: u32 = 2; // 2 iterations
 const ARGON2_PARALLELISM: u32 = 1; // Single thread
 
+// Upper bounds on stored Argon2 parameters, so a tampered keystore can't
+// make unlocking exhaust memory or run for hours
+const MAX_ARGON2_MEM_COST: u32 = 4 * 1024 * 1024; // 4 GiB
+const MAX_ARGON2_TIME_COST: u32 = 64;
+const MAX_ARGON2_PARALLELISM: u32 = 16;
+
 // Rate limiting
 const MAX_FAILED_ATTEMPTS: u32 = 5;
 const LOCKOUT_DURATION_SECS: u64 = 300; // 5 minutes
@@ -68,6 +77,171 @@ pub struct EncryptedAccount {
     pub created_at: u64,
     #[serde(default)]
     pub encryption_version: u32,
+    #[serde(default)]
+    pub secret_kind: SecretKind,
+    /// Mnemonic account this account is derived from; derived accounts hold
+    /// no encrypted data of their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+    /// BIP-44 path (EVM) or Substrate junctions applied to the parent's seed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+    /// Where the key is held; accounts outside the keystore file have no
+    /// encrypted data
+    #[serde(default, skip_serializing_if = "SignerBackend::is_keystore")]
+    pub backend: SignerBackend,
+    /// Argon2id parameters the encrypted data was encrypted with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kdf: Option<KdfParams>,
+}
+
+/// Argon2id parameters an account's encryption key is derived with
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub m_cost: u32,
+    /// Number of passes
+    pub t_cost: u32,
+    /// Degree of parallelism
+    pub p_cost: u32,
+}
+
+impl KdfParams {
+    /// Parameters format v1 keystores used for every account without recording them
+    pub const V1: KdfParams = KdfParams {
+        m_cost: 19 * 1024,
+        t_cost: 2,
+        p_cost: 1,
+    };
+
+    /// Check the parameters are accepted by Argon2 and within sane bounds
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=MAX_ARGON2_PARALLELISM).contains(&self.p_cost) {
+            anyhow::bail!(
+                "Argon2 parallelism must be between 1 and {}",
+                MAX_ARGON2_PARALLELISM
+            );
+        }
+        if !(1..=MAX_ARGON2_TIME_COST).contains(&self.t_cost) {
+            anyhow::bail!(
+                "Argon2 time cost must be between 1 and {}",
+                MAX_ARGON2_TIME_COST
+            );
+        }
+        let min_mem_cost = 8 * self.p_cost;
+        if !(min_mem_cost..=MAX_ARGON2_MEM_COST).contains(&self.m_cost) {
+            anyhow::bail!(
+                "Argon2 memory cost must be between {} and {} KiB",
+                min_mem_cost,
+                MAX_ARGON2_MEM_COST
+            );
+        }
+        Ok(())
+    }
+
+    /// Whether any parameter is below the defaults for new accounts
+    pub fn is_weaker_than_default(&self) -> bool {
+        let default = Self::default();
+        self.m_cost < default.m_cost || self.t_cost < default.t_cost
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            m_cost: ARGON2_MEM_COST,
+            t_cost: ARGON2_TIME_COST,
+            p_cost: ARGON2_PARALLELISM,
+        }
+    }
+}
+
+impl std::fmt::Display for KdfParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "argon2id, {} KiB, {} passes, parallelism {}",
+            self.m_cost, self.t_cost, self.p_cost
+        )
+    }
+}
+
+/// What the encrypted data of an account holds
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretKind {
+    /// BIP-39 mnemonic phrase
+    #[default]
+    Mnemonic,
+    /// Raw private key, e.g. imported from a V3 keystore or Polkadot.js JSON
+    ///
+    /// EVM keys are 32-byte secp256k1 scalars, Substrate keys 64-byte sr25519
+    /// secret keys (key and nonce).
+    PrivateKey,
+}
+
+/// Decrypted account secret
+pub enum AccountSecret {
+    Mnemonic(String),
+    PrivateKey(Vec<u8>),
+    /// Parent mnemonic and the derivation path of a derived account
+    Derived {
+        phrase: String,
+        path: String,
+    },
+}
+
+impl AccountSecret {
+    /// Substrate sr25519 key pair for this secret
+    ///
+    /// Mnemonics and derivation paths are read as a secret URI, so the keys
+    /// match `subkey` and polkadot.js for the same phrase and path.
+    pub fn substrate_pair(&self) -> Result<sp_core::sr25519::Pair> {
+        use sp_core::Pair;
+
+        let uri = match self {
+            AccountSecret::Mnemonic(phrase) => zeroize::Zeroizing::new(phrase.clone()),
+            AccountSecret::Derived { phrase, path } => {
+                validate_substrate_path(path)?;
+                zeroize::Zeroizing::new(format!("{}{}", phrase, path))
+            }
+            AccountSecret::PrivateKey(key) => {
+                return sp_core::sr25519::Pair::from_seed_slice(key)
+                    .map_err(|e| anyhow::anyhow!("Failed to create keypair: {:?}", e));
+            }
+        };
+
+        sp_core::sr25519::Pair::from_string(&uri, None)
+            .map_err(|e| anyhow::anyhow!("Failed to derive keypair: {:?}", e))
+    }
+
+    /// EVM wallet for this secret (first account for mnemonics)
+    pub fn evm_wallet(&self) -> Result<apex_sdk_evm::wallet::Wallet> {
+        match self {
+            AccountSecret::Mnemonic(phrase) => {
+                apex_sdk_evm::wallet::Wallet::from_mnemonic(phrase, 0)
+                    .context("Failed to create wallet from mnemonic")
+            }
+            AccountSecret::PrivateKey(key) => {
+                apex_sdk_evm::wallet::Wallet::from_private_key(&hex::encode(key))
+                    .context("Failed to create wallet from private key")
+            }
+            AccountSecret::Derived { phrase, path } => {
+                apex_sdk_evm::wallet::Wallet::from_mnemonic_with_path(phrase, path)
+                    .context("Failed to derive wallet from mnemonic")
+            }
+        }
+    }
+}
+
+impl Drop for AccountSecret {
+    fn drop(&mut self) {
+        match self {
+            AccountSecret::Mnemonic(phrase) => phrase.zeroize(),
+            AccountSecret::PrivateKey(key) => key.zeroize(),
+            AccountSecret::Derived { phrase, .. } => phrase.zeroize(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -91,12 +265,41 @@ pub struct Keystore {
     pub accounts: Vec<EncryptedAccount>,
     #[serde(default = "default_version")]
     pub version: u32,
+    /// Failed unlock attempts per account, as unix timestamps
+    #[serde(skip)]
+    failed_attempts: HashMap<String, Vec<u64>>,
+    /// File the failed attempts persist to, so lockouts survive across runs
     #[serde(skip)]
-    failed_attempts: HashMap<String, Vec<Instant>>,
+    lockout_path: Option<PathBuf>,
+    /// Format migration performed by [`Keystore::load`], if any
+    #[serde(skip)]
+    migration: Option<Migration>,
+}
+
+/// An older keystore format upgraded while loading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+    pub from: u32,
+    pub to: u32,
+    /// Copy of the keystore file as it was before the migration
+    pub backup: PathBuf,
+}
+
+impl std::fmt::Display for Migration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Migrated keystore from format v{} to v{} (backup: {})",
+            self.from,
+            self.to,
+            self.backup.display()
+        )
+    }
 }
 
+/// Keystore files without a version predate versioning
 fn default_version() -> u32 {
-    KEYSTORE_VERSION
+    1
 }
 
 impl Default for Keystore {
@@ -105,21 +308,68 @@ impl Default for Keystore {
             accounts: Vec::new(),
             version: KEYSTORE_VERSION,
             failed_attempts: HashMap::new(),
+            lockout_path: None,
+            migration: None,
         }
     }
 }
 
 impl Keystore {
     /// Load keystore from disk
+    ///
+    /// Keystores in an older format are migrated and saved, after copying the
+    /// original to `<file>.v<version>.bak`; see [`Keystore::migration`].
     pub fn load(path: &Path) -> Result<Self> {
-        if path.exists() {
+        let mut migration = None;
+        let mut keystore = if path.exists() {
             let data = std::fs::read_to_string(path).context("Failed to read keystore file")?;
-            let keystore: Keystore =
+            let mut value: serde_json::Value =
                 serde_json::from_str(&data).context("Failed to parse keystore file")?;
-            Ok(keystore)
+
+            let version = value
+                .get("version")
+                .and_then(|v| v.as_u64())
+                .map_or(1, |v| u32::try_from(v).unwrap_or(u32::MAX))
+                .max(1);
+            if version > KEYSTORE_VERSION {
+                anyhow::bail!(
+                    "Keystore {} uses format v{}, but this version of apex only supports up to v{}. \
+                     Please upgrade apex-sdk-cli",
+                    path.display(),
+                    version,
+                    KEYSTORE_VERSION
+                );
+            }
+
+            if version < KEYSTORE_VERSION {
+                let backup = backup_file(path, version)?;
+                migrate(&mut value, version)?;
+                let keystore: Keystore =
+                    serde_json::from_value(value).context("Failed to parse migrated keystore")?;
+                keystore.save(path)?;
+                migration = Some(Migration {
+                    from: version,
+                    to: KEYSTORE_VERSION,
+                    backup,
+                });
+                keystore
+            } else {
+                serde_json::from_value(value).context("Failed to parse keystore file")?
+            }
         } else {
-            Ok(Self::default())
-        }
+            Self::default()
+        };
+
+        let lockout_path = path.with_extension("lockout.json");
+        keystore.failed_attempts = read_failed_attempts(&lockout_path);
+        keystore.lockout_path = Some(lockout_path);
+        keystore.migration = migration;
+        Ok(keystore)
+    }
+
+    /// The format migration the last [`Keystore::load`] performed, if any
+    pub fn migration(&self) -> Option<&Migration> {
+        self.migration.as_ref()
     }
 
     /// Save keystore to disk
@@ -131,21 +381,11 @@ impl Keystore {
 
         let data = serde_json::to_string_pretty(self).context("Failed to serialize keystore")?;
         std::fs::write(path, data).context("Failed to write keystore file")?;
-
-        // Set restrictive permissions on Unix systems
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = std::fs::metadata(path)?.permissions();
-            perms.set_mode(0o600); // Read/write for owner only
-            std::fs::set_permissions(path, perms)?;
-        }
-
-        Ok(())
+        set_owner_only(path)
     }
 
     /// Validate password strength
-    fn validate_password(password: &str) -> Result<()> {
+    pub(crate) fn validate_password(password: &str) -> Result<()> {
         if password.len() < MIN_PASSWORD_LENGTH {
             anyhow::bail!(
                 "Password must be at least {} characters long (current: {})",
@@ -189,12 +429,11 @@ impl Keystore {
 
     /// Check if account is locked out due to failed attempts
     fn is_locked_out(&mut self, account_name: &str) -> bool {
-        let now = Instant::now();
+        let now = unix_now();
 
         // Clean up old failed attempts
         if let Some(attempts) = self.failed_attempts.get_mut(account_name) {
-            attempts
-                .retain(|&t| now.duration_since(t) < Duration::from_secs(LOCKOUT_DURATION_SECS));
+            attempts.retain(|&t| now.saturating_sub(t) < LOCKOUT_DURATION_SECS);
 
             attempts.len() >= MAX_FAILED_ATTEMPTS as usize
         } else {
@@ -204,16 +443,44 @@ impl Keystore {
 
     /// Record a failed decryption attempt
     fn record_failed_attempt(&mut self, account_name: &str) {
+        // Pick up attempts other processes made since this keystore was loaded
+        if let Some(path) = &self.lockout_path {
+            self.failed_attempts = read_failed_attempts(path);
+        }
+
         let attempts = self
             .failed_attempts
             .entry(account_name.to_string())
             .or_default();
-        attempts.push(Instant::now());
+        attempts.push(unix_now());
+        self.persist_failed_attempts();
     }
 
     /// Clear failed attempts for an account (on successful access)
     fn clear_failed_attempts(&mut self, account_name: &str) {
-        self.failed_attempts.remove(account_name);
+        if self.failed_attempts.remove(account_name).is_some() {
+            self.persist_failed_attempts();
+        }
+    }
+
+    /// Write failed attempts next to the keystore; failures are logged, as
+    /// they must not hide the result of the unlock itself
+    fn persist_failed_attempts(&self) {
+        let Some(path) = &self.lockout_path else {
+            return;
+        };
+
+        let result = if self.failed_attempts.is_empty() {
+            match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+                _ => Ok(()),
+            }
+        } else {
+            write_failed_attempts(path, &self.failed_attempts)
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to save failed unlock attempts: {}", e);
+        }
     }
 
     /// Encrypt and add an account
@@ -224,6 +491,44 @@ impl Keystore {
         address: String,
         secret_data: &[u8],
         password: &str,
+    ) -> Result<()> {
+        self.insert_account(
+            name,
+            account_type,
+            address,
+            secret_data,
+            SecretKind::Mnemonic,
+            password,
+        )
+    }
+
+    /// Encrypt and add an account holding a raw private key
+    pub fn add_private_key(
+        &mut self,
+        name: String,
+        account_type: AccountType,
+        address: String,
+        private_key: &[u8],
+        password: &str,
+    ) -> Result<()> {
+        self.insert_account(
+            name,
+            account_type,
+            address,
+            private_key,
+            SecretKind::PrivateKey,
+            password,
+        )
+    }
+
+    fn insert_account(
+        &mut self,
+        name: String,
+        account_type: AccountType,
+        address: String,
+        secret_data: &[u8],
+        secret_kind: SecretKind,
+        password: &str,
     ) -> Result<()> {
         // Validate password strength
         Self::validate_password(password)?;
@@ -233,7 +538,8 @@ impl Keystore {
             anyhow::bail!("Account with name '{}' already exists", name);
         }
 
-        let (encrypted_data, nonce, salt) = encrypt_data(secret_data, password)?;
+        let kdf = KdfParams::default();
+        let (encrypted_data, nonce, salt) = encrypt_data(secret_data, password, &kdf)?;
 
         let account = EncryptedAccount {
             name,
@@ -247,14 +553,206 @@ impl Keystore {
                 .context("Failed to get system time")?
                 .as_secs(),
             encryption_version: KEYSTORE_VERSION,
+            secret_kind,
+            parent: None,
+            derivation_path: None,
+            backend: SignerBackend::Keystore,
+            kdf: Some(kdf),
         };
 
         self.accounts.push(account);
         Ok(())
     }
 
+    /// Add an account derived from a mnemonic account
+    ///
+    /// Only the path is stored; the seed stays encrypted in the parent account.
+    pub fn add_derived_account(
+        &mut self,
+        name: String,
+        parent: &str,
+        derivation_path: String,
+        address: String,
+    ) -> Result<()> {
+        if self.accounts.iter().any(|a| a.name == name) {
+            anyhow::bail!("Account with name '{}' already exists", name);
+        }
+
+        let parent_account = self
+            .accounts
+            .iter()
+            .find(|a| a.name == parent)
+            .ok_or_else(|| anyhow::anyhow!("Account '{}' not found", parent))?;
+        if parent_account.parent.is_some()
+            || parent_account.secret_kind != SecretKind::Mnemonic
+            || matches!(parent_account.backend, SignerBackend::Remote { .. })
+        {
+            anyhow::bail!(
+                "Account '{}' is not a mnemonic account. Accounts can only be derived from imported or generated mnemonics",
+                parent
+            );
+        }
+
+        let account = EncryptedAccount {
+            name,
+            account_type: parent_account.account_type.clone(),
+            address,
+            encrypted_data: Vec::new(),
+            nonce: Vec::new(),
+            salt: Vec::new(),
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .context("Failed to get system time")?
+                .as_secs(),
+            encryption_version: KEYSTORE_VERSION,
+            secret_kind: SecretKind::Mnemonic,
+            parent: Some(parent.to_string()),
+            derivation_path: Some(derivation_path),
+            backend: SignerBackend::Keystore,
+            kdf: None,
+        };
+
+        self.accounts.push(account);
+        Ok(())
+    }
+
+    /// Add an account whose key is held outside the keystore file
+    pub fn add_external_account(
+        &mut self,
+        name: String,
+        account_type: AccountType,
+        address: String,
+        secret_kind: SecretKind,
+        backend: SignerBackend,
+    ) -> Result<()> {
+        if backend.is_keystore() {
+            anyhow::bail!("Keystore accounts must be added with an encrypted secret");
+        }
+        if self.accounts.iter().any(|a| a.name == name) {
+            anyhow::bail!("Account with name '{}' already exists", name);
+        }
+
+        self.accounts.push(EncryptedAccount {
+            name,
+            account_type,
+            address,
+            encrypted_data: Vec::new(),
+            nonce: Vec::new(),
+            salt: Vec::new(),
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .context("Failed to get system time")?
+                .as_secs(),
+            encryption_version: KEYSTORE_VERSION,
+            secret_kind,
+            parent: None,
+            derivation_path: None,
+            backend,
+            kdf: None,
+        });
+        Ok(())
+    }
+
+    /// Hand an account's key over to another backend, wiping its encrypted data
+    ///
+    /// The caller stores the decrypted secret in the new backend first.
+    pub fn set_backend(&mut self, name: &str, backend: SignerBackend) -> Result<()> {
+        let account = self
+            .accounts
+            .iter_mut()
+            .find(|a| a.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Account '{}' not found", name))?;
+        if account.parent.is_some() {
+            anyhow::bail!(
+                "Account '{}' is derived and uses its parent's key. Move the parent instead",
+                name
+            );
+        }
+
+        account.encrypted_data.zeroize();
+        account.encrypted_data = Vec::new();
+        account.nonce = Vec::new();
+        account.salt = Vec::new();
+        account.kdf = None;
+        account.backend = backend;
+        Ok(())
+    }
+
+    /// Re-encrypt an account with new Argon2 parameters, and a new password if given
+    pub fn rekey(
+        &mut self,
+        name: &str,
+        password: &str,
+        new_password: Option<&str>,
+        kdf: KdfParams,
+    ) -> Result<()> {
+        kdf.validate()?;
+        if let Some(new_password) = new_password {
+            Self::validate_password(new_password)?;
+        }
+
+        let account = self
+            .accounts
+            .iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Account '{}' not found", name))?;
+        if let Some(parent) = &account.parent {
+            anyhow::bail!(
+                "Account '{}' is derived and uses the key of '{}'. Rekey '{}' instead",
+                name,
+                parent,
+                parent
+            );
+        }
+
+        let mut data = self.get_account(name, password)?;
+        let encrypted = encrypt_data(&data, new_password.unwrap_or(password), &kdf);
+        data.zeroize();
+        let (encrypted_data, nonce, salt) = encrypted?;
+
+        let account = self
+            .accounts
+            .iter_mut()
+            .find(|a| a.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Account '{}' not found", name))?;
+        account.encrypted_data = encrypted_data;
+        account.nonce = nonce;
+        account.salt = salt;
+        account.kdf = Some(kdf);
+        account.encryption_version = KEYSTORE_VERSION;
+        Ok(())
+    }
+
+    /// The account holding the key for `name`: its parent if derived, else itself
+    pub fn root_account(&self, name: &str) -> Result<&EncryptedAccount> {
+        let account = self
+            .accounts
+            .iter()
+            .find(|a| a.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Account '{}' not found", name))?;
+        match &account.parent {
+            Some(parent) => self
+                .accounts
+                .iter()
+                .find(|a| &a.name == parent)
+                .ok_or_else(|| anyhow::anyhow!("Parent account '{}' not found", parent)),
+            None => Ok(account),
+        }
+    }
+
     /// Decrypt and retrieve account data
+    ///
+    /// For derived accounts this is the parent's mnemonic.
     pub fn get_account(&mut self, name: &str, password: &str) -> Result<Vec<u8>> {
+        // Derived accounts share the parent's encrypted seed and lockout
+        let name = self
+            .accounts
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| a.parent.clone().unwrap_or_else(|| a.name.clone()))
+            .ok_or_else(|| anyhow::anyhow!("Account '{}' not found", name))?;
+        let name = name.as_str();
+
         // Check for lockout
         if self.is_locked_out(name) {
             anyhow::bail!(
@@ -270,6 +768,13 @@ impl Keystore {
             .iter()
             .find(|a| a.name == name)
             .ok_or_else(|| anyhow::anyhow!("Account '{}' not found", name))?;
+        if !account.backend.is_keystore() {
+            anyhow::bail!(
+                "Account '{}' is held by the {}, not the keystore file",
+                name,
+                account.backend
+            );
+        }
 
         // Attempt decryption
         match decrypt_data(
@@ -277,6 +782,7 @@ impl Keystore {
             &account.nonce,
             &account.salt,
             password,
+            &account.kdf.unwrap_or(KdfParams::V1),
         ) {
             Ok(data) => {
                 // Success - clear failed attempts
@@ -307,6 +813,27 @@ impl Keystore {
         }
     }
 
+    /// Decode the secret of `name` from its root account's decrypted data
+    pub fn secret_from_data(&self, name: &str, data: Vec<u8>) -> Result<AccountSecret> {
+        let (kind, path) = self
+            .accounts
+            .iter()
+            .find(|a| a.name == name)
+            .map(|a| (a.secret_kind, a.derivation_path.clone()))
+            .unwrap_or_default();
+
+        match (kind, path) {
+            (SecretKind::Mnemonic, path) => {
+                let phrase = String::from_utf8(data).context("Failed to decode mnemonic")?;
+                Ok(match path {
+                    Some(path) => AccountSecret::Derived { phrase, path },
+                    None => AccountSecret::Mnemonic(phrase),
+                })
+            }
+            (SecretKind::PrivateKey, _) => Ok(AccountSecret::PrivateKey(data)),
+        }
+    }
+
     /// List all account names
     pub fn list_accounts(&self) -> Vec<&EncryptedAccount> {
         self.accounts.iter().collect()
@@ -314,6 +841,20 @@ impl Keystore {
 
     /// Remove an account
     pub fn remove_account(&mut self, name: &str) -> Result<()> {
+        let children: Vec<&str> = self
+            .accounts
+            .iter()
+            .filter(|a| a.parent.as_deref() == Some(name))
+            .map(|a| a.name.as_str())
+            .collect();
+        if !children.is_empty() {
+            anyhow::bail!(
+                "Account '{}' has derived accounts ({}). Remove them first",
+                name,
+                children.join(", ")
+            );
+        }
+
         let initial_len = self.accounts.len();
         self.accounts.retain(|a| a.name != name);
 
@@ -330,18 +871,45 @@ impl Keystore {
     }
 }
 
-/// Derive encryption key from password using Argon2 with a given salt
+/// Check a Substrate derivation path such as `//Alice` or `//stash/1`
+///
+/// `//` marks a hard junction and `/` a soft one, as in `subkey`. Passwords
+/// (`///password`) are not part of a stored path.
+pub fn validate_substrate_path(path: &str) -> Result<()> {
+    use std::str::FromStr;
+
+    let invalid = || {
+        anyhow::anyhow!(
+            "Invalid derivation path '{}'. Substrate paths look like //Alice or //stash/1",
+            path
+        )
+    };
+    if !path.starts_with('/') {
+        return Err(invalid());
+    }
+
+    let uri = sp_core::crypto::SecretUri::from_str(path).map_err(|_| invalid())?;
+    if uri.password.is_some() {
+        anyhow::bail!(
+            "Invalid derivation path '{}': passwords are not supported",
+            path
+        );
+    }
+
+    Ok(())
+}
+
+/// Derive encryption key from password using Argon2id with a given salt
 ///
-/// Uses OWASP recommended Argon2id parameters (2023):
-/// - Memory cost: 19 MiB
-/// - Time cost: 2 iterations
-/// - Parallelism: 1 thread
-fn derive_key(password: &str, salt: &SaltString) -> Result<[u8; 32]> {
-    // Configure Argon2 with OWASP recommended parameters
+/// New accounts use OWASP recommended parameters (2023, see
+/// [`KdfParams::default`]); existing accounts use the parameters they were
+/// encrypted with.
+fn derive_key(password: &str, salt: &SaltString, kdf: &KdfParams) -> Result<[u8; 32]> {
+    kdf.validate()?;
     let params = ParamsBuilder::new()
-        .m_cost(ARGON2_MEM_COST)
-        .t_cost(ARGON2_TIME_COST)
-        .p_cost(ARGON2_PARALLELISM)
+        .m_cost(kdf.m_cost)
+        .t_cost(kdf.t_cost)
+        .p_cost(kdf.p_cost)
         .build()
         .map_err(|e| anyhow::anyhow!("Failed to build Argon2 parameters: {}", e))?;
 
@@ -368,12 +936,16 @@ fn derive_key(password: &str, salt: &SaltString) -> Result<[u8; 32]> {
 }
 
 /// Encrypt data using AES-256-GCM
-fn encrypt_data(data: &[u8], password: &str) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+fn encrypt_data(
+    data: &[u8],
+    password: &str,
+    kdf: &KdfParams,
+) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
     // Generate salt
     let salt = SaltString::generate(&mut OsRng);
     let salt_bytes = salt.as_str().as_bytes().to_vec();
 
-    let mut key = derive_key(password, &salt)?;
+    let mut key = derive_key(password, &salt, kdf)?;
     let cipher = Aes256Gcm::new_from_slice(&key)
         .map_err(|e| anyhow::anyhow!("Failed to create cipher: {}", e))?;
 
@@ -399,13 +971,14 @@ fn decrypt_data(
     nonce_bytes: &[u8],
     salt_bytes: &[u8],
     password: &str,
+    kdf: &KdfParams,
 ) -> Result<Vec<u8>> {
     // Reconstruct salt
     let salt_str = std::str::from_utf8(salt_bytes).context("Invalid salt encoding")?;
     let salt = SaltString::from_b64(salt_str)
         .map_err(|e| anyhow::anyhow!("Failed to parse salt: {}", e))?;
 
-    let mut key = derive_key(password, &salt)?;
+    let mut key = derive_key(password, &salt, kdf)?;
     let cipher = Aes256Gcm::new_from_slice(&key)
         .map_err(|e| anyhow::anyhow!("Failed to create cipher: {}", e))?;
 
@@ -424,6 +997,93 @@ fn decrypt_data(
     Ok(decrypted)
 }
 
+/// Upgrade keystore JSON from `version` to [`KEYSTORE_VERSION`], one format at a time
+///
+/// - v1 → v2: record the Argon2 parameters of each encrypted account, which
+///   v1 hard-coded
+fn migrate(value: &mut serde_json::Value, mut version: u32) -> Result<()> {
+    while version < KEYSTORE_VERSION {
+        match version {
+            1 => {
+                let kdf = serde_json::to_value(KdfParams::V1)?;
+                let accounts = value
+                    .get_mut("accounts")
+                    .and_then(|a| a.as_array_mut())
+                    .ok_or_else(|| anyhow::anyhow!("Keystore file has no accounts list"))?;
+                for account in accounts {
+                    let encrypted = account
+                        .get("encrypted_data")
+                        .and_then(|d| d.as_array())
+                        .is_some_and(|d| !d.is_empty());
+                    if encrypted && account.get("kdf").is_none() {
+                        account["kdf"] = kdf.clone();
+                    }
+                }
+            }
+            _ => anyhow::bail!("No migration from keystore format v{}", version),
+        }
+
+        version += 1;
+        value["version"] = version.into();
+    }
+    Ok(())
+}
+
+/// Copy a keystore about to be migrated to `<file>.v<version>.bak`, keeping
+/// earlier backups
+fn backup_file(path: &Path, version: u32) -> Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("keystore.json");
+    let mut backup = path.with_file_name(format!("{}.v{}.bak", file_name, version));
+    if backup.exists() {
+        backup = path.with_file_name(format!("{}.v{}.{}.bak", file_name, version, unix_now()));
+    }
+
+    std::fs::copy(path, &backup).context("Failed to back up keystore before migrating it")?;
+    set_owner_only(&backup)?;
+    Ok(backup)
+}
+
+/// Read persisted failed attempts, treating a missing or unreadable file as none
+fn read_failed_attempts(path: &Path) -> HashMap<String, Vec<u64>> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+fn write_failed_attempts(path: &Path, attempts: &HashMap<String, Vec<u64>>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string(attempts)?)?;
+    set_owner_only(path)
+}
+
+/// Restrict a file to its owner on Unix systems
+fn set_owner_only(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = std::fs::metadata(path)?.permissions();
+        perms.set_mode(0o600); // Read/write for owner only
+        std::fs::set_permissions(path, perms)?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(())
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
 /// Get the default keystore path
 pub fn get_keystore_path() -> Result<PathBuf> {
     let config_dir = dirs::config_dir()
@@ -435,15 +1095,21 @@ pub fn get_keystore_path() -> Result<PathBuf> {
 mod tests {
     use super::*;
 
+    fn get_secret(keystore: &mut Keystore, name: &str, password: &str) -> AccountSecret {
+        let data = keystore.get_account(name, password).unwrap();
+        keystore.secret_from_data(name, data).unwrap()
+    }
+
     #[test]
     fn test_encrypt_decrypt() {
         let data = b"secret private key data";
         let password = "test_password_123";
 
-        let (encrypted, nonce, salt) = encrypt_data(data, password).unwrap();
+        let (encrypted, nonce, salt) = encrypt_data(data, password, &KdfParams::default()).unwrap();
         assert_ne!(encrypted.as_slice(), data);
 
-        let decrypted = decrypt_data(&encrypted, &nonce, &salt, password).unwrap();
+        let decrypted =
+            decrypt_data(&encrypted, &nonce, &salt, password, &KdfParams::default()).unwrap();
         assert_eq!(decrypted, data);
     }
 
@@ -453,8 +1119,14 @@ mod tests {
         let password = "correct_password";
         let wrong_password = "wrong_password";
 
-        let (encrypted, nonce, salt) = encrypt_data(data, password).unwrap();
-        let result = decrypt_data(&encrypted, &nonce, &salt, wrong_password);
+        let (encrypted, nonce, salt) = encrypt_data(data, password, &KdfParams::default()).unwrap();
+        let result = decrypt_data(
+            &encrypted,
+            &nonce,
+            &salt,
+            wrong_password,
+            &KdfParams::default(),
+        );
 
         assert!(result.is_err());
     }
@@ -504,6 +1176,298 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_keystore_private_key_secret() {
+        let mut keystore = Keystore::default();
+        let password = "TestPassword123";
+        let key = hex::decode("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80")
+            .unwrap();
+
+        keystore
+            .add_private_key(
+                "imported".to_string(),
+                AccountType::Evm,
+                "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string(),
+                &key,
+                password,
+            )
+            .unwrap();
+
+        let wallet = get_secret(&mut keystore, "imported", password)
+            .evm_wallet()
+            .unwrap();
+        assert_eq!(
+            wallet.address(),
+            "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"
+        );
+    }
+
+    #[test]
+    fn test_validate_substrate_path() {
+        assert!(validate_substrate_path("//Alice").is_ok());
+        assert!(validate_substrate_path("//stash/1").is_ok());
+
+        assert!(validate_substrate_path("Alice").is_err());
+        assert!(validate_substrate_path("//Alice//").is_err());
+        assert!(validate_substrate_path("//Alice///password").is_err());
+    }
+
+    #[test]
+    fn test_substrate_pair_matches_subkey() {
+        use sp_core::crypto::{Ss58Codec, DEV_PHRASE};
+        use sp_core::Pair;
+
+        // `subkey inspect "<DEV_PHRASE>//Alice"`
+        let alice = AccountSecret::Derived {
+            phrase: DEV_PHRASE.to_string(),
+            path: "//Alice".to_string(),
+        };
+        assert_eq!(
+            alice.substrate_pair().unwrap().public().to_ss58check(),
+            "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+        );
+
+        // `subkey inspect "<DEV_PHRASE>"`
+        let root = AccountSecret::Mnemonic(DEV_PHRASE.to_string());
+        assert_eq!(
+            root.substrate_pair().unwrap().public().to_ss58check(),
+            "5DfhGyQdFobKM8NsWvEeAKk5EQQgYe9AydgJ7rMB6E1EqRzV"
+        );
+    }
+
+    #[test]
+    fn test_keystore_derived_accounts() {
+        use sp_core::Pair;
+
+        let mut keystore = Keystore::default();
+        let password = "TestPassword123";
+        let mnemonic =
+            "legal winner thank year wave sausage worth useful legal winner thank yellow";
+
+        keystore
+            .add_account(
+                "root".to_string(),
+                AccountType::Substrate,
+                "addr".to_string(),
+                mnemonic.as_bytes(),
+                password,
+            )
+            .unwrap();
+        keystore
+            .add_derived_account(
+                "alice".to_string(),
+                "root",
+                "//Alice".to_string(),
+                "addr2".to_string(),
+            )
+            .unwrap();
+
+        let root = get_secret(&mut keystore, "root", password);
+        let alice = get_secret(&mut keystore, "alice", password);
+        assert!(matches!(alice, AccountSecret::Derived { ref path, .. } if path == "//Alice"));
+        assert_ne!(
+            root.substrate_pair().unwrap().public(),
+            alice.substrate_pair().unwrap().public()
+        );
+
+        // Derived accounts can't be derived from again, and block removing the parent
+        assert!(keystore
+            .add_derived_account("x".to_string(), "alice", "//1".to_string(), "a".to_string())
+            .is_err());
+        assert!(keystore.remove_account("root").is_err());
+        keystore.remove_account("alice").unwrap();
+        keystore.remove_account("root").unwrap();
+    }
+
+    #[test]
+    fn test_keystore_external_backends() {
+        let mut keystore = Keystore::default();
+        let password = "TestPassword123";
+        let mnemonic =
+            "legal winner thank year wave sausage worth useful legal winner thank yellow";
+
+        keystore
+            .add_account(
+                "local".to_string(),
+                AccountType::Substrate,
+                "addr".to_string(),
+                mnemonic.as_bytes(),
+                password,
+            )
+            .unwrap();
+        keystore
+            .add_derived_account(
+                "child".to_string(),
+                "local",
+                "//1".to_string(),
+                "addr2".to_string(),
+            )
+            .unwrap();
+
+        // Derived accounts follow their parent's key
+        assert!(keystore
+            .set_backend("child", SignerBackend::Keychain)
+            .is_err());
+        keystore
+            .set_backend("local", SignerBackend::Keychain)
+            .unwrap();
+        let local = keystore.root_account("child").unwrap();
+        assert_eq!(local.name, "local");
+        assert!(local.encrypted_data.is_empty());
+        assert!(keystore.get_account("local", password).is_err());
+
+        keystore
+            .add_external_account(
+                "remote".to_string(),
+                AccountType::Evm,
+                "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_string(),
+                SecretKind::PrivateKey,
+                SignerBackend::Remote {
+                    url: "http://127.0.0.1:8550".to_string(),
+                },
+            )
+            .unwrap();
+        assert!(keystore
+            .add_derived_account(
+                "remote-child".to_string(),
+                "remote",
+                "m/44'/60'/0'/0/1".to_string(),
+                "addr3".to_string(),
+            )
+            .is_err());
+
+        // The backend survives a save and load; keystore accounts omit it
+        let json = serde_json::to_string(&keystore).unwrap();
+        let loaded: Keystore = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            loaded.root_account("remote").unwrap().backend,
+            SignerBackend::Remote {
+                url: "http://127.0.0.1:8550".to_string()
+            }
+        );
+        assert_eq!(json.matches("\"backend\"").count(), 2);
+    }
+
+    #[test]
+    fn test_keystore_rekey() {
+        let mut keystore = Keystore::default();
+        let password = "TestPassword123";
+        let new_password = "NewPassword456";
+
+        keystore
+            .add_account(
+                "test".to_string(),
+                AccountType::Substrate,
+                "addr".to_string(),
+                b"data",
+                password,
+            )
+            .unwrap();
+        assert_eq!(
+            keystore.root_account("test").unwrap().kdf,
+            Some(KdfParams::default())
+        );
+
+        let kdf = KdfParams {
+            m_cost: 32 * 1024,
+            t_cost: 3,
+            p_cost: 1,
+        };
+        keystore
+            .rekey("test", password, Some(new_password), kdf)
+            .unwrap();
+        assert_eq!(keystore.root_account("test").unwrap().kdf, Some(kdf));
+        assert!(keystore.get_account("test", password).is_err());
+        assert_eq!(keystore.get_account("test", new_password).unwrap(), b"data");
+
+        let unbounded = KdfParams {
+            m_cost: u32::MAX,
+            ..kdf
+        };
+        assert!(keystore
+            .rekey("test", new_password, None, unbounded)
+            .is_err());
+    }
+
+    #[test]
+    fn test_keystore_migrates_v1() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keystore.json");
+        let password = "TestPassword123";
+
+        // v1 files had no format version and no per-account KDF parameters
+        let mut keystore = Keystore::default();
+        keystore
+            .add_account(
+                "old".to_string(),
+                AccountType::Evm,
+                "addr".to_string(),
+                b"data",
+                password,
+            )
+            .unwrap();
+        let mut value = serde_json::to_value(&keystore).unwrap();
+        value.as_object_mut().unwrap().remove("version");
+        value["accounts"][0].as_object_mut().unwrap().remove("kdf");
+        let v1 = serde_json::to_string_pretty(&value).unwrap();
+        std::fs::write(&path, &v1).unwrap();
+
+        let mut loaded = Keystore::load(&path).unwrap();
+        assert_eq!(loaded.version, KEYSTORE_VERSION);
+        assert_eq!(loaded.root_account("old").unwrap().kdf, Some(KdfParams::V1));
+        assert_eq!(loaded.get_account("old", password).unwrap(), b"data");
+
+        let backup = dir.path().join("keystore.json.v1.bak");
+        assert_eq!(
+            loaded.migration(),
+            Some(&Migration {
+                from: 1,
+                to: KEYSTORE_VERSION,
+                backup: backup.clone(),
+            })
+        );
+        assert_eq!(std::fs::read_to_string(backup).unwrap(), v1);
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], KEYSTORE_VERSION);
+        assert!(Keystore::load(&path).unwrap().migration().is_none());
+
+        // Files from a newer apex are refused rather than rewritten
+        let mut newer = saved.clone();
+        newer["version"] = (KEYSTORE_VERSION + 1).into();
+        std::fs::write(&path, newer.to_string()).unwrap();
+        assert!(Keystore::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_keystore_lockout_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keystore.json");
+        let password = "TestPassword123";
+
+        let mut keystore = Keystore::load(&path).unwrap();
+        keystore
+            .add_account(
+                "test".to_string(),
+                AccountType::Substrate,
+                "addr".to_string(),
+                b"data",
+                password,
+            )
+            .unwrap();
+        keystore.save(&path).unwrap();
+
+        // Each failed attempt comes from a fresh process
+        for _ in 0..MAX_FAILED_ATTEMPTS {
+            let mut keystore = Keystore::load(&path).unwrap();
+            assert!(keystore.get_account("test", "WrongPassword1").is_err());
+        }
+
+        let mut keystore = Keystore::load(&path).unwrap();
+        let err = keystore.get_account("test", password).unwrap_err();
+        assert!(err.to_string().contains("temporarily locked"));
+    }
+
     #[test]
     fn test_keystore_remove() {
         let mut keystore = Keystore::default();
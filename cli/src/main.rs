@@ -4,13 +4,25 @@ use anyhow::Context;
 use clap::{Parser, Subcommand};
 use std::path::{Path, PathBuf};
 
+mod abi;
 mod account;
+mod agent;
 mod balance;
 mod completions;
 mod config;
 mod config_cmd;
 mod deploy;
+mod keyfile;
 mod keystore;
+mod manifest;
+mod metadata;
+mod node;
+mod signer;
+mod storage;
+#[cfg(test)]
+mod test_rpc;
+mod tx;
+mod watch;
 
 #[derive(Parser)]
 #[command(name = "apex")]
@@ -42,22 +54,35 @@ enum Commands {
         #[arg(short, long)]
         filter: Option<String>,
     },
-    /// Deploy a smart contract
+    /// Deploy a smart contract, or the contracts of a project manifest environment
     Deploy {
         /// Path to the contract file
-        contract: String,
+        #[arg(required_unless_present = "env", conflicts_with = "env")]
+        contract: Option<String>,
         /// Chain to deploy to (polkadot, ethereum, etc.)
-        #[arg(short, long)]
-        chain: String,
+        #[arg(short, long, required_unless_present = "env", conflicts_with = "env")]
+        chain: Option<String>,
         /// RPC endpoint URL
-        #[arg(short, long)]
-        endpoint: String,
-        /// Account name to use for deployment
+        #[arg(short, long, required_unless_present = "env", conflicts_with = "env")]
+        endpoint: Option<String>,
+        /// Account name to use for deployment (overrides the environment's account)
         #[arg(short, long)]
         account: Option<String>,
         /// Perform a dry-run without broadcasting the transaction
         #[arg(long)]
         dry_run: bool,
+        /// Deploy the contracts of this environment from the project manifest
+        #[arg(long)]
+        env: Option<String>,
+        /// Path to the project manifest
+        #[arg(long, default_value = manifest::MANIFEST_FILE, requires = "env")]
+        manifest: std::path::PathBuf,
+        /// Redeploy all contracts, even unchanged ones
+        #[arg(long, requires = "env")]
+        force: bool,
+        /// Skip the confirmation prompt
+        #[arg(short, long, requires = "env")]
+        yes: bool,
     },
     /// Manage accounts and wallets
     Account {
@@ -74,6 +99,31 @@ enum Commands {
         #[command(subcommand)]
         action: ChainCommands,
     },
+    /// Send transactions and track their status
+    Tx {
+        #[command(subcommand)]
+        action: TxCommands,
+    },
+    /// Browse and compare runtime metadata (Substrate)
+    Metadata {
+        #[command(subcommand)]
+        action: MetadataCommands,
+    },
+    /// Query runtime storage (Substrate)
+    Storage {
+        #[command(subcommand)]
+        action: StorageCommands,
+    },
+    /// Encode and decode Solidity ABI data and look up selectors
+    Abi {
+        #[command(subcommand)]
+        action: AbiCommands,
+    },
+    /// Run local development chains for testing
+    Node {
+        #[command(subcommand)]
+        action: NodeCommands,
+    },
     /// Generate shell completions
     Completions {
         /// Shell to generate completions for (bash, zsh, fish, powershell, elvish)
@@ -93,6 +143,13 @@ enum Commands {
     },
     /// Show version information
     Version,
+    /// Run the session agent holding unlocked accounts (started by 'apex account unlock')
+    #[command(hide = true)]
+    Agent {
+        /// Socket to listen on
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -106,29 +163,99 @@ enum AccountCommands {
         #[arg(short, long)]
         name: Option<String>,
     },
-    /// Import account from mnemonic
+    /// Import account from a mnemonic or a key file
     Import {
         /// Mnemonic phrase
-        mnemonic: String,
+        #[arg(required_unless_present = "keystore")]
+        mnemonic: Option<String>,
         /// Account type (substrate, evm)
-        #[arg(short = 't', long)]
-        account_type: String,
+        #[arg(short = 't', long, required_unless_present = "keystore")]
+        account_type: Option<String>,
         /// Account name
         #[arg(short, long)]
         name: String,
+        /// Ethereum V3 keystore or Polkadot.js account JSON to import
+        #[arg(long, conflicts_with_all = ["mnemonic", "account_type"])]
+        keystore: Option<String>,
     },
     /// List all accounts
     List,
-    /// Export account mnemonic
+    /// Derive a new account from a mnemonic account
+    Derive {
+        /// Mnemonic account to derive from
+        parent: String,
+        /// Name of the derived account
+        #[arg(short, long)]
+        name: String,
+        /// Derivation path: BIP-44 for EVM (m/44'/60'/0'/0/1), junctions for Substrate (//Alice, //stash/1)
+        #[arg(short, long, required_unless_present = "index")]
+        path: Option<String>,
+        /// Account index, shorthand for m/44'/60'/0'/0/<index> (EVM) or //<index> (Substrate)
+        #[arg(short, long, conflicts_with = "path")]
+        index: Option<u32>,
+    },
+    /// Export account mnemonic, private key or key file
     Export {
         /// Account name
         name: String,
+        /// Export format (mnemonic, web3, polkadot-js)
+        #[arg(short, long, default_value = "mnemonic")]
+        format: String,
+        /// Write the key file here instead of printing it
+        #[arg(short, long)]
+        output: Option<String>,
     },
     /// Remove an account
     Remove {
         /// Account name
         name: String,
     },
+    /// Move an account's key from the keystore file into the OS keychain
+    Keychain {
+        /// Account name
+        name: String,
+    },
+    /// Re-encrypt an account with new key derivation parameters or a new password
+    Rekey {
+        /// Account name
+        name: String,
+        /// Prompt for a new password instead of keeping the current one
+        #[arg(long)]
+        new_password: bool,
+        /// Argon2 memory cost in KiB
+        #[arg(long)]
+        memory_cost: Option<u32>,
+        /// Argon2 passes
+        #[arg(long)]
+        time_cost: Option<u32>,
+        /// Argon2 parallelism
+        #[arg(long)]
+        parallelism: Option<u32>,
+    },
+    /// Add an EVM account held by a remote JSON-RPC signer (Clef, Web3Signer)
+    AddRemote {
+        /// Account name
+        name: String,
+        /// Signer URL
+        #[arg(short, long)]
+        url: String,
+        /// Account address, required if the signer manages several accounts
+        #[arg(short, long)]
+        address: Option<String>,
+    },
+    /// Cache an account's key in the session agent so commands stop prompting
+    Unlock {
+        /// Account name
+        name: String,
+        /// Seconds to stay unlocked (defaults to preferences.unlock_timeout)
+        #[arg(short, long)]
+        timeout: Option<u64>,
+    },
+    /// Drop an account, or all accounts, from the session agent
+    Lock {
+        /// Account name (all accounts if omitted)
+        name: Option<String>,
+    },
     /// Get account balance
     Balance {
         /// Account address
@@ -187,6 +314,229 @@ enum ChainCommands {
         /// RPC endpoint
         endpoint: String,
     },
+    /// Stream new blocks and their events
+    Watch {
+        #[command(flatten)]
+        opts: watch::WatchOptions,
+    },
+}
+
+#[derive(Subcommand)]
+enum MetadataCommands {
+    /// List the runtime's pallets
+    Pallets {
+        #[command(flatten)]
+        chain: metadata::ChainArgs,
+    },
+    /// List a pallet's calls
+    Calls {
+        /// Pallet name
+        pallet: String,
+        #[command(flatten)]
+        chain: metadata::ChainArgs,
+    },
+    /// List a pallet's events
+    Events {
+        /// Pallet name
+        pallet: String,
+        #[command(flatten)]
+        chain: metadata::ChainArgs,
+    },
+    /// List a pallet's errors
+    Errors {
+        /// Pallet name
+        pallet: String,
+        #[command(flatten)]
+        chain: metadata::ChainArgs,
+    },
+    /// Look up types in the runtime's type registry
+    Types {
+        /// Only show types whose path contains this (case-insensitive)
+        filter: Option<String>,
+        /// Show the type with this id
+        #[arg(long, conflicts_with = "filter")]
+        id: Option<u32>,
+        #[command(flatten)]
+        chain: metadata::ChainArgs,
+    },
+    /// Compare the metadata of two runtimes
+    Diff {
+        /// Block hash on the selected chain, or an endpoint URL
+        from: String,
+        /// Block hash on the selected chain, or an endpoint URL
+        to: String,
+        #[command(flatten)]
+        chain: metadata::ChainArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum NodeCommands {
+    /// Start local EVM and/or Substrate dev nodes until Ctrl-C
+    Start {
+        #[command(flatten)]
+        opts: node::StartOptions,
+    },
+}
+
+#[derive(Subcommand)]
+enum AbiCommands {
+    /// ABI-encode a function call
+    Encode {
+        /// Function signature, e.g. "transfer(address,uint256)"
+        signature: String,
+        /// Arguments (addresses, numbers, hex or JSON for arrays and tuples)
+        args: Vec<String>,
+        /// Omit the selector, e.g. to encode constructor arguments
+        #[arg(long)]
+        args_only: bool,
+    },
+    /// Decode calldata, or an event log given as JSON or with --topic
+    Decode {
+        /// Hex calldata or log data, or a log object with `topics` and `data`
+        data: String,
+        /// Log topic (repeatable, in order)
+        #[arg(long)]
+        topic: Vec<String>,
+        #[command(flatten)]
+        source: abi::AbiSource,
+        /// Print JSON output
+        #[arg(long)]
+        json: bool,
+    },
+    /// List the selectors of an ABI file or directory, compute the selector
+    /// of a signature, or look up a selector in the project's ABIs
+    Selectors {
+        /// ABI file, directory, signature or 0x selector
+        query: String,
+        /// Project directory searched when looking up a selector
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+        /// Print JSON output
+        #[arg(long)]
+        json: bool,
+    },
+    /// Decode revert data into the error and its arguments
+    Error {
+        /// Hex revert data
+        data: String,
+        #[command(flatten)]
+        source: abi::AbiSource,
+        /// Print JSON output
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum StorageCommands {
+    /// Read a storage value
+    Get {
+        /// Pallet name
+        pallet: String,
+        /// Storage item name
+        item: String,
+        /// Map keys (addresses, numbers, hex or JSON)
+        keys: Vec<String>,
+        #[command(flatten)]
+        chain: metadata::ChainArgs,
+    },
+    /// Iterate the entries of a storage map
+    Iter {
+        /// Pallet name
+        pallet: String,
+        /// Storage item name
+        item: String,
+        /// Leading map keys to iterate under
+        keys: Vec<String>,
+        /// Entries fetched per request
+        #[arg(long, default_value_t = apex_sdk_substrate::storage::DEFAULT_PAGE_SIZE)]
+        page_size: u32,
+        /// Stop after this many entries
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+        #[command(flatten)]
+        chain: metadata::ChainArgs,
+    },
+}
+
+#[derive(Subcommand)]
+enum TxCommands {
+    /// Send native tokens
+    Send {
+        /// Recipient address
+        to: String,
+        /// Amount in whole tokens (e.g. 1.5)
+        amount: String,
+        /// Treat the amount as base units (wei, planck)
+        #[arg(long)]
+        raw: bool,
+        #[command(flatten)]
+        opts: tx::TxOptions,
+    },
+    /// Transfer ERC-20 tokens
+    Erc20Transfer {
+        /// Token contract address
+        token: String,
+        /// Recipient address
+        to: String,
+        /// Amount in whole tokens, scaled by the token's decimals
+        amount: String,
+        /// Treat the amount as base units
+        #[arg(long)]
+        raw: bool,
+        #[command(flatten)]
+        opts: tx::TxOptions,
+    },
+    /// Call a contract function or pallet call
+    Call {
+        /// Function signature (e.g. "approve(address,uint256)") or Pallet.call
+        method: String,
+        /// Call arguments, as plain values or JSON
+        args: Vec<String>,
+        /// Contract address (EVM)
+        #[arg(long)]
+        to: Option<String>,
+        /// Native value to send with the call, in whole tokens (EVM)
+        #[arg(long)]
+        value: Option<String>,
+        #[command(flatten)]
+        opts: tx::TxOptions,
+    },
+    /// Show the status of a transaction
+    Status {
+        /// Transaction hash
+        hash: String,
+        /// Chain name from the configured endpoints
+        #[arg(short, long)]
+        chain: Option<String>,
+        /// RPC endpoint URL
+        #[arg(short, long)]
+        endpoint: Option<String>,
+        /// Print JSON instead of human-readable output
+        #[arg(long)]
+        json: bool,
+    },
+    /// Wait for a transaction to be confirmed
+    Wait {
+        /// Transaction hash
+        hash: String,
+        /// Chain name from the configured endpoints
+        #[arg(short, long)]
+        chain: Option<String>,
+        /// RPC endpoint URL
+        #[arg(short, long)]
+        endpoint: Option<String>,
+        /// Block confirmations to wait for (default: 1 on EVM, finality on Substrate)
+        #[arg(long)]
+        confirmations: Option<u32>,
+        /// Timeout in seconds
+        #[arg(long, default_value = "120")]
+        timeout: u64,
+        /// Print JSON instead of human-readable output
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[tokio::main]
@@ -218,14 +568,25 @@ async fn main() -> anyhow::Result<()> {
             endpoint,
             account,
             dry_run,
-        } => {
-            if dry_run {
-                println!("Dry-run mode: Simulating deployment without broadcasting...");
-            } else {
-                println!("Deploying contract...");
+            env,
+            manifest,
+            force,
+            yes,
+        } => match (env, contract, chain, endpoint) {
+            (Some(env), _, _, _) => {
+                deploy::deploy_environment(&manifest, &env, account, dry_run, force, yes).await?;
             }
-            deploy::deploy_contract(&contract, &chain, &endpoint, account, dry_run).await?;
-        }
+            (None, Some(contract), Some(chain), Some(endpoint)) => {
+                if dry_run {
+                    println!("Dry-run mode: Simulating deployment without broadcasting...");
+                } else {
+                    println!("Deploying contract...");
+                }
+                deploy::deploy_contract(&contract, &chain, &endpoint, account, dry_run).await?;
+            }
+            // clap requires contract, chain and endpoint without --env
+            _ => unreachable!(),
+        },
         Commands::Account { action } => match action {
             AccountCommands::Generate { account_type, name } => {
                 println!("🔑 Generating new {} account...", account_type);
@@ -235,19 +596,60 @@ async fn main() -> anyhow::Result<()> {
                 mnemonic,
                 account_type,
                 name,
-            } => {
-                println!("📥 Importing {} account...", account_type);
-                account::import_account(&mnemonic, &account_type, name)?;
-            }
+                keystore,
+            } => match (keystore, mnemonic, account_type) {
+                (Some(path), _, _) => {
+                    println!("📥 Importing account from {}...", path);
+                    account::import_key_file(&path, name)?;
+                }
+                (None, Some(mnemonic), Some(account_type)) => {
+                    println!("📥 Importing {} account...", account_type);
+                    account::import_account(&mnemonic, &account_type, name)?;
+                }
+                _ => anyhow::bail!("Provide a mnemonic and --account-type, or --keystore <FILE>"),
+            },
             AccountCommands::List => {
                 account::list_accounts()?;
             }
-            AccountCommands::Export { name } => {
-                account::export_account(&name)?;
+            AccountCommands::Derive {
+                parent,
+                name,
+                path,
+                index,
+            } => {
+                account::derive_account(&parent, name, path.as_deref(), index)?;
+            }
+            AccountCommands::Export {
+                name,
+                format,
+                output,
+            } => {
+                account::export_account(&name, &format, output.as_deref())?;
             }
             AccountCommands::Remove { name } => {
                 account::remove_account(&name)?;
             }
+            AccountCommands::Keychain { name } => {
+                account::move_to_keychain(&name)?;
+            }
+            AccountCommands::Rekey {
+                name,
+                new_password,
+                memory_cost,
+                time_cost,
+                parallelism,
+            } => {
+                account::rekey_account(&name, new_password, memory_cost, time_cost, parallelism)?;
+            }
+            AccountCommands::AddRemote { name, url, address } => {
+                account::add_remote_account(name, &url, address.as_deref()).await?;
+            }
+            AccountCommands::Unlock { name, timeout } => {
+                account::unlock_account(&name, timeout)?;
+            }
+            AccountCommands::Lock { name } => {
+                account::lock_accounts(name.as_deref())?;
+            }
             AccountCommands::Balance {
                 address,
                 chain,
@@ -289,6 +691,146 @@ async fn main() -> anyhow::Result<()> {
                 println!("🏥 Checking chain health...");
                 check_chain_health(&endpoint).await?;
             }
+            ChainCommands::Watch { opts } => {
+                watch::watch(&opts).await?;
+            }
+        },
+        Commands::Metadata { action } => match action {
+            MetadataCommands::Pallets { chain } => {
+                metadata::pallets(&chain).await?;
+            }
+            MetadataCommands::Calls { pallet, chain } => {
+                metadata::variants(
+                    &chain,
+                    &pallet,
+                    apex_sdk_substrate::metadata_diff::ItemKind::Call,
+                )
+                .await?;
+            }
+            MetadataCommands::Events { pallet, chain } => {
+                metadata::variants(
+                    &chain,
+                    &pallet,
+                    apex_sdk_substrate::metadata_diff::ItemKind::Event,
+                )
+                .await?;
+            }
+            MetadataCommands::Errors { pallet, chain } => {
+                metadata::variants(
+                    &chain,
+                    &pallet,
+                    apex_sdk_substrate::metadata_diff::ItemKind::Error,
+                )
+                .await?;
+            }
+            MetadataCommands::Types { filter, id, chain } => {
+                metadata::types(&chain, filter.as_deref(), id).await?;
+            }
+            MetadataCommands::Diff { from, to, chain } => {
+                metadata::diff(&chain, &from, &to).await?;
+            }
+        },
+        Commands::Storage { action } => match action {
+            StorageCommands::Get {
+                pallet,
+                item,
+                keys,
+                chain,
+            } => {
+                storage::get(&chain, &pallet, &item, &keys).await?;
+            }
+            StorageCommands::Iter {
+                pallet,
+                item,
+                keys,
+                page_size,
+                limit,
+                chain,
+            } => {
+                storage::iter(&chain, &pallet, &item, &keys, page_size, limit).await?;
+            }
+        },
+        Commands::Abi { action } => match action {
+            AbiCommands::Encode {
+                signature,
+                args,
+                args_only,
+            } => {
+                abi::encode(&signature, &args, args_only)?;
+            }
+            AbiCommands::Decode {
+                data,
+                topic,
+                source,
+                json,
+            } => {
+                abi::decode(&data, &topic, &source, json)?;
+            }
+            AbiCommands::Selectors { query, dir, json } => {
+                abi::selectors(&query, &dir, json)?;
+            }
+            AbiCommands::Error { data, source, json } => {
+                abi::error(&data, &source, json)?;
+            }
+        },
+        Commands::Node { action } => match action {
+            NodeCommands::Start { opts } => {
+                node::start(&opts).await?;
+            }
+        },
+        Commands::Tx { action } => match action {
+            TxCommands::Send {
+                to,
+                amount,
+                raw,
+                opts,
+            } => {
+                tx::send(&to, &amount, raw, &opts).await?;
+            }
+            TxCommands::Erc20Transfer {
+                token,
+                to,
+                amount,
+                raw,
+                opts,
+            } => {
+                tx::erc20_transfer(&token, &to, &amount, raw, &opts).await?;
+            }
+            TxCommands::Call {
+                method,
+                args,
+                to,
+                value,
+                opts,
+            } => {
+                tx::call(&method, &args, to.as_deref(), value.as_deref(), &opts).await?;
+            }
+            TxCommands::Status {
+                hash,
+                chain,
+                endpoint,
+                json,
+            } => {
+                tx::status(&hash, chain.as_deref(), endpoint.as_deref(), json).await?;
+            }
+            TxCommands::Wait {
+                hash,
+                chain,
+                endpoint,
+                confirmations,
+                timeout,
+                json,
+            } => {
+                tx::wait(
+                    &hash,
+                    chain.as_deref(),
+                    endpoint.as_deref(),
+                    confirmations,
+                    timeout,
+                    json,
+                )
+                .await?;
+            }
         },
         Commands::Completions { shell } => {
             completions::generate_completions(&shell)?;
@@ -319,6 +861,13 @@ async fn main() -> anyhow::Result<()> {
             println!("  • Substrate: Polkadot, Kusama, Moonbeam, Astar");
             println!("  • EVM: Ethereum, BSC, Polygon, Avalanche");
         }
+        Commands::Agent { socket } => {
+            let socket = match socket {
+                Some(socket) => socket,
+                None => agent::socket_path()?,
+            };
+            agent::serve(&socket).await?;
+        }
     }
 
     Ok(())
@@ -0,0 +1,525 @@
+//! Project manifest (`apex.toml`) and deployments lockfile (`apex.lock`)
+//!
+//! The manifest declares a project's contracts with their constructor
+//! arguments and the environments they are deployed to:
+//!
+//! ```toml
+//! [contracts.Token]
+//! artifact = "out/Token.sol/Token.json"
+//! args = ["Apex Token", "APX", "1000000"]
+//!
+//! [contracts.Vault]
+//! artifact = "out/Vault.sol/Vault.json"
+//! args = ["${Token}"]
+//!
+//! [environments.testnet]
+//! chain = "sepolia"
+//! account = "deployer"
+//! ```
+//!
+//! `${Name}` in an argument is replaced by the address `Name` was deployed at,
+//! which also makes `Name` a dependency deployed first. `apex deploy --env`
+//! records every deployment in the lockfile next to the manifest, keyed by
+//! environment, so unchanged contracts are skipped on the next run.
+//!
+//! On Substrate chains the artifacts are ink! contracts (`.contract` bundles
+//! or `.wasm`), whose code is uploaded with `Contracts::upload_code`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Default manifest file name
+pub const MANIFEST_FILE: &str = "apex.toml";
+
+/// Lockfile written next to the manifest
+pub const LOCKFILE: &str = "apex.lock";
+
+/// Current lockfile format version
+const LOCKFILE_VERSION: u32 = 1;
+
+/// A project manifest
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(default)]
+    pub project: ProjectInfo,
+    #[serde(default)]
+    pub contracts: BTreeMap<String, ContractSpec>,
+    #[serde(default)]
+    pub environments: BTreeMap<String, Environment>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectInfo {
+    pub name: Option<String>,
+}
+
+/// A contract to deploy
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContractSpec {
+    /// Compiler artifact with `abi` and `bytecode`, or raw bytecode (`.bin`, `.hex`),
+    /// relative to the manifest; an ink! `.contract` or `.wasm` file on Substrate
+    pub artifact: PathBuf,
+    /// Constructor arguments, see [`crate::tx::parse_arg`] for strings
+    #[serde(default)]
+    pub args: Vec<toml::Value>,
+    /// Contracts deployed first, in addition to those linked in `args`
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+}
+
+/// A named deployment target
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Environment {
+    /// Chain name from the CLI config (defaults to default_chain)
+    pub chain: Option<String>,
+    /// RPC endpoint URL, overriding the configured one
+    pub endpoint: Option<String>,
+    /// Keystore account to deploy with (defaults to default_account)
+    pub account: Option<String>,
+    /// Contracts to deploy, with their dependencies; all by default
+    pub contracts: Option<Vec<String>>,
+    /// Constructor arguments replacing a contract's `args` in this environment
+    #[serde(default)]
+    pub args: BTreeMap<String, Vec<toml::Value>>,
+}
+
+impl Manifest {
+    /// Read and validate a manifest
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).with_context(|| {
+            format!(
+                "Failed to read {}; create one to deploy with --env",
+                path.display()
+            )
+        })?;
+        Self::parse(&text).with_context(|| format!("Invalid manifest {}", path.display()))
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        let manifest: Self = toml::from_str(text)?;
+        manifest.validate()?;
+        Ok(manifest)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let known = |name: &str, context: &str| {
+            if self.contracts.contains_key(name) {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!(
+                    "Unknown contract '{}' in {}",
+                    name,
+                    context
+                ))
+            }
+        };
+
+        for (name, spec) in &self.contracts {
+            for dependency in self.dependencies(name, &spec.args) {
+                known(&dependency, &format!("contracts.{}", name))?;
+            }
+        }
+        for (env_name, env) in &self.environments {
+            let context = format!("environments.{}", env_name);
+            for name in env.contracts.iter().flatten() {
+                known(name, &context)?;
+            }
+            for (name, args) in &env.args {
+                known(name, &context)?;
+                for dependency in references(args) {
+                    known(&dependency, &context)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Look up an environment by name
+    pub fn environment(&self, name: &str) -> Result<&Environment> {
+        self.environments.get(name).ok_or_else(|| {
+            let known: Vec<&str> = self.environments.keys().map(String::as_str).collect();
+            anyhow::anyhow!(
+                "Environment '{}' not found in the manifest (available: {})",
+                name,
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            )
+        })
+    }
+
+    /// Constructor arguments of a contract in an environment
+    pub fn args<'a>(&'a self, env: &'a Environment, name: &str) -> &'a [toml::Value] {
+        env.args
+            .get(name)
+            .or_else(|| self.contracts.get(name).map(|spec| &spec.args))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Contracts a contract depends on, declared or linked in its arguments
+    fn dependencies(&self, name: &str, args: &[toml::Value]) -> BTreeSet<String> {
+        let mut dependencies = references(args);
+        if let Some(spec) = self.contracts.get(name) {
+            dependencies.extend(spec.depends_on.iter().cloned());
+        }
+        dependencies
+    }
+
+    /// Contracts to deploy to an environment, dependencies first
+    pub fn deployment_order(&self, env: &Environment) -> Result<Vec<String>> {
+        let mut selected: BTreeSet<String> = match &env.contracts {
+            Some(names) => names.iter().cloned().collect(),
+            None => self.contracts.keys().cloned().collect(),
+        };
+
+        // Pull in dependencies of the selected contracts
+        let mut pending: Vec<String> = selected.iter().cloned().collect();
+        while let Some(name) = pending.pop() {
+            for dependency in self.dependencies(&name, self.args(env, &name)) {
+                if selected.insert(dependency.clone()) {
+                    pending.push(dependency);
+                }
+            }
+        }
+
+        // Depth-first topological sort; names are visited in order so the
+        // result is stable
+        fn visit(
+            manifest: &Manifest,
+            env: &Environment,
+            name: &str,
+            visiting: &mut Vec<String>,
+            order: &mut Vec<String>,
+        ) -> Result<()> {
+            if order.iter().any(|n| n == name) {
+                return Ok(());
+            }
+            if let Some(start) = visiting.iter().position(|n| n == name) {
+                let mut cycle = visiting[start..].to_vec();
+                cycle.push(name.to_string());
+                anyhow::bail!("Contracts depend on each other: {}", cycle.join(" -> "));
+            }
+
+            visiting.push(name.to_string());
+            for dependency in manifest.dependencies(name, manifest.args(env, name)) {
+                visit(manifest, env, &dependency, visiting, order)?;
+            }
+            visiting.pop();
+            order.push(name.to_string());
+            Ok(())
+        }
+
+        let mut order = Vec::new();
+        for name in &selected {
+            visit(self, env, name, &mut Vec::new(), &mut order)?;
+        }
+        Ok(order)
+    }
+}
+
+/// Names linked with `${Name}` in constructor arguments
+fn references(args: &[toml::Value]) -> BTreeSet<String> {
+    fn collect(value: &toml::Value, names: &mut BTreeSet<String>) {
+        match value {
+            toml::Value::String(s) => {
+                let mut rest = s.as_str();
+                while let Some(start) = rest.find("${") {
+                    let Some(end) = rest[start..].find('}') else {
+                        break;
+                    };
+                    names.insert(rest[start + 2..start + end].trim().to_string());
+                    rest = &rest[start + end + 1..];
+                }
+            }
+            toml::Value::Array(items) => items.iter().for_each(|item| collect(item, names)),
+            toml::Value::Table(table) => table.values().for_each(|item| collect(item, names)),
+            _ => {}
+        }
+    }
+
+    let mut names = BTreeSet::new();
+    args.iter().for_each(|arg| collect(arg, &mut names));
+    names
+}
+
+/// Replace `${Name}` links with deployed addresses and convert the arguments
+/// to JSON for ABI encoding
+///
+/// Returns the name of the first linked contract without an address as error.
+pub fn link_args(
+    args: &[toml::Value],
+    addresses: &BTreeMap<String, String>,
+) -> std::result::Result<Vec<serde_json::Value>, String> {
+    fn link(
+        value: &toml::Value,
+        addresses: &BTreeMap<String, String>,
+    ) -> std::result::Result<serde_json::Value, String> {
+        Ok(match value {
+            toml::Value::String(s) => {
+                let mut linked = s.clone();
+                for name in references(std::slice::from_ref(value)) {
+                    let address = addresses.get(&name).ok_or_else(|| name.clone())?;
+                    linked = linked.replace(&format!("${{{}}}", name), address);
+                }
+                crate::tx::parse_arg(&linked)
+            }
+            toml::Value::Integer(i) => serde_json::Value::from(*i),
+            toml::Value::Float(f) => serde_json::Value::from(*f),
+            toml::Value::Boolean(b) => serde_json::Value::Bool(*b),
+            toml::Value::Datetime(d) => serde_json::Value::String(d.to_string()),
+            toml::Value::Array(items) => serde_json::Value::Array(
+                items
+                    .iter()
+                    .map(|item| link(item, addresses))
+                    .collect::<std::result::Result<_, _>>()?,
+            ),
+            toml::Value::Table(table) => serde_json::Value::Object(
+                table
+                    .iter()
+                    .map(|(key, item)| link(item, addresses).map(|value| (key.clone(), value)))
+                    .collect::<std::result::Result<_, _>>()?,
+            ),
+        })
+    }
+
+    args.iter().map(|arg| link(arg, addresses)).collect()
+}
+
+/// Deployments recorded by `apex deploy --env`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub version: u32,
+    /// Deployments by environment and contract name
+    #[serde(default)]
+    pub environments: BTreeMap<String, BTreeMap<String, Deployment>>,
+}
+
+impl Default for Lockfile {
+    fn default() -> Self {
+        Self {
+            version: LOCKFILE_VERSION,
+            environments: BTreeMap::new(),
+        }
+    }
+}
+
+/// A deployed contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deployment {
+    /// Contract address; ink! code is uploaded without being instantiated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    pub tx_hash: String,
+    /// keccak256 of the EVM creation code, constructor arguments included, or
+    /// the on-chain code hash (blake2-256) of ink! code
+    pub code_hash: String,
+    /// EVM chain ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<u64>,
+    /// Genesis hash of a Substrate chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub genesis_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+    /// RFC 3339 timestamp
+    pub deployed_at: String,
+}
+
+impl Lockfile {
+    /// Path of the lockfile belonging to a manifest
+    pub fn path_for(manifest: &Path) -> PathBuf {
+        manifest.with_file_name(LOCKFILE)
+    }
+
+    /// Read a lockfile, or start an empty one if there is none
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let lockfile: Self = toml::from_str(&text)
+            .with_context(|| format!("Invalid lockfile {}", path.display()))?;
+        if lockfile.version > LOCKFILE_VERSION {
+            anyhow::bail!(
+                "{} was written by a newer apex (version {}); please upgrade",
+                path.display(),
+                lockfile.version
+            );
+        }
+        Ok(lockfile)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let text = format!(
+            "# Written by `apex deploy`; commit it to share deployment addresses.\n{}",
+            toml::to_string_pretty(self)?
+        );
+        std::fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    pub fn get(&self, env: &str, contract: &str) -> Option<&Deployment> {
+        self.environments.get(env)?.get(contract)
+    }
+
+    pub fn insert(&mut self, env: &str, contract: &str, deployment: Deployment) {
+        self.environments
+            .entry(env.to_string())
+            .or_default()
+            .insert(contract.to_string(), deployment);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const MANIFEST: &str = r#"
+        [project]
+        name = "dapp"
+
+        [contracts.Token]
+        artifact = "out/Token.json"
+        args = ["Apex", "APX", 1000]
+
+        [contracts.Vault]
+        artifact = "out/Vault.json"
+        args = ["${Token}", ["${Oracle}", "0x00"]]
+
+        [contracts.Oracle]
+        artifact = "out/Oracle.bin"
+
+        [contracts.Router]
+        artifact = "out/Router.json"
+        depends_on = ["Vault"]
+
+        [environments.local]
+        endpoint = "http://127.0.0.1:8545"
+
+        [environments.testnet]
+        chain = "sepolia"
+        contracts = ["Vault"]
+
+        [environments.testnet.args]
+        Token = ["Test", "TST", 1]
+    "#;
+
+    #[test]
+    fn test_deployment_order() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+
+        let local = manifest.environment("local").unwrap();
+        assert_eq!(
+            manifest.deployment_order(local).unwrap(),
+            vec!["Oracle", "Token", "Vault", "Router"]
+        );
+
+        let testnet = manifest.environment("testnet").unwrap();
+        assert_eq!(
+            manifest.deployment_order(testnet).unwrap(),
+            vec!["Oracle", "Token", "Vault"]
+        );
+        assert_eq!(
+            manifest.args(testnet, "Token"),
+            &[
+                toml::Value::from("Test"),
+                toml::Value::from("TST"),
+                toml::Value::from(1)
+            ]
+        );
+        assert!(manifest.environment("mainnet").is_err());
+    }
+
+    #[test]
+    fn test_invalid_manifests() {
+        let cycle = r#"
+            [contracts.A]
+            artifact = "a.json"
+            args = ["${B}"]
+            [contracts.B]
+            artifact = "b.json"
+            depends_on = ["A"]
+            [environments.local]
+        "#;
+        let manifest = Manifest::parse(cycle).unwrap();
+        let err = manifest
+            .deployment_order(manifest.environment("local").unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("A -> B -> A"));
+
+        let unknown = r#"
+            [contracts.A]
+            artifact = "a.json"
+            args = ["${Missing}"]
+        "#;
+        assert!(Manifest::parse(unknown).is_err());
+
+        assert!(Manifest::parse("[contracts.A]\nartifact = \"a.json\"\ntypo = 1\n").is_err());
+    }
+
+    #[test]
+    fn test_link_args() {
+        let manifest = Manifest::parse(MANIFEST).unwrap();
+        let args = &manifest.contracts["Vault"].args;
+
+        let mut addresses = BTreeMap::new();
+        addresses.insert("Token".to_string(), "0x01".to_string());
+        assert_eq!(link_args(args, &addresses), Err("Oracle".to_string()));
+
+        addresses.insert("Oracle".to_string(), "0x02".to_string());
+        assert_eq!(
+            link_args(args, &addresses).unwrap(),
+            vec![json!("0x01"), json!(["0x02", "0x00"])]
+        );
+    }
+
+    #[test]
+    fn test_lockfile_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = Lockfile::path_for(&dir.path().join(MANIFEST_FILE));
+        assert_eq!(path, dir.path().join(LOCKFILE));
+        assert!(Lockfile::load(&path).unwrap().environments.is_empty());
+
+        let deployment = Deployment {
+            address: Some("0x5FbDB2315678afecb367f032d93F642f64180aa3".to_string()),
+            tx_hash: format!("0x{}", "ab".repeat(32)),
+            code_hash: format!("0x{}", "cd".repeat(32)),
+            chain_id: Some(31337),
+            genesis_hash: None,
+            block_number: Some(1),
+            deployed_at: "2026-01-01T00:00:00Z".to_string(),
+        };
+        let flipper = Deployment {
+            address: None,
+            tx_hash: format!("0x{}", "12".repeat(32)),
+            code_hash: format!("0x{}", "34".repeat(32)),
+            chain_id: None,
+            genesis_hash: Some(format!("0x{}", "56".repeat(32))),
+            block_number: Some(7),
+            deployed_at: "2026-01-01T00:00:00Z".to_string(),
+        };
+        let mut lockfile = Lockfile::default();
+        lockfile.insert("local", "Token", deployment.clone());
+        lockfile.insert("contracts", "Flipper", flipper.clone());
+        lockfile.save(&path).unwrap();
+
+        let loaded = Lockfile::load(&path).unwrap();
+        assert_eq!(loaded.get("local", "Token"), Some(&deployment));
+        assert_eq!(loaded.get("contracts", "Flipper"), Some(&flipper));
+        assert_eq!(loaded.get("testnet", "Token"), None);
+
+        std::fs::write(&path, "version = 99\n").unwrap();
+        assert!(Lockfile::load(&path).is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//! Runtime metadata browser for Substrate chains
+//!
+//! Lists pallets and their calls, events and errors, looks up registry types,
+//! and compares the metadata of two runtimes, either two blocks of one chain
+//! (e.g. around a runtime upgrade) or two endpoints.
+
+use anyhow::{Context, Result};
+use apex_sdk_substrate::metadata_diff::{self, ChangeKind, ItemKind, MetadataDiff};
+use apex_sdk_substrate::StorageClient;
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::tx::Target;
+
+/// Chain selection shared by `apex metadata` and `apex storage`
+#[derive(Debug, Clone, clap::Args)]
+pub struct ChainArgs {
+    /// Chain name from the configured endpoints (defaults to default_chain)
+    #[arg(short, long)]
+    pub chain: Option<String>,
+    /// RPC endpoint URL, overriding the configured one
+    #[arg(short, long)]
+    pub endpoint: Option<String>,
+    /// Read at this block hash instead of the latest block
+    #[arg(long, value_name = "BLOCK_HASH")]
+    pub at: Option<String>,
+    /// Print JSON instead of human-readable output
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl ChainArgs {
+    /// Connect to the selected Substrate chain, pinned to `--at` if given
+    pub(crate) async fn storage_client(&self) -> Result<StorageClient> {
+        let target = Target::resolve(self.chain.as_deref(), self.endpoint.as_deref())?;
+        if !target.is_substrate {
+            anyhow::bail!(
+                "{} is not a Substrate chain; runtime metadata and storage are Substrate only",
+                target.chain
+            );
+        }
+
+        let storage = connect(&target.endpoint).await?;
+        match &self.at {
+            Some(hash) => Ok(storage.storage_at(hash).await?),
+            None => Ok(storage),
+        }
+    }
+}
+
+async fn connect(endpoint: &str) -> Result<StorageClient> {
+    Ok(apex_sdk_substrate::SubstrateAdapter::connect(endpoint)
+        .await
+        .with_context(|| format!("Failed to connect to {}", endpoint))?
+        .storage())
+}
+
+#[derive(Debug, Serialize)]
+struct PalletReport {
+    index: u8,
+    name: String,
+    calls: usize,
+    events: usize,
+    errors: usize,
+    storage: usize,
+    constants: usize,
+}
+
+/// List the runtime's pallets
+pub async fn pallets(args: &ChainArgs) -> Result<()> {
+    let metadata = args.storage_client().await?.runtime_metadata().await?;
+
+    let mut pallets: Vec<PalletReport> = metadata
+        .pallets()
+        .map(|p| PalletReport {
+            index: p.index(),
+            name: p.name().to_string(),
+            calls: p.call_variants().map_or(0, |v| v.len()),
+            events: p.event_variants().map_or(0, |v| v.len()),
+            errors: p.error_variants().map_or(0, |v| v.len()),
+            storage: p.storage().map_or(0, |s| s.entries().len()),
+            constants: p.constants().len(),
+        })
+        .collect();
+    pallets.sort_by_key(|p| p.index);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&pallets)?);
+        return Ok(());
+    }
+
+    println!("\n{}", format!("Pallets ({})", pallets.len()).cyan().bold());
+    println!("{}", "═".repeat(60).dimmed());
+    for p in &pallets {
+        println!(
+            "{:>4}  {:<28} {}",
+            p.index,
+            p.name.green(),
+            format!(
+                "{} calls, {} events, {} errors, {} storage, {} constants",
+                p.calls, p.events, p.errors, p.storage, p.constants
+            )
+            .dimmed()
+        );
+    }
+    println!();
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct VariantReport {
+    index: u8,
+    name: String,
+    signature: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    docs: Vec<String>,
+}
+
+/// List a pallet's calls, events or errors
+pub async fn variants(args: &ChainArgs, pallet: &str, kind: ItemKind) -> Result<()> {
+    let metadata = args.storage_client().await?.runtime_metadata().await?;
+    let types = metadata.types();
+    let pallet_metadata = metadata
+        .pallet_by_name(pallet)
+        .ok_or_else(|| anyhow::anyhow!("Pallet {} not found", pallet))?;
+
+    let variants = match kind {
+        ItemKind::Call => pallet_metadata.call_variants(),
+        ItemKind::Event => pallet_metadata.event_variants(),
+        ItemKind::Error => pallet_metadata.error_variants(),
+        ItemKind::Storage | ItemKind::Constant => None,
+    }
+    .unwrap_or_default();
+
+    let reports: Vec<VariantReport> = variants
+        .iter()
+        .map(|v| VariantReport {
+            index: v.index,
+            name: v.name.clone(),
+            signature: metadata_diff::variant_signature(v, types),
+            docs: v.docs.clone(),
+        })
+        .collect();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        return Ok(());
+    }
+
+    let title = format!("{} {}s ({})", pallet_metadata.name(), kind, reports.len());
+    println!("\n{}", title.cyan().bold());
+    println!("{}", "═".repeat(60).dimmed());
+    if reports.is_empty() {
+        println!("{}", format!("No {}s", kind).dimmed());
+    }
+    for report in &reports {
+        println!("{:>4}  {}", report.index, report.signature.green());
+        if let Some(doc) = report.docs.iter().map(|d| d.trim()).find(|d| !d.is_empty()) {
+            println!("      {}", doc.dimmed());
+        }
+    }
+    println!();
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct TypeReport {
+    id: u32,
+    path: String,
+    definition: String,
+}
+
+/// Look up registry types by id or by a case-insensitive path filter
+pub async fn types(args: &ChainArgs, filter: Option<&str>, id: Option<u32>) -> Result<()> {
+    let metadata = args.storage_client().await?.runtime_metadata().await?;
+    let registry = metadata.types();
+    let filter = filter.map(str::to_lowercase);
+
+    let reports: Vec<TypeReport> = registry
+        .types
+        .iter()
+        .filter(|t| id.is_none_or(|id| t.id == id))
+        .filter_map(|t| {
+            let path = t.ty.path.segments.join("::");
+            let matches = match &filter {
+                Some(filter) => path.to_lowercase().contains(filter),
+                // Without a filter, only named types are listed
+                None => id.is_some() || !path.is_empty(),
+            };
+            matches.then(|| TypeReport {
+                id: t.id,
+                path,
+                definition: metadata_diff::type_definition(t.id, registry),
+            })
+        })
+        .collect();
+
+    if let Some(id) = id {
+        if reports.is_empty() {
+            anyhow::bail!("Type #{} not found", id);
+        }
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        return Ok(());
+    }
+
+    println!("\n{}", format!("Types ({})", reports.len()).cyan().bold());
+    println!("{}", "═".repeat(60).dimmed());
+    for report in &reports {
+        println!(
+            "{:>6}  {}",
+            format!("#{}", report.id).dimmed(),
+            report.definition
+        );
+    }
+    println!();
+    Ok(())
+}
+
+/// Compare the metadata of two runtimes
+///
+/// Each side is either a block hash on the selected chain or an endpoint URL.
+pub async fn diff(args: &ChainArgs, from: &str, to: &str) -> Result<()> {
+    let old = runtime_metadata_of(args, from).await?;
+    let new = runtime_metadata_of(args, to).await?;
+    let diff = MetadataDiff::between(&old, &new);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+
+    println!(
+        "\n{}",
+        format!("Metadata diff: {} → {}", from, to).cyan().bold()
+    );
+    println!("{}", "═".repeat(60).dimmed());
+    if diff.is_empty() {
+        println!("{}", "No differences".green());
+        println!();
+        return Ok(());
+    }
+
+    for pallet in &diff.pallets {
+        let index = match (pallet.old_index, pallet.new_index) {
+            (Some(old), Some(new)) if old != new => format!(" (index {} → {})", old, new),
+            (_, Some(index)) | (Some(index), None) => format!(" (index {})", index),
+            (None, None) => String::new(),
+        };
+        println!(
+            "{} {}{}",
+            marker(pallet.change),
+            pallet.name.bold(),
+            index.dimmed()
+        );
+        for item in &pallet.items {
+            let label = format!("{:<8} {}", item.item, item.name);
+            match (&item.old, &item.new) {
+                (Some(old), Some(new)) => {
+                    println!("    {} {}", marker(item.change), label);
+                    println!("        {} {}", "-".red(), old.red());
+                    println!("        {} {}", "+".green(), new.green());
+                }
+                (Some(signature), None) | (None, Some(signature)) => {
+                    println!(
+                        "    {} {}  {}",
+                        marker(item.change),
+                        label,
+                        signature.dimmed()
+                    );
+                }
+                (None, None) => println!("    {} {}", marker(item.change), label),
+            }
+        }
+    }
+
+    let (added, removed, changed) =
+        diff.pallets
+            .iter()
+            .fold((0, 0, 0), |(a, r, c), p| match p.change {
+                ChangeKind::Added => (a + 1, r, c),
+                ChangeKind::Removed => (a, r + 1, c),
+                ChangeKind::Changed => (a, r, c + 1),
+            });
+    println!(
+        "\n{}",
+        format!(
+            "{} pallet(s) added, {} removed, {} changed",
+            added, removed, changed
+        )
+        .dimmed()
+    );
+    println!();
+    Ok(())
+}
+
+fn marker(change: ChangeKind) -> colored::ColoredString {
+    match change {
+        ChangeKind::Added => "+".green().bold(),
+        ChangeKind::Removed => "-".red().bold(),
+        ChangeKind::Changed => "~".yellow().bold(),
+    }
+}
+
+/// Where to read one side of a metadata diff from
+#[derive(Debug, PartialEq, Eq)]
+enum DiffSource<'a> {
+    Endpoint(&'a str),
+    Block(&'a str),
+}
+
+impl<'a> DiffSource<'a> {
+    fn parse(source: &'a str) -> Result<Self> {
+        if source.contains("://") {
+            Ok(Self::Endpoint(source))
+        } else if source.starts_with("0x") && source.len() == 66 {
+            Ok(Self::Block(source))
+        } else {
+            anyhow::bail!(
+                "Expected a 0x-prefixed block hash or an endpoint URL, got '{}'",
+                source
+            )
+        }
+    }
+}
+
+async fn runtime_metadata_of(args: &ChainArgs, source: &str) -> Result<subxt::Metadata> {
+    let storage = match DiffSource::parse(source)? {
+        DiffSource::Endpoint(endpoint) => connect(endpoint).await?,
+        DiffSource::Block(hash) => {
+            let args = ChainArgs {
+                at: Some(hash.to_string()),
+                ..args.clone()
+            };
+            args.storage_client().await?
+        }
+    };
+    storage
+        .runtime_metadata()
+        .await
+        .with_context(|| format!("Failed to read metadata of {}", source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_source() {
+        let hash = format!("0x{}", "ab".repeat(32));
+        assert_eq!(DiffSource::parse(&hash).unwrap(), DiffSource::Block(&hash));
+        assert_eq!(
+            DiffSource::parse("wss://rpc.polkadot.io").unwrap(),
+            DiffSource::Endpoint("wss://rpc.polkadot.io")
+        );
+        assert!(DiffSource::parse("0x1234").is_err());
+        assert!(DiffSource::parse("latest").is_err());
+    }
+}
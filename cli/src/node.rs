@@ -0,0 +1,751 @@
+//! Local development chains for testing
+//!
+//! `apex node start` launches an anvil-compatible EVM node and/or a Substrate
+//! `--dev` node found on PATH, waits until their RPC answers, funds the
+//! keystore accounts and registers the endpoints in the CLI config as
+//! `local-evm` and `local-substrate`. Everything is torn down on Ctrl-C: the
+//! nodes are stopped and the config entries are restored.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::keystore::AccountType;
+
+/// Binaries tried for `--evm`, in order
+const EVM_BINARIES: &[&str] = &["anvil"];
+/// Binaries tried for `--substrate`, in order
+const SUBSTRATE_BINARIES: &[&str] = &["substrate-node", "substrate-contracts-node"];
+
+/// Chain names the endpoints are registered under
+const EVM_CHAIN: &str = "local-evm";
+const SUBSTRATE_CHAIN: &str = "local-substrate";
+
+/// Dev account funding the keystore accounts on Substrate
+const SUBSTRATE_FAUCET: &str = "//Alice";
+
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Options of `apex node start`
+#[derive(Debug, Clone, clap::Args)]
+#[command(group(clap::ArgGroup::new("kind").required(true).multiple(true)))]
+pub struct StartOptions {
+    /// Start an anvil-compatible EVM node
+    #[arg(long, group = "kind")]
+    pub evm: bool,
+    /// Start a Substrate node in --dev mode
+    #[arg(long, group = "kind")]
+    pub substrate: bool,
+    /// EVM node binary (defaults to anvil on PATH)
+    #[arg(long, value_name = "PATH")]
+    pub evm_bin: Option<PathBuf>,
+    /// Substrate node binary (defaults to substrate-node or substrate-contracts-node on PATH)
+    #[arg(long, value_name = "PATH")]
+    pub substrate_bin: Option<PathBuf>,
+    /// RPC port of the EVM node
+    #[arg(long, default_value_t = 8545)]
+    pub evm_port: u16,
+    /// RPC port of the Substrate node
+    #[arg(long, default_value_t = 9944)]
+    pub substrate_port: u16,
+    /// Seconds between EVM blocks (mines on every transaction if unset)
+    #[arg(long)]
+    pub block_time: Option<u64>,
+    /// Amount of native tokens each keystore account is funded with
+    #[arg(long, default_value = "1000")]
+    pub fund: String,
+    /// Do not fund the keystore accounts
+    #[arg(long)]
+    pub no_fund: bool,
+    /// Also make the node the default chain while it runs (the Substrate one
+    /// if both are started)
+    #[arg(long)]
+    pub set_default: bool,
+    /// Seconds to wait for a node to answer RPC requests
+    #[arg(long, default_value_t = 60)]
+    pub timeout: u64,
+    /// Print the endpoints as JSON once the nodes are ready
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Kind of local chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeKind {
+    Evm,
+    Substrate,
+}
+
+impl NodeKind {
+    fn chain(self) -> &'static str {
+        match self {
+            NodeKind::Evm => EVM_CHAIN,
+            NodeKind::Substrate => SUBSTRATE_CHAIN,
+        }
+    }
+
+    fn endpoint(self, port: u16) -> String {
+        match self {
+            NodeKind::Evm => format!("http://127.0.0.1:{}", port),
+            NodeKind::Substrate => format!("ws://127.0.0.1:{}", port),
+        }
+    }
+
+    /// Command line of the node binary
+    fn args(self, port: u16, block_time: Option<u64>) -> Vec<String> {
+        match self {
+            NodeKind::Evm => {
+                let mut args = vec!["--port".to_string(), port.to_string()];
+                if let Some(block_time) = block_time {
+                    args.extend(["--block-time".to_string(), block_time.to_string()]);
+                }
+                args
+            }
+            NodeKind::Substrate => vec![
+                "--dev".to_string(),
+                "--tmp".to_string(),
+                "--rpc-port".to_string(),
+                port.to_string(),
+                "--rpc-cors".to_string(),
+                "all".to_string(),
+            ],
+        }
+    }
+
+    /// RPC method that answers once the node is ready
+    fn ready_method(self) -> &'static str {
+        match self {
+            NodeKind::Evm => "eth_chainId",
+            NodeKind::Substrate => "system_health",
+        }
+    }
+
+    fn account_type(self) -> AccountType {
+        match self {
+            NodeKind::Evm => AccountType::Evm,
+            NodeKind::Substrate => AccountType::Substrate,
+        }
+    }
+}
+
+impl std::fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeKind::Evm => write!(f, "EVM"),
+            NodeKind::Substrate => write!(f, "Substrate"),
+        }
+    }
+}
+
+/// A running node process
+struct Node {
+    kind: NodeKind,
+    binary: PathBuf,
+    port: u16,
+    child: tokio::process::Child,
+    log: PathBuf,
+    funded: Vec<String>,
+}
+
+impl Node {
+    fn endpoint(&self) -> String {
+        self.kind.endpoint(self.port)
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct NodeReport {
+    chain: String,
+    endpoint: String,
+    binary: String,
+    pid: Option<u32>,
+    log: String,
+    funded: Vec<String>,
+}
+
+/// Start local chains and run them until Ctrl-C
+pub async fn start(opts: &StartOptions) -> Result<()> {
+    let mut kinds = Vec::new();
+    if opts.evm {
+        kinds.push(NodeKind::Evm);
+    }
+    if opts.substrate {
+        kinds.push(NodeKind::Substrate);
+    }
+
+    let config_path = crate::config::get_config_path()?;
+    let mut nodes = Vec::new();
+    let mut registered = None;
+
+    let result = async {
+        for &kind in &kinds {
+            nodes.push(spawn(kind, opts).await?);
+        }
+        for node in &mut nodes {
+            wait_ready(node, Duration::from_secs(opts.timeout)).await?;
+        }
+        if !opts.no_fund {
+            for node in &mut nodes {
+                node.funded = fund_accounts(node, &opts.fund).await.with_context(|| {
+                    format!("Failed to fund accounts on the {} node", node.kind)
+                })?;
+            }
+        }
+
+        let endpoints: Vec<(&str, String)> = nodes
+            .iter()
+            .map(|node| (node.kind.chain(), node.endpoint()))
+            .collect();
+        registered = Some(Registration::apply(
+            &config_path,
+            &endpoints,
+            opts.set_default,
+        )?);
+
+        print_ready(&nodes, opts.json)?;
+        wait_for_exit(&mut nodes).await
+    }
+    .await;
+
+    teardown(&mut nodes, registered.as_ref(), &config_path, opts.json).await;
+    match &result {
+        // Keep the logs of a failed run for troubleshooting
+        Err(_) => {
+            for node in &nodes {
+                eprintln!("{} {}", "Node log kept at".yellow(), node.log.display());
+            }
+        }
+        Ok(()) => {
+            for node in &nodes {
+                let _ = std::fs::remove_file(&node.log);
+            }
+        }
+    }
+    result
+}
+
+/// Find a node binary: the explicit path, or the first candidate on PATH
+fn find_binary(explicit: Option<&Path>, candidates: &[&str]) -> Result<PathBuf> {
+    if let Some(path) = explicit {
+        return match path.components().count() {
+            // A bare name is looked up on PATH like the defaults
+            1 => find_on_path(&path.to_string_lossy())
+                .with_context(|| format!("{} not found on PATH", path.display())),
+            _ if path.is_file() => Ok(path.to_path_buf()),
+            _ => anyhow::bail!("Node binary {} does not exist", path.display()),
+        };
+    }
+
+    candidates
+        .iter()
+        .find_map(|name| find_on_path(name))
+        .with_context(|| {
+            format!(
+                "None of {} found on PATH",
+                candidates
+                    .iter()
+                    .map(|name| format!("'{}'", name))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })
+}
+
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).find_map(|dir| {
+        let candidate = dir.join(name);
+        if is_executable(&candidate) {
+            return Some(candidate);
+        }
+        #[cfg(windows)]
+        {
+            let candidate = dir.join(format!("{}.exe", name));
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+        None
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+async fn spawn(kind: NodeKind, opts: &StartOptions) -> Result<Node> {
+    let (explicit, candidates, port) = match kind {
+        NodeKind::Evm => (opts.evm_bin.as_deref(), EVM_BINARIES, opts.evm_port),
+        NodeKind::Substrate => (
+            opts.substrate_bin.as_deref(),
+            SUBSTRATE_BINARIES,
+            opts.substrate_port,
+        ),
+    };
+    let binary = find_binary(explicit, candidates)
+        .with_context(|| format!("Cannot start the {} node", kind))?;
+
+    // Fail early instead of waiting on whatever already listens there
+    std::net::TcpListener::bind(("127.0.0.1", port)).with_context(|| {
+        format!(
+            "Port {} is in use; is a node already running? Pick another with --{}-port",
+            port,
+            match kind {
+                NodeKind::Evm => "evm",
+                NodeKind::Substrate => "substrate",
+            }
+        )
+    })?;
+
+    let log = std::env::temp_dir().join(format!("apex-node-{}-{}.log", kind.chain(), port));
+    let log_file = std::fs::File::create(&log)
+        .with_context(|| format!("Failed to create log file {}", log.display()))?;
+
+    let child = tokio::process::Command::new(&binary)
+        .args(kind.args(port, opts.block_time))
+        .stdin(Stdio::null())
+        .stdout(log_file.try_clone()?)
+        .stderr(log_file)
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start {}", binary.display()))?;
+
+    if !opts.json {
+        println!(
+            "{} {} node: {}",
+            "Starting".cyan().bold(),
+            kind,
+            binary.display()
+        );
+    }
+
+    Ok(Node {
+        kind,
+        binary,
+        port,
+        child,
+        log,
+        funded: Vec::new(),
+    })
+}
+
+/// Poll the node's RPC until it answers, or fail if the process exits
+async fn wait_ready(node: &mut Node, timeout: Duration) -> Result<()> {
+    let client = reqwest::Client::new();
+    let url = format!("http://127.0.0.1:{}", node.port);
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": node.kind.ready_method(),
+        "params": [],
+    });
+    let deadline = tokio::time::Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = node.child.try_wait()? {
+            anyhow::bail!(
+                "The {} node exited with {} before it was ready:\n{}",
+                node.kind,
+                status,
+                log_tail(&node.log, 10)
+            );
+        }
+
+        let response = client
+            .post(&url)
+            .json(&request)
+            .timeout(READY_POLL_INTERVAL * 4)
+            .send()
+            .await;
+        if let Ok(response) = response {
+            let body: serde_json::Value = response.json().await.unwrap_or_default();
+            if body.get("result").is_some() {
+                return Ok(());
+            }
+        }
+
+        if tokio::time::Instant::now() >= deadline {
+            anyhow::bail!(
+                "The {} node did not answer on port {} within {}s",
+                node.kind,
+                node.port,
+                timeout.as_secs()
+            );
+        }
+        tokio::time::sleep(READY_POLL_INTERVAL).await;
+    }
+}
+
+/// Last lines of a node log, for errors
+fn log_tail(path: &Path, lines: usize) -> String {
+    let log = std::fs::read_to_string(path).unwrap_or_default();
+    let all: Vec<&str> = log.lines().collect();
+    all[all.len().saturating_sub(lines)..].join("\n")
+}
+
+/// Fund the keystore accounts of the node's kind, returning their names
+async fn fund_accounts(node: &Node, amount: &str) -> Result<Vec<String>> {
+    let keystore = crate::account::load_keystore(&crate::keystore::get_keystore_path()?)?;
+    let accounts: Vec<(String, String)> = keystore
+        .list_accounts()
+        .into_iter()
+        .filter(|account| account.account_type == node.kind.account_type())
+        .map(|account| (account.name.clone(), account.address.clone()))
+        .collect();
+    if accounts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    match node.kind {
+        NodeKind::Evm => fund_evm(&node.endpoint(), &accounts, amount).await?,
+        NodeKind::Substrate => fund_substrate(&node.endpoint(), &accounts, amount).await?,
+    }
+    Ok(accounts.into_iter().map(|(name, _)| name).collect())
+}
+
+/// Set the balances with `anvil_setBalance`
+async fn fund_evm(endpoint: &str, accounts: &[(String, String)], amount: &str) -> Result<()> {
+    use alloy::primitives::Address;
+    use alloy::providers::Provider;
+
+    let provider = apex_sdk_evm::EvmProvider::new(endpoint)
+        .await
+        .context("Failed to connect to EVM endpoint")?;
+    let balance = crate::tx::parse_amount(amount, 18)?;
+
+    for (name, address) in accounts {
+        let address: Address = address
+            .parse()
+            .with_context(|| format!("Invalid address of account '{}'", name))?;
+        provider
+            .provider
+            .raw_request::<_, serde_json::Value>(
+                "anvil_setBalance".into(),
+                (address, format!("{:#x}", balance)),
+            )
+            .await
+            .context("The node does not support anvil_setBalance")?;
+    }
+    Ok(())
+}
+
+/// Transfer from the dev account, submitting all transfers before waiting
+async fn fund_substrate(endpoint: &str, accounts: &[(String, String)], amount: &str) -> Result<()> {
+    use sp_core::Pair;
+    use subxt::config::DefaultExtrinsicParamsBuilder;
+    use subxt::dynamic::Value;
+    use subxt::utils::AccountId32;
+
+    let client = crate::tx::SubstrateClient::connect(endpoint).await?;
+    let value: u128 = crate::tx::parse_amount(amount, client.token_decimals().await)?
+        .try_into()
+        .context("Amount does not fit in a Substrate balance")?;
+
+    let faucet = sp_core::sr25519::Pair::from_string(SUBSTRATE_FAUCET, None)
+        .map_err(|e| anyhow::anyhow!("Invalid dev account: {:?}", e))?;
+    let faucet_id = AccountId32(faucet.public().0);
+    let signer = apex_sdk_substrate::signer::Sr25519Signer::new(faucet);
+    let mut nonce = client
+        .api
+        .tx()
+        .account_nonce(&faucet_id)
+        .await
+        .context("Failed to read the dev account nonce")?;
+
+    let mut pending = Vec::new();
+    for (name, address) in accounts {
+        let dest: AccountId32 = address
+            .parse()
+            .with_context(|| format!("Invalid address of account '{}'", name))?;
+        let payload = subxt::dynamic::tx(
+            "Balances",
+            "transfer_keep_alive",
+            vec![
+                Value::unnamed_variant("Id", vec![Value::from_bytes(dest.0)]),
+                Value::u128(value),
+            ],
+        );
+        let params = DefaultExtrinsicParamsBuilder::new().nonce(nonce).build();
+        pending.push(
+            client
+                .api
+                .tx()
+                .sign_and_submit_then_watch(&payload, &signer, params)
+                .await
+                .with_context(|| format!("Failed to fund account '{}'", name))?,
+        );
+        nonce += 1;
+    }
+
+    for (progress, (name, _)) in pending.into_iter().zip(accounts) {
+        progress
+            .wait_for_finalized_success()
+            .await
+            .with_context(|| format!("Failed to fund account '{}'", name))?;
+    }
+    Ok(())
+}
+
+fn print_ready(nodes: &[Node], json: bool) -> Result<()> {
+    if json {
+        let reports: Vec<NodeReport> = nodes
+            .iter()
+            .map(|node| NodeReport {
+                chain: node.kind.chain().to_string(),
+                endpoint: node.endpoint(),
+                binary: node.binary.display().to_string(),
+                pid: node.child.id(),
+                log: node.log.display().to_string(),
+                funded: node.funded.clone(),
+            })
+            .collect();
+        println!("{}", serde_json::to_string(&reports)?);
+        return Ok(());
+    }
+
+    println!("\n{}", "Local Nodes Ready".green().bold());
+    println!("{}", "═══════════════════════════════════════".dimmed());
+    for node in nodes {
+        println!("{} ({})", node.kind.chain().bold(), node.kind);
+        println!("  {}: {}", "Endpoint".dimmed(), node.endpoint());
+        println!("  {}: {}", "Log".dimmed(), node.log.display());
+        if !node.funded.is_empty() {
+            println!("  {}: {}", "Funded".dimmed(), node.funded.join(", "));
+        }
+    }
+    println!(
+        "\n{}",
+        format!(
+            "Use them with --chain {}. Press Ctrl-C to stop.",
+            nodes
+                .iter()
+                .map(|node| node.kind.chain())
+                .collect::<Vec<_>>()
+                .join(" or --chain ")
+        )
+        .dimmed()
+    );
+    Ok(())
+}
+
+/// Wait for Ctrl-C, SIGTERM, or a node exiting on its own
+async fn wait_for_exit(nodes: &mut [Node]) -> Result<()> {
+    let exited = subxt::ext::futures::future::select_all(
+        nodes
+            .iter_mut()
+            .map(|node| Box::pin(async move { (node.kind, node.child.wait().await) })),
+    );
+
+    tokio::select! {
+        ((kind, status), _, _) = exited => {
+            anyhow::bail!("The {} node exited unexpectedly ({})", kind, status?)
+        }
+        result = shutdown_signal() => result,
+    }
+}
+
+#[cfg(unix)]
+async fn shutdown_signal() -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        result = tokio::signal::ctrl_c() => Ok(result?),
+        _ = terminate.recv() => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+async fn shutdown_signal() -> Result<()> {
+    Ok(tokio::signal::ctrl_c().await?)
+}
+
+/// Stop the nodes and undo the config changes; errors are reported, not returned
+async fn teardown(
+    nodes: &mut [Node],
+    registered: Option<&Registration>,
+    config_path: &Path,
+    json: bool,
+) {
+    for node in nodes.iter_mut() {
+        if matches!(node.child.try_wait(), Ok(Some(_))) {
+            continue;
+        }
+        if let Err(e) = node.child.kill().await {
+            eprintln!("{} {}: {}", "Failed to stop the".red(), node.kind, e);
+        }
+    }
+    if let Some(registration) = registered {
+        if let Err(e) = registration.restore(config_path) {
+            eprintln!("{} {}", "Failed to restore the CLI config:".red(), e);
+        }
+    }
+    if !json && !nodes.is_empty() {
+        println!("\n{}", "Local nodes stopped.".green());
+    }
+}
+
+/// Config values replaced while the nodes run, restored on teardown
+#[derive(Debug, Default, PartialEq)]
+struct Registration {
+    endpoints: Vec<(String, Option<String>)>,
+    default: Option<(String, String)>,
+}
+
+impl Registration {
+    /// Register the endpoints (and the default chain) in the config file
+    fn apply(path: &Path, endpoints: &[(&str, String)], set_default: bool) -> Result<Self> {
+        let mut config = Config::load(path)?;
+        let registration = Self::update(&mut config, endpoints, set_default);
+        config.save(path)?;
+        Ok(registration)
+    }
+
+    fn update(config: &mut Config, endpoints: &[(&str, String)], set_default: bool) -> Self {
+        let mut registration = Self::default();
+        for (chain, endpoint) in endpoints {
+            let previous = config.endpoints.insert(chain.to_string(), endpoint.clone());
+            registration.endpoints.push((chain.to_string(), previous));
+        }
+        if let (true, Some((chain, endpoint))) = (set_default, endpoints.last()) {
+            registration.default = Some((
+                std::mem::replace(&mut config.default_chain, chain.to_string()),
+                std::mem::replace(&mut config.default_endpoint, endpoint.clone()),
+            ));
+        }
+        registration
+    }
+
+    fn restore(&self, path: &Path) -> Result<()> {
+        // Reload so changes made while the nodes ran are kept
+        let mut config = Config::load(path)?;
+        self.revert(&mut config);
+        config.save(path)
+    }
+
+    fn revert(&self, config: &mut Config) {
+        for (chain, previous) in &self.endpoints {
+            match previous {
+                Some(endpoint) => config.endpoints.insert(chain.clone(), endpoint.clone()),
+                None => config.endpoints.remove(chain),
+            };
+        }
+        if let Some((chain, endpoint)) = &self.default {
+            config.default_chain = chain.clone();
+            config.default_endpoint = endpoint.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_node_args() {
+        assert_eq!(NodeKind::Evm.args(8546, None), vec!["--port", "8546"]);
+        assert_eq!(
+            NodeKind::Evm.args(8545, Some(2)),
+            vec!["--port", "8545", "--block-time", "2"]
+        );
+        assert_eq!(
+            NodeKind::Substrate.args(9945, Some(2)),
+            vec!["--dev", "--tmp", "--rpc-port", "9945", "--rpc-cors", "all"]
+        );
+        assert_eq!(NodeKind::Evm.endpoint(8545), "http://127.0.0.1:8545");
+        assert_eq!(NodeKind::Substrate.endpoint(9944), "ws://127.0.0.1:9944");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_binary() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let node = dir.path().join("substrate-contracts-node");
+        std::fs::write(&node, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&node, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let not_executable = dir.path().join("readme");
+        std::fs::write(&not_executable, "").unwrap();
+
+        assert_eq!(find_binary(Some(&node), &[]).unwrap(), node);
+        assert!(find_binary(Some(&dir.path().join("missing")), &[]).is_err());
+        assert!(!is_executable(&not_executable));
+        assert!(!is_executable(dir.path()));
+        assert!(find_binary(None, &["apex-node-that-does-not-exist"]).is_err());
+    }
+
+    #[test]
+    fn test_registration_roundtrip() {
+        let mut config = Config::default();
+        config
+            .endpoints
+            .insert(EVM_CHAIN.to_string(), "http://10.0.0.1:8545".to_string());
+        let original = (
+            config.default_chain.clone(),
+            config.default_endpoint.clone(),
+            config.endpoints.clone(),
+        );
+
+        let endpoints = [
+            (EVM_CHAIN, NodeKind::Evm.endpoint(8545)),
+            (SUBSTRATE_CHAIN, NodeKind::Substrate.endpoint(9944)),
+        ];
+        let registration = Registration::update(&mut config, &endpoints, true);
+        assert_eq!(config.endpoints[EVM_CHAIN], "http://127.0.0.1:8545");
+        assert_eq!(config.endpoints[SUBSTRATE_CHAIN], "ws://127.0.0.1:9944");
+        assert_eq!(config.default_chain, SUBSTRATE_CHAIN);
+        assert_eq!(
+            config.resolve_endpoint(None, None).unwrap().1,
+            "ws://127.0.0.1:9944"
+        );
+
+        registration.revert(&mut config);
+        assert_eq!(
+            (
+                config.default_chain.clone(),
+                config.default_endpoint.clone(),
+                config.endpoints.clone()
+            ),
+            original
+        );
+    }
+
+    #[test]
+    fn test_registration_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.json");
+
+        let endpoints = [(EVM_CHAIN, NodeKind::Evm.endpoint(8545))];
+        let registration = Registration::apply(&path, &endpoints, false).unwrap();
+        assert_eq!(
+            Config::load(&path).unwrap().endpoints[EVM_CHAIN],
+            "http://127.0.0.1:8545"
+        );
+
+        // Settings changed while the node runs survive the teardown
+        let mut config = Config::load(&path).unwrap();
+        config.default_account = Some("alice".to_string());
+        config.save(&path).unwrap();
+
+        registration.restore(&path).unwrap();
+        let config = Config::load(&path).unwrap();
+        assert!(!config.endpoints.contains_key(EVM_CHAIN));
+        assert_eq!(config.default_account.as_deref(), Some("alice"));
+    }
+}
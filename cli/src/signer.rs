@@ -0,0 +1,869 @@
+//! Signer backends for keystore accounts
+//!
+//! Every account in the keystore records where its key is held:
+//!
+//! - **Keystore**: encrypted in the keystore file (the default)
+//! - **Keychain**: in the OS secret service (GNOME Keyring, KWallet, ...)
+//!   over D-Bus, so no keystore password is needed
+//! - **Remote**: in a signer speaking Ethereum JSON-RPC (`eth_accounts`,
+//!   `eth_signTransaction`), such as Clef or Web3Signer. The key never
+//!   leaves the signer, so only EVM transactions can be signed with it
+//!
+//! Keys unlocked with `apex account unlock` are cached by the session agent
+//! (see [`crate::agent`]) and used without prompting until they expire.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::keystore::{AccountSecret, AccountType, EncryptedAccount, Keystore};
+
+/// Environment variable holding the keystore password for non-interactive use
+pub const PASSWORD_ENV: &str = "APEX_KEYSTORE_PASSWORD";
+
+/// Where an account's key is held
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SignerBackend {
+    /// Encrypted in the keystore file
+    #[default]
+    Keystore,
+    /// In the OS secret service
+    Keychain,
+    /// In a remote JSON-RPC signer
+    Remote { url: String },
+}
+
+impl SignerBackend {
+    pub fn is_keystore(&self) -> bool {
+        matches!(self, SignerBackend::Keystore)
+    }
+}
+
+impl std::fmt::Display for SignerBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignerBackend::Keystore => write!(f, "keystore file"),
+            SignerBackend::Keychain => write!(f, "OS keychain"),
+            SignerBackend::Remote { url } => write!(f, "remote signer at {}", url),
+        }
+    }
+}
+
+/// An unlocked account, ready to sign
+pub enum AccountSigner {
+    /// Key material held by this process
+    Local(AccountSecret),
+    /// Key held by a remote signer, which signs on request
+    Remote(RemoteSigner),
+}
+
+impl AccountSigner {
+    /// The decrypted secret, for everything a remote signer cannot do
+    pub fn secret(&self) -> Result<&AccountSecret> {
+        match self {
+            AccountSigner::Local(secret) => Ok(secret),
+            AccountSigner::Remote(remote) => anyhow::bail!(
+                "Account {} is held by the remote signer at {}, which can only sign EVM transactions",
+                remote.address(),
+                remote.url()
+            ),
+        }
+    }
+}
+
+/// Unlock an account through its backend, using the session agent's cached
+/// key if there is one
+///
+/// With `account_type` set, accounts of the other type are rejected.
+pub fn unlock(name: &str, account_type: Option<AccountType>) -> Result<AccountSigner> {
+    open(name, account_type, true)
+}
+
+/// Unlock an account through its backend, bypassing the session agent
+///
+/// Used where the secret is shown or exported, so a cached key alone is not
+/// enough to reveal it.
+pub fn unlock_uncached(name: &str) -> Result<AccountSigner> {
+    open(name, None, false)
+}
+
+fn open(name: &str, account_type: Option<AccountType>, use_agent: bool) -> Result<AccountSigner> {
+    let keystore_path = crate::keystore::get_keystore_path()?;
+    let mut keystore = crate::account::load_keystore(&keystore_path)?;
+
+    let account = keystore
+        .list_accounts()
+        .into_iter()
+        .find(|a| a.name == name)
+        .ok_or_else(|| anyhow::anyhow!("Account '{}' not found", name))?
+        .clone();
+    if let Some(account_type) = account_type {
+        if account.account_type != account_type {
+            anyhow::bail!(
+                "Account '{}' is a {} account but the chain needs a {} account",
+                name,
+                account.account_type,
+                account_type
+            );
+        }
+    }
+
+    let root = keystore.root_account(name)?.clone();
+    if let SignerBackend::Remote { url } = &root.backend {
+        let address = account
+            .address
+            .parse()
+            .with_context(|| format!("Invalid address for remote account '{}'", name))?;
+        return Ok(AccountSigner::Remote(RemoteSigner::new(url, address)));
+    }
+
+    let cached = if use_agent {
+        crate::agent::cached_secret(&root.name)
+    } else {
+        None
+    };
+    let mut data = match cached {
+        Some(data) => data,
+        None => load_secret_data(&mut keystore, &root)?,
+    };
+
+    let secret = keystore.secret_from_data(name, std::mem::take(&mut *data))?;
+    Ok(AccountSigner::Local(secret))
+}
+
+/// Fetch the decrypted secret of a root account from its backend
+pub fn load_secret_data(
+    keystore: &mut Keystore,
+    root: &EncryptedAccount,
+) -> Result<Zeroizing<Vec<u8>>> {
+    match &root.backend {
+        SignerBackend::Keystore => {
+            let password = read_password(&root.name)?;
+            Ok(Zeroizing::new(keystore.get_account(&root.name, &password)?))
+        }
+        SignerBackend::Keychain => keychain::load(&root.name),
+        SignerBackend::Remote { url } => anyhow::bail!(
+            "Account '{}' is held by the remote signer at {} and its key cannot be read",
+            root.name,
+            url
+        ),
+    }
+}
+
+/// Keystore password from `APEX_KEYSTORE_PASSWORD` or a prompt
+pub fn read_password(name: &str) -> Result<Zeroizing<String>> {
+    match std::env::var(PASSWORD_ENV) {
+        Ok(password) => Ok(Zeroizing::new(password)),
+        Err(_) => rpassword::prompt_password(format!("Enter password for '{}': ", name))
+            .map(Zeroizing::new)
+            .context("Failed to read password"),
+    }
+}
+
+/// OS keychain access through the freedesktop Secret Service over D-Bus
+///
+/// Secrets are stored hex-encoded in the default collection under the
+/// attributes `service=apex-sdk account=<name>`, the layout `secret-tool`
+/// uses, and travel over the bus encrypted with a Diffie-Hellman session.
+pub mod keychain {
+    use anyhow::{Context, Result};
+    use secret_service::blocking::SecretService;
+    use secret_service::EncryptionType;
+    use std::collections::HashMap;
+    use zeroize::Zeroizing;
+
+    const SERVICE: &str = "apex-sdk";
+
+    /// Store an account's secret, replacing any existing one
+    pub fn store(name: &str, secret: &[u8]) -> Result<()> {
+        store_in(&connect(EncryptionType::Dh)?, name, secret)
+    }
+
+    /// Load an account's secret
+    pub fn load(name: &str) -> Result<Zeroizing<Vec<u8>>> {
+        load_from(&connect(EncryptionType::Dh)?, name)
+    }
+
+    /// Delete an account's secret; missing entries are not an error
+    pub fn delete(name: &str) -> Result<()> {
+        delete_from(&connect(EncryptionType::Dh)?, name)
+    }
+
+    pub(super) fn connect(encryption: EncryptionType) -> Result<SecretService<'static>> {
+        SecretService::connect(encryption).context(
+            "Failed to connect to the OS keychain. Make sure a secret service such as \
+             GNOME Keyring or KWallet is running on the D-Bus session bus",
+        )
+    }
+
+    pub(super) fn store_in(service: &SecretService, name: &str, secret: &[u8]) -> Result<()> {
+        let collection = service
+            .get_default_collection()
+            .context("OS keychain has no default collection")?;
+        collection
+            .ensure_unlocked()
+            .context("Failed to unlock the OS keychain")?;
+
+        let encoded = Zeroizing::new(hex::encode(secret));
+        collection
+            .create_item(
+                &format!("Apex SDK account '{}'", name),
+                attributes(name),
+                encoded.as_bytes(),
+                true,
+                "text/plain",
+            )
+            .with_context(|| format!("Failed to store '{}' in the OS keychain", name))?;
+        Ok(())
+    }
+
+    pub(super) fn load_from(service: &SecretService, name: &str) -> Result<Zeroizing<Vec<u8>>> {
+        let found = service
+            .search_items(attributes(name))
+            .context("Failed to search the OS keychain")?;
+        let item = found
+            .unlocked
+            .first()
+            .or(found.locked.first())
+            .ok_or_else(|| anyhow::anyhow!("No key for account '{}' in the OS keychain", name))?;
+        item.ensure_unlocked()
+            .context("Failed to unlock the OS keychain")?;
+
+        let encoded = Zeroizing::new(
+            item.get_secret()
+                .with_context(|| format!("Failed to read '{}' from the OS keychain", name))?,
+        );
+        std::str::from_utf8(&encoded)
+            .ok()
+            .and_then(|encoded| hex::decode(encoded.trim()).ok())
+            .map(Zeroizing::new)
+            .ok_or_else(|| anyhow::anyhow!("OS keychain entry for '{}' is not valid", name))
+    }
+
+    pub(super) fn delete_from(service: &SecretService, name: &str) -> Result<()> {
+        let found = service
+            .search_items(attributes(name))
+            .context("Failed to search the OS keychain")?;
+        for item in found.unlocked.iter().chain(&found.locked) {
+            item.delete()
+                .with_context(|| format!("Failed to remove '{}' from the OS keychain", name))?;
+        }
+        Ok(())
+    }
+
+    fn attributes(name: &str) -> HashMap<&str, &str> {
+        HashMap::from([("service", SERVICE), ("account", name)])
+    }
+}
+
+/// Client for a remote signer speaking Ethereum JSON-RPC
+pub struct RemoteSigner {
+    url: String,
+    address: alloy::primitives::Address,
+    client: reqwest::Client,
+}
+
+impl RemoteSigner {
+    pub fn new(url: &str, address: alloy::primitives::Address) -> Self {
+        Self {
+            url: url.to_string(),
+            address,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn address(&self) -> alloy::primitives::Address {
+        self.address
+    }
+
+    /// Accounts the signer at `url` can sign for (`eth_accounts`)
+    pub async fn accounts(url: &str) -> Result<Vec<alloy::primitives::Address>> {
+        let result = rpc_call(
+            &reqwest::Client::new(),
+            url,
+            "eth_accounts",
+            serde_json::json!([]),
+        )
+        .await?;
+        serde_json::from_value(result).context("Invalid eth_accounts response from remote signer")
+    }
+
+    /// Sign a transaction (`eth_signTransaction`), returning the raw signed
+    /// transaction ready for `eth_sendRawTransaction`
+    ///
+    /// The request must be complete (nonce, gas, fees and chain ID); signers
+    /// differ in which fields they fill in themselves. The signed transaction
+    /// is checked to be signed by this account and to keep the requested
+    /// recipient, value, calldata, nonce and chain ID.
+    pub async fn sign_transaction(
+        &self,
+        tx: alloy::rpc::types::TransactionRequest,
+    ) -> Result<alloy::primitives::Bytes> {
+        use alloy::rpc::types::TransactionInput;
+
+        let mut tx = tx.from(self.address);
+        // Geth-style signers read `input`, older ones (Clef) `data`
+        if let Some(input) = tx.input.input().cloned() {
+            tx.input = TransactionInput::both(input);
+        }
+
+        let params = serde_json::to_value([&tx]).context("Failed to encode transaction")?;
+        let result = rpc_call(&self.client, &self.url, "eth_signTransaction", params).await?;
+
+        // Web3Signer returns the raw transaction, Geth and Clef `{raw, tx}`
+        let raw = match &result {
+            serde_json::Value::String(raw) => Some(raw.as_str()),
+            serde_json::Value::Object(object) => object.get("raw").and_then(|raw| raw.as_str()),
+            _ => None,
+        }
+        .ok_or_else(|| anyhow::anyhow!("Unexpected eth_signTransaction response: {}", result))?;
+
+        let raw: alloy::primitives::Bytes = raw
+            .parse()
+            .context("Remote signer returned an invalid raw transaction")?;
+        self.verify_signed(&raw, &tx)?;
+        Ok(raw)
+    }
+
+    /// Check that a signed transaction is the requested one, signed by us
+    fn verify_signed(&self, raw: &[u8], tx: &alloy::rpc::types::TransactionRequest) -> Result<()> {
+        use alloy::consensus::transaction::SignerRecoverable;
+        use alloy::consensus::{Transaction, TxEnvelope};
+        use alloy::eips::eip2718::Decodable2718;
+        use alloy::primitives::TxKind;
+
+        let signed = TxEnvelope::decode_2718(&mut &raw[..])
+            .context("Remote signer returned an invalid raw transaction")?;
+        let signer = signed
+            .recover_signer()
+            .context("Remote signer returned an invalid signature")?;
+        if signer != self.address {
+            anyhow::bail!(
+                "Remote signer signed with {} instead of {}",
+                signer,
+                self.address
+            );
+        }
+
+        let changed =
+            |field: &str| anyhow::anyhow!("Remote signer changed the transaction's {}", field);
+        if signed.kind() != tx.to.unwrap_or(TxKind::Create) {
+            return Err(changed("recipient"));
+        }
+        if signed.value() != tx.value.unwrap_or_default() {
+            return Err(changed("value"));
+        }
+        if signed.input().as_ref() != tx.input.input().map_or(&[][..], |input| input.as_ref()) {
+            return Err(changed("calldata"));
+        }
+        if tx.nonce.is_some_and(|nonce| nonce != signed.nonce()) {
+            return Err(changed("nonce"));
+        }
+        if tx.chain_id.is_some() && signed.chain_id() != tx.chain_id {
+            return Err(changed("chain ID"));
+        }
+        Ok(())
+    }
+}
+
+async fn rpc_call(
+    client: &reqwest::Client,
+    url: &str,
+    method: &str,
+    params: serde_json::Value,
+) -> Result<serde_json::Value> {
+    let body = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": params,
+    });
+
+    let response: serde_json::Value = client
+        .post(url)
+        .json(&body)
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach remote signer at {}", url))?
+        .error_for_status()
+        .with_context(|| format!("Remote signer at {} returned an error", url))?
+        .json()
+        .await
+        .context("Invalid JSON-RPC response from remote signer")?;
+
+    if let Some(error) = response.get("error") {
+        let message = error
+            .get("message")
+            .and_then(|m| m.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+        anyhow::bail!("Remote signer rejected {}: {}", method, message);
+    }
+
+    response
+        .get("result")
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Remote signer returned no result for {}", method))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_rpc::stand_in;
+    use serde_json::json;
+
+    #[test]
+    fn test_backend_serialization() {
+        assert_eq!(
+            serde_json::to_value(SignerBackend::Keychain).unwrap(),
+            json!({"type": "keychain"})
+        );
+        let remote = SignerBackend::Remote {
+            url: "http://127.0.0.1:8550".to_string(),
+        };
+        let value = serde_json::to_value(&remote).unwrap();
+        assert_eq!(
+            value,
+            json!({"type": "remote", "url": "http://127.0.0.1:8550"})
+        );
+        assert_eq!(
+            serde_json::from_value::<SignerBackend>(value).unwrap(),
+            remote
+        );
+        assert!(SignerBackend::default().is_keystore());
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_accounts() {
+        let (url, _) = stand_in(|method, _| {
+            assert_eq!(method, "eth_accounts");
+            Ok(json!(["0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"]))
+        })
+        .await;
+
+        let accounts = RemoteSigner::accounts(&url).await.unwrap();
+        assert_eq!(
+            accounts,
+            vec!["0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+                .parse::<alloy::primitives::Address>()
+                .unwrap()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_sign_transaction() {
+        use alloy::consensus::{SignableTransaction, TxEip1559, TxEnvelope};
+        use alloy::eips::eip2718::Encodable2718;
+        use alloy::network::TransactionBuilder;
+        use alloy::primitives::{address, hex, Bytes, TxKind, U256};
+        use alloy::signers::SignerSync;
+        use alloy_signer_local::PrivateKeySigner;
+
+        // Anvil's first and second development keys
+        let key: PrivateKeySigner =
+            "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+                .parse()
+                .unwrap();
+        let other: PrivateKeySigner =
+            "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d"
+                .parse()
+                .unwrap();
+        let from = key.address();
+        let to = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+
+        // Signs what it was asked to, except for nonce 9 (value changed) and
+        // 10 (wrong key); nonce 7 gets a Clef-style response, others Web3Signer's
+        let (url, mut requests) = stand_in(move |method, params| {
+            assert_eq!(method, "eth_signTransaction");
+            let nonce = u64::from_str_radix(
+                params[0]["nonce"]
+                    .as_str()
+                    .unwrap()
+                    .trim_start_matches("0x"),
+                16,
+            )
+            .unwrap();
+            let tx = TxEip1559 {
+                chain_id: 31337,
+                nonce,
+                gas_limit: 21_000,
+                max_fee_per_gas: 2_000_000_000,
+                max_priority_fee_per_gas: 1_000_000_000,
+                to: TxKind::Call(to),
+                value: U256::from(if nonce == 9 { 1_000_000 } else { 1000 }),
+                input: Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb]),
+                ..Default::default()
+            };
+            let key = if nonce == 10 { &other } else { &key };
+            let signature = key.sign_hash_sync(&tx.signature_hash()).unwrap();
+            let raw =
+                hex::encode_prefixed(TxEnvelope::from(tx.into_signed(signature)).encoded_2718());
+            Ok(match nonce {
+                7 => json!({"raw": raw, "tx": {}}),
+                _ => json!(raw),
+            })
+        })
+        .await;
+
+        let signer = RemoteSigner::new(&url, from);
+        let tx = alloy::rpc::types::TransactionRequest::default()
+            .with_to(to)
+            .with_value(U256::from(1000))
+            .with_input(Bytes::from(vec![0xa9, 0x05, 0x9c, 0xbb]))
+            .with_nonce(7)
+            .with_chain_id(31337);
+
+        let raw = signer.sign_transaction(tx.clone()).await.unwrap();
+        assert_eq!(raw[0], 0x02);
+
+        let request = requests.recv().await.unwrap();
+        let sent = &request["params"][0];
+        assert_eq!(
+            sent["from"],
+            json!("0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266")
+        );
+        assert_eq!(sent["chainId"], json!("0x7a69"));
+        assert_eq!(sent["input"], json!("0xa9059cbb"));
+        assert_eq!(sent["data"], json!("0xa9059cbb"));
+
+        assert!(signer
+            .sign_transaction(tx.clone().with_nonce(8))
+            .await
+            .is_ok());
+
+        let err = signer
+            .sign_transaction(tx.clone().with_nonce(9))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("value"), "{}", err);
+
+        let err = signer
+            .sign_transaction(tx.clone().with_nonce(10))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("signed with"), "{}", err);
+
+        // The signer answered for chain 31337, not the requested one
+        let err = signer
+            .sign_transaction(tx.with_nonce(8).with_chain_id(1))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("chain ID"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_error() {
+        let (url, _) =
+            stand_in(|_, _| Err(json!({"code": -32000, "message": "Request denied"}))).await;
+
+        let err = RemoteSigner::accounts(&url).await.unwrap_err();
+        assert!(err.to_string().contains("Request denied"));
+    }
+
+    /// Minimal in-memory Secret Service: a default collection whose items
+    /// live at `<collection>/<id>`, with `plain` sessions only
+    mod secret_service_stand_in {
+        use aes::cipher::block_padding::Pkcs7;
+        use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+        use num_bigint::BigUint;
+        use rand::Rng;
+        use std::collections::HashMap;
+        use std::sync::{Arc, Mutex};
+        use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+        pub const COLLECTION: &str = "/org/freedesktop/secrets/collection/default";
+        const DH: &str = "dh-ietf1024-sha256-aes128-cbc-pkcs7";
+        // RFC 2409 second Oakley group, as used by the Secret Service API
+        const MODP_1024: &[u8] = b"FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD1\
+            29024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F1437\
+            4FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5\
+            AE9F24117C4B1FE649286651ECE65381FFFFFFFFFFFFFFFF";
+
+        type Secret = (OwnedObjectPath, Vec<u8>, Vec<u8>, String);
+        type Key = [u8; 16];
+        type Items = HashMap<u32, (HashMap<String, String>, Vec<u8>)>;
+
+        /// Items by id and the AES key of every session (`None` for plain ones)
+        #[derive(Clone, Default)]
+        pub struct Keyring {
+            items: Arc<Mutex<Items>>,
+            sessions: Arc<Mutex<HashMap<OwnedObjectPath, Option<Key>>>>,
+        }
+
+        impl Keyring {
+            fn session_key(&self, session: &OwnedObjectPath) -> zbus::fdo::Result<Option<Key>> {
+                self.sessions
+                    .lock()
+                    .unwrap()
+                    .get(session)
+                    .copied()
+                    .ok_or_else(|| zbus::fdo::Error::UnknownObject(session.to_string()))
+            }
+        }
+
+        fn path(value: &str) -> OwnedObjectPath {
+            OwnedObjectPath::try_from(value.to_string()).unwrap()
+        }
+
+        fn item_path(id: u32) -> OwnedObjectPath {
+            path(&format!("{}/{}", COLLECTION, id))
+        }
+
+        /// Server half of the key exchange: our public key and the derived AES key
+        fn negotiate(client_public: &[u8]) -> (Vec<u8>, Key) {
+            let prime = BigUint::parse_bytes(MODP_1024, 16).unwrap();
+            let mut private = [0u8; 128];
+            rand::rng().fill(&mut private[..]);
+            let private = BigUint::from_bytes_be(&private);
+
+            let public = BigUint::from(2u8).modpow(&private, &prime);
+            let shared = BigUint::from_bytes_be(client_public)
+                .modpow(&private, &prime)
+                .to_bytes_be();
+            let mut ikm = vec![0u8; 128 - shared.len()];
+            ikm.extend(shared);
+
+            let mut key = [0u8; 16];
+            hkdf::Hkdf::<sha2::Sha256>::new(None, &ikm)
+                .expand(&[], &mut key)
+                .unwrap();
+            (public.to_bytes_be(), key)
+        }
+
+        pub struct Service {
+            pub keyring: Keyring,
+        }
+
+        #[zbus::interface(name = "org.freedesktop.Secret.Service")]
+        impl Service {
+            #[zbus(out_args("output", "result"))]
+            fn open_session(
+                &self,
+                algorithm: &str,
+                input: Value<'_>,
+            ) -> zbus::fdo::Result<(OwnedValue, OwnedObjectPath)> {
+                let (output, key) = match algorithm {
+                    "plain" => (OwnedValue::from(0u8), None),
+                    DH => {
+                        let client_public = Vec::<u8>::try_from(input)
+                            .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?;
+                        let (public, key) = negotiate(&client_public);
+                        let output = OwnedValue::try_from(Value::from(public))
+                            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+                        (output, Some(key))
+                    }
+                    _ => return Err(zbus::fdo::Error::NotSupported(algorithm.to_string())),
+                };
+                let mut sessions = self.keyring.sessions.lock().unwrap();
+                let session = path(&format!(
+                    "/org/freedesktop/secrets/session/{}",
+                    sessions.len() + 1
+                ));
+                sessions.insert(session.clone(), key);
+                Ok((output, session))
+            }
+
+            fn read_alias(&self, name: &str) -> OwnedObjectPath {
+                path(if name == "default" { COLLECTION } else { "/" })
+            }
+
+            #[zbus(out_args("unlocked", "locked"))]
+            fn search_items(
+                &self,
+                attributes: HashMap<String, String>,
+            ) -> (Vec<OwnedObjectPath>, Vec<OwnedObjectPath>) {
+                let items = self.keyring.items.lock().unwrap();
+                let found = items
+                    .iter()
+                    .filter(|(_, (attrs, _))| {
+                        attributes.iter().all(|(k, v)| attrs.get(k) == Some(v))
+                    })
+                    .map(|(id, _)| item_path(*id))
+                    .collect();
+                (found, Vec::new())
+            }
+        }
+
+        pub struct Collection {
+            pub keyring: Keyring,
+        }
+
+        #[zbus::interface(name = "org.freedesktop.Secret.Collection")]
+        impl Collection {
+            #[zbus(out_args("item", "prompt"))]
+            async fn create_item(
+                &self,
+                properties: HashMap<String, OwnedValue>,
+                secret: Secret,
+                replace: bool,
+                #[zbus(object_server)] server: &zbus::ObjectServer,
+            ) -> zbus::fdo::Result<(OwnedObjectPath, OwnedObjectPath)> {
+                let attributes: HashMap<String, String> = properties
+                    .get("org.freedesktop.Secret.Item.Attributes")
+                    .and_then(|attrs| HashMap::try_from(attrs.try_clone().ok()?).ok())
+                    .ok_or_else(|| zbus::fdo::Error::InvalidArgs("attributes".to_string()))?;
+
+                let (session, iv, value, _) = secret;
+                let value = match self.keyring.session_key(&session)? {
+                    Some(key) => cbc::Decryptor::<aes::Aes128>::new_from_slices(&key, &iv)
+                        .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?
+                        .decrypt_padded_vec_mut::<Pkcs7>(&value)
+                        .map_err(|e| zbus::fdo::Error::InvalidArgs(e.to_string()))?,
+                    None => value,
+                };
+
+                let (id, is_new) = {
+                    let mut items = self.keyring.items.lock().unwrap();
+                    let existing = items
+                        .iter()
+                        .find(|(_, (attrs, _))| replace && *attrs == attributes)
+                        .map(|(id, _)| *id);
+                    let id = existing.unwrap_or(items.len() as u32 + 1);
+                    items.insert(id, (attributes, value));
+                    (id, existing.is_none())
+                };
+                if is_new {
+                    let item = Item {
+                        id,
+                        keyring: self.keyring.clone(),
+                    };
+                    server.at(item_path(id), item).await?;
+                }
+                Ok((item_path(id), path("/")))
+            }
+
+            #[zbus(property)]
+            fn locked(&self) -> bool {
+                false
+            }
+        }
+
+        struct Item {
+            id: u32,
+            keyring: Keyring,
+        }
+
+        #[zbus::interface(name = "org.freedesktop.Secret.Item")]
+        impl Item {
+            fn get_secret(&self, session: OwnedObjectPath) -> zbus::fdo::Result<Secret> {
+                let key = self.keyring.session_key(&session)?;
+                let items = self.keyring.items.lock().unwrap();
+                let (_, secret) = items
+                    .get(&self.id)
+                    .ok_or_else(|| zbus::fdo::Error::UnknownObject(self.id.to_string()))?;
+                let (iv, value) = match key {
+                    Some(key) => {
+                        let iv: [u8; 16] = rand::random();
+                        let value = cbc::Encryptor::<aes::Aes128>::new(&key.into(), &iv.into())
+                            .encrypt_padded_vec_mut::<Pkcs7>(secret);
+                        (iv.to_vec(), value)
+                    }
+                    None => (Vec::new(), secret.clone()),
+                };
+                Ok((session, iv, value, "text/plain".to_string()))
+            }
+
+            fn delete(&self) -> OwnedObjectPath {
+                self.keyring.items.lock().unwrap().remove(&self.id);
+                path("/")
+            }
+
+            #[zbus(property)]
+            fn locked(&self) -> bool {
+                false
+            }
+        }
+    }
+
+    /// Set in the child process that runs the round trip against a private bus
+    const KEYCHAIN_TEST_BUS: &str = "APEX_KEYCHAIN_TEST_BUS";
+
+    #[test]
+    #[ignore = "starts a private dbus-daemon"]
+    fn test_keychain_round_trip() {
+        use std::io::BufRead;
+        use std::process::{Command, Stdio};
+
+        if let Ok(address) = std::env::var(KEYCHAIN_TEST_BUS) {
+            return keychain_round_trip(&address);
+        }
+
+        // A private session bus, so the test never touches the user's keyring
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("Failed to start dbus-daemon");
+        let mut address = String::new();
+        std::io::BufReader::new(daemon.stdout.take().unwrap())
+            .read_line(&mut address)
+            .unwrap();
+        let address = address.trim();
+
+        // The secret service client only connects to the session bus, so the
+        // round trip runs in a child process whose session bus is ours
+        let status = Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "signer::tests::test_keychain_round_trip",
+                "--ignored",
+                "--nocapture",
+            ])
+            .env("DBUS_SESSION_BUS_ADDRESS", address)
+            .env(KEYCHAIN_TEST_BUS, address)
+            .status()
+            .unwrap();
+
+        daemon.kill().unwrap();
+        daemon.wait().unwrap();
+        assert!(status.success(), "keychain round trip failed");
+    }
+
+    fn keychain_round_trip(address: &str) {
+        use secret_service::EncryptionType;
+        use secret_service_stand_in::{Collection, Keyring, Service, COLLECTION};
+
+        let keyring = Keyring::default();
+        let _server = zbus::blocking::connection::Builder::address(address)
+            .unwrap()
+            .name("org.freedesktop.secrets")
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/secrets",
+                Service {
+                    keyring: keyring.clone(),
+                },
+            )
+            .unwrap()
+            .serve_at(COLLECTION, Collection { keyring })
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let secret = b"legal winner thank year wave sausage worth useful legal winner thank yellow";
+        for (name, encryption) in [
+            ("apex-plain", EncryptionType::Plain),
+            ("apex-dh", EncryptionType::Dh),
+        ] {
+            let service = keychain::connect(encryption).unwrap();
+
+            keychain::store_in(&service, name, b"replaced").unwrap();
+            keychain::store_in(&service, name, secret).unwrap();
+            assert_eq!(
+                keychain::load_from(&service, name).unwrap().as_slice(),
+                secret
+            );
+            assert!(keychain::load_from(&service, "other").is_err());
+
+            keychain::delete_from(&service, name).unwrap();
+            assert!(keychain::load_from(&service, name).is_err());
+            keychain::delete_from(&service, name).unwrap();
+        }
+    }
+}
@@ -0,0 +1,346 @@
+//! Storage queries for Substrate chains
+//!
+//! Keys are given as command line arguments and converted with the runtime
+//! metadata: addresses, numbers and hex as plain strings, composite keys as
+//! JSON. Values are decoded to JSON; `apex storage iter --json` prints one
+//! entry per line (NDJSON).
+
+use anyhow::Result;
+use apex_sdk_substrate::storage::{decode_value, value_to_json, PageOptions};
+use colored::Colorize;
+use serde::Serialize;
+use subxt::ext::futures::TryStreamExt;
+
+use crate::metadata::ChainArgs;
+use crate::tx::parse_arg;
+
+/// Read a storage value
+pub async fn get(args: &ChainArgs, pallet: &str, item: &str, keys: &[String]) -> Result<()> {
+    let client = args.storage_client().await?;
+    let keys = keys_from_args(&client, pallet, item, keys)?;
+
+    let value = match client.query_json(pallet, item, keys).await? {
+        Some(value) => Some(value),
+        None => default_value(&client.runtime_metadata().await?, pallet, item)?,
+    };
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&value.unwrap_or(serde_json::Value::Null))?
+        );
+        return Ok(());
+    }
+
+    match value {
+        Some(value) => println!("{}", serde_json::to_string_pretty(&value)?),
+        None => println!(
+            "{}",
+            format!("No value stored at {}::{}", pallet, item).dimmed()
+        ),
+    }
+    Ok(())
+}
+
+/// Convert command line keys with the storage entry's key types
+fn keys_from_args(
+    client: &apex_sdk_substrate::StorageClient,
+    pallet: &str,
+    item: &str,
+    keys: &[String],
+) -> Result<Vec<subxt::dynamic::Value>> {
+    let json_keys: Vec<serde_json::Value> = keys.iter().map(|k| parse_arg(k)).collect();
+    Ok(client.keys_from_json(pallet, item, &json_keys)?)
+}
+
+/// The value of an unset entry: its declared default, or `None` for optional entries
+fn default_value(
+    metadata: &subxt::Metadata,
+    pallet: &str,
+    item: &str,
+) -> Result<Option<serde_json::Value>> {
+    use subxt::metadata::types::StorageEntryModifier;
+
+    let Some(entry) = metadata
+        .pallet_by_name(pallet)
+        .and_then(|p| p.storage())
+        .and_then(|s| s.entry_by_name(item))
+    else {
+        return Ok(None);
+    };
+    if !matches!(entry.modifier(), StorageEntryModifier::Default) {
+        return Ok(None);
+    }
+
+    let value = decode_value(
+        entry.default_bytes(),
+        entry.entry_type().value_ty(),
+        metadata.types(),
+    )?;
+    Ok(Some(value_to_json(&value, metadata.types())))
+}
+
+/// One storage map entry as printed by `apex storage iter`
+#[derive(Debug, Serialize)]
+struct EntryReport {
+    keys: Vec<serde_json::Value>,
+    value: serde_json::Value,
+}
+
+/// Iterate a storage map, optionally under a prefix of its keys
+pub async fn iter(
+    args: &ChainArgs,
+    pallet: &str,
+    item: &str,
+    partial_keys: &[String],
+    page_size: u32,
+    limit: Option<usize>,
+) -> Result<()> {
+    let client = args.storage_client().await?;
+    let keys = keys_from_args(&client, pallet, item, partial_keys)?;
+
+    let mut pages = client.iter_pages(
+        pallet,
+        item,
+        keys,
+        PageOptions::new().with_page_size(page_size),
+    );
+
+    if !args.json {
+        println!("\n{}", format!("{}::{}", pallet, item).cyan().bold());
+        println!("{}", "═".repeat(60).dimmed());
+    }
+
+    let mut count = 0;
+    'pages: while let Some(page) = pages.try_next().await? {
+        for entry in &page.entries {
+            if limit.is_some_and(|limit| count >= limit) {
+                break 'pages;
+            }
+            count += 1;
+
+            let report = EntryReport {
+                keys: client.keys_to_json(pallet, item, &entry.keys)?,
+                value: client.to_json(&entry.value),
+            };
+            if args.json {
+                println!("{}", serde_json::to_string(&report)?);
+            } else {
+                println!(
+                    "{} => {}",
+                    serde_json::to_string(&report.keys)?.green(),
+                    serde_json::to_string(&report.value)?
+                );
+            }
+        }
+        // Don't fetch another page once the limit is reached
+        if limit.is_some_and(|limit| count >= limit) {
+            break;
+        }
+    }
+
+    if !args.json {
+        println!("\n{}", format!("{} entries", count).dimmed());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scale_info::meta_type;
+    use serde_json::json;
+    use subxt::backend::rpc::{RawRpcFuture, RawRpcSubscription, RawValue, RpcClient, RpcClientT};
+    use subxt::ext::frame_metadata::v15::{
+        CustomMetadata, ExtrinsicMetadata, OuterEnums, PalletMetadata, PalletStorageMetadata,
+        RuntimeMetadataV15, StorageEntryMetadata, StorageEntryModifier, StorageEntryType,
+        StorageHasher,
+    };
+
+    /// RPC client for tests that never reach the node
+    struct NoRpc;
+
+    impl RpcClientT for NoRpc {
+        fn request_raw<'a>(
+            &'a self,
+            method: &'a str,
+            _params: Option<Box<RawValue>>,
+        ) -> RawRpcFuture<'a, Box<RawValue>> {
+            Box::pin(async move {
+                Err(subxt::ext::subxt_rpcs::Error::Client(
+                    format!("unexpected RPC request {}", method).into(),
+                ))
+            })
+        }
+
+        fn subscribe_raw<'a>(
+            &'a self,
+            sub: &'a str,
+            _params: Option<Box<RawValue>>,
+            _unsub: &'a str,
+        ) -> RawRpcFuture<'a, RawRpcSubscription> {
+            Box::pin(async move {
+                Err(subxt::ext::subxt_rpcs::Error::Client(
+                    format!("unexpected RPC subscription {}", sub).into(),
+                ))
+            })
+        }
+    }
+
+    fn entry(
+        name: &'static str,
+        modifier: StorageEntryModifier,
+        ty: StorageEntryType,
+        default: Vec<u8>,
+    ) -> StorageEntryMetadata {
+        StorageEntryMetadata {
+            name,
+            modifier,
+            ty,
+            default,
+            docs: Vec::new(),
+        }
+    }
+
+    /// Metadata with a `Test` pallet holding an account map, a double map
+    /// and an optional plain value
+    fn test_metadata() -> subxt::Metadata {
+        let entries = vec![
+            entry(
+                "Accounts",
+                StorageEntryModifier::Default,
+                StorageEntryType::Map {
+                    hashers: vec![StorageHasher::Blake2_128Concat],
+                    key: meta_type::<[u8; 32]>(),
+                    value: meta_type::<u32>(),
+                },
+                vec![7, 0, 0, 0],
+            ),
+            entry(
+                "Pairs",
+                StorageEntryModifier::Default,
+                StorageEntryType::Map {
+                    hashers: vec![StorageHasher::Twox64Concat, StorageHasher::Twox64Concat],
+                    key: meta_type::<(u32, u64)>(),
+                    value: meta_type::<bool>(),
+                },
+                vec![0],
+            ),
+            entry(
+                "Maybe",
+                StorageEntryModifier::Optional,
+                StorageEntryType::Plain(meta_type::<u64>()),
+                vec![0],
+            ),
+        ];
+        let pallet = PalletMetadata {
+            name: "Test",
+            storage: Some(PalletStorageMetadata {
+                prefix: "Test",
+                entries,
+            }),
+            calls: None,
+            event: None,
+            constants: Vec::new(),
+            error: None,
+            index: 0,
+            docs: Vec::new(),
+        };
+
+        let metadata = RuntimeMetadataV15::new(
+            vec![pallet],
+            ExtrinsicMetadata {
+                version: 4,
+                address_ty: meta_type::<()>(),
+                call_ty: meta_type::<()>(),
+                signature_ty: meta_type::<()>(),
+                extra_ty: meta_type::<()>(),
+                signed_extensions: Vec::new(),
+            },
+            meta_type::<()>(),
+            Vec::new(),
+            OuterEnums {
+                call_enum_ty: meta_type::<()>(),
+                event_enum_ty: meta_type::<()>(),
+                error_enum_ty: meta_type::<()>(),
+            },
+            CustomMetadata {
+                map: Default::default(),
+            },
+        );
+        subxt::metadata::types::Metadata::try_from(metadata)
+            .unwrap()
+            .into()
+    }
+
+    fn test_client() -> apex_sdk_substrate::StorageClient {
+        let client = subxt::OnlineClient::<subxt::PolkadotConfig>::from_rpc_client_with(
+            Default::default(),
+            subxt::client::RuntimeVersion {
+                spec_version: 1,
+                transaction_version: 1,
+            },
+            test_metadata(),
+            RpcClient::new(NoRpc),
+        )
+        .unwrap();
+        apex_sdk_substrate::StorageClient::new(client, apex_sdk_substrate::Metrics::new())
+    }
+
+    fn args(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|k| k.to_string()).collect()
+    }
+
+    #[test]
+    fn test_keys_from_args() {
+        let client = test_client();
+        let alice = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+        let alice_hex = "0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d";
+
+        // SS58 and hex give the same account key
+        let keys = keys_from_args(&client, "Test", "Accounts", &args(&[alice])).unwrap();
+        assert_eq!(
+            client.keys_to_json("Test", "Accounts", &keys).unwrap(),
+            vec![json!(alice_hex)]
+        );
+        let keys = keys_from_args(&client, "Test", "Accounts", &args(&[alice_hex])).unwrap();
+        assert_eq!(
+            client.keys_to_json("Test", "Accounts", &keys).unwrap(),
+            vec![json!(alice_hex)]
+        );
+
+        // Double maps take both keys or a prefix of them
+        let keys = keys_from_args(&client, "Test", "Pairs", &args(&["1", "2"])).unwrap();
+        assert_eq!(
+            client.keys_to_json("Test", "Pairs", &keys).unwrap(),
+            vec![json!(1), json!(2)]
+        );
+        assert_eq!(
+            keys_from_args(&client, "Test", "Pairs", &args(&["1"]))
+                .unwrap()
+                .len(),
+            1
+        );
+
+        assert!(keys_from_args(&client, "Test", "Pairs", &args(&["1", "2", "3"])).is_err());
+        assert!(keys_from_args(&client, "Test", "Accounts", &args(&["not-an-address"])).is_err());
+        assert!(keys_from_args(&client, "Test", "Missing", &args(&[])).is_err());
+    }
+
+    #[test]
+    fn test_default_value() {
+        let metadata = test_metadata();
+
+        assert_eq!(
+            default_value(&metadata, "Test", "Accounts").unwrap(),
+            Some(json!(7))
+        );
+        assert_eq!(
+            default_value(&metadata, "Test", "Pairs").unwrap(),
+            Some(json!(false))
+        );
+        // Optional entries and unknown items have no default
+        assert_eq!(default_value(&metadata, "Test", "Maybe").unwrap(), None);
+        assert_eq!(default_value(&metadata, "Test", "Missing").unwrap(), None);
+    }
+}
@@ -0,0 +1,53 @@
+//! JSON-RPC stand-in for tests against remote signers and nodes
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+/// Local JSON-RPC server over HTTP: answers each JSON-RPC request with
+/// `respond(method, params)`, a result or an error object, and records the
+/// requests it saw
+pub(crate) async fn stand_in(
+    respond: impl Fn(&str, &Value) -> Result<Value, Value> + Send + 'static,
+) -> (String, tokio::sync::mpsc::UnboundedReceiver<Value>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (seen, requests) = tokio::sync::mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).await.unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).await.unwrap();
+
+            let request: Value = serde_json::from_slice(&body).unwrap();
+            let response = match respond(request["method"].as_str().unwrap(), &request["params"]) {
+                Ok(result) => json!({"jsonrpc": "2.0", "id": request["id"], "result": result}),
+                Err(error) => json!({"jsonrpc": "2.0", "id": request["id"], "error": error}),
+            }
+            .to_string();
+            let _ = seen.send(request);
+            let reply = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                response.len(),
+                response
+            );
+            reader.get_mut().write_all(reply.as_bytes()).await.unwrap();
+        }
+    });
+
+    (url, requests)
+}
@@ -0,0 +1,1081 @@
+//! Transaction sending and tracking for Substrate and EVM chains
+//!
+//! Endpoints are resolved from the CLI config (`endpoints.<chain>`) unless
+//! `--endpoint` is given, and transactions are signed with keystore accounts
+//! through their signer backend. For scripts, the keystore password can be
+//! passed in the `APEX_KEYSTORE_PASSWORD` environment variable and every
+//! command accepts `--json` for machine-readable output.
+
+use anyhow::{Context, Result};
+use apex_sdk_core::{ConfirmationStrategy, ReceiptWatcher};
+use apex_sdk_types::{Chain, ChainType, TransactionStatus, TxStatus};
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::keystore::AccountType;
+use crate::signer::AccountSigner;
+
+/// Seconds to wait for inclusion when `--wait` is given
+const DEFAULT_WAIT_TIMEOUT_SECS: u64 = 120;
+
+/// Options shared by the commands that submit transactions
+#[derive(Debug, Clone, clap::Args)]
+pub struct TxOptions {
+    /// Chain name from the configured endpoints (defaults to default_chain)
+    #[arg(short, long)]
+    pub chain: Option<String>,
+    /// RPC endpoint URL, overriding the configured one
+    #[arg(short, long)]
+    pub endpoint: Option<String>,
+    /// Keystore account to sign with (defaults to default_account)
+    #[arg(short, long)]
+    pub account: Option<String>,
+    /// Simulate the transaction and estimate fees without broadcasting
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Wait for the transaction to be included before returning
+    #[arg(long)]
+    pub wait: bool,
+    /// Print JSON instead of human-readable output
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Outcome of a submitted or simulated transaction
+#[derive(Debug, Default, Serialize)]
+pub struct TxReport {
+    pub chain: String,
+    pub from: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    pub call: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    pub dry_run: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// Estimated fee in the chain's base unit (wei, planck)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_fee: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_limit: Option<u64>,
+    /// Decoded return value of a simulated EVM call
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<TransactionStatus>,
+}
+
+impl TxReport {
+    fn print(&self, json: bool) -> Result<()> {
+        if json {
+            println!("{}", serde_json::to_string_pretty(self)?);
+            return Ok(());
+        }
+
+        let title = if self.dry_run {
+            "Dry-Run Complete"
+        } else {
+            "Transaction Submitted"
+        };
+        println!("\n{}", title.green().bold());
+        println!("{}", "═══════════════════════════════════════".dimmed());
+        println!("{}: {}", "Chain".dimmed(), self.chain);
+        println!("{}: {}", "Call".dimmed(), self.call);
+        println!("{}: {}", "From".dimmed(), self.from);
+        if let Some(to) = &self.to {
+            println!("{}: {}", "To".dimmed(), to);
+        }
+        if let Some(value) = &self.value {
+            println!("{}: {}", "Value".dimmed(), value);
+        }
+        if let Some(gas_limit) = self.gas_limit {
+            println!("{}: {}", "Gas Limit".dimmed(), gas_limit);
+        }
+        if let Some(fee) = &self.estimated_fee {
+            println!("{}: {}", "Estimated Fee".yellow(), fee);
+        }
+        if let Some(output) = &self.output {
+            println!("{}: {}", "Output".cyan(), output);
+        }
+        if let Some(hash) = &self.hash {
+            println!("{}: {}", "Transaction Hash".cyan().bold(), hash);
+        }
+        if let Some(status) = &self.status {
+            println!();
+            print_status(status);
+        }
+
+        Ok(())
+    }
+}
+
+/// Chain and endpoint a command runs against
+pub(crate) struct Target {
+    pub(crate) chain: String,
+    pub(crate) endpoint: String,
+    pub(crate) is_substrate: bool,
+}
+
+impl Target {
+    pub(crate) fn resolve(chain: Option<&str>, endpoint: Option<&str>) -> Result<Self> {
+        let config = Config::load(&crate::config::get_config_path()?)?;
+        Self::from_config(&config, chain, endpoint)
+    }
+
+    /// Resolve against a loaded config, detecting Substrate chains by their
+    /// endpoint or chain name
+    fn from_config(config: &Config, chain: Option<&str>, endpoint: Option<&str>) -> Result<Self> {
+        let (chain, endpoint) = config.resolve_endpoint(chain, endpoint)?;
+
+        let is_substrate = Chain::is_substrate_endpoint(&endpoint)
+            || Chain::from_str_case_insensitive(&chain)
+                .map(|c| c.chain_type() == ChainType::Substrate)
+                .unwrap_or(false);
+
+        Ok(Self {
+            chain,
+            endpoint,
+            is_substrate,
+        })
+    }
+
+    fn account_type(&self) -> AccountType {
+        if self.is_substrate {
+            AccountType::Substrate
+        } else {
+            AccountType::Evm
+        }
+    }
+}
+
+/// Send native tokens
+pub async fn send(to: &str, amount: &str, raw: bool, opts: &TxOptions) -> Result<()> {
+    let target = Target::resolve(opts.chain.as_deref(), opts.endpoint.as_deref())?;
+    let account = unlock_account(opts.account.as_deref(), target.account_type())?;
+
+    send_as(&account, &target, to, amount, raw, opts)
+        .await?
+        .print(opts.json)
+}
+
+/// Like [`send`] with a resolved target and an unlocked account
+async fn send_as(
+    account: &AccountSigner,
+    target: &Target,
+    to: &str,
+    amount: &str,
+    raw: bool,
+    opts: &TxOptions,
+) -> Result<TxReport> {
+    if target.is_substrate {
+        use subxt::dynamic::Value;
+
+        let client = SubstrateClient::connect(&target.endpoint).await?;
+        let dest: subxt::utils::AccountId32 = to.parse().context("Invalid Substrate address")?;
+        let decimals = if raw {
+            0
+        } else {
+            client.token_decimals().await
+        };
+        let value: u128 = parse_amount(amount, decimals)?
+            .try_into()
+            .context("Amount does not fit in a Substrate balance")?;
+
+        let payload = subxt::dynamic::tx(
+            "Balances",
+            "transfer_keep_alive",
+            vec![
+                Value::unnamed_variant("Id", vec![Value::from_bytes(dest.0)]),
+                Value::u128(value),
+            ],
+        );
+        let report = TxReport {
+            to: Some(to.to_string()),
+            call: "Balances.transfer_keep_alive".to_string(),
+            value: Some(value.to_string()),
+            ..Default::default()
+        };
+        client.submit(account, target, payload, report, opts).await
+    } else {
+        use alloy::primitives::Address;
+
+        let to: Address = to.parse().context("Invalid EVM address")?;
+        let value = parse_amount(amount, if raw { 0 } else { 18 })?;
+        let report = TxReport {
+            call: "transfer".to_string(),
+            ..Default::default()
+        };
+        evm_submit_as(
+            account,
+            target,
+            Some(to),
+            value,
+            Vec::new(),
+            None,
+            report,
+            opts,
+        )
+        .await
+    }
+}
+
+/// Transfer ERC-20 tokens
+pub async fn erc20_transfer(
+    token: &str,
+    to: &str,
+    amount: &str,
+    raw: bool,
+    opts: &TxOptions,
+) -> Result<()> {
+    use alloy::primitives::Address;
+    use apex_sdk_evm::abi::ContractAbi;
+    use apex_sdk_evm::{contract::Erc20, EvmAdapter};
+
+    let target = Target::resolve(opts.chain.as_deref(), opts.endpoint.as_deref())?;
+    if target.is_substrate {
+        anyhow::bail!(
+            "ERC-20 transfers need an EVM chain, but '{}' is a Substrate chain",
+            target.chain
+        );
+    }
+
+    let token: Address = token.parse().context("Invalid token address")?;
+    let recipient: Address = to.parse().context("Invalid recipient address")?;
+
+    let adapter = EvmAdapter::new(&target.endpoint, &target.chain)
+        .await
+        .context("Failed to connect to EVM endpoint")?;
+    let erc20 = Erc20::new(token, adapter);
+    let decimals = if raw {
+        0
+    } else {
+        erc20
+            .decimals()
+            .await
+            .context("Failed to read token decimals, pass --raw to give base units")?
+    };
+    let amount = parse_amount(amount, decimals)?;
+
+    let abi = ContractAbi::from_signature(
+        "function transfer(address to, uint256 amount) returns (bool)",
+    )?;
+    let report = TxReport {
+        call: format!("transfer({}, {})", recipient, amount),
+        ..Default::default()
+    };
+    let report = evm_submit(
+        &target,
+        token,
+        alloy::primitives::U256::ZERO,
+        erc20.encode_transfer(recipient, amount),
+        Some((&abi, "transfer")),
+        report,
+        opts,
+    )
+    .await?;
+
+    report.print(opts.json)
+}
+
+/// Call a contract function (EVM) or a pallet call (Substrate)
+pub async fn call(
+    method: &str,
+    args: &[String],
+    to: Option<&str>,
+    value: Option<&str>,
+    opts: &TxOptions,
+) -> Result<()> {
+    let target = Target::resolve(opts.chain.as_deref(), opts.endpoint.as_deref())?;
+    let args: Vec<serde_json::Value> = args.iter().map(|arg| parse_arg(arg)).collect();
+
+    let report = if target.is_substrate {
+        let (pallet, call) = method
+            .split_once("::")
+            .or_else(|| method.split_once('.'))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Substrate calls are written as Pallet.call, e.g. Balances.transfer_keep_alive"
+                )
+            })?;
+
+        let client = SubstrateClient::connect(&target.endpoint).await?;
+        let payload = client.call_payload(pallet, call, &args)?;
+        let account = unlock_account(opts.account.as_deref(), AccountType::Substrate)?;
+        let report = TxReport {
+            call: format!("{}.{}", pallet, call),
+            ..Default::default()
+        };
+        client
+            .submit(&account, &target, payload, report, opts)
+            .await?
+    } else {
+        use alloy::primitives::Address;
+        use apex_sdk_evm::abi::ContractAbi;
+
+        let contract: Address = to
+            .ok_or_else(|| anyhow::anyhow!("EVM calls need the contract address in --to"))?
+            .parse()
+            .context("Invalid contract address")?;
+        let value = value.map(|v| parse_amount(v, 18)).transpose()?;
+
+        let abi = ContractAbi::from_signature(method)?;
+        let function = abi
+            .abi()
+            .functions()
+            .next()
+            .map(|f| f.name.clone())
+            .unwrap_or_default();
+        let data = abi.encode_call_json(&function, &args)?;
+
+        let report = TxReport {
+            call: method.to_string(),
+            ..Default::default()
+        };
+        evm_submit(
+            &target,
+            contract,
+            value.unwrap_or_default(),
+            data,
+            Some((&abi, &function)),
+            report,
+            opts,
+        )
+        .await?
+    };
+
+    report.print(opts.json)
+}
+
+/// Show the current status of a transaction
+pub async fn status(
+    hash: &str,
+    chain: Option<&str>,
+    endpoint: Option<&str>,
+    json: bool,
+) -> Result<()> {
+    let target = Target::resolve(chain, endpoint)?;
+
+    let status = if target.is_substrate {
+        apex_sdk_substrate::SubstrateAdapter::connect(&target.endpoint)
+            .await
+            .context("Failed to connect to Substrate endpoint")?
+            .get_transaction_status(hash)
+            .await?
+    } else {
+        evm_watcher(&target)
+            .await?
+            .get_receipt_status(hash)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Transaction {} not found", hash))?
+    };
+
+    print_status_report(&status, json)
+}
+
+/// Wait until a transaction is included, failing if it reverted
+pub async fn wait(
+    hash: &str,
+    chain: Option<&str>,
+    endpoint: Option<&str>,
+    confirmations: Option<u32>,
+    timeout_secs: u64,
+    json: bool,
+) -> Result<()> {
+    let target = Target::resolve(chain, endpoint)?;
+    let strategy = wait_strategy(target.is_substrate, confirmations, timeout_secs);
+
+    if !json {
+        println!("{} {}...", "Waiting for".yellow(), hash);
+    }
+
+    let status = if target.is_substrate {
+        apex_sdk_substrate::SubstrateAdapter::connect(&target.endpoint)
+            .await
+            .context("Failed to connect to Substrate endpoint")?
+            .wait_for_receipt_with_strategy(hash, &strategy)
+            .await?
+    } else {
+        evm_watcher(&target)
+            .await?
+            .wait_for_receipt_with_strategy(hash, &strategy)
+            .await?
+    };
+
+    print_status_report(&status, json)
+}
+
+/// How `wait` confirms a transaction: the requested depth, or else one block
+/// on EVM and finality on Substrate
+fn wait_strategy(
+    is_substrate: bool,
+    confirmations: Option<u32>,
+    timeout_secs: u64,
+) -> ConfirmationStrategy {
+    match (confirmations, is_substrate) {
+        (Some(confirmations), _) => ConfirmationStrategy::BlockConfirmations {
+            confirmations,
+            timeout_secs,
+        },
+        (None, true) => ConfirmationStrategy::Finalized { timeout_secs },
+        (None, false) => ConfirmationStrategy::BlockConfirmations {
+            confirmations: 1,
+            timeout_secs,
+        },
+    }
+}
+
+fn print_status_report(status: &TransactionStatus, json: bool) -> Result<()> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(status)?);
+    } else {
+        println!("\n{}", "Transaction Status".cyan().bold());
+        println!("{}", "═══════════════════════════════════════".dimmed());
+        print_status(status);
+    }
+
+    if status.status == TxStatus::Failed {
+        anyhow::bail!(
+            "Transaction {} failed{}",
+            status.hash,
+            status
+                .error
+                .as_ref()
+                .map(|e| format!(": {}", e))
+                .unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+fn print_status(status: &TransactionStatus) {
+    let label = format!("{:?}", status.status);
+    let label = match status.status {
+        TxStatus::Confirmed | TxStatus::Finalized => label.green().bold(),
+        TxStatus::Failed => label.red().bold(),
+        _ => label.yellow().bold(),
+    };
+
+    println!("{}: {}", "Hash".dimmed(), status.hash);
+    println!("{}: {}", "Status".dimmed(), label);
+    if let Some(block) = status.block_number {
+        println!("{}: {}", "Block".dimmed(), block);
+    }
+    if let Some(block_hash) = &status.block_hash {
+        println!("{}: {}", "Block Hash".dimmed(), block_hash);
+    }
+    if let Some(gas_used) = status.gas_used {
+        println!("{}: {}", "Gas Used".dimmed(), gas_used);
+    }
+    if let Some(confirmations) = status.confirmations {
+        println!("{}: {}", "Confirmations".dimmed(), confirmations);
+    }
+    if let Some(error) = &status.error {
+        println!("{}: {}", "Error".red(), error);
+    }
+}
+
+/// Unlock an account for signing, checking it matches the chain type
+pub(crate) fn unlock_account(
+    name: Option<&str>,
+    account_type: AccountType,
+) -> Result<AccountSigner> {
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => Config::load(&crate::config::get_config_path()?)?
+            .default_account
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No account given. Pass --account <name> or set one with:\n  \
+                     apex config set default_account <name>"
+                )
+            })?,
+    };
+
+    crate::signer::unlock(&name, Some(account_type))
+}
+
+/// Key an EVM transaction is signed with
+enum EvmSigner<'a> {
+    Wallet(Box<apex_sdk_evm::wallet::Wallet>),
+    /// Signs with `eth_signTransaction`, the key never leaves the signer
+    Remote(&'a crate::signer::RemoteSigner),
+}
+
+/// Simulate, estimate and optionally send an EVM transaction
+async fn evm_submit(
+    target: &Target,
+    to: alloy::primitives::Address,
+    value: alloy::primitives::U256,
+    data: Vec<u8>,
+    abi: Option<(&apex_sdk_evm::abi::ContractAbi, &str)>,
+    report: TxReport,
+    opts: &TxOptions,
+) -> Result<TxReport> {
+    let account = unlock_account(opts.account.as_deref(), AccountType::Evm)?;
+    evm_submit_as(&account, target, Some(to), value, data, abi, report, opts).await
+}
+
+/// Like [`evm_submit`] with an unlocked account, or a contract creation with
+/// `data` as init code when `to` is `None`
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn evm_submit_as(
+    account: &AccountSigner,
+    target: &Target,
+    to: Option<alloy::primitives::Address>,
+    value: alloy::primitives::U256,
+    data: Vec<u8>,
+    abi: Option<(&apex_sdk_evm::abi::ContractAbi, &str)>,
+    mut report: TxReport,
+    opts: &TxOptions,
+) -> Result<TxReport> {
+    use alloy::providers::Provider;
+    use alloy::rpc::types::TransactionRequest;
+    use apex_sdk_evm::{EvmAdapter, EvmReceiptWatcher};
+
+    let adapter = EvmAdapter::new(&target.endpoint, &target.chain)
+        .await
+        .context("Failed to connect to EVM endpoint")?;
+    let signer = match account {
+        AccountSigner::Local(secret) => EvmSigner::Wallet(Box::new(
+            secret.evm_wallet()?.with_chain_id(adapter.chain_id()),
+        )),
+        AccountSigner::Remote(remote) => EvmSigner::Remote(remote),
+    };
+    let from = match &signer {
+        EvmSigner::Wallet(wallet) => wallet.eth_address(),
+        EvmSigner::Remote(remote) => remote.address(),
+    };
+
+    report.chain = target.chain.clone();
+    report.from = format!("{:?}", from);
+    report.to = to.map(|to| to.to_checksum(None));
+    report.dry_run = opts.dry_run;
+    if !value.is_zero() {
+        report.value = Some(value.to_string());
+    }
+
+    // Simulate first so reverts surface with a reason instead of a failed estimate
+    let mut request = TransactionRequest::default()
+        .from(from)
+        .value(value)
+        .input(data.clone().into());
+    if let Some(to) = to {
+        request = request.to(to);
+    }
+    match adapter.provider().provider.call(request).await {
+        Ok(output) => {
+            if let Some((abi, function)) = abi {
+                report.output = abi.decode_output_json(function, &output).ok();
+            }
+        }
+        Err(e) => {
+            let reason = e
+                .as_error_resp()
+                .and_then(|resp| resp.as_revert_data())
+                .and_then(|data| abi.and_then(|(abi, _)| abi.decode_revert(&data)));
+            match reason {
+                Some(reason) => anyhow::bail!("Transaction would revert: {}", reason),
+                None => anyhow::bail!("Transaction would fail: {}", e),
+            }
+        }
+    }
+
+    let executor = adapter.get_transaction_executor();
+    let data = (!data.is_empty()).then_some(data);
+    let estimate = executor
+        .estimate_gas(from, to, Some(value), data.clone())
+        .await
+        .context("Failed to estimate gas")?;
+    report.gas_limit = Some(estimate.gas_limit.to::<u64>());
+    report.estimated_fee = Some(estimate.total_cost.to_string());
+
+    if opts.dry_run {
+        return Ok(report);
+    }
+
+    let hash = match &signer {
+        EvmSigner::Wallet(wallet) => {
+            let tx = match to {
+                Some(to) => {
+                    executor
+                        .build_transaction(wallet, to, value, data, Some(estimate))
+                        .await?
+                }
+                None => {
+                    executor
+                        .build_deployment(wallet, data.unwrap_or_default(), Some(estimate))
+                        .await?
+                }
+            };
+            format!("{:?}", executor.send_raw_transaction(wallet, tx).await?)
+        }
+        EvmSigner::Remote(remote) => {
+            use alloy::network::TransactionBuilder;
+
+            let tx = match to {
+                Some(to) => {
+                    executor
+                        .build_transaction_from(from, to, value, data, Some(estimate))
+                        .await?
+                }
+                None => {
+                    executor
+                        .build_deployment_from(from, data.unwrap_or_default(), Some(estimate))
+                        .await?
+                }
+            }
+            .with_chain_id(adapter.chain_id());
+            let raw = remote.sign_transaction(tx).await?;
+            let pending = adapter
+                .provider()
+                .provider
+                .send_raw_transaction(&raw)
+                .await
+                .context("Failed to send signed transaction")?;
+            format!("{:?}", pending.tx_hash())
+        }
+    };
+    report.hash = Some(hash.clone());
+
+    if opts.wait {
+        let watcher = EvmReceiptWatcher::new(adapter.provider().provider.clone());
+        report.status = Some(
+            watcher
+                .wait_for_receipt_with_strategy(
+                    &hash,
+                    &ConfirmationStrategy::BlockConfirmations {
+                        confirmations: 1,
+                        timeout_secs: DEFAULT_WAIT_TIMEOUT_SECS,
+                    },
+                )
+                .await?,
+        );
+    }
+
+    Ok(report)
+}
+
+async fn evm_watcher(target: &Target) -> Result<apex_sdk_evm::EvmReceiptWatcher> {
+    let provider = apex_sdk_evm::EvmProvider::new(&target.endpoint)
+        .await
+        .context("Failed to connect to EVM endpoint")?;
+    Ok(apex_sdk_evm::EvmReceiptWatcher::new(provider.provider))
+}
+
+/// Substrate client keeping the raw RPC handle for chain properties
+pub(crate) struct SubstrateClient {
+    rpc: subxt::backend::rpc::RpcClient,
+    pub(crate) api: subxt::OnlineClient<subxt::PolkadotConfig>,
+}
+
+impl SubstrateClient {
+    pub(crate) async fn connect(endpoint: &str) -> Result<Self> {
+        let rpc = subxt::backend::rpc::RpcClient::from_url(endpoint)
+            .await
+            .context("Failed to connect to Substrate endpoint")?;
+        let api = subxt::OnlineClient::from_rpc_client(rpc.clone())
+            .await
+            .context("Failed to connect to Substrate endpoint")?;
+        Ok(Self { rpc, api })
+    }
+
+    /// Decimals of the native token from `system_properties`, 10 if unset
+    pub(crate) async fn token_decimals(&self) -> u8 {
+        use subxt::backend::legacy::LegacyRpcMethods;
+
+        let properties = LegacyRpcMethods::<subxt::PolkadotConfig>::new(self.rpc.clone())
+            .system_properties()
+            .await
+            .unwrap_or_default();
+        match properties.get("tokenDecimals") {
+            Some(serde_json::Value::Array(decimals)) => decimals.first().and_then(|d| d.as_u64()),
+            Some(decimals) => decimals.as_u64(),
+            None => None,
+        }
+        .and_then(|d| u8::try_from(d).ok())
+        .unwrap_or(10)
+    }
+
+    /// Build a pallet call from JSON arguments typed by the runtime metadata
+    fn call_payload(
+        &self,
+        pallet: &str,
+        call: &str,
+        args: &[serde_json::Value],
+    ) -> Result<subxt::tx::DynamicPayload> {
+        let metadata = self.api.metadata();
+        let pallet_meta = metadata
+            .pallet_by_name(pallet)
+            .ok_or_else(|| anyhow::anyhow!("Pallet '{}' not found in runtime metadata", pallet))?;
+        let variant = pallet_meta
+            .call_variant_by_name(call)
+            .ok_or_else(|| anyhow::anyhow!("Call '{}.{}' not found", pallet, call))?;
+
+        if variant.fields.len() != args.len() {
+            let params: Vec<String> = variant
+                .fields
+                .iter()
+                .map(|f| f.name.clone().unwrap_or_else(|| "_".to_string()))
+                .collect();
+            anyhow::bail!(
+                "{}.{} expects {} arguments ({}), got {}",
+                pallet,
+                call,
+                variant.fields.len(),
+                params.join(", "),
+                args.len()
+            );
+        }
+
+        let values = variant
+            .fields
+            .iter()
+            .zip(args)
+            .map(|(field, arg)| {
+                apex_sdk_substrate::contracts::json_to_value(arg, field.ty.id, metadata.types())
+                    .with_context(|| {
+                        format!(
+                            "Invalid value for '{}'",
+                            field.name.as_deref().unwrap_or("argument")
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(subxt::dynamic::tx(pallet, call, values))
+    }
+
+    /// Sign, estimate and optionally submit an extrinsic
+    async fn submit(
+        &self,
+        account: &AccountSigner,
+        target: &Target,
+        payload: subxt::tx::DynamicPayload,
+        mut report: TxReport,
+        opts: &TxOptions,
+    ) -> Result<TxReport> {
+        use sp_core::crypto::Ss58Codec;
+        use subxt::tx::ValidationResult;
+
+        self.api
+            .tx()
+            .validate(&payload)
+            .context("Call does not match the runtime metadata")?;
+
+        use sp_core::Pair;
+
+        let pair = account.secret()?.substrate_pair()?;
+
+        report.chain = target.chain.clone();
+        report.from = pair.public().to_ss58check();
+        let signer = apex_sdk_substrate::signer::Sr25519Signer::new(pair);
+        report.dry_run = opts.dry_run;
+
+        let tx = self
+            .api
+            .tx()
+            .create_signed(&payload, &signer, Default::default())
+            .await
+            .context("Failed to sign extrinsic")?;
+        report.estimated_fee = Some(
+            tx.partial_fee_estimate()
+                .await
+                .context("Failed to estimate fee")?
+                .to_string(),
+        );
+
+        if opts.dry_run {
+            match tx
+                .validate()
+                .await
+                .context("Failed to validate extrinsic")?
+            {
+                ValidationResult::Valid(_) => {}
+                ValidationResult::Invalid(reason) => {
+                    anyhow::bail!("Transaction would be rejected: {:?}", reason)
+                }
+                ValidationResult::Unknown(reason) => {
+                    anyhow::bail!("Transaction validity unknown: {:?}", reason)
+                }
+            }
+            return Ok(report);
+        }
+
+        let hash = format!("0x{}", hex::encode(tx.hash()));
+        report.hash = Some(hash.clone());
+
+        if opts.wait {
+            let in_block = tx
+                .submit_and_watch()
+                .await
+                .context("Failed to submit extrinsic")?
+                .wait_for_finalized()
+                .await
+                .context("Transaction was not finalized")?;
+            let block_hash = in_block.block_hash();
+
+            let status = match in_block.wait_for_success().await {
+                Ok(_) => {
+                    let block = self.api.blocks().at(block_hash).await?;
+                    TransactionStatus::finalized(
+                        hash,
+                        block.number() as u64,
+                        format!("{:?}", block_hash),
+                        None,
+                        None,
+                        None,
+                    )
+                }
+                Err(e) => TransactionStatus::failed(hash, e.to_string()),
+            };
+            report.status = Some(status);
+        } else {
+            tx.submit().await.context("Failed to submit extrinsic")?;
+        }
+
+        Ok(report)
+    }
+}
+
+/// Parse a decimal amount into base units with the given number of decimals
+pub(crate) fn parse_amount(amount: &str, decimals: u8) -> Result<alloy::primitives::U256> {
+    let amount = amount.trim();
+
+    // parse_units truncates extra digits, which would silently send less
+    if let Some((_, fraction)) = amount.split_once('.') {
+        if fraction.len() > decimals as usize {
+            anyhow::bail!(
+                "Invalid amount '{}': at most {} decimal places allowed",
+                amount,
+                decimals
+            );
+        }
+    }
+
+    alloy::primitives::utils::parse_units(amount, decimals)
+        .map(Into::into)
+        .map_err(|e| anyhow::anyhow!("Invalid amount '{}': {}", amount, e))
+}
+
+/// Parse a command line argument: JSON for arrays, objects and literals,
+/// otherwise a plain string (addresses, numbers, hex)
+pub(crate) fn parse_arg(arg: &str) -> serde_json::Value {
+    let trimmed = arg.trim();
+    let is_json = trimmed.starts_with('[')
+        || trimmed.starts_with('{')
+        || matches!(trimmed, "true" | "false" | "null");
+
+    is_json
+        .then(|| serde_json::from_str(trimmed).ok())
+        .flatten()
+        .unwrap_or_else(|| serde_json::Value::String(arg.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::U256;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(
+            parse_amount("1.5", 18).unwrap(),
+            U256::from(1_500_000_000_000_000_000u128)
+        );
+        assert_eq!(
+            parse_amount("2", 10).unwrap(),
+            U256::from(20_000_000_000u64)
+        );
+        assert_eq!(parse_amount("12345", 0).unwrap(), U256::from(12345));
+
+        assert!(parse_amount("1.5", 0).is_err());
+        assert!(parse_amount("abc", 18).is_err());
+    }
+
+    #[test]
+    fn test_parse_arg() {
+        assert_eq!(
+            parse_arg("0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb7"),
+            json!("0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb7")
+        );
+        // Large integers stay strings so they keep their precision
+        assert_eq!(
+            parse_arg("100000000000000000000000"),
+            json!("100000000000000000000000")
+        );
+        assert_eq!(parse_arg("[1, 2]"), json!([1, 2]));
+        assert_eq!(parse_arg(r#"{"Id": "5Grw"}"#), json!({"Id": "5Grw"}));
+        assert_eq!(parse_arg("true"), json!(true));
+        assert_eq!(parse_arg("[unclosed"), json!("[unclosed"));
+    }
+
+    #[test]
+    fn test_report_json_skips_empty_fields() {
+        let report = TxReport {
+            chain: "sepolia".to_string(),
+            from: "0xabc".to_string(),
+            call: "transfer".to_string(),
+            dry_run: true,
+            estimated_fee: Some("21000".to_string()),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["estimated_fee"], json!("21000"));
+        assert!(json.get("hash").is_none());
+        assert!(json.get("status").is_none());
+    }
+
+    #[test]
+    fn test_report_json_with_status() {
+        let report = TxReport {
+            chain: "paseo".to_string(),
+            from: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_string(),
+            to: Some("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".to_string()),
+            call: "Balances.transfer_keep_alive".to_string(),
+            value: Some("10000000000".to_string()),
+            hash: Some("0x01".to_string()),
+            status: Some(TransactionStatus::finalized(
+                "0x01".to_string(),
+                42,
+                "0x02".to_string(),
+                None,
+                None,
+                None,
+            )),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["dry_run"], json!(false));
+        assert_eq!(json["value"], json!("10000000000"));
+        assert_eq!(json["status"]["status"], json!("Finalized"));
+        assert_eq!(json["status"]["block_number"], json!(42));
+        assert!(json.get("estimated_fee").is_none());
+        assert!(json.get("gas_limit").is_none());
+    }
+
+    #[test]
+    fn test_wait_strategy() {
+        assert!(matches!(
+            wait_strategy(true, None, 60),
+            ConfirmationStrategy::Finalized { timeout_secs: 60 }
+        ));
+        assert!(matches!(
+            wait_strategy(true, Some(3), 60),
+            ConfirmationStrategy::BlockConfirmations {
+                confirmations: 3,
+                timeout_secs: 60
+            }
+        ));
+        assert!(matches!(
+            wait_strategy(false, None, 60),
+            ConfirmationStrategy::BlockConfirmations {
+                confirmations: 1,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_target_from_config() {
+        let mut config = Config {
+            default_chain: "paseo".to_string(),
+            ..Default::default()
+        };
+        config
+            .endpoints
+            .insert("local".to_string(), "http://127.0.0.1:8545".to_string());
+
+        let target = Target::from_config(&config, None, None).unwrap();
+        assert_eq!(target.chain, "paseo");
+        assert!(target.is_substrate);
+        assert_eq!(target.account_type(), AccountType::Substrate);
+
+        let target = Target::from_config(&config, Some("local"), None).unwrap();
+        assert_eq!(target.endpoint, "http://127.0.0.1:8545");
+        assert!(!target.is_substrate);
+        assert_eq!(target.account_type(), AccountType::Evm);
+
+        // A known Substrate chain stays Substrate behind an HTTP endpoint
+        let target =
+            Target::from_config(&config, Some("polkadot"), Some("http://127.0.0.1:9933")).unwrap();
+        assert_eq!(target.endpoint, "http://127.0.0.1:9933");
+        assert!(target.is_substrate);
+
+        let err = Target::from_config(&config, Some("unknown"), None)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("No endpoint configured for chain 'unknown'"));
+    }
+
+    #[tokio::test]
+    async fn test_send_dry_run_does_not_broadcast() {
+        use crate::signer::RemoteSigner;
+        use crate::test_rpc::stand_in;
+
+        let (url, mut requests) = stand_in(|method, _| match method {
+            "eth_chainId" => Ok(json!("0x7a69")),
+            "eth_call" => Ok(json!("0x")),
+            "eth_estimateGas" => Ok(json!("0x5208")),
+            "eth_gasPrice" => Ok(json!("0x3b9aca00")),
+            // No EIP-1559 fee history, so the estimate uses the legacy gas price
+            _ => Err(json!({"code": -32601, "message": "Method not found"})),
+        })
+        .await;
+
+        let from = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+            .parse()
+            .unwrap();
+        let account = AccountSigner::Remote(RemoteSigner::new(&url, from));
+        let target = Target {
+            chain: "local".to_string(),
+            endpoint: url,
+            is_substrate: false,
+        };
+        let opts = TxOptions {
+            chain: None,
+            endpoint: None,
+            account: None,
+            dry_run: true,
+            wait: false,
+            json: true,
+        };
+
+        let report = send_as(
+            &account,
+            &target,
+            "0x70997970C51812dc3A010C7d01b50e0d17dc79C8",
+            "1.5",
+            false,
+            &opts,
+        )
+        .await
+        .unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.chain, "local");
+        assert_eq!(
+            report.to.as_deref(),
+            Some("0x70997970C51812dc3A010C7d01b50e0d17dc79C8")
+        );
+        assert_eq!(report.value.as_deref(), Some("1500000000000000000"));
+        let gas_limit = report.gas_limit.unwrap();
+        assert!(gas_limit >= 21_000);
+        assert_eq!(
+            report.estimated_fee,
+            Some((gas_limit as u128 * 1_000_000_000).to_string())
+        );
+        assert!(report.hash.is_none());
+
+        let mut methods = Vec::new();
+        while let Ok(request) = requests.try_recv() {
+            methods.push(request["method"].as_str().unwrap().to_string());
+        }
+        assert!(methods.iter().any(|m| m == "eth_call"));
+        assert!(!methods.iter().any(|m| m.starts_with("eth_send")));
+    }
+}
@@ -0,0 +1,451 @@
+//! Live block and event monitor for Substrate and EVM chains
+//!
+//! Substrate blocks come from a best or finalized block subscription and are
+//! read with [`BlockQuery`], which decodes event fields with the runtime
+//! metadata. EVM blocks are polled over HTTP and their logs decoded with an
+//! optional contract ABI. With `--json`, every block is printed as one JSON
+//! object per line (NDJSON), so the output can be piped into `jq`.
+
+use anyhow::{Context, Result};
+use apex_sdk_core::BlockEvent;
+use apex_sdk_substrate::BlockQuery;
+use colored::Colorize;
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::tx::Target;
+
+/// How often EVM endpoints are polled for new blocks
+const EVM_POLL_INTERVAL_SECS: u64 = 2;
+
+/// Options of `apex chain watch`
+#[derive(Debug, Clone, clap::Args)]
+pub struct WatchOptions {
+    /// Chain name from the configured endpoints (defaults to default_chain)
+    #[arg(short, long)]
+    pub chain: Option<String>,
+    /// RPC endpoint URL, overriding the configured one
+    #[arg(short, long)]
+    pub endpoint: Option<String>,
+    /// Follow finalized blocks instead of the best chain
+    #[arg(long)]
+    pub finalized: bool,
+    /// Only show events of this pallet (Substrate, repeatable)
+    #[arg(long)]
+    pub pallet: Vec<String>,
+    /// Only show this event, as `Name` or `Pallet.Name` (Substrate, repeatable)
+    #[arg(long)]
+    pub event: Vec<String>,
+    /// Only show logs emitted by this contract (EVM, repeatable)
+    #[arg(long)]
+    pub address: Vec<String>,
+    /// Only show logs whose first topic is this hash or event signature,
+    /// e.g. `Transfer(address,address,uint256)` (EVM, repeatable)
+    #[arg(long)]
+    pub topic: Vec<String>,
+    /// Contract ABI or compiler artifact to decode logs with (EVM)
+    #[arg(long)]
+    pub abi: Option<PathBuf>,
+    /// Stop after this many blocks
+    #[arg(short = 'n', long)]
+    pub count: Option<u64>,
+    /// Print one JSON object per block instead of human-readable output
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// A watched block with the events that passed the filters
+#[derive(Debug, Serialize)]
+struct BlockReport {
+    chain: String,
+    number: u64,
+    hash: String,
+    finalized: bool,
+    /// Extrinsics (Substrate) or transactions (EVM) in the block
+    transaction_count: u32,
+    /// Events (Substrate) or logs (EVM) in the block, before filtering
+    event_count: u32,
+    events: Vec<EventReport>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum EventReport {
+    Substrate(BlockEvent),
+    Evm(LogReport),
+}
+
+/// An EVM log, decoded when an ABI was given
+#[derive(Debug, Serialize)]
+struct LogReport {
+    log_index: Option<u64>,
+    transaction_hash: Option<String>,
+    address: String,
+    topics: Vec<String>,
+    data: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<serde_json::Value>,
+}
+
+impl BlockReport {
+    fn print(&self, json: bool) -> Result<()> {
+        if json {
+            println!("{}", serde_json::to_string(self)?);
+            return Ok(());
+        }
+
+        let status = if self.finalized {
+            "finalized".green()
+        } else {
+            "best".yellow()
+        };
+        println!(
+            "{} {} {} {} txs, {} events",
+            format!("#{}", self.number).cyan().bold(),
+            self.hash.dimmed(),
+            status,
+            self.transaction_count,
+            self.event_count
+        );
+
+        for event in &self.events {
+            match event {
+                EventReport::Substrate(event) => {
+                    let origin = event
+                        .extrinsic_index
+                        .map(|i| format!("ext {}", i))
+                        .unwrap_or_else(|| "block".to_string());
+                    println!(
+                        "  {} {} {}",
+                        format!("{}.{}", event.pallet, event.event).bold(),
+                        format!("[{}]", origin).dimmed(),
+                        event
+                            .fields
+                            .as_ref()
+                            .map(|f| f.to_string())
+                            .unwrap_or_default()
+                    );
+                }
+                EventReport::Evm(log) => {
+                    let tx = log
+                        .transaction_hash
+                        .as_deref()
+                        .map(|h| format!("[tx {}]", h))
+                        .unwrap_or_default();
+                    match (&log.event, &log.fields) {
+                        (Some(name), Some(fields)) => println!(
+                            "  {} {} {} {}",
+                            name.bold(),
+                            log.address,
+                            tx.dimmed(),
+                            fields
+                        ),
+                        _ => println!(
+                            "  {} {} {} data {}",
+                            log.address.bold(),
+                            tx.dimmed(),
+                            log.topics.join(" "),
+                            log.data
+                        ),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Pallet and event names Substrate events are filtered by
+#[derive(Debug, Default)]
+struct EventFilter {
+    pallets: Vec<String>,
+    /// Event names, each with the pallet it must come from if one was given
+    events: Vec<(Option<String>, String)>,
+}
+
+impl EventFilter {
+    fn new(pallets: &[String], events: &[String]) -> Self {
+        Self {
+            pallets: pallets.to_vec(),
+            events: events
+                .iter()
+                .map(|event| match event.split_once('.') {
+                    Some((pallet, name)) => (Some(pallet.to_string()), name.to_string()),
+                    None => (None, event.clone()),
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether an event passes; names are compared case-insensitively
+    fn matches(&self, pallet: &str, event: &str) -> bool {
+        let pallet_ok =
+            self.pallets.is_empty() || self.pallets.iter().any(|p| p.eq_ignore_ascii_case(pallet));
+        let event_ok = self.events.is_empty()
+            || self.events.iter().any(|(p, name)| {
+                name.eq_ignore_ascii_case(event)
+                    && p.as_ref().is_none_or(|p| p.eq_ignore_ascii_case(pallet))
+            });
+        pallet_ok && event_ok
+    }
+}
+
+/// Stream blocks and their events until interrupted or `--count` is reached
+pub async fn watch(opts: &WatchOptions) -> Result<()> {
+    let target = Target::resolve(opts.chain.as_deref(), opts.endpoint.as_deref())?;
+
+    if target.is_substrate {
+        if !opts.address.is_empty() || !opts.topic.is_empty() || opts.abi.is_some() {
+            anyhow::bail!("--address, --topic and --abi only apply to EVM chains");
+        }
+    } else if !opts.pallet.is_empty() || !opts.event.is_empty() {
+        anyhow::bail!("--pallet and --event only apply to Substrate chains");
+    }
+
+    if !opts.json {
+        println!(
+            "{} {} ({} blocks, Ctrl-C to stop)",
+            "Watching".cyan().bold(),
+            target.chain,
+            if opts.finalized { "finalized" } else { "best" }
+        );
+    }
+
+    let watch = async {
+        if target.is_substrate {
+            watch_substrate(&target, opts).await
+        } else {
+            watch_evm(&target, opts).await
+        }
+    };
+
+    tokio::select! {
+        result = watch => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+    }
+}
+
+async fn watch_substrate(target: &Target, opts: &WatchOptions) -> Result<()> {
+    use subxt::{OnlineClient, PolkadotConfig};
+
+    let api = OnlineClient::<PolkadotConfig>::from_url(&target.endpoint)
+        .await
+        .context("Failed to connect to Substrate endpoint")?;
+    let query = BlockQuery::new(api.clone());
+    let filter = EventFilter::new(&opts.pallet, &opts.event);
+
+    let mut blocks = if opts.finalized {
+        api.blocks().subscribe_finalized().await
+    } else {
+        api.blocks().subscribe_best().await
+    }
+    .context("Failed to subscribe to blocks")?;
+
+    let mut seen = 0;
+    while let Some(block) = blocks.next().await {
+        let block = block.context("Block subscription failed")?;
+        let hash = format!("0x{}", hex::encode(block.hash()));
+        let detailed = query
+            .get_detailed_block_by_hash(&hash)
+            .await
+            .with_context(|| format!("Failed to read block {}", hash))?;
+
+        let report = BlockReport {
+            chain: target.chain.clone(),
+            number: detailed.basic.number,
+            hash,
+            finalized: opts.finalized || detailed.basic.is_finalized,
+            transaction_count: detailed.basic.extrinsic_count,
+            event_count: detailed.events.len() as u32,
+            events: detailed
+                .events
+                .into_iter()
+                .filter(|e| filter.matches(&e.pallet, &e.event))
+                .map(EventReport::Substrate)
+                .collect(),
+        };
+        report.print(opts.json)?;
+
+        seen += 1;
+        if opts.count.is_some_and(|count| seen >= count) {
+            return Ok(());
+        }
+    }
+
+    anyhow::bail!("Block subscription ended")
+}
+
+async fn watch_evm(target: &Target, opts: &WatchOptions) -> Result<()> {
+    use alloy::primitives::Address;
+    use alloy::providers::Provider;
+    use alloy::rpc::types::BlockNumberOrTag;
+
+    let provider = apex_sdk_evm::EvmProvider::new(&target.endpoint)
+        .await
+        .context("Failed to connect to EVM endpoint")?
+        .provider;
+
+    let addresses = opts
+        .address
+        .iter()
+        .map(|a| {
+            a.parse::<Address>()
+                .with_context(|| format!("Invalid address '{}'", a))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let topics = opts
+        .topic
+        .iter()
+        .map(|t| parse_topic(t))
+        .collect::<Result<Vec<_>>>()?;
+    let abi = opts.abi.as_deref().map(crate::abi::load_abi).transpose()?;
+
+    let head_tag = if opts.finalized {
+        BlockNumberOrTag::Finalized
+    } else {
+        BlockNumberOrTag::Latest
+    };
+
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(EVM_POLL_INTERVAL_SECS));
+    let mut next: Option<u64> = None;
+    let mut seen = 0;
+    loop {
+        interval.tick().await;
+
+        let head = match provider.get_block_by_number(head_tag).await {
+            Ok(Some(block)) => block.header.number,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!("Failed to fetch head block: {}", e);
+                continue;
+            }
+        };
+
+        // Start at the current head; after a reorg to a lower head, wait for it
+        let from = next.unwrap_or(head);
+        for number in from..=head {
+            let block = match provider
+                .get_block_by_number(BlockNumberOrTag::Number(number))
+                .await
+            {
+                Ok(Some(block)) => block,
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::warn!("Failed to fetch block {}: {}", number, e);
+                    break;
+                }
+            };
+            let filter = alloy::rpc::types::Filter::new().at_block_hash(block.header.hash);
+            let logs = match provider.get_logs(&filter).await {
+                Ok(logs) => logs,
+                Err(e) => {
+                    tracing::warn!("Failed to fetch logs of block {}: {}", number, e);
+                    break;
+                }
+            };
+
+            let report = BlockReport {
+                chain: target.chain.clone(),
+                number,
+                hash: format!("{:?}", block.header.hash),
+                finalized: opts.finalized,
+                transaction_count: block.transactions.len() as u32,
+                event_count: logs.len() as u32,
+                events: logs
+                    .iter()
+                    .filter(|log| addresses.is_empty() || addresses.contains(&log.address()))
+                    .filter(|log| {
+                        topics.is_empty()
+                            || log
+                                .topics()
+                                .first()
+                                .is_some_and(|topic| topics.contains(topic))
+                    })
+                    .map(|log| EventReport::Evm(log_report(log, abi.as_ref())))
+                    .collect(),
+            };
+            report.print(opts.json)?;
+            next = Some(number + 1);
+
+            seen += 1;
+            if opts.count.is_some_and(|count| seen >= count) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn log_report(
+    log: &alloy::rpc::types::Log,
+    abi: Option<&apex_sdk_evm::abi::ContractAbi>,
+) -> LogReport {
+    let topics: Vec<[u8; 32]> = log.topics().iter().map(|t| t.0).collect();
+    let decoded = abi.and_then(|abi| abi.decode_log(&topics, log.data().data.as_ref()).ok());
+
+    LogReport {
+        log_index: log.log_index,
+        transaction_hash: log.transaction_hash.map(|h| format!("{:?}", h)),
+        address: format!("{:?}", log.address()),
+        topics: log.topics().iter().map(|t| format!("{:?}", t)).collect(),
+        data: format!("0x{}", hex::encode(log.data().data.as_ref())),
+        fields: decoded.as_ref().map(|d| d.to_json()),
+        event: decoded.map(|d| d.name),
+    }
+}
+
+/// Parse a log topic given as a 32-byte hash or an event signature
+fn parse_topic(topic: &str) -> Result<alloy::primitives::B256> {
+    if topic.contains('(') {
+        let signature: String = topic.chars().filter(|c| !c.is_whitespace()).collect();
+        return Ok(alloy::primitives::keccak256(signature.as_bytes()));
+    }
+
+    topic.parse().with_context(|| {
+        format!(
+            "Invalid topic '{}': expected a 32-byte hash or an event signature",
+            topic
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_filter() {
+        let all = EventFilter::default();
+        assert!(all.matches("Balances", "Transfer"));
+
+        let filter = EventFilter::new(&[], &["balances.Transfer".to_string()]);
+        assert!(filter.matches("Balances", "Transfer"));
+        assert!(!filter.matches("Assets", "Transfer"));
+
+        let filter = EventFilter::new(
+            &["System".to_string(), "XcmPallet".to_string()],
+            &["Sent".to_string(), "ExtrinsicFailed".to_string()],
+        );
+        assert!(filter.matches("XcmPallet", "Sent"));
+        assert!(filter.matches("System", "ExtrinsicFailed"));
+        assert!(!filter.matches("System", "ExtrinsicSuccess"));
+        assert!(!filter.matches("Balances", "Sent"));
+    }
+
+    #[test]
+    fn test_parse_topic() {
+        let transfer = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+        assert_eq!(
+            parse_topic("Transfer(address, address, uint256)").unwrap(),
+            transfer.parse::<alloy::primitives::B256>().unwrap()
+        );
+        assert_eq!(
+            parse_topic(transfer).unwrap(),
+            parse_topic("Transfer(address,address,uint256)").unwrap()
+        );
+        assert!(parse_topic("0x1234").is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! Metrics server endpoint tests
+//!
+//! Binds the embedded server on an ephemeral port and scrapes it over HTTP.
+
+use apex_sdk::metrics_server::{
+    ComponentHealth, EndpointStatus, HealthCheck, MetricsServer, MetricsServerConfig, Status,
+};
+use apex_sdk_core::MetricsCollector;
+use async_trait::async_trait;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+struct FakePool(Mutex<Status>);
+
+#[async_trait]
+impl HealthCheck for FakePool {
+    async fn check(&self) -> ComponentHealth {
+        let status = *self.0.lock().unwrap();
+        ComponentHealth::from_endpoints(
+            "fake",
+            vec![EndpointStatus {
+                endpoint: "http://localhost:8545".to_string(),
+                status,
+                blocks_behind: 0,
+                failure_count: 0,
+            }],
+        )
+    }
+}
+
+fn config() -> MetricsServerConfig {
+    MetricsServerConfig::new(SocketAddr::from(([127, 0, 0, 1], 0)))
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint_serves_prometheus_text() {
+    let collector = MetricsCollector::new();
+    collector.record_provider_response_time("ethereum", "eth_call", Duration::from_millis(40));
+
+    let evm_metrics = apex_sdk_evm::Metrics::new().with_telemetry(
+        apex_sdk_core::Telemetry::new("ethereum", "http://localhost:8545")
+            .with_collector(collector.clone()),
+    );
+    evm_metrics.record_rpc_call(Duration::from_millis(10));
+
+    let handle = MetricsServer::new(config())
+        .with_collector(collector)
+        .with_evm_metrics(evm_metrics)
+        .bind()
+        .await
+        .unwrap();
+
+    let response = reqwest::get(format!("http://{}/metrics", handle.local_addr()))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body = response.text().await.unwrap();
+
+    assert!(body.contains("_bucket{"));
+    assert!(body.contains("le=\"+Inf\""));
+    assert!(body.contains(
+        "apex_evm_rpc_calls_total{chain=\"ethereum\",endpoint=\"http://localhost:8545\"} 1"
+    ));
+
+    handle.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_health_and_readiness_follow_pool_status() {
+    let pool = Arc::new(FakePool(Mutex::new(Status::Unknown)));
+    let handle = MetricsServer::new(config())
+        .with_health_check(pool.clone())
+        .bind()
+        .await
+        .unwrap();
+    let base = format!("http://{}", handle.local_addr());
+
+    // Not checked yet: alive but not ready
+    assert_eq!(
+        reqwest::get(format!("{base}/healthz"))
+            .await
+            .unwrap()
+            .status(),
+        200
+    );
+    assert_eq!(
+        reqwest::get(format!("{base}/readyz"))
+            .await
+            .unwrap()
+            .status(),
+        503
+    );
+
+    *pool.0.lock().unwrap() = Status::Healthy;
+    let ready = reqwest::get(format!("{base}/readyz")).await.unwrap();
+    assert_eq!(ready.status(), 200);
+    let report: serde_json::Value = ready.json().await.unwrap();
+    assert_eq!(report["components"][0]["status"], "healthy");
+
+    *pool.0.lock().unwrap() = Status::Unhealthy;
+    assert_eq!(
+        reqwest::get(format!("{base}/healthz"))
+            .await
+            .unwrap()
+            .status(),
+        503
+    );
+
+    assert_eq!(
+        reqwest::get(format!("{base}/nope")).await.unwrap().status(),
+        404
+    );
+
+    handle.shutdown().await;
+}